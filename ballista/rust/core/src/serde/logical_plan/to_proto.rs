@@ -834,6 +834,8 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
                     JoinType::Left => protobuf::JoinType::Left,
                     JoinType::Right => protobuf::JoinType::Right,
                     JoinType::Full => protobuf::JoinType::Full,
+                    JoinType::Semi => protobuf::JoinType::Semi,
+                    JoinType::Anti => protobuf::JoinType::Anti,
                 };
                 let left_join_column = on.iter().map(|on| on.0.to_owned()).collect();
                 let right_join_column = on.iter().map(|on| on.1.to_owned()).collect();
@@ -967,6 +969,11 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
             LogicalPlan::Extension { .. } => unimplemented!(),
             LogicalPlan::Union { .. } => unimplemented!(),
             LogicalPlan::CrossJoin { .. } => unimplemented!(),
+            LogicalPlan::SetVariable { .. } => unimplemented!(),
+            LogicalPlan::Analyze { .. } => unimplemented!(),
+            LogicalPlan::Values { .. } => unimplemented!(),
+            LogicalPlan::Sample { .. } => unimplemented!(),
+            LogicalPlan::Deduplicate { .. } => unimplemented!(),
         }
     }
 }
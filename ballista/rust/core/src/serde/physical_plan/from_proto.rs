@@ -48,15 +48,16 @@ use datafusion::physical_plan::window_functions::{
 use datafusion::physical_plan::windows::create_window_expr;
 use datafusion::physical_plan::windows::WindowAggExec;
 use datafusion::physical_plan::{
-    coalesce_batches::CoalesceBatchesExec,
+    coalesce_batches::{BatchSizePolicy, CoalesceBatchesExec},
     csv::CsvExec,
     empty::EmptyExec,
     expressions::{Avg, Column, PhysicalSortExpr},
     filter::FilterExec,
     hash_join::HashJoinExec,
-    hash_utils::JoinType,
+    hash_utils::{HashSeeds, JoinType},
     limit::{GlobalLimitExec, LocalLimitExec},
     parquet::ParquetExec,
+    placeholder_row::PlaceholderRowExec,
     projection::ProjectionExec,
     repartition::RepartitionExec,
     sort::{SortExec, SortOptions},
@@ -137,10 +138,19 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
             PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
                 let input: Arc<dyn ExecutionPlan> =
                     convert_box_required!(coalesce_batches.input)?;
-                Ok(Arc::new(CoalesceBatchesExec::new(
-                    input,
-                    coalesce_batches.target_batch_size as usize,
-                )))
+                let policy = match coalesce_batches.target_bytes {
+                    0 => BatchSizePolicy::Rows,
+                    target_bytes => BatchSizePolicy::Adaptive {
+                        target_bytes: target_bytes as usize,
+                    },
+                };
+                Ok(Arc::new(
+                    CoalesceBatchesExec::new(
+                        input,
+                        coalesce_batches.target_batch_size as usize,
+                    )
+                    .with_policy(policy),
+                ))
             }
             PhysicalPlanType::Merge(merge) => {
                 let input: Arc<dyn ExecutionPlan> = convert_box_required!(merge.input)?;
@@ -148,7 +158,17 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
             }
             PhysicalPlanType::Repartition(repart) => {
                 let input: Arc<dyn ExecutionPlan> = convert_box_required!(repart.input)?;
-                match repart.partition_method {
+                let hash_seeds = match repart.hash_seeds.as_slice() {
+                    [] => HashSeeds::default(),
+                    [k0, k1, k2, k3] => HashSeeds([*k0, *k1, *k2, *k3]),
+                    seeds => {
+                        return Err(BallistaError::General(format!(
+                            "Expected 4 hash seeds, got {}",
+                            seeds.len()
+                        )))
+                    }
+                };
+                let partitioning = match repart.partition_method {
                     Some(PartitionMethod::Hash(ref hash_part)) => {
                         let expr = hash_part
                             .hash_expr
@@ -156,34 +176,29 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                             .map(|e| compile_expr(e, &input.schema()))
                             .collect::<Result<Vec<Arc<dyn PhysicalExpr>>, _>>()?;
 
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::Hash(
-                                expr,
-                                hash_part.partition_count.try_into().unwrap(),
-                            ),
-                        )?))
+                        Partitioning::Hash(
+                            expr,
+                            hash_part.partition_count.try_into().unwrap(),
+                        )
                     }
                     Some(PartitionMethod::RoundRobin(partition_count)) => {
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::RoundRobinBatch(
-                                partition_count.try_into().unwrap(),
-                            ),
-                        )?))
+                        Partitioning::RoundRobinBatch(partition_count.try_into().unwrap())
                     }
                     Some(PartitionMethod::Unknown(partition_count)) => {
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::UnknownPartitioning(
-                                partition_count.try_into().unwrap(),
-                            ),
-                        )?))
+                        Partitioning::UnknownPartitioning(
+                            partition_count.try_into().unwrap(),
+                        )
                     }
-                    _ => Err(BallistaError::General(
-                        "Invalid partitioning scheme".to_owned(),
-                    )),
-                }
+                    _ => {
+                        return Err(BallistaError::General(
+                            "Invalid partitioning scheme".to_owned(),
+                        ))
+                    }
+                };
+                Ok(Arc::new(
+                    RepartitionExec::try_new(input, partitioning)?
+                        .with_hash_seeds(hash_seeds),
+                ))
             }
             PhysicalPlanType::GlobalLimit(limit) => {
                 let input: Arc<dyn ExecutionPlan> = convert_box_required!(limit.input)?;
@@ -214,10 +229,13 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                 let ctx_state = ExecutionContextState {
                     catalog_list,
                     scalar_functions: Default::default(),
+                    table_functions: Default::default(),
                     var_provider: Default::default(),
                     aggregate_functions: Default::default(),
                     config: ExecutionConfig::new(),
                     execution_props: ExecutionProps::new(),
+                    runtime_env: Default::default(),
+                    plan_cache: Default::default(),
                 };
 
                 let window_agg_expr: Vec<(Expr, String)> = window_agg
@@ -302,10 +320,13 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                 let ctx_state = ExecutionContextState {
                     catalog_list,
                     scalar_functions: Default::default(),
+                    table_functions: Default::default(),
                     var_provider: Default::default(),
                     aggregate_functions: Default::default(),
                     config: ExecutionConfig::new(),
                     execution_props: ExecutionProps::new(),
+                    runtime_env: Default::default(),
+                    plan_cache: Default::default(),
                 };
 
                 let input_schema = hash_agg
@@ -379,6 +400,8 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                     protobuf::JoinType::Left => JoinType::Left,
                     protobuf::JoinType::Right => JoinType::Right,
                     protobuf::JoinType::Full => JoinType::Full,
+                    protobuf::JoinType::Semi => JoinType::Semi,
+                    protobuf::JoinType::Anti => JoinType::Anti,
                 };
                 Ok(Arc::new(HashJoinExec::try_new(
                     left,
@@ -401,7 +424,11 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
             }
             PhysicalPlanType::Empty(empty) => {
                 let schema = Arc::new(convert_required!(empty.schema)?);
-                Ok(Arc::new(EmptyExec::new(empty.produce_one_row, schema)))
+                if empty.produce_one_row {
+                    Ok(Arc::new(PlaceholderRowExec::new(schema)))
+                } else {
+                    Ok(Arc::new(EmptyExec::new(schema)))
+                }
             }
             PhysicalPlanType::Sort(sort) => {
                 let input: Arc<dyn ExecutionPlan> = convert_box_required!(sort.input)?;
@@ -469,10 +496,13 @@ fn compile_expr(
     let state = ExecutionContextState {
         catalog_list,
         scalar_functions: HashMap::new(),
+        table_functions: HashMap::new(),
         var_provider: HashMap::new(),
         aggregate_functions: HashMap::new(),
         config: ExecutionConfig::new(),
         execution_props: ExecutionProps::new(),
+        runtime_env: Default::default(),
+        plan_cache: Default::default(),
     };
     let expr: Expr = expr.try_into()?;
     df_planner
@@ -37,6 +37,7 @@ mod roundtrip_tests {
             hash_join::{HashJoinExec, PartitionMode},
             hash_utils::JoinType,
             limit::{GlobalLimitExec, LocalLimitExec},
+            placeholder_row::PlaceholderRowExec,
             sort::SortExec,
             AggregateExpr, ColumnarValue, Distribution, ExecutionPlan, Partitioning,
             PhysicalExpr,
@@ -59,13 +60,18 @@ mod roundtrip_tests {
 
     #[test]
     fn roundtrip_empty() -> Result<()> {
-        roundtrip_test(Arc::new(EmptyExec::new(false, Arc::new(Schema::empty()))))
+        roundtrip_test(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))))
+    }
+
+    #[test]
+    fn roundtrip_placeholder_row() -> Result<()> {
+        roundtrip_test(Arc::new(PlaceholderRowExec::new(Arc::new(Schema::empty()))))
     }
 
     #[test]
     fn roundtrip_local_limit() -> Result<()> {
         roundtrip_test(Arc::new(LocalLimitExec::new(
-            Arc::new(EmptyExec::new(false, Arc::new(Schema::empty()))),
+            Arc::new(EmptyExec::new(Arc::new(Schema::empty()))),
             25,
         )))
     }
@@ -73,7 +79,7 @@ mod roundtrip_tests {
     #[test]
     fn roundtrip_global_limit() -> Result<()> {
         roundtrip_test(Arc::new(GlobalLimitExec::new(
-            Arc::new(EmptyExec::new(false, Arc::new(Schema::empty()))),
+            Arc::new(EmptyExec::new(Arc::new(Schema::empty()))),
             25,
         )))
     }
@@ -85,8 +91,8 @@ mod roundtrip_tests {
         let schema_right = Schema::new(vec![field_a]);
 
         roundtrip_test(Arc::new(HashJoinExec::try_new(
-            Arc::new(EmptyExec::new(false, Arc::new(schema_left))),
-            Arc::new(EmptyExec::new(false, Arc::new(schema_right))),
+            Arc::new(EmptyExec::new(Arc::new(schema_left))),
+            Arc::new(EmptyExec::new(Arc::new(schema_right))),
             &[("col".to_string(), "col".to_string())],
             &JoinType::Inner,
             PartitionMode::CollectLeft,
@@ -116,7 +122,7 @@ mod roundtrip_tests {
             AggregateMode::Final,
             groups.clone(),
             aggregates.clone(),
-            Arc::new(EmptyExec::new(false, schema.clone())),
+            Arc::new(EmptyExec::new(schema.clone())),
             schema,
         )?))
     }
@@ -139,7 +145,7 @@ mod roundtrip_tests {
         let and = binary(not, Operator::And, in_list, &schema)?;
         roundtrip_test(Arc::new(FilterExec::try_new(
             and,
-            Arc::new(EmptyExec::new(false, schema.clone())),
+            Arc::new(EmptyExec::new(schema.clone())),
         )?))
     }
 
@@ -166,7 +172,7 @@ mod roundtrip_tests {
         ];
         roundtrip_test(Arc::new(SortExec::try_new(
             sort_exprs,
-            Arc::new(EmptyExec::new(false, schema)),
+            Arc::new(EmptyExec::new(schema)),
         )?))
     }
 }
@@ -26,7 +26,7 @@ use std::{
     sync::Arc,
 };
 
-use datafusion::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use datafusion::physical_plan::coalesce_batches::{BatchSizePolicy, CoalesceBatchesExec};
 use datafusion::physical_plan::csv::CsvExec;
 use datafusion::physical_plan::expressions::{
     CaseExpr, InListExpr, IsNotNullExpr, IsNullExpr, NegativeExpr, NotExpr,
@@ -48,6 +48,7 @@ use datafusion::{
 use datafusion::physical_plan::{
     empty::EmptyExec,
     expressions::{Avg, BinaryExpr, Column, Sum},
+    placeholder_row::PlaceholderRowExec,
     Partitioning,
 };
 use datafusion::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr};
@@ -59,6 +60,7 @@ use crate::execution_plans::{ShuffleReaderExec, UnresolvedShuffleExec};
 use crate::serde::protobuf::repartition_exec_node::PartitionMethod;
 use crate::serde::{protobuf, BallistaError};
 use datafusion::physical_plan::functions::{BuiltinScalarFunction, ScalarFunctionExpr};
+use datafusion::physical_plan::hash_utils::HashSeeds;
 use datafusion::physical_plan::merge::MergeExec;
 use datafusion::physical_plan::repartition::RepartitionExec;
 
@@ -133,6 +135,8 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                 JoinType::Left => protobuf::JoinType::Left,
                 JoinType::Right => protobuf::JoinType::Right,
                 JoinType::Full => protobuf::JoinType::Full,
+                JoinType::Semi => protobuf::JoinType::Semi,
+                JoinType::Anti => protobuf::JoinType::Anti,
             };
             Ok(protobuf::PhysicalPlanNode {
                 physical_plan_type: Some(PhysicalPlanType::HashJoin(Box::new(
@@ -196,7 +200,17 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
             Ok(protobuf::PhysicalPlanNode {
                 physical_plan_type: Some(PhysicalPlanType::Empty(
                     protobuf::EmptyExecNode {
-                        produce_one_row: empty.produce_one_row(),
+                        produce_one_row: false,
+                        schema: Some(schema),
+                    },
+                )),
+            })
+        } else if let Some(placeholder) = plan.downcast_ref::<PlaceholderRowExec>() {
+            let schema = placeholder.schema().as_ref().into();
+            Ok(protobuf::PhysicalPlanNode {
+                physical_plan_type: Some(PhysicalPlanType::Empty(
+                    protobuf::EmptyExecNode {
+                        produce_one_row: true,
                         schema: Some(schema),
                     },
                 )),
@@ -210,6 +224,12 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                     protobuf::CoalesceBatchesExecNode {
                         input: Some(Box::new(input)),
                         target_batch_size: coalesce_batches.target_batch_size() as u32,
+                        target_bytes: match coalesce_batches.policy() {
+                            BatchSizePolicy::Rows => 0,
+                            BatchSizePolicy::Adaptive { target_bytes } => {
+                                target_bytes as u64
+                            }
+                        },
                     },
                 ))),
             })
@@ -315,6 +335,11 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                     protobuf::RepartitionExecNode {
                         input: Some(Box::new(input)),
                         partition_method: Some(pb_partition_method),
+                        hash_seeds: if exec.hash_seeds() == HashSeeds::default() {
+                            vec![]
+                        } else {
+                            exec.hash_seeds().0.to_vec()
+                        },
                     },
                 ))),
             })
@@ -0,0 +1,1041 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Generator of TPC-H data, following the value distributions of the TPC-H
+//! specification closely enough for the queries to produce meaningful
+//! results. The data is deterministic for a given scale factor and seed,
+//! but does not match the data generated by `dbgen`.
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    ArrayRef, Date32Array, Float64Array, Int32Array, StringArray,
+};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::MemTable;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::parquet::file::properties::WriterProperties;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::prelude::*;
+
+use super::{get_schema, TABLES};
+
+/// 1992-01-01, the first order date, in days since the UNIX epoch
+const START_DATE: i32 = 8035;
+/// 1998-12-31, the last possible receipt date
+const END_DATE: i32 = 10591;
+/// 1995-06-17, the date line items are shipped or returned relative to
+const CURRENT_DATE: i32 = 9298;
+
+const REGIONS: &[&str] = &["AFRICA", "AMERICA", "ASIA", "EUROPE", "MIDDLE EAST"];
+
+/// Nations with the keys of their regions
+const NATIONS: &[(&str, i32)] = &[
+    ("ALGERIA", 0),
+    ("ARGENTINA", 1),
+    ("BRAZIL", 1),
+    ("CANADA", 1),
+    ("EGYPT", 4),
+    ("ETHIOPIA", 0),
+    ("FRANCE", 3),
+    ("GERMANY", 3),
+    ("INDIA", 2),
+    ("INDONESIA", 2),
+    ("IRAN", 4),
+    ("IRAQ", 4),
+    ("JAPAN", 2),
+    ("JORDAN", 4),
+    ("KENYA", 0),
+    ("MOROCCO", 0),
+    ("MOZAMBIQUE", 0),
+    ("PERU", 1),
+    ("CHINA", 2),
+    ("ROMANIA", 3),
+    ("SAUDI ARABIA", 4),
+    ("VIETNAM", 2),
+    ("RUSSIA", 3),
+    ("UNITED KINGDOM", 3),
+    ("UNITED STATES", 1),
+];
+
+/// Words part names are made of
+const COLORS: &[&str] = &[
+    "almond",
+    "antique",
+    "aquamarine",
+    "azure",
+    "beige",
+    "bisque",
+    "black",
+    "blanched",
+    "blue",
+    "blush",
+    "brown",
+    "burlywood",
+    "burnished",
+    "chartreuse",
+    "chiffon",
+    "chocolate",
+    "coral",
+    "cornflower",
+    "cornsilk",
+    "cream",
+    "cyan",
+    "dark",
+    "deep",
+    "dim",
+    "dodger",
+    "drab",
+    "firebrick",
+    "floral",
+    "forest",
+    "frosted",
+    "gainsboro",
+    "ghost",
+    "goldenrod",
+    "green",
+    "grey",
+    "honeydew",
+    "hot",
+    "indian",
+    "ivory",
+    "khaki",
+    "lace",
+    "lavender",
+    "lawn",
+    "lemon",
+    "light",
+    "lime",
+    "linen",
+    "magenta",
+    "maroon",
+    "medium",
+    "metallic",
+    "midnight",
+    "mint",
+    "misty",
+    "moccasin",
+    "navajo",
+    "navy",
+    "olive",
+    "orange",
+    "orchid",
+    "pale",
+    "papaya",
+    "peach",
+    "peru",
+    "pink",
+    "plum",
+    "powder",
+    "puff",
+    "purple",
+    "red",
+    "rose",
+    "rosy",
+    "royal",
+    "saddle",
+    "salmon",
+    "sandy",
+    "seashell",
+    "sienna",
+    "sky",
+    "slate",
+    "smoke",
+    "snow",
+    "spring",
+    "steel",
+    "tan",
+    "thistle",
+    "tomato",
+    "turquoise",
+    "violet",
+    "wheat",
+    "white",
+    "yellow",
+];
+
+const TYPE_SIZES: &[&str] = &["STANDARD", "SMALL", "MEDIUM", "LARGE", "ECONOMY", "PROMO"];
+const TYPE_FINISHES: &[&str] =
+    &["ANODIZED", "BURNISHED", "PLATED", "POLISHED", "BRUSHED"];
+const TYPE_MATERIALS: &[&str] = &["TIN", "NICKEL", "BRASS", "STEEL", "COPPER"];
+const CONTAINER_SIZES: &[&str] = &["SM", "LG", "MED", "JUMBO", "WRAP"];
+const CONTAINER_TYPES: &[&str] =
+    &["CASE", "BOX", "BAG", "JAR", "PKG", "PACK", "CAN", "DRUM"];
+const SEGMENTS: &[&str] = &[
+    "AUTOMOBILE",
+    "BUILDING",
+    "FURNITURE",
+    "MACHINERY",
+    "HOUSEHOLD",
+];
+const PRIORITIES: &[&str] =
+    &["1-URGENT", "2-HIGH", "3-MEDIUM", "4-NOT SPECIFIED", "5-LOW"];
+const INSTRUCTIONS: &[&str] = &[
+    "DELIVER IN PERSON",
+    "COLLECT COD",
+    "NONE",
+    "TAKE BACK RETURN",
+];
+const MODES: &[&str] = &["REG AIR", "AIR", "RAIL", "SHIP", "TRUCK", "MAIL", "FOB"];
+
+/// Words comments are made of
+const WORDS: &[&str] = &[
+    "furiously",
+    "quickly",
+    "carefully",
+    "blithely",
+    "slyly",
+    "ironic",
+    "final",
+    "regular",
+    "express",
+    "special",
+    "pending",
+    "bold",
+    "unusual",
+    "even",
+    "silent",
+    "packages",
+    "requests",
+    "accounts",
+    "deposits",
+    "instructions",
+    "theodolites",
+    "foxes",
+    "pinto",
+    "beans",
+    "ideas",
+    "dependencies",
+    "excuses",
+    "platelets",
+    "asymptotes",
+    "courts",
+    "dolphins",
+    "sleep",
+    "wake",
+    "are",
+    "haggle",
+    "nag",
+    "use",
+    "boost",
+    "affix",
+    "detect",
+    "integrate",
+    "cajole",
+    "doze",
+    "about",
+    "above",
+    "across",
+    "after",
+    "against",
+    "along",
+];
+
+/// Generates the TPC-H tables for a scale factor, where a scale factor of 1
+/// corresponds to about 1GB of `dbgen` output. Fractional scale factors,
+/// such as 0.01, generate proportionally smaller tables.
+///
+/// Order keys are 32 bit integers, so scale factors above 300 are not
+/// supported.
+///
+/// ```
+/// use arrow_benchmarks::tpch::TpchGenerator;
+///
+/// let generator = TpchGenerator::new(0.001).with_partitions(2);
+/// let partitions = generator.generate("nation").unwrap();
+/// let rows: usize = partitions.iter().flatten().map(|b| b.num_rows()).sum();
+/// assert_eq!(rows, 25);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TpchGenerator {
+    scale_factor: f64,
+    partitions: usize,
+    batch_size: usize,
+    seed: u64,
+}
+
+impl TpchGenerator {
+    /// Create a generator for `scale_factor`, generating a single partition
+    /// per table
+    pub fn new(scale_factor: f64) -> Self {
+        Self {
+            scale_factor,
+            partitions: 1,
+            batch_size: 8192,
+            seed: 0,
+        }
+    }
+
+    /// Split every table into `n` partitions
+    pub fn with_partitions(mut self, n: usize) -> Self {
+        self.partitions = n.max(1);
+        self
+    }
+
+    /// Set the maximum number of rows of the generated batches. The
+    /// batches of `lineitem` may hold up to 7 times as many rows, as its
+    /// rows are generated per order.
+    pub fn with_batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    /// Generate different data for the same scale factor
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generate the partitions of `table`
+    pub fn generate(&self, table: &str) -> Result<Vec<Vec<RecordBatch>>> {
+        let rows = self.row_count(table)?;
+        let schema = Arc::new(get_schema(table));
+        let partition_size = (rows + self.partitions - 1) / self.partitions;
+
+        (0..self.partitions)
+            .map(|partition| {
+                let start = (partition * partition_size).min(rows);
+                let end = (start + partition_size).min(rows);
+                (start..end)
+                    .step_by(self.batch_size)
+                    .map(|batch_start| {
+                        let batch_end = (batch_start + self.batch_size).min(end);
+                        self.generate_batch(table, &schema, batch_start..batch_end)
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect()
+    }
+
+    /// Generate `table` as a [`MemTable`]
+    pub fn mem_table(&self, table: &str) -> Result<MemTable> {
+        let schema = Arc::new(get_schema(table));
+        MemTable::try_new(schema, self.generate(table)?)
+    }
+
+    /// Register all TPC-H tables with `ctx`, as [`MemTable`]s
+    pub fn register_tables(&self, ctx: &mut ExecutionContext) -> Result<()> {
+        for table in TABLES {
+            ctx.register_table(*table, Arc::new(self.mem_table(table)?))?;
+        }
+        Ok(())
+    }
+
+    /// Write all TPC-H tables as Parquet files to `path`, one directory per
+    /// table with one file per partition, as read by
+    /// [`ExecutionContext::register_parquet`]
+    pub async fn write_parquet(
+        &self,
+        ctx: &ExecutionContext,
+        path: impl AsRef<Path>,
+        writer_properties: Option<WriterProperties>,
+    ) -> Result<()> {
+        std::fs::create_dir_all(path.as_ref())?;
+        for table in TABLES {
+            let schema = Arc::new(get_schema(table));
+            let plan = MemoryExec::try_new(&self.generate(table)?, schema, None)?;
+            let table_path = path.as_ref().join(table);
+            let table_path = table_path.to_str().ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "Invalid path {}",
+                    table_path.display()
+                ))
+            })?;
+            ctx.write_parquet(
+                Arc::new(plan),
+                table_path.to_owned(),
+                writer_properties.clone(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Number of rows of `table`, or of orders for `lineitem`
+    fn row_count(&self, table: &str) -> Result<usize> {
+        Ok(match table {
+            "part" | "partsupp" => self.scaled(200_000),
+            "supplier" => self.scaled(10_000),
+            "customer" => self.scaled(150_000),
+            "orders" | "lineitem" => self.scaled(1_500_000),
+            "nation" => NATIONS.len(),
+            "region" => REGIONS.len(),
+            other => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unknown TPC-H table '{}'",
+                    other
+                )))
+            }
+        })
+    }
+
+    fn scaled(&self, rows: usize) -> usize {
+        ((rows as f64 * self.scale_factor).round() as usize).max(1)
+    }
+
+    /// Generates the batch of `table` for `rows`, which are the rows of
+    /// `part` for `partsupp` and of `orders` for `lineitem`
+    fn generate_batch(
+        &self,
+        table: &str,
+        schema: &SchemaRef,
+        rows: Range<usize>,
+    ) -> Result<RecordBatch> {
+        let columns = match table {
+            "part" => self.part(rows),
+            "supplier" => self.supplier(rows),
+            "partsupp" => self.partsupp(rows),
+            "customer" => self.customer(rows),
+            "orders" => self.orders(rows),
+            "lineitem" => self.lineitem(rows),
+            "nation" => self.nation(rows),
+            _ => self.region(rows),
+        };
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+
+    /// Random number generator for `row` of `table`, so that every row can
+    /// be generated independently of the others
+    fn rng(&self, table: u64, row: usize) -> Rng {
+        Rng::new(self.seed ^ (table << 48) ^ row as u64)
+    }
+
+    fn part(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut columns = PartColumns::default();
+        for row in rows {
+            let mut rng = self.rng(1, row);
+            let key = row as i32 + 1;
+            let mut name_words = Vec::with_capacity(5);
+            while name_words.len() < 5 {
+                let word = rng.pick(COLORS);
+                if !name_words.contains(&word) {
+                    name_words.push(word);
+                }
+            }
+            let manufacturer = rng.range(1, 5);
+            columns.partkey.push(key);
+            columns.name.push(name_words.join(" "));
+            columns.mfgr.push(format!("Manufacturer#{}", manufacturer));
+            columns
+                .brand
+                .push(format!("Brand#{}{}", manufacturer, rng.range(1, 5)));
+            columns.part_type.push(format!(
+                "{} {} {}",
+                rng.pick(TYPE_SIZES),
+                rng.pick(TYPE_FINISHES),
+                rng.pick(TYPE_MATERIALS)
+            ));
+            columns.size.push(rng.range(1, 50) as i32);
+            columns.container.push(format!(
+                "{} {}",
+                rng.pick(CONTAINER_SIZES),
+                rng.pick(CONTAINER_TYPES)
+            ));
+            columns.retailprice.push(retail_price(key));
+            columns.comment.push(rng.text(5, 22));
+        }
+        vec![
+            Arc::new(Int32Array::from(columns.partkey)),
+            strings(columns.name),
+            strings(columns.mfgr),
+            strings(columns.brand),
+            strings(columns.part_type),
+            Arc::new(Int32Array::from(columns.size)),
+            strings(columns.container),
+            Arc::new(Float64Array::from(columns.retailprice)),
+            strings(columns.comment),
+        ]
+    }
+
+    fn supplier(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut suppkey = vec![];
+        let mut name = vec![];
+        let mut address = vec![];
+        let mut nationkey = vec![];
+        let mut phone = vec![];
+        let mut acctbal = vec![];
+        let mut comment = vec![];
+        for row in rows {
+            let mut rng = self.rng(2, row);
+            let key = row as i32 + 1;
+            let nation = rng.range(0, 24) as i32;
+            suppkey.push(key);
+            name.push(format!("Supplier#{:09}", key));
+            address.push(rng.address());
+            nationkey.push(nation);
+            phone.push(rng.phone(nation));
+            acctbal.push(rng.price(-99_999, 999_999));
+            // some suppliers have complaints, see query 16
+            comment.push(if rng.range(0, 1999) == 0 {
+                format!(
+                    "{} Customer {} Complaints",
+                    rng.text(5, 20),
+                    rng.text(5, 20)
+                )
+            } else {
+                rng.text(25, 100)
+            });
+        }
+        vec![
+            Arc::new(Int32Array::from(suppkey)),
+            strings(name),
+            strings(address),
+            Arc::new(Int32Array::from(nationkey)),
+            strings(phone),
+            Arc::new(Float64Array::from(acctbal)),
+            strings(comment),
+        ]
+    }
+
+    fn partsupp(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let suppliers = self.scaled(10_000);
+        let mut partkey = vec![];
+        let mut suppkey = vec![];
+        let mut availqty = vec![];
+        let mut supplycost = vec![];
+        let mut comment = vec![];
+        for row in rows {
+            let mut rng = self.rng(3, row);
+            let key = row as i32 + 1;
+            for i in 0..4 {
+                partkey.push(key);
+                suppkey.push(part_supplier(key, i, suppliers));
+                availqty.push(rng.range(1, 9999) as i32);
+                supplycost.push(rng.price(100, 100_000));
+                comment.push(rng.text(49, 198));
+            }
+        }
+        vec![
+            Arc::new(Int32Array::from(partkey)),
+            Arc::new(Int32Array::from(suppkey)),
+            Arc::new(Int32Array::from(availqty)),
+            Arc::new(Float64Array::from(supplycost)),
+            strings(comment),
+        ]
+    }
+
+    fn customer(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut custkey = vec![];
+        let mut name = vec![];
+        let mut address = vec![];
+        let mut nationkey = vec![];
+        let mut phone = vec![];
+        let mut acctbal = vec![];
+        let mut mktsegment = vec![];
+        let mut comment = vec![];
+        for row in rows {
+            let mut rng = self.rng(4, row);
+            let key = row as i32 + 1;
+            let nation = rng.range(0, 24) as i32;
+            custkey.push(key);
+            name.push(format!("Customer#{:09}", key));
+            address.push(rng.address());
+            nationkey.push(nation);
+            phone.push(rng.phone(nation));
+            acctbal.push(rng.price(-99_999, 999_999));
+            mktsegment.push(rng.pick(SEGMENTS));
+            comment.push(rng.text(29, 116));
+        }
+        vec![
+            Arc::new(Int32Array::from(custkey)),
+            strings(name),
+            strings(address),
+            Arc::new(Int32Array::from(nationkey)),
+            strings(phone),
+            Arc::new(Float64Array::from(acctbal)),
+            Arc::new(StringArray::from(mktsegment)),
+            strings(comment),
+        ]
+    }
+
+    fn orders(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut orderkey = vec![];
+        let mut custkey = vec![];
+        let mut orderstatus = vec![];
+        let mut totalprice = vec![];
+        let mut orderdate = vec![];
+        let mut orderpriority = vec![];
+        let mut clerk = vec![];
+        let mut shippriority = vec![];
+        let mut comment = vec![];
+        for row in rows {
+            let (order, lineitems) = self.order(row);
+            let shipped = lineitems.iter().filter(|l| l.linestatus == "F").count();
+            orderkey.push(order.orderkey);
+            custkey.push(order.custkey);
+            orderstatus.push(if shipped == lineitems.len() {
+                "F"
+            } else if shipped == 0 {
+                "O"
+            } else {
+                "P"
+            });
+            totalprice.push(
+                lineitems
+                    .iter()
+                    .map(|l| l.extendedprice * (1.0 + l.tax) * (1.0 - l.discount))
+                    .sum::<f64>(),
+            );
+            orderdate.push(order.orderdate);
+            orderpriority.push(order.orderpriority);
+            clerk.push(order.clerk);
+            shippriority.push(0);
+            comment.push(order.comment);
+        }
+        vec![
+            Arc::new(Int32Array::from(orderkey)),
+            Arc::new(Int32Array::from(custkey)),
+            Arc::new(StringArray::from(orderstatus)),
+            Arc::new(Float64Array::from(totalprice)),
+            Arc::new(Date32Array::from(orderdate)),
+            Arc::new(StringArray::from(orderpriority)),
+            strings(clerk),
+            Arc::new(Int32Array::from(shippriority)),
+            strings(comment),
+        ]
+    }
+
+    fn lineitem(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut columns = LineitemColumns::default();
+        for row in rows {
+            let (order, lineitems) = self.order(row);
+            for (number, lineitem) in lineitems.into_iter().enumerate() {
+                columns.orderkey.push(order.orderkey);
+                columns.partkey.push(lineitem.partkey);
+                columns.suppkey.push(lineitem.suppkey);
+                columns.linenumber.push(number as i32 + 1);
+                columns.quantity.push(lineitem.quantity);
+                columns.extendedprice.push(lineitem.extendedprice);
+                columns.discount.push(lineitem.discount);
+                columns.tax.push(lineitem.tax);
+                columns.returnflag.push(lineitem.returnflag);
+                columns.linestatus.push(lineitem.linestatus);
+                columns.shipdate.push(lineitem.shipdate);
+                columns.commitdate.push(lineitem.commitdate);
+                columns.receiptdate.push(lineitem.receiptdate);
+                columns.shipinstruct.push(lineitem.shipinstruct);
+                columns.shipmode.push(lineitem.shipmode);
+                columns.comment.push(lineitem.comment);
+            }
+        }
+        vec![
+            Arc::new(Int32Array::from(columns.orderkey)),
+            Arc::new(Int32Array::from(columns.partkey)),
+            Arc::new(Int32Array::from(columns.suppkey)),
+            Arc::new(Int32Array::from(columns.linenumber)),
+            Arc::new(Float64Array::from(columns.quantity)),
+            Arc::new(Float64Array::from(columns.extendedprice)),
+            Arc::new(Float64Array::from(columns.discount)),
+            Arc::new(Float64Array::from(columns.tax)),
+            Arc::new(StringArray::from(columns.returnflag)),
+            Arc::new(StringArray::from(columns.linestatus)),
+            Arc::new(Date32Array::from(columns.shipdate)),
+            Arc::new(Date32Array::from(columns.commitdate)),
+            Arc::new(Date32Array::from(columns.receiptdate)),
+            Arc::new(StringArray::from(columns.shipinstruct)),
+            Arc::new(StringArray::from(columns.shipmode)),
+            strings(columns.comment),
+        ]
+    }
+
+    fn nation(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut rng = self.rng(7, 0);
+        let nations = &NATIONS[rows.clone()];
+        vec![
+            Arc::new(Int32Array::from(
+                rows.map(|row| row as i32).collect::<Vec<_>>(),
+            )),
+            Arc::new(StringArray::from(
+                nations.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            )),
+            Arc::new(Int32Array::from(
+                nations
+                    .iter()
+                    .map(|(_, region)| *region)
+                    .collect::<Vec<_>>(),
+            )),
+            strings(nations.iter().map(|_| rng.text(31, 114)).collect()),
+        ]
+    }
+
+    fn region(&self, rows: Range<usize>) -> Vec<ArrayRef> {
+        let mut rng = self.rng(8, 0);
+        let regions = &REGIONS[rows.clone()];
+        vec![
+            Arc::new(Int32Array::from(
+                rows.map(|row| row as i32).collect::<Vec<_>>(),
+            )),
+            Arc::new(StringArray::from(regions.to_vec())),
+            strings(regions.iter().map(|_| rng.text(31, 115)).collect()),
+        ]
+    }
+
+    /// Generates the order with index `row` and its line items
+    fn order(&self, row: usize) -> (Order, Vec<Lineitem>) {
+        let mut rng = self.rng(5, row);
+        let customers = self.scaled(150_000) as i64;
+        let parts = self.scaled(200_000) as i64;
+        let suppliers = self.scaled(10_000);
+        let clerks = self.scaled(1_000) as i64;
+
+        // only two thirds of the customers have orders
+        let mut custkey = rng.range(1, customers);
+        while custkey % 3 == 0 && customers >= 3 {
+            custkey = rng.range(1, customers);
+        }
+        let orderdate = rng.range(START_DATE as i64, END_DATE as i64 - 151) as i32;
+        let order = Order {
+            // only the first 8 of every 32 keys are used
+            orderkey: (row / 8 * 32 + row % 8 + 1) as i32,
+            custkey: custkey as i32,
+            orderdate,
+            orderpriority: rng.pick(PRIORITIES),
+            clerk: format!("Clerk#{:09}", rng.range(1, clerks)),
+            comment: rng.text(19, 78),
+        };
+
+        let lineitems = (0..rng.range(1, 7))
+            .map(|_| {
+                let partkey = rng.range(1, parts) as i32;
+                let quantity = rng.range(1, 50) as f64;
+                let shipdate = orderdate + rng.range(1, 121) as i32;
+                let receiptdate = shipdate + rng.range(1, 30) as i32;
+                Lineitem {
+                    partkey,
+                    suppkey: part_supplier(partkey, rng.range(0, 3) as i32, suppliers),
+                    quantity,
+                    extendedprice: quantity * retail_price(partkey),
+                    discount: rng.price(0, 10),
+                    tax: rng.price(0, 8),
+                    returnflag: if receiptdate <= CURRENT_DATE {
+                        if rng.range(0, 1) == 0 {
+                            "R"
+                        } else {
+                            "A"
+                        }
+                    } else {
+                        "N"
+                    },
+                    linestatus: if shipdate > CURRENT_DATE { "O" } else { "F" },
+                    shipdate,
+                    commitdate: orderdate + rng.range(30, 90) as i32,
+                    receiptdate,
+                    shipinstruct: rng.pick(INSTRUCTIONS),
+                    shipmode: rng.pick(MODES),
+                    comment: rng.text(10, 43),
+                }
+            })
+            .collect();
+
+        (order, lineitems)
+    }
+}
+
+#[derive(Default)]
+struct PartColumns {
+    partkey: Vec<i32>,
+    name: Vec<String>,
+    mfgr: Vec<String>,
+    brand: Vec<String>,
+    part_type: Vec<String>,
+    size: Vec<i32>,
+    container: Vec<String>,
+    retailprice: Vec<f64>,
+    comment: Vec<String>,
+}
+
+#[derive(Default)]
+struct LineitemColumns {
+    orderkey: Vec<i32>,
+    partkey: Vec<i32>,
+    suppkey: Vec<i32>,
+    linenumber: Vec<i32>,
+    quantity: Vec<f64>,
+    extendedprice: Vec<f64>,
+    discount: Vec<f64>,
+    tax: Vec<f64>,
+    returnflag: Vec<&'static str>,
+    linestatus: Vec<&'static str>,
+    shipdate: Vec<i32>,
+    commitdate: Vec<i32>,
+    receiptdate: Vec<i32>,
+    shipinstruct: Vec<&'static str>,
+    shipmode: Vec<&'static str>,
+    comment: Vec<String>,
+}
+
+struct Order {
+    orderkey: i32,
+    custkey: i32,
+    orderdate: i32,
+    orderpriority: &'static str,
+    clerk: String,
+    comment: String,
+}
+
+struct Lineitem {
+    partkey: i32,
+    suppkey: i32,
+    quantity: f64,
+    extendedprice: f64,
+    discount: f64,
+    tax: f64,
+    returnflag: &'static str,
+    linestatus: &'static str,
+    shipdate: i32,
+    commitdate: i32,
+    receiptdate: i32,
+    shipinstruct: &'static str,
+    shipmode: &'static str,
+    comment: String,
+}
+
+/// The retail price of the part `partkey`, as defined by the specification
+fn retail_price(partkey: i32) -> f64 {
+    let key = partkey as i64;
+    (90_000 + (key / 10) % 20_001 + 100 * (key % 1_000)) as f64 / 100.0
+}
+
+/// The key of the `i`-th of the 4 suppliers of the part `partkey`, as
+/// defined by the specification
+fn part_supplier(partkey: i32, i: i32, suppliers: usize) -> i32 {
+    let (partkey, i, suppliers) = (partkey as i64, i as i64, suppliers as i64);
+    ((partkey + i * (suppliers / 4 + (partkey - 1) / suppliers)) % suppliers + 1) as i32
+}
+
+fn strings(values: Vec<String>) -> ArrayRef {
+    Arc::new(StringArray::from(
+        values.iter().map(String::as_str).collect::<Vec<_>>(),
+    ))
+}
+
+/// SplitMix64, a small and fast pseudo random number generator whose
+/// output does not depend on the versions of any dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        let mut rng = Self(seed);
+        rng.next_u64();
+        rng
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed integer between `low` and `high`, inclusive
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low + 1) as u64) as i64
+    }
+
+    /// Uniformly distributed price between `low` and `high` cents
+    fn price(&mut self, low: i64, high: i64) -> f64 {
+        self.range(low, high) as f64 / 100.0
+    }
+
+    fn pick(&mut self, values: &[&'static str]) -> &'static str {
+        values[self.range(0, values.len() as i64 - 1) as usize]
+    }
+
+    /// Random words with a total length between `min` and `max`
+    fn text(&mut self, min: usize, max: usize) -> String {
+        let len = self.range(min as i64, max as i64) as usize;
+        let mut text = String::with_capacity(len + 16);
+        while text.len() < len {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(self.pick(WORDS));
+        }
+        text.truncate(len);
+        text.trim_end().to_owned()
+    }
+
+    fn address(&mut self) -> String {
+        const CHARS: &[u8] =
+            b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789,. ";
+        (0..self.range(10, 40))
+            .map(|_| CHARS[self.range(0, CHARS.len() as i64 - 1) as usize] as char)
+            .collect()
+    }
+
+    /// Phone number starting with the country code of `nation`
+    fn phone(&mut self, nation: i32) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            nation + 10,
+            self.range(100, 999),
+            self.range(100, 999),
+            self.range(1000, 9999)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::tpch::get_query_sql;
+    use datafusion::arrow::array::Array;
+
+    fn num_rows(partitions: &[Vec<RecordBatch>]) -> usize {
+        partitions.iter().flatten().map(|b| b.num_rows()).sum()
+    }
+
+    fn int32_column(partitions: &[Vec<RecordBatch>], column: usize) -> Vec<i32> {
+        partitions
+            .iter()
+            .flatten()
+            .flat_map(|batch| {
+                let array = batch
+                    .column(column)
+                    .as_any()
+                    .downcast_ref::<Int32Array>()
+                    .unwrap();
+                (0..array.len()).map(|i| array.value(i)).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn row_counts() -> Result<()> {
+        let generator = TpchGenerator::new(0.01)
+            .with_partitions(3)
+            .with_batch_size(100);
+        for (table, rows) in &[
+            ("part", 2000),
+            ("supplier", 100),
+            ("partsupp", 8000),
+            ("customer", 1500),
+            ("orders", 15000),
+            ("nation", 25),
+            ("region", 5),
+        ] {
+            let partitions = generator.generate(table)?;
+            assert_eq!(partitions.len(), 3);
+            assert_eq!(num_rows(&partitions), *rows, "{}", table);
+        }
+
+        // 1 to 7 line items per order
+        let lineitems = num_rows(&generator.generate("lineitem")?);
+        assert!(
+            lineitems > 15000 * 3 && lineitems < 15000 * 5,
+            "{}",
+            lineitems
+        );
+
+        assert!(generator.generate("foo").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn deterministic() -> Result<()> {
+        let generator = TpchGenerator::new(0.001);
+        let custkeys = int32_column(&generator.generate("orders")?, 1);
+
+        let repartitioned = generator.clone().with_partitions(4).with_batch_size(7);
+        assert_eq!(
+            custkeys,
+            int32_column(&repartitioned.generate("orders")?, 1)
+        );
+
+        let other_seed = generator.with_seed(42);
+        assert_ne!(custkeys, int32_column(&other_seed.generate("orders")?, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn foreign_keys() -> Result<()> {
+        let generator = TpchGenerator::new(0.001);
+
+        let orderkeys: HashSet<_> = int32_column(&generator.generate("orders")?, 0)
+            .into_iter()
+            .collect();
+        let customers = num_rows(&generator.generate("customer")?) as i32;
+        assert!(int32_column(&generator.generate("orders")?, 1)
+            .iter()
+            .all(|custkey| *custkey >= 1 && *custkey <= customers));
+
+        let partsupp = generator.generate("partsupp")?;
+        let partsupp: HashSet<_> = int32_column(&partsupp, 0)
+            .into_iter()
+            .zip(int32_column(&partsupp, 1))
+            .collect();
+        let lineitem = generator.generate("lineitem")?;
+        for (orderkey, key) in int32_column(&lineitem, 0).into_iter().zip(
+            int32_column(&lineitem, 1)
+                .into_iter()
+                .zip(int32_column(&lineitem, 2)),
+        ) {
+            assert!(orderkeys.contains(&orderkey));
+            assert!(partsupp.contains(&key), "{:?}", key);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_queries() -> Result<()> {
+        let config = ExecutionConfig::new().with_concurrency(2);
+        let mut ctx = ExecutionContext::with_config(config);
+        TpchGenerator::new(0.001)
+            .with_partitions(2)
+            .register_tables(&mut ctx)?;
+
+        for query in &[1, 3, 5, 6, 9, 10, 12, 14, 19] {
+            ctx.sql(get_query_sql(*query)?)?.collect().await?;
+        }
+
+        // pricing summary report over all shipped line items
+        let results = ctx.sql(get_query_sql(1)?)?.collect().await?;
+        let groups: usize = results.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(groups, 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_parquet() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("tpch-{}", std::process::id()));
+        let ctx = ExecutionContext::new();
+        let generator = TpchGenerator::new(0.001).with_partitions(2);
+        generator.write_parquet(&ctx, &dir, None).await?;
+
+        let mut ctx = ExecutionContext::new();
+        for table in TABLES {
+            ctx.register_parquet(table, dir.join(table).to_str().unwrap())?;
+        }
+        let results = ctx.sql("SELECT COUNT(*) FROM nation")?.collect().await?;
+        let count = results[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<datafusion::arrow::array::UInt64Array>()
+            .unwrap()
+            .value(0);
+        assert_eq!(count, 25);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures::{Stream, StreamExt};
+use prost::Message;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::ipc::writer::IpcWriteOptions;
+use datafusion::prelude::*;
+
+use arrow_flight::{
+    flight_service_server::FlightService, flight_service_server::FlightServiceServer,
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint,
+    FlightInfo, HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
+};
+
+/// The subset of the Flight SQL protocol messages (`FlightSql.proto`) this
+/// service understands. Flight SQL wraps all of them in a `google.protobuf.Any`.
+mod flight_sql {
+    /// `google.protobuf.Any`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Any {
+        #[prost(string, tag = "1")]
+        pub type_url: String,
+        #[prost(bytes, tag = "2")]
+        pub value: Vec<u8>,
+    }
+
+    /// Executes an ad-hoc SQL query
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommandStatementQuery {
+        #[prost(string, tag = "1")]
+        pub query: String,
+    }
+
+    /// Fetches the results of a `CommandStatementQuery`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TicketStatementQuery {
+        #[prost(bytes, tag = "1")]
+        pub statement_handle: Vec<u8>,
+    }
+
+    /// Executes a prepared statement
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommandPreparedStatementQuery {
+        #[prost(bytes, tag = "1")]
+        pub prepared_statement_handle: Vec<u8>,
+    }
+
+    /// Body of the `CreatePreparedStatement` action
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ActionCreatePreparedStatementRequest {
+        #[prost(string, tag = "1")]
+        pub query: String,
+    }
+
+    /// Result of the `CreatePreparedStatement` action
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ActionCreatePreparedStatementResult {
+        #[prost(bytes, tag = "1")]
+        pub prepared_statement_handle: Vec<u8>,
+        #[prost(bytes, tag = "2")]
+        pub dataset_schema: Vec<u8>,
+        #[prost(bytes, tag = "3")]
+        pub parameter_schema: Vec<u8>,
+    }
+
+    /// Body of the `ClosePreparedStatement` action
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ActionClosePreparedStatementRequest {
+        #[prost(bytes, tag = "1")]
+        pub prepared_statement_handle: Vec<u8>,
+    }
+
+    /// A Flight SQL message that can be packed into an [`Any`]
+    pub trait AnyMessage: prost::Message + Default {
+        const TYPE_NAME: &'static str;
+
+        fn type_url() -> String {
+            format!(
+                "type.googleapis.com/arrow.flight.protocol.sql.{}",
+                Self::TYPE_NAME
+            )
+        }
+    }
+
+    macro_rules! any_message {
+        ($($MESSAGE:ident),*) => {
+            $(impl AnyMessage for $MESSAGE {
+                const TYPE_NAME: &'static str = stringify!($MESSAGE);
+            })*
+        };
+    }
+
+    any_message!(
+        CommandStatementQuery,
+        TicketStatementQuery,
+        CommandPreparedStatementQuery,
+        ActionCreatePreparedStatementRequest,
+        ActionCreatePreparedStatementResult,
+        ActionClosePreparedStatementRequest
+    );
+
+    /// Encodes `message` into a new buffer
+    pub fn encode<M: prost::Message>(message: &M) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(message.encoded_len());
+        // encoding into a `Vec` can only fail if it runs out of capacity
+        message.encode(&mut buf).unwrap();
+        buf
+    }
+
+    impl Any {
+        pub fn pack<M: AnyMessage>(message: &M) -> Self {
+            Any {
+                type_url: M::type_url(),
+                value: encode(message),
+            }
+        }
+
+        pub fn is<M: AnyMessage>(&self) -> bool {
+            self.type_url == M::type_url()
+        }
+
+        pub fn unpack<M: AnyMessage>(&self) -> Result<M, prost::DecodeError> {
+            M::decode(self.value.as_slice())
+        }
+    }
+}
+
+use flight_sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
+    ActionCreatePreparedStatementResult, Any, CommandPreparedStatementQuery,
+    CommandStatementQuery, TicketStatementQuery,
+};
+
+const CREATE_PREPARED_STATEMENT: &str = "CreatePreparedStatement";
+const CLOSE_PREPARED_STATEMENT: &str = "ClosePreparedStatement";
+
+/// A Flight SQL service that plans and executes queries with an embedded
+/// `ExecutionContext`, so that standard Flight SQL clients (e.g. the JDBC
+/// driver) can query the tables registered with it.
+#[derive(Clone)]
+pub struct FlightSqlServiceImpl {
+    ctx: ExecutionContext,
+    /// SQL text of the open prepared statements by handle
+    prepared_statements: Arc<Mutex<HashMap<Vec<u8>, String>>>,
+    next_handle: Arc<AtomicU64>,
+}
+
+impl FlightSqlServiceImpl {
+    pub fn new(ctx: ExecutionContext) -> Self {
+        Self {
+            ctx,
+            prepared_statements: Arc::new(Mutex::new(HashMap::new())),
+            next_handle: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Plans `sql` and returns the schema of its results
+    fn plan(&self, sql: &str) -> Result<Schema, Status> {
+        let mut ctx = self.ctx.clone();
+        let df = ctx.sql(sql).map_err(to_tonic_err)?;
+        Ok(df.schema().clone().into())
+    }
+
+    fn prepared_statement(&self, handle: &[u8]) -> Result<String, Status> {
+        self.prepared_statements
+            .lock()
+            .unwrap()
+            .get(handle)
+            .cloned()
+            .ok_or_else(|| Status::not_found("Unknown prepared statement handle"))
+    }
+
+    fn flight_info(
+        &self,
+        sql: &str,
+        descriptor: FlightDescriptor,
+        ticket: Any,
+    ) -> Result<FlightInfo, Status> {
+        let schema = self.plan(sql)?;
+        Ok(FlightInfo {
+            schema: ipc_schema(&schema),
+            flight_descriptor: Some(descriptor),
+            endpoint: vec![FlightEndpoint {
+                ticket: Some(Ticket {
+                    ticket: flight_sql::encode(&ticket),
+                }),
+                location: vec![],
+            }],
+            total_records: -1,
+            total_bytes: -1,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightSqlServiceImpl {
+    type HandshakeStream = Pin<
+        Box<dyn Stream<Item = Result<HandshakeResponse, Status>> + Send + Sync + 'static>,
+    >;
+    type ListFlightsStream =
+        Pin<Box<dyn Stream<Item = Result<FlightInfo, Status>> + Send + Sync + 'static>>;
+    type DoGetStream =
+        Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + Sync + 'static>>;
+    type DoPutStream =
+        Pin<Box<dyn Stream<Item = Result<PutResult, Status>> + Send + Sync + 'static>>;
+    type DoActionStream = Pin<
+        Box<
+            dyn Stream<Item = Result<arrow_flight::Result, Status>>
+                + Send
+                + Sync
+                + 'static,
+        >,
+    >;
+    type ListActionsStream =
+        Pin<Box<dyn Stream<Item = Result<ActionType, Status>> + Send + Sync + 'static>>;
+    type DoExchangeStream =
+        Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + Sync + 'static>>;
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let descriptor = request.into_inner();
+        let command = decode_any(&descriptor.cmd)?;
+
+        let info = if command.is::<CommandStatementQuery>() {
+            let command: CommandStatementQuery =
+                command.unpack().map_err(to_decode_err)?;
+            // ad-hoc statements are not kept on the server, their handle is
+            // the query itself
+            let ticket = Any::pack(&TicketStatementQuery {
+                statement_handle: command.query.clone().into_bytes(),
+            });
+            self.flight_info(&command.query, descriptor, ticket)?
+        } else if command.is::<CommandPreparedStatementQuery>() {
+            let prepared: CommandPreparedStatementQuery =
+                command.unpack().map_err(to_decode_err)?;
+            let sql = self.prepared_statement(&prepared.prepared_statement_handle)?;
+            self.flight_info(&sql, descriptor, command)?
+        } else {
+            return Err(Status::unimplemented(format!(
+                "Unsupported Flight SQL command {}",
+                command.type_url
+            )));
+        };
+        Ok(Response::new(info))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let ticket = decode_any(&request.into_inner().ticket)?;
+
+        let sql = if ticket.is::<TicketStatementQuery>() {
+            let ticket: TicketStatementQuery = ticket.unpack().map_err(to_decode_err)?;
+            String::from_utf8(ticket.statement_handle).map_err(|e| {
+                Status::invalid_argument(format!("Invalid statement handle: {:?}", e))
+            })?
+        } else if ticket.is::<CommandPreparedStatementQuery>() {
+            let prepared: CommandPreparedStatementQuery =
+                ticket.unpack().map_err(to_decode_err)?;
+            self.prepared_statement(&prepared.prepared_statement_handle)?
+        } else {
+            return Err(Status::unimplemented(format!(
+                "Unsupported Flight SQL ticket {}",
+                ticket.type_url
+            )));
+        };
+
+        let mut ctx = self.ctx.clone();
+        let df = ctx.sql(&sql).map_err(to_tonic_err)?;
+        let schema: Schema = df.schema().clone().into();
+        let results = df.execute_stream().await.map_err(to_tonic_err)?;
+
+        // the first message sent is the schema, followed by the dictionaries
+        // and record batches as they are produced
+        let options = IpcWriteOptions::default();
+        let schema_flight_data =
+            arrow_flight::utils::flight_data_from_arrow_schema(&schema, &options);
+        let batches = results.flat_map(move |batch| {
+            let flights: Vec<Result<FlightData, Status>> = match batch {
+                Ok(batch) => {
+                    let (flight_dictionaries, flight_batch) =
+                        arrow_flight::utils::flight_data_from_arrow_batch(
+                            &batch, &options,
+                        );
+                    flight_dictionaries
+                        .into_iter()
+                        .chain(std::iter::once(flight_batch))
+                        .map(Ok)
+                        .collect()
+                }
+                Err(e) => vec![Err(Status::internal(format!("{:?}", e)))],
+            };
+            futures::stream::iter(flights)
+        });
+        let output =
+            futures::stream::once(async { Ok(schema_flight_data) }).chain(batches);
+
+        Ok(Response::new(Box::pin(output) as Self::DoGetStream))
+    }
+
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        let action = request.into_inner();
+        let body = decode_any(&action.body)?;
+
+        let results = match action.r#type.as_str() {
+            CREATE_PREPARED_STATEMENT => {
+                let request: ActionCreatePreparedStatementRequest =
+                    body.unpack().map_err(to_decode_err)?;
+                // plan the statement once to report errors and its schema early
+                let schema = self.plan(&request.query)?;
+                let handle = self
+                    .next_handle
+                    .fetch_add(1, Ordering::SeqCst)
+                    .to_string()
+                    .into_bytes();
+                self.prepared_statements
+                    .lock()
+                    .unwrap()
+                    .insert(handle.clone(), request.query);
+
+                let result = Any::pack(&ActionCreatePreparedStatementResult {
+                    prepared_statement_handle: handle,
+                    dataset_schema: ipc_schema(&schema),
+                    parameter_schema: vec![],
+                });
+                vec![Ok(arrow_flight::Result {
+                    body: flight_sql::encode(&result),
+                })]
+            }
+            CLOSE_PREPARED_STATEMENT => {
+                let request: ActionClosePreparedStatementRequest =
+                    body.unpack().map_err(to_decode_err)?;
+                self.prepared_statements
+                    .lock()
+                    .unwrap()
+                    .remove(&request.prepared_statement_handle);
+                vec![]
+            }
+            other => {
+                return Err(Status::unimplemented(format!(
+                    "Unsupported action {}",
+                    other
+                )))
+            }
+        };
+
+        let output = futures::stream::iter(results);
+        Ok(Response::new(Box::pin(output) as Self::DoActionStream))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        let actions = vec![
+            Ok(ActionType {
+                r#type: CREATE_PREPARED_STATEMENT.to_owned(),
+                description:
+                    "Creates a reusable prepared statement resource on the server"
+                        .to_owned(),
+            }),
+            Ok(ActionType {
+                r#type: CLOSE_PREPARED_STATEMENT.to_owned(),
+                description:
+                    "Closes a reusable prepared statement resource on the server"
+                        .to_owned(),
+            }),
+        ];
+        let output = futures::stream::iter(actions);
+        Ok(Response::new(Box::pin(output) as Self::ListActionsStream))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+}
+
+/// Encodes `schema` as an IPC-encapsulated message, as Flight SQL expects it
+fn ipc_schema(schema: &Schema) -> Vec<u8> {
+    let options = IpcWriteOptions::default();
+    arrow_flight::utils::flight_schema_from_arrow_schema(schema, &options).schema
+}
+
+fn decode_any(bytes: &[u8]) -> Result<Any, Status> {
+    Any::decode(bytes).map_err(to_decode_err)
+}
+
+fn to_decode_err(e: prost::DecodeError) -> Status {
+    Status::invalid_argument(format!("Invalid Flight SQL message: {:?}", e))
+}
+
+fn to_tonic_err(e: datafusion::error::DataFusionError) -> Status {
+    Status::internal(format!("{:?}", e))
+}
+
+/// This example shows how to expose an embedded `ExecutionContext` through
+/// Arrow Flight SQL, so that standard Flight SQL clients can run queries
+/// against the tables registered with it.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let testdata = datafusion::arrow::util::test_util::parquet_test_data();
+
+    // register parquet file with the execution context
+    let mut ctx = ExecutionContext::new();
+    ctx.register_parquet(
+        "alltypes_plain",
+        &format!("{}/alltypes_plain.parquet", testdata),
+    )?;
+
+    let addr = "0.0.0.0:50051".parse()?;
+    let svc = FlightServiceServer::new(FlightSqlServiceImpl::new(ctx));
+
+    println!("Listening on {:?}", addr);
+
+    Server::builder().add_service(svc).serve(addr).await?;
+
+    Ok(())
+}
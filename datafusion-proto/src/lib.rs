@@ -0,0 +1,147 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serialization of DataFusion [`LogicalPlan`]s, [`Expr`]s and
+//! [`ExecutionPlan`]s to and from [protocol buffers](https://developers.google.com/protocol-buffers),
+//! for example to ship plans between the processes of a distributed
+//! scheduler.
+//!
+//! Built-in functions and file scans (CSV and Parquet) are serialized
+//! completely. User defined functions and tables other than file scans are
+//! serialized by the name they are registered with, and looked up in the
+//! [`ExecutionContext`] a plan is deserialized with.
+//!
+//! ```
+//! # use datafusion::prelude::*;
+//! # use datafusion::error::Result;
+//! # use datafusion_proto::{logical_plan_from_bytes, logical_plan_to_bytes};
+//! # fn main() -> Result<()> {
+//! let ctx = ExecutionContext::new();
+//! let plan = LogicalPlanBuilder::empty(true)
+//!     .project(vec![lit(1).alias("one")])?
+//!     .build()?;
+//!
+//! let bytes = logical_plan_to_bytes(&plan)?;
+//! let round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+//! assert_eq!(format!("{:?}", plan), format!("{:?}", round_trip));
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::ExecutionContext;
+use datafusion::logical_plan::{Expr, FunctionRegistry, LogicalPlan};
+use datafusion::physical_plan::ExecutionPlan;
+use prost::Message;
+
+// include the generated protobuf source as a submodule
+#[allow(clippy::all)]
+#[allow(missing_docs)]
+pub mod protobuf {
+    include!(concat!(env!("OUT_DIR"), "/datafusion.rs"));
+}
+
+macro_rules! convert_required {
+    ($PB:expr) => {{
+        if let Some(field) = $PB.as_ref() {
+            field.try_into()
+        } else {
+            Err(proto_error("Missing required field in protobuf"))
+        }
+    }};
+}
+
+macro_rules! convert_box_required {
+    ($PB:expr) => {{
+        if let Some(field) = $PB.as_ref() {
+            field.as_ref().try_into()
+        } else {
+            Err(proto_error("Missing required field in protobuf"))
+        }
+    }};
+}
+
+pub mod logical_plan;
+pub mod physical_plan;
+
+/// Serializes a logical expression to protobuf bytes
+pub fn expr_to_bytes(expr: &Expr) -> Result<Vec<u8>> {
+    let proto: protobuf::LogicalExprNode = expr.try_into()?;
+    encode(&proto)
+}
+
+/// Deserializes a logical expression from protobuf bytes, looking up user
+/// defined functions in `registry`
+pub fn expr_from_bytes(bytes: &[u8], registry: &dyn FunctionRegistry) -> Result<Expr> {
+    let proto = decode::<protobuf::LogicalExprNode>(bytes)?;
+    logical_plan::from_proto::parse_expr(&proto, registry)
+}
+
+/// Serializes a logical plan to protobuf bytes
+pub fn logical_plan_to_bytes(plan: &LogicalPlan) -> Result<Vec<u8>> {
+    let proto: protobuf::LogicalPlanNode = plan.try_into()?;
+    encode(&proto)
+}
+
+/// Deserializes a logical plan from protobuf bytes, looking up user defined
+/// functions and tables in `ctx`
+pub fn logical_plan_from_bytes(
+    bytes: &[u8],
+    ctx: &ExecutionContext,
+) -> Result<LogicalPlan> {
+    let proto = decode::<protobuf::LogicalPlanNode>(bytes)?;
+    let state = ctx.state.lock().unwrap().clone();
+    logical_plan::from_proto::parse_logical_plan(&proto, &state)
+}
+
+/// Serializes a physical plan to protobuf bytes
+pub fn physical_plan_to_bytes(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<u8>> {
+    let proto: protobuf::PhysicalPlanNode = plan.try_into()?;
+    encode(&proto)
+}
+
+/// Deserializes a physical plan from protobuf bytes, looking up user defined
+/// functions in `ctx` and planning expressions with its configuration
+pub fn physical_plan_from_bytes(
+    bytes: &[u8],
+    ctx: &ExecutionContext,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let proto = decode::<protobuf::PhysicalPlanNode>(bytes)?;
+    let state = ctx.state.lock().unwrap().clone();
+    physical_plan::from_proto::parse_physical_plan(&proto, &state)
+}
+
+fn encode<M: Message>(message: &M) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message.encode(&mut buf).map_err(|e| {
+        DataFusionError::Internal(format!("Error encoding protobuf: {}", e))
+    })?;
+    Ok(buf)
+}
+
+fn decode<M: Message + Default>(bytes: &[u8]) -> Result<M> {
+    M::decode(bytes)
+        .map_err(|e| DataFusionError::Plan(format!("Error decoding protobuf: {}", e)))
+}
+
+pub(crate) fn proto_error<S: Into<String>>(message: S) -> DataFusionError {
+    DataFusionError::Plan(message.into())
+}
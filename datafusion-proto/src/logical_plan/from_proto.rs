@@ -0,0 +1,772 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serde code to convert from protocol buffers to Arrow schemas and
+//! DataFusion logical plans
+
+use std::borrow::Borrow;
+use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use datafusion::catalog::TableReference;
+use datafusion::datasource::parquet::ParquetTable;
+use datafusion::datasource::{CsvFile, CsvReadOptions, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::ExecutionContextState;
+use datafusion::logical_plan::{
+    Expr, FunctionRegistry, JoinType, LogicalPlan, LogicalPlanBuilder, Operator,
+    Partitioning, ToDFSchema,
+};
+use datafusion::physical_plan::aggregates::AggregateFunction;
+use datafusion::physical_plan::functions::BuiltinScalarFunction;
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::parser::FileType;
+use datafusion::sql::planner::ContextProvider;
+
+use crate::proto_error;
+use crate::protobuf;
+use protobuf::arrow_type::ArrowTypeEnum;
+use protobuf::logical_expr_node::ExprType;
+use protobuf::logical_plan_node::LogicalPlanType;
+use protobuf::scalar_value::Value;
+
+/// Converts a protobuf logical plan back into a [`LogicalPlan`], looking up
+/// user defined functions and tables by name in `state`
+pub fn parse_logical_plan(
+    proto: &protobuf::LogicalPlanNode,
+    state: &ExecutionContextState,
+) -> Result<LogicalPlan> {
+    let plan = proto.logical_plan_type.as_ref().ok_or_else(|| {
+        proto_error("logical_plan::from_proto() Unsupported logical plan")
+    })?;
+    match plan {
+        LogicalPlanType::TableScan(scan) => {
+            let schema: Schema = convert_required!(scan.schema)?;
+            let provider: Arc<dyn TableProvider> = match &scan.source {
+                Some(protobuf::table_scan_node::Source::Csv(csv)) => {
+                    let delimiter = match csv.delimiter.as_bytes() {
+                        [delimiter] => *delimiter,
+                        _ => {
+                            return Err(proto_error(format!(
+                                "Invalid CSV delimiter '{}'",
+                                csv.delimiter
+                            )))
+                        }
+                    };
+                    let options = CsvReadOptions::new()
+                        .schema(&schema)
+                        .has_header(csv.has_header)
+                        .delimiter(delimiter)
+                        .file_extension(&csv.file_extension);
+                    Arc::new(CsvFile::try_new(&csv.path, options)?)
+                }
+                Some(protobuf::table_scan_node::Source::Parquet(parquet)) => Arc::new(
+                    ParquetTable::try_new(
+                        &parquet.path,
+                        parquet.max_concurrency as usize,
+                    )?
+                    .with_enable_pruning(parquet.enable_pruning),
+                ),
+                None => state
+                    .get_table_provider(TableReference::from(scan.table_name.as_str()))
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(format!(
+                            "Table '{}' not found",
+                            scan.table_name
+                        ))
+                    })?,
+            };
+
+            let projection = scan
+                .projection
+                .as_ref()
+                .map(|projection| {
+                    let schema = provider.schema();
+                    projection
+                        .columns
+                        .iter()
+                        .map(|name| schema.index_of(name).map_err(DataFusionError::from))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?;
+
+            let mut plan =
+                LogicalPlanBuilder::scan(&scan.table_name, provider, projection)?
+                    .build()?;
+            if let LogicalPlan::TableScan { filters, limit, .. } = &mut plan {
+                *filters = parse_exprs(&scan.filters, state)?;
+                *limit = scan.optional_limit.as_ref().map(|limit| match limit {
+                    protobuf::table_scan_node::OptionalLimit::Limit(limit) => {
+                        *limit as usize
+                    }
+                });
+            }
+            Ok(plan)
+        }
+        LogicalPlanType::Projection(projection) => {
+            let input = parse_required_plan(&projection.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .project(parse_exprs(&projection.expr, state)?)?
+                .build()
+        }
+        LogicalPlanType::Selection(selection) => {
+            let input = parse_required_plan(&selection.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .filter(parse_required_expr(&selection.expr, state)?)?
+                .build()
+        }
+        LogicalPlanType::Aggregate(aggregate) => {
+            let input = parse_required_plan(&aggregate.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .aggregate(
+                    parse_exprs(&aggregate.group_expr, state)?,
+                    parse_exprs(&aggregate.aggr_expr, state)?,
+                )?
+                .build()
+        }
+        LogicalPlanType::Join(join) => {
+            let left = parse_required_plan(&join.left, state)?;
+            let right = parse_required_plan(&join.right, state)?;
+            let join_type =
+                protobuf::JoinType::from_i32(join.join_type).ok_or_else(|| {
+                    proto_error(format!(
+                        "Received a JoinNode message with unknown JoinType {}",
+                        join.join_type
+                    ))
+                })?;
+            let left_keys: Vec<&str> =
+                join.left_join_column.iter().map(|s| s.as_str()).collect();
+            let right_keys: Vec<&str> =
+                join.right_join_column.iter().map(|s| s.as_str()).collect();
+            LogicalPlanBuilder::from(&left)
+                .join(&right, join_type.into(), &left_keys, &right_keys)?
+                .build()
+        }
+        LogicalPlanType::CrossJoin(cross_join) => {
+            let left = parse_required_plan(&cross_join.left, state)?;
+            let right = parse_required_plan(&cross_join.right, state)?;
+            LogicalPlanBuilder::from(&left).cross_join(&right)?.build()
+        }
+        LogicalPlanType::Limit(limit) => {
+            let input = parse_required_plan(&limit.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .limit(limit.limit as usize)?
+                .build()
+        }
+        LogicalPlanType::Sort(sort) => {
+            let input = parse_required_plan(&sort.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .sort(parse_exprs(&sort.expr, state)?)?
+                .build()
+        }
+        LogicalPlanType::Repartition(repartition) => {
+            use protobuf::repartition_node::PartitionMethod;
+
+            let input = parse_required_plan(&repartition.input, state)?;
+            let partitioning_scheme = match repartition.partition_method.as_ref() {
+                Some(PartitionMethod::Hash(hash)) => Partitioning::Hash(
+                    parse_exprs(&hash.hash_expr, state)?,
+                    hash.partition_count as usize,
+                ),
+                Some(PartitionMethod::RoundRobin(partition_count)) => {
+                    Partitioning::RoundRobinBatch(*partition_count as usize)
+                }
+                None => {
+                    return Err(proto_error(
+                        "Protobuf deserialization error, RepartitionNode was missing required field 'partition_method'",
+                    ))
+                }
+            };
+            LogicalPlanBuilder::from(&input)
+                .repartition(partitioning_scheme)?
+                .build()
+        }
+        LogicalPlanType::Union(union) => {
+            let inputs = union
+                .inputs
+                .iter()
+                .map(|input| parse_logical_plan(input, state))
+                .collect::<Result<Vec<_>>>()?;
+            let schema = inputs
+                .first()
+                .ok_or_else(|| {
+                    proto_error("Received a UnionNode message without inputs")
+                })?
+                .schema()
+                .clone();
+            let alias = if union.alias.is_empty() {
+                None
+            } else {
+                Some(union.alias.clone())
+            };
+            Ok(LogicalPlan::Union {
+                inputs,
+                schema,
+                alias,
+            })
+        }
+        LogicalPlanType::EmptyRelation(empty_relation) => {
+            LogicalPlanBuilder::empty(empty_relation.produce_one_row).build()
+        }
+        LogicalPlanType::Unnest(unnest) => {
+            let input = parse_required_plan(&unnest.input, state)?;
+            LogicalPlanBuilder::from(&input)
+                .unnest(&unnest.column)?
+                .build()
+        }
+        LogicalPlanType::CreateExternalTable(create_extern_table) => {
+            let schema: Schema = convert_required!(create_extern_table.schema)?;
+            let file_type = protobuf::FileType::from_i32(create_extern_table.file_type)
+                .ok_or_else(|| {
+                proto_error(format!(
+                    "Received a CreateExternalTableNode message with unknown FileType {}",
+                    create_extern_table.file_type
+                ))
+            })?;
+            let file_type = match file_type {
+                protobuf::FileType::NdJson => FileType::NdJson,
+                protobuf::FileType::Parquet => FileType::Parquet,
+                protobuf::FileType::Csv => FileType::CSV,
+            };
+            Ok(LogicalPlan::CreateExternalTable {
+                schema: schema.to_dfschema_ref()?,
+                name: create_extern_table.name.clone(),
+                location: create_extern_table.location.clone(),
+                file_type,
+                has_header: create_extern_table.has_header,
+            })
+        }
+    }
+}
+
+fn parse_required_plan(
+    proto: &Option<Box<protobuf::LogicalPlanNode>>,
+    state: &ExecutionContextState,
+) -> Result<LogicalPlan> {
+    match proto {
+        Some(plan) => parse_logical_plan(plan, state),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
+
+/// Converts a protobuf expression back into an [`Expr`], looking up user
+/// defined functions by name in `registry`
+pub fn parse_expr(
+    proto: &protobuf::LogicalExprNode,
+    registry: &dyn FunctionRegistry,
+) -> Result<Expr> {
+    let expr_type = proto
+        .expr_type
+        .as_ref()
+        .ok_or_else(|| proto_error("Unexpected empty logical expression"))?;
+    match expr_type {
+        ExprType::ColumnName(column_name) => Ok(Expr::Column(column_name.clone())),
+        ExprType::Alias(alias) => Ok(Expr::Alias(
+            Box::new(parse_required_expr(&alias.expr, registry)?),
+            alias.alias.clone(),
+        )),
+        ExprType::ScalarVariable(scalar_variable) => {
+            Ok(Expr::ScalarVariable(scalar_variable.names.clone()))
+        }
+        ExprType::Literal(literal) => Ok(Expr::Literal(literal.try_into()?)),
+        ExprType::BinaryExpr(binary_expr) => Ok(Expr::BinaryExpr {
+            left: Box::new(parse_required_expr(&binary_expr.l, registry)?),
+            op: parse_binary_op(&binary_expr.op)?,
+            right: Box::new(parse_required_expr(&binary_expr.r, registry)?),
+        }),
+        ExprType::NotExpr(not) => Ok(Expr::Not(Box::new(parse_required_expr(
+            &not.expr, registry,
+        )?))),
+        ExprType::IsNullExpr(is_null) => Ok(Expr::IsNull(Box::new(parse_required_expr(
+            &is_null.expr,
+            registry,
+        )?))),
+        ExprType::IsNotNullExpr(is_not_null) => Ok(Expr::IsNotNull(Box::new(
+            parse_required_expr(&is_not_null.expr, registry)?,
+        ))),
+        ExprType::Negative(negative) => Ok(Expr::Negative(Box::new(
+            parse_required_expr(&negative.expr, registry)?,
+        ))),
+        ExprType::Between(between) => Ok(Expr::Between {
+            expr: Box::new(parse_required_expr(&between.expr, registry)?),
+            negated: between.negated,
+            low: Box::new(parse_required_expr(&between.low, registry)?),
+            high: Box::new(parse_required_expr(&between.high, registry)?),
+        }),
+        ExprType::Case(case) => {
+            let when_then_expr = case
+                .when_then_expr
+                .iter()
+                .map(|e| {
+                    Ok((
+                        Box::new(parse_required_expr(&e.when_expr, registry)?),
+                        Box::new(parse_required_expr(&e.then_expr, registry)?),
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Expr::Case {
+                expr: parse_optional_expr(&case.expr, registry)?.map(Box::new),
+                when_then_expr,
+                else_expr: parse_optional_expr(&case.else_expr, registry)?.map(Box::new),
+            })
+        }
+        ExprType::Cast(cast) => Ok(Expr::Cast {
+            expr: Box::new(parse_required_expr(&cast.expr, registry)?),
+            data_type: convert_required!(cast.arrow_type)?,
+        }),
+        ExprType::TryCast(cast) => Ok(Expr::TryCast {
+            expr: Box::new(parse_required_expr(&cast.expr, registry)?),
+            data_type: convert_required!(cast.arrow_type)?,
+        }),
+        ExprType::Sort(sort) => Ok(Expr::Sort {
+            expr: Box::new(parse_required_expr(&sort.expr, registry)?),
+            asc: sort.asc,
+            nulls_first: sort.nulls_first,
+        }),
+        ExprType::ScalarFunction(scalar_function) => {
+            let fun = protobuf::ScalarFunction::from_i32(scalar_function.fun)
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "Received an unknown scalar function: {}",
+                        scalar_function.fun
+                    ))
+                })?;
+            Ok(Expr::ScalarFunction {
+                fun: fun.into(),
+                args: parse_exprs(&scalar_function.args, registry)?,
+            })
+        }
+        ExprType::ScalarUdfExpr(udf) => Ok(Expr::ScalarUDF {
+            fun: registry.udf(&udf.fun_name)?,
+            args: parse_exprs(&udf.args, registry)?,
+        }),
+        ExprType::AggregateExpr(aggregate_expr) => {
+            let fun = protobuf::AggregateFunction::from_i32(aggregate_expr.aggr_function)
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "Received an unknown aggregate function: {}",
+                        aggregate_expr.aggr_function
+                    ))
+                })?;
+            Ok(Expr::AggregateFunction {
+                fun: fun.into(),
+                args: parse_exprs(&aggregate_expr.args, registry)?,
+                distinct: aggregate_expr.distinct,
+            })
+        }
+        ExprType::AggregateUdfExpr(udaf) => Ok(Expr::AggregateUDF {
+            fun: registry.udaf(&udaf.fun_name)?,
+            args: parse_exprs(&udaf.args, registry)?,
+        }),
+        ExprType::InList(in_list) => Ok(Expr::InList {
+            expr: Box::new(parse_required_expr(&in_list.expr, registry)?),
+            list: parse_exprs(&in_list.list, registry)?,
+            negated: in_list.negated,
+        }),
+        ExprType::Wildcard(_) => Ok(Expr::Wildcard),
+    }
+}
+
+fn parse_exprs(
+    protos: &[protobuf::LogicalExprNode],
+    registry: &dyn FunctionRegistry,
+) -> Result<Vec<Expr>> {
+    protos
+        .iter()
+        .map(|proto| parse_expr(proto, registry))
+        .collect()
+}
+
+fn parse_required_expr<E: Borrow<protobuf::LogicalExprNode>>(
+    proto: &Option<E>,
+    registry: &dyn FunctionRegistry,
+) -> Result<Expr> {
+    match proto {
+        Some(expr) => parse_expr(expr.borrow(), registry),
+        None => Err(proto_error("Missing required expression in protobuf")),
+    }
+}
+
+fn parse_optional_expr<E: Borrow<protobuf::LogicalExprNode>>(
+    proto: &Option<E>,
+    registry: &dyn FunctionRegistry,
+) -> Result<Option<Expr>> {
+    proto
+        .as_ref()
+        .map(|expr| parse_expr(expr.borrow(), registry))
+        .transpose()
+}
+
+/// Parses the `Debug` representation of an [`Operator`]
+fn parse_binary_op(op: &str) -> Result<Operator> {
+    match op {
+        "Eq" => Ok(Operator::Eq),
+        "NotEq" => Ok(Operator::NotEq),
+        "Lt" => Ok(Operator::Lt),
+        "LtEq" => Ok(Operator::LtEq),
+        "Gt" => Ok(Operator::Gt),
+        "GtEq" => Ok(Operator::GtEq),
+        "Plus" => Ok(Operator::Plus),
+        "Minus" => Ok(Operator::Minus),
+        "Multiply" => Ok(Operator::Multiply),
+        "Divide" => Ok(Operator::Divide),
+        "Modulus" => Ok(Operator::Modulus),
+        "And" => Ok(Operator::And),
+        "Or" => Ok(Operator::Or),
+        "Like" => Ok(Operator::Like),
+        "NotLike" => Ok(Operator::NotLike),
+        other => Err(proto_error(format!(
+            "Unsupported binary operator '{:?}'",
+            other
+        ))),
+    }
+}
+
+impl TryFrom<&protobuf::ScalarValue> for ScalarValue {
+    type Error = DataFusionError;
+
+    fn try_from(scalar: &protobuf::ScalarValue) -> Result<Self> {
+        let value = scalar
+            .value
+            .as_ref()
+            .ok_or_else(|| proto_error("Unexpected empty scalar value"))?;
+        Ok(match value {
+            Value::BoolValue(v) => ScalarValue::Boolean(Some(*v)),
+            Value::Utf8Value(v) => ScalarValue::Utf8(Some(v.clone())),
+            Value::LargeUtf8Value(v) => ScalarValue::LargeUtf8(Some(v.clone())),
+            Value::Int8Value(v) => ScalarValue::Int8(Some(*v as i8)),
+            Value::Int16Value(v) => ScalarValue::Int16(Some(*v as i16)),
+            Value::Int32Value(v) => ScalarValue::Int32(Some(*v)),
+            Value::Int64Value(v) => ScalarValue::Int64(Some(*v)),
+            Value::Uint8Value(v) => ScalarValue::UInt8(Some(*v as u8)),
+            Value::Uint16Value(v) => ScalarValue::UInt16(Some(*v as u16)),
+            Value::Uint32Value(v) => ScalarValue::UInt32(Some(*v)),
+            Value::Uint64Value(v) => ScalarValue::UInt64(Some(*v)),
+            Value::Float32Value(v) => ScalarValue::Float32(Some(*v)),
+            Value::Float64Value(v) => ScalarValue::Float64(Some(*v)),
+            Value::BinaryValue(v) => ScalarValue::Binary(Some(v.clone())),
+            Value::LargeBinaryValue(v) => ScalarValue::LargeBinary(Some(v.clone())),
+            Value::Date32Value(v) => ScalarValue::Date32(Some(*v)),
+            Value::Date64Value(v) => ScalarValue::Date64(Some(*v)),
+            Value::TimestampSecondValue(v) => ScalarValue::TimestampSecond(Some(*v)),
+            Value::TimestampMillisecondValue(v) => {
+                ScalarValue::TimestampMillisecond(Some(*v))
+            }
+            Value::TimestampMicrosecondValue(v) => {
+                ScalarValue::TimestampMicrosecond(Some(*v))
+            }
+            Value::TimestampNanosecondValue(v) => {
+                ScalarValue::TimestampNanosecond(Some(*v))
+            }
+            Value::IntervalYearmonthValue(v) => ScalarValue::IntervalYearMonth(Some(*v)),
+            Value::IntervalDaytimeValue(v) => ScalarValue::IntervalDayTime(Some(*v)),
+            Value::ListValue(list) => ScalarValue::List(
+                Some(
+                    list.values
+                        .iter()
+                        .map(|value| value.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                ),
+                convert_required!(list.datatype)?,
+            ),
+            Value::StructValue(struct_value) => ScalarValue::Struct(
+                Some(
+                    struct_value
+                        .values
+                        .iter()
+                        .map(|value| value.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                ),
+                struct_value
+                    .fields
+                    .iter()
+                    .map(|field| field.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            Value::NullValue(data_type) => {
+                let data_type: DataType = data_type.try_into()?;
+                ScalarValue::try_from(&data_type)?
+            }
+        })
+    }
+}
+
+impl TryFrom<&protobuf::Schema> for Schema {
+    type Error = DataFusionError;
+
+    fn try_from(schema: &protobuf::Schema) -> Result<Self> {
+        let fields = schema
+            .columns
+            .iter()
+            .map(|field| field.try_into())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Schema::new(fields))
+    }
+}
+
+impl TryFrom<&protobuf::Field> for Field {
+    type Error = DataFusionError;
+
+    fn try_from(field: &protobuf::Field) -> Result<Self> {
+        let data_type = field
+            .arrow_type
+            .as_ref()
+            .ok_or_else(|| proto_error("Protobuf deserialization error: Field message missing required field 'arrow_type'"))?;
+        Ok(Field::new(
+            &field.name,
+            data_type.as_ref().try_into()?,
+            field.nullable,
+        ))
+    }
+}
+
+impl TryFrom<&protobuf::ArrowType> for DataType {
+    type Error = DataFusionError;
+
+    fn try_from(arrow_type: &protobuf::ArrowType) -> Result<Self> {
+        let arrow_type_enum = arrow_type
+            .arrow_type_enum
+            .as_ref()
+            .ok_or_else(|| proto_error("Protobuf deserialization error: ArrowType missing required field 'arrow_type_enum'"))?;
+        Ok(match arrow_type_enum {
+            ArrowTypeEnum::None(_) => DataType::Null,
+            ArrowTypeEnum::Bool(_) => DataType::Boolean,
+            ArrowTypeEnum::Uint8(_) => DataType::UInt8,
+            ArrowTypeEnum::Int8(_) => DataType::Int8,
+            ArrowTypeEnum::Uint16(_) => DataType::UInt16,
+            ArrowTypeEnum::Int16(_) => DataType::Int16,
+            ArrowTypeEnum::Uint32(_) => DataType::UInt32,
+            ArrowTypeEnum::Int32(_) => DataType::Int32,
+            ArrowTypeEnum::Uint64(_) => DataType::UInt64,
+            ArrowTypeEnum::Int64(_) => DataType::Int64,
+            ArrowTypeEnum::Float16(_) => DataType::Float16,
+            ArrowTypeEnum::Float32(_) => DataType::Float32,
+            ArrowTypeEnum::Float64(_) => DataType::Float64,
+            ArrowTypeEnum::Utf8(_) => DataType::Utf8,
+            ArrowTypeEnum::LargeUtf8(_) => DataType::LargeUtf8,
+            ArrowTypeEnum::Binary(_) => DataType::Binary,
+            ArrowTypeEnum::FixedSizeBinary(size) => DataType::FixedSizeBinary(*size),
+            ArrowTypeEnum::LargeBinary(_) => DataType::LargeBinary,
+            ArrowTypeEnum::Date32(_) => DataType::Date32,
+            ArrowTypeEnum::Date64(_) => DataType::Date64,
+            ArrowTypeEnum::Duration(time_unit) => {
+                DataType::Duration(parse_time_unit(*time_unit)?)
+            }
+            ArrowTypeEnum::Timestamp(timestamp) => DataType::Timestamp(
+                parse_time_unit(timestamp.time_unit)?,
+                if timestamp.timezone.is_empty() {
+                    None
+                } else {
+                    Some(timestamp.timezone.clone())
+                },
+            ),
+            ArrowTypeEnum::Time32(time_unit) => {
+                DataType::Time32(parse_time_unit(*time_unit)?)
+            }
+            ArrowTypeEnum::Time64(time_unit) => {
+                DataType::Time64(parse_time_unit(*time_unit)?)
+            }
+            ArrowTypeEnum::Interval(interval_unit) => {
+                let interval_unit = protobuf::IntervalUnit::from_i32(*interval_unit)
+                    .ok_or_else(|| {
+                        proto_error(format!(
+                            "Received an unknown IntervalUnit: {}",
+                            interval_unit
+                        ))
+                    })?;
+                DataType::Interval(match interval_unit {
+                    protobuf::IntervalUnit::YearMonth => IntervalUnit::YearMonth,
+                    protobuf::IntervalUnit::DayTime => IntervalUnit::DayTime,
+                })
+            }
+            ArrowTypeEnum::Decimal(decimal) => {
+                DataType::Decimal(decimal.whole as usize, decimal.fractional as usize)
+            }
+            ArrowTypeEnum::List(list) => {
+                DataType::List(Box::new(convert_box_required!(list.field_type)?))
+            }
+            ArrowTypeEnum::LargeList(list) => {
+                DataType::LargeList(Box::new(convert_box_required!(list.field_type)?))
+            }
+            ArrowTypeEnum::FixedSizeList(list) => DataType::FixedSizeList(
+                Box::new(convert_box_required!(list.field_type)?),
+                list.list_size,
+            ),
+            ArrowTypeEnum::Struct(strct) => DataType::Struct(
+                strct
+                    .sub_field_types
+                    .iter()
+                    .map(|field| field.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            ArrowTypeEnum::Union(union) => DataType::Union(
+                union
+                    .union_types
+                    .iter()
+                    .map(|field| field.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            ArrowTypeEnum::Dictionary(dict) => DataType::Dictionary(
+                Box::new(convert_box_required!(dict.key)?),
+                Box::new(convert_box_required!(dict.value)?),
+            ),
+        })
+    }
+}
+
+fn parse_time_unit(time_unit: i32) -> Result<TimeUnit> {
+    let time_unit = protobuf::TimeUnit::from_i32(time_unit).ok_or_else(|| {
+        proto_error(format!("Received an unknown TimeUnit: {}", time_unit))
+    })?;
+    Ok(match time_unit {
+        protobuf::TimeUnit::Second => TimeUnit::Second,
+        protobuf::TimeUnit::TimeMillisecond => TimeUnit::Millisecond,
+        protobuf::TimeUnit::Microsecond => TimeUnit::Microsecond,
+        protobuf::TimeUnit::Nanosecond => TimeUnit::Nanosecond,
+    })
+}
+
+impl From<protobuf::JoinType> for JoinType {
+    fn from(join_type: protobuf::JoinType) -> Self {
+        match join_type {
+            protobuf::JoinType::InnerJoin => JoinType::Inner,
+            protobuf::JoinType::LeftJoin => JoinType::Left,
+            protobuf::JoinType::RightJoin => JoinType::Right,
+            protobuf::JoinType::FullJoin => JoinType::Full,
+            protobuf::JoinType::SemiJoin => JoinType::Semi,
+            protobuf::JoinType::AntiJoin => JoinType::Anti,
+        }
+    }
+}
+
+impl From<protobuf::AggregateFunction> for AggregateFunction {
+    fn from(fun: protobuf::AggregateFunction) -> Self {
+        match fun {
+            protobuf::AggregateFunction::Min => AggregateFunction::Min,
+            protobuf::AggregateFunction::Max => AggregateFunction::Max,
+            protobuf::AggregateFunction::Sum => AggregateFunction::Sum,
+            protobuf::AggregateFunction::Avg => AggregateFunction::Avg,
+            protobuf::AggregateFunction::Count => AggregateFunction::Count,
+        }
+    }
+}
+
+impl From<protobuf::ScalarFunction> for BuiltinScalarFunction {
+    fn from(fun: protobuf::ScalarFunction) -> Self {
+        match fun {
+            protobuf::ScalarFunction::Abs => Self::Abs,
+            protobuf::ScalarFunction::Acos => Self::Acos,
+            protobuf::ScalarFunction::Asin => Self::Asin,
+            protobuf::ScalarFunction::Atan => Self::Atan,
+            protobuf::ScalarFunction::Atan2 => Self::Atan2,
+            protobuf::ScalarFunction::Cbrt => Self::Cbrt,
+            protobuf::ScalarFunction::Ceil => Self::Ceil,
+            protobuf::ScalarFunction::Cos => Self::Cos,
+            protobuf::ScalarFunction::Degrees => Self::Degrees,
+            protobuf::ScalarFunction::Exp => Self::Exp,
+            protobuf::ScalarFunction::Factorial => Self::Factorial,
+            protobuf::ScalarFunction::Floor => Self::Floor,
+            protobuf::ScalarFunction::Gcd => Self::Gcd,
+            protobuf::ScalarFunction::Lcm => Self::Lcm,
+            protobuf::ScalarFunction::Ln => Self::Ln,
+            protobuf::ScalarFunction::Log => Self::Log,
+            protobuf::ScalarFunction::Log10 => Self::Log10,
+            protobuf::ScalarFunction::Log2 => Self::Log2,
+            protobuf::ScalarFunction::Pi => Self::Pi,
+            protobuf::ScalarFunction::Power => Self::Power,
+            protobuf::ScalarFunction::Radians => Self::Radians,
+            protobuf::ScalarFunction::Round => Self::Round,
+            protobuf::ScalarFunction::Signum => Self::Signum,
+            protobuf::ScalarFunction::Sin => Self::Sin,
+            protobuf::ScalarFunction::Sqrt => Self::Sqrt,
+            protobuf::ScalarFunction::Tan => Self::Tan,
+            protobuf::ScalarFunction::Trunc => Self::Trunc,
+            protobuf::ScalarFunction::Array => Self::Array,
+            protobuf::ScalarFunction::ArrayAppend => Self::ArrayAppend,
+            protobuf::ScalarFunction::ArrayConcat => Self::ArrayConcat,
+            protobuf::ScalarFunction::ArrayContains => Self::ArrayContains,
+            protobuf::ScalarFunction::ArrayLength => Self::ArrayLength,
+            protobuf::ScalarFunction::ArrayPosition => Self::ArrayPosition,
+            protobuf::ScalarFunction::ArraySlice => Self::ArraySlice,
+            protobuf::ScalarFunction::ArrowTypeof => Self::ArrowTypeof,
+            protobuf::ScalarFunction::Ascii => Self::Ascii,
+            protobuf::ScalarFunction::BitLength => Self::BitLength,
+            protobuf::ScalarFunction::Btrim => Self::Btrim,
+            protobuf::ScalarFunction::CharacterLength => Self::CharacterLength,
+            protobuf::ScalarFunction::Chr => Self::Chr,
+            protobuf::ScalarFunction::Coalesce => Self::Coalesce,
+            protobuf::ScalarFunction::Concat => Self::Concat,
+            protobuf::ScalarFunction::ConcatWithSeparator => Self::ConcatWithSeparator,
+            protobuf::ScalarFunction::DateBin => Self::DateBin,
+            protobuf::ScalarFunction::DatePart => Self::DatePart,
+            protobuf::ScalarFunction::DateTrunc => Self::DateTrunc,
+            protobuf::ScalarFunction::Decode => Self::Decode,
+            protobuf::ScalarFunction::ElementAt => Self::ElementAt,
+            protobuf::ScalarFunction::Encode => Self::Encode,
+            protobuf::ScalarFunction::FuzzyMatch => Self::FuzzyMatch,
+            protobuf::ScalarFunction::InitCap => Self::InitCap,
+            protobuf::ScalarFunction::JsonGet => Self::JsonGet,
+            protobuf::ScalarFunction::JsonGetBool => Self::JsonGetBool,
+            protobuf::ScalarFunction::JsonGetFloat => Self::JsonGetFloat,
+            protobuf::ScalarFunction::JsonGetInt => Self::JsonGetInt,
+            protobuf::ScalarFunction::JsonGetStr => Self::JsonGetStr,
+            protobuf::ScalarFunction::Left => Self::Left,
+            protobuf::ScalarFunction::Levenshtein => Self::Levenshtein,
+            protobuf::ScalarFunction::Lpad => Self::Lpad,
+            protobuf::ScalarFunction::Lower => Self::Lower,
+            protobuf::ScalarFunction::Ltrim => Self::Ltrim,
+            protobuf::ScalarFunction::MakeArray => Self::MakeArray,
+            protobuf::ScalarFunction::MapKeys => Self::MapKeys,
+            protobuf::ScalarFunction::MapValues => Self::MapValues,
+            protobuf::ScalarFunction::Md5 => Self::MD5,
+            protobuf::ScalarFunction::MonotonicId => Self::MonotonicId,
+            protobuf::ScalarFunction::NamedStruct => Self::NamedStruct,
+            protobuf::ScalarFunction::NullIf => Self::NullIf,
+            protobuf::ScalarFunction::Nvl => Self::Nvl,
+            protobuf::ScalarFunction::Nvl2 => Self::Nvl2,
+            protobuf::ScalarFunction::OctetLength => Self::OctetLength,
+            protobuf::ScalarFunction::Overlay => Self::Overlay,
+            protobuf::ScalarFunction::Random => Self::Random,
+            protobuf::ScalarFunction::RegexpReplace => Self::RegexpReplace,
+            protobuf::ScalarFunction::Repeat => Self::Repeat,
+            protobuf::ScalarFunction::Replace => Self::Replace,
+            protobuf::ScalarFunction::Reverse => Self::Reverse,
+            protobuf::ScalarFunction::Right => Self::Right,
+            protobuf::ScalarFunction::Rpad => Self::Rpad,
+            protobuf::ScalarFunction::Rtrim => Self::Rtrim,
+            protobuf::ScalarFunction::Sha224 => Self::SHA224,
+            protobuf::ScalarFunction::Sha256 => Self::SHA256,
+            protobuf::ScalarFunction::Sha384 => Self::SHA384,
+            protobuf::ScalarFunction::Sha512 => Self::SHA512,
+            protobuf::ScalarFunction::Soundex => Self::Soundex,
+            protobuf::ScalarFunction::SplitPart => Self::SplitPart,
+            protobuf::ScalarFunction::StartsWith => Self::StartsWith,
+            protobuf::ScalarFunction::Strpos => Self::Strpos,
+            protobuf::ScalarFunction::Struct => Self::Struct,
+            protobuf::ScalarFunction::Substr => Self::Substr,
+            protobuf::ScalarFunction::ToChar => Self::ToChar,
+            protobuf::ScalarFunction::ToHex => Self::ToHex,
+            protobuf::ScalarFunction::ToTimestamp => Self::ToTimestamp,
+            protobuf::ScalarFunction::Now => Self::Now,
+            protobuf::ScalarFunction::Translate => Self::Translate,
+            protobuf::ScalarFunction::Trim => Self::Trim,
+            protobuf::ScalarFunction::Upper => Self::Upper,
+            protobuf::ScalarFunction::Uuid => Self::Uuid,
+            protobuf::ScalarFunction::RegexpMatch => Self::RegexpMatch,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serialization of logical plans and expressions
+
+pub mod from_proto;
+pub mod to_proto;
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{ArrayRef, Float64Array, Int32Array};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::datasource::MemTable;
+    use datafusion::error::Result;
+    use datafusion::logical_plan::{
+        binary_expr, case, count_distinct, create_udaf, sqrt, when, FunctionRegistry,
+        Operator,
+    };
+    use datafusion::physical_plan::expressions::AvgAccumulator;
+    use datafusion::physical_plan::functions::make_scalar_function;
+    use datafusion::prelude::*;
+    use datafusion::scalar::ScalarValue;
+
+    use crate::{
+        expr_from_bytes, expr_to_bytes, logical_plan_from_bytes, logical_plan_to_bytes,
+    };
+
+    // Given a LogicalPlan, converts it to protobuf and back, using debug
+    // formatting to test equality
+    macro_rules! roundtrip_test {
+        ($plan:expr, $ctx:expr) => {
+            let plan = $plan;
+            let bytes = logical_plan_to_bytes(&plan)?;
+            let round_trip = logical_plan_from_bytes(&bytes, &$ctx)?;
+            assert_eq!(format!("{:?}", plan), format!("{:?}", round_trip));
+        };
+    }
+
+    fn context() -> Result<ExecutionContext> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(Float64Array::from(vec![Some(1.5), None, Some(3.0)])),
+            ],
+        )?;
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))?;
+
+        let double = make_scalar_function(|args: &[ArrayRef]| Ok(args[0].clone()));
+        ctx.register_udf(create_udf(
+            "double",
+            vec![DataType::Float64],
+            Arc::new(DataType::Float64),
+            double,
+        ));
+        ctx.register_udaf(create_udaf(
+            "my_avg",
+            DataType::Float64,
+            Arc::new(DataType::Float64),
+            Arc::new(|| Ok(Box::new(AvgAccumulator::try_new(&DataType::Float64)?))),
+            Arc::new(vec![DataType::UInt64, DataType::Float64]),
+        ));
+        Ok(ctx)
+    }
+
+    #[test]
+    fn roundtrip_expressions() -> Result<()> {
+        let ctx = context()?;
+        let exprs = vec![
+            col("a").eq(lit(1)).and(col("b").lt_eq(lit(2.5))),
+            col("a").alias("x"),
+            col("a").is_null().or(col("b").is_not_null()),
+            Expr::Not(Box::new(col("c").like(lit("%a_")))),
+            Expr::Negative(Box::new(col("a"))),
+            Expr::Between {
+                expr: Box::new(col("a")),
+                negated: false,
+                low: Box::new(lit(1)),
+                high: Box::new(lit(10)),
+            },
+            col("a").in_list(vec![lit(1), lit(2)], true),
+            when(col("a").gt(lit(1)), lit("big")).otherwise(lit("small"))?,
+            case(col("a")).when(lit(1), lit(true)).end()?,
+            Expr::Cast {
+                expr: Box::new(col("a")),
+                data_type: DataType::Utf8,
+            },
+            Expr::TryCast {
+                expr: Box::new(col("a")),
+                data_type: DataType::Timestamp(
+                    datafusion::arrow::datatypes::TimeUnit::Millisecond,
+                    Some("UTC".to_owned()),
+                ),
+            },
+            col("a").sort(false, true),
+            sqrt(col("b")),
+            concat(&[col("c"), lit("x")]),
+            count_distinct(col("a")),
+            sum(binary_expr(col("a"), Operator::Modulus, lit(2))),
+            lit(ScalarValue::Utf8(None)),
+            lit(ScalarValue::List(
+                Some(vec![ScalarValue::Int64(Some(1)), ScalarValue::Int64(None)]),
+                DataType::Int64,
+            )),
+            lit(ScalarValue::Struct(
+                Some(vec![ScalarValue::Boolean(Some(true))]),
+                vec![Field::new("f", DataType::Boolean, true)],
+            )),
+            lit(ScalarValue::Binary(Some(vec![1, 2, 3]))),
+            Expr::ScalarVariable(vec!["@name".to_owned()]),
+            Expr::Wildcard,
+        ];
+
+        for expr in exprs {
+            let bytes = expr_to_bytes(&expr)?;
+            let round_trip = expr_from_bytes(&bytes, &ctx)?;
+            assert_eq!(format!("{:?}", expr), format!("{:?}", round_trip));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_user_defined_functions() -> Result<()> {
+        let ctx = context()?;
+        let udf = ctx.udf("double")?;
+        let udaf = ctx.udaf("my_avg")?;
+        let exprs = vec![udf.call(vec![col("b")]), udaf.call(vec![col("b")])];
+
+        for expr in exprs {
+            let bytes = expr_to_bytes(&expr)?;
+            let round_trip = expr_from_bytes(&bytes, &ctx)?;
+            assert_eq!(format!("{:?}", expr), format!("{:?}", round_trip));
+        }
+
+        // user defined functions must be registered to be deserialized
+        let bytes = expr_to_bytes(&udf.call(vec![col("b")]))?;
+        assert!(expr_from_bytes(&bytes, &ExecutionContext::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_plans() -> Result<()> {
+        let mut ctx = context()?;
+        let sqls = vec![
+            "SELECT a, b FROM t WHERE a > 1 ORDER BY b DESC LIMIT 2",
+            "SELECT a, count(*), my_avg(b) FROM t GROUP BY a",
+            "SELECT double(b) AS b FROM t UNION ALL SELECT b FROM t",
+        ];
+
+        for sql in sqls {
+            let plan = ctx.sql(sql)?.to_logical_plan();
+            roundtrip_test!(plan.clone(), ctx);
+            roundtrip_test!(ctx.optimize(&plan)?, ctx);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_joins() -> Result<()> {
+        let ctx = context()?;
+        let left = ctx.table("t")?.to_logical_plan();
+        let right = LogicalPlanBuilder::from(&left)
+            .project(vec![col("a").alias("c"), col("b").alias("d")])?
+            .build()?;
+
+        for join_type in vec![JoinType::Inner, JoinType::Left, JoinType::Full] {
+            let plan = LogicalPlanBuilder::from(&left)
+                .join(&right, join_type, &["a"], &["c"])?
+                .build()?;
+            roundtrip_test!(plan, ctx);
+        }
+        let plan = LogicalPlanBuilder::from(&left)
+            .cross_join(&right)?
+            .build()?;
+        roundtrip_test!(plan, ctx);
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_repartition() -> Result<()> {
+        let ctx = context()?;
+        let input = ctx.table("t")?.to_logical_plan();
+        for partitioning_scheme in vec![
+            Partitioning::RoundRobinBatch(4),
+            Partitioning::Hash(vec![col("a"), col("b") + lit(1.0)], 8),
+        ] {
+            let plan = LogicalPlanBuilder::from(&input)
+                .repartition(partitioning_scheme)?
+                .build()?;
+            roundtrip_test!(plan, ctx);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_csv_scan() -> Result<()> {
+        let tmp_dir = tempfile::TempDir::new()?;
+        let path = tmp_dir.path().join("example.csv");
+        std::fs::write(&path, "a;b\n1;x\n2;y\n")?;
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, false),
+        ]);
+        let plan = LogicalPlanBuilder::scan_csv(
+            path.to_str().unwrap(),
+            CsvReadOptions::new()
+                .schema(&schema)
+                .delimiter(b';')
+                .has_header(true),
+            Some(vec![1]),
+        )?
+        .build()?;
+
+        // the scan does not need to be registered with the context
+        roundtrip_test!(plan, ExecutionContext::new());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_table() -> Result<()> {
+        let ctx = context()?;
+        let plan = ctx.table("t")?.to_logical_plan();
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let err = logical_plan_from_bytes(&bytes, &ExecutionContext::new())
+            .expect_err("table is not registered");
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Table 't' not found"
+        );
+        Ok(())
+    }
+}
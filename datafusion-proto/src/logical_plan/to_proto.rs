@@ -0,0 +1,710 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serde code to convert Arrow schemas and DataFusion logical plans to the
+//! protocol buffer format
+
+use std::convert::{TryFrom, TryInto};
+
+use datafusion::arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use datafusion::datasource::parquet::ParquetTable;
+use datafusion::datasource::CsvFile;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_plan::{Expr, JoinType, LogicalPlan, Partitioning};
+use datafusion::physical_plan::aggregates::AggregateFunction;
+use datafusion::physical_plan::functions::BuiltinScalarFunction;
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::parser::FileType;
+
+use crate::protobuf;
+use protobuf::arrow_type::ArrowTypeEnum;
+use protobuf::logical_expr_node::ExprType;
+use protobuf::logical_plan_node::LogicalPlanType;
+use protobuf::scalar_value::Value;
+
+impl From<&TimeUnit> for protobuf::TimeUnit {
+    fn from(val: &TimeUnit) -> Self {
+        match val {
+            TimeUnit::Second => protobuf::TimeUnit::Second,
+            TimeUnit::Millisecond => protobuf::TimeUnit::TimeMillisecond,
+            TimeUnit::Microsecond => protobuf::TimeUnit::Microsecond,
+            TimeUnit::Nanosecond => protobuf::TimeUnit::Nanosecond,
+        }
+    }
+}
+
+impl From<&IntervalUnit> for protobuf::IntervalUnit {
+    fn from(interval_unit: &IntervalUnit) -> Self {
+        match interval_unit {
+            IntervalUnit::YearMonth => protobuf::IntervalUnit::YearMonth,
+            IntervalUnit::DayTime => protobuf::IntervalUnit::DayTime,
+        }
+    }
+}
+
+impl From<&Field> for protobuf::Field {
+    fn from(field: &Field) -> Self {
+        protobuf::Field {
+            name: field.name().to_owned(),
+            arrow_type: Some(Box::new(field.data_type().into())),
+            nullable: field.is_nullable(),
+        }
+    }
+}
+
+impl From<&Schema> for protobuf::Schema {
+    fn from(schema: &Schema) -> Self {
+        protobuf::Schema {
+            columns: schema.fields().iter().map(protobuf::Field::from).collect(),
+        }
+    }
+}
+
+impl From<&DataType> for protobuf::ArrowType {
+    fn from(val: &DataType) -> protobuf::ArrowType {
+        protobuf::ArrowType {
+            arrow_type_enum: Some(val.into()),
+        }
+    }
+}
+
+impl From<&DataType> for ArrowTypeEnum {
+    fn from(val: &DataType) -> ArrowTypeEnum {
+        use protobuf::EmptyMessage;
+        match val {
+            DataType::Null => ArrowTypeEnum::None(EmptyMessage {}),
+            DataType::Boolean => ArrowTypeEnum::Bool(EmptyMessage {}),
+            DataType::Int8 => ArrowTypeEnum::Int8(EmptyMessage {}),
+            DataType::Int16 => ArrowTypeEnum::Int16(EmptyMessage {}),
+            DataType::Int32 => ArrowTypeEnum::Int32(EmptyMessage {}),
+            DataType::Int64 => ArrowTypeEnum::Int64(EmptyMessage {}),
+            DataType::UInt8 => ArrowTypeEnum::Uint8(EmptyMessage {}),
+            DataType::UInt16 => ArrowTypeEnum::Uint16(EmptyMessage {}),
+            DataType::UInt32 => ArrowTypeEnum::Uint32(EmptyMessage {}),
+            DataType::UInt64 => ArrowTypeEnum::Uint64(EmptyMessage {}),
+            DataType::Float16 => ArrowTypeEnum::Float16(EmptyMessage {}),
+            DataType::Float32 => ArrowTypeEnum::Float32(EmptyMessage {}),
+            DataType::Float64 => ArrowTypeEnum::Float64(EmptyMessage {}),
+            DataType::Timestamp(time_unit, timezone) => {
+                ArrowTypeEnum::Timestamp(protobuf::Timestamp {
+                    time_unit: protobuf::TimeUnit::from(time_unit) as i32,
+                    timezone: timezone.to_owned().unwrap_or_else(String::new),
+                })
+            }
+            DataType::Date32 => ArrowTypeEnum::Date32(EmptyMessage {}),
+            DataType::Date64 => ArrowTypeEnum::Date64(EmptyMessage {}),
+            DataType::Time32(time_unit) => {
+                ArrowTypeEnum::Time32(protobuf::TimeUnit::from(time_unit) as i32)
+            }
+            DataType::Time64(time_unit) => {
+                ArrowTypeEnum::Time64(protobuf::TimeUnit::from(time_unit) as i32)
+            }
+            DataType::Duration(time_unit) => {
+                ArrowTypeEnum::Duration(protobuf::TimeUnit::from(time_unit) as i32)
+            }
+            DataType::Interval(interval_unit) => {
+                ArrowTypeEnum::Interval(protobuf::IntervalUnit::from(interval_unit) as i32)
+            }
+            DataType::Binary => ArrowTypeEnum::Binary(EmptyMessage {}),
+            DataType::FixedSizeBinary(size) => ArrowTypeEnum::FixedSizeBinary(*size),
+            DataType::LargeBinary => ArrowTypeEnum::LargeBinary(EmptyMessage {}),
+            DataType::Utf8 => ArrowTypeEnum::Utf8(EmptyMessage {}),
+            DataType::LargeUtf8 => ArrowTypeEnum::LargeUtf8(EmptyMessage {}),
+            DataType::List(item_type) => ArrowTypeEnum::List(Box::new(protobuf::List {
+                field_type: Some(Box::new(item_type.as_ref().into())),
+            })),
+            DataType::FixedSizeList(item_type, size) => {
+                ArrowTypeEnum::FixedSizeList(Box::new(protobuf::FixedSizeList {
+                    field_type: Some(Box::new(item_type.as_ref().into())),
+                    list_size: *size,
+                }))
+            }
+            DataType::LargeList(item_type) => {
+                ArrowTypeEnum::LargeList(Box::new(protobuf::List {
+                    field_type: Some(Box::new(item_type.as_ref().into())),
+                }))
+            }
+            DataType::Struct(struct_fields) => ArrowTypeEnum::Struct(protobuf::Struct {
+                sub_field_types: struct_fields.iter().map(|field| field.into()).collect(),
+            }),
+            DataType::Union(union_types) => ArrowTypeEnum::Union(protobuf::Union {
+                union_types: union_types.iter().map(|field| field.into()).collect(),
+            }),
+            DataType::Dictionary(key_type, value_type) => {
+                ArrowTypeEnum::Dictionary(Box::new(protobuf::Dictionary {
+                    key: Some(Box::new(key_type.as_ref().into())),
+                    value: Some(Box::new(value_type.as_ref().into())),
+                }))
+            }
+            DataType::Decimal(whole, fractional) => {
+                ArrowTypeEnum::Decimal(protobuf::Decimal {
+                    whole: *whole as u64,
+                    fractional: *fractional as u64,
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<&ScalarValue> for protobuf::ScalarValue {
+    type Error = DataFusionError;
+
+    fn try_from(val: &ScalarValue) -> Result<Self> {
+        let value = match val {
+            ScalarValue::Boolean(Some(v)) => Value::BoolValue(*v),
+            ScalarValue::Float32(Some(v)) => Value::Float32Value(*v),
+            ScalarValue::Float64(Some(v)) => Value::Float64Value(*v),
+            ScalarValue::Int8(Some(v)) => Value::Int8Value(*v as i32),
+            ScalarValue::Int16(Some(v)) => Value::Int16Value(*v as i32),
+            ScalarValue::Int32(Some(v)) => Value::Int32Value(*v),
+            ScalarValue::Int64(Some(v)) => Value::Int64Value(*v),
+            ScalarValue::UInt8(Some(v)) => Value::Uint8Value(*v as u32),
+            ScalarValue::UInt16(Some(v)) => Value::Uint16Value(*v as u32),
+            ScalarValue::UInt32(Some(v)) => Value::Uint32Value(*v),
+            ScalarValue::UInt64(Some(v)) => Value::Uint64Value(*v),
+            ScalarValue::Utf8(Some(v)) => Value::Utf8Value(v.clone()),
+            ScalarValue::LargeUtf8(Some(v)) => Value::LargeUtf8Value(v.clone()),
+            ScalarValue::Binary(Some(v)) => Value::BinaryValue(v.clone()),
+            ScalarValue::LargeBinary(Some(v)) => Value::LargeBinaryValue(v.clone()),
+            ScalarValue::Date32(Some(v)) => Value::Date32Value(*v),
+            ScalarValue::Date64(Some(v)) => Value::Date64Value(*v),
+            ScalarValue::TimestampSecond(Some(v)) => Value::TimestampSecondValue(*v),
+            ScalarValue::TimestampMillisecond(Some(v)) => {
+                Value::TimestampMillisecondValue(*v)
+            }
+            ScalarValue::TimestampMicrosecond(Some(v)) => {
+                Value::TimestampMicrosecondValue(*v)
+            }
+            ScalarValue::TimestampNanosecond(Some(v)) => {
+                Value::TimestampNanosecondValue(*v)
+            }
+            ScalarValue::IntervalYearMonth(Some(v)) => Value::IntervalYearmonthValue(*v),
+            ScalarValue::IntervalDayTime(Some(v)) => Value::IntervalDaytimeValue(*v),
+            ScalarValue::List(Some(values), data_type) => {
+                Value::ListValue(protobuf::ScalarListValue {
+                    datatype: Some(data_type.into()),
+                    values: values
+                        .iter()
+                        .map(|value| value.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                })
+            }
+            ScalarValue::Struct(Some(values), fields) => {
+                Value::StructValue(protobuf::ScalarStructValue {
+                    fields: fields.iter().map(|field| field.into()).collect(),
+                    values: values
+                        .iter()
+                        .map(|value| value.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                })
+            }
+            // all variants with a value are handled above
+            _ => Value::NullValue((&val.get_datatype()).into()),
+        };
+        Ok(protobuf::ScalarValue { value: Some(value) })
+    }
+}
+
+impl TryFrom<&Expr> for protobuf::LogicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(expr: &Expr) -> Result<Self> {
+        let expr_type = match expr {
+            Expr::Column(name) => ExprType::ColumnName(name.clone()),
+            Expr::Alias(expr, alias) => ExprType::Alias(Box::new(protobuf::AliasNode {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+                alias: alias.clone(),
+            })),
+            Expr::ScalarVariable(names) => {
+                ExprType::ScalarVariable(protobuf::ScalarVariableNode {
+                    names: names.clone(),
+                })
+            }
+            Expr::Literal(value) => ExprType::Literal(value.try_into()?),
+            Expr::BinaryExpr { left, op, right } => {
+                ExprType::BinaryExpr(Box::new(protobuf::BinaryExprNode {
+                    l: Some(Box::new(left.as_ref().try_into()?)),
+                    r: Some(Box::new(right.as_ref().try_into()?)),
+                    op: format!("{:?}", op),
+                }))
+            }
+            Expr::Not(expr) => ExprType::NotExpr(Box::new(protobuf::Not {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+            })),
+            Expr::IsNull(expr) => ExprType::IsNullExpr(Box::new(protobuf::IsNull {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+            })),
+            Expr::IsNotNull(expr) => {
+                ExprType::IsNotNullExpr(Box::new(protobuf::IsNotNull {
+                    expr: Some(Box::new(expr.as_ref().try_into()?)),
+                }))
+            }
+            Expr::Negative(expr) => {
+                ExprType::Negative(Box::new(protobuf::NegativeNode {
+                    expr: Some(Box::new(expr.as_ref().try_into()?)),
+                }))
+            }
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => ExprType::Between(Box::new(protobuf::BetweenNode {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+                negated: *negated,
+                low: Some(Box::new(low.as_ref().try_into()?)),
+                high: Some(Box::new(high.as_ref().try_into()?)),
+            })),
+            Expr::Case {
+                expr,
+                when_then_expr,
+                else_expr,
+            } => ExprType::Case(Box::new(protobuf::CaseNode {
+                expr: expr
+                    .as_ref()
+                    .map(|e| e.as_ref().try_into().map(Box::new))
+                    .transpose()?,
+                when_then_expr: when_then_expr
+                    .iter()
+                    .map(|(when_expr, then_expr)| {
+                        Ok(protobuf::WhenThen {
+                            when_expr: Some(when_expr.as_ref().try_into()?),
+                            then_expr: Some(then_expr.as_ref().try_into()?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                else_expr: else_expr
+                    .as_ref()
+                    .map(|e| e.as_ref().try_into().map(Box::new))
+                    .transpose()?,
+            })),
+            Expr::Cast { expr, data_type } => {
+                ExprType::Cast(Box::new(protobuf::CastNode {
+                    expr: Some(Box::new(expr.as_ref().try_into()?)),
+                    arrow_type: Some(data_type.into()),
+                }))
+            }
+            Expr::TryCast { expr, data_type } => {
+                ExprType::TryCast(Box::new(protobuf::TryCastNode {
+                    expr: Some(Box::new(expr.as_ref().try_into()?)),
+                    arrow_type: Some(data_type.into()),
+                }))
+            }
+            Expr::Sort {
+                expr,
+                asc,
+                nulls_first,
+            } => ExprType::Sort(Box::new(protobuf::SortExprNode {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+                asc: *asc,
+                nulls_first: *nulls_first,
+            })),
+            Expr::ScalarFunction { fun, args } => {
+                ExprType::ScalarFunction(protobuf::ScalarFunctionNode {
+                    fun: protobuf::ScalarFunction::from(fun).into(),
+                    args: exprs_to_proto(args)?,
+                })
+            }
+            Expr::ScalarUDF { fun, args } => {
+                ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
+                    fun_name: fun.name.clone(),
+                    args: exprs_to_proto(args)?,
+                })
+            }
+            Expr::AggregateFunction {
+                fun,
+                args,
+                distinct,
+            } => ExprType::AggregateExpr(protobuf::AggregateExprNode {
+                aggr_function: protobuf::AggregateFunction::from(fun).into(),
+                args: exprs_to_proto(args)?,
+                distinct: *distinct,
+            }),
+            Expr::AggregateUDF { fun, args } => {
+                ExprType::AggregateUdfExpr(protobuf::AggregateUdfExprNode {
+                    fun_name: fun.name.clone(),
+                    args: exprs_to_proto(args)?,
+                })
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => ExprType::InList(Box::new(protobuf::InListNode {
+                expr: Some(Box::new(expr.as_ref().try_into()?)),
+                list: exprs_to_proto(list)?,
+                negated: *negated,
+            })),
+            Expr::Wildcard => ExprType::Wildcard(true),
+            Expr::WindowFunction { .. } => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Serializing window functions to protobuf is not supported: {:?}",
+                    expr
+                )))
+            }
+        };
+        Ok(protobuf::LogicalExprNode {
+            expr_type: Some(expr_type),
+        })
+    }
+}
+
+fn exprs_to_proto(exprs: &[Expr]) -> Result<Vec<protobuf::LogicalExprNode>> {
+    exprs.iter().map(|expr| expr.try_into()).collect()
+}
+
+impl TryFrom<&LogicalPlan> for protobuf::LogicalPlanNode {
+    type Error = DataFusionError;
+
+    fn try_from(plan: &LogicalPlan) -> Result<Self> {
+        let logical_plan_type = match plan {
+            LogicalPlan::TableScan {
+                table_name,
+                source,
+                projection,
+                filters,
+                limit,
+                ..
+            } => {
+                let schema = source.schema();
+                let projection =
+                    projection
+                        .as_ref()
+                        .map(|columns| protobuf::ProjectionColumns {
+                            columns: columns
+                                .iter()
+                                .map(|i| schema.field(*i).name().to_owned())
+                                .collect(),
+                        });
+
+                let source = if let Some(csv) = source.as_any().downcast_ref::<CsvFile>()
+                {
+                    if csv.path().is_empty() {
+                        return Err(DataFusionError::NotImplemented(
+                            "Serializing scans of CSV readers to protobuf is not supported"
+                                .to_owned(),
+                        ));
+                    }
+                    let delimiter = [csv.delimiter()];
+                    let delimiter = std::str::from_utf8(&delimiter).map_err(|_| {
+                        DataFusionError::Plan("Invalid CSV delimiter".to_owned())
+                    })?;
+                    Some(protobuf::table_scan_node::Source::Csv(
+                        protobuf::CsvSource {
+                            path: csv.path().to_owned(),
+                            has_header: csv.has_header(),
+                            delimiter: delimiter.to_owned(),
+                            file_extension: csv.file_extension().to_owned(),
+                        },
+                    ))
+                } else if let Some(parquet) =
+                    source.as_any().downcast_ref::<ParquetTable>()
+                {
+                    Some(protobuf::table_scan_node::Source::Parquet(
+                        protobuf::ParquetSource {
+                            path: parquet.path().to_owned(),
+                            max_concurrency: parquet.max_concurrency() as u32,
+                            enable_pruning: parquet.enable_pruning(),
+                        },
+                    ))
+                } else if table_name.is_empty() {
+                    return Err(DataFusionError::NotImplemented(
+                        "Serializing scans of unnamed tables to protobuf is only supported for CSV and Parquet files".to_owned(),
+                    ));
+                } else {
+                    None
+                };
+
+                LogicalPlanType::TableScan(protobuf::TableScanNode {
+                    table_name: table_name.clone(),
+                    projection,
+                    schema: Some(schema.as_ref().into()),
+                    filters: exprs_to_proto(filters)?,
+                    optional_limit: limit.map(|limit| {
+                        protobuf::table_scan_node::OptionalLimit::Limit(limit as u64)
+                    }),
+                    source,
+                })
+            }
+            LogicalPlan::Projection { expr, input, .. } => {
+                LogicalPlanType::Projection(Box::new(protobuf::ProjectionNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    expr: exprs_to_proto(expr)?,
+                }))
+            }
+            LogicalPlan::Filter { predicate, input } => {
+                LogicalPlanType::Selection(Box::new(protobuf::SelectionNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    expr: Some(predicate.try_into()?),
+                }))
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+                ..
+            } => LogicalPlanType::Aggregate(Box::new(protobuf::AggregateNode {
+                input: Some(Box::new(input.as_ref().try_into()?)),
+                group_expr: exprs_to_proto(group_expr)?,
+                aggr_expr: exprs_to_proto(aggr_expr)?,
+            })),
+            LogicalPlan::Join {
+                left,
+                right,
+                on,
+                join_type,
+                ..
+            } => {
+                let (left_join_column, right_join_column) = on.iter().cloned().unzip();
+                LogicalPlanType::Join(Box::new(protobuf::JoinNode {
+                    left: Some(Box::new(left.as_ref().try_into()?)),
+                    right: Some(Box::new(right.as_ref().try_into()?)),
+                    join_type: protobuf::JoinType::from(join_type).into(),
+                    left_join_column,
+                    right_join_column,
+                }))
+            }
+            LogicalPlan::CrossJoin { left, right, .. } => {
+                LogicalPlanType::CrossJoin(Box::new(protobuf::CrossJoinNode {
+                    left: Some(Box::new(left.as_ref().try_into()?)),
+                    right: Some(Box::new(right.as_ref().try_into()?)),
+                }))
+            }
+            LogicalPlan::Limit { input, n } => {
+                LogicalPlanType::Limit(Box::new(protobuf::LimitNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    limit: *n as u64,
+                }))
+            }
+            LogicalPlan::Sort { input, expr } => {
+                LogicalPlanType::Sort(Box::new(protobuf::SortNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    expr: exprs_to_proto(expr)?,
+                }))
+            }
+            LogicalPlan::Repartition {
+                input,
+                partitioning_scheme,
+            } => {
+                use protobuf::repartition_node::PartitionMethod;
+
+                let partition_method = match partitioning_scheme {
+                    Partitioning::Hash(exprs, partition_count) => {
+                        PartitionMethod::Hash(protobuf::HashRepartition {
+                            hash_expr: exprs_to_proto(exprs)?,
+                            partition_count: *partition_count as u64,
+                        })
+                    }
+                    Partitioning::RoundRobinBatch(partition_count) => {
+                        PartitionMethod::RoundRobin(*partition_count as u64)
+                    }
+                };
+                LogicalPlanType::Repartition(Box::new(protobuf::RepartitionNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    partition_method: Some(partition_method),
+                }))
+            }
+            LogicalPlan::Union { inputs, alias, .. } => {
+                LogicalPlanType::Union(protobuf::UnionNode {
+                    inputs: inputs
+                        .iter()
+                        .map(|input| input.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    alias: alias.clone().unwrap_or_default(),
+                })
+            }
+            LogicalPlan::EmptyRelation {
+                produce_one_row, ..
+            } => LogicalPlanType::EmptyRelation(protobuf::EmptyRelationNode {
+                produce_one_row: *produce_one_row,
+            }),
+            LogicalPlan::Unnest { input, column, .. } => {
+                LogicalPlanType::Unnest(Box::new(protobuf::UnnestNode {
+                    input: Some(Box::new(input.as_ref().try_into()?)),
+                    column: column.clone(),
+                }))
+            }
+            LogicalPlan::CreateExternalTable {
+                name,
+                location,
+                file_type,
+                has_header,
+                schema,
+            } => {
+                let schema: Schema = schema.as_ref().to_owned().into();
+                let file_type = match file_type {
+                    FileType::NdJson => protobuf::FileType::NdJson,
+                    FileType::Parquet => protobuf::FileType::Parquet,
+                    FileType::CSV => protobuf::FileType::Csv,
+                };
+                LogicalPlanType::CreateExternalTable(protobuf::CreateExternalTableNode {
+                    name: name.clone(),
+                    location: location.clone(),
+                    file_type: file_type.into(),
+                    has_header: *has_header,
+                    schema: Some((&schema).into()),
+                })
+            }
+            LogicalPlan::Window { .. }
+            | LogicalPlan::SetVariable { .. }
+            | LogicalPlan::Explain { .. }
+            | LogicalPlan::Analyze { .. }
+            | LogicalPlan::Values { .. }
+            | LogicalPlan::Sample { .. }
+            | LogicalPlan::Deduplicate { .. }
+            | LogicalPlan::Extension { .. } => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Serializing this logical plan to protobuf is not supported: {:?}",
+                    plan
+                )))
+            }
+        };
+        Ok(protobuf::LogicalPlanNode {
+            logical_plan_type: Some(logical_plan_type),
+        })
+    }
+}
+
+impl From<&JoinType> for protobuf::JoinType {
+    fn from(join_type: &JoinType) -> Self {
+        match join_type {
+            JoinType::Inner => protobuf::JoinType::InnerJoin,
+            JoinType::Left => protobuf::JoinType::LeftJoin,
+            JoinType::Right => protobuf::JoinType::RightJoin,
+            JoinType::Full => protobuf::JoinType::FullJoin,
+            JoinType::Semi => protobuf::JoinType::SemiJoin,
+            JoinType::Anti => protobuf::JoinType::AntiJoin,
+        }
+    }
+}
+
+impl From<&AggregateFunction> for protobuf::AggregateFunction {
+    fn from(value: &AggregateFunction) -> Self {
+        match value {
+            AggregateFunction::Min => Self::Min,
+            AggregateFunction::Max => Self::Max,
+            AggregateFunction::Sum => Self::Sum,
+            AggregateFunction::Avg => Self::Avg,
+            AggregateFunction::Count => Self::Count,
+        }
+    }
+}
+
+impl From<&BuiltinScalarFunction> for protobuf::ScalarFunction {
+    fn from(fun: &BuiltinScalarFunction) -> Self {
+        match fun {
+            BuiltinScalarFunction::Abs => Self::Abs,
+            BuiltinScalarFunction::Acos => Self::Acos,
+            BuiltinScalarFunction::Asin => Self::Asin,
+            BuiltinScalarFunction::Atan => Self::Atan,
+            BuiltinScalarFunction::Atan2 => Self::Atan2,
+            BuiltinScalarFunction::Cbrt => Self::Cbrt,
+            BuiltinScalarFunction::Ceil => Self::Ceil,
+            BuiltinScalarFunction::Cos => Self::Cos,
+            BuiltinScalarFunction::Degrees => Self::Degrees,
+            BuiltinScalarFunction::Exp => Self::Exp,
+            BuiltinScalarFunction::Factorial => Self::Factorial,
+            BuiltinScalarFunction::Floor => Self::Floor,
+            BuiltinScalarFunction::Gcd => Self::Gcd,
+            BuiltinScalarFunction::Lcm => Self::Lcm,
+            BuiltinScalarFunction::Ln => Self::Ln,
+            BuiltinScalarFunction::Log => Self::Log,
+            BuiltinScalarFunction::Log10 => Self::Log10,
+            BuiltinScalarFunction::Log2 => Self::Log2,
+            BuiltinScalarFunction::Pi => Self::Pi,
+            BuiltinScalarFunction::Power => Self::Power,
+            BuiltinScalarFunction::Radians => Self::Radians,
+            BuiltinScalarFunction::Round => Self::Round,
+            BuiltinScalarFunction::Signum => Self::Signum,
+            BuiltinScalarFunction::Sin => Self::Sin,
+            BuiltinScalarFunction::Sqrt => Self::Sqrt,
+            BuiltinScalarFunction::Tan => Self::Tan,
+            BuiltinScalarFunction::Trunc => Self::Trunc,
+            BuiltinScalarFunction::Array => Self::Array,
+            BuiltinScalarFunction::ArrayAppend => Self::ArrayAppend,
+            BuiltinScalarFunction::ArrayConcat => Self::ArrayConcat,
+            BuiltinScalarFunction::ArrayContains => Self::ArrayContains,
+            BuiltinScalarFunction::ArrayLength => Self::ArrayLength,
+            BuiltinScalarFunction::ArrayPosition => Self::ArrayPosition,
+            BuiltinScalarFunction::ArraySlice => Self::ArraySlice,
+            BuiltinScalarFunction::ArrowTypeof => Self::ArrowTypeof,
+            BuiltinScalarFunction::Ascii => Self::Ascii,
+            BuiltinScalarFunction::BitLength => Self::BitLength,
+            BuiltinScalarFunction::Btrim => Self::Btrim,
+            BuiltinScalarFunction::CharacterLength => Self::CharacterLength,
+            BuiltinScalarFunction::Chr => Self::Chr,
+            BuiltinScalarFunction::Coalesce => Self::Coalesce,
+            BuiltinScalarFunction::Concat => Self::Concat,
+            BuiltinScalarFunction::ConcatWithSeparator => Self::ConcatWithSeparator,
+            BuiltinScalarFunction::DateBin => Self::DateBin,
+            BuiltinScalarFunction::DatePart => Self::DatePart,
+            BuiltinScalarFunction::DateTrunc => Self::DateTrunc,
+            BuiltinScalarFunction::Decode => Self::Decode,
+            BuiltinScalarFunction::ElementAt => Self::ElementAt,
+            BuiltinScalarFunction::Encode => Self::Encode,
+            BuiltinScalarFunction::FuzzyMatch => Self::FuzzyMatch,
+            BuiltinScalarFunction::InitCap => Self::InitCap,
+            BuiltinScalarFunction::JsonGet => Self::JsonGet,
+            BuiltinScalarFunction::JsonGetBool => Self::JsonGetBool,
+            BuiltinScalarFunction::JsonGetFloat => Self::JsonGetFloat,
+            BuiltinScalarFunction::JsonGetInt => Self::JsonGetInt,
+            BuiltinScalarFunction::JsonGetStr => Self::JsonGetStr,
+            BuiltinScalarFunction::Left => Self::Left,
+            BuiltinScalarFunction::Levenshtein => Self::Levenshtein,
+            BuiltinScalarFunction::Lpad => Self::Lpad,
+            BuiltinScalarFunction::Lower => Self::Lower,
+            BuiltinScalarFunction::Ltrim => Self::Ltrim,
+            BuiltinScalarFunction::MakeArray => Self::MakeArray,
+            BuiltinScalarFunction::MapKeys => Self::MapKeys,
+            BuiltinScalarFunction::MapValues => Self::MapValues,
+            BuiltinScalarFunction::MD5 => Self::Md5,
+            BuiltinScalarFunction::MonotonicId => Self::MonotonicId,
+            BuiltinScalarFunction::NamedStruct => Self::NamedStruct,
+            BuiltinScalarFunction::NullIf => Self::NullIf,
+            BuiltinScalarFunction::Nvl => Self::Nvl,
+            BuiltinScalarFunction::Nvl2 => Self::Nvl2,
+            BuiltinScalarFunction::OctetLength => Self::OctetLength,
+            BuiltinScalarFunction::Overlay => Self::Overlay,
+            BuiltinScalarFunction::Random => Self::Random,
+            BuiltinScalarFunction::RegexpReplace => Self::RegexpReplace,
+            BuiltinScalarFunction::Repeat => Self::Repeat,
+            BuiltinScalarFunction::Replace => Self::Replace,
+            BuiltinScalarFunction::Reverse => Self::Reverse,
+            BuiltinScalarFunction::Right => Self::Right,
+            BuiltinScalarFunction::Rpad => Self::Rpad,
+            BuiltinScalarFunction::Rtrim => Self::Rtrim,
+            BuiltinScalarFunction::SHA224 => Self::Sha224,
+            BuiltinScalarFunction::SHA256 => Self::Sha256,
+            BuiltinScalarFunction::SHA384 => Self::Sha384,
+            BuiltinScalarFunction::SHA512 => Self::Sha512,
+            BuiltinScalarFunction::Soundex => Self::Soundex,
+            BuiltinScalarFunction::SplitPart => Self::SplitPart,
+            BuiltinScalarFunction::StartsWith => Self::StartsWith,
+            BuiltinScalarFunction::Strpos => Self::Strpos,
+            BuiltinScalarFunction::Struct => Self::Struct,
+            BuiltinScalarFunction::Substr => Self::Substr,
+            BuiltinScalarFunction::ToChar => Self::ToChar,
+            BuiltinScalarFunction::ToHex => Self::ToHex,
+            BuiltinScalarFunction::ToTimestamp => Self::ToTimestamp,
+            BuiltinScalarFunction::Now => Self::Now,
+            BuiltinScalarFunction::Translate => Self::Translate,
+            BuiltinScalarFunction::Trim => Self::Trim,
+            BuiltinScalarFunction::Upper => Self::Upper,
+            BuiltinScalarFunction::Uuid => Self::Uuid,
+            BuiltinScalarFunction::RegexpMatch => Self::RegexpMatch,
+        }
+    }
+}
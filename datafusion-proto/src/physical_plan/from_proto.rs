@@ -0,0 +1,406 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serde code to convert from protocol buffers to DataFusion physical plans
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use datafusion::arrow::compute::SortOptions;
+use datafusion::arrow::datatypes::{Schema, SchemaRef};
+use datafusion::datasource::CsvReadOptions;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::ExecutionContextState;
+use datafusion::logical_plan::Expr;
+use datafusion::physical_plan::coalesce_batches::{BatchSizePolicy, CoalesceBatchesExec};
+use datafusion::physical_plan::cross_join::CrossJoinExec;
+use datafusion::physical_plan::csv::CsvExec;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::expressions::PhysicalSortExpr;
+use datafusion::physical_plan::filter::FilterExec;
+use datafusion::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
+use datafusion::physical_plan::hash_join::{HashJoinExec, PartitionMode};
+use datafusion::physical_plan::hash_utils::{HashSeeds, JoinType};
+use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use datafusion::physical_plan::merge::MergeExec;
+use datafusion::physical_plan::parquet::ParquetExec;
+use datafusion::physical_plan::placeholder_row::PlaceholderRowExec;
+use datafusion::physical_plan::planner::DefaultPhysicalPlanner;
+use datafusion::physical_plan::projection::ProjectionExec;
+use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::sort::SortExec;
+use datafusion::physical_plan::union::UnionExec;
+use datafusion::physical_plan::{
+    aggregates, udaf, AggregateExpr, ExecutionPlan, Partitioning, PhysicalExpr,
+};
+
+use crate::logical_plan::from_proto::parse_expr;
+use crate::proto_error;
+use crate::protobuf;
+use protobuf::logical_expr_node::ExprType;
+use protobuf::physical_plan_node::PhysicalPlanType;
+
+/// Converts a protobuf physical plan back into an [`ExecutionPlan`],
+/// planning its expressions with the configuration of `state` and looking
+/// up user defined functions there
+pub fn parse_physical_plan(
+    proto: &protobuf::PhysicalPlanNode,
+    state: &ExecutionContextState,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let plan = proto.physical_plan_type.as_ref().ok_or_else(|| {
+        proto_error("physical_plan::from_proto() Unsupported physical plan")
+    })?;
+    match plan {
+        PhysicalPlanType::Projection(projection) => {
+            let input = parse_required_plan(&projection.input, state)?;
+            let exprs = projection
+                .expr
+                .iter()
+                .zip(projection.expr_name.iter())
+                .map(|(expr, name)| {
+                    compile_expr(expr, &input.schema(), state)
+                        .map(|expr| (expr, name.clone()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+        }
+        PhysicalPlanType::Filter(filter) => {
+            let input = parse_required_plan(&filter.input, state)?;
+            let expr = filter.expr.as_ref().ok_or_else(|| {
+                proto_error("Missing required field 'expr' in FilterExecNode")
+            })?;
+            let predicate = compile_expr(expr, &input.schema(), state)?;
+            Ok(Arc::new(FilterExec::try_new(predicate, input)?))
+        }
+        PhysicalPlanType::CsvScan(scan) => {
+            let schema: Schema = convert_required!(scan.schema)?;
+            let delimiter = match scan.delimiter.as_bytes() {
+                [delimiter] => *delimiter,
+                _ => {
+                    return Err(proto_error(format!(
+                        "Invalid CSV delimiter '{}'",
+                        scan.delimiter
+                    )))
+                }
+            };
+            let options = CsvReadOptions::new()
+                .schema(&schema)
+                .has_header(scan.has_header)
+                .delimiter(delimiter)
+                .file_extension(&scan.file_extension);
+            let projection = scan
+                .optional_projection
+                .as_ref()
+                .map(|projection| match projection {
+                    protobuf::csv_scan_exec_node::OptionalProjection::Projection(
+                        projection,
+                    ) => projection
+                        .columns
+                        .iter()
+                        .map(|name| schema.index_of(name).map_err(DataFusionError::from))
+                        .collect::<Result<Vec<_>>>(),
+                })
+                .transpose()?;
+            let limit = scan.optional_limit.as_ref().map(|limit| match limit {
+                protobuf::csv_scan_exec_node::OptionalLimit::Limit(limit) => {
+                    *limit as usize
+                }
+            });
+            Ok(Arc::new(CsvExec::try_new(
+                &scan.path,
+                options,
+                projection,
+                scan.batch_size as usize,
+                limit,
+            )?))
+        }
+        PhysicalPlanType::ParquetScan(scan) => {
+            let projection = scan.projection.iter().map(|i| *i as usize).collect();
+            let filenames: Vec<&str> = scan.filename.iter().map(|s| s.as_str()).collect();
+            Ok(Arc::new(ParquetExec::try_from_files(
+                &filenames,
+                Some(projection),
+                None,
+                scan.batch_size as usize,
+                scan.num_partitions as usize,
+                None,
+            )?))
+        }
+        PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
+            let input = parse_required_plan(&coalesce_batches.input, state)?;
+            let policy = match coalesce_batches.target_bytes {
+                0 => BatchSizePolicy::Rows,
+                target_bytes => BatchSizePolicy::Adaptive {
+                    target_bytes: target_bytes as usize,
+                },
+            };
+            Ok(Arc::new(
+                CoalesceBatchesExec::new(
+                    input,
+                    coalesce_batches.target_batch_size as usize,
+                )
+                .with_policy(policy),
+            ))
+        }
+        PhysicalPlanType::Merge(merge) => {
+            let input = parse_required_plan(&merge.input, state)?;
+            Ok(Arc::new(
+                MergeExec::new(input).with_preserve_order(merge.preserve_order),
+            ))
+        }
+        PhysicalPlanType::Repartition(repart) => {
+            use protobuf::repartition_exec_node::PartitionMethod;
+
+            let input = parse_required_plan(&repart.input, state)?;
+            let partitioning = match repart.partition_method.as_ref() {
+                Some(PartitionMethod::Hash(hash)) => Partitioning::Hash(
+                    hash.hash_expr
+                        .iter()
+                        .map(|expr| compile_expr(expr, &input.schema(), state))
+                        .collect::<Result<Vec<_>>>()?,
+                    hash.partition_count as usize,
+                ),
+                Some(PartitionMethod::RoundRobin(partition_count)) => {
+                    Partitioning::RoundRobinBatch(*partition_count as usize)
+                }
+                Some(PartitionMethod::Unknown(partition_count)) => {
+                    Partitioning::UnknownPartitioning(*partition_count as usize)
+                }
+                None => return Err(proto_error(
+                    "Missing required field 'partition_method' in RepartitionExecNode",
+                )),
+            };
+            let hash_seeds = match repart.hash_seeds.as_slice() {
+                [] => HashSeeds::default(),
+                [a, b, c, d] => HashSeeds([*a, *b, *c, *d]),
+                seeds => {
+                    return Err(proto_error(format!(
+                        "Expected 4 hash seeds in RepartitionExecNode, got {}",
+                        seeds.len()
+                    )))
+                }
+            };
+            Ok(Arc::new(
+                RepartitionExec::try_new(input, partitioning)?
+                    .with_preserve_order(repart.preserve_order)
+                    .with_hash_seeds(hash_seeds),
+            ))
+        }
+        PhysicalPlanType::GlobalLimit(limit) => {
+            let input = parse_required_plan(&limit.input, state)?;
+            Ok(Arc::new(GlobalLimitExec::new(input, limit.limit as usize)))
+        }
+        PhysicalPlanType::LocalLimit(limit) => {
+            let input = parse_required_plan(&limit.input, state)?;
+            Ok(Arc::new(LocalLimitExec::new(input, limit.limit as usize)))
+        }
+        PhysicalPlanType::HashAggregate(hash_agg) => {
+            let input = parse_required_plan(&hash_agg.input, state)?;
+            let mode = protobuf::AggregateMode::from_i32(hash_agg.mode).ok_or_else(|| {
+                proto_error(format!(
+                    "Received a HashAggregateExecNode message with unknown AggregateMode {}",
+                    hash_agg.mode
+                ))
+            })?;
+            let mode = match mode {
+                protobuf::AggregateMode::Partial => AggregateMode::Partial,
+                protobuf::AggregateMode::Final => AggregateMode::Final,
+                protobuf::AggregateMode::FinalPartitioned => {
+                    AggregateMode::FinalPartitioned
+                }
+            };
+
+            let group_expr = hash_agg
+                .group_expr
+                .iter()
+                .zip(hash_agg.group_expr_name.iter())
+                .map(|(expr, name)| {
+                    compile_expr(expr, &input.schema(), state)
+                        .map(|expr| (expr, name.clone()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // aggregate expressions of all modes refer to the input of the
+            // partial aggregate
+            let input_schema: SchemaRef =
+                Arc::new(convert_required!(hash_agg.input_schema)?);
+            let aggr_expr = hash_agg
+                .aggr_expr
+                .iter()
+                .zip(hash_agg.aggr_expr_name.iter())
+                .map(|(expr, name)| {
+                    compile_aggregate_expr(expr, name, &input_schema, state)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Arc::new(HashAggregateExec::try_new(
+                mode,
+                group_expr,
+                aggr_expr,
+                input,
+                input_schema,
+            )?))
+        }
+        PhysicalPlanType::HashJoin(hash_join) => {
+            let left = parse_required_plan(&hash_join.left, state)?;
+            let right = parse_required_plan(&hash_join.right, state)?;
+            let on: Vec<(String, String)> = hash_join
+                .on
+                .iter()
+                .map(|on| (on.left.clone(), on.right.clone()))
+                .collect();
+            let join_type = protobuf::JoinType::from_i32(hash_join.join_type)
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "Received a HashJoinExecNode message with unknown JoinType {}",
+                        hash_join.join_type
+                    ))
+                })?;
+            let join_type = match join_type {
+                protobuf::JoinType::InnerJoin => JoinType::Inner,
+                protobuf::JoinType::LeftJoin => JoinType::Left,
+                protobuf::JoinType::RightJoin => JoinType::Right,
+                protobuf::JoinType::FullJoin => JoinType::Full,
+                protobuf::JoinType::SemiJoin => JoinType::Semi,
+                protobuf::JoinType::AntiJoin => JoinType::Anti,
+            };
+            let partition_mode = protobuf::PartitionMode::from_i32(
+                hash_join.partition_mode,
+            )
+            .ok_or_else(|| {
+                proto_error(format!(
+                    "Received a HashJoinExecNode message with unknown PartitionMode {}",
+                    hash_join.partition_mode
+                ))
+            })?;
+            let partition_mode = match partition_mode {
+                protobuf::PartitionMode::CollectLeft => PartitionMode::CollectLeft,
+                protobuf::PartitionMode::Partitioned => PartitionMode::Partitioned,
+            };
+            Ok(Arc::new(HashJoinExec::try_new(
+                left,
+                right,
+                &on,
+                &join_type,
+                partition_mode,
+            )?))
+        }
+        PhysicalPlanType::CrossJoin(cross_join) => {
+            let left = parse_required_plan(&cross_join.left, state)?;
+            let right = parse_required_plan(&cross_join.right, state)?;
+            Ok(Arc::new(CrossJoinExec::try_new(left, right)?))
+        }
+        PhysicalPlanType::Empty(empty) => {
+            let schema = Arc::new(convert_required!(empty.schema)?);
+            if empty.produce_one_row {
+                Ok(Arc::new(PlaceholderRowExec::new(schema)))
+            } else {
+                Ok(Arc::new(EmptyExec::new(schema)))
+            }
+        }
+        PhysicalPlanType::Sort(sort) => {
+            let input = parse_required_plan(&sort.input, state)?;
+            let expr = sort
+                .expr
+                .iter()
+                .map(|expr| match expr.expr_type.as_ref() {
+                    Some(ExprType::Sort(sort_expr)) => {
+                        let expr = sort_expr.expr.as_ref().ok_or_else(|| {
+                            proto_error("Missing required field 'expr' in SortExprNode")
+                        })?;
+                        Ok(PhysicalSortExpr {
+                            expr: compile_expr(expr, &input.schema(), state)?,
+                            options: SortOptions {
+                                descending: !sort_expr.asc,
+                                nulls_first: sort_expr.nulls_first,
+                            },
+                        })
+                    }
+                    _ => Err(proto_error("Expected a sort expression in SortExecNode")),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Arc::new(SortExec::new_with_partitioning(
+                expr,
+                input,
+                sort.preserve_partitioning,
+            )))
+        }
+        PhysicalPlanType::Union(union) => {
+            let inputs = union
+                .inputs
+                .iter()
+                .map(|input| parse_physical_plan(input, state))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Arc::new(UnionExec::try_new(inputs)?))
+        }
+    }
+}
+
+fn parse_required_plan(
+    proto: &Option<Box<protobuf::PhysicalPlanNode>>,
+    state: &ExecutionContextState,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    match proto {
+        Some(plan) => parse_physical_plan(plan, state),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
+
+fn compile_expr(
+    expr: &protobuf::LogicalExprNode,
+    schema: &Schema,
+    state: &ExecutionContextState,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    let expr = parse_expr(expr, state)?;
+    DefaultPhysicalPlanner::default().create_physical_expr(&expr, schema, state)
+}
+
+fn compile_aggregate_expr(
+    expr: &protobuf::LogicalExprNode,
+    name: &str,
+    schema: &Schema,
+    state: &ExecutionContextState,
+) -> Result<Arc<dyn AggregateExpr>> {
+    let planner = DefaultPhysicalPlanner::default();
+    let compile_args = |args: &[Expr]| {
+        args.iter()
+            .map(|arg| planner.create_physical_expr(arg, schema, state))
+            .collect::<Result<Vec<_>>>()
+    };
+    match parse_expr(expr, state)? {
+        Expr::AggregateFunction {
+            fun,
+            args,
+            distinct,
+        } => aggregates::create_aggregate_expr(
+            &fun,
+            distinct,
+            &compile_args(&args)?,
+            schema,
+            name.to_owned(),
+        ),
+        Expr::AggregateUDF { fun, args } => udaf::create_aggregate_expr(
+            &fun,
+            &compile_args(&args)?,
+            schema,
+            name.to_owned(),
+        ),
+        other => Err(proto_error(format!(
+            "Expected an aggregate expression in HashAggregateExecNode, got {:?}",
+            other
+        ))),
+    }
+}
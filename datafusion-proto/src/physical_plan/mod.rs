@@ -0,0 +1,115 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serialization of physical plans
+
+pub mod from_proto;
+pub mod to_proto;
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::ArrayRef;
+    use datafusion::arrow::datatypes::DataType;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+    use datafusion::error::Result;
+    use datafusion::physical_plan::functions::make_scalar_function;
+    use datafusion::physical_plan::union::UnionExec;
+    use datafusion::physical_plan::{collect, displayable, ExecutionPlan};
+    use datafusion::prelude::*;
+    use tempfile::TempDir;
+
+    use crate::{physical_plan_from_bytes, physical_plan_to_bytes};
+
+    fn context(tmp_dir: &TempDir) -> Result<ExecutionContext> {
+        let t = tmp_dir.path().join("t.csv");
+        std::fs::write(&t, "a,b\n1,x\n2,y\n3,x\n4,z\n")?;
+        let u = tmp_dir.path().join("u.csv");
+        std::fs::write(&u, "c,d\n1,1.5\n3,2.5\n5,3.5\n")?;
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_csv("t", t.to_str().unwrap(), CsvReadOptions::new())?;
+        ctx.register_csv("u", u.to_str().unwrap(), CsvReadOptions::new())?;
+        let double = make_scalar_function(|args: &[ArrayRef]| Ok(args[0].clone()));
+        ctx.register_udf(create_udf(
+            "double",
+            vec![DataType::Float64],
+            Arc::new(DataType::Float64),
+            double,
+        ));
+        Ok(ctx)
+    }
+
+    async fn execute(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<String>> {
+        let batches = collect(plan).await?;
+        let mut lines = pretty_format_batches(&batches)?
+            .lines()
+            .map(|line| line.to_owned())
+            .collect::<Vec<_>>();
+        lines.sort();
+        Ok(lines)
+    }
+
+    #[tokio::test]
+    async fn roundtrip_plans() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let mut ctx = context(&tmp_dir)?;
+        let sqls = vec![
+            "SELECT a, b FROM t WHERE a > 1 ORDER BY b DESC LIMIT 2",
+            "SELECT b, count(a), sum(a), min(a), max(a), avg(a) FROM t GROUP BY b",
+            "SELECT count(DISTINCT b) FROM t",
+            "SELECT a, d FROM t JOIN u ON a = c",
+            "SELECT CASE WHEN a > 1 THEN 'big' ELSE b END, a IN (1, 2) FROM t",
+            "SELECT double(CAST(a AS DOUBLE)) FROM t",
+        ];
+
+        let mut plans = vec![];
+        for sql in sqls {
+            let plan = ctx.sql(sql)?.to_logical_plan();
+            plans.push(ctx.create_physical_plan(&ctx.optimize(&plan)?)?);
+        }
+        plans.push(Arc::new(UnionExec::try_new(vec![
+            plans[0].clone(),
+            plans[0].clone(),
+        ])?));
+
+        for plan in plans {
+            let bytes = physical_plan_to_bytes(plan.clone())?;
+            let round_trip = physical_plan_from_bytes(&bytes, &ctx)?;
+
+            assert_eq!(
+                displayable(plan.as_ref()).indent().to_string(),
+                displayable(round_trip.as_ref()).indent().to_string(),
+            );
+            assert_eq!(execute(plan).await?, execute(round_trip).await?);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unsupported_plan() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let plan = ctx.sql("SELECT 1")?.to_logical_plan();
+        let plan = ctx.create_physical_plan(&ctx.optimize(&plan)?)?;
+        assert!(physical_plan_to_bytes(plan).is_ok());
+
+        let plan = ctx.sql("EXPLAIN SELECT 1")?.to_logical_plan();
+        let plan = ctx.create_physical_plan(&plan)?;
+        assert!(physical_plan_to_bytes(plan).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,509 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Serde code to convert DataFusion physical plans to the protocol buffer
+//! format. Physical expressions are serialized as the logical expressions
+//! they are planned from.
+
+use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::physical_plan::coalesce_batches::{BatchSizePolicy, CoalesceBatchesExec};
+use datafusion::physical_plan::cross_join::CrossJoinExec;
+use datafusion::physical_plan::csv::CsvExec;
+use datafusion::physical_plan::distinct_expressions::DistinctCount;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::expressions::{
+    Avg, BinaryExpr, CaseExpr, CastExpr, CoalesceExpr, Column, Count, InListExpr,
+    IsNotNullExpr, IsNullExpr, Literal, Max, Min, NegativeExpr, NotExpr, Sum,
+    TryCastExpr,
+};
+use datafusion::physical_plan::filter::FilterExec;
+use datafusion::physical_plan::functions::{BuiltinScalarFunction, ScalarFunctionExpr};
+use datafusion::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
+use datafusion::physical_plan::hash_join::{HashJoinExec, PartitionMode};
+use datafusion::physical_plan::hash_utils::{HashSeeds, JoinType};
+use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use datafusion::physical_plan::merge::MergeExec;
+use datafusion::physical_plan::parquet::ParquetExec;
+use datafusion::physical_plan::placeholder_row::PlaceholderRowExec;
+use datafusion::physical_plan::projection::ProjectionExec;
+use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::sort::SortExec;
+use datafusion::physical_plan::udaf::AggregateFunctionExpr;
+use datafusion::physical_plan::union::UnionExec;
+use datafusion::physical_plan::{
+    AggregateExpr, ExecutionPlan, Partitioning, PhysicalExpr,
+};
+use datafusion::scalar::ScalarValue;
+
+use crate::protobuf;
+use protobuf::logical_expr_node::ExprType;
+use protobuf::physical_plan_node::PhysicalPlanType;
+
+impl TryFrom<Arc<dyn ExecutionPlan>> for protobuf::PhysicalPlanNode {
+    type Error = DataFusionError;
+
+    fn try_from(plan: Arc<dyn ExecutionPlan>) -> Result<Self> {
+        let physical_plan_type = physical_plan_type(plan)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(physical_plan_type),
+        })
+    }
+}
+
+fn physical_plan_type(plan: Arc<dyn ExecutionPlan>) -> Result<PhysicalPlanType> {
+    let any = plan.as_any();
+
+    if let Some(exec) = any.downcast_ref::<ProjectionExec>() {
+        Ok(PhysicalPlanType::Projection(Box::new(
+            protobuf::ProjectionExecNode {
+                input: Some(Box::new(exec.input().clone().try_into()?)),
+                expr: exec
+                    .expr()
+                    .iter()
+                    .map(|(expr, _)| expr.clone().try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                expr_name: exec.expr().iter().map(|(_, name)| name.clone()).collect(),
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<FilterExec>() {
+        Ok(PhysicalPlanType::Filter(Box::new(
+            protobuf::FilterExecNode {
+                input: Some(Box::new(exec.input().clone().try_into()?)),
+                expr: Some(exec.predicate().clone().try_into()?),
+            },
+        )))
+    } else if let Some(limit) = any.downcast_ref::<GlobalLimitExec>() {
+        Ok(PhysicalPlanType::GlobalLimit(Box::new(
+            protobuf::GlobalLimitExecNode {
+                input: Some(Box::new(limit.input().clone().try_into()?)),
+                limit: limit.limit() as u64,
+            },
+        )))
+    } else if let Some(limit) = any.downcast_ref::<LocalLimitExec>() {
+        Ok(PhysicalPlanType::LocalLimit(Box::new(
+            protobuf::LocalLimitExecNode {
+                input: Some(Box::new(limit.input().clone().try_into()?)),
+                limit: limit.limit() as u64,
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<HashJoinExec>() {
+        let join_type = match exec.join_type() {
+            JoinType::Inner => protobuf::JoinType::InnerJoin,
+            JoinType::Left => protobuf::JoinType::LeftJoin,
+            JoinType::Right => protobuf::JoinType::RightJoin,
+            JoinType::Full => protobuf::JoinType::FullJoin,
+            JoinType::Semi => protobuf::JoinType::SemiJoin,
+            JoinType::Anti => protobuf::JoinType::AntiJoin,
+        };
+        let partition_mode = match exec.partition_mode() {
+            PartitionMode::CollectLeft => protobuf::PartitionMode::CollectLeft,
+            PartitionMode::Partitioned => protobuf::PartitionMode::Partitioned,
+        };
+        Ok(PhysicalPlanType::HashJoin(Box::new(
+            protobuf::HashJoinExecNode {
+                left: Some(Box::new(exec.left().clone().try_into()?)),
+                right: Some(Box::new(exec.right().clone().try_into()?)),
+                on: exec
+                    .on()
+                    .iter()
+                    .map(|(left, right)| protobuf::JoinOn {
+                        left: left.clone(),
+                        right: right.clone(),
+                    })
+                    .collect(),
+                join_type: join_type.into(),
+                partition_mode: partition_mode.into(),
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<CrossJoinExec>() {
+        Ok(PhysicalPlanType::CrossJoin(Box::new(
+            protobuf::CrossJoinExecNode {
+                left: Some(Box::new(exec.left().clone().try_into()?)),
+                right: Some(Box::new(exec.right().clone().try_into()?)),
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<HashAggregateExec>() {
+        let mode = match exec.mode() {
+            AggregateMode::Partial => protobuf::AggregateMode::Partial,
+            AggregateMode::Final => protobuf::AggregateMode::Final,
+            AggregateMode::FinalPartitioned => protobuf::AggregateMode::FinalPartitioned,
+        };
+        Ok(PhysicalPlanType::HashAggregate(Box::new(
+            protobuf::HashAggregateExecNode {
+                group_expr: exec
+                    .group_expr()
+                    .iter()
+                    .map(|(expr, _)| expr.clone().try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                aggr_expr: exec
+                    .aggr_expr()
+                    .iter()
+                    .map(|expr| expr.clone().try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                mode: mode.into(),
+                input: Some(Box::new(exec.input().clone().try_into()?)),
+                group_expr_name: exec
+                    .group_expr()
+                    .iter()
+                    .map(|(_, name)| name.clone())
+                    .collect(),
+                aggr_expr_name: exec
+                    .aggr_expr()
+                    .iter()
+                    .map(|expr| expr.name().to_owned())
+                    .collect(),
+                input_schema: Some(exec.input_schema().as_ref().into()),
+            },
+        )))
+    } else if let Some(empty) = any.downcast_ref::<EmptyExec>() {
+        Ok(PhysicalPlanType::Empty(protobuf::EmptyExecNode {
+            produce_one_row: false,
+            schema: Some(empty.schema().as_ref().into()),
+        }))
+    } else if let Some(placeholder) = any.downcast_ref::<PlaceholderRowExec>() {
+        Ok(PhysicalPlanType::Empty(protobuf::EmptyExecNode {
+            produce_one_row: true,
+            schema: Some(placeholder.schema().as_ref().into()),
+        }))
+    } else if let Some(exec) = any.downcast_ref::<CoalesceBatchesExec>() {
+        Ok(PhysicalPlanType::CoalesceBatches(Box::new(
+            protobuf::CoalesceBatchesExecNode {
+                input: Some(Box::new(exec.input().clone().try_into()?)),
+                target_batch_size: exec.target_batch_size() as u64,
+                target_bytes: match exec.policy() {
+                    BatchSizePolicy::Rows => 0,
+                    BatchSizePolicy::Adaptive { target_bytes } => target_bytes as u64,
+                },
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<CsvExec>() {
+        if exec.path().is_empty() {
+            return Err(DataFusionError::NotImplemented(
+                "Serializing scans of CSV readers to protobuf is not supported"
+                    .to_owned(),
+            ));
+        }
+        let delimiter = [*exec.delimiter().unwrap_or(&b',')];
+        let delimiter = std::str::from_utf8(&delimiter)
+            .map_err(|_| DataFusionError::Plan("Invalid CSV delimiter".to_owned()))?;
+        let schema = exec.file_schema();
+        Ok(PhysicalPlanType::CsvScan(protobuf::CsvScanExecNode {
+            path: exec.path().to_owned(),
+            optional_projection: exec.projection().map(|projection| {
+                protobuf::csv_scan_exec_node::OptionalProjection::Projection(
+                    protobuf::ProjectionColumns {
+                        columns: projection
+                            .iter()
+                            .map(|i| schema.field(*i).name().to_owned())
+                            .collect(),
+                    },
+                )
+            }),
+            schema: Some(schema.as_ref().into()),
+            file_extension: exec.file_extension().to_owned(),
+            has_header: exec.has_header(),
+            batch_size: exec.batch_size() as u32,
+            delimiter: delimiter.to_owned(),
+            optional_limit: exec.limit().map(|limit| {
+                protobuf::csv_scan_exec_node::OptionalLimit::Limit(limit as u64)
+            }),
+        }))
+    } else if let Some(exec) = any.downcast_ref::<ParquetExec>() {
+        Ok(PhysicalPlanType::ParquetScan(
+            protobuf::ParquetScanExecNode {
+                filename: exec
+                    .partitions()
+                    .iter()
+                    .flat_map(|partition| partition.filenames().to_owned())
+                    .collect(),
+                projection: exec.projection().iter().map(|i| *i as u32).collect(),
+                num_partitions: exec.partitions().len() as u32,
+                batch_size: exec.batch_size() as u32,
+            },
+        ))
+    } else if let Some(exec) = any.downcast_ref::<MergeExec>() {
+        Ok(PhysicalPlanType::Merge(Box::new(protobuf::MergeExecNode {
+            input: Some(Box::new(exec.input().clone().try_into()?)),
+            preserve_order: exec.preserve_order(),
+        })))
+    } else if let Some(exec) = any.downcast_ref::<RepartitionExec>() {
+        use protobuf::repartition_exec_node::PartitionMethod;
+
+        let partition_method = match exec.partitioning() {
+            Partitioning::Hash(exprs, partition_count) => {
+                PartitionMethod::Hash(protobuf::HashRepartition {
+                    hash_expr: exprs
+                        .iter()
+                        .map(|expr| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    partition_count: *partition_count as u64,
+                })
+            }
+            Partitioning::RoundRobinBatch(partition_count) => {
+                PartitionMethod::RoundRobin(*partition_count as u64)
+            }
+            Partitioning::UnknownPartitioning(partition_count) => {
+                PartitionMethod::Unknown(*partition_count as u64)
+            }
+        };
+        Ok(PhysicalPlanType::Repartition(Box::new(
+            protobuf::RepartitionExecNode {
+                input: Some(Box::new(exec.input().clone().try_into()?)),
+                partition_method: Some(partition_method),
+                hash_seeds: if exec.hash_seeds() == HashSeeds::default() {
+                    vec![]
+                } else {
+                    exec.hash_seeds().0.to_vec()
+                },
+                preserve_order: exec.preserve_order(),
+            },
+        )))
+    } else if let Some(exec) = any.downcast_ref::<SortExec>() {
+        let expr = exec
+            .expr()
+            .iter()
+            .map(|expr| {
+                Ok(protobuf::LogicalExprNode {
+                    expr_type: Some(ExprType::Sort(Box::new(protobuf::SortExprNode {
+                        expr: Some(Box::new(expr.expr.clone().try_into()?)),
+                        asc: !expr.options.descending,
+                        nulls_first: expr.options.nulls_first,
+                    }))),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PhysicalPlanType::Sort(Box::new(protobuf::SortExecNode {
+            input: Some(Box::new(exec.input().clone().try_into()?)),
+            expr,
+            preserve_partitioning: exec.preserve_partitioning(),
+        })))
+    } else if any.downcast_ref::<UnionExec>().is_some() {
+        Ok(PhysicalPlanType::Union(protobuf::UnionExecNode {
+            inputs: plan
+                .children()
+                .into_iter()
+                .map(|input| input.try_into())
+                .collect::<Result<Vec<_>>>()?,
+        }))
+    } else {
+        Err(DataFusionError::NotImplemented(format!(
+            "Serializing this physical plan to protobuf is not supported: {:?}",
+            plan
+        )))
+    }
+}
+
+impl TryFrom<Arc<dyn AggregateExpr>> for protobuf::LogicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(expr: Arc<dyn AggregateExpr>) -> Result<Self> {
+        let any = expr.as_any();
+        let args = expr
+            .expressions()
+            .into_iter()
+            .map(|arg| arg.try_into())
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(udaf) = any.downcast_ref::<AggregateFunctionExpr>() {
+            return Ok(protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::AggregateUdfExpr(
+                    protobuf::AggregateUdfExprNode {
+                        fun_name: udaf.fun().name.clone(),
+                        args,
+                    },
+                )),
+            });
+        }
+
+        let (aggr_function, distinct) = if any.downcast_ref::<Count>().is_some() {
+            (protobuf::AggregateFunction::Count, false)
+        } else if any.downcast_ref::<DistinctCount>().is_some() {
+            (protobuf::AggregateFunction::Count, true)
+        } else if any.downcast_ref::<Sum>().is_some() {
+            (protobuf::AggregateFunction::Sum, false)
+        } else if any.downcast_ref::<Min>().is_some() {
+            (protobuf::AggregateFunction::Min, false)
+        } else if any.downcast_ref::<Max>().is_some() {
+            (protobuf::AggregateFunction::Max, false)
+        } else if any.downcast_ref::<Avg>().is_some() {
+            (protobuf::AggregateFunction::Avg, false)
+        } else {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Serializing this aggregate expression to protobuf is not supported: {:?}",
+                expr
+            )));
+        };
+        Ok(protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::AggregateExpr(protobuf::AggregateExprNode {
+                aggr_function: aggr_function.into(),
+                args,
+                distinct,
+            })),
+        })
+    }
+}
+
+impl TryFrom<Arc<dyn PhysicalExpr>> for protobuf::LogicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(expr: Arc<dyn PhysicalExpr>) -> Result<Self> {
+        let any = expr.as_any();
+
+        let expr_type = if let Some(column) = any.downcast_ref::<Column>() {
+            ExprType::ColumnName(column.name().to_owned())
+        } else if let Some(lit) = any.downcast_ref::<Literal>() {
+            ExprType::Literal(lit.value().try_into()?)
+        } else if let Some(binary) = any.downcast_ref::<BinaryExpr>() {
+            ExprType::BinaryExpr(Box::new(protobuf::BinaryExprNode {
+                l: Some(Box::new(binary.left().clone().try_into()?)),
+                r: Some(Box::new(binary.right().clone().try_into()?)),
+                op: format!("{:?}", binary.op()),
+            }))
+        } else if let Some(case) = any.downcast_ref::<CaseExpr>() {
+            ExprType::Case(Box::new(protobuf::CaseNode {
+                expr: case
+                    .expr()
+                    .as_ref()
+                    .map(|expr| expr.clone().try_into().map(Box::new))
+                    .transpose()?,
+                when_then_expr: case
+                    .when_then_expr()
+                    .iter()
+                    .map(|(when_expr, then_expr)| {
+                        Ok(protobuf::WhenThen {
+                            when_expr: Some(when_expr.clone().try_into()?),
+                            then_expr: Some(then_expr.clone().try_into()?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                else_expr: case
+                    .else_expr()
+                    .map(|expr| expr.clone().try_into().map(Box::new))
+                    .transpose()?,
+            }))
+        } else if let Some(not) = any.downcast_ref::<NotExpr>() {
+            ExprType::NotExpr(Box::new(protobuf::Not {
+                expr: Some(Box::new(not.arg().clone().try_into()?)),
+            }))
+        } else if let Some(is_null) = any.downcast_ref::<IsNullExpr>() {
+            ExprType::IsNullExpr(Box::new(protobuf::IsNull {
+                expr: Some(Box::new(is_null.arg().clone().try_into()?)),
+            }))
+        } else if let Some(is_not_null) = any.downcast_ref::<IsNotNullExpr>() {
+            ExprType::IsNotNullExpr(Box::new(protobuf::IsNotNull {
+                expr: Some(Box::new(is_not_null.arg().clone().try_into()?)),
+            }))
+        } else if let Some(negative) = any.downcast_ref::<NegativeExpr>() {
+            ExprType::Negative(Box::new(protobuf::NegativeNode {
+                expr: Some(Box::new(negative.arg().clone().try_into()?)),
+            }))
+        } else if let Some(in_list) = any.downcast_ref::<InListExpr>() {
+            ExprType::InList(Box::new(protobuf::InListNode {
+                expr: Some(Box::new(in_list.expr().clone().try_into()?)),
+                list: physical_exprs_to_proto(in_list.list())?,
+                negated: in_list.negated(),
+            }))
+        } else if let Some(cast) = any.downcast_ref::<CastExpr>() {
+            ExprType::Cast(Box::new(protobuf::CastNode {
+                expr: Some(Box::new(cast.expr().clone().try_into()?)),
+                arrow_type: Some(cast.cast_type().into()),
+            }))
+        } else if let Some(cast) = any.downcast_ref::<TryCastExpr>() {
+            ExprType::TryCast(Box::new(protobuf::TryCastNode {
+                expr: Some(Box::new(cast.expr().clone().try_into()?)),
+                arrow_type: Some(cast.cast_type().into()),
+            }))
+        } else if let Some(coalesce) = any.downcast_ref::<CoalesceExpr>() {
+            ExprType::ScalarFunction(protobuf::ScalarFunctionNode {
+                fun: protobuf::ScalarFunction::Coalesce.into(),
+                args: physical_exprs_to_proto(coalesce.args())?,
+            })
+        } else if let Some(fun) = any.downcast_ref::<ScalarFunctionExpr>() {
+            scalar_function_expr_type(fun)?
+        } else {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Serializing this physical expression to protobuf is not supported: {:?}",
+                expr
+            )));
+        };
+        Ok(protobuf::LogicalExprNode {
+            expr_type: Some(expr_type),
+        })
+    }
+}
+
+fn physical_exprs_to_proto(
+    exprs: &[Arc<dyn PhysicalExpr>],
+) -> Result<Vec<protobuf::LogicalExprNode>> {
+    exprs.iter().map(|expr| expr.clone().try_into()).collect()
+}
+
+/// Scalar functions only keep the name of the function they evaluate: names
+/// of built-in functions are resolved here, all other names refer to UDFs.
+fn scalar_function_expr_type(expr: &ScalarFunctionExpr) -> Result<ExprType> {
+    let mut args = physical_exprs_to_proto(expr.args())?;
+    let fun = match builtin_scalar_function(expr.name()) {
+        Some(fun) => fun,
+        None => {
+            return Ok(ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
+                fun_name: expr.name().to_owned(),
+                args,
+            }))
+        }
+    };
+
+    // the field names of `named_struct` are bound when it is planned, restore
+    // them from its return type
+    if fun == BuiltinScalarFunction::NamedStruct {
+        if let DataType::Struct(fields) = expr.return_type() {
+            args = fields
+                .iter()
+                .map(|field| ScalarValue::Utf8(Some(field.name().to_owned())))
+                .zip(args)
+                .map(|(name, value)| {
+                    let name = protobuf::LogicalExprNode {
+                        expr_type: Some(ExprType::Literal((&name).try_into()?)),
+                    };
+                    Ok(vec![name, value])
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .flatten()
+                .collect();
+        }
+    }
+
+    Ok(ExprType::ScalarFunction(protobuf::ScalarFunctionNode {
+        fun: protobuf::ScalarFunction::from(&fun).into(),
+        args,
+    }))
+}
+
+fn builtin_scalar_function(name: &str) -> Option<BuiltinScalarFunction> {
+    let mut i = 0;
+    while let Some(fun) = protobuf::ScalarFunction::from_i32(i) {
+        let fun = BuiltinScalarFunction::from(fun);
+        if fun.to_string() == name {
+            return Some(fun);
+        }
+        i += 1;
+    }
+    None
+}
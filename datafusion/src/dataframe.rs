@@ -20,8 +20,10 @@
 use crate::arrow::record_batch::RecordBatch;
 use crate::error::Result;
 use crate::logical_plan::{
-    DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, Partitioning,
+    DFSchema, DuplicateKeep, Expr, FunctionRegistry, JoinType, LogicalPlan, Partitioning,
+    SampleMethod,
 };
+use crate::physical_plan::SendableRecordBatchStream;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -118,6 +120,62 @@ pub trait DataFrame: Send + Sync {
         aggr_expr: Vec<Expr>,
     ) -> Result<Arc<dyn DataFrame>>;
 
+    /// Compute window functions over all the rows of this DataFrame, appending
+    /// one column per window expression.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::logical_plan::Expr;
+    /// # use datafusion::physical_plan::aggregates::AggregateFunction;
+    /// # use datafusion::physical_plan::window_functions::WindowFunction;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    ///
+    /// // The following use is the equivalent of "SELECT MAX(b) OVER (), * FROM t"
+    /// let df = df.window(vec![Expr::WindowFunction {
+    ///     fun: WindowFunction::AggregateFunction(AggregateFunction::Max),
+    ///     args: vec![col("b")],
+    /// }])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn window(&self, window_expr: Vec<Expr>) -> Result<Arc<dyn DataFrame>>;
+
+    /// Add a column computed by an expression, replacing any existing column
+    /// with the same name.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.with_column("ab_sum", col("a") + col("b"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn with_column(&self, name: &str, expr: Expr) -> Result<Arc<dyn DataFrame>>;
+
+    /// Rename a column, erroring if there is no column with that name.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.with_column_renamed("a", "id")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn with_column_renamed(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<Arc<dyn DataFrame>>;
+
     /// Limit the number of rows returned from this DataFrame.
     ///
     /// ```
@@ -132,6 +190,62 @@ pub trait DataFrame: Send + Sync {
     /// ```
     fn limit(&self, n: usize) -> Result<Arc<dyn DataFrame>>;
 
+    /// Keep only the first or the last row of every distinct value of the
+    /// columns `subset`, or of all columns if it is `None`. If the DataFrame
+    /// is sorted by these columns, the rows are deduplicated while streaming.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.drop_duplicates(Some(&["a"]), DuplicateKeep::First)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn drop_duplicates(
+        &self,
+        subset: Option<&[&str]>,
+        keep: DuplicateKeep,
+    ) -> Result<Arc<dyn DataFrame>>;
+
+    /// Sample the rows of the DataFrame with `method`. The sample is
+    /// reproducible for the same input if a `seed` is given.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.sample(SampleMethod::Bernoulli { fraction: 0.1 }, Some(42))?;
+    /// let df = df.sample(SampleMethod::Reservoir { size: 10 }, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn sample(
+        &self,
+        method: SampleMethod,
+        seed: Option<u64>,
+    ) -> Result<Arc<dyn DataFrame>>;
+
+    /// Expand the list column `column` into one row per item of its lists,
+    /// repeating the values of the other columns. Rows with null or empty
+    /// lists are removed.
+    ///
+    /// ```no_run
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_parquet("nested.parquet")?;
+    /// let df = df.unnest("tags")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn unnest(&self, column: &str) -> Result<Arc<dyn DataFrame>>;
+
     /// Calculate the union two [`DataFrame`]s.  The two [`DataFrame`]s must have exactly the same schema
     ///
     /// ```
@@ -146,6 +260,36 @@ pub trait DataFrame: Send + Sync {
     /// ```
     fn union(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>>;
 
+    /// Calculate the distinct rows of this [`DataFrame`] that are also rows of
+    /// another [`DataFrame`]. The two [`DataFrame`]s must have the same column types.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.intersect(df.filter(col("a").gt(lit(1)))?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn intersect(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>>;
+
+    /// Calculate the distinct rows of this [`DataFrame`] that are not rows of
+    /// another [`DataFrame`]. The two [`DataFrame`]s must have the same column types.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let df = df.except(df.filter(col("a").gt(lit(1)))?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn except(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>>;
+
     /// Sort the DataFrame by the specified sorting expressions. Any expression can be turned into
     /// a sort expression by calling its [sort](../logical_plan/enum.Expr.html#method.sort) method.
     ///
@@ -188,6 +332,38 @@ pub trait DataFrame: Send + Sync {
         right_cols: &[&str],
     ) -> Result<Arc<dyn DataFrame>>;
 
+    /// Join this DataFrame with another DataFrame on the conjunction of the
+    /// `on` predicates. Equalities between columns of both DataFrames are used
+    /// as join keys, other predicates are only supported for inner joins.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let left = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let right = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?
+    ///   .select(vec![
+    ///     col("a").alias("a2"),
+    ///     col("b").alias("b2"),
+    ///     col("c").alias("c2")])?;
+    /// let join = left.join_on(
+    ///     right,
+    ///     JoinType::Inner,
+    ///     vec![col("a").eq(col("a2")), col("b").lt(col("b2"))],
+    /// )?;
+    /// let batches = join.collect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn join_on(
+        &self,
+        right: Arc<dyn DataFrame>,
+        join_type: JoinType,
+        on: Vec<Expr>,
+    ) -> Result<Arc<dyn DataFrame>>;
+
     /// Repartition a DataFrame based on a logical partitioning scheme.
     ///
     /// ```
@@ -220,6 +396,48 @@ pub trait DataFrame: Send + Sync {
     /// ```
     async fn collect(&self) -> Result<Vec<RecordBatch>>;
 
+    /// Computes summary statistics of each column: the count of non-null
+    /// values, the null count, the mean, the standard deviation, the minimum,
+    /// the maximum and the 25th, 50th and 75th percentiles. The statistics
+    /// are the rows of the returned batch, named by its `describe` column.
+    /// Numeric columns are summarized as floats, other columns as strings
+    /// without the statistics that only apply to numbers. The percentiles are
+    /// approximated by a sample of the rows.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let summary = df.describe().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn describe(&self) -> Result<RecordBatch>;
+
+    /// Executes this DataFrame and returns a stream over a single partition of
+    /// its results. Batches are computed as the stream is consumed, so large
+    /// results can be processed without holding all of them in memory.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let mut stream = df.execute_stream().await?;
+    /// while let Some(batch) = stream.next().await {
+    ///     let batch = batch?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn execute_stream(&self) -> Result<SendableRecordBatchStream>;
+
     /// Executes this DataFrame and collects all results into a vector of vector of RecordBatch
     /// maintaining the input partitioning.
     ///
@@ -236,6 +454,22 @@ pub trait DataFrame: Send + Sync {
     /// ```
     async fn collect_partitioned(&self) -> Result<Vec<Vec<RecordBatch>>>;
 
+    /// Executes this DataFrame and returns one stream per partition of its
+    /// results, see [`execute_stream`](Self::execute_stream).
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let streams = df.execute_stream_partitioned().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn execute_stream_partitioned(&self) -> Result<Vec<SendableRecordBatchStream>>;
+
     /// Returns the schema describing the output of this DataFrame in terms of columns returned,
     /// where each column has a name, data type, and nullability attribute.
 
@@ -269,6 +503,23 @@ pub trait DataFrame: Send + Sync {
     /// ```
     fn explain(&self, verbose: bool) -> Result<Arc<dyn DataFrame>>;
 
+    /// Return a DataFrame that runs its plan so far to completion, and
+    /// produces its physical plan annotated with the metrics collected
+    /// while running it, like `EXPLAIN ANALYZE`.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new())?;
+    /// let batches = df.limit(100)?.explain_analyze(false)?.collect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn explain_analyze(&self, verbose: bool) -> Result<Arc<dyn DataFrame>>;
+
     /// Return a `FunctionRegistry` used to plan udf's calls
     ///
     /// ```
@@ -43,9 +43,10 @@ use crate::datasource::datasource::Statistics;
 use crate::datasource::{Source, TableProvider};
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
+use crate::physical_plan::common::{self, ListingOptions};
 use crate::physical_plan::csv::CsvExec;
 pub use crate::physical_plan::csv::CsvReadOptions;
-use crate::physical_plan::{common, ExecutionPlan};
+use crate::physical_plan::ExecutionPlan;
 
 /// Represents a CSV file with a provided schema
 pub struct CsvFile {
@@ -54,6 +55,7 @@ pub struct CsvFile {
     has_header: bool,
     delimiter: u8,
     file_extension: String,
+    listing: ListingOptions,
     statistics: Statistics,
 }
 
@@ -63,7 +65,11 @@ impl CsvFile {
         let schema = Arc::new(match options.schema {
             Some(s) => s.clone(),
             None => {
-                let filenames = common::build_file_list(path, options.file_extension)?;
+                let filenames = common::build_file_list_with_options(
+                    path,
+                    options.file_extension,
+                    &options.listing,
+                )?;
                 if filenames.is_empty() {
                     return Err(DataFusionError::Plan(format!(
                         "No files found at {path} with file extension {file_extension}",
@@ -81,6 +87,7 @@ impl CsvFile {
             has_header: options.has_header,
             delimiter: options.delimiter,
             file_extension: String::from(options.file_extension),
+            listing: options.listing,
             statistics: Statistics::default(),
         })
     }
@@ -106,6 +113,7 @@ impl CsvFile {
             delimiter: options.delimiter,
             statistics: Statistics::default(),
             file_extension: String::new(),
+            listing: options.listing,
         })
     }
 
@@ -123,7 +131,7 @@ impl CsvFile {
                     Some(options.schema_infer_max_records),
                     options.has_header,
                 )?;
-                schema
+                options.resolve_inferred_schema(&schema)
             }
         });
 
@@ -134,6 +142,7 @@ impl CsvFile {
             delimiter: options.delimiter,
             statistics: Statistics::default(),
             file_extension: String::new(),
+            listing: options.listing,
         })
     }
 
@@ -159,6 +168,11 @@ impl CsvFile {
     pub fn file_extension(&self) -> &str {
         &self.file_extension
     }
+
+    /// Get how the files of the directory represented by this CsvFile instance are listed
+    pub fn listing(&self) -> ListingOptions {
+        self.listing
+    }
 }
 
 impl TableProvider for CsvFile {
@@ -181,7 +195,8 @@ impl TableProvider for CsvFile {
             .schema(&self.schema)
             .has_header(self.has_header)
             .delimiter(self.delimiter)
-            .file_extension(self.file_extension.as_str());
+            .file_extension(self.file_extension.as_str())
+            .listing(self.listing);
         let batch_size = limit
             .map(|l| std::cmp::min(l, batch_size))
             .unwrap_or(batch_size);
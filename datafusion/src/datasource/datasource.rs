@@ -116,4 +116,18 @@ pub trait TableProvider: Sync + Send {
     ) -> Result<TableProviderFilterPushDown> {
         Ok(TableProviderFilterPushDown::Unsupported)
     }
+
+    /// Names of the partition columns of the table, whose values are the
+    /// same for all rows of a partition, such as the `key=value` directories
+    /// of Hive-style partitioned tables. Empty by default.
+    fn partition_columns(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Returns the table with only the partitions for which `filter`, which
+    /// only refers to partition columns, may be true, or `None` if the table
+    /// can not prune its partitions, which is the default.
+    fn prune_partitions(&self, _filter: &Expr) -> Result<Option<Arc<dyn TableProvider>>> {
+        Ok(None)
+    }
 }
@@ -67,7 +67,7 @@ impl TableProvider for EmptyTable {
                 .map(|i| self.schema.field(*i).clone())
                 .collect(),
         );
-        Ok(Arc::new(EmptyExec::new(false, Arc::new(projected_schema))))
+        Ok(Arc::new(EmptyExec::new(Arc::new(projected_schema))))
     }
 
     fn statistics(&self) -> Statistics {
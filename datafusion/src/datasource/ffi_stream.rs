@@ -0,0 +1,130 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A table reading an Arrow C stream produced by another runtime, see
+//! [`crate::physical_plan::ffi_stream`].
+
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::SchemaRef;
+
+use crate::datasource::datasource::Statistics;
+use crate::datasource::TableProvider;
+use crate::error::Result;
+use crate::logical_plan::Expr;
+use crate::physical_plan::ffi_stream::{
+    ArrowArrayStreamExec, ArrowArrayStreamReader, FFI_ArrowArrayStream,
+};
+use crate::physical_plan::ExecutionPlan;
+
+/// A table whose record batches are read from an [`FFI_ArrowArrayStream`].
+/// As the stream can only be read once, so can the table.
+pub struct ArrowArrayStreamTable {
+    schema: SchemaRef,
+    reader: Arc<Mutex<Option<ArrowArrayStreamReader>>>,
+}
+
+impl ArrowArrayStreamTable {
+    /// Initialize a new `ArrowArrayStreamTable` reading `stream`
+    pub fn try_new(stream: FFI_ArrowArrayStream) -> Result<Self> {
+        Ok(Self::new(ArrowArrayStreamReader::try_new(stream)?))
+    }
+
+    /// Initialize a new `ArrowArrayStreamTable` reading the batches of `reader`
+    pub fn new(reader: ArrowArrayStreamReader) -> Self {
+        Self {
+            schema: reader.schema(),
+            reader: Arc::new(Mutex::new(Some(reader))),
+        }
+    }
+}
+
+impl TableProvider for ArrowArrayStreamTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: &Option<Vec<usize>>,
+        _batch_size: usize,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(ArrowArrayStreamExec::new(
+            self.reader.clone(),
+            self.schema.clone(),
+            projection.clone(),
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::prelude::*;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    #[tokio::test]
+    async fn query_imported_stream() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+            ],
+        )?;
+        let exec = MemoryExec::try_new(&[vec![batch.clone(), batch]], schema, None)?;
+        let stream = FFI_ArrowArrayStream::new(exec.execute(0).await?);
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", Arc::new(ArrowArrayStreamTable::try_new(stream)?))?;
+        let df = ctx.sql("SELECT b, SUM(a) FROM t WHERE b IS NOT NULL GROUP BY b")?;
+        let results = df.collect().await?;
+        let expected = vec![
+            "+---+--------+",
+            "| b | SUM(a) |",
+            "+---+--------+",
+            "| x | 2      |",
+            "| z | 6      |",
+            "+---+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        // the stream was consumed by the first query
+        let err = ctx.sql("SELECT a FROM t")?.collect().await.unwrap_err();
+        assert!(err.to_string().contains("already read"));
+        Ok(())
+    }
+}
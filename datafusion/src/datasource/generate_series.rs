@@ -0,0 +1,173 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! A table of a series of integers, backing the `generate_series` and `range`
+//! table functions.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::datatypes::*;
+
+use crate::datasource::datasource::{ColumnStatistics, Statistics};
+use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Expr;
+use crate::physical_plan::{generate_series::GenerateSeriesExec, ExecutionPlan};
+use crate::scalar::ScalarValue;
+
+/// A table with a single non-nullable Int64 column holding the integers from
+/// `start` up to `stop` in increments of `step`.
+pub struct GenerateSeriesTable {
+    schema: SchemaRef,
+    start: i64,
+    step: i64,
+    len: usize,
+}
+
+impl GenerateSeriesTable {
+    /// Initialize a new `GenerateSeriesTable` with the column `column_name`,
+    /// which includes `stop` if `include_stop` is set and the series reaches
+    /// it. A negative `step` produces a descending series.
+    pub fn try_new(
+        column_name: &str,
+        start: i64,
+        stop: i64,
+        step: i64,
+        include_stop: bool,
+    ) -> Result<Self> {
+        if step == 0 {
+            return Err(DataFusionError::Plan(
+                "The step of a series must not be zero".to_string(),
+            ));
+        }
+        // the distance from start to the last possible value, in the
+        // direction of step
+        let (start_wide, stop_wide, step_wide) =
+            (start as i128, stop as i128, step as i128);
+        let distance = if step > 0 {
+            stop_wide - start_wide
+        } else {
+            start_wide - stop_wide
+        } - if include_stop { 0 } else { 1 };
+        let len = if distance < 0 {
+            0
+        } else {
+            distance / step_wide.abs() + 1
+        };
+        if len > usize::MAX as i128 {
+            return Err(DataFusionError::Plan(format!(
+                "The series from {} to {} has too many values",
+                start, stop
+            )));
+        }
+        let schema = Schema::new(vec![Field::new(column_name, DataType::Int64, false)]);
+        Ok(Self {
+            schema: Arc::new(schema),
+            start,
+            step,
+            len: len as usize,
+        })
+    }
+
+    /// The number of values in the series
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the series has no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl TableProvider for GenerateSeriesTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        _projection: &Option<Vec<usize>>,
+        batch_size: usize,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // the single column is always produced, as batches need at least one
+        let len = limit.map_or(self.len, |limit| limit.min(self.len));
+        Ok(Arc::new(GenerateSeriesExec::new(
+            self.schema.clone(),
+            self.start,
+            self.step,
+            len,
+            batch_size,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        let (min_value, max_value) = if self.is_empty() {
+            (None, None)
+        } else {
+            let last = self
+                .start
+                .wrapping_add(((self.len - 1) as i64).wrapping_mul(self.step));
+            (
+                Some(ScalarValue::Int64(Some(self.start.min(last)))),
+                Some(ScalarValue::Int64(Some(self.start.max(last)))),
+            )
+        };
+        Statistics {
+            num_rows: Some(self.len),
+            total_byte_size: Some(self.len * std::mem::size_of::<i64>()),
+            column_statistics: Some(vec![ColumnStatistics {
+                null_count: Some(0),
+                distinct_count: Some(self.len),
+                max_value,
+                min_value,
+            }]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn series_length() -> Result<()> {
+        let len = |start, stop, step, include_stop| {
+            GenerateSeriesTable::try_new("x", start, stop, step, include_stop)
+                .map(|t| t.len())
+        };
+        assert_eq!(len(1, 10, 1, true)?, 10);
+        assert_eq!(len(1, 10, 1, false)?, 9);
+        assert_eq!(len(1, 10, 4, true)?, 3);
+        assert_eq!(len(1, 9, 4, true)?, 3);
+        assert_eq!(len(1, 9, 4, false)?, 2);
+        assert_eq!(len(10, 1, -3, true)?, 4);
+        assert_eq!(len(10, 1, 1, true)?, 0);
+        assert_eq!(len(5, 5, 1, true)?, 1);
+        assert_eq!(len(5, 5, 1, false)?, 0);
+        assert_eq!(len(i64::MIN, i64::MAX, i64::MAX, true)?, 3);
+        assert!(len(1, 10, 0, true).is_err());
+        assert!(len(i64::MIN, i64::MAX, 1, true).is_err());
+        Ok(())
+    }
+}
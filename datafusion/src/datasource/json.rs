@@ -30,7 +30,7 @@ use crate::{
     datasource::{Source, TableProvider},
     error::{DataFusionError, Result},
     physical_plan::{
-        common,
+        common::{self, ListingOptions},
         json::{NdJsonExec, NdJsonReadOptions},
         ExecutionPlan,
     },
@@ -48,16 +48,21 @@ pub struct NdJsonFile {
     source: Source<Box<dyn SeekRead + Send + Sync + 'static>>,
     schema: SchemaRef,
     file_extension: String,
+    listing: ListingOptions,
     statistics: Statistics,
 }
 
 impl NdJsonFile {
     /// Attempt to initialize a `NdJsonFile` from a path. The schema can be inferred automatically.
     pub fn try_new(path: &str, options: NdJsonReadOptions) -> Result<Self> {
-        let schema = if let Some(schema) = options.schema {
-            schema
+        let schema = if let Some(schema) = &options.schema {
+            schema.clone()
         } else {
-            let filenames = common::build_file_list(path, options.file_extension)?;
+            let filenames = common::build_file_list_with_options(
+                path,
+                options.file_extension,
+                &options.listing,
+            )?;
             if filenames.is_empty() {
                 return Err(DataFusionError::Plan(format!(
                     "No files found at {path} with file extension {file_extension}",
@@ -66,17 +71,18 @@ impl NdJsonFile {
                 )));
             }
 
-            NdJsonExec::try_infer_schema(
+            let schema = NdJsonExec::try_infer_schema(
                 filenames,
                 Some(options.schema_infer_max_records),
-            )?
-            .into()
+            )?;
+            options.resolve_inferred_schema(&schema).into()
         };
 
         Ok(Self {
             source: Source::Path(path.to_string()),
             schema,
             file_extension: options.file_extension.to_string(),
+            listing: options.listing,
             statistics: Statistics::default(),
         })
     }
@@ -86,23 +92,23 @@ impl NdJsonFile {
         mut reader: R,
         options: NdJsonReadOptions,
     ) -> Result<Self> {
-        let schema = if let Some(schema) = options.schema {
-            schema
+        let schema = if let Some(schema) = &options.schema {
+            schema.clone()
         } else {
             let mut bufr = BufReader::new(reader);
             let schema = infer_json_schema_from_seekable(
                 &mut bufr,
                 Some(options.schema_infer_max_records),
-            )?
-            .into();
+            )?;
             reader = bufr.into_inner();
-            schema
+            options.resolve_inferred_schema(&schema).into()
         };
         Ok(Self {
             source: Source::Reader(Mutex::new(Some(Box::new(reader)))),
             schema,
             statistics: Statistics::default(),
             file_extension: String::new(),
+            listing: options.listing,
         })
     }
 }
@@ -126,6 +132,8 @@ impl TableProvider for NdJsonFile {
             schema: Some(self.schema.clone()),
             schema_infer_max_records: 0, // schema will always be provided, so it's unnecessary to infer schema
             file_extension: self.file_extension.as_str(),
+            listing: self.listing,
+            ..Default::default()
         };
         let batch_size = limit
             .map(|l| std::cmp::min(l, batch_size))
@@ -20,9 +20,13 @@
 pub mod csv;
 pub mod datasource;
 pub mod empty;
+pub mod ffi_stream;
+pub mod generate_series;
 pub mod json;
 pub mod memory;
 pub mod parquet;
+pub mod partitioned;
+pub mod stream;
 
 pub use self::csv::{CsvFile, CsvReadOptions};
 pub use self::datasource::{TableProvider, TableType};
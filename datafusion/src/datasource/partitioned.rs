@@ -0,0 +1,426 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Hive-style partitioned tables, whose files are stored in one `key=value`
+//! directory per partition column, such as `sales/year=2021/month=01/`.
+//!
+//! Example:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use datafusion::datasource::TableProvider;
+//! use datafusion::datasource::csv::{CsvFile, CsvReadOptions};
+//! use datafusion::datasource::partitioned::PartitionedTable;
+//!
+//! let table = PartitionedTable::try_new("/data/sales", |path| {
+//!     Ok(Arc::new(CsvFile::try_new(path, CsvReadOptions::new())?))
+//! }).unwrap();
+//! assert_eq!(table.partition_columns(), vec!["year", "month"]);
+//! ```
+
+use std::any::Any;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, BooleanArray, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use crate::datasource::datasource::Statistics;
+use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionContextState;
+use crate::logical_plan::Expr;
+use crate::physical_plan::empty::EmptyExec;
+use crate::physical_plan::expressions::{col, lit};
+use crate::physical_plan::planner::DefaultPhysicalPlanner;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::union::UnionExec;
+use crate::physical_plan::{ColumnarValue, ExecutionPlan, PhysicalExpr};
+use crate::scalar::ScalarValue;
+
+/// A table whose partitions are stored in `key=value` directories, one level
+/// per partition column. Each partition is read by its own table provider,
+/// and its partition columns are added to its rows as `Utf8` columns after
+/// the columns of the files.
+///
+/// Filters on the partition columns remove whole partitions from scans, see
+/// [`PartitionPruning`](crate::optimizer::partition_pruning::PartitionPruning).
+pub struct PartitionedTable {
+    schema: SchemaRef,
+    partition_columns: Vec<String>,
+    partitions: Vec<Partition>,
+}
+
+/// A partition of a [`PartitionedTable`]
+#[derive(Clone)]
+struct Partition {
+    /// The value of each partition column
+    values: Vec<String>,
+    /// Reads the files of the partition
+    table: Arc<dyn TableProvider>,
+}
+
+impl PartitionedTable {
+    /// Attempt to initialize a new `PartitionedTable` from the partition
+    /// directories below `path`, whose files are read by the table providers
+    /// `open` creates for the path of each partition directory. All
+    /// partitions must have the same partition columns and file schema.
+    pub fn try_new<F>(path: &str, open: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Result<Arc<dyn TableProvider>>,
+    {
+        let mut directories = vec![];
+        list_partitions(Path::new(path), vec![], &mut directories)?;
+        let partition_columns: Vec<String> = match directories.first() {
+            Some((_, values)) => values.iter().map(|(key, _)| key.clone()).collect(),
+            None => {
+                return Err(DataFusionError::Plan(format!(
+                    "No partitions found at {}",
+                    path
+                )))
+            }
+        };
+
+        let partitions = directories
+            .into_iter()
+            .map(|(directory, values)| {
+                let (keys, values): (Vec<_>, Vec<_>) = values.into_iter().unzip();
+                if keys != partition_columns {
+                    return Err(DataFusionError::Plan(format!(
+                        "Partition {:?} has partition columns {:?} instead of {:?}",
+                        directory, keys, partition_columns
+                    )));
+                }
+                let directory = directory.to_str().ok_or_else(|| {
+                    DataFusionError::Plan(format!(
+                        "Partition path {:?} is not valid UTF-8",
+                        directory
+                    ))
+                })?;
+                Ok(Partition {
+                    values,
+                    table: open(directory)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let file_schema = partitions[0].table.schema();
+        if let Some(partition) = partitions
+            .iter()
+            .find(|partition| partition.table.schema() != file_schema)
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Partition {:?} has schema {:?} instead of {:?}",
+                partition.values,
+                partition.table.schema(),
+                file_schema
+            )));
+        }
+        let mut fields = file_schema.fields().clone();
+        for column in &partition_columns {
+            if file_schema.field_with_name(column).is_ok() {
+                return Err(DataFusionError::Plan(format!(
+                    "Partition column {} is also a column of the files",
+                    column
+                )));
+            }
+            fields.push(Field::new(column, DataType::Utf8, false));
+        }
+
+        Ok(Self {
+            schema: Arc::new(Schema::new(fields)),
+            partition_columns,
+            partitions,
+        })
+    }
+
+    /// Number of partitions of the table
+    pub fn num_partitions(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// Number of columns read from the files, which precede the partition
+    /// columns
+    fn num_file_columns(&self) -> usize {
+        self.schema.fields().len() - self.partition_columns.len()
+    }
+}
+
+/// Adds the partition directories below `directory` to `partitions`: the
+/// directories without `key=value` subdirectories, with the keys and values
+/// of the `key=value` directories on the way to them, which start with
+/// `values`
+fn list_partitions(
+    directory: &Path,
+    values: Vec<(String, String)>,
+    partitions: &mut Vec<(PathBuf, Vec<(String, String)>)>,
+) -> Result<()> {
+    let mut subdirectories = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        if let Some(index) = name.find('=') {
+            let key = name[..index].to_string();
+            let value = name[index + 1..].to_string();
+            subdirectories.push((path.clone(), key, value));
+        }
+    }
+    if subdirectories.is_empty() {
+        partitions.push((directory.to_path_buf(), values));
+        return Ok(());
+    }
+    // the order of the directory listing is not deterministic
+    subdirectories.sort();
+    for (path, key, value) in subdirectories {
+        let mut values = values.clone();
+        values.push((key, value));
+        list_partitions(&path, values, partitions)?;
+    }
+    Ok(())
+}
+
+impl TableProvider for PartitionedTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: &Option<Vec<usize>>,
+        batch_size: usize,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let num_file_columns = self.num_file_columns();
+        let projection = projection
+            .clone()
+            .unwrap_or_else(|| (0..self.schema.fields().len()).collect());
+        let mut file_projection: Vec<usize> = projection
+            .iter()
+            .filter(|index| **index < num_file_columns)
+            .cloned()
+            .collect();
+        // the rows are counted by reading a column of the files, even if
+        // only partition columns are projected
+        if file_projection.is_empty() && num_file_columns > 0 {
+            file_projection.push(0);
+        }
+
+        let plans = self
+            .partitions
+            .iter()
+            .map(|partition| {
+                let input = partition.table.scan(
+                    &Some(file_projection.clone()),
+                    batch_size,
+                    &[],
+                    limit,
+                )?;
+                let exprs = projection
+                    .iter()
+                    .map(|index| {
+                        let name = self.schema.field(*index).name();
+                        let expr: Arc<dyn PhysicalExpr> = if *index < num_file_columns {
+                            col(name)
+                        } else {
+                            let value = &partition.values[*index - num_file_columns];
+                            lit(ScalarValue::Utf8(Some(value.clone())))
+                        };
+                        (expr, name.clone())
+                    })
+                    .collect();
+                Ok(Arc::new(ProjectionExec::try_new(exprs, input)?)
+                    as Arc<dyn ExecutionPlan>)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if plans.is_empty() {
+            let fields = projection
+                .iter()
+                .map(|index| self.schema.field(*index).clone())
+                .collect();
+            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::new(fields)))));
+        }
+        Ok(Arc::new(UnionExec::try_new(plans)?))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn partition_columns(&self) -> Vec<String> {
+        self.partition_columns.clone()
+    }
+
+    fn prune_partitions(&self, filter: &Expr) -> Result<Option<Arc<dyn TableProvider>>> {
+        let schema = Arc::new(Schema::new(
+            self.partition_columns
+                .iter()
+                .map(|column| Field::new(column, DataType::Utf8, false))
+                .collect(),
+        ));
+        let predicate = DefaultPhysicalPlanner::default().create_physical_expr(
+            filter,
+            &schema,
+            &ExecutionContextState::new(),
+        )?;
+
+        let mut partitions = vec![];
+        for partition in &self.partitions {
+            let columns = partition
+                .values
+                .iter()
+                .map(|value| {
+                    Arc::new(StringArray::from(vec![value.as_str()])) as ArrayRef
+                })
+                .collect();
+            let batch = RecordBatch::try_new(schema.clone(), columns)?;
+            let result = match predicate.evaluate(&batch)? {
+                ColumnarValue::Array(array) => array,
+                ColumnarValue::Scalar(scalar) => scalar.to_array_of_size(1),
+            };
+            let result =
+                result
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(format!(
+                            "Partition filter {:?} is not a boolean expression",
+                            filter
+                        ))
+                    })?;
+            // partitions for which the filter is NULL have no matching rows
+            if result.is_valid(0) && result.value(0) {
+                partitions.push(partition.clone());
+            }
+        }
+        Ok(Some(Arc::new(PartitionedTable {
+            schema: self.schema.clone(),
+            partition_columns: self.partition_columns.clone(),
+            partitions,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::datasource::csv::{CsvFile, CsvReadOptions};
+    use crate::execution::context::ExecutionContext;
+    use crate::logical_plan::{col as column, lit as literal};
+    use crate::test::create_hive_partitioned_csv;
+
+    fn partitioned_table(path: &Path) -> Result<PartitionedTable> {
+        PartitionedTable::try_new(path.to_str().unwrap(), |path| {
+            Ok(Arc::new(CsvFile::try_new(path, CsvReadOptions::new())?))
+        })
+    }
+
+    #[tokio::test]
+    async fn scan_partition_columns() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_hive_partitioned_csv(dir.path())?;
+        let table = partitioned_table(dir.path())?;
+        assert_eq!(table.partition_columns(), vec!["year", "month"]);
+        assert_eq!(table.num_partitions(), 3);
+        assert_eq!(
+            crate::test::columns(&table.schema()),
+            vec!["a", "b", "year", "month"]
+        );
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", Arc::new(table))?;
+        let results = ctx
+            .sql("SELECT a, year, month FROM t ORDER BY a")?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+---+------+-------+",
+            "| a | year | month |",
+            "+---+------+-------+",
+            "| 1 | 2020 | 01    |",
+            "| 2 | 2020 | 01    |",
+            "| 3 | 2020 | 02    |",
+            "| 4 | 2021 | 01    |",
+            "+---+------+-------+",
+        ];
+        assert_batches_eq!(expected, &results);
+
+        // only partition columns
+        let results = ctx
+            .sql("SELECT month FROM t WHERE year = '2020' ORDER BY month")?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+-------+",
+            "| month |",
+            "+-------+",
+            "| 01    |",
+            "| 01    |",
+            "| 02    |",
+            "+-------+",
+        ];
+        assert_batches_eq!(expected, &results);
+        Ok(())
+    }
+
+    #[test]
+    fn prune_partitions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_hive_partitioned_csv(dir.path())?;
+        let table = partitioned_table(dir.path())?;
+
+        let filter = column("year")
+            .eq(literal("2020"))
+            .and(column("month").eq(literal("02")));
+        let pruned = table.prune_partitions(&filter)?.unwrap();
+        let pruned = pruned.as_any().downcast_ref::<PartitionedTable>().unwrap();
+        assert_eq!(pruned.num_partitions(), 1);
+        assert_eq!(pruned.schema(), table.schema());
+
+        let pruned = table.prune_partitions(&column("year").eq(literal("2019")))?;
+        let pruned = pruned.unwrap();
+        let pruned = pruned.as_any().downcast_ref::<PartitionedTable>().unwrap();
+        assert_eq!(pruned.num_partitions(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn mismatched_partition_columns() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_hive_partitioned_csv(dir.path())?;
+        let other = dir.path().join("month=03");
+        fs::create_dir(&other)?;
+        fs::write(other.join("0.csv"), "a,b\n5,v\n")?;
+
+        let err = partitioned_table(dir.path()).err().unwrap();
+        assert!(matches!(err, DataFusionError::Plan(_)));
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A table reading a stream of record batches, see
+//! [`crate::physical_plan::stream`].
+
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use futures::stream::{Stream, StreamExt};
+
+use crate::datasource::datasource::Statistics;
+use crate::datasource::TableProvider;
+use crate::error::Result;
+use crate::logical_plan::Expr;
+use crate::physical_plan::stream::{SharedStream, StreamExec};
+use crate::physical_plan::ExecutionPlan;
+
+/// A table whose record batches are read from a stream. As the stream can
+/// only be read once, so can the table.
+pub struct StreamTable {
+    schema: SchemaRef,
+    stream: SharedStream,
+}
+
+impl StreamTable {
+    /// Initialize a new `StreamTable` reading the batches of `stream`, which
+    /// must be of the schema `schema`
+    pub fn new(
+        schema: SchemaRef,
+        stream: impl Stream<Item = ArrowResult<RecordBatch>> + Send + 'static,
+    ) -> Self {
+        Self {
+            schema,
+            stream: Arc::new(Mutex::new(Some(stream.boxed()))),
+        }
+    }
+}
+
+impl TableProvider for StreamTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: &Option<Vec<usize>>,
+        _batch_size: usize,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(StreamExec::new(
+            self.stream.clone(),
+            self.schema.clone(),
+            projection.clone(),
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
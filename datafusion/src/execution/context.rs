@@ -26,9 +26,10 @@ use crate::{
     },
     physical_optimizer::optimizer::PhysicalOptimizerRule,
 };
-use log::debug;
+use log::{debug, trace};
+use std::any::Any;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::string::String;
 use std::sync::Arc;
 use std::{
@@ -36,10 +37,13 @@ use std::{
     sync::Mutex,
 };
 
-use futures::{StreamExt, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
 use tokio::task::{self, JoinHandle};
 
 use arrow::csv;
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
 
 use crate::catalog::{
     catalog::{CatalogProvider, MemoryCatalogProvider},
@@ -48,28 +52,46 @@ use crate::catalog::{
 };
 use crate::datasource::csv::CsvFile;
 use crate::datasource::parquet::ParquetTable;
-use crate::datasource::TableProvider;
+use crate::datasource::stream::StreamTable;
+use crate::datasource::{MemTable, TableProvider};
 use crate::error::{DataFusionError, Result};
 use crate::execution::dataframe_impl::DataFrameImpl;
+use crate::execution::extensions::Extensions;
+use crate::execution::function_factory::{FunctionFactory, RegisterFunction};
+use crate::execution::plan_cache::{self, PlanCache};
+use crate::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
 use crate::logical_plan::{
     FunctionRegistry, LogicalPlan, LogicalPlanBuilder, ToDFSchema,
 };
 use crate::optimizer::constant_folding::ConstantFolding;
 use crate::optimizer::filter_push_down::FilterPushDown;
 use crate::optimizer::limit_push_down::LimitPushDown;
-use crate::optimizer::optimizer::OptimizerRule;
+use crate::optimizer::merge_filters::MergeFilters;
+use crate::optimizer::merge_projections::MergeProjections;
+use crate::optimizer::optimizer::{Optimizer, OptimizerRule};
+use crate::optimizer::or_to_in_list::OrToInList;
+use crate::optimizer::partition_pruning::PartitionPruning;
 use crate::optimizer::projection_push_down::ProjectionPushDown;
+use crate::physical_optimizer::aggregate_push_down::AggregatePushDown;
 use crate::physical_optimizer::coalesce_batches::CoalesceBatches;
+use crate::physical_optimizer::deterministic::DeterministicExecution;
 use crate::physical_optimizer::merge_exec::AddMergeExec;
+use crate::physical_optimizer::pipeline_checker::PipelineChecker;
 use crate::physical_optimizer::repartition::Repartition;
+use crate::physical_optimizer::runtime_filter::RuntimeFilterPushDown;
 
+use crate::physical_plan::adaptive::ReplanHook;
+use crate::physical_plan::async_udf::AsyncScalarUDF;
+use crate::physical_plan::coalesce_batches::BatchSizePolicy;
 use crate::physical_plan::csv::CsvReadOptions;
-use crate::physical_plan::planner::DefaultPhysicalPlanner;
+use crate::physical_plan::hash_utils::HashSeeds;
+use crate::physical_plan::planner::{DefaultPhysicalPlanner, ExtensionPlanner};
 use crate::physical_plan::udf::ScalarUDF;
+use crate::physical_plan::udtf::TableUDF;
 use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::PhysicalPlanner;
 use crate::sql::{
-    parser::{DFParser, FileType},
+    parser::{CreateFunction, DFParser, DropFunction, FileType, Statement},
     planner::{ContextProvider, SqlToRel},
 };
 use crate::variable::{VarProvider, VarType};
@@ -157,17 +179,50 @@ impl ExecutionContext {
             state: Arc::new(Mutex::new(ExecutionContextState {
                 catalog_list,
                 scalar_functions: HashMap::new(),
+                table_functions: HashMap::new(),
                 var_provider: HashMap::new(),
                 aggregate_functions: HashMap::new(),
+                runtime_env: Arc::new(RuntimeEnv::new(config.runtime_config.clone())),
+                plan_cache: Arc::new(PlanCache::new(config.plan_cache_size)),
                 config,
                 execution_props: ExecutionProps::new(),
             })),
         }
     }
 
+    /// Returns the runtime environment shared by all queries of this context
+    pub fn runtime_env(&self) -> Arc<RuntimeEnv> {
+        self.state.lock().unwrap().runtime_env.clone()
+    }
+
     /// Creates a dataframe that will execute a SQL query.
+    ///
+    /// If the context has a plan cache (see
+    /// [`ExecutionConfig::with_plan_cache_size`]), the optimized plans of
+    /// queries are cached and reused when the same query is issued again.
     pub fn sql(&mut self, sql: &str) -> Result<Arc<dyn DataFrame>> {
-        let plan = self.create_logical_plan(sql)?;
+        let plan_cache = self.state.lock().unwrap().plan_cache.clone();
+        let catalog_version = plan_cache.catalog_version();
+        if let Some(plan) = plan_cache.get(sql) {
+            return Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)));
+        }
+
+        let statement = Self::parse_single_statement(sql)?;
+        match statement {
+            Statement::CreateFunction(ref create) => {
+                self.create_function(create)?;
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                return Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)));
+            }
+            Statement::DropFunction(ref drop) => {
+                self.drop_function(drop)?;
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                return Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)));
+            }
+            _ => {}
+        }
+
+        let plan = self.statement_to_plan(&statement)?;
         match plan {
             LogicalPlan::CreateExternalTable {
                 ref schema,
@@ -198,29 +253,123 @@ impl ExecutionContext {
                 ))),
             },
 
-            plan => Ok(Arc::new(DataFrameImpl::new(
-                self.state.clone(),
-                &self.optimize(&plan)?,
-            ))),
+            LogicalPlan::SetVariable {
+                ref variable,
+                ref value,
+                ..
+            } => {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.config.set(variable, value)?;
+                    state.plan_cache.invalidate();
+                    // the runtime environment is shared by all queries, so
+                    // changes to it apply to queries planned from now on
+                    if matches!(
+                        variable.to_lowercase().as_str(),
+                        "memory_limit" | "local_dirs"
+                    ) {
+                        state.runtime_env = Arc::new(RuntimeEnv::new(
+                            state.config.runtime_config.clone(),
+                        ));
+                    }
+                }
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
+            plan => {
+                let optimized = self.optimize(&plan)?;
+                if plan_cache::is_cacheable(&plan) {
+                    plan_cache.insert(sql, catalog_version, &optimized);
+                }
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &optimized)))
+            }
         }
     }
 
+    /// Drops all plans cached by [`ExecutionContext::sql`].
+    ///
+    /// Registering tables, functions, catalogs or variable providers
+    /// through the context, as well as changing its configuration with
+    /// `SET`, does so automatically. This needs to be called after
+    /// changing catalogs, schemas or tables in other ways, e.g. by
+    /// registering a table with a [`SchemaProvider`] directly.
+    pub fn invalidate_plan_cache(&self) {
+        self.state.lock().unwrap().plan_cache.invalidate();
+    }
+
     /// Creates a logical plan.
     ///
     /// This function is intended for internal use and should not be called directly.
     pub fn create_logical_plan(&self, sql: &str) -> Result<LogicalPlan> {
-        let statements = DFParser::parse_sql(sql)?;
+        let statement = Self::parse_single_statement(sql)?;
+        self.statement_to_plan(&statement)
+    }
+
+    fn parse_single_statement(sql: &str) -> Result<Statement> {
+        let mut statements = DFParser::parse_sql(sql)?;
 
         if statements.len() != 1 {
             return Err(DataFusionError::NotImplemented(
                 "The context currently only supports a single SQL statement".to_string(),
             ));
         }
+        Ok(statements.pop().unwrap())
+    }
 
+    fn statement_to_plan(&self, statement: &Statement) -> Result<LogicalPlan> {
         // create a query planner
         let state = self.state.lock().unwrap().clone();
         let query_planner = SqlToRel::new(&state);
-        query_planner.statement_to_plan(&statements[0])
+        query_planner.statement_to_plan(statement)
+    }
+
+    /// Registers the function created by the configured [`FunctionFactory`]
+    /// for a `CREATE FUNCTION` statement
+    fn create_function(&mut self, create: &CreateFunction) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let factory = state.config.function_factory.clone().ok_or_else(|| {
+            DataFusionError::Plan(
+                "CREATE FUNCTION requires a FunctionFactory, see \
+                 ExecutionConfig::with_function_factory"
+                    .to_string(),
+            )
+        })?;
+        if !create.or_replace
+            && (state.scalar_functions.contains_key(&create.name)
+                || state.aggregate_functions.contains_key(&create.name))
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Function '{}' already exists",
+                create.name
+            )));
+        }
+
+        match factory.create(&state, create)? {
+            RegisterFunction::Scalar(udf) => {
+                state.remove_udaf(&create.name);
+                state.add_udf(udf);
+            }
+            RegisterFunction::Aggregate(udaf) => {
+                state.remove_udf(&create.name);
+                state.add_udaf(udaf);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deregisters the function of a `DROP FUNCTION` statement
+    fn drop_function(&mut self, drop: &DropFunction) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let udf = state.remove_udf(&drop.name);
+        let udaf = state.remove_udaf(&drop.name);
+        if udf.is_none() && udaf.is_none() && !drop.if_exists {
+            return Err(DataFusionError::Plan(format!(
+                "Function '{}' does not exist",
+                drop.name
+            )));
+        }
+        Ok(())
     }
 
     /// Registers a variable provider within this context.
@@ -229,11 +378,9 @@ impl ExecutionContext {
         variable_type: VarType,
         provider: Arc<dyn VarProvider + Send + Sync>,
     ) {
-        self.state
-            .lock()
-            .unwrap()
-            .var_provider
-            .insert(variable_type, provider);
+        let mut state = self.state.lock().unwrap();
+        state.var_provider.insert(variable_type, provider);
+        state.plan_cache.invalidate();
     }
 
     /// Registers a scalar UDF within this context.
@@ -244,11 +391,35 @@ impl ExecutionContext {
     /// `SELECT MY_FUNC(x)...` will look for a function named `"my_func"`
     /// `SELECT "my_FUNC"(x)` will look for a function named `"my_FUNC"`
     pub fn register_udf(&mut self, f: ScalarUDF) {
-        self.state
-            .lock()
-            .unwrap()
-            .scalar_functions
-            .insert(f.name.clone(), Arc::new(f));
+        self.state.lock().unwrap().add_udf(Arc::new(f));
+    }
+
+    /// Deregisters the scalar UDF named `name` along with its other names,
+    /// returning it if it was registered.
+    pub fn deregister_udf(&mut self, name: &str) -> Option<Arc<ScalarUDF>> {
+        self.state.lock().unwrap().remove_udf(name)
+    }
+
+    /// Registers an asynchronous scalar UDF within this context, with the
+    /// same naming rules as [`ExecutionContext::register_udf`].
+    ///
+    /// Async UDFs can be called in projections and filters, where they are
+    /// evaluated in batches of the UDF's batch size before the other
+    /// expressions, even if they are only reached conditionally.
+    pub fn register_async_udf(&mut self, f: AsyncScalarUDF) {
+        self.register_udf(f.into_scalar_udf())
+    }
+
+    /// Registers a table UDF within this context, with the same naming rules
+    /// as [`ExecutionContext::register_udf`]. Built-in table functions such
+    /// as `generate_series` take precedence over table UDFs of the same name.
+    ///
+    /// `SELECT * FROM my_func(1, 'a')` plans a scan of the table returned by
+    /// `my_func` for the literal arguments `1` and `'a'`.
+    pub fn register_table_function(&mut self, f: TableUDF) {
+        let mut state = self.state.lock().unwrap();
+        state.table_functions.insert(f.name.clone(), Arc::new(f));
+        state.plan_cache.invalidate();
     }
 
     /// Registers an aggregate UDF within this context.
@@ -259,11 +430,13 @@ impl ExecutionContext {
     /// `SELECT MY_UDAF(x)...` will look for an aggregate named `"my_udaf"`
     /// `SELECT "my_UDAF"(x)` will look for an aggregate named `"my_UDAF"`
     pub fn register_udaf(&mut self, f: AggregateUDF) {
-        self.state
-            .lock()
-            .unwrap()
-            .aggregate_functions
-            .insert(f.name.clone(), Arc::new(f));
+        self.state.lock().unwrap().add_udaf(Arc::new(f));
+    }
+
+    /// Deregisters the aggregate UDF named `name` along with its other names,
+    /// returning it if it was registered.
+    pub fn deregister_udaf(&mut self, name: &str) -> Option<Arc<AggregateUDF>> {
+        self.state.lock().unwrap().remove_udaf(name)
     }
 
     /// Creates a DataFrame for reading a CSV data source.
@@ -282,12 +455,8 @@ impl ExecutionContext {
     pub fn read_parquet(&mut self, filename: &str) -> Result<Arc<dyn DataFrame>> {
         Ok(Arc::new(DataFrameImpl::new(
             self.state.clone(),
-            &LogicalPlanBuilder::scan_parquet(
-                &filename,
-                None,
-                self.state.lock().unwrap().config.concurrency,
-            )?
-            .build()?,
+            &LogicalPlanBuilder::scan("", Arc::new(self.parquet_table(filename)?), None)?
+                .build()?,
         )))
     }
 
@@ -326,14 +495,39 @@ impl ExecutionContext {
     /// Registers a Parquet data source so that it can be referenced from SQL statements
     /// executed against this context.
     pub fn register_parquet(&mut self, name: &str, filename: &str) -> Result<()> {
-        let table = ParquetTable::try_new(
-            &filename,
-            self.state.lock().unwrap().config.concurrency,
-        )?;
+        let table = self.parquet_table(filename)?;
         self.register_table(name, Arc::new(table))?;
         Ok(())
     }
 
+    /// Registers a single record batch as a table so that it can be referenced
+    /// from SQL statements executed against this context.
+    pub fn register_batch(&mut self, name: &str, batch: RecordBatch) -> Result<()> {
+        let table = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+        self.register_table(name, Arc::new(table))?;
+        Ok(())
+    }
+
+    /// Registers a stream of record batches of the schema `schema` as a table
+    /// so that it can be referenced from SQL statements executed against this
+    /// context. As the stream is consumed by the first query of the table,
+    /// the table can only be queried once.
+    pub fn register_stream(
+        &mut self,
+        name: &str,
+        schema: SchemaRef,
+        stream: impl Stream<Item = ArrowResult<RecordBatch>> + Send + 'static,
+    ) -> Result<()> {
+        self.register_table(name, Arc::new(StreamTable::new(schema, stream)))?;
+        Ok(())
+    }
+
+    fn parquet_table(&self, filename: &str) -> Result<ParquetTable> {
+        let state = self.state.lock().unwrap();
+        Ok(ParquetTable::try_new(&filename, state.config.concurrency)?
+            .with_enable_pruning(state.config.parquet_pruning))
+    }
+
     /// Registers a named catalog using a custom `CatalogProvider` so that
     /// it can be referenced from SQL statements executed against this
     /// context.
@@ -357,6 +551,7 @@ impl ExecutionContext {
             catalog
         };
 
+        state.plan_cache.invalidate();
         state.catalog_list.register_catalog(name, catalog)
     }
 
@@ -377,9 +572,9 @@ impl ExecutionContext {
         provider: Arc<dyn TableProvider>,
     ) -> Result<Option<Arc<dyn TableProvider>>> {
         let table_ref = table_ref.into();
-        self.state
-            .lock()
-            .unwrap()
+        let state = self.state.lock().unwrap();
+        state.plan_cache.invalidate();
+        state
             .schema_for_ref(table_ref)?
             .register_table(table_ref.table().to_owned(), provider)
     }
@@ -392,9 +587,9 @@ impl ExecutionContext {
         table_ref: impl Into<TableReference<'a>>,
     ) -> Result<Option<Arc<dyn TableProvider>>> {
         let table_ref = table_ref.into();
-        self.state
-            .lock()
-            .unwrap()
+        let state = self.state.lock().unwrap();
+        state.plan_cache.invalidate();
+        state
             .schema_for_ref(table_ref)?
             .deregister_table(table_ref.table())
     }
@@ -458,15 +653,19 @@ impl ExecutionContext {
     pub fn optimize(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
         let state = &mut self.state.lock().unwrap();
         let execution_props = &mut state.execution_props.clone();
+        execution_props.safe_casts = state.config.safe_casts;
         let optimizers = &state.config.optimizers;
 
-        let execution_props = execution_props.start_execution();
+        let execution_props =
+            execution_props.start_execution_at(state.config.time_source.now());
 
-        let mut new_plan = plan.clone();
         debug!("Logical plan:\n {:?}", plan);
-        for optimizer in optimizers {
-            new_plan = optimizer.optimize(&new_plan, execution_props)?;
-        }
+        let new_plan = Optimizer::new(optimizers.clone())
+            .with_max_passes(state.config.max_optimizer_passes)
+            .with_skip_failed_rules(state.config.skip_failed_optimizer_rules)
+            .optimize(plan, execution_props, |plan, rule| {
+                trace!("Logical plan after {}:\n {:?}", rule.name(), plan)
+            })?;
         debug!("Optimized logical plan:\n {:?}", new_plan);
         Ok(new_plan)
     }
@@ -477,7 +676,8 @@ impl ExecutionContext {
         logical_plan: &LogicalPlan,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         let mut state = self.state.lock().unwrap();
-        state.execution_props.start_execution();
+        let now = state.config.time_source.now();
+        state.execution_props.start_execution_at(now);
 
         state
             .config
@@ -577,6 +777,10 @@ impl FunctionRegistry for ExecutionContext {
         self.state.lock().unwrap().udfs()
     }
 
+    fn udafs(&self) -> HashSet<String> {
+        self.state.lock().unwrap().udafs()
+    }
+
     fn udf(&self, name: &str) -> Result<Arc<ScalarUDF>> {
         self.state.lock().unwrap().udf(name)
     }
@@ -584,6 +788,25 @@ impl FunctionRegistry for ExecutionContext {
     fn udaf(&self, name: &str) -> Result<Arc<AggregateUDF>> {
         self.state.lock().unwrap().udaf(name)
     }
+
+    fn register_udf(&mut self, udf: Arc<ScalarUDF>) -> Result<Option<Arc<ScalarUDF>>> {
+        FunctionRegistry::register_udf(&mut *self.state.lock().unwrap(), udf)
+    }
+
+    fn register_udaf(
+        &mut self,
+        udaf: Arc<AggregateUDF>,
+    ) -> Result<Option<Arc<AggregateUDF>>> {
+        FunctionRegistry::register_udaf(&mut *self.state.lock().unwrap(), udaf)
+    }
+
+    fn deregister_udf(&mut self, name: &str) -> Result<Option<Arc<ScalarUDF>>> {
+        FunctionRegistry::deregister_udf(&mut *self.state.lock().unwrap(), name)
+    }
+
+    fn deregister_udaf(&mut self, name: &str) -> Result<Option<Arc<AggregateUDF>>> {
+        FunctionRegistry::deregister_udaf(&mut *self.state.lock().unwrap(), name)
+    }
 }
 
 /// A planner used to add extensions to DataFusion logical and physical plans.
@@ -600,13 +823,16 @@ pub trait QueryPlanner {
 struct DefaultQueryPlanner {}
 
 impl QueryPlanner for DefaultQueryPlanner {
-    /// Given a `LogicalPlan`, create an `ExecutionPlan` suitable for execution
+    /// Given a `LogicalPlan`, create an `ExecutionPlan` suitable for execution,
+    /// planning user-defined nodes with the configured extension planners
     fn create_physical_plan(
         &self,
         logical_plan: &LogicalPlan,
         ctx_state: &ExecutionContextState,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let planner = DefaultPhysicalPlanner::default();
+        let planner = DefaultPhysicalPlanner::with_extension_planners(
+            ctx_state.config.extension_planners.clone(),
+        );
         planner.create_physical_plan(logical_plan, ctx_state)
     }
 }
@@ -614,16 +840,30 @@ impl QueryPlanner for DefaultQueryPlanner {
 /// Configuration options for execution context
 #[derive(Clone)]
 pub struct ExecutionConfig {
-    /// Number of concurrent threads for query execution.
+    /// Number of concurrent threads for query execution, which is also the
+    /// number of partitions operators repartition their input into.
     pub concurrency: usize,
     /// Default batch size when reading data sources
     pub batch_size: usize,
     /// Responsible for optimizing a logical plan
     optimizers: Vec<Arc<dyn OptimizerRule + Send + Sync>>,
+    /// Maximum number of passes over all logical optimizer rules
+    max_optimizer_passes: usize,
+    /// Whether logical optimizer rules that fail are skipped
+    skip_failed_optimizer_rules: bool,
     /// Responsible for optimizing a physical execution plan
     pub physical_optimizers: Vec<Arc<dyn PhysicalOptimizerRule + Send + Sync>>,
     /// Responsible for planning `LogicalPlan`s, and `ExecutionPlan`
     query_planner: Arc<dyn QueryPlanner + Send + Sync>,
+    /// Responsible for planning user-defined logical nodes with the default
+    /// query planner
+    extension_planners: Vec<Arc<dyn ExtensionPlanner + Send + Sync>>,
+    /// Responsible for creating the functions of `CREATE FUNCTION` statements
+    function_factory: Option<Arc<dyn FunctionFactory + Send + Sync>>,
+    /// Clock queries read their start time, e.g. of `now()`, from
+    time_source: Arc<dyn TimeSource>,
+    /// Maximum number of optimized plans cached by `ExecutionContext::sql`
+    plan_cache_size: usize,
     /// Default catalog name for table resolution
     default_catalog: String,
     /// Default schema name for table resolution
@@ -639,6 +879,39 @@ pub struct ExecutionConfig {
     /// Should DataFusion repartition data using the aggregate keys to execute aggregates in parallel
     /// using the provided `concurrency` level
     pub repartition_aggregations: bool,
+    /// Number of shards the groups of every partition of grouped aggregations
+    /// are split into
+    pub aggregation_shards: usize,
+    /// Should DataFusion skip row groups of parquet files that can not match
+    /// the filters of a scan, based on the statistics of the row groups
+    pub parquet_pruning: bool,
+    /// How operators that combine small batches choose the size of the
+    /// batches they emit
+    pub coalesce_policy: BatchSizePolicy,
+    /// Should queries produce the same batches in the same order every time
+    /// they are executed over the same data
+    pub deterministic: bool,
+    /// Seeds rows are hashed with to assign them to partitions
+    pub hash_seeds: HashSeeds,
+    /// Request-scoped values embedders pass to their own extension planners,
+    /// physical optimizer rules and operators
+    pub extensions: Extensions,
+    /// Configuration of the runtime environment shared by all queries
+    runtime_config: RuntimeConfig,
+    /// Should casts return NULL for values that overflow or lose precision
+    /// in the target type, rather than erroring
+    pub safe_casts: bool,
+    /// Should the progress of every partition of every operator of the
+    /// physical plan be monitored, see
+    /// [`monitor_plan`](crate::physical_plan::monitor::monitor_plan)
+    pub plan_monitoring: bool,
+    /// Asked to re-plan the rest of the plan based on observed cardinalities,
+    /// see [`ReplanHook`]
+    pub replan_hook: Option<Arc<dyn ReplanHook>>,
+    /// Should DataFusion emit `tracing` spans for every partition of every
+    /// operator of the physical plan
+    #[cfg(feature = "tracing")]
+    pub tracing: bool,
 }
 
 impl ExecutionConfig {
@@ -649,24 +922,50 @@ impl ExecutionConfig {
             batch_size: 8192,
             optimizers: vec![
                 Arc::new(ConstantFolding::new()),
+                Arc::new(OrToInList::new()),
                 Arc::new(EliminateLimit::new()),
+                Arc::new(MergeFilters::new()),
+                Arc::new(MergeProjections::new()),
                 Arc::new(ProjectionPushDown::new()),
                 Arc::new(FilterPushDown::new()),
+                Arc::new(PartitionPruning::new()),
                 Arc::new(HashBuildProbeOrder::new()),
                 Arc::new(LimitPushDown::new()),
             ],
+            max_optimizer_passes: 1,
+            skip_failed_optimizer_rules: false,
             physical_optimizers: vec![
+                Arc::new(AggregatePushDown::new()),
                 Arc::new(CoalesceBatches::new()),
                 Arc::new(Repartition::new()),
                 Arc::new(AddMergeExec::new()),
+                Arc::new(DeterministicExecution::new()),
+                Arc::new(PipelineChecker::new()),
+                Arc::new(RuntimeFilterPushDown::new()),
             ],
             query_planner: Arc::new(DefaultQueryPlanner {}),
+            extension_planners: vec![],
+            function_factory: None,
+            time_source: Arc::new(SystemTimeSource {}),
+            plan_cache_size: 0,
             default_catalog: "datafusion".to_owned(),
             default_schema: "public".to_owned(),
             create_default_catalog_and_schema: true,
             information_schema: false,
             repartition_joins: true,
             repartition_aggregations: true,
+            aggregation_shards: 1,
+            parquet_pruning: true,
+            coalesce_policy: BatchSizePolicy::Rows,
+            deterministic: false,
+            hash_seeds: HashSeeds::default(),
+            extensions: Extensions::new(),
+            runtime_config: RuntimeConfig::new(),
+            safe_casts: false,
+            plan_monitoring: false,
+            replan_hook: None,
+            #[cfg(feature = "tracing")]
+            tracing: false,
         }
     }
 
@@ -678,6 +977,12 @@ impl ExecutionConfig {
         self
     }
 
+    /// Customize the number of partitions operators repartition their input
+    /// into, the same as [`with_concurrency`](Self::with_concurrency)
+    pub fn with_target_partitions(self, n: usize) -> Self {
+        self.with_concurrency(n)
+    }
+
     /// Customize batch size
     pub fn with_batch_size(mut self, n: usize) -> Self {
         // batch size must be greater than zero
@@ -686,6 +991,40 @@ impl ExecutionConfig {
         self
     }
 
+    /// Customize the runtime environment, such as the directories to spill to
+    pub fn with_runtime_config(mut self, runtime_config: RuntimeConfig) -> Self {
+        self.runtime_config = runtime_config;
+        self
+    }
+
+    /// Count the rows every partition of every operator of the physical plan
+    /// produced so far, so that they can be reported by a
+    /// [`snapshot`](crate::physical_plan::monitor::snapshot) of the plan
+    /// while it is running.
+    pub fn with_plan_monitoring(mut self, enabled: bool) -> Self {
+        self.plan_monitoring = enabled;
+        self
+    }
+
+    /// Experimental: let `replan_hook` replace the parts of physical plans
+    /// that did not execute yet, based on the cardinalities observed by the
+    /// operators that did, e.g. to swap the sides of a hash join whose build
+    /// side turned out to be large. See
+    /// [`SwapJoinSides`](crate::physical_plan::adaptive::SwapJoinSides).
+    pub fn with_replan_hook(mut self, replan_hook: Arc<dyn ReplanHook>) -> Self {
+        self.replan_hook = Some(replan_hook);
+        self
+    }
+
+    /// Emit a `tracing` span for every partition of every operator of the
+    /// physical plan. The span records the id of the operator within the
+    /// plan, the partition and the number of rows and bytes produced.
+    #[cfg(feature = "tracing")]
+    pub fn with_tracing(mut self, enabled: bool) -> Self {
+        self.tracing = enabled;
+        self
+    }
+
     /// Replace the default query planner
     pub fn with_query_planner(
         mut self,
@@ -713,6 +1052,27 @@ impl ExecutionConfig {
         self
     }
 
+    /// Adds a new [`ExtensionPlanner`] the default query planner uses to plan
+    /// [`LogicalPlan::Extension`] nodes. Planners are tried in the order they
+    /// were added, the first one to return a plan for a node is used.
+    pub fn add_extension_planner(
+        mut self,
+        extension_planner: Arc<dyn ExtensionPlanner + Send + Sync>,
+    ) -> Self {
+        self.extension_planners.push(extension_planner);
+        self
+    }
+
+    /// Set the [`FunctionFactory`] creating the functions of
+    /// `CREATE FUNCTION` statements, which are not supported without one
+    pub fn with_function_factory(
+        mut self,
+        function_factory: Arc<dyn FunctionFactory + Send + Sync>,
+    ) -> Self {
+        self.function_factory = Some(function_factory);
+        self
+    }
+
     /// Adds a new [`PhysicalOptimizerRule`]
     pub fn add_physical_optimizer_rule(
         mut self,
@@ -722,6 +1082,101 @@ impl ExecutionConfig {
         self
     }
 
+    /// Replace the logical optimizer rules
+    pub fn with_optimizer_rules(
+        mut self,
+        optimizers: Vec<Arc<dyn OptimizerRule + Send + Sync>>,
+    ) -> Self {
+        self.optimizers = optimizers;
+        self
+    }
+
+    /// Returns the logical optimizer rules, in the order they are applied
+    pub fn optimizer_rules(&self) -> &[Arc<dyn OptimizerRule + Send + Sync>] {
+        &self.optimizers
+    }
+
+    /// Customize the maximum number of passes over all logical optimizer
+    /// rules. Optimization stops early once a pass does not change the plan.
+    /// At least one pass is made, `0` is treated as `1`.
+    pub fn with_max_optimizer_passes(mut self, n: usize) -> Self {
+        self.max_optimizer_passes = n.max(1);
+        self
+    }
+
+    /// Skip logical optimizer rules that fail, logging a warning, instead of
+    /// failing the query
+    pub fn with_skip_failed_optimizer_rules(mut self, skip: bool) -> Self {
+        self.skip_failed_optimizer_rules = skip;
+        self
+    }
+
+    /// Removes all [`OptimizerRule`]s with the given name
+    pub fn remove_optimizer_rule(mut self, name: &str) -> Self {
+        self.optimizers.retain(|rule| rule.name() != name);
+        self
+    }
+
+    /// Removes all [`PhysicalOptimizerRule`]s with the given name
+    pub fn remove_physical_optimizer_rule(mut self, name: &str) -> Self {
+        self.physical_optimizers.retain(|rule| rule.name() != name);
+        self
+    }
+
+    /// Replaces the [`OptimizerRule`]s with the given name by `optimizer_rule`,
+    /// keeping its position in the list of rules.
+    ///
+    /// Returns an error if no rule with this name is registered.
+    pub fn replace_optimizer_rule(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn OptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let mut found = false;
+        for rule in self
+            .optimizers
+            .iter_mut()
+            .filter(|rule| rule.name() == name)
+        {
+            *rule = optimizer_rule.clone();
+            found = true;
+        }
+        if !found {
+            return Err(DataFusionError::Plan(format!(
+                "There is no optimizer rule named \"{}\"",
+                name
+            )));
+        }
+        Ok(self)
+    }
+
+    /// Replaces the [`PhysicalOptimizerRule`]s with the given name by
+    /// `optimizer_rule`, keeping its position in the list of rules.
+    ///
+    /// Returns an error if no rule with this name is registered.
+    pub fn replace_physical_optimizer_rule(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn PhysicalOptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let mut found = false;
+        for rule in self
+            .physical_optimizers
+            .iter_mut()
+            .filter(|rule| rule.name() == name)
+        {
+            *rule = optimizer_rule.clone();
+            found = true;
+        }
+        if !found {
+            return Err(DataFusionError::Plan(format!(
+                "There is no physical optimizer rule named \"{}\"",
+                name
+            )));
+        }
+        Ok(self)
+    }
+
     /// Selects a name for the default catalog and schema
     pub fn with_default_catalog_and_schema(
         mut self,
@@ -755,6 +1210,210 @@ impl ExecutionConfig {
         self.repartition_aggregations = enabled;
         self
     }
+
+    /// Customize the number of shards the groups of every partition of
+    /// grouped aggregations are split into, rounded up to a power of two.
+    /// The shards are updated concurrently, which speeds up aggregations
+    /// with very many groups.
+    pub fn with_aggregation_shards(mut self, n: usize) -> Self {
+        // the number of shards must be greater than zero
+        assert!(n > 0);
+        self.aggregation_shards = n;
+        self
+    }
+
+    /// Enables or disables pruning the row groups of parquet files
+    /// registered afterwards, based on the filters of a scan
+    pub fn with_parquet_pruning(mut self, enabled: bool) -> Self {
+        self.parquet_pruning = enabled;
+        self
+    }
+
+    /// Sets the policy used to choose the size of coalesced batches. With
+    /// [`BatchSizePolicy::Adaptive`] batches of wide rows are emitted with
+    /// fewer rows, so downstream operators see batches of similar memory size
+    pub fn with_coalesce_policy(mut self, policy: BatchSizePolicy) -> Self {
+        self.coalesce_policy = policy;
+        self
+    }
+
+    /// Enables or disables deterministic execution, intended for tests asserting
+    /// on the exact output of queries. Partitions are still executed in
+    /// parallel, but combined in partition order, and hash aggregations use
+    /// fixed seeds. Collecting a [`DataFrame`] merges its partitions in order.
+    pub fn with_deterministic(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        self
+    }
+
+    /// Replace the system clock queries read their start time and timeouts
+    /// from, e.g. on wasm32 hosts without a clock, or to make `now()`
+    /// deterministic
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Returns the clock queries read their start time and timeouts from
+    pub fn time_source(&self) -> &Arc<dyn TimeSource> {
+        &self.time_source
+    }
+
+    /// Cache the optimized plans of up to `n` distinct SQL queries, so that
+    /// queries issued repeatedly skip parsing, planning and optimization.
+    /// Disabled (zero) by default.
+    ///
+    /// Plans are keyed by their SQL, ignoring differences in whitespace,
+    /// and dropped when tables, functions or the configuration change.
+    /// Queries using `now()` are not cached.
+    pub fn with_plan_cache_size(mut self, n: usize) -> Self {
+        self.plan_cache_size = n;
+        self
+    }
+
+    /// Enables or disables safe casts. Casts between numeric types and
+    /// between timestamp units error on values that overflow or lose
+    /// precision in the target type by default, and return NULL for them
+    /// when enabled, as `TRY_CAST` always does.
+    pub fn with_safe_casts(mut self, enabled: bool) -> Self {
+        self.safe_casts = enabled;
+        self
+    }
+
+    /// Customize the seeds rows are hashed with to assign them to the
+    /// partitions of joins and aggregations. Processes using the same seeds
+    /// and number of partitions assign rows with equal keys to the same
+    /// partition, see [`HashSeeds`].
+    pub fn with_hash_seeds(mut self, hash_seeds: HashSeeds) -> Self {
+        self.hash_seeds = hash_seeds;
+        self
+    }
+
+    /// Add `extension` to the [`Extensions`] of this configuration, replacing
+    /// any previous value of the same type.
+    ///
+    /// Unlike globals, extensions are scoped to the contexts created with this
+    /// configuration. They are available to
+    /// [`ExtensionPlanner`](crate::physical_plan::planner::ExtensionPlanner)s and
+    /// [`PhysicalOptimizerRule`]s, which hand them to the operators they create.
+    pub fn with_extension<T: Any + Send + Sync>(mut self, extension: Arc<T>) -> Self {
+        self.extensions.insert(extension);
+        self
+    }
+
+    /// Sets the option named `variable` to `value`, as done by SQL
+    /// `SET variable = value` statements. The supported options are
+    ///
+    /// * `batch_size`: see [`with_batch_size`](Self::with_batch_size)
+    /// * `target_partitions`: see [`with_target_partitions`](Self::with_target_partitions)
+    /// * `repartition_joins`: see [`with_repartition_joins`](Self::with_repartition_joins)
+    /// * `repartition_aggregations`: see [`with_repartition_aggregations`](Self::with_repartition_aggregations)
+    /// * `aggregation_shards`: see [`with_aggregation_shards`](Self::with_aggregation_shards)
+    /// * `parquet_pruning`: see [`with_parquet_pruning`](Self::with_parquet_pruning)
+    /// * `deterministic`: see [`with_deterministic`](Self::with_deterministic)
+    /// * `coalesce_target_bytes`: switches to [`BatchSizePolicy::Adaptive`] with
+    ///   the given number of bytes, see [`with_coalesce_policy`](Self::with_coalesce_policy)
+    /// * `memory_limit`: the number of bytes available to operators, see
+    ///   [`RuntimeConfig::with_memory_limit`]
+    /// * `local_dirs`: comma separated list of directories to spill to, see
+    ///   [`RuntimeConfig::with_local_dirs`]
+    ///
+    /// Changes to `memory_limit` and `local_dirs` only take effect for
+    /// contexts created with this configuration afterwards, or when set
+    /// through SQL.
+    pub fn set(&mut self, variable: &str, value: &str) -> Result<()> {
+        match variable.to_lowercase().as_str() {
+            "batch_size" => {
+                self.batch_size = parse_positive(variable, value)?;
+            }
+            "target_partitions" => {
+                self.concurrency = parse_positive(variable, value)?;
+            }
+            "repartition_joins" => {
+                self.repartition_joins = parse_bool(variable, value)?;
+            }
+            "repartition_aggregations" => {
+                self.repartition_aggregations = parse_bool(variable, value)?;
+            }
+            "aggregation_shards" => {
+                self.aggregation_shards = parse_positive(variable, value)?;
+            }
+            "parquet_pruning" => {
+                self.parquet_pruning = parse_bool(variable, value)?;
+            }
+            "deterministic" => {
+                self.deterministic = parse_bool(variable, value)?;
+            }
+            "safe_casts" => {
+                self.safe_casts = parse_bool(variable, value)?;
+            }
+            "coalesce_target_bytes" => {
+                self.coalesce_policy = BatchSizePolicy::Adaptive {
+                    target_bytes: parse_positive(variable, value)?,
+                };
+            }
+            "memory_limit" => {
+                let max_memory = parse_positive(variable, value)?;
+                self.runtime_config = self
+                    .runtime_config
+                    .clone()
+                    .with_memory_limit(max_memory, 1.0);
+            }
+            "local_dirs" => {
+                let local_dirs = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from)
+                    .collect();
+                self.runtime_config =
+                    self.runtime_config.clone().with_local_dirs(local_dirs);
+            }
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unknown configuration option '{}'",
+                    variable
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_positive(variable: &str, value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(DataFusionError::Plan(format!(
+            "Configuration option '{}' must be a positive integer, got '{}'",
+            variable, value
+        ))),
+    }
+}
+
+fn parse_bool(variable: &str, value: &str) -> Result<bool> {
+    value.to_lowercase().parse::<bool>().map_err(|_| {
+        DataFusionError::Plan(format!(
+            "Configuration option '{}' must be true or false, got '{}'",
+            variable, value
+        ))
+    })
+}
+
+/// Source of the current time, which queries read their start time and
+/// timeouts from
+pub trait TimeSource: std::fmt::Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The time source reading the system clock
+#[derive(Debug)]
+pub struct SystemTimeSource {}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
 }
 
 /// Holds per-execution properties and data (such as starting timestamps, etc).
@@ -764,6 +1423,8 @@ impl ExecutionConfig {
 #[derive(Clone)]
 pub struct ExecutionProps {
     pub(crate) query_execution_start_time: DateTime<Utc>,
+    /// See [`ExecutionConfig::safe_casts`]
+    pub(crate) safe_casts: bool,
 }
 
 /// Execution context for registering data sources and executing queries
@@ -773,6 +1434,8 @@ pub struct ExecutionContextState {
     pub catalog_list: Arc<dyn CatalogList>,
     /// Scalar functions that are registered with the context
     pub scalar_functions: HashMap<String, Arc<ScalarUDF>>,
+    /// Table functions that are registered with the context
+    pub table_functions: HashMap<String, Arc<TableUDF>>,
     /// Variable provider that are registered with the context
     pub var_provider: HashMap<VarType, Arc<dyn VarProvider + Send + Sync>>,
     /// Aggregate functions registered in the context
@@ -781,6 +1444,10 @@ pub struct ExecutionContextState {
     pub config: ExecutionConfig,
     /// Execution properties
     pub execution_props: ExecutionProps,
+    /// Resources shared by all queries of the context
+    pub runtime_env: Arc<RuntimeEnv>,
+    /// Optimized plans of previously issued SQL queries
+    pub plan_cache: Arc<PlanCache>,
 }
 
 impl ExecutionProps {
@@ -788,12 +1455,18 @@ impl ExecutionProps {
     pub fn new() -> Self {
         ExecutionProps {
             query_execution_start_time: chrono::Utc::now(),
+            safe_casts: false,
         }
     }
 
     /// Marks the execution of query started timestamp
     pub fn start_execution(&mut self) -> &Self {
-        self.query_execution_start_time = chrono::Utc::now();
+        self.start_execution_at(chrono::Utc::now())
+    }
+
+    /// Marks the execution of query started at `time`
+    pub fn start_execution_at(&mut self, time: DateTime<Utc>) -> &Self {
+        self.query_execution_start_time = time;
         &*self
     }
 }
@@ -804,11 +1477,54 @@ impl ExecutionContextState {
         ExecutionContextState {
             catalog_list: Arc::new(MemoryCatalogList::new()),
             scalar_functions: HashMap::new(),
+            table_functions: HashMap::new(),
             var_provider: HashMap::new(),
             aggregate_functions: HashMap::new(),
             config: ExecutionConfig::new(),
             execution_props: ExecutionProps::new(),
+            runtime_env: Arc::new(RuntimeEnv::default()),
+            plan_cache: Arc::new(PlanCache::new(0)),
+        }
+    }
+
+    /// Registers `udf` under its name and aliases, returning the udf
+    /// previously registered under its name
+    fn add_udf(&mut self, udf: Arc<ScalarUDF>) -> Option<Arc<ScalarUDF>> {
+        self.plan_cache.invalidate();
+        for alias in &udf.aliases {
+            self.scalar_functions.insert(alias.clone(), udf.clone());
         }
+        self.scalar_functions.insert(udf.name.clone(), udf)
+    }
+
+    /// Registers `udaf` under its name and aliases, returning the udaf
+    /// previously registered under its name
+    fn add_udaf(&mut self, udaf: Arc<AggregateUDF>) -> Option<Arc<AggregateUDF>> {
+        self.plan_cache.invalidate();
+        for alias in &udaf.aliases {
+            self.aggregate_functions.insert(alias.clone(), udaf.clone());
+        }
+        self.aggregate_functions.insert(udaf.name.clone(), udaf)
+    }
+
+    /// Removes the udf registered under `name` from all the names it is
+    /// registered under
+    fn remove_udf(&mut self, name: &str) -> Option<Arc<ScalarUDF>> {
+        let udf = self.scalar_functions.remove(name)?;
+        self.plan_cache.invalidate();
+        self.scalar_functions
+            .retain(|_, registered| !Arc::ptr_eq(registered, &udf));
+        Some(udf)
+    }
+
+    /// Removes the udaf registered under `name` from all the names it is
+    /// registered under
+    fn remove_udaf(&mut self, name: &str) -> Option<Arc<AggregateUDF>> {
+        let udaf = self.aggregate_functions.remove(name)?;
+        self.plan_cache.invalidate();
+        self.aggregate_functions
+            .retain(|_, registered| !Arc::ptr_eq(registered, &udaf));
+        Some(udaf)
     }
 
     fn resolve_table_ref<'a>(
@@ -858,6 +1574,10 @@ impl ContextProvider for ExecutionContextState {
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
         self.aggregate_functions.get(name).cloned()
     }
+
+    fn get_table_function_meta(&self, name: &str) -> Option<Arc<TableUDF>> {
+        self.table_functions.get(name).cloned()
+    }
 }
 
 impl FunctionRegistry for ExecutionContextState {
@@ -865,6 +1585,29 @@ impl FunctionRegistry for ExecutionContextState {
         self.scalar_functions.keys().cloned().collect()
     }
 
+    fn udafs(&self) -> HashSet<String> {
+        self.aggregate_functions.keys().cloned().collect()
+    }
+
+    fn register_udf(&mut self, udf: Arc<ScalarUDF>) -> Result<Option<Arc<ScalarUDF>>> {
+        Ok(self.add_udf(udf))
+    }
+
+    fn register_udaf(
+        &mut self,
+        udaf: Arc<AggregateUDF>,
+    ) -> Result<Option<Arc<AggregateUDF>>> {
+        Ok(self.add_udaf(udaf))
+    }
+
+    fn deregister_udf(&mut self, name: &str) -> Result<Option<Arc<ScalarUDF>>> {
+        Ok(self.remove_udf(name))
+    }
+
+    fn deregister_udaf(&mut self, name: &str) -> Result<Option<Arc<AggregateUDF>>> {
+        Ok(self.remove_udaf(name))
+    }
+
     fn udf(&self, name: &str) -> Result<Arc<ScalarUDF>> {
         let result = self.scalar_functions.get(name);
 
@@ -892,13 +1635,19 @@ impl FunctionRegistry for ExecutionContextState {
 mod tests {
 
     use super::*;
+    use crate::execution::memory_pool::MemoryConsumer;
+    use crate::execution::query_limits::QueryLimits;
+    use crate::physical_plan::async_udf::AsyncScalarFunctionImplementation;
     use crate::physical_plan::functions::make_scalar_function;
-    use crate::physical_plan::{collect, collect_partitioned};
+    use crate::physical_plan::udtf::TableFunctionImplementation;
+    use crate::physical_plan::{collect, collect_partitioned, ColumnarValue};
+    use crate::scalar::ScalarValue;
     use crate::test;
     use crate::variable::VarType;
     use crate::{
         assert_batches_eq, assert_batches_sorted_eq,
-        logical_plan::{col, create_udf, sum, Expr},
+        logical_plan::{col, create_udf, return_type_of_argument, sum, Expr},
+        physical_plan::functions::{ReturnTypeFunction, Signature},
     };
     use crate::{
         datasource::{MemTable, TableType},
@@ -913,6 +1662,7 @@ mod tests {
     use arrow::compute::add;
     use arrow::datatypes::*;
     use arrow::record_batch::RecordBatch;
+    use futures::future::{BoxFuture, FutureExt};
     use std::fs::File;
     use std::thread::{self, JoinHandle};
     use std::{io::prelude::*, sync::Mutex};
@@ -1873,6 +2623,43 @@ mod tests {
         run_test_case::<UInt64Type>().await;
     }
 
+    #[tokio::test]
+    async fn dictionary_string_functions() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+
+        let dict_array: DictionaryArray<Int32Type> =
+            vec![Some("a"), Some("b"), None, Some("a")]
+                .into_iter()
+                .collect();
+        let dict_array = Arc::new(dict_array);
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "dict",
+            dict_array.data_type().clone(),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![dict_array])?;
+        let provider = MemTable::try_new(schema, vec![vec![batch]])?;
+        ctx.register_table("t", Arc::new(provider))?;
+
+        // string results stay dictionary-encoded
+        let results = plan_and_collect(
+            &mut ctx,
+            "SELECT upper(dict) AS u, arrow_typeof(upper(dict)) AS t, \
+             character_length(dict) AS l FROM t WHERE dict <> 'b'",
+        )
+        .await?;
+        let expected = vec![
+            "+---+-------------------------+---+",
+            "| u | t                       | l |",
+            "+---+-------------------------+---+",
+            "| A | Dictionary(Int32, Utf8) | 1 |",
+            "| A | Dictionary(Int32, Utf8) | 1 |",
+            "+---+-------------------------+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+        Ok(())
+    }
+
     async fn run_count_distinct_integers_aggregated_scenario(
         partitions: Vec<Vec<(&str, u64)>>,
     ) -> Result<Vec<RecordBatch>> {
@@ -2177,6 +2964,457 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn register_batch_and_stream() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["x", "y", "x"])),
+            ],
+        )?;
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_batch("t", batch.clone())?;
+        let stream = futures::stream::iter(vec![Ok(batch.clone()), Ok(batch)]);
+        ctx.register_stream("s", schema, stream)?;
+
+        let sql = "SELECT b, SUM(a) AS total FROM s GROUP BY b";
+        let results = plan_and_collect(&mut ctx, sql).await?;
+        let expected = vec![
+            "+---+-------+",
+            "| b | total |",
+            "+---+-------+",
+            "| x | 8     |",
+            "| y | 4     |",
+            "+---+-------+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        // the batch can be queried again, the stream was consumed
+        for _ in 0..2 {
+            let results = plan_and_collect(&mut ctx, "SELECT a, b FROM t").await?;
+            assert_eq!(results.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+        }
+        let err = plan_and_collect(&mut ctx, "SELECT a FROM s")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already read"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn plan_cache() -> Result<()> {
+        let mut ctx =
+            ExecutionContext::with_config(ExecutionConfig::new().with_plan_cache_size(2));
+        let plan_cache = ctx.state.lock().unwrap().plan_cache.clone();
+        ctx.register_table("t", test::table_with_sequence(1, 3)?)?;
+
+        let sql = "SELECT SUM(i) AS total FROM t";
+        let expected = vec![
+            "+-------+",
+            "| total |",
+            "+-------+",
+            "| 6     |",
+            "+-------+",
+        ];
+        let results = plan_and_collect(&mut ctx, sql).await?;
+        assert_batches_eq!(expected, &results);
+        assert_eq!(plan_cache.len(), 1);
+
+        // the cached plan is used regardless of whitespace
+        let results =
+            plan_and_collect(&mut ctx, "SELECT SUM(i) AS total\n  FROM t;").await?;
+        assert_batches_eq!(expected, &results);
+        assert_eq!(plan_cache.len(), 1);
+
+        // replacing the table drops the plans scanning the old one
+        ctx.register_table("t", test::table_with_sequence(1, 4)?)?;
+        assert!(plan_cache.is_empty());
+        let results = plan_and_collect(&mut ctx, sql).await?;
+        let expected = vec![
+            "+-------+",
+            "| total |",
+            "+-------+",
+            "| 10    |",
+            "+-------+",
+        ];
+        assert_batches_eq!(expected, &results);
+        assert_eq!(plan_cache.len(), 1);
+
+        // as does changing the configuration
+        plan_and_collect(&mut ctx, "SET batch_size = 1024").await?;
+        assert!(plan_cache.is_empty());
+
+        // queries folding in their start time are never cached
+        plan_and_collect(&mut ctx, "SELECT now()").await?;
+        assert!(plan_cache.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn user_defined_function_aliases() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 1)?)?;
+
+        let myfunc = make_scalar_function(|args: &[ArrayRef]| Ok(Arc::clone(&args[0])));
+        ctx.register_udf(
+            create_udf(
+                "my_func",
+                vec![DataType::Int32],
+                Arc::new(DataType::Int32),
+                myfunc,
+            )
+            .with_aliases(vec!["my_alias"]),
+        );
+        assert_eq!(
+            ctx.udfs(),
+            vec!["my_func".to_string(), "my_alias".to_string()]
+                .into_iter()
+                .collect()
+        );
+
+        let result = plan_and_collect(&mut ctx, "SELECT my_alias(i) FROM t").await?;
+        let expected = vec![
+            "+------------+",
+            "| my_func(i) |",
+            "+------------+",
+            "| 1          |",
+            "+------------+",
+        ];
+        assert_batches_eq!(expected, &result);
+
+        // deregistering by any name removes all the names
+        assert!(ctx.deregister_udf("my_alias").is_some());
+        assert!(ctx.udfs().is_empty());
+        assert!(ctx.deregister_udf("my_func").is_none());
+        assert!(plan_and_collect(&mut ctx, "SELECT my_func(i) FROM t")
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    /// Creates functions adding the constant body of the statement to their
+    /// Int64 argument
+    struct AddConstantFactory {}
+
+    impl FunctionFactory for AddConstantFactory {
+        fn create(
+            &self,
+            _state: &ExecutionContextState,
+            statement: &CreateFunction,
+        ) -> Result<RegisterFunction> {
+            let constant: i64 = statement
+                .body
+                .as_deref()
+                .unwrap_or_default()
+                .parse()
+                .map_err(|e| DataFusionError::Plan(format!("Invalid body: {}", e)))?;
+            let fun = make_scalar_function(move |args: &[ArrayRef]| {
+                let values = args[0].as_any().downcast_ref::<Int64Array>().unwrap();
+                let result: Int64Array =
+                    values.iter().map(|v| v.map(|v| v + constant)).collect();
+                Ok(Arc::new(result) as ArrayRef)
+            });
+            Ok(RegisterFunction::Scalar(Arc::new(create_udf(
+                &statement.name,
+                vec![DataType::Int64],
+                Arc::new(DataType::Int64),
+                fun,
+            ))))
+        }
+    }
+
+    #[tokio::test]
+    async fn create_and_drop_function() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let err = ctx
+            .sql("CREATE FUNCTION add_ten(BIGINT) RETURNS BIGINT AS '10'")
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("CREATE FUNCTION requires a FunctionFactory"));
+
+        let mut ctx = ExecutionContext::with_config(
+            ExecutionConfig::new().with_function_factory(Arc::new(AddConstantFactory {})),
+        );
+        ctx.register_table("t", test::table_with_sequence(1, 2)?)?;
+
+        ctx.sql("CREATE FUNCTION Add_Ten(x BIGINT) RETURNS BIGINT AS '10'")?;
+        let result = plan_and_collect(&mut ctx, "SELECT ADD_TEN(i) FROM t").await?;
+        let expected = vec![
+            "+------------+",
+            "| add_ten(i) |",
+            "+------------+",
+            "| 11         |",
+            "| 12         |",
+            "+------------+",
+        ];
+        assert_batches_eq!(expected, &result);
+
+        let err = ctx
+            .sql("CREATE FUNCTION add_ten(x BIGINT) RETURNS BIGINT AS '20'")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Function 'add_ten' already exists"
+        );
+
+        ctx.sql("CREATE OR REPLACE FUNCTION add_ten(x BIGINT) RETURNS BIGINT AS '20'")?;
+        let result = plan_and_collect(&mut ctx, "SELECT add_ten(i) FROM t").await?;
+        let expected = vec![
+            "+------------+",
+            "| add_ten(i) |",
+            "+------------+",
+            "| 21         |",
+            "| 22         |",
+            "+------------+",
+        ];
+        assert_batches_eq!(expected, &result);
+
+        ctx.sql("DROP FUNCTION add_ten")?;
+        assert!(ctx.udfs().is_empty());
+        let err = ctx.sql("DROP FUNCTION add_ten").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Function 'add_ten' does not exist"
+        );
+        ctx.sql("DROP FUNCTION IF EXISTS add_ten")?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn generic_user_defined_functions() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 2).unwrap())
+            .unwrap();
+
+        // my_first: (T, T) -> T for any numeric T
+        let myfunc = |args: &[ArrayRef]| Ok(Arc::clone(&args[0]));
+        ctx.register_udf(ScalarUDF::new(
+            "my_first",
+            &Signature::Numeric(2),
+            &return_type_of_argument(0),
+            &make_scalar_function(myfunc),
+        ));
+
+        // i is Int32 and the literal is Int64, so both are coerced to Int64
+        let results = plan_and_collect(
+            &mut ctx,
+            "SELECT my_first(i, 1) AS a, arrow_typeof(my_first(i, 1)) AS t FROM t",
+        )
+        .await?;
+        let expected = vec![
+            "+---+-------+",
+            "| a | t     |",
+            "+---+-------+",
+            "| 1 | Int64 |",
+            "| 2 | Int64 |",
+            "+---+-------+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+        assert_eq!(results[0].schema().field(0).data_type(), &DataType::Int64);
+
+        // Int32 and Utf8 have no common numeric type
+        let err = plan_and_collect(&mut ctx, "SELECT my_first(i, 'a') FROM t")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Coercion from [Int32, Utf8]"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn user_defined_functions_with_literal_return_types() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 2).unwrap())
+            .unwrap();
+
+        // parse_as(expr, type): casts expr to the type named by a literal
+        fn parse_type(name: &str) -> Result<DataType> {
+            match name {
+                "Int64" => Ok(DataType::Int64),
+                "Float64" => Ok(DataType::Float64),
+                other => Err(DataFusionError::Plan(format!("Unknown type {}", other))),
+            }
+        }
+        let myfunc = |args: &[ArrayRef]| {
+            let names = args[1].as_any().downcast_ref::<StringArray>().unwrap();
+            Ok(arrow::compute::cast(
+                &args[0],
+                &parse_type(names.value(0))?,
+            )?)
+        };
+        let return_type: ReturnTypeFunction = Arc::new(|_| {
+            Err(DataFusionError::Plan(
+                "parse_as requires a literal type".to_string(),
+            ))
+        });
+        let udf = ScalarUDF::new(
+            "parse_as",
+            &Signature::Exact(vec![DataType::Int32, DataType::Utf8]),
+            &return_type,
+            &make_scalar_function(myfunc),
+        )
+        .with_return_type_from_args(Arc::new(|_, literals| match literals[1] {
+            Some(ScalarValue::Utf8(Some(name))) => Ok(Arc::new(parse_type(name)?)),
+            _ => Err(DataFusionError::Plan(
+                "parse_as requires a literal type".to_string(),
+            )),
+        }));
+        ctx.register_udf(udf);
+
+        let results = plan_and_collect(
+            &mut ctx,
+            "SELECT parse_as(i, 'Float64') AS f, parse_as(i, 'Int64') AS i FROM t",
+        )
+        .await?;
+        let schema = results[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+        let expected = vec![
+            "+---+---+",
+            "| f | i |",
+            "+---+---+",
+            "| 1 | 1 |",
+            "| 2 | 2 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        let err = plan_and_collect(&mut ctx, "SELECT parse_as(i, 'Utf8') FROM t")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: Unknown type Utf8");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn async_user_defined_functions() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 5).unwrap())
+            .unwrap();
+
+        // remote_plus_one(i): adds one, recording the rows of each invocation
+        let invocations = Arc::new(Mutex::new(vec![]));
+        let recorded = invocations.clone();
+        let fun: AsyncScalarFunctionImplementation = Arc::new(
+            move |args: Vec<ColumnarValue>| -> BoxFuture<'static, Result<ColumnarValue>> {
+                let recorded = recorded.clone();
+                async move {
+                    let array = match &args[0] {
+                        ColumnarValue::Array(array) => array.clone(),
+                        ColumnarValue::Scalar(_) => unreachable!(),
+                    };
+                    recorded.lock().unwrap().push(array.len());
+                    let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+                    let result: Int32Array =
+                        array.iter().map(|v| v.map(|v| v + 1)).collect();
+                    Ok(ColumnarValue::Array(Arc::new(result)))
+                }
+                .boxed()
+            },
+        );
+        let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Int32)));
+        let udf = AsyncScalarUDF::new(
+            "remote_plus_one",
+            &Signature::Exact(vec![DataType::Int32]),
+            &return_type,
+            &fun,
+        )
+        .with_batch_size(2)
+        .with_max_concurrency(2);
+        ctx.register_async_udf(udf);
+
+        let results = plan_and_collect(
+            &mut ctx,
+            "SELECT i, remote_plus_one(remote_plus_one(i)) AS j FROM t \
+             WHERE remote_plus_one(i) > 4",
+        )
+        .await?;
+        let expected = vec![
+            "+---+---+",
+            "| i | j |",
+            "+---+---+",
+            "| 4 | 6 |",
+            "| 5 | 7 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        // 5 rows for the filter, then 2 rows twice for the projection
+        let invocations = invocations.lock().unwrap();
+        assert!(invocations.iter().all(|rows| *rows <= 2));
+        assert_eq!(invocations.iter().sum::<usize>(), 9);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn table_functions() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+
+        let results = plan_and_collect(
+            &mut ctx,
+            "SELECT COUNT(*) AS n, SUM(generate_series) AS total \
+             FROM generate_series(1, 1000000)",
+        )
+        .await?;
+        let expected = vec![
+            "+---------+--------------+",
+            "| n       | total        |",
+            "+---------+--------------+",
+            "| 1000000 | 500000500000 |",
+            "+---------+--------------+",
+        ];
+        assert_batches_eq!(expected, &results);
+
+        let results =
+            plan_and_collect(&mut ctx, "SELECT * FROM range(10, 0, -4)").await?;
+        let expected = vec![
+            "+-------+",
+            "| range |",
+            "+-------+",
+            "| 10    |",
+            "| 6     |",
+            "| 2     |",
+            "+-------+",
+        ];
+        assert_batches_eq!(expected, &results);
+
+        // sequence(n): the table of the integers from 1 to n
+        let fun: TableFunctionImplementation = Arc::new(|args| match args {
+            [ScalarValue::Int64(Some(n))] => test::table_with_sequence(1, *n as i32),
+            _ => Err(DataFusionError::Plan(
+                "sequence takes a single integer".to_string(),
+            )),
+        });
+        ctx.register_table_function(TableUDF::new("sequence", &fun));
+
+        let results = plan_and_collect(&mut ctx, "SELECT i FROM sequence(3)").await?;
+        let expected = vec![
+            "+---+", "| i |", "+---+", "| 1 |", "| 2 |", "| 3 |", "+---+",
+        ];
+        assert_batches_eq!(expected, &results);
+
+        let err = plan_and_collect(&mut ctx, "SELECT * FROM sequence('a')")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: sequence takes a single integer"
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn case_sensitive_identifiers_aggregates() {
         let mut ctx = ExecutionContext::new();
@@ -2385,6 +3623,12 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn at_least_one_optimizer_pass() {
+        let config = ExecutionConfig::new().with_max_optimizer_passes(0);
+        assert_eq!(config.max_optimizer_passes, 1);
+    }
+
     #[test]
     fn ctx_sql_should_optimize_plan() -> Result<()> {
         let mut ctx = ExecutionContext::new();
@@ -2598,6 +3842,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn custom_optimizer_rules() -> Result<()> {
+        let config = ExecutionConfig::new()
+            .remove_optimizer_rule("limit_push_down")
+            .add_optimizer_rule(Arc::new(LimitZeroRule {}));
+        let names = config
+            .optimizer_rules()
+            .iter()
+            .map(|rule| rule.name().to_owned())
+            .collect::<Vec<_>>();
+        assert!(!names.contains(&"limit_push_down".to_owned()));
+        assert_eq!(names.last().unwrap(), "limit_zero");
+
+        let ctx = ExecutionContext::with_config(config);
+        let plan = ctx.create_logical_plan("SELECT 1")?;
+        let plan = ctx.optimize(&plan)?;
+        assert_eq!(
+            format!("{:?}", plan),
+            "Limit: 0\n  Projection: Int64(1)\n    EmptyRelation"
+        );
+
+        let config = ExecutionConfig::new()
+            .replace_optimizer_rule("eliminate_limit", Arc::new(LimitZeroRule {}))?;
+        let names = config
+            .optimizer_rules()
+            .iter()
+            .map(|rule| rule.name().to_owned())
+            .collect::<Vec<_>>();
+        assert!(!names.contains(&"eliminate_limit".to_owned()));
+        assert!(names.contains(&"limit_zero".to_owned()));
+
+        assert!(ExecutionConfig::new()
+            .replace_physical_optimizer_rule("no_such_rule", Arc::new(Repartition::new()))
+            .is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn information_schema_tables_not_exist_by_default() {
         let mut ctx = ExecutionContext::new();
@@ -3215,6 +4496,71 @@ mod tests {
         assert_batches_sorted_eq!(expected, &result);
     }
 
+    #[tokio::test]
+    async fn set_variable() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+
+        plan_and_collect(&mut ctx, "SET batch_size = 1024").await?;
+        plan_and_collect(&mut ctx, "SET target_partitions = 3").await?;
+        plan_and_collect(&mut ctx, "SET parquet_pruning TO false").await?;
+        plan_and_collect(&mut ctx, "SET coalesce_target_bytes = 65536").await?;
+        plan_and_collect(&mut ctx, "SET deterministic = true").await?;
+        plan_and_collect(&mut ctx, "SET safe_casts = true").await?;
+        {
+            let state = ctx.state.lock().unwrap();
+            assert_eq!(state.config.batch_size, 1024);
+            assert_eq!(state.config.concurrency, 3);
+            assert!(!state.config.parquet_pruning);
+            assert!(state.config.deterministic);
+            assert!(state.config.safe_casts);
+            assert_eq!(
+                state.config.coalesce_policy,
+                BatchSizePolicy::Adaptive {
+                    target_bytes: 65536
+                }
+            );
+        }
+
+        plan_and_collect(&mut ctx, "SET memory_limit = 1000").await?;
+        let pool = ctx.runtime_env().memory_pool.clone();
+        let mut reservation = MemoryConsumer::new("test").register(&pool);
+        assert!(reservation.try_grow(2000).is_err());
+        reservation.try_grow(500)?;
+
+        let err = plan_and_collect(&mut ctx, "SET batch_size = 0")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("must be a positive integer"));
+        let err = plan_and_collect(&mut ctx, "SET no_such_option = 1")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Unknown configuration option 'no_such_option'"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_memory_limit() -> Result<()> {
+        let runtime_config = RuntimeConfig::new()
+            .with_query_limits(QueryLimits::new().with_max_memory(16));
+        let mut ctx = ExecutionContext::with_config(
+            ExecutionConfig::new().with_runtime_config(runtime_config),
+        );
+        ctx.register_table("t", test::table_with_sequence(1, 1000)?)?;
+
+        // scanning the table does not hold any memory
+        let results = plan_and_collect(&mut ctx, "SELECT i FROM t").await?;
+        assert_eq!(results.iter().map(|b| b.num_rows()).sum::<usize>(), 1000);
+
+        let err = plan_and_collect(&mut ctx, "SELECT i FROM t ORDER BY i DESC")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{}", err);
+        Ok(())
+    }
+
     struct MyPhysicalPlanner {}
 
     impl PhysicalPlanner for MyPhysicalPlanner {
@@ -3229,6 +4575,23 @@ mod tests {
         }
     }
 
+    /// Optimizer rule that wraps a plan in a `LIMIT 0`
+    struct LimitZeroRule {}
+
+    impl OptimizerRule for LimitZeroRule {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            LogicalPlanBuilder::from(plan).limit(0)?.build()
+        }
+
+        fn name(&self) -> &str {
+            "limit_zero"
+        }
+    }
+
     struct MyQueryPlanner {}
 
     impl QueryPlanner for MyQueryPlanner {
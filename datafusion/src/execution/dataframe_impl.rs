@@ -23,16 +23,39 @@ use crate::arrow::record_batch::RecordBatch;
 use crate::error::Result;
 use crate::execution::context::{ExecutionContext, ExecutionContextState};
 use crate::logical_plan::{
-    col, DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, LogicalPlanBuilder,
-    Partitioning,
+    avg, col, count, lit, max, min, DFSchema, DuplicateKeep, Expr, FunctionRegistry,
+    JoinType, LogicalPlan, LogicalPlanBuilder, Partitioning, SampleMethod,
 };
+use crate::scalar::ScalarValue;
 use crate::{
     dataframe::*,
-    physical_plan::{collect, collect_partitioned},
+    physical_plan::{
+        collect, collect_partitioned, execute_stream, execute_stream_partitioned,
+        merge::MergeExec, ExecutionPlan, SendableRecordBatchStream,
+    },
 };
 
+use arrow::array::{ArrayRef, Float64Array, StringArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema};
 use async_trait::async_trait;
 
+/// The number of rows sampled to approximate the percentiles of `describe`
+const DESCRIBE_SAMPLE_SIZE: usize = 10_000;
+
+/// The statistics computed by `describe`, in order
+const DESCRIBE_STATISTICS: &[&str] = &[
+    "count",
+    "null_count",
+    "mean",
+    "stddev",
+    "min",
+    "max",
+    "25%",
+    "50%",
+    "75%",
+];
+
 /// Implementation of DataFrame API
 pub struct DataFrameImpl {
     ctx_state: Arc<Mutex<ExecutionContextState>>,
@@ -47,6 +70,29 @@ impl DataFrameImpl {
             plan: plan.clone(),
         }
     }
+
+    /// Create a physical plan for this DataFrame, and whether its partitions
+    /// should be merged deterministically
+    fn create_physical_plan(&self) -> Result<(Arc<dyn ExecutionPlan>, bool)> {
+        let state = self.ctx_state.lock().unwrap().clone();
+        let deterministic = state.config.deterministic;
+        let ctx = ExecutionContext::from(Arc::new(Mutex::new(state)));
+        let plan = ctx.optimize(&self.plan)?;
+        Ok((ctx.create_physical_plan(&plan)?, deterministic))
+    }
+
+    /// Merge the partitions of `plan` in order if execution is deterministic
+    fn merge_in_order(
+        plan: Arc<dyn ExecutionPlan>,
+        deterministic: bool,
+    ) -> Arc<dyn ExecutionPlan> {
+        if deterministic && plan.output_partitioning().partition_count() > 1 {
+            // merge the partitions in order rather than as they become available
+            Arc::new(MergeExec::new(plan).with_preserve_order(true))
+        } else {
+            plan
+        }
+    }
 }
 
 #[async_trait]
@@ -89,12 +135,72 @@ impl DataFrame for DataFrameImpl {
         Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
     }
 
+    /// Compute window functions over all the rows
+    fn window(&self, window_expr: Vec<Expr>) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .window(window_expr)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
+    /// Add or replace a column
+    fn with_column(&self, name: &str, expr: Expr) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .with_column(name, expr)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
+    /// Rename a column
+    fn with_column_renamed(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .with_column_renamed(old_name, new_name)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
     /// Limit the number of rows
     fn limit(&self, n: usize) -> Result<Arc<dyn DataFrame>> {
         let plan = LogicalPlanBuilder::from(&self.plan).limit(n)?.build()?;
         Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
     }
 
+    /// Keep one row per distinct value of some columns
+    fn drop_duplicates(
+        &self,
+        subset: Option<&[&str]>,
+        keep: DuplicateKeep,
+    ) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .drop_duplicates(subset, keep)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
+    /// Sample the rows
+    fn sample(
+        &self,
+        method: SampleMethod,
+        seed: Option<u64>,
+    ) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .sample(method, seed)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
+    /// Expand a list column into one row per item
+    fn unnest(&self, column: &str) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .unnest(column)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
     /// Sort by specified sorting expressions
     fn sort(&self, expr: Vec<Expr>) -> Result<Arc<dyn DataFrame>> {
         let plan = LogicalPlanBuilder::from(&self.plan).sort(expr)?.build()?;
@@ -115,6 +221,19 @@ impl DataFrame for DataFrameImpl {
         Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
     }
 
+    /// Join with another DataFrame on arbitrary predicates
+    fn join_on(
+        &self,
+        right: Arc<dyn DataFrame>,
+        join_type: JoinType,
+        on: Vec<Expr>,
+    ) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .join_on(&right.to_logical_plan(), join_type, on)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
     fn repartition(
         &self,
         partitioning_scheme: Partitioning,
@@ -133,23 +252,190 @@ impl DataFrame for DataFrameImpl {
     // Convert the logical plan represented by this DataFrame into a physical plan and
     // execute it
     async fn collect(&self) -> Result<Vec<RecordBatch>> {
-        let state = self.ctx_state.lock().unwrap().clone();
-        let ctx = ExecutionContext::from(Arc::new(Mutex::new(state)));
-        let plan = ctx.optimize(&self.plan)?;
-        let plan = ctx.create_physical_plan(&plan)?;
-        Ok(collect(plan).await?)
+        let (plan, deterministic) = self.create_physical_plan()?;
+        Ok(collect(Self::merge_in_order(plan, deterministic)).await?)
+    }
+
+    // Convert the logical plan represented by this DataFrame into a physical plan and
+    // execute it, returning a single stream
+    async fn execute_stream(&self) -> Result<SendableRecordBatchStream> {
+        let (plan, deterministic) = self.create_physical_plan()?;
+        execute_stream(Self::merge_in_order(plan, deterministic)).await
     }
 
     // Convert the logical plan represented by this DataFrame into a physical plan and
     // execute it
     async fn collect_partitioned(&self) -> Result<Vec<Vec<RecordBatch>>> {
-        let state = self.ctx_state.lock().unwrap().clone();
-        let ctx = ExecutionContext::from(Arc::new(Mutex::new(state)));
-        let plan = ctx.optimize(&self.plan)?;
-        let plan = ctx.create_physical_plan(&plan)?;
+        let (plan, _) = self.create_physical_plan()?;
         Ok(collect_partitioned(plan).await?)
     }
 
+    // Convert the logical plan represented by this DataFrame into a physical plan and
+    // execute it, returning one stream per partition
+    async fn execute_stream_partitioned(&self) -> Result<Vec<SendableRecordBatchStream>> {
+        let (plan, _) = self.create_physical_plan()?;
+        execute_stream_partitioned(plan).await
+    }
+
+    async fn describe(&self) -> Result<RecordBatch> {
+        let fields = self.plan.schema().fields();
+
+        // the aggregates of all columns are computed by a single plan
+        let mut aggr_expr = vec![count(lit(1_u8))];
+        let mut indices = Vec::with_capacity(fields.len());
+        for field in fields {
+            let column = col(field.name());
+            let first = aggr_expr.len();
+            aggr_expr.push(count(column.clone()));
+            if is_describe_numeric(field.data_type()) {
+                let value = Expr::Cast {
+                    expr: Box::new(column.clone()),
+                    data_type: DataType::Float64,
+                };
+                aggr_expr.push(avg(value.clone()));
+                aggr_expr.push(avg(value.clone() * value));
+            }
+            if has_describe_min_max(field.data_type()) {
+                aggr_expr.push(min(column.clone()));
+                aggr_expr.push(max(column));
+            }
+            indices.push(first);
+        }
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .aggregate(vec![], aggr_expr)?
+            .build()?;
+        let batches = DataFrameImpl::new(self.ctx_state.clone(), &plan)
+            .collect()
+            .await?;
+        let aggregates = batches
+            .iter()
+            .find(|batch| batch.num_rows() > 0)
+            .map(|batch| {
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| ScalarValue::try_from_array(column, 0))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        // the percentiles are approximated by those of a sample
+        let numeric = fields
+            .iter()
+            .filter(|field| is_describe_numeric(field.data_type()))
+            .collect::<Vec<_>>();
+        let mut samples = vec![];
+        if !numeric.is_empty() {
+            let plan = LogicalPlanBuilder::from(&self.plan)
+                .project(numeric.iter().map(|field| Expr::Cast {
+                    expr: Box::new(col(field.name())),
+                    data_type: DataType::Float64,
+                }))?
+                .sample(
+                    SampleMethod::Reservoir {
+                        size: DESCRIBE_SAMPLE_SIZE,
+                    },
+                    None,
+                )?
+                .build()?;
+            let batches = DataFrameImpl::new(self.ctx_state.clone(), &plan)
+                .collect()
+                .await?;
+            for i in 0..numeric.len() {
+                let mut values = vec![];
+                for batch in &batches {
+                    let column = batch
+                        .column(i)
+                        .as_any()
+                        .downcast_ref::<Float64Array>()
+                        .unwrap();
+                    values.extend(column.iter().flatten().filter(|v| !v.is_nan()));
+                }
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                samples.push(values);
+            }
+        }
+
+        let total = aggregates.get(0).and_then(describe_f64).unwrap_or(0.0);
+        let mut schema_fields = vec![Field::new("describe", DataType::Utf8, false)];
+        let mut columns: Vec<ArrayRef> =
+            vec![Arc::new(StringArray::from(DESCRIBE_STATISTICS.to_vec()))];
+        let mut samples = samples.into_iter();
+        for (field, first) in fields.iter().zip(indices) {
+            let mut next = first;
+            let mut take = || {
+                next += 1;
+                aggregates.get(next - 1)
+            };
+            let non_null = take().and_then(describe_f64).unwrap_or(0.0);
+            if is_describe_numeric(field.data_type()) {
+                let mean = take().and_then(describe_f64);
+                let mean_of_squares = take().and_then(describe_f64);
+                let stddev = match (mean, mean_of_squares) {
+                    (Some(mean), Some(squares)) if non_null > 1.0 => {
+                        let variance =
+                            (squares - mean * mean) * non_null / (non_null - 1.0);
+                        Some(variance.max(0.0).sqrt())
+                    }
+                    _ => None,
+                };
+                let min = take().and_then(describe_f64);
+                let max = take().and_then(describe_f64);
+                let sample = samples.next().unwrap();
+                let percentile = |p: f64| {
+                    if sample.is_empty() {
+                        None
+                    } else {
+                        let rank = (p * (sample.len() - 1) as f64).round() as usize;
+                        Some(sample[rank])
+                    }
+                };
+                let values = vec![
+                    Some(non_null),
+                    Some(total - non_null),
+                    mean,
+                    stddev,
+                    min,
+                    max,
+                    percentile(0.25),
+                    percentile(0.5),
+                    percentile(0.75),
+                ];
+                schema_fields.push(Field::new(field.name(), DataType::Float64, true));
+                columns.push(Arc::new(Float64Array::from(values)));
+            } else {
+                let (min, max) = if has_describe_min_max(field.data_type()) {
+                    (
+                        take().and_then(describe_string),
+                        take().and_then(describe_string),
+                    )
+                } else {
+                    (None, None)
+                };
+                let values = vec![
+                    Some(non_null.to_string()),
+                    Some((total - non_null).to_string()),
+                    None,
+                    None,
+                    min,
+                    max,
+                    None,
+                    None,
+                    None,
+                ];
+                schema_fields.push(Field::new(field.name(), DataType::Utf8, true));
+                columns.push(Arc::new(
+                    values.iter().map(|v| v.as_deref()).collect::<StringArray>(),
+                ));
+            }
+        }
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(schema_fields)),
+            columns,
+        )?)
+    }
+
     /// Returns the schema from the logical plan
     fn schema(&self) -> &DFSchema {
         self.plan.schema()
@@ -162,6 +448,13 @@ impl DataFrame for DataFrameImpl {
         Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
     }
 
+    fn explain_analyze(&self, verbose: bool) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .explain_analyze(verbose)?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
     fn registry(&self) -> Arc<dyn FunctionRegistry> {
         let registry = self.ctx_state.lock().unwrap().clone();
         Arc::new(registry)
@@ -173,6 +466,67 @@ impl DataFrame for DataFrameImpl {
             .build()?;
         Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
     }
+
+    fn intersect(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .intersect(&dataframe.to_logical_plan())?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+
+    fn except(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>> {
+        let plan = LogicalPlanBuilder::from(&self.plan)
+            .except(&dataframe.to_logical_plan())?
+            .build()?;
+        Ok(Arc::new(DataFrameImpl::new(self.ctx_state.clone(), &plan)))
+    }
+}
+
+/// Whether `describe` reports the statistics of a column of `data_type` as
+/// numbers
+fn is_describe_numeric(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+    )
+}
+
+/// Whether `describe` reports the minimum and maximum of a column of
+/// `data_type`
+fn has_describe_min_max(data_type: &DataType) -> bool {
+    is_describe_numeric(data_type)
+        || matches!(
+            data_type,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Timestamp(_, _)
+        )
+}
+
+/// Converts a numeric aggregate to a float, `None` if it is null
+fn describe_f64(value: &ScalarValue) -> Option<f64> {
+    if value.is_null() {
+        return None;
+    }
+    let array = cast(&value.to_array(), &DataType::Float64).ok()?;
+    let array = array.as_any().downcast_ref::<Float64Array>()?;
+    Some(array.value(0))
+}
+
+/// Formats an aggregate, `None` if it is null
+fn describe_string(value: &ScalarValue) -> Option<String> {
+    if value.is_null() {
+        None
+    } else {
+        Some(value.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -181,6 +535,9 @@ mod tests {
 
     use super::*;
     use crate::logical_plan::*;
+    use crate::physical_plan::aggregates::AggregateFunction;
+    use crate::physical_plan::common;
+    use crate::physical_plan::window_functions::WindowFunction;
     use crate::{assert_batches_sorted_eq, execution::context::ExecutionContext};
     use crate::{datasource::csv::CsvReadOptions, physical_plan::ColumnarValue};
     use crate::{physical_plan::functions::ScalarFunctionImplementation, test};
@@ -252,6 +609,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn execute_stream() -> Result<()> {
+        let df = test_table()?.select_columns(&["c1", "c2"])?;
+        let expected = df.collect().await?;
+        let expected_rows: usize = expected.iter().map(|b| b.num_rows()).sum();
+
+        let batches = common::collect(df.execute_stream().await?).await?;
+        assert_eq!(
+            expected_rows,
+            batches.iter().map(|b| b.num_rows()).sum::<usize>()
+        );
+
+        let streams = df.execute_stream_partitioned().await?;
+        let mut rows = 0;
+        for stream in streams {
+            for batch in common::collect(stream).await? {
+                rows += batch.num_rows();
+            }
+        }
+        assert_eq!(expected_rows, rows);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn join() -> Result<()> {
         let left = test_table()?.select_columns(&["c1", "c2"])?;
@@ -266,6 +646,93 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn join_on() -> Result<()> {
+        let left = test_table()?.select_columns(&["c1", "c2"])?;
+        let right = test_table()?.select(vec![col("c1").alias("c1_r"), col("c3")])?;
+        let join = left.join_on(
+            right,
+            JoinType::Inner,
+            vec![col("c1").eq(col("c1_r")), col("c3").gt(lit(0))],
+        )?;
+        let plan = format!("{:?}", join.to_logical_plan());
+        assert!(plan.starts_with("Filter: #c3 Gt Int32(0)\n  Join: c1 = c1_r"));
+
+        let join_rows = join.collect().await?;
+        let rows = join_rows.iter().map(|x| x.num_rows()).sum::<usize>();
+        assert!(rows > 0 && rows < 2008);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn intersect_except() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let left = ctx.read_table(test::table_with_sequence(1, 5)?)?;
+        let right = ctx.read_table(test::table_with_sequence(3, 7)?)?;
+        let left = left.union(left.clone())?;
+
+        let expected = vec![
+            "+---+", "| i |", "+---+", "| 3 |", "| 4 |", "| 5 |", "+---+",
+        ];
+        let results = left.intersect(right.clone())?.collect().await?;
+        assert_batches_sorted_eq!(expected, &results);
+
+        let expected = vec!["+---+", "| i |", "+---+", "| 1 |", "| 2 |", "+---+"];
+        let results = left.except(right)?.collect().await?;
+        assert_batches_sorted_eq!(expected, &results);
+
+        let other = test_table()?.select_columns(&["c1"])?;
+        assert!(left.intersect(other).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_column() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let df = ctx
+            .read_table(test::table_with_sequence(1, 3)?)?
+            .with_column("j", col("i") * lit(10))?
+            .with_column("i", col("i") + lit(1))?
+            .with_column_renamed("j", "k")?;
+
+        let expected = vec![
+            "+---+----+",
+            "| i | k  |",
+            "+---+----+",
+            "| 2 | 10 |",
+            "| 3 | 20 |",
+            "| 4 | 30 |",
+            "+---+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &df.collect().await?);
+
+        assert!(df.with_column_renamed("j", "l").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn window() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let df = ctx
+            .read_table(test::table_with_sequence(1, 3)?)?
+            .window(vec![Expr::WindowFunction {
+                fun: WindowFunction::AggregateFunction(AggregateFunction::Max),
+                args: vec![col("i")],
+            }])?;
+
+        let expected = vec![
+            "+--------+---+",
+            "| MAX(i) | i |",
+            "+--------+---+",
+            "| 3      | 1 |",
+            "| 3      | 2 |",
+            "| 3      | 3 |",
+            "+--------+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &df.collect().await?);
+        Ok(())
+    }
+
     #[test]
     fn limit() -> Result<()> {
         // build query using Table API
@@ -283,6 +750,163 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn sample() -> Result<()> {
+        let t = test_table()?.select_columns(&["c1", "c2"])?;
+
+        let df = t.sample(SampleMethod::Reservoir { size: 10 }, Some(1))?;
+        let expected = "Sample: Reservoir(size=10) seed=1\
+        \n  Projection: #c1, #c2\
+        \n    TableScan: aggregate_test_100 projection=None";
+        assert_eq!(format!("{:?}", df.to_logical_plan()), expected);
+        let rows: usize = df.collect().await?.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 10);
+
+        // the same seed produces the same sample
+        let method = SampleMethod::Bernoulli { fraction: 0.5 };
+        let first = t.sample(method, Some(7))?.collect().await?;
+        let second = t.sample(method, Some(7))?.collect().await?;
+        assert_eq!(
+            arrow::util::pretty::pretty_format_batches(&first)?,
+            arrow::util::pretty::pretty_format_batches(&second)?
+        );
+
+        let method = SampleMethod::Bernoulli { fraction: -0.5 };
+        assert!(t.sample(method, None).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn describe() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(arrow::array::Int32Array::from(vec![
+                    Some(1),
+                    Some(2),
+                    Some(3),
+                    None,
+                    Some(5),
+                ])),
+                Arc::new(StringArray::from(vec![
+                    Some("x"),
+                    None,
+                    None,
+                    Some("z"),
+                    Some("y"),
+                ])),
+            ],
+        )?;
+        let mut ctx = ExecutionContext::new();
+        ctx.register_batch("t", batch)?;
+        let summary = ctx.table("t")?.describe().await?;
+
+        let statistics = summary
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(statistics, &StringArray::from(DESCRIBE_STATISTICS.to_vec()));
+
+        let a = summary
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let a = a.iter().map(|v| v.unwrap()).collect::<Vec<_>>();
+        assert_eq!(a[..3], [4.0, 1.0, 2.75]);
+        assert!((a[3] - (8.75_f64 / 3.0).sqrt()).abs() < 1e-9);
+        // all rows are sampled, so the percentiles are exact
+        assert_eq!(a[4..], [1.0, 5.0, 2.0, 3.0, 3.0]);
+
+        let b = summary
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let b = b.iter().collect::<Vec<_>>();
+        let expected = vec![
+            Some("3"),
+            Some("2"),
+            None,
+            None,
+            Some("x"),
+            Some("z"),
+            None,
+            None,
+            None,
+        ];
+        assert_eq!(b, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn drop_duplicates() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Utf8, true),
+            Field::new("v", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![
+                    Some("b"),
+                    Some("a"),
+                    None,
+                    Some("b"),
+                    None,
+                ])),
+                Arc::new(arrow::array::Int32Array::from(vec![1, 2, 3, 4, 5])),
+            ],
+        )?;
+        let mut ctx = ExecutionContext::new();
+        ctx.register_batch("t", batch)?;
+        let t = ctx.table("t")?;
+
+        let df = t.drop_duplicates(Some(&["k"]), DuplicateKeep::First)?;
+        let expected = "Deduplicate: #k keep=First\
+        \n  TableScan: t projection=None";
+        assert_eq!(format!("{:?}", df.to_logical_plan()), expected);
+        let expected = vec![
+            "+---+---+",
+            "| k | v |",
+            "+---+---+",
+            "| b | 1 |",
+            "| a | 2 |",
+            "|   | 3 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &df.collect().await?);
+
+        // the sorted input is deduplicated while streaming
+        let df = t
+            .sort(vec![col("k").sort(true, true), col("v").sort(true, true)])?
+            .drop_duplicates(Some(&["k"]), DuplicateKeep::Last)?;
+        let expected = vec![
+            "+---+---+",
+            "| k | v |",
+            "+---+---+",
+            "|   | 5 |",
+            "| a | 2 |",
+            "| b | 4 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &df.collect().await?);
+
+        // without a subset, rows are duplicates if all columns are equal
+        let df = t.drop_duplicates(None, DuplicateKeep::First)?;
+        assert_eq!(df.collect().await?[0].num_rows(), 5);
+
+        assert!(t
+            .drop_duplicates(Some(&["x"]), DuplicateKeep::First)
+            .is_err());
+        Ok(())
+    }
+
     #[test]
     fn explain() -> Result<()> {
         // build query using Table API
@@ -0,0 +1,140 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Manages files created during query execution, such as the spill files
+//! of operators exceeding their memory budget.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use tempfile::{Builder, NamedTempFile, TempDir};
+
+use crate::error::Result;
+
+/// Hands out temporary files in the configured spill directories and keeps
+/// track of the number of bytes spilled to them.
+///
+/// A temporary directory is created in each spill directory on first use.
+/// These directories, including any files left in them, are removed when
+/// the `DiskManager` is dropped.
+#[derive(Debug)]
+pub struct DiskManager {
+    /// Directories to create the temporary directories in
+    local_dirs: Vec<PathBuf>,
+    /// Temporary directories, created on first use
+    temp_dirs: Mutex<Vec<TempDir>>,
+    /// Number of files created so far, used to spread files over directories
+    file_count: AtomicUsize,
+    /// Total number of bytes spilled to disk
+    spilled_bytes: AtomicUsize,
+}
+
+impl DiskManager {
+    /// Create a new `DiskManager` spilling to `local_dirs`, or to the
+    /// temporary directory of the OS if `local_dirs` is empty
+    pub fn new(local_dirs: Vec<PathBuf>) -> Self {
+        let local_dirs = if local_dirs.is_empty() {
+            vec![std::env::temp_dir()]
+        } else {
+            local_dirs
+        };
+        Self {
+            local_dirs,
+            temp_dirs: Mutex::new(vec![]),
+            file_count: AtomicUsize::new(0),
+            spilled_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Directories spill files are created in
+    pub fn local_dirs(&self) -> &[PathBuf] {
+        &self.local_dirs
+    }
+
+    /// Creates a new temporary file, which is deleted when it is dropped.
+    /// Files are created in the spill directories in round-robin order.
+    pub fn create_tmp_file(&self) -> Result<NamedTempFile> {
+        let mut temp_dirs = self.temp_dirs.lock().unwrap();
+        if temp_dirs.is_empty() {
+            *temp_dirs = self
+                .local_dirs
+                .iter()
+                .map(|dir| Builder::new().prefix("datafusion-").tempdir_in(dir))
+                .collect::<std::io::Result<Vec<_>>>()?;
+        }
+
+        let index = self.file_count.fetch_add(1, Ordering::Relaxed) % temp_dirs.len();
+        Ok(Builder::new()
+            .prefix("spill-")
+            .tempfile_in(temp_dirs[index].path())?)
+    }
+
+    /// Records that `bytes` were written to a spill file
+    pub fn record_spill(&self, bytes: usize) {
+        self.spilled_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Total number of bytes spilled to disk so far
+    pub fn spilled_bytes(&self) -> usize {
+        self.spilled_bytes.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn spill_files_round_robin() -> Result<()> {
+        let dir1 = tempfile::tempdir()?;
+        let dir2 = tempfile::tempdir()?;
+        let manager =
+            DiskManager::new(vec![dir1.path().to_owned(), dir2.path().to_owned()]);
+
+        let mut file1 = manager.create_tmp_file()?;
+        let file2 = manager.create_tmp_file()?;
+        assert!(file1.path().starts_with(dir1.path()));
+        assert!(file2.path().starts_with(dir2.path()));
+
+        file1.write_all(&[0; 16])?;
+        manager.record_spill(16);
+        assert_eq!(manager.spilled_bytes(), 16);
+
+        // files are removed when dropped
+        let path = file2.path().to_owned();
+        drop(file2);
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn cleanup_on_drop() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = DiskManager::new(vec![dir.path().to_owned()]);
+
+        let file = manager.create_tmp_file()?;
+        let (_, path) = file.keep().unwrap();
+        assert!(path.exists());
+
+        drop(manager);
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 0);
+        Ok(())
+    }
+}
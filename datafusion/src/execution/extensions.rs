@@ -0,0 +1,146 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Typed map of request-scoped values, such as a tenant id or credentials,
+//! that embedders pass to their own operators and functions.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use datafusion::execution::context::ExecutionConfig;
+//!
+//! struct TenantId(String);
+//!
+//! let config = ExecutionConfig::new()
+//!     .with_extension(Arc::new(TenantId("tenant-a".to_string())));
+//!
+//! // e.g. within an `ExtensionPlanner`, from `ctx_state.config`
+//! let tenant = config.extensions.get::<TenantId>().unwrap();
+//! assert_eq!(tenant.0, "tenant-a");
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Map from a type to a single value of that type.
+///
+/// Values are shared, so cloning the map, e.g. along with the
+/// [`ExecutionConfig`](super::context::ExecutionConfig) it is part of, is cheap.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, (&'static str, Arc<dyn Any + Send + Sync>)>,
+}
+
+impl Extensions {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Insert `value`, returning the value of the same type that was
+    /// replaced, if any
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: Arc<T>) -> Option<Arc<T>> {
+        self.map
+            .insert(TypeId::of::<T>(), (std::any::type_name::<T>(), value))
+            .and_then(|(_, previous)| previous.downcast().ok())
+    }
+
+    /// Return the value of type `T`, if any
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|(_, value)| value.clone().downcast().ok())
+    }
+
+    /// Remove and return the value of type `T`, if any
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<Arc<T>> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|(_, value)| value.downcast().ok())
+    }
+
+    /// Whether the map contains a value of type `T`
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Number of values in the map
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether the map contains no values
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // values are not required to implement `Debug`, so only list their types
+        f.debug_set()
+            .entries(self.map.values().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TenantId(String);
+
+    #[derive(Debug, PartialEq)]
+    struct FeatureFlag(bool);
+
+    #[test]
+    fn typed_values() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.is_empty());
+        assert!(extensions.get::<TenantId>().is_none());
+
+        assert!(extensions
+            .insert(Arc::new(TenantId("a".to_string())))
+            .is_none());
+        assert!(extensions.insert(Arc::new(FeatureFlag(true))).is_none());
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(
+            extensions.get::<TenantId>().as_deref(),
+            Some(&TenantId("a".to_string()))
+        );
+        assert_eq!(
+            extensions.get::<FeatureFlag>().as_deref(),
+            Some(&FeatureFlag(true))
+        );
+
+        // a value of the same type is replaced
+        let previous = extensions.insert(Arc::new(TenantId("b".to_string())));
+        assert_eq!(previous.as_deref(), Some(&TenantId("a".to_string())));
+        assert_eq!(extensions.len(), 2);
+
+        // clones share the values, but not later changes
+        let cloned = extensions.clone();
+        assert_eq!(
+            extensions.remove::<FeatureFlag>().as_deref(),
+            Some(&FeatureFlag(true))
+        );
+        assert!(!extensions.contains::<FeatureFlag>());
+        assert!(cloned.contains::<FeatureFlag>());
+    }
+}
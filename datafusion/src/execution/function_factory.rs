@@ -0,0 +1,73 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Hook creating the functions of `CREATE FUNCTION` statements, so that
+//! functions defined in SQL or loaded dynamically can be installed at runtime.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use datafusion::error::Result;
+//! use datafusion::execution::context::{ExecutionConfig, ExecutionContextState};
+//! use datafusion::execution::function_factory::{FunctionFactory, RegisterFunction};
+//! use datafusion::sql::parser::CreateFunction;
+//!
+//! struct MyFunctionFactory {}
+//!
+//! impl FunctionFactory for MyFunctionFactory {
+//!     fn create(
+//!         &self,
+//!         state: &ExecutionContextState,
+//!         statement: &CreateFunction,
+//!     ) -> Result<RegisterFunction> {
+//!         // e.g. compile `statement.body` in `statement.language`
+//!         unimplemented!()
+//!     }
+//! }
+//!
+//! let config = ExecutionConfig::new()
+//!     .with_function_factory(Arc::new(MyFunctionFactory {}));
+//! ```
+
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionContextState;
+use crate::physical_plan::udaf::AggregateUDF;
+use crate::physical_plan::udf::ScalarUDF;
+use crate::sql::parser::CreateFunction;
+
+/// Creates the functions defined by `CREATE FUNCTION` statements.
+///
+/// The types of the statement are SQL types, which
+/// [`convert_data_type`](crate::sql::planner::convert_data_type) converts to
+/// Arrow types.
+pub trait FunctionFactory {
+    /// Create the function defined by `statement`, named after the statement
+    fn create(
+        &self,
+        state: &ExecutionContextState,
+        statement: &CreateFunction,
+    ) -> Result<RegisterFunction>;
+}
+
+/// A function created by a [`FunctionFactory`]
+pub enum RegisterFunction {
+    /// A scalar function
+    Scalar(Arc<ScalarUDF>),
+    /// An aggregate function
+    Aggregate(Arc<AggregateUDF>),
+}
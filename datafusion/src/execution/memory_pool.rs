@@ -0,0 +1,431 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tracks the memory used by operators during query execution.
+//!
+//! Operators that buffer data, such as sorts, joins and aggregations,
+//! register a [`MemoryConsumer`] with the [`MemoryPool`] of the
+//! [`RuntimeEnv`](super::runtime_env::RuntimeEnv), and grow the resulting
+//! [`MemoryReservation`] before allocating memory. How much memory each
+//! consumer may reserve is decided by the policy of the pool.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{DataFusionError, Result};
+
+/// Policy deciding whether memory reservations may grow.
+///
+/// Implementations are notified about every registered [`MemoryConsumer`]
+/// and every change to the size of its [`MemoryReservation`].
+pub trait MemoryPool: Send + Sync + Debug {
+    /// Registers a new [`MemoryConsumer`]. The default implementation does
+    /// nothing.
+    fn register(&self, _consumer: &MemoryConsumer) {}
+
+    /// Unregisters a [`MemoryConsumer`], after its reservation was freed.
+    /// The default implementation does nothing.
+    fn unregister(&self, _consumer: &MemoryConsumer) {}
+
+    /// Grows `reservation` by `additional` bytes, which are already
+    /// allocated and can therefore not be refused.
+    fn grow(&self, reservation: &MemoryReservation, additional: usize);
+
+    /// Shrinks `reservation` by `shrink` bytes
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize);
+
+    /// Grows `reservation` by `additional` bytes, or returns
+    /// [`DataFusionError::ResourcesExhausted`] if the policy does not allow
+    /// the reservation to grow.
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()>;
+
+    /// Total number of bytes currently reserved
+    fn reserved(&self) -> usize;
+}
+
+/// An operator, or part of an operator, reserving memory from a
+/// [`MemoryPool`]
+#[derive(Debug)]
+pub struct MemoryConsumer {
+    name: String,
+    can_spill: bool,
+}
+
+impl MemoryConsumer {
+    /// Create a consumer named `name` that can not spill to disk
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            can_spill: false,
+        }
+    }
+
+    /// Set whether this consumer can spill to disk to free memory
+    pub fn with_can_spill(mut self, can_spill: bool) -> Self {
+        self.can_spill = can_spill;
+        self
+    }
+
+    /// Name of the consumer
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the consumer can spill to disk to free memory
+    pub fn can_spill(&self) -> bool {
+        self.can_spill
+    }
+
+    /// Registers this consumer with `pool`, returning an empty reservation
+    pub fn register(self, pool: &Arc<dyn MemoryPool>) -> MemoryReservation {
+        pool.register(&self);
+        MemoryReservation {
+            consumer: self,
+            size: 0,
+            pool: pool.clone(),
+        }
+    }
+}
+
+/// Memory reserved by a [`MemoryConsumer`] from a [`MemoryPool`], which is
+/// returned to the pool when the reservation is dropped.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    consumer: MemoryConsumer,
+    size: usize,
+    pool: Arc<dyn MemoryPool>,
+}
+
+impl MemoryReservation {
+    /// The consumer holding this reservation
+    pub fn consumer(&self) -> &MemoryConsumer {
+        &self.consumer
+    }
+
+    /// Number of bytes reserved
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns all reserved memory to the pool, returning the number of
+    /// bytes freed
+    pub fn free(&mut self) -> usize {
+        let size = self.size;
+        if size != 0 {
+            self.shrink(size)
+        }
+        size
+    }
+
+    /// Shrinks the reservation by `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` exceeds the size of the reservation
+    pub fn shrink(&mut self, capacity: usize) {
+        let new_size = self.size.checked_sub(capacity).unwrap();
+        self.pool.shrink(self, capacity);
+        self.size = new_size;
+    }
+
+    /// Grows the reservation by `capacity` bytes, even if this exceeds the
+    /// limits of the pool
+    pub fn grow(&mut self, capacity: usize) {
+        self.pool.grow(self, capacity);
+        self.size += capacity;
+    }
+
+    /// Tries to grow the reservation by `capacity` bytes
+    pub fn try_grow(&mut self, capacity: usize) -> Result<()> {
+        self.pool.try_grow(self, capacity)?;
+        self.size += capacity;
+        Ok(())
+    }
+
+    /// Tries to set the size of the reservation to `capacity` bytes
+    pub fn try_resize(&mut self, capacity: usize) -> Result<()> {
+        if capacity > self.size {
+            self.try_grow(capacity - self.size)?
+        } else if capacity < self.size {
+            self.shrink(self.size - capacity)
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.free();
+        self.pool.unregister(&self.consumer);
+    }
+}
+
+/// A [`MemoryPool`] that never refuses a reservation
+#[derive(Debug, Default)]
+pub struct UnboundedMemoryPool {
+    used: AtomicUsize,
+}
+
+impl MemoryPool for UnboundedMemoryPool {
+    fn grow(&self, _reservation: &MemoryReservation, additional: usize) {
+        self.used.fetch_add(additional, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, _reservation: &MemoryReservation, shrink: usize) {
+        self.used.fetch_sub(shrink, Ordering::Relaxed);
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()> {
+        self.grow(reservation, additional);
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+/// A [`MemoryPool`] that hands out memory on a first-come first-served
+/// basis until `pool_size` bytes are reserved.
+///
+/// This is a good fit for a single query with a single memory hungry
+/// operator, but a consumer may starve all others.
+#[derive(Debug)]
+pub struct GreedyMemoryPool {
+    pool_size: usize,
+    used: AtomicUsize,
+}
+
+impl GreedyMemoryPool {
+    /// Create a pool of `pool_size` bytes
+    pub fn new(pool_size: usize) -> Self {
+        Self {
+            pool_size,
+            used: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl MemoryPool for GreedyMemoryPool {
+    fn grow(&self, _reservation: &MemoryReservation, additional: usize) {
+        self.used.fetch_add(additional, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, _reservation: &MemoryReservation, shrink: usize) {
+        self.used.fetch_sub(shrink, Ordering::Relaxed);
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                let new_used = used + additional;
+                if new_used <= self.pool_size {
+                    Some(new_used)
+                } else {
+                    None
+                }
+            })
+            .map_err(|used| {
+                insufficient_capacity_err(
+                    reservation,
+                    additional,
+                    self.pool_size.saturating_sub(used),
+                )
+            })?;
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+/// A [`MemoryPool`] that shares the memory fairly between the consumers
+/// that can spill, so that concurrent operators degrade predictably.
+///
+/// Consumers that can not spill are served first, as failing them would
+/// fail their query. The remaining memory is split evenly between all
+/// registered consumers that can spill, each of which may reserve at most
+/// its share.
+#[derive(Debug)]
+pub struct FairSpillPool {
+    pool_size: usize,
+    state: Mutex<FairSpillPoolState>,
+}
+
+#[derive(Debug)]
+struct FairSpillPoolState {
+    /// Number of registered consumers that can spill
+    num_spill: usize,
+    /// Bytes reserved by consumers that can spill
+    spillable: usize,
+    /// Bytes reserved by consumers that can not spill
+    unspillable: usize,
+}
+
+impl FairSpillPool {
+    /// Create a pool of `pool_size` bytes
+    pub fn new(pool_size: usize) -> Self {
+        Self {
+            pool_size,
+            state: Mutex::new(FairSpillPoolState {
+                num_spill: 0,
+                spillable: 0,
+                unspillable: 0,
+            }),
+        }
+    }
+}
+
+impl MemoryPool for FairSpillPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        if consumer.can_spill() {
+            self.state.lock().unwrap().num_spill += 1;
+        }
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        if consumer.can_spill() {
+            self.state.lock().unwrap().num_spill -= 1;
+        }
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        let mut state = self.state.lock().unwrap();
+        if reservation.consumer().can_spill() {
+            state.spillable += additional;
+        } else {
+            state.unspillable += additional;
+        }
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        let mut state = self.state.lock().unwrap();
+        if reservation.consumer().can_spill() {
+            state.spillable -= shrink;
+        } else {
+            state.unspillable -= shrink;
+        }
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if reservation.consumer().can_spill() {
+            // the memory not used by unspillable consumers is shared evenly
+            let spill_available = self.pool_size.saturating_sub(state.unspillable);
+            let available = spill_available
+                .checked_div(state.num_spill)
+                .unwrap_or(spill_available);
+            if reservation.size() + additional > available {
+                return Err(insufficient_capacity_err(
+                    reservation,
+                    additional,
+                    available.saturating_sub(reservation.size()),
+                ));
+            }
+            state.spillable += additional;
+        } else {
+            let available = self
+                .pool_size
+                .saturating_sub(state.unspillable + state.spillable);
+            if additional > available {
+                return Err(insufficient_capacity_err(
+                    reservation,
+                    additional,
+                    available,
+                ));
+            }
+            state.unspillable += additional;
+        }
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.spillable + state.unspillable
+    }
+}
+
+fn insufficient_capacity_err(
+    reservation: &MemoryReservation,
+    additional: usize,
+    available: usize,
+) -> DataFusionError {
+    DataFusionError::ResourcesExhausted(format!(
+        "Failed to allocate additional {} bytes for {} with {} bytes already allocated - maximum available is {}",
+        additional,
+        reservation.consumer().name(),
+        reservation.size(),
+        available
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greedy_pool() {
+        let pool: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(100));
+        let mut r1 = MemoryConsumer::new("r1").register(&pool);
+        let mut r2 = MemoryConsumer::new("r2").register(&pool);
+
+        r1.try_grow(70).unwrap();
+        let err = r2.try_grow(40).unwrap_err().to_string();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 40 bytes for r2 with 0 bytes already allocated - maximum available is 30");
+        r2.try_grow(30).unwrap();
+        assert_eq!(pool.reserved(), 100);
+
+        r1.shrink(20);
+        r2.try_resize(50).unwrap();
+        assert_eq!(pool.reserved(), 100);
+
+        drop(r1);
+        assert_eq!(pool.reserved(), 50);
+    }
+
+    #[test]
+    fn fair_spill_pool() {
+        let pool: Arc<dyn MemoryPool> = Arc::new(FairSpillPool::new(100));
+        let mut unspillable = MemoryConsumer::new("unspillable").register(&pool);
+        unspillable.try_grow(20).unwrap();
+
+        // a single spillable consumer gets all the remaining memory
+        let mut r1 = MemoryConsumer::new("r1")
+            .with_can_spill(true)
+            .register(&pool);
+        r1.try_grow(80).unwrap();
+        r1.shrink(50);
+
+        // two spillable consumers get half of the remaining memory each
+        let mut r2 = MemoryConsumer::new("r2")
+            .with_can_spill(true)
+            .register(&pool);
+        let err = r2.try_grow(50).unwrap_err().to_string();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 50 bytes for r2 with 0 bytes already allocated - maximum available is 40");
+        r2.try_grow(40).unwrap();
+        assert!(r1.try_grow(20).is_err());
+        r1.try_grow(10).unwrap();
+
+        // unspillable consumers can use all memory not reserved yet
+        assert!(unspillable.try_grow(1).is_err());
+        drop(r2);
+        unspillable.try_grow(40).unwrap();
+        assert_eq!(pool.reserved(), 100);
+    }
+}
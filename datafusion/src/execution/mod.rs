@@ -19,3 +19,10 @@
 
 pub mod context;
 pub mod dataframe_impl;
+pub mod disk_manager;
+pub mod extensions;
+pub mod function_factory;
+pub mod memory_pool;
+pub mod plan_cache;
+pub mod query_limits;
+pub mod runtime_env;
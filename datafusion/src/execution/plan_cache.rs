@@ -0,0 +1,369 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A cache of optimized logical plans keyed by their SQL text, so that
+//! queries issued repeatedly against an
+//! [`ExecutionContext`](super::context::ExecutionContext) skip parsing,
+//! planning and optimization.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::Result;
+use crate::logical_plan::{Expr, ExpressionVisitor, LogicalPlan, Recursion};
+use crate::physical_plan::functions::Volatility;
+
+/// Least recently used cache of optimized [`LogicalPlan`]s.
+///
+/// Plans are keyed by their normalized SQL (see [`normalize_sql`]) and
+/// the catalog version they were planned at. The catalog version is
+/// bumped by [`PlanCache::invalidate`] whenever something a plan could
+/// depend on changes, such as the registered tables, functions or the
+/// configuration, so that stale plans are never returned.
+#[derive(Debug, Default)]
+pub struct PlanCache {
+    /// Maximum number of cached plans. Zero disables the cache
+    capacity: usize,
+    inner: Mutex<PlanCacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct PlanCacheInner {
+    catalog_version: u64,
+    /// Incremented on every access, to find the least recently used plan
+    clock: u64,
+    plans: HashMap<String, CachedPlan>,
+}
+
+#[derive(Debug)]
+struct CachedPlan {
+    catalog_version: u64,
+    last_used: u64,
+    plan: LogicalPlan,
+}
+
+impl PlanCache {
+    /// Create a cache holding at most `capacity` plans
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(PlanCacheInner::default()),
+        }
+    }
+
+    /// Maximum number of cached plans
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns true if plans are cached at all
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Number of currently cached plans
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().plans.len()
+    }
+
+    /// Returns true if no plan is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The current catalog version. Plans must be inserted with the
+    /// version read before they were planned.
+    pub fn catalog_version(&self) -> u64 {
+        self.inner.lock().unwrap().catalog_version
+    }
+
+    /// Bump the catalog version and drop all cached plans
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.catalog_version += 1;
+        inner.plans.clear();
+    }
+
+    /// Get the plan cached for `sql` at the current catalog version
+    pub fn get(&self, sql: &str) -> Option<LogicalPlan> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let (clock, catalog_version) = (inner.clock, inner.catalog_version);
+        let cached = inner.plans.get_mut(&normalize_sql(sql))?;
+        if cached.catalog_version != catalog_version {
+            return None;
+        }
+        cached.last_used = clock;
+        Some(cached.plan.clone())
+    }
+
+    /// Cache `plan` for `sql`, if it was planned at `catalog_version`.
+    /// Evicts the least recently used plan if the cache is full.
+    ///
+    /// Callers must check that the plan can be reused with
+    /// [`is_cacheable`] before it is optimized.
+    pub fn insert(&self, sql: &str, catalog_version: u64, plan: &LogicalPlan) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        // the catalog changed while the plan was created
+        if inner.catalog_version != catalog_version {
+            return;
+        }
+
+        let key = normalize_sql(sql);
+        if !inner.plans.contains_key(&key) && inner.plans.len() >= self.capacity {
+            let lru = inner
+                .plans
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                inner.plans.remove(&lru);
+            }
+        }
+
+        inner.clock += 1;
+        let cached = CachedPlan {
+            catalog_version,
+            last_used: inner.clock,
+            plan: plan.clone(),
+        };
+        inner.plans.insert(key, cached);
+    }
+}
+
+/// Normalizes `sql` for use as a cache key: `--` and `/* */` comments and
+/// runs of whitespace outside of quoted strings and identifiers are
+/// collapsed into a single space, and leading and trailing whitespace and
+/// semicolons are removed.
+pub fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut pending_space = false;
+
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => {
+                pending_space = true;
+                continue;
+            }
+            // comments separate tokens like whitespace
+            None if c == '-' && chars.peek() == Some(&'-') => {
+                for c in &mut chars {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                pending_space = true;
+                continue;
+            }
+            None if c == '/' && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for c in &mut chars {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+                pending_space = true;
+                continue;
+            }
+            None => {
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+            }
+        }
+        if pending_space && !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        pending_space = false;
+        normalized.push(c);
+    }
+
+    while normalized.ends_with(';') || normalized.ends_with(' ') {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Returns false if the optimized version of the unoptimized `plan` can
+/// not be reused for later executions, because it calls functions that are
+/// not [`Volatility::Immutable`]: optimizing it may fold in per-query values
+/// such as the result of `now()`, and the results of volatile functions such
+/// as `random()` or `uuid()` must not be shared between executions.
+pub fn is_cacheable(plan: &LogicalPlan) -> bool {
+    struct MutableFunctionVisitor {
+        found: bool,
+    }
+
+    impl ExpressionVisitor for MutableFunctionVisitor {
+        fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+            if let Expr::ScalarFunction { fun, .. } = expr {
+                if fun.volatility() != Volatility::Immutable {
+                    self.found = true;
+                    return Ok(Recursion::Stop(self));
+                }
+            }
+            Ok(Recursion::Continue(self))
+        }
+    }
+
+    let calls_mutable_function = plan.expressions().iter().any(|expr| {
+        expr.accept(MutableFunctionVisitor { found: false })
+            .map(|visitor| visitor.found)
+            .unwrap_or(true)
+    });
+
+    !calls_mutable_function && plan.inputs().into_iter().all(is_cacheable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{lit, LogicalPlanBuilder};
+    use crate::physical_plan::functions::BuiltinScalarFunction;
+
+    fn plan(value: i64) -> LogicalPlan {
+        LogicalPlanBuilder::empty(true)
+            .project(vec![lit(value)])
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            "SELECT a, 'x  y' FROM t",
+            normalize_sql("  SELECT\ta,\n  'x  y'   FROM t ;")
+        );
+        assert_eq!(
+            "SELECT \"a  b\" FROM t",
+            normalize_sql("SELECT \"a  b\"\nFROM t")
+        );
+    }
+
+    #[test]
+    fn normalize_comments() {
+        assert_eq!("SELECT 1 + 1", normalize_sql("SELECT 1 -- c\n+ 1"));
+        assert_eq!("SELECT 1", normalize_sql("SELECT 1 -- c + 1"));
+        assert_eq!("SELECT 1 + 1", normalize_sql("SELECT 1 /* c\n*/ + 1;"));
+        assert_eq!("SELECT 1", normalize_sql("-- c\nSELECT 1; -- c"));
+        assert_eq!(
+            "SELECT '-- c', '/* c */'",
+            normalize_sql("SELECT '-- c', '/* c */'")
+        );
+    }
+
+    #[test]
+    fn get_and_insert() {
+        let cache = PlanCache::new(2);
+        let version = cache.catalog_version();
+
+        cache.insert("SELECT 1", version, &plan(1));
+        let cached = cache.get("SELECT  1;").expect("plan is cached");
+        assert_eq!(format!("{:?}", plan(1)), format!("{:?}", cached));
+        assert!(cache.get("SELECT 2").is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = PlanCache::new(2);
+        let version = cache.catalog_version();
+
+        cache.insert("SELECT 1", version, &plan(1));
+        cache.insert("SELECT 2", version, &plan(2));
+        cache.get("SELECT 1").unwrap();
+        cache.insert("SELECT 3", version, &plan(3));
+
+        assert_eq!(2, cache.len());
+        assert!(cache.get("SELECT 1").is_some());
+        assert!(cache.get("SELECT 2").is_none());
+        assert!(cache.get("SELECT 3").is_some());
+    }
+
+    #[test]
+    fn invalidate() {
+        let cache = PlanCache::new(2);
+        let version = cache.catalog_version();
+        cache.insert("SELECT 1", version, &plan(1));
+
+        cache.invalidate();
+        assert!(cache.is_empty());
+        assert!(cache.get("SELECT 1").is_none());
+
+        // plans created before the invalidation are not cached
+        cache.insert("SELECT 1", version, &plan(1));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = PlanCache::new(0);
+        cache.insert("SELECT 1", cache.catalog_version(), &plan(1));
+        assert!(cache.get("SELECT 1").is_none());
+    }
+
+    #[test]
+    fn volatile_plans_are_not_cached() {
+        for fun in &[BuiltinScalarFunction::Random, BuiltinScalarFunction::Uuid] {
+            let plan = LogicalPlanBuilder::empty(true)
+                .project(vec![Expr::ScalarFunction {
+                    fun: fun.clone(),
+                    args: vec![],
+                }])
+                .unwrap()
+                .build()
+                .unwrap();
+            assert!(!is_cacheable(&plan), "{}", fun);
+        }
+    }
+
+    #[test]
+    fn time_dependent_plans_are_not_cached() {
+        let now = LogicalPlanBuilder::empty(true)
+            .project(vec![Expr::ScalarFunction {
+                fun: BuiltinScalarFunction::Now,
+                args: vec![],
+            }])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(!is_cacheable(&now));
+
+        let above_now = LogicalPlanBuilder::from(&now)
+            .project(vec![lit(1)])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(!is_cacheable(&above_now));
+        assert!(is_cacheable(&plan(1)));
+    }
+}
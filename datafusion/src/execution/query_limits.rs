@@ -0,0 +1,343 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Limits on the resources a single query may use, and admission control of
+//! the queries sharing a [`RuntimeEnv`](super::runtime_env::RuntimeEnv), so
+//! that one runaway query can not take down a service embedding DataFusion.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+
+use crate::error::{DataFusionError, Result};
+use crate::execution::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation};
+
+/// What to do with a query that can not be admitted right away because too
+/// many partitions of other queries are executing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionPolicy {
+    /// Fail the query with [`DataFusionError::ResourcesExhausted`]
+    Fail,
+    /// Wait until enough partitions of other queries finished
+    Queue,
+}
+
+impl Default for AdmissionPolicy {
+    fn default() -> Self {
+        AdmissionPolicy::Fail
+    }
+}
+
+/// Limits on the resources used by the queries of an
+/// [`ExecutionContext`](super::context::ExecutionContext). No resource is
+/// limited by default.
+///
+/// Queries exceeding `max_memory` or `max_spill_bytes` while executing, or
+/// running for longer than `timeout`, fail with
+/// [`DataFusionError::ResourcesExhausted`]. Queries exceeding
+/// `max_concurrent_partitions` are failed or queued before they start,
+/// depending on the [`AdmissionPolicy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Maximum number of bytes the operators of a single query may hold.
+    /// Operators reserving memory beyond it are refused, and spill to disk
+    /// if they can.
+    pub max_memory: Option<usize>,
+    /// Maximum number of bytes a single query may spill to disk
+    pub max_spill_bytes: Option<usize>,
+    /// Maximum number of partitions executing at the same time, summed over
+    /// all running queries
+    pub max_concurrent_partitions: Option<usize>,
+    /// What to do with queries exceeding `max_concurrent_partitions`
+    pub admission_policy: AdmissionPolicy,
+    /// Maximum time a single query may execute, starting when it is admitted
+    pub timeout: Option<Duration>,
+}
+
+impl QueryLimits {
+    /// Create limits that do not limit any resource
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Limit the memory held by the operators of a single query
+    pub fn with_max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
+    /// Limit the number of bytes a single query may spill to disk
+    pub fn with_max_spill_bytes(mut self, max_spill_bytes: usize) -> Self {
+        self.max_spill_bytes = Some(max_spill_bytes);
+        self
+    }
+
+    /// Limit the number of partitions executing at the same time across all
+    /// queries
+    pub fn with_max_concurrent_partitions(mut self, max_partitions: usize) -> Self {
+        self.max_concurrent_partitions = Some(max_partitions);
+        self
+    }
+
+    /// Abort queries executing for longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Customize what happens to queries exceeding the concurrent partitions
+    pub fn with_admission_policy(mut self, admission_policy: AdmissionPolicy) -> Self {
+        self.admission_policy = admission_policy;
+        self
+    }
+
+    /// Returns true if no resource is limited
+    pub fn is_unlimited(&self) -> bool {
+        self.max_memory.is_none()
+            && self.max_spill_bytes.is_none()
+            && self.max_concurrent_partitions.is_none()
+            && self.timeout.is_none()
+    }
+}
+
+/// Admits queries as long as the partitions they execute, summed over all
+/// admitted queries, stay within a maximum
+#[derive(Debug)]
+pub struct AdmissionController {
+    /// One permit per partition that may execute
+    semaphore: Arc<Semaphore>,
+    /// Total number of permits
+    max_partitions: usize,
+    /// What to do if not enough permits are available
+    policy: AdmissionPolicy,
+}
+
+impl AdmissionController {
+    /// Create a controller admitting up to `max_partitions` partitions at once
+    pub fn new(max_partitions: usize, policy: AdmissionPolicy) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_partitions)),
+            max_partitions,
+            policy,
+        }
+    }
+
+    /// Number of partitions that can currently be admitted without waiting
+    pub fn available_partitions(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Admit a query executing `partitions` partitions at once. The
+    /// partitions are handed back once the returned permit is dropped.
+    pub async fn admit(&self, partitions: usize) -> Result<AdmissionPermit> {
+        if partitions > self.max_partitions {
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "Query executes {} partitions concurrently, more than the limit of {}",
+                partitions, self.max_partitions
+            )));
+        }
+
+        let permits = partitions as u32;
+        match self.policy {
+            AdmissionPolicy::Fail => match self.semaphore.try_acquire_many(permits) {
+                Ok(permit) => permit.forget(),
+                Err(_) => {
+                    return Err(DataFusionError::ResourcesExhausted(format!(
+                        "Query executes {} partitions concurrently, but only {} of {} are available",
+                        partitions,
+                        self.available_partitions(),
+                        self.max_partitions
+                    )))
+                }
+            },
+            AdmissionPolicy::Queue => self
+                .semaphore
+                .acquire_many(permits)
+                .await
+                .map_err(|e| DataFusionError::Internal(e.to_string()))?
+                .forget(),
+        }
+
+        Ok(AdmissionPermit {
+            semaphore: self.semaphore.clone(),
+            partitions,
+        })
+    }
+}
+
+/// Partitions admitted by an [`AdmissionController`], handed back when
+/// dropped
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    semaphore: Arc<Semaphore>,
+    partitions: usize,
+}
+
+impl AdmissionPermit {
+    /// Number of admitted partitions
+    pub fn partitions(&self) -> usize {
+        self.partitions
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(self.partitions);
+    }
+}
+
+/// A [`MemoryPool`] for the operators of a single query, refusing
+/// reservations once the query would hold more than `max_memory` bytes.
+/// Reservations within the limit are passed on to the pool shared by all
+/// queries, whose policy decides whether they may grow.
+#[derive(Debug)]
+pub struct QueryMemoryPool {
+    inner: Arc<dyn MemoryPool>,
+    max_memory: usize,
+    used: AtomicUsize,
+}
+
+impl QueryMemoryPool {
+    /// Create a pool limiting a query to `max_memory` bytes of `inner`
+    pub fn new(inner: Arc<dyn MemoryPool>, max_memory: usize) -> Self {
+        Self {
+            inner,
+            max_memory,
+            used: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl MemoryPool for QueryMemoryPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        self.inner.register(consumer)
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        self.inner.unregister(consumer)
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        self.used.fetch_add(additional, Ordering::Relaxed);
+        self.inner.grow(reservation, additional);
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        self.used.fetch_sub(shrink, Ordering::Relaxed);
+        self.inner.shrink(reservation, shrink);
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                let new_used = used + additional;
+                if new_used <= self.max_memory {
+                    Some(new_used)
+                } else {
+                    None
+                }
+            })
+            .map_err(|used| {
+                DataFusionError::ResourcesExhausted(format!(
+                    "Failed to allocate additional {} bytes for {}, as the query would hold {} bytes, more than the limit of {} bytes",
+                    additional,
+                    reservation.consumer().name(),
+                    used + additional,
+                    self.max_memory
+                ))
+            })?;
+        if let Err(e) = self.inner.try_grow(reservation, additional) {
+            self.used.fetch_sub(additional, Ordering::Relaxed);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::memory_pool::GreedyMemoryPool;
+    use futures::FutureExt;
+
+    #[tokio::test]
+    async fn admission_fail() -> Result<()> {
+        let admission = AdmissionController::new(4, AdmissionPolicy::Fail);
+
+        let permit = admission.admit(3).await?;
+        assert_eq!(admission.available_partitions(), 1);
+
+        let err = admission.admit(2).await.unwrap_err();
+        assert!(matches!(err, DataFusionError::ResourcesExhausted(_)));
+
+        drop(permit);
+        assert_eq!(admission.available_partitions(), 4);
+        admission.admit(2).await?;
+
+        // can never be admitted
+        let err = admission.admit(5).await.unwrap_err();
+        assert!(matches!(err, DataFusionError::ResourcesExhausted(_)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn admission_queue() -> Result<()> {
+        let admission = AdmissionController::new(4, AdmissionPolicy::Queue);
+
+        let permit = admission.admit(3).await?;
+        assert!(admission.admit(2).now_or_never().is_none());
+
+        drop(permit);
+        let permit = admission.admit(2).now_or_never().unwrap()?;
+        assert_eq!(permit.partitions(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn query_memory_pool() {
+        let shared: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(100));
+        let pool: Arc<dyn MemoryPool> =
+            Arc::new(QueryMemoryPool::new(shared.clone(), 50));
+        let mut r1 = MemoryConsumer::new("r1").register(&pool);
+        let mut r2 = MemoryConsumer::new("r2").register(&pool);
+
+        // the limit of the query is checked when memory is reserved
+        r1.try_grow(40).unwrap();
+        let err = r2.try_grow(20).unwrap_err().to_string();
+        assert!(err.contains("more than the limit of 50 bytes"), "{}", err);
+        r2.try_grow(10).unwrap();
+        assert_eq!(pool.reserved(), 50);
+        assert_eq!(shared.reserved(), 50);
+
+        // the shared pool may refuse reservations within the limit
+        r2.shrink(10);
+        let mut other = MemoryConsumer::new("other").register(&shared);
+        other.try_grow(60).unwrap();
+        assert!(r2.try_grow(10).is_err());
+        assert_eq!(pool.reserved(), 40);
+
+        drop(r1);
+        assert_eq!(pool.reserved(), 0);
+        assert_eq!(shared.reserved(), 60);
+    }
+}
@@ -0,0 +1,175 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution runtime environment, holding the resources shared by all
+//! queries run by an [`ExecutionContext`](super::context::ExecutionContext).
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::runtime::Handle;
+
+use crate::execution::disk_manager::DiskManager;
+use crate::execution::memory_pool::{GreedyMemoryPool, MemoryPool, UnboundedMemoryPool};
+use crate::execution::query_limits::{AdmissionController, QueryLimits, QueryMemoryPool};
+use crate::physical_plan::retry::RetryPolicy;
+
+/// Configuration of the [`RuntimeEnv`]
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    /// Directories to write spill files to. The temporary directory of the
+    /// OS is used if empty.
+    pub local_dirs: Vec<PathBuf>,
+    /// Policy for handing out memory to operators. Memory is not limited
+    /// if `None`.
+    pub memory_pool: Option<Arc<dyn MemoryPool>>,
+    /// Runtime to read from files and object stores on, separate from the
+    /// runtime executing the CPU heavy operators. Table scans run on the
+    /// runtime executing the query if `None`.
+    pub io_runtime: Option<Handle>,
+    /// Limits on the resources used by each query
+    pub query_limits: QueryLimits,
+    /// Policy for retrying to open the files read by table scans
+    pub read_retry: RetryPolicy,
+}
+
+impl RuntimeConfig {
+    /// Create a runtime config with default settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Customize the directories spill files are written to
+    pub fn with_local_dirs(mut self, local_dirs: Vec<PathBuf>) -> Self {
+        self.local_dirs = local_dirs;
+        self
+    }
+
+    /// Customize the policy for handing out memory to operators, such as a
+    /// [`GreedyMemoryPool`](super::memory_pool::GreedyMemoryPool) or a
+    /// [`FairSpillPool`](super::memory_pool::FairSpillPool)
+    pub fn with_memory_pool(mut self, memory_pool: Arc<dyn MemoryPool>) -> Self {
+        self.memory_pool = Some(memory_pool);
+        self
+    }
+
+    /// Limit the memory available to operators to `memory_fraction` of
+    /// `max_memory` bytes, handed out on a first come first served basis by
+    /// a [`GreedyMemoryPool`].
+    ///
+    /// # Panics
+    ///
+    /// If `memory_fraction` is not within `(0, 1]`
+    pub fn with_memory_limit(self, max_memory: usize, memory_fraction: f64) -> Self {
+        assert!(
+            memory_fraction > 0.0 && memory_fraction <= 1.0,
+            "memory_fraction must be within (0, 1], got {}",
+            memory_fraction
+        );
+        let pool_size = (max_memory as f64 * memory_fraction) as usize;
+        self.with_memory_pool(Arc::new(GreedyMemoryPool::new(pool_size)))
+    }
+
+    /// Run table scans on the runtime of `io_runtime`, which must be kept
+    /// alive for as long as queries are executed
+    pub fn with_io_runtime(mut self, io_runtime: Handle) -> Self {
+        self.io_runtime = Some(io_runtime);
+        self
+    }
+
+    /// Customize the limits on the resources used by each query
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.query_limits = query_limits;
+        self
+    }
+
+    /// Customize the policy for retrying to open the files read by table
+    /// scans, e.g. to recover from transient failures of remote file systems
+    pub fn with_read_retry(mut self, read_retry: RetryPolicy) -> Self {
+        self.read_retry = read_retry;
+        self
+    }
+}
+
+/// Resources available to operators during query execution
+#[derive(Debug)]
+pub struct RuntimeEnv {
+    /// Manages the files operators spill to
+    pub disk_manager: Arc<DiskManager>,
+    /// Hands out memory to operators
+    pub memory_pool: Arc<dyn MemoryPool>,
+    /// Runtime table scans are executed on, if any
+    pub io_runtime: Option<Handle>,
+    /// Limits on the resources used by each query
+    pub query_limits: QueryLimits,
+    /// Admits queries within the limit of concurrently executing
+    /// partitions, if any
+    pub admission: Option<Arc<AdmissionController>>,
+    /// Policy for retrying to open the files read by table scans
+    pub read_retry: RetryPolicy,
+}
+
+impl RuntimeEnv {
+    /// Create a runtime environment from `config`
+    pub fn new(config: RuntimeConfig) -> Self {
+        let query_limits = config.query_limits;
+        Self {
+            disk_manager: Arc::new(DiskManager::new(config.local_dirs)),
+            memory_pool: config
+                .memory_pool
+                .unwrap_or_else(|| Arc::new(UnboundedMemoryPool::default())),
+            io_runtime: config.io_runtime,
+            query_limits,
+            read_retry: config.read_retry,
+            admission: query_limits
+                .max_concurrent_partitions
+                .map(|max_partitions| {
+                    Arc::new(AdmissionController::new(
+                        max_partitions,
+                        query_limits.admission_policy,
+                    ))
+                }),
+        }
+    }
+
+    /// The resources available to the operators of a single query, which
+    /// reserve memory from a [`QueryMemoryPool`] enforcing the `max_memory`
+    /// of the query limits, if any, within the memory pool of this runtime
+    pub fn for_query(&self) -> Self {
+        let memory_pool = match self.query_limits.max_memory {
+            Some(max_memory) => {
+                Arc::new(QueryMemoryPool::new(self.memory_pool.clone(), max_memory))
+                    as Arc<dyn MemoryPool>
+            }
+            None => self.memory_pool.clone(),
+        };
+        Self {
+            disk_manager: self.disk_manager.clone(),
+            memory_pool,
+            io_runtime: self.io_runtime.clone(),
+            query_limits: self.query_limits,
+            admission: self.admission.clone(),
+            read_retry: self.read_retry,
+        }
+    }
+}
+
+impl Default for RuntimeEnv {
+    fn default() -> Self {
+        Self::new(RuntimeConfig::new())
+    }
+}
@@ -194,6 +194,8 @@ pub mod logical_plan;
 pub mod optimizer;
 pub mod physical_optimizer;
 pub mod physical_plan;
+#[cfg(feature = "pgwire")]
+pub mod pgwire;
 pub mod prelude;
 pub mod scalar;
 pub mod sql;
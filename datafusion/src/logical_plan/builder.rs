@@ -17,20 +17,25 @@
 
 //! This module provides a builder for creating LogicalPlans
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 
 use arrow::{
-    datatypes::{Schema, SchemaRef},
+    datatypes::{DataType, Schema, SchemaRef},
     record_batch::RecordBatch,
 };
 
 use super::dfschema::ToDFSchema;
 use super::{
-    col, exprlist_to_fields, Expr, JoinType, LogicalPlan, PlanType, StringifiedPlan,
+    col, combine_filters, exprlist_to_fields, lit, max, Expr, JoinType, LogicalPlan,
+    Operator, PlanType, StringifiedPlan,
 };
 use crate::datasource::TableProvider;
 use crate::error::{DataFusionError, Result};
-use crate::logical_plan::{DFField, DFSchema, DFSchemaRef, Partitioning};
+use crate::logical_plan::{
+    DFField, DFSchema, DFSchemaRef, DuplicateKeep, Partitioning, SampleMethod,
+};
+use crate::physical_plan::expressions::order_coercion;
+use crate::scalar::ScalarValue;
 use crate::{
     datasource::{empty::EmptyTable, parquet::ParquetTable, CsvFile, MemTable},
     prelude::CsvReadOptions,
@@ -95,6 +100,92 @@ impl LogicalPlanBuilder {
         })
     }
 
+    /// Create a relation from a list of literal rows, such as
+    /// `VALUES (1, 'a'), (2, NULL)`. The columns are named `column1`,
+    /// `column2`, ... and each has the common type of its non-null values,
+    /// to which the other values are cast.
+    pub fn values(values: Vec<Vec<Expr>>) -> Result<Self> {
+        let n_cols = match values.first() {
+            Some(row) if !row.is_empty() => row.len(),
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "Values list cannot be empty".to_string(),
+                ))
+            }
+        };
+        if let Some(row) = values.iter().find(|row| row.len() != n_cols) {
+            return Err(DataFusionError::Plan(format!(
+                "Inconsistent number of values in VALUES row: expected {}, got {}",
+                n_cols,
+                row.len()
+            )));
+        }
+
+        let empty_schema = DFSchema::empty();
+        let is_null = |expr: &Expr| matches!(expr, Expr::Literal(v) if v.is_null());
+
+        let mut fields = Vec::with_capacity(n_cols);
+        for i in 0..n_cols {
+            // nulls take the type of the other values of the column
+            let mut data_type: Option<DataType> = None;
+            for row in values.iter().filter(|row| !is_null(&row[i])) {
+                let value_type = row[i].get_type(&empty_schema)?;
+                data_type = match data_type {
+                    None => Some(value_type),
+                    Some(data_type) => Some(
+                        order_coercion(&data_type, &value_type).ok_or_else(|| {
+                            DataFusionError::Plan(format!(
+                                "Inconsistent data types in column{} of VALUES: \
+                                 {:?} and {:?}",
+                                i + 1,
+                                data_type,
+                                value_type
+                            ))
+                        })?,
+                    ),
+                };
+            }
+            let nullable = values
+                .iter()
+                .map(|row| row[i].nullable(&empty_schema))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .any(|nullable| nullable);
+            fields.push(DFField::new(
+                None,
+                &format!("column{}", i + 1),
+                data_type.unwrap_or(DataType::Utf8),
+                nullable,
+            ));
+        }
+
+        let values = values
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(fields.iter())
+                    .map(|(expr, field)| {
+                        if is_null(&expr) {
+                            Ok(Expr::Literal(ScalarValue::try_from(field.data_type())?))
+                        } else if &expr.get_type(&empty_schema)? != field.data_type() {
+                            Ok(Expr::Cast {
+                                expr: Box::new(expr),
+                                data_type: field.data_type().clone(),
+                            })
+                        } else {
+                            Ok(expr)
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::from(&LogicalPlan::Values {
+            schema: DFSchemaRef::new(DFSchema::new(fields)?),
+            values,
+        }))
+    }
+
     /// Scan a memory data source
     pub fn scan_memory(
         partitions: Vec<Vec<RecordBatch>>,
@@ -219,13 +310,8 @@ impl LogicalPlanBuilder {
 
     /// Apply a union
     pub fn union(&self, plan: LogicalPlan) -> Result<Self> {
-        let schema = self.plan.schema();
+        let schema = union_schema(&[self.plan.clone(), plan.clone()])?;
 
-        if plan.schema() != schema {
-            return Err(DataFusionError::Plan(
-                "Schema's for union should be the same ".to_string(),
-            ));
-        }
         // Add plan to existing union if possible
         let mut inputs = match &self.plan {
             LogicalPlan::Union { inputs, .. } => inputs.clone(),
@@ -235,7 +321,7 @@ impl LogicalPlanBuilder {
 
         Ok(Self::from(&LogicalPlan::Union {
             inputs,
-            schema: schema.clone(),
+            schema,
             alias: None,
         }))
     }
@@ -280,6 +366,250 @@ impl LogicalPlanBuilder {
         }))
     }
 
+    /// Apply a join on the conjunction of the `on` predicates.
+    ///
+    /// Equalities between a column of this plan and a column of `right` are
+    /// used as join keys. Any other predicate is applied as a filter on the
+    /// output of the join, which is only supported for inner joins.
+    pub fn join_on(
+        &self,
+        right: &LogicalPlan,
+        join_type: JoinType,
+        on: impl IntoIterator<Item = Expr>,
+    ) -> Result<Self> {
+        let left_schema = self.plan.schema();
+        let right_schema = right.schema();
+
+        let mut predicates = vec![];
+        on.into_iter()
+            .for_each(|expr| split_conjunction(expr, &mut predicates));
+
+        let mut keys: Vec<(String, String)> = vec![];
+        let mut filters = vec![];
+        for predicate in predicates {
+            match &predicate {
+                Expr::BinaryExpr {
+                    left,
+                    op: Operator::Eq,
+                    right,
+                } => match (left.as_ref(), right.as_ref()) {
+                    (Expr::Column(l), Expr::Column(r))
+                        if left_schema.field_with_unqualified_name(l).is_ok()
+                            && right_schema.field_with_unqualified_name(r).is_ok() =>
+                    {
+                        keys.push((l.clone(), r.clone()))
+                    }
+                    (Expr::Column(l), Expr::Column(r))
+                        if left_schema.field_with_unqualified_name(r).is_ok()
+                            && right_schema.field_with_unqualified_name(l).is_ok() =>
+                    {
+                        keys.push((r.clone(), l.clone()))
+                    }
+                    _ => filters.push(predicate),
+                },
+                _ => filters.push(predicate),
+            }
+        }
+
+        if !filters.is_empty() && !matches!(join_type, JoinType::Inner) {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{:?} joins only support equality predicates between columns of \
+                 both inputs, found {:?}",
+                join_type, filters
+            )));
+        }
+
+        let join = if keys.is_empty() {
+            if !matches!(join_type, JoinType::Inner) {
+                return Err(DataFusionError::Plan(format!(
+                    "{:?} joins require at least one equality predicate between \
+                     columns of both inputs",
+                    join_type
+                )));
+            }
+            self.cross_join(right)?
+        } else {
+            let left_keys: Vec<&str> = keys.iter().map(|(l, _)| l.as_str()).collect();
+            let right_keys: Vec<&str> = keys.iter().map(|(_, r)| r.as_str()).collect();
+            self.join(right, join_type, &left_keys, &right_keys)?
+        };
+
+        match combine_filters(&filters) {
+            Some(predicate) => join.filter(predicate),
+            None => Ok(join),
+        }
+    }
+
+    /// Apply an intersection, returning the distinct rows of this plan that
+    /// are also rows of `plan`. The inputs must have the same column types,
+    /// columns are matched by position and named after this plan.
+    pub fn intersect(&self, plan: &LogicalPlan) -> Result<Self> {
+        self.set_operation(plan, true)
+    }
+
+    /// Apply a difference, returning the distinct rows of this plan that are
+    /// not rows of `plan`. The inputs must have the same column types,
+    /// columns are matched by position and named after this plan.
+    pub fn except(&self, plan: &LogicalPlan) -> Result<Self> {
+        self.set_operation(plan, false)
+    }
+
+    /// Plans `INTERSECT` (or `EXCEPT`) as a union of both inputs tagged with
+    /// the side they come from, aggregated on all columns, keeping the
+    /// groups found on the left side and (or not) on the right side.
+    fn set_operation(&self, plan: &LogicalPlan, intersect: bool) -> Result<Self> {
+        const LEFT_MARKER: &str = "__set_left";
+        const RIGHT_MARKER: &str = "__set_right";
+
+        let left_fields = self.plan.schema().fields();
+        let right_fields = plan.schema().fields();
+        if left_fields.len() != right_fields.len()
+            || left_fields
+                .iter()
+                .zip(right_fields.iter())
+                .any(|(l, r)| l.data_type() != r.data_type())
+        {
+            return Err(DataFusionError::Plan(format!(
+                "{} requires inputs with the same column types",
+                if intersect { "INTERSECT" } else { "EXCEPT" }
+            )));
+        }
+
+        let columns: Vec<Expr> = left_fields.iter().map(|f| col(f.name())).collect();
+        let left = self
+            .project(columns.iter().cloned().chain(vec![
+                lit(1_i64).alias(LEFT_MARKER),
+                lit(0_i64).alias(RIGHT_MARKER),
+            ]))?
+            .build()?;
+        let right = Self::from(plan)
+            .project(
+                left_fields
+                    .iter()
+                    .zip(right_fields.iter())
+                    .map(|(l, r)| col(r.name()).alias(l.name()))
+                    .chain(vec![
+                        lit(0_i64).alias(LEFT_MARKER),
+                        lit(1_i64).alias(RIGHT_MARKER),
+                    ]),
+            )?
+            .build()?;
+
+        // the inputs may differ in nullability, so the union is nullable
+        // wherever either input is
+        let union_fields = left
+            .schema()
+            .fields()
+            .iter()
+            .zip(right.schema().fields())
+            .map(|(l, r)| {
+                DFField::new(
+                    None,
+                    l.name(),
+                    l.data_type().clone(),
+                    l.is_nullable() || r.is_nullable(),
+                )
+            })
+            .collect();
+        let union = LogicalPlan::Union {
+            inputs: vec![left, right],
+            schema: DFSchemaRef::new(DFSchema::new(union_fields)?),
+            alias: None,
+        };
+
+        let right_predicate = if intersect {
+            col(RIGHT_MARKER).gt(lit(0_i64))
+        } else {
+            col(RIGHT_MARKER).eq(lit(0_i64))
+        };
+        Self::from(&union)
+            .aggregate(
+                columns.clone(),
+                vec![
+                    max(col(LEFT_MARKER)).alias(LEFT_MARKER),
+                    max(col(RIGHT_MARKER)).alias(RIGHT_MARKER),
+                ],
+            )?
+            .filter(col(LEFT_MARKER).gt(lit(0_i64)).and(right_predicate))?
+            .project(columns)
+    }
+
+    /// Add a column named `name` computed by `expr`, replacing the column of
+    /// the same name if there is one.
+    pub fn with_column(&self, name: &str, expr: Expr) -> Result<Self> {
+        let mut replaced = false;
+        let mut projected_expr = vec![];
+        for field in self.plan.schema().fields() {
+            if field.name() == name {
+                replaced = true;
+                projected_expr.push(expr.clone().alias(name));
+            } else {
+                projected_expr.push(col(field.name()));
+            }
+        }
+        if !replaced {
+            projected_expr.push(expr.alias(name));
+        }
+        self.project(projected_expr)
+    }
+
+    /// Rename the column `old_name` to `new_name`
+    pub fn with_column_renamed(&self, old_name: &str, new_name: &str) -> Result<Self> {
+        let schema = self.plan.schema();
+        // error if there is no such column
+        schema.field_with_unqualified_name(old_name)?;
+        self.project(schema.fields().iter().map(|field| {
+            if field.name() == old_name {
+                col(old_name).alias(new_name)
+            } else {
+                col(field.name())
+            }
+        }))
+    }
+
+    /// Expand the list column `column` into one row per item, repeating the
+    /// values of the other columns. Rows with null or empty lists are dropped.
+    pub fn unnest(&self, column: &str) -> Result<Self> {
+        let schema = build_unnest_schema(self.plan.schema(), column)?;
+        Ok(Self::from(&LogicalPlan::Unnest {
+            input: Arc::new(self.plan.clone()),
+            column: column.to_owned(),
+            schema: DFSchemaRef::new(schema),
+        }))
+    }
+
+    /// Keep the first or the last row of every distinct value of the columns
+    /// `subset`, or of all columns if it is `None`
+    pub fn drop_duplicates(
+        &self,
+        subset: Option<&[&str]>,
+        keep: DuplicateKeep,
+    ) -> Result<Self> {
+        let schema = self.plan.schema();
+        let columns = match subset {
+            Some(subset) => subset
+                .iter()
+                .map(|name| Ok(schema.field_with_unqualified_name(name)?.name().clone()))
+                .collect::<Result<Vec<_>>>()?,
+            None => schema.fields().iter().map(|f| f.name().clone()).collect(),
+        };
+        Ok(Self::from(&LogicalPlan::Deduplicate {
+            input: Arc::new(self.plan.clone()),
+            columns,
+            keep,
+        }))
+    }
+
+    /// Sample the rows with `method`, reproducibly if a `seed` is given
+    pub fn sample(&self, method: SampleMethod, seed: Option<u64>) -> Result<Self> {
+        method.validate()?;
+        Ok(Self::from(&LogicalPlan::Sample {
+            input: Arc::new(self.plan.clone()),
+            method,
+            seed,
+        }))
+    }
+
     /// Repartition
     pub fn repartition(&self, partitioning_scheme: Partitioning) -> Result<Self> {
         Ok(Self::from(&LogicalPlan::Repartition {
@@ -377,6 +707,18 @@ impl LogicalPlanBuilder {
         }))
     }
 
+    /// Create an expression that runs the plan and represents its physical
+    /// plan annotated with the metrics collected while running it
+    pub fn explain_analyze(&self, verbose: bool) -> Result<Self> {
+        let schema = LogicalPlan::explain_schema();
+
+        Ok(Self::from(&LogicalPlan::Analyze {
+            verbose,
+            input: Arc::new(self.plan.clone()),
+            schema: schema.to_dfschema_ref()?,
+        }))
+    }
+
     /// Build the plan
     pub fn build(&self) -> Result<LogicalPlan> {
         Ok(self.plan.clone())
@@ -428,10 +770,105 @@ fn build_join_schema(
             // left then right
             left_fields.chain(right_fields).cloned().collect()
         }
+        JoinType::Semi | JoinType::Anti => {
+            // only the rows of the left side are returned, without the
+            // columns of the right side
+            left.fields().clone()
+        }
     };
     DFSchema::new(fields)
 }
 
+/// Creates the schema of unnesting the list column `column` of `input`, in
+/// which the list column is replaced by a column of its items
+fn build_unnest_schema(input: &DFSchema, column: &str) -> Result<DFSchema> {
+    let index = input.index_of(column)?;
+    let fields = input
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if i != index {
+                return Ok(field.clone());
+            }
+            match field.data_type() {
+                DataType::List(item) | DataType::LargeList(item) => Ok(DFField::new(
+                    field.qualifier().map(|q| q.as_str()),
+                    field.name(),
+                    item.data_type().clone(),
+                    item.is_nullable(),
+                )),
+                other => Err(DataFusionError::Plan(format!(
+                    "Only list columns can be unnested, column '{}' is of type {:?}",
+                    column, other
+                ))),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    DFSchema::new(fields)
+}
+
+/// Combines the schemas of the inputs of a union, which must have the same
+/// column names. Each column has the common type of the matching columns,
+/// and is nullable if any of them is.
+pub(crate) fn union_schema(inputs: &[LogicalPlan]) -> Result<DFSchemaRef> {
+    let (first, rest) = inputs.split_first().ok_or_else(|| {
+        DataFusionError::Plan("Union requires at least one input".to_string())
+    })?;
+
+    let mut fields = first.schema().fields().clone();
+    for input in rest {
+        let other_fields = input.schema().fields();
+        if other_fields.len() != fields.len()
+            || fields
+                .iter()
+                .zip(other_fields)
+                .any(|(field, other)| field.name() != other.name())
+        {
+            return Err(DataFusionError::Plan(
+                "Union inputs should have the same column names".to_string(),
+            ));
+        }
+        fields = fields
+            .iter()
+            .zip(other_fields)
+            .map(|(field, other)| {
+                let data_type = order_coercion(field.data_type(), other.data_type())
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(format!(
+                            "Union cannot coerce column {} from {:?} and {:?}",
+                            field.name(),
+                            field.data_type(),
+                            other.data_type()
+                        ))
+                    })?;
+                Ok(DFField::new(
+                    field.qualifier().map(|q| q.as_str()),
+                    field.name(),
+                    data_type,
+                    field.is_nullable() || other.is_nullable(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+    }
+    Ok(DFSchemaRef::new(DFSchema::new(fields)?))
+}
+
+/// Splits `expr` into the predicates of its top-level conjunction
+fn split_conjunction(expr: Expr, predicates: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(*left, predicates);
+            split_conjunction(*right, predicates);
+        }
+        other => predicates.push(other),
+    }
+}
+
 /// Errors if one or more expressions have equal names.
 fn validate_unique_names<'a>(
     node_name: &str,
@@ -486,6 +923,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn plan_builder_values() -> Result<()> {
+        let plan = LogicalPlanBuilder::values(vec![
+            vec![lit(1i64), lit("a")],
+            vec![lit(2.5f64), Expr::Literal(ScalarValue::Utf8(None))],
+        ])?
+        .build()?;
+
+        let expected = "Values: (CAST(Int64(1) AS Float64), Utf8(\"a\")), \
+                        (Float64(2.5), Utf8(NULL))";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let fields = plan.schema().fields();
+        assert_eq!(fields[0].name(), "column1");
+        assert_eq!(fields[0].data_type(), &DataType::Float64);
+        assert!(!fields[0].is_nullable());
+        assert_eq!(fields[1].name(), "column2");
+        assert_eq!(fields[1].data_type(), &DataType::Utf8);
+        assert!(fields[1].is_nullable());
+
+        // rows must have the same number of values
+        let result = LogicalPlanBuilder::values(vec![vec![lit(1i64)], vec![]]);
+        assert!(matches!(result, Err(DataFusionError::Plan(_))));
+
+        // values of a column must have a common type
+        let result = LogicalPlanBuilder::values(vec![vec![lit(1i64)], vec![lit(true)]]);
+        assert!(matches!(result, Err(DataFusionError::Plan(_))));
+
+        Ok(())
+    }
+
     #[test]
     fn plan_builder_aggregate() -> Result<()> {
         let plan = LogicalPlanBuilder::scan_empty(
@@ -616,6 +1084,137 @@ mod tests {
         }
     }
 
+    #[test]
+    fn plan_builder_unnest() -> Result<()> {
+        let item = Box::new(Field::new("item", DataType::Utf8, true));
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("tags", DataType::List(item), true),
+        ]);
+        let plan = LogicalPlanBuilder::scan_empty("posts", &schema, None)?
+            .unnest("tags")?
+            .build()?;
+
+        let expected = "Unnest: #tags\
+        \n  TableScan: posts projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let field = plan.schema().field_with_unqualified_name("tags")?;
+        assert_eq!(field.data_type(), &DataType::Utf8);
+        assert!(field.is_nullable());
+
+        let err = LogicalPlanBuilder::scan_empty("posts", &schema, None)?
+            .unnest("id")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Only list columns can be unnested, column 'id' is of type Int32"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_join_on() -> Result<()> {
+        let right_schema = Schema::new(vec![
+            Field::new("employee_id", DataType::Int32, false),
+            Field::new("bonus", DataType::Int32, false),
+        ]);
+        let right =
+            LogicalPlanBuilder::scan_empty("bonus", &right_schema, None)?.build()?;
+
+        let plan =
+            LogicalPlanBuilder::scan_empty("employee.csv", &employee_schema(), None)?
+                .join_on(
+                    &right,
+                    JoinType::Inner,
+                    vec![col("employee_id")
+                        .eq(col("id"))
+                        .and(col("bonus").gt(col("salary")))],
+                )?
+                .build()?;
+
+        let expected = "Filter: #bonus Gt #salary\
+        \n  Join: id = employee_id\
+        \n    TableScan: employee.csv projection=None\
+        \n    TableScan: bonus projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let err =
+            LogicalPlanBuilder::scan_empty("employee.csv", &employee_schema(), None)?
+                .join_on(&right, JoinType::Left, vec![col("bonus").gt(col("salary"))])
+                .err()
+                .unwrap();
+        assert!(err.to_string().contains("Left joins only support equality"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_intersect() -> Result<()> {
+        let other = LogicalPlanBuilder::scan_empty("other", &employee_schema(), None)?
+            .project(vec![col("state")])?
+            .build()?;
+        let plan =
+            LogicalPlanBuilder::scan_empty("employee.csv", &employee_schema(), None)?
+                .project(vec![col("state")])?
+                .intersect(&other)?
+                .build()?;
+
+        let expected = "Projection: #state\
+        \n  Filter: #__set_left Gt Int64(0) And #__set_right Gt Int64(0)\
+        \n    Aggregate: groupBy=[[#state]], aggr=[[MAX(#__set_left) AS __set_left, MAX(#__set_right) AS __set_right]]\
+        \n      Union\
+        \n        Projection: #state, Int64(1) AS __set_left, Int64(0) AS __set_right\
+        \n          Projection: #state\
+        \n            TableScan: employee.csv projection=None\
+        \n        Projection: #state AS state, Int64(0) AS __set_left, Int64(1) AS __set_right\
+        \n          Projection: #state\
+        \n            TableScan: other projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let err =
+            LogicalPlanBuilder::scan_empty("employee.csv", &employee_schema(), None)?
+                .project(vec![col("id")])?
+                .except(&other)
+                .err()
+                .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: EXCEPT requires inputs with the same column types"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_with_column() -> Result<()> {
+        let plan = LogicalPlanBuilder::scan_empty(
+            "employee.csv",
+            &employee_schema(),
+            Some(vec![0, 4]),
+        )?
+        .with_column("salary", col("salary") + lit(1))?
+        .with_column("bonus", lit(100))?
+        .with_column_renamed("id", "employee_id")?
+        .build()?;
+
+        let expected = "Projection: #id AS employee_id, #salary, #bonus\
+        \n  Projection: #id, #salary, Int32(100) AS bonus\
+        \n    Projection: #id, #salary Plus Int32(1) AS salary\
+        \n      TableScan: employee.csv projection=Some([0, 4])";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let err = LogicalPlanBuilder::from(&plan)
+            .with_column_renamed("id", "other")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("No field named 'id'"));
+
+        Ok(())
+    }
+
     fn employee_schema() -> Schema {
         Schema::new(vec![
             Field::new("id", DataType::Int32, false),
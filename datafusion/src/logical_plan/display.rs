@@ -120,30 +120,34 @@ pub fn display_schema(schema: &Schema) -> impl fmt::Display + '_ {
 
 /// Logic related to creating DOT language graphs.
 #[derive(Default)]
-struct GraphvizBuilder {
+pub(crate) struct GraphvizBuilder {
     id_gen: usize,
 }
 
 impl GraphvizBuilder {
-    fn next_id(&mut self) -> usize {
+    pub(crate) fn next_id(&mut self) -> usize {
         self.id_gen += 1;
         self.id_gen
     }
 
     // write out the start of the subgraph cluster
-    fn start_cluster(&mut self, f: &mut fmt::Formatter, title: &str) -> fmt::Result {
+    pub(crate) fn start_cluster(
+        &mut self,
+        f: &mut fmt::Formatter,
+        title: &str,
+    ) -> fmt::Result {
         writeln!(f, "  subgraph cluster_{}", self.next_id())?;
         writeln!(f, "  {{")?;
         writeln!(f, "    graph[label={}]", Self::quoted(title))
     }
 
     // write out the end of the subgraph cluster
-    fn end_cluster(&mut self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub(crate) fn end_cluster(&mut self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "  }}")
     }
 
     /// makes a quoted string suitable for inclusion in a graphviz chart
-    fn quoted(label: &str) -> String {
+    pub(crate) fn quoted(label: &str) -> String {
         let label = label.replace('"', "_");
         format!("\"{}\"", label)
     }
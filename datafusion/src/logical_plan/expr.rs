@@ -29,8 +29,8 @@ use arrow::{compute::can_cast_types, datatypes::DataType};
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::{DFField, DFSchema};
 use crate::physical_plan::{
-    aggregates, expressions::binary_operator_data_type, functions, udf::ScalarUDF,
-    window_functions,
+    aggregates, expressions::binary_operator_data_type, functions, struct_expressions,
+    type_coercion, udf::ScalarUDF, window_functions,
 };
 use crate::{physical_plan::udaf::AggregateUDF, scalar::ScalarValue};
 use functions::{ReturnTypeFunction, ScalarFunctionImplementation, Signature};
@@ -243,7 +243,39 @@ impl Expr {
                     .iter()
                     .map(|e| e.get_type(schema))
                     .collect::<Result<Vec<_>>>()?;
-                Ok((fun.return_type)(&data_types)?.as_ref().clone())
+                // the UDF receives its arguments coerced to its signature
+                let data_types = type_coercion::data_types(&data_types, &fun.signature)?;
+                let literals = args
+                    .iter()
+                    .map(|e| match e {
+                        Expr::Literal(value) => Some(value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                Ok(fun
+                    .return_type_for(&data_types, &literals)?
+                    .as_ref()
+                    .clone())
+            }
+            Expr::ScalarFunction {
+                fun: functions::BuiltinScalarFunction::NamedStruct,
+                args,
+            } => {
+                let literals = args
+                    .iter()
+                    .map(|e| match e {
+                        Expr::Literal(value) => Some(value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                let names = struct_expressions::named_struct_names(&literals)?;
+                let data_types = args
+                    .iter()
+                    .skip(1)
+                    .step_by(2)
+                    .map(|e| e.get_type(schema))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(struct_expressions::struct_type(&names, &data_types))
             }
             Expr::ScalarFunction { fun, args } => {
                 let data_types = args
@@ -495,6 +527,30 @@ impl Expr {
         }
     }
 
+    /// Returns true if this expression, or any of its children, may return
+    /// a different result on every evaluation, such as `random()`. See
+    /// [`Volatility`](functions::Volatility).
+    pub fn is_volatile(&self) -> Result<bool> {
+        struct VolatilityVisitor {
+            volatile: bool,
+        }
+
+        impl ExpressionVisitor for VolatilityVisitor {
+            fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+                if let Expr::ScalarFunction { fun, .. } = expr {
+                    self.volatile |= fun.volatility() == functions::Volatility::Volatile;
+                }
+                Ok(if self.volatile {
+                    Recursion::Stop(self)
+                } else {
+                    Recursion::Continue(self)
+                })
+            }
+        }
+
+        Ok(self.accept(VolatilityVisitor { volatile: false })?.volatile)
+    }
+
     /// Performs a depth first walk of an expression and
     /// its children, calling [`ExpressionVisitor::pre_visit`] and
     /// `visitor.post_visit`.
@@ -918,6 +974,16 @@ pub fn when(when: Expr, then: Expr) -> CaseBuilder {
     }
 }
 
+/// Returns the first of `args` that is not null, after coercing them to a
+/// common type. Each argument is only evaluated once, and only for the rows
+/// whose previous arguments are null.
+pub fn coalesce(args: Vec<Expr>) -> Expr {
+    Expr::ScalarFunction {
+        fun: functions::BuiltinScalarFunction::Coalesce,
+        args,
+    }
+}
+
 /// return a new expression l <op> r
 pub fn binary_expr(l: Expr, op: Operator, r: Expr) -> Expr {
     Expr::BinaryExpr {
@@ -1079,6 +1145,23 @@ pub fn lit<T: Literal>(n: T) -> Expr {
     n.lit()
 }
 
+/// Returns a new random version 4 UUID for every row
+pub fn uuid() -> Expr {
+    Expr::ScalarFunction {
+        fun: functions::BuiltinScalarFunction::Uuid,
+        args: vec![],
+    }
+}
+
+/// Returns a unique id for every row, that increases with the rows of each
+/// partition. The ids are not consecutive.
+pub fn monotonic_id() -> Expr {
+    Expr::ScalarFunction {
+        fun: functions::BuiltinScalarFunction::MonotonicId,
+        args: vec![],
+    }
+}
+
 /// Create an convenience function representing a unary scalar function
 macro_rules! unary_scalar_expr {
     ($ENUM:ident, $FUNC:ident) => {
@@ -1160,6 +1243,26 @@ pub fn array(args: Vec<Expr>) -> Expr {
     }
 }
 
+/// returns a list with each argument on it.
+pub fn make_array(args: Vec<Expr>) -> Expr {
+    Expr::ScalarFunction {
+        fun: functions::BuiltinScalarFunction::MakeArray,
+        args,
+    }
+}
+
+/// returns a struct with a field of each name and value.
+pub fn named_struct(fields: Vec<(&str, Expr)>) -> Expr {
+    let args = fields
+        .into_iter()
+        .flat_map(|(name, value)| vec![lit(name), value])
+        .collect();
+    Expr::ScalarFunction {
+        fun: functions::BuiltinScalarFunction::NamedStruct,
+        args,
+    }
+}
+
 /// Creates a new UDF with a specific signature and specific return type.
 /// This is a helper function to create a new UDF.
 /// The function `create_udf` returns a subset of all possible `ScalarFunction`:
@@ -1175,6 +1278,20 @@ pub fn create_udf(
     ScalarUDF::new(name, &Signature::Exact(input_types), &return_type, &fun)
 }
 
+/// Returns a [`ReturnTypeFunction`] for functions that return the type of their
+/// argument at `index`, e.g. `T -> T` for a UDF of one argument whose signature
+/// accepts several types.
+pub fn return_type_of_argument(index: usize) -> ReturnTypeFunction {
+    Arc::new(move |arg_types| match arg_types.get(index) {
+        Some(data_type) => Ok(Arc::new(data_type.clone())),
+        None => Err(DataFusionError::Plan(format!(
+            "The function expected at least {} arguments but received {}",
+            index + 1,
+            arg_types.len()
+        ))),
+    })
+}
+
 /// Creates a new UDAF with a specific signature, state type and return type.
 /// The signature and state type must match the `Accumulator's implementation`.
 #[allow(clippy::rc_buffer)]
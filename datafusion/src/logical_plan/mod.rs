@@ -23,28 +23,31 @@
 
 mod builder;
 mod dfschema;
-mod display;
+pub(crate) mod display;
 mod expr;
 mod extension;
 mod operators;
 mod plan;
 mod registry;
+pub(crate) use builder::union_schema;
 pub use builder::LogicalPlanBuilder;
 pub use dfschema::{DFField, DFSchema, DFSchemaRef, ToDFSchema};
 pub use display::display_schema;
 pub use expr::{
     abs, acos, and, array, ascii, asin, atan, avg, binary_expr, bit_length, btrim, case,
-    ceil, character_length, chr, col, combine_filters, concat, concat_ws, cos, count,
-    count_distinct, create_udaf, create_udf, exp, exprlist_to_fields, floor, in_list,
-    initcap, left, length, lit, ln, log10, log2, lower, lpad, ltrim, max, md5, min, now,
-    octet_length, or, random, regexp_match, regexp_replace, repeat, replace, reverse,
+    ceil, character_length, chr, coalesce, col, combine_filters, concat, concat_ws, cos,
+    count, count_distinct, create_udaf, create_udf, exp, exprlist_to_fields, floor,
+    in_list, initcap, left, length, lit, ln, log10, log2, lower, lpad, ltrim, make_array,
+    max, md5, min, monotonic_id, named_struct, now, octet_length, or, random,
+    regexp_match, regexp_replace, repeat, replace, return_type_of_argument, reverse,
     right, round, rpad, rtrim, sha224, sha256, sha384, sha512, signum, sin, split_part,
     sqrt, starts_with, strpos, substr, sum, tan, to_hex, translate, trim, trunc, upper,
-    when, Expr, ExprRewriter, ExpressionVisitor, Literal, Recursion,
+    uuid, when, Expr, ExprRewriter, ExpressionVisitor, Literal, Recursion,
 };
 pub use extension::UserDefinedLogicalNode;
 pub use operators::Operator;
 pub use plan::{
-    JoinType, LogicalPlan, Partitioning, PlanType, PlanVisitor, StringifiedPlan,
+    DuplicateKeep, JoinType, LogicalPlan, Partitioning, PlanRewriter, PlanType,
+    PlanVisitor, SampleMethod, StringifiedPlan,
 };
 pub use registry::FunctionRegistry;
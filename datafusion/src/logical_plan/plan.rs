@@ -24,7 +24,9 @@ use super::{
     display::{GraphvizVisitor, IndentVisitor},
 };
 use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
 use crate::logical_plan::dfschema::DFSchemaRef;
+use crate::optimizer::utils;
 use crate::sql::parser::FileType;
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::{
@@ -44,6 +46,71 @@ pub enum JoinType {
     Right,
     /// Full Join
     Full,
+    /// Semi Join, the rows of the left side that have a match on the right
+    Semi,
+    /// Anti Join, the rows of the left side that have no match on the right
+    Anti,
+}
+
+/// Which row of duplicates is kept by a deduplication
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateKeep {
+    /// Keep the first row of every key
+    First,
+    /// Keep the last row of every key
+    Last,
+}
+
+/// How the rows of a sample are chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMethod {
+    /// Keep each row independently with the probability `fraction`
+    Bernoulli {
+        /// The probability of keeping a row, between 0 and 1
+        fraction: f64,
+    },
+    /// Keep a uniformly chosen subset of `size` rows, or all rows if there
+    /// are fewer
+    Reservoir {
+        /// The number of rows to keep
+        size: usize,
+    },
+}
+
+impl SampleMethod {
+    /// Returns an error if the sample can not be taken
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SampleMethod::Bernoulli { fraction } if !(0.0..=1.0).contains(fraction) => {
+                Err(DataFusionError::Plan(format!(
+                    "The fraction of a bernoulli sample must be between 0 and 1, got {}",
+                    fraction
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The expected number of rows of a sample of `num_rows` rows
+    pub fn expected_num_rows(&self, num_rows: usize) -> usize {
+        match self {
+            SampleMethod::Bernoulli { fraction } => {
+                (num_rows as f64 * fraction).round() as usize
+            }
+            SampleMethod::Reservoir { size } => min(*size, num_rows),
+        }
+    }
+}
+
+impl Display for SampleMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SampleMethod::Bernoulli { fraction } => {
+                write!(f, "Bernoulli(fraction={})", fraction)
+            }
+            SampleMethod::Reservoir { size } => write!(f, "Reservoir(size={})", size),
+        }
+    }
 }
 
 /// A LogicalPlan represents the different types of relational
@@ -176,6 +243,47 @@ pub enum LogicalPlan {
         /// The schema description of the output
         schema: DFSchemaRef,
     },
+    /// Produces a relation from a list of literal rows, such as
+    /// `VALUES (1, 'a'), (2, 'b')`. All rows have the same number of
+    /// expressions, which have already been coerced to the types of the
+    /// schema.
+    Values {
+        /// The schema description of the output
+        schema: DFSchemaRef,
+        /// The rows of expressions
+        values: Vec<Vec<Expr>>,
+    },
+    /// Expands the list column `column` of its input into one row per
+    /// item, repeating the values of the other columns. Rows with null or
+    /// empty lists produce no rows.
+    Unnest {
+        /// The incoming logical plan
+        input: Arc<LogicalPlan>,
+        /// The name of the list column to unnest
+        column: String,
+        /// The schema description of the output, in which the list column
+        /// is replaced by a column of its items
+        schema: DFSchemaRef,
+    },
+    /// Produces one tuple of its input per distinct value of the columns
+    /// `columns`, the first or the last one in the order of the input.
+    Deduplicate {
+        /// The incoming logical plan
+        input: Arc<LogicalPlan>,
+        /// The names of the columns identifying duplicates
+        columns: Vec<String>,
+        /// Which tuple of duplicates is kept
+        keep: DuplicateKeep,
+    },
+    /// Produces a random subset of the tuples of its input.
+    Sample {
+        /// The incoming logical plan
+        input: Arc<LogicalPlan>,
+        /// How the tuples are chosen
+        method: SampleMethod,
+        /// The seed of the random number generator, random if `None`
+        seed: Option<u64>,
+    },
     /// Produces the first `n` tuples from its input and discards the rest.
     Limit {
         /// The limit
@@ -196,6 +304,16 @@ pub enum LogicalPlan {
         /// Whether the CSV file contains a header
         has_header: bool,
     },
+    /// Changes a configuration option of the execution context, see
+    /// [`ExecutionConfig::set`](crate::execution::context::ExecutionConfig::set).
+    SetVariable {
+        /// The name of the option
+        variable: String,
+        /// The new value of the option
+        value: String,
+        /// The empty schema of the statement
+        schema: DFSchemaRef,
+    },
     /// Produces a relation with string representations of
     /// various parts of the plan
     Explain {
@@ -208,6 +326,17 @@ pub enum LogicalPlan {
         /// The output schema of the explain (2 columns of text)
         schema: DFSchemaRef,
     },
+    /// Runs its input to completion and produces a relation with string
+    /// representations of its physical plan, annotated with the metrics
+    /// collected while running it
+    Analyze {
+        /// Should the total number of rows and the duration be included?
+        verbose: bool,
+        /// The logical plan that is being EXPLAIN ANALYZE'd
+        input: Arc<LogicalPlan>,
+        /// The output schema of the analyze (2 columns of text)
+        schema: DFSchemaRef,
+    },
     /// Extension operator defined outside of DataFusion
     Extension {
         /// The runtime extension operator
@@ -220,6 +349,7 @@ impl LogicalPlan {
     pub fn schema(&self) -> &DFSchemaRef {
         match self {
             LogicalPlan::EmptyRelation { schema, .. } => &schema,
+            LogicalPlan::Values { schema, .. } => &schema,
             LogicalPlan::TableScan {
                 projected_schema, ..
             } => &projected_schema,
@@ -232,8 +362,13 @@ impl LogicalPlan {
             LogicalPlan::CrossJoin { schema, .. } => &schema,
             LogicalPlan::Repartition { input, .. } => input.schema(),
             LogicalPlan::Limit { input, .. } => input.schema(),
+            LogicalPlan::Sample { input, .. } => input.schema(),
+            LogicalPlan::Deduplicate { input, .. } => input.schema(),
+            LogicalPlan::Unnest { schema, .. } => &schema,
             LogicalPlan::CreateExternalTable { schema, .. } => &schema,
+            LogicalPlan::SetVariable { schema, .. } => &schema,
             LogicalPlan::Explain { schema, .. } => &schema,
+            LogicalPlan::Analyze { schema, .. } => &schema,
             LogicalPlan::Extension { node } => &node.schema(),
             LogicalPlan::Union { schema, .. } => &schema,
         }
@@ -246,6 +381,8 @@ impl LogicalPlan {
                 projected_schema, ..
             } => vec![&projected_schema],
             LogicalPlan::Window { input, schema, .. }
+            | LogicalPlan::Unnest { input, schema, .. }
+            | LogicalPlan::Analyze { input, schema, .. }
             | LogicalPlan::Aggregate { input, schema, .. }
             | LogicalPlan::Projection { input, schema, .. } => {
                 let mut schemas = input.all_schemas();
@@ -274,8 +411,12 @@ impl LogicalPlan {
             LogicalPlan::Extension { node } => vec![&node.schema()],
             LogicalPlan::Explain { schema, .. }
             | LogicalPlan::EmptyRelation { schema, .. }
-            | LogicalPlan::CreateExternalTable { schema, .. } => vec![&schema],
+            | LogicalPlan::Values { schema, .. }
+            | LogicalPlan::CreateExternalTable { schema, .. }
+            | LogicalPlan::SetVariable { schema, .. } => vec![&schema],
             LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Sample { input, .. }
+            | LogicalPlan::Deduplicate { input, .. }
             | LogicalPlan::Repartition { input, .. }
             | LogicalPlan::Sort { input, .. }
             | LogicalPlan::Filter { input, .. } => input.all_schemas(),
@@ -327,14 +468,24 @@ impl LogicalPlan {
                 on.iter().flat_map(|(l, r)| vec![col(l), col(r)]).collect()
             }
             LogicalPlan::Sort { expr, .. } => expr.clone(),
+            LogicalPlan::Values { values, .. } => {
+                values.iter().flatten().cloned().collect()
+            }
+            LogicalPlan::Unnest { column, .. } => vec![col(column)],
+            LogicalPlan::Deduplicate { columns, .. } => {
+                columns.iter().map(|name| col(name)).collect()
+            }
             LogicalPlan::Extension { node } => node.expressions(),
             // plans without expressions
             LogicalPlan::TableScan { .. }
             | LogicalPlan::EmptyRelation { .. }
             | LogicalPlan::Limit { .. }
+            | LogicalPlan::Sample { .. }
             | LogicalPlan::CreateExternalTable { .. }
+            | LogicalPlan::SetVariable { .. }
             | LogicalPlan::CrossJoin { .. }
             | LogicalPlan::Explain { .. }
+            | LogicalPlan::Analyze { .. }
             | LogicalPlan::Union { .. } => {
                 vec![]
             }
@@ -354,12 +505,18 @@ impl LogicalPlan {
             LogicalPlan::Join { left, right, .. } => vec![left, right],
             LogicalPlan::CrossJoin { left, right, .. } => vec![left, right],
             LogicalPlan::Limit { input, .. } => vec![input],
+            LogicalPlan::Sample { input, .. } => vec![input],
+            LogicalPlan::Deduplicate { input, .. } => vec![input],
+            LogicalPlan::Unnest { input, .. } => vec![input],
+            LogicalPlan::Analyze { input, .. } => vec![input],
             LogicalPlan::Extension { node } => node.inputs(),
             LogicalPlan::Union { inputs, .. } => inputs.iter().collect(),
             // plans without inputs
             LogicalPlan::TableScan { .. }
             | LogicalPlan::EmptyRelation { .. }
+            | LogicalPlan::Values { .. }
             | LogicalPlan::CreateExternalTable { .. }
+            | LogicalPlan::SetVariable { .. }
             | LogicalPlan::Explain { .. } => vec![],
         }
     }
@@ -458,6 +615,10 @@ impl LogicalPlan {
                 true
             }
             LogicalPlan::Limit { input, .. } => input.accept(visitor)?,
+            LogicalPlan::Sample { input, .. } => input.accept(visitor)?,
+            LogicalPlan::Deduplicate { input, .. } => input.accept(visitor)?,
+            LogicalPlan::Unnest { input, .. } => input.accept(visitor)?,
+            LogicalPlan::Analyze { input, .. } => input.accept(visitor)?,
             LogicalPlan::Extension { node } => {
                 for input in node.inputs() {
                     if !input.accept(visitor)? {
@@ -469,7 +630,9 @@ impl LogicalPlan {
             // plans without inputs
             LogicalPlan::TableScan { .. }
             | LogicalPlan::EmptyRelation { .. }
+            | LogicalPlan::Values { .. }
             | LogicalPlan::CreateExternalTable { .. }
+            | LogicalPlan::SetVariable { .. }
             | LogicalPlan::Explain { .. } => true,
         };
         if !recurse {
@@ -484,6 +647,69 @@ impl LogicalPlan {
     }
 }
 
+/// Trait for potentially recursively rewriting a [`LogicalPlan`] tree.
+/// When passed to [`LogicalPlan::rewrite`], `pre_mutate` is invoked on
+/// every node before its inputs are rewritten (top-down), and `mutate`
+/// after all of its inputs have been rewritten (bottom-up).
+///
+/// For example, for a logical plan like:
+///
+/// ```text
+/// Projection: #id
+///    Filter: #state Eq Utf8(\"CO\")\
+///       CsvScan: employee.csv projection=Some([0, 3])";
+/// ```
+///
+/// The sequence of rewrite operations would be:
+/// ```text
+/// rewriter.pre_mutate(Projection)
+/// rewriter.pre_mutate(Filter)
+/// rewriter.pre_mutate(CsvScan)
+/// rewriter.mutate(CsvScan)
+/// rewriter.mutate(Filter)
+/// rewriter.mutate(Projection)
+/// ```
+pub trait PlanRewriter: Sized {
+    /// Invoked before any inputs of `plan` are rewritten and returns a
+    /// potentially modified plan, whose inputs are rewritten next.
+    /// Default implementation returns `plan` unchanged.
+    fn pre_mutate(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        Ok(plan)
+    }
+
+    /// Invoked after all inputs of `plan` have been rewritten and
+    /// returns a potentially modified plan. Default implementation
+    /// returns `plan` unchanged.
+    fn mutate(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        Ok(plan)
+    }
+}
+
+impl LogicalPlan {
+    /// Performs a depth first walk of the plan, rewriting every node
+    /// with `rewriter`. See [`PlanRewriter`] for the order in which
+    /// nodes are rewritten.
+    pub fn rewrite<R>(self, rewriter: &mut R) -> Result<Self>
+    where
+        R: PlanRewriter,
+    {
+        let plan = rewriter.pre_mutate(self)?;
+
+        let inputs = plan
+            .inputs()
+            .into_iter()
+            .map(|input| input.clone().rewrite(rewriter))
+            .collect::<Result<Vec<_>>>()?;
+        let plan = if inputs.is_empty() {
+            plan
+        } else {
+            utils::from_plan(&plan, &plan.expressions(), &inputs)?
+        };
+
+        rewriter.mutate(plan)
+    }
+}
+
 // Various implementations for printing out LogicalPlans
 impl LogicalPlan {
     /// Return a `format`able structure that produces a single line
@@ -657,6 +883,21 @@ impl LogicalPlan {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 match &*self.0 {
                     LogicalPlan::EmptyRelation { .. } => write!(f, "EmptyRelation"),
+                    LogicalPlan::Values { ref values, .. } => {
+                        let rows = values
+                            .iter()
+                            .take(5)
+                            .map(|row| {
+                                let items = row
+                                    .iter()
+                                    .map(|expr| format!("{:?}", expr))
+                                    .collect::<Vec<_>>();
+                                format!("({})", items.join(", "))
+                            })
+                            .collect::<Vec<_>>();
+                        let ellipsis = if values.len() > 5 { "..." } else { "" };
+                        write!(f, "Values: {}{}", rows.join(", "), ellipsis)
+                    }
                     LogicalPlan::TableScan {
                         ref table_name,
                         ref projection,
@@ -757,10 +998,41 @@ impl LogicalPlan {
                         }
                     },
                     LogicalPlan::Limit { ref n, .. } => write!(f, "Limit: {}", n),
+                    LogicalPlan::Sample {
+                        ref method,
+                        ref seed,
+                        ..
+                    } => {
+                        write!(f, "Sample: {}", method)?;
+                        if let Some(seed) = seed {
+                            write!(f, " seed={}", seed)?;
+                        }
+                        Ok(())
+                    }
+                    LogicalPlan::Unnest { ref column, .. } => {
+                        write!(f, "Unnest: #{}", column)
+                    }
+                    LogicalPlan::Deduplicate {
+                        ref columns,
+                        ref keep,
+                        ..
+                    } => {
+                        let columns = columns
+                            .iter()
+                            .map(|name| format!("#{}", name))
+                            .collect::<Vec<_>>();
+                        write!(f, "Deduplicate: {} keep={:?}", columns.join(", "), keep)
+                    }
                     LogicalPlan::CreateExternalTable { ref name, .. } => {
                         write!(f, "CreateExternalTable: {:?}", name)
                     }
+                    LogicalPlan::SetVariable {
+                        ref variable,
+                        ref value,
+                        ..
+                    } => write!(f, "SetVariable: {} = {}", variable, value),
                     LogicalPlan::Explain { .. } => write!(f, "Explain"),
+                    LogicalPlan::Analyze { .. } => write!(f, "Analyze"),
                     LogicalPlan::Union { .. } => write!(f, "Union"),
                     LogicalPlan::Extension { ref node } => node.fmt_for_explain(f),
                 }
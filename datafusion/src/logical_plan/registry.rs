@@ -17,18 +17,60 @@
 
 use std::{collections::HashSet, sync::Arc};
 
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::physical_plan::udaf::AggregateUDF;
 use crate::physical_plan::udf::ScalarUDF;
 
 /// A registry knows how to build logical expressions out of user-defined function' names
+///
+/// Functions are looked up by their name and by their aliases. Registries
+/// that can be modified override the `register_*` and `deregister_*`
+/// methods, which error by default.
 pub trait FunctionRegistry {
-    /// Set of all available udfs.
+    /// Set of all available udfs, including their aliases.
     fn udfs(&self) -> HashSet<String>;
 
+    /// Set of all available udafs, including their aliases.
+    fn udafs(&self) -> HashSet<String>;
+
     /// Returns a reference to the udf named `name`.
     fn udf(&self, name: &str) -> Result<Arc<ScalarUDF>>;
 
     /// Returns a reference to the udaf named `name`.
     fn udaf(&self, name: &str) -> Result<Arc<AggregateUDF>>;
+
+    /// Registers `udf` under its name and aliases, returning the udf
+    /// previously registered under its name, if any.
+    fn register_udf(&mut self, _udf: Arc<ScalarUDF>) -> Result<Option<Arc<ScalarUDF>>> {
+        Err(DataFusionError::NotImplemented(
+            "Registering UDFs is not supported by this registry".to_string(),
+        ))
+    }
+
+    /// Registers `udaf` under its name and aliases, returning the udaf
+    /// previously registered under its name, if any.
+    fn register_udaf(
+        &mut self,
+        _udaf: Arc<AggregateUDF>,
+    ) -> Result<Option<Arc<AggregateUDF>>> {
+        Err(DataFusionError::NotImplemented(
+            "Registering UDAFs is not supported by this registry".to_string(),
+        ))
+    }
+
+    /// Removes the udf named `name` along with its other names, returning
+    /// it if it was registered.
+    fn deregister_udf(&mut self, _name: &str) -> Result<Option<Arc<ScalarUDF>>> {
+        Err(DataFusionError::NotImplemented(
+            "Deregistering UDFs is not supported by this registry".to_string(),
+        ))
+    }
+
+    /// Removes the udaf named `name` along with its other names, returning
+    /// it if it was registered.
+    fn deregister_udaf(&mut self, _name: &str) -> Result<Option<Arc<AggregateUDF>>> {
+        Err(DataFusionError::NotImplemented(
+            "Deregistering UDAFs is not supported by this registry".to_string(),
+        ))
+    }
 }
@@ -27,9 +27,11 @@ use crate::execution::context::ExecutionProps;
 use crate::logical_plan::{DFSchemaRef, Expr, ExprRewriter, LogicalPlan, Operator};
 use crate::optimizer::optimizer::OptimizerRule;
 use crate::optimizer::utils;
+use crate::physical_plan::expressions::cast_column;
 use crate::physical_plan::functions::BuiltinScalarFunction;
 use crate::scalar::ScalarValue;
 use arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
+use arrow::compute::CastOptions;
 
 /// Optimizer that simplifies comparison expressions involving boolean literals.
 ///
@@ -40,6 +42,8 @@ use arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
 /// * `false = true` and `true = false` to `false`
 /// * `!!expr` to `expr`
 /// * `expr = null` and `expr != null` to `null`
+/// * `CAST(literal AS type)` and `TRY_CAST(literal AS type)` to a literal, unless
+///   the cast errors, which is then left to execution
 pub struct ConstantFolding {}
 
 impl ConstantFolding {
@@ -76,11 +80,17 @@ impl OptimizerRule for ConstantFolding {
             | LogicalPlan::Aggregate { .. }
             | LogicalPlan::Repartition { .. }
             | LogicalPlan::CreateExternalTable { .. }
+            | LogicalPlan::SetVariable { .. }
             | LogicalPlan::Extension { .. }
             | LogicalPlan::Sort { .. }
             | LogicalPlan::Explain { .. }
+            | LogicalPlan::Analyze { .. }
             | LogicalPlan::Limit { .. }
+            | LogicalPlan::Sample { .. }
+            | LogicalPlan::Deduplicate { .. }
+            | LogicalPlan::Unnest { .. }
             | LogicalPlan::Union { .. }
+            | LogicalPlan::Values { .. }
             | LogicalPlan::Join { .. }
             | LogicalPlan::CrossJoin { .. } => {
                 // apply the optimization to all inputs of the plan
@@ -127,6 +137,20 @@ impl<'a> ConstantRewriter<'a> {
     }
 }
 
+/// Casts the literal `value` to `data_type` the same way it is cast during
+/// execution, returning `None` if that errors or the result can't be a
+/// literal of `data_type`, such as a dictionary
+fn cast_literal(
+    value: &ScalarValue,
+    data_type: &DataType,
+    cast_options: &CastOptions,
+) -> Option<ScalarValue> {
+    let array = cast_column(&value.to_array(), data_type, cast_options).ok()?;
+    ScalarValue::try_from_array(&array, 0)
+        .ok()
+        .filter(|value| &value.get_datatype() == data_type)
+}
+
 impl<'a> ExprRewriter for ConstantRewriter<'a> {
     /// rewrite the expression simplifying any constant expressions
     fn mutate(&mut self, expr: Expr) -> Result<Expr> {
@@ -210,6 +234,32 @@ impl<'a> ExprRewriter for ConstantRewriter<'a> {
                     Expr::Not(inner)
                 }
             }
+            Expr::Cast { expr, data_type } => {
+                let cast_options = CastOptions {
+                    safe: self.execution_props.safe_casts,
+                };
+                match expr.as_ref() {
+                    Expr::Literal(value) => {
+                        match cast_literal(value, &data_type, &cast_options) {
+                            Some(value) => Expr::Literal(value),
+                            None => Expr::Cast { expr, data_type },
+                        }
+                    }
+                    _ => Expr::Cast { expr, data_type },
+                }
+            }
+            Expr::TryCast { expr, data_type } => {
+                let cast_options = CastOptions { safe: true };
+                match expr.as_ref() {
+                    Expr::Literal(value) => {
+                        match cast_literal(value, &data_type, &cast_options) {
+                            Some(value) => Expr::Literal(value),
+                            None => Expr::TryCast { expr, data_type },
+                        }
+                    }
+                    _ => Expr::TryCast { expr, data_type },
+                }
+            }
             Expr::ScalarFunction {
                 fun: BuiltinScalarFunction::Now,
                 ..
@@ -302,6 +352,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn optimize_expr_cast_literal() -> Result<()> {
+        let schema = expr_test_schema();
+        let mut rewriter = ConstantRewriter {
+            schemas: vec![&schema],
+            execution_props: &ExecutionProps::new(),
+        };
+        let cast = |value: i64, data_type: DataType| Expr::Cast {
+            expr: Box::new(lit(value)),
+            data_type,
+        };
+
+        assert_eq!(
+            cast(5, DataType::Int8).rewrite(&mut rewriter)?,
+            lit(ScalarValue::Int8(Some(5))),
+        );
+        // overflowing casts are left to error during execution
+        assert_eq!(
+            cast(300, DataType::Int8).rewrite(&mut rewriter)?,
+            cast(300, DataType::Int8),
+        );
+        assert_eq!(
+            Expr::TryCast {
+                expr: Box::new(lit(300_i64)),
+                data_type: DataType::Int8,
+            }
+            .rewrite(&mut rewriter)?,
+            lit(ScalarValue::Int8(None)),
+        );
+
+        let mut rewriter = ConstantRewriter {
+            schemas: vec![&schema],
+            execution_props: &ExecutionProps {
+                query_execution_start_time: Utc::now(),
+                safe_casts: true,
+            },
+        };
+        assert_eq!(
+            cast(300, DataType::Int8).rewrite(&mut rewriter)?,
+            lit(ScalarValue::Int8(None)),
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn optimize_expr_null_comparison() -> Result<()> {
         let schema = expr_test_schema();
@@ -654,6 +749,7 @@ mod tests {
         let rule = ConstantFolding::new();
         let execution_props = ExecutionProps {
             query_execution_start_time: *date_time,
+            safe_casts: false,
         };
 
         let optimized_plan = rule
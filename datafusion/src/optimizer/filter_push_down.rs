@@ -271,15 +271,25 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
             // A projection is filter-commutable, but re-writes all predicate expressions
             // collect projection.
             let mut projection = HashMap::new();
-            schema.fields().iter().enumerate().for_each(|(i, field)| {
+            let mut volatile_columns = HashSet::new();
+            for (i, field) in schema.fields().iter().enumerate() {
                 // strip alias, as they should not be part of filters
                 let expr = match &expr[i] {
                     Expr::Alias(expr, _) => expr.as_ref().clone(),
                     expr => expr.clone(),
                 };
 
+                if expr.is_volatile()? {
+                    volatile_columns.insert(field.name().clone());
+                }
                 projection.insert(field.name().clone(), expr);
-            });
+            }
+
+            // filters on volatile expressions such as `random()` must stay above the
+            // projection, as re-writing them would evaluate the expression twice
+            if !get_predicates(&state, &volatile_columns).0.is_empty() {
+                return issue_filters(state, volatile_columns, plan);
+            }
 
             // re-write all filters based on this projection
             // E.g. in `Filter: #b\n  Projection: #a > 1 as b`, we can swap them, but the filter must be "#a > 1"
@@ -328,6 +338,35 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                 .collect::<HashSet<_>>();
             issue_filters(state, used_columns, plan)
         }
+        LogicalPlan::Sample { input, .. } => {
+            // filtering a sample is not the same as sampling the filtered
+            // rows => collect all columns from its input
+            let used_columns = input
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<HashSet<_>>();
+            issue_filters(state, used_columns, plan)
+        }
+        LogicalPlan::Deduplicate { input, columns, .. } => {
+            // filters on the key columns keep or remove all duplicates of a
+            // key => only filters on the other columns must stay above
+            let used_columns = input
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .filter(|name| !columns.contains(name))
+                .collect::<HashSet<_>>();
+            issue_filters(state, used_columns, plan)
+        }
+        LogicalPlan::Unnest { column, .. } => {
+            // only filters on the unnested column must stay above the unnest
+            let mut used_columns = HashSet::new();
+            used_columns.insert(column.clone());
+            issue_filters(state, used_columns, plan)
+        }
         LogicalPlan::Join { left, right, .. }
         | LogicalPlan::CrossJoin { left, right, .. } => {
             let (pushable_to_left, pushable_to_right, keep) =
@@ -455,6 +494,7 @@ mod tests {
     use crate::datasource::datasource::Statistics;
     use crate::datasource::TableProvider;
     use crate::logical_plan::{lit, sum, DFSchema, Expr, LogicalPlanBuilder, Operator};
+    use crate::physical_plan::functions::BuiltinScalarFunction;
     use crate::physical_plan::ExecutionPlan;
     use crate::test::*;
     use crate::{logical_plan::col, prelude::JoinType};
@@ -506,6 +546,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn filter_on_volatile_projection() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let random = Expr::ScalarFunction {
+            fun: BuiltinScalarFunction::Random,
+            args: vec![],
+        };
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .project(vec![col("a"), random.alias("r")])?
+            .filter(and(col("a").eq(lit(1i64)), col("r").lt(lit(0.5))))?
+            .build()?;
+        // the filter on `r` is not pushed down, as `random` would be evaluated twice
+        let expected = "\
+            Filter: #r Lt Float64(0.5)\
+            \n  Projection: #a, random() AS r\
+            \n    Filter: #a Eq Int64(1)\
+            \n      TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
     #[test]
     fn filter_no_columns() -> Result<()> {
         let table_scan = test_table_scan()?;
@@ -50,10 +50,16 @@ fn get_num_rows(logical_plan: &LogicalPlan) -> Option<usize> {
                 Some(0)
             }
         }
+        LogicalPlan::Values { values, .. } => Some(values.len()),
         LogicalPlan::Limit { n: limit, input } => {
             let num_rows_input = get_num_rows(input);
             num_rows_input.map(|rows| std::cmp::min(*limit, rows))
         }
+        LogicalPlan::Sample { input, method, .. } => {
+            // the size of a bernoulli sample is only an estimate, which is
+            // good enough to choose the build side of a join
+            get_num_rows(input).map(|rows| method.expected_num_rows(rows))
+        }
         LogicalPlan::Window { input, .. } => {
             // window functions do not change num of rows
             get_num_rows(input)
@@ -80,9 +86,19 @@ fn get_num_rows(logical_plan: &LogicalPlan) -> Option<usize> {
             // we cannot predict how rows will be repartitioned
             None
         }
+        LogicalPlan::Unnest { .. } => {
+            // we do not know the lengths of the lists
+            None
+        }
+        LogicalPlan::Deduplicate { .. } => {
+            // we do not know how many rows are duplicates
+            None
+        }
         // the following operators are special cases and not querying data
         LogicalPlan::CreateExternalTable { .. } => None,
+        LogicalPlan::SetVariable { .. } => None,
         LogicalPlan::Explain { .. } => None,
+        LogicalPlan::Analyze { .. } => None,
         // we do not support estimating rows with extensions yet
         LogicalPlan::Extension { .. } => None,
         // the following operators do not modify row count in any way
@@ -128,7 +144,10 @@ impl OptimizerRule for HashBuildProbeOrder {
             } => {
                 let left = self.optimize(left, execution_props)?;
                 let right = self.optimize(right, execution_props)?;
-                if should_swap_join_order(&left, &right) {
+                if let (true, Some(swapped_join_type)) = (
+                    should_swap_join_order(&left, &right),
+                    swap_join_type(*join_type),
+                ) {
                     // Swap left and right, change join type and (equi-)join key order
                     Ok(LogicalPlan::Join {
                         left: Arc::new(right),
@@ -137,7 +156,7 @@ impl OptimizerRule for HashBuildProbeOrder {
                             .iter()
                             .map(|(l, r)| (r.to_string(), l.to_string()))
                             .collect(),
-                        join_type: swap_join_type(*join_type),
+                        join_type: swapped_join_type,
                         schema: schema.clone(),
                     })
                 } else {
@@ -180,12 +199,18 @@ impl OptimizerRule for HashBuildProbeOrder {
             | LogicalPlan::Aggregate { .. }
             | LogicalPlan::TableScan { .. }
             | LogicalPlan::Limit { .. }
+            | LogicalPlan::Sample { .. }
+            | LogicalPlan::Deduplicate { .. }
             | LogicalPlan::Filter { .. }
             | LogicalPlan::Repartition { .. }
+            | LogicalPlan::Unnest { .. }
             | LogicalPlan::EmptyRelation { .. }
+            | LogicalPlan::Values { .. }
             | LogicalPlan::Sort { .. }
             | LogicalPlan::CreateExternalTable { .. }
+            | LogicalPlan::SetVariable { .. }
             | LogicalPlan::Explain { .. }
+            | LogicalPlan::Analyze { .. }
             | LogicalPlan::Union { .. }
             | LogicalPlan::Extension { .. } => {
                 let expr = plan.expressions();
@@ -210,12 +235,15 @@ impl HashBuildProbeOrder {
     }
 }
 
-fn swap_join_type(join_type: JoinType) -> JoinType {
+/// The join type of a join with swapped inputs, if there is one
+fn swap_join_type(join_type: JoinType) -> Option<JoinType> {
     match join_type {
-        JoinType::Inner => JoinType::Inner,
-        JoinType::Full => JoinType::Full,
-        JoinType::Left => JoinType::Right,
-        JoinType::Right => JoinType::Left,
+        JoinType::Inner => Some(JoinType::Inner),
+        JoinType::Full => Some(JoinType::Full),
+        JoinType::Left => Some(JoinType::Right),
+        JoinType::Right => Some(JoinType::Left),
+        // only the left side of these is part of the output
+        JoinType::Semi | JoinType::Anti => None,
     }
 }
 
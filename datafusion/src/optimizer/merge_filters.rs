@@ -0,0 +1,128 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule that combines stacked filters into a single filter with a
+//! conjunction of their predicates.
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::{and, LogicalPlan};
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::optimizer::utils;
+
+/// Optimization rule that merges adjacent [LogicalPlan::Filter] nodes.
+///
+/// ```text
+/// Filter: #a Gt Int64(1)
+///   Filter: #b Lt Int64(2)
+/// ```
+///
+/// is rewritten to
+///
+/// ```text
+/// Filter: #b Lt Int64(2) And #a Gt Int64(1)
+/// ```
+pub struct MergeFilters {}
+
+impl MergeFilters {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for MergeFilters {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter { predicate, input } => {
+                // the input is optimized first, so it contains at most one filter
+                match self.optimize(input, execution_props)? {
+                    LogicalPlan::Filter {
+                        predicate: inner_predicate,
+                        input: inner_input,
+                    } => Ok(LogicalPlan::Filter {
+                        predicate: and(inner_predicate, predicate.clone()),
+                        input: inner_input,
+                    }),
+                    input => Ok(LogicalPlan::Filter {
+                        predicate: predicate.clone(),
+                        input: Arc::new(input),
+                    }),
+                }
+            }
+            _ => utils::optimize_children(self, plan, execution_props),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "merge_filters"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit, LogicalPlanBuilder};
+    use crate::test::*;
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = MergeFilters::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn merge_stacked_filters() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(col("a").gt(lit(1i64)))?
+            .filter(col("b").lt(lit(2i64)))?
+            .filter(col("c").eq(lit(3i64)))?
+            .build()?;
+
+        let expected = "Filter: #a Gt Int64(1) And #b Lt Int64(2) And #c Eq Int64(3)\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_separated_filters() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(col("a").gt(lit(1i64)))?
+            .limit(10)?
+            .filter(col("b").lt(lit(2i64)))?
+            .build()?;
+
+        let expected = "Filter: #b Lt Int64(2)\
+        \n  Limit: 10\
+        \n    Filter: #a Gt Int64(1)\
+        \n      TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
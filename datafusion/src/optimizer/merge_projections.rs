@@ -0,0 +1,295 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule that collapses chains of projections into a single projection.
+//! This avoids evaluating (and copying) the same columns several times per batch.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::{
+    DFSchema, Expr, ExprRewriter, ExpressionVisitor, LogicalPlan, Recursion,
+};
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::optimizer::utils;
+
+/// Optimization rule that merges a [LogicalPlan::Projection] into the
+/// [LogicalPlan::Projection] directly below it.
+///
+/// ```text
+/// Projection: #b AS c
+///   Projection: #a Plus Int64(1) AS b
+/// ```
+///
+/// is rewritten to
+///
+/// ```text
+/// Projection: #a Plus Int64(1) AS c
+/// ```
+///
+/// Projections in such a chain that only forward all columns of their input
+/// unchanged (identity projections) are removed.
+///
+/// Projections are not merged if an expression other than a column or a
+/// literal would have to be evaluated more than once in the merged projection.
+pub struct MergeProjections {}
+
+impl MergeProjections {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for MergeProjections {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Projection {
+                expr,
+                input,
+                schema,
+            } => {
+                let input = self.optimize(input, execution_props)?;
+
+                if let LogicalPlan::Projection {
+                    expr: inner_expr,
+                    input: inner_input,
+                    schema: inner_schema,
+                } = &input
+                {
+                    if is_identity(expr, schema, inner_schema) {
+                        return Ok(input);
+                    }
+
+                    if let Some(merged_expr) =
+                        merge_expressions(expr, inner_expr, inner_schema, inner_input)?
+                    {
+                        if matches!(inner_input.as_ref(), LogicalPlan::Projection { .. })
+                            && is_identity(&merged_expr, schema, inner_input.schema())
+                        {
+                            return Ok(inner_input.as_ref().clone());
+                        }
+
+                        return Ok(LogicalPlan::Projection {
+                            expr: merged_expr,
+                            input: inner_input.clone(),
+                            schema: schema.clone(),
+                        });
+                    }
+                }
+
+                Ok(LogicalPlan::Projection {
+                    expr: expr.clone(),
+                    input: Arc::new(input),
+                    schema: schema.clone(),
+                })
+            }
+            _ => utils::optimize_children(self, plan, execution_props),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "merge_projections"
+    }
+}
+
+/// Returns true if a projection of `expr` (with output `schema`) returns
+/// exactly the columns of `input_schema`, in the same order.
+fn is_identity(expr: &[Expr], schema: &DFSchema, input_schema: &DFSchema) -> bool {
+    schema == input_schema
+        && expr.len() == input_schema.fields().len()
+        && expr
+            .iter()
+            .zip(input_schema.fields())
+            .all(|(e, field)| matches!(e, Expr::Column(name) if name == field.name()))
+}
+
+/// Rewrites `outer_expr`, which reference the output of a projection of
+/// `inner_expr`, to reference the input of that projection directly.
+///
+/// Returns `None` if merging would duplicate the evaluation of a
+/// non-trivial expression.
+fn merge_expressions(
+    outer_expr: &[Expr],
+    inner_expr: &[Expr],
+    inner_schema: &DFSchema,
+    inner_input: &LogicalPlan,
+) -> Result<Option<Vec<Expr>>> {
+    let replacements = inner_expr
+        .iter()
+        .zip(inner_schema.fields())
+        .map(|(e, field)| {
+            let e = match e {
+                Expr::Alias(e, _) => e.as_ref().clone(),
+                _ => e.clone(),
+            };
+            (field.name().clone(), e)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut counter = ColumnReferenceCounter {
+        counts: HashMap::new(),
+    };
+    for e in outer_expr {
+        counter = e.accept(counter)?;
+    }
+    let duplicates_work = counter.counts.iter().any(|(name, count)| {
+        *count > 1
+            && !matches!(
+                replacements.get(name),
+                None | Some(Expr::Column(_)) | Some(Expr::Literal(_))
+            )
+    });
+    if duplicates_work {
+        return Ok(None);
+    }
+
+    let mut rewriter = ColumnReplacer {
+        replacements: &replacements,
+    };
+    outer_expr
+        .iter()
+        .map(|e| {
+            // keep the output name of the outer expression stable
+            let name = e.name(inner_schema)?;
+            let merged = e.clone().rewrite(&mut rewriter)?;
+            if merged.name(inner_input.schema())? == name {
+                Ok(merged)
+            } else {
+                Ok(Expr::Alias(Box::new(merged), name))
+            }
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Counts how often each column is referenced
+struct ColumnReferenceCounter {
+    counts: HashMap<String, usize>,
+}
+
+impl ExpressionVisitor for ColumnReferenceCounter {
+    fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+        if let Expr::Column(name) = expr {
+            *self.counts.entry(name.clone()).or_insert(0) += 1;
+        }
+        Ok(Recursion::Continue(self))
+    }
+}
+
+/// Replaces column references with the expressions that produce them
+struct ColumnReplacer<'a> {
+    replacements: &'a HashMap<String, Expr>,
+}
+
+impl<'a> ExprRewriter for ColumnReplacer<'a> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match expr {
+            Expr::Column(name) => Ok(self
+                .replacements
+                .get(&name)
+                .cloned()
+                .unwrap_or(Expr::Column(name))),
+            expr => Ok(expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit, LogicalPlanBuilder};
+    use crate::physical_plan::functions::BuiltinScalarFunction;
+    use crate::test::*;
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = MergeProjections::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn merge_two_projections() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .project(vec![col("a").alias("b"), col("c")])?
+            .project(vec![col("b"), col("c").alias("d")])?
+            .build()?;
+
+        let expected = "Projection: #a AS b, #c AS d\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_keeps_output_names() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .project(vec![(col("a") + lit(1)).alias("x")])?
+            .project(vec![col("x")])?
+            .build()?;
+
+        let expected = "Projection: #a Plus Int32(1) AS x\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn remove_identity_projection() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .project(vec![col("a"), col("b")])?
+            .project(vec![col("a"), col("b")])?
+            .project(vec![col("a"), col("b")])?
+            .build()?;
+
+        let expected = "Projection: #a, #b\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn do_not_duplicate_expressions() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let random = Expr::ScalarFunction {
+            fun: BuiltinScalarFunction::Random,
+            args: vec![],
+        };
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .project(vec![random.alias("r")])?
+            .project(vec![col("r").alias("r1"), col("r").alias("r2")])?
+            .build()?;
+
+        let expected = "Projection: #r AS r1, #r AS r2\
+        \n  Projection: random() AS r\
+        \n    TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
@@ -23,6 +23,10 @@ pub mod eliminate_limit;
 pub mod filter_push_down;
 pub mod hash_build_probe_order;
 pub mod limit_push_down;
+pub mod merge_filters;
+pub mod merge_projections;
 pub mod optimizer;
+pub mod or_to_in_list;
+pub mod partition_pruning;
 pub mod projection_push_down;
 pub mod utils;
@@ -14,12 +14,17 @@
 // KIND, either express or implied.  See the License for the
 // specific language governing permissions and limitations
 // under the License.
-
 //! Query optimizer traits
 
-use crate::error::Result;
+use std::sync::Arc;
+
+use log::{debug, warn};
+
+use crate::error::{DataFusionError, Result};
 use crate::execution::context::ExecutionProps;
-use crate::logical_plan::LogicalPlan;
+use crate::logical_plan::{
+    DFSchema, LogicalPlan, PlanRewriter, PlanType, StringifiedPlan,
+};
 
 /// `OptimizerRule` transforms one ['LogicalPlan'] into another which
 /// computes the same results, but in a potentially more efficient
@@ -34,4 +39,428 @@ pub trait OptimizerRule {
 
     /// A human readable name for this optimizer rule
     fn name(&self) -> &str;
+
+    /// How the [`Optimizer`] applies this rule to a plan.
+    ///
+    /// If `None` (the default), `optimize` is invoked on the root of the
+    /// plan only and is responsible for recursing into its inputs.
+    /// Otherwise `optimize` is invoked on every node of the plan in the
+    /// given order, and only needs to rewrite the node it is passed.
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        None
+    }
+}
+
+/// Order in which the [`Optimizer`] applies an [`OptimizerRule`] to the
+/// nodes of a plan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApplyOrder {
+    /// Nodes are rewritten before their inputs
+    TopDown,
+    /// Nodes are rewritten after their inputs
+    BottomUp,
+}
+
+/// Applies a list of [`OptimizerRule`]s to a plan, in order, until the
+/// plan does not change anymore or the maximum number of passes over all
+/// rules is reached.
+pub struct Optimizer {
+    /// Rules to apply, in order
+    rules: Vec<Arc<dyn OptimizerRule + Send + Sync>>,
+    /// Maximum number of passes over all rules
+    max_passes: usize,
+    /// Whether a rule that fails is skipped instead of failing the query
+    skip_failed_rules: bool,
+}
+
+impl Optimizer {
+    /// Create a new optimizer applying `rules` in a single pass, which
+    /// fails if any of the rules fail.
+    pub fn new(rules: Vec<Arc<dyn OptimizerRule + Send + Sync>>) -> Self {
+        Self {
+            rules,
+            max_passes: 1,
+            skip_failed_rules: false,
+        }
+    }
+
+    /// Apply the rules up to `max_passes` times, stopping early once a
+    /// pass does not change the plan.
+    pub fn with_max_passes(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes;
+        self
+    }
+
+    /// Skip rules that return an error, logging a warning, instead of
+    /// failing the whole optimization.
+    pub fn with_skip_failed_rules(mut self, skip_failed_rules: bool) -> Self {
+        self.skip_failed_rules = skip_failed_rules;
+        self
+    }
+
+    /// Optimizes `plan`, invoking `observer` with the rewritten plan
+    /// after every application of a rule.
+    ///
+    /// For [`LogicalPlan::Explain`], the explained plan is optimized and the
+    /// plan after every rule of the first pass is recorded.
+    pub fn optimize<F>(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+        mut observer: F,
+    ) -> Result<LogicalPlan>
+    where
+        F: FnMut(&LogicalPlan, &dyn OptimizerRule),
+    {
+        if let LogicalPlan::Explain {
+            verbose,
+            plan,
+            stringified_plans,
+            schema,
+        } = plan
+        {
+            let mut stringified_plans = stringified_plans.clone();
+            let mut applications = 0;
+            let plan = self.optimize_passes(plan, execution_props, |plan, rule| {
+                if applications < self.rules.len() {
+                    let optimizer_name = rule.name().into();
+                    stringified_plans.push(StringifiedPlan::new(
+                        PlanType::OptimizedLogicalPlan { optimizer_name },
+                        format!("{:#?}", plan),
+                    ));
+                }
+                applications += 1;
+                observer(plan, rule);
+            })?;
+            return Ok(LogicalPlan::Explain {
+                verbose: *verbose,
+                plan: Arc::new(plan),
+                stringified_plans,
+                schema: schema.clone(),
+            });
+        }
+
+        self.optimize_passes(plan, execution_props, observer)
+    }
+
+    fn optimize_passes<F>(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+        mut observer: F,
+    ) -> Result<LogicalPlan>
+    where
+        F: FnMut(&LogicalPlan, &dyn OptimizerRule),
+    {
+        let mut new_plan = plan.clone();
+        for pass in 0..self.max_passes {
+            let previous = format!("{}", new_plan.display_indent_schema());
+
+            for rule in &self.rules {
+                match self.apply_rule(rule.as_ref(), &new_plan, execution_props) {
+                    Ok(plan) => {
+                        new_plan = plan;
+                        observer(&new_plan, rule.as_ref());
+                    }
+                    Err(e) if self.skip_failed_rules => {
+                        warn!(
+                            "Skipping optimizer rule {} that failed: {}",
+                            rule.name(),
+                            e
+                        );
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            if format!("{}", new_plan.display_indent_schema()) == previous {
+                debug!("Optimizer reached a fixpoint after {} passes", pass + 1);
+                break;
+            }
+        }
+        Ok(new_plan)
+    }
+
+    fn apply_rule(
+        &self,
+        rule: &dyn OptimizerRule,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        let new_plan = match rule.apply_order() {
+            None => rule.optimize(plan, execution_props)?,
+            Some(order) => plan.clone().rewrite(&mut RuleRewriter {
+                rule,
+                order,
+                execution_props,
+            })?,
+        };
+
+        // rules must not change the output of a plan, which is cheap enough
+        // to verify in debug builds
+        if cfg!(debug_assertions) {
+            check_schema_unchanged(rule, plan.schema(), new_plan.schema())?;
+        }
+        Ok(new_plan)
+    }
+}
+
+/// Returns an error naming `rule` if the fields of `new_schema` differ
+/// from the fields of `schema` in name, type or nullability.
+fn check_schema_unchanged(
+    rule: &dyn OptimizerRule,
+    schema: &DFSchema,
+    new_schema: &DFSchema,
+) -> Result<()> {
+    let describe = |schema: &DFSchema| {
+        schema
+            .fields()
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}: {:?}{}",
+                    field.qualified_name(),
+                    field.data_type(),
+                    if field.is_nullable() { "" } else { " NOT NULL" }
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let (fields, new_fields) = (describe(schema), describe(new_schema));
+    if fields != new_fields {
+        return Err(DataFusionError::Internal(format!(
+            "Optimizer rule '{}' changed the schema of the plan from [{}] to [{}]",
+            rule.name(),
+            fields.join(", "),
+            new_fields.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Applies a rule to every node of a plan
+struct RuleRewriter<'a> {
+    rule: &'a dyn OptimizerRule,
+    order: ApplyOrder,
+    execution_props: &'a ExecutionProps,
+}
+
+impl<'a> PlanRewriter for RuleRewriter<'a> {
+    fn pre_mutate(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        match self.order {
+            ApplyOrder::TopDown => self.rule.optimize(&plan, self.execution_props),
+            ApplyOrder::BottomUp => Ok(plan),
+        }
+    }
+
+    fn mutate(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        match self.order {
+            ApplyOrder::TopDown => Ok(plan),
+            ApplyOrder::BottomUp => self.rule.optimize(&plan, self.execution_props),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit, LogicalPlanBuilder};
+    use crate::test::*;
+    use std::sync::Mutex;
+
+    /// Replaces `Limit: n` by `Limit: n - 1` until it reaches zero
+    struct DecrementLimit {}
+
+    impl OptimizerRule for DecrementLimit {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            match plan {
+                LogicalPlan::Limit { n, input } if *n > 0 => Ok(LogicalPlan::Limit {
+                    n: n - 1,
+                    input: input.clone(),
+                }),
+                _ => Ok(plan.clone()),
+            }
+        }
+
+        fn name(&self) -> &str {
+            "decrement_limit"
+        }
+
+        fn apply_order(&self) -> Option<ApplyOrder> {
+            Some(ApplyOrder::BottomUp)
+        }
+    }
+
+    /// Records the order in which the plan nodes are visited
+    struct RecordOrder {
+        order: ApplyOrder,
+        visited: Mutex<Vec<String>>,
+    }
+
+    impl OptimizerRule for RecordOrder {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            let node = format!("{:?}", plan);
+            let node = node.lines().next().unwrap().to_string();
+            self.visited.lock().unwrap().push(node);
+            Ok(plan.clone())
+        }
+
+        fn name(&self) -> &str {
+            "record_order"
+        }
+
+        fn apply_order(&self) -> Option<ApplyOrder> {
+            Some(self.order)
+        }
+    }
+
+    struct FailingRule {}
+
+    impl OptimizerRule for FailingRule {
+        fn optimize(&self, _: &LogicalPlan, _: &ExecutionProps) -> Result<LogicalPlan> {
+            Err(DataFusionError::Plan("rule failed".to_string()))
+        }
+
+        fn name(&self) -> &str {
+            "failing_rule"
+        }
+    }
+
+    /// Drops all columns but the first one
+    struct DropColumns {}
+
+    impl OptimizerRule for DropColumns {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            LogicalPlanBuilder::from(plan)
+                .project(vec![col(plan.schema().field(0).name())])?
+                .build()
+        }
+
+        fn name(&self) -> &str {
+            "drop_columns"
+        }
+    }
+
+    fn limit_plan(n: usize) -> Result<LogicalPlan> {
+        LogicalPlanBuilder::from(&test_table_scan()?)
+            .filter(col("a").eq(lit(1u32)))?
+            .limit(n)?
+            .build()
+    }
+
+    #[test]
+    fn optimize_until_fixpoint() -> Result<()> {
+        let optimizer =
+            Optimizer::new(vec![Arc::new(DecrementLimit {})]).with_max_passes(10);
+        let mut applications = 0;
+        let plan =
+            optimizer.optimize(&limit_plan(3)?, &ExecutionProps::new(), |_, _| {
+                applications += 1
+            })?;
+
+        let expected = "Limit: 0\
+        \n  Filter: #a Eq UInt32(1)\
+        \n    TableScan: test projection=None";
+        assert_eq!(format!("{:?}", plan), expected);
+        // three passes change the plan, the fourth one finds the fixpoint
+        assert_eq!(applications, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn optimize_max_passes() -> Result<()> {
+        let optimizer =
+            Optimizer::new(vec![Arc::new(DecrementLimit {})]).with_max_passes(2);
+        let plan =
+            optimizer.optimize(&limit_plan(3)?, &ExecutionProps::new(), |_, _| {})?;
+
+        let expected = "Limit: 1\
+        \n  Filter: #a Eq UInt32(1)\
+        \n    TableScan: test projection=None";
+        assert_eq!(format!("{:?}", plan), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn apply_order() -> Result<()> {
+        for (order, expected) in vec![
+            (
+                ApplyOrder::TopDown,
+                vec![
+                    "Limit: 3",
+                    "Filter: #a Eq UInt32(1)",
+                    "TableScan: test projection=None",
+                ],
+            ),
+            (
+                ApplyOrder::BottomUp,
+                vec![
+                    "TableScan: test projection=None",
+                    "Filter: #a Eq UInt32(1)",
+                    "Limit: 3",
+                ],
+            ),
+        ] {
+            let rule = Arc::new(RecordOrder {
+                order,
+                visited: Mutex::new(vec![]),
+            });
+            let rules: Vec<Arc<dyn OptimizerRule + Send + Sync>> = vec![rule.clone()];
+            Optimizer::new(rules).optimize(
+                &limit_plan(3)?,
+                &ExecutionProps::new(),
+                |_, _| {},
+            )?;
+            assert_eq!(*rule.visited.lock().unwrap(), expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn skip_failed_rules() -> Result<()> {
+        let rules: Vec<Arc<dyn OptimizerRule + Send + Sync>> =
+            vec![Arc::new(FailingRule {}), Arc::new(DecrementLimit {})];
+        let plan = limit_plan(3)?;
+
+        let err = Optimizer::new(rules.clone())
+            .optimize(&plan, &ExecutionProps::new(), |_, _| {})
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: rule failed");
+
+        let optimized = Optimizer::new(rules)
+            .with_skip_failed_rules(true)
+            .optimize(&plan, &ExecutionProps::new(), |_, _| {})?;
+        let expected = "Limit: 2\
+        \n  Filter: #a Eq UInt32(1)\
+        \n    TableScan: test projection=None";
+        assert_eq!(format!("{:?}", optimized), expected);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn rule_changing_schema() -> Result<()> {
+        let err = Optimizer::new(vec![Arc::new(DropColumns {})])
+            .optimize(&limit_plan(3)?, &ExecutionProps::new(), |_, _| {})
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "Optimizer rule 'drop_columns' changed the schema of the plan \
+            from [a: UInt32 NOT NULL, b: UInt32 NOT NULL, c: UInt32 NOT NULL] \
+            to [a: UInt32 NOT NULL]"
+        ));
+        Ok(())
+    }
 }
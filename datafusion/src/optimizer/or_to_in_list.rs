@@ -0,0 +1,369 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule rewriting chains of equality comparisons on the same column,
+//! combined with `OR`, into a single `IN` list.
+
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::{
+    col, or, DFSchemaRef, Expr, ExprRewriter, LogicalPlan, Operator,
+};
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::optimizer::utils;
+use crate::physical_plan::expressions::is_numeric;
+use crate::scalar::ScalarValue;
+
+/// Minimum number of values compared against a column before the
+/// comparisons are rewritten into an `IN` list
+const MIN_IN_LIST_LEN: usize = 3;
+
+/// Optimizer rule that rewrites
+///
+/// `#c Eq Int64(1) Or #c Eq Int64(2) Or #c Eq Int64(3)`
+///
+/// to
+///
+/// `#c IN ([Int64(1), Int64(2), Int64(3)])`
+///
+/// so that each row is checked against the set of values with a single
+/// lookup instead of one comparison per value. Disjuncts on other
+/// columns or of other shapes are kept as they are.
+pub struct OrToInList {}
+
+impl OrToInList {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for OrToInList {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        let mut rewriter = OrChainRewriter {
+            schemas: plan.all_schemas(),
+        };
+
+        let new_inputs = plan
+            .inputs()
+            .iter()
+            .map(|plan| self.optimize(plan, execution_props))
+            .collect::<Result<Vec<_>>>()?;
+
+        let expr = plan
+            .expressions()
+            .into_iter()
+            .map(|e| e.rewrite(&mut rewriter))
+            .collect::<Result<Vec<_>>>()?;
+
+        utils::from_plan(plan, &expr, &new_inputs)
+    }
+
+    fn name(&self) -> &str {
+        "or_to_in_list"
+    }
+}
+
+/// One disjunct of an `OR` chain
+enum Disjunct {
+    /// Index into the list of column comparisons
+    Column(usize),
+    /// Any other expression
+    Other(Expr),
+}
+
+struct OrChainRewriter<'a> {
+    /// input schemas
+    schemas: Vec<&'a DFSchemaRef>,
+}
+
+impl<'a> OrChainRewriter<'a> {
+    /// Returns the type of the column `name`. Only columns of types that are
+    /// supported by the `IN` list physical expression are rewritten
+    fn supported_column_type(&self, name: &str) -> Option<DataType> {
+        self.schemas
+            .iter()
+            .filter_map(|schema| col(name).get_type(schema).ok())
+            .find(|data_type| {
+                matches!(
+                    data_type,
+                    DataType::Boolean
+                        | DataType::Int8
+                        | DataType::Int16
+                        | DataType::Int32
+                        | DataType::Int64
+                        | DataType::UInt8
+                        | DataType::UInt16
+                        | DataType::UInt32
+                        | DataType::UInt64
+                        | DataType::Float32
+                        | DataType::Float64
+                        | DataType::Utf8
+                        | DataType::LargeUtf8
+                )
+            })
+    }
+
+    /// If `expr` compares a column against one or more non-null literals,
+    /// returns the column name and the literals, converted to the type of
+    /// the column. Returns `None` if any literal can not be converted
+    /// without changing its value, e.g. `1.5` or `3000000000` for an
+    /// `Int32` column, as the `IN` list would then match other rows.
+    fn as_column_comparison(&self, expr: &Expr) -> Option<(String, Vec<Expr>)> {
+        let (name, values) = match expr {
+            Expr::BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            } => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(name), value @ Expr::Literal(_))
+                | (value @ Expr::Literal(_), Expr::Column(name)) => {
+                    (name, vec![value.clone()])
+                }
+                _ => return None,
+            },
+            Expr::InList {
+                expr,
+                list,
+                negated: false,
+            } => match expr.as_ref() {
+                Expr::Column(name) if list.iter().all(is_literal) => (name, list.clone()),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let data_type = self.supported_column_type(name)?;
+        let values = values
+            .iter()
+            .map(|value| match value {
+                Expr::Literal(value) => {
+                    coerce_literal(value, &data_type).map(Expr::Literal)
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some((name.clone(), values))
+    }
+}
+
+/// Converts `value` to `data_type` if that is possible without loss:
+/// either the types already match, or both are numeric and converting
+/// the value back yields the original value. Returns `None` otherwise,
+/// including for null values.
+fn coerce_literal(value: &ScalarValue, data_type: &DataType) -> Option<ScalarValue> {
+    if value.is_null() {
+        return None;
+    }
+    let value_type = value.get_datatype();
+    if &value_type == data_type {
+        return Some(value.clone());
+    }
+    if !is_numeric(&value_type) || !is_numeric(data_type) {
+        return None;
+    }
+
+    let array = cast(&value.to_array(), data_type).ok()?;
+    let coerced = ScalarValue::try_from_array(&array, 0).ok()?;
+    if coerced.is_null() {
+        return None;
+    }
+    let round_trip =
+        ScalarValue::try_from_array(&cast(&array, &value_type).ok()?, 0).ok()?;
+    if &round_trip == value {
+        Some(coerced)
+    } else {
+        None
+    }
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(_))
+}
+
+/// Collects all disjuncts of a tree of `OR` expressions
+fn split_disjunction(expr: Expr, disjuncts: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        } => {
+            split_disjunction(*left, disjuncts);
+            split_disjunction(*right, disjuncts);
+        }
+        other => disjuncts.push(other),
+    }
+}
+
+impl<'a> ExprRewriter for OrChainRewriter<'a> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        if !matches!(
+            expr,
+            Expr::BinaryExpr {
+                op: Operator::Or,
+                ..
+            }
+        ) {
+            return Ok(expr);
+        }
+
+        let mut disjuncts = vec![];
+        split_disjunction(expr, &mut disjuncts);
+
+        // comparisons grouped by column, in order of first appearance
+        let mut comparisons: Vec<(String, Vec<Expr>)> = vec![];
+        let disjuncts = disjuncts
+            .into_iter()
+            .filter_map(|disjunct| match self.as_column_comparison(&disjunct) {
+                Some((name, values)) => {
+                    match comparisons.iter().position(|(n, _)| *n == name) {
+                        Some(index) => {
+                            comparisons[index].1.extend(values);
+                            None
+                        }
+                        None => {
+                            comparisons.push((name, values));
+                            Some(Disjunct::Column(comparisons.len() - 1))
+                        }
+                    }
+                }
+                None => Some(Disjunct::Other(disjunct)),
+            })
+            .collect::<Vec<_>>();
+
+        let mut comparisons = comparisons.into_iter().map(Some).collect::<Vec<_>>();
+        let rewritten = disjuncts
+            .into_iter()
+            .flat_map(|disjunct| match disjunct {
+                Disjunct::Other(expr) => vec![expr],
+                Disjunct::Column(index) => {
+                    let (name, values) = comparisons[index].take().unwrap();
+                    if values.len() >= MIN_IN_LIST_LEN {
+                        vec![col(&name).in_list(values, false)]
+                    } else {
+                        values.into_iter().map(|v| col(&name).eq(v)).collect()
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // `split_disjunction` always returns at least one expression
+        let mut rewritten = rewritten.into_iter();
+        let first = rewritten.next().unwrap();
+        Ok(rewritten.fold(first, or))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{lit, LogicalPlanBuilder};
+    use crate::test::*;
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = OrToInList::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn or_chain_to_in_list() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(
+                col("a")
+                    .eq(lit(1u32))
+                    .or(col("a").eq(lit(2u32)))
+                    .or(lit(3u32).eq(col("a")))
+                    .or(col("a").eq(lit(4u32))),
+            )?
+            .build()?;
+
+        let expected = "Filter: #a IN ([UInt32(1), UInt32(2), UInt32(3), UInt32(4)])\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn or_chain_keeps_other_disjuncts() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(
+                col("a")
+                    .eq(lit(1u32))
+                    .or(col("b").gt(lit(2u32)))
+                    .or(col("a").eq(lit(3u32)))
+                    .or(col("c").eq(lit(4u32)))
+                    .or(col("a").eq(lit(5u32))),
+            )?
+            .build()?;
+
+        let expected = "Filter: #a IN ([UInt32(1), UInt32(3), UInt32(5)]) \
+        Or #b Gt UInt32(2) Or #c Eq UInt32(4)\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn or_chain_with_mixed_literal_types() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(
+                col("a")
+                    .eq(lit(1.5f64))
+                    .or(col("a").eq(lit(2i64)))
+                    .or(col("a").eq(lit(-1i64)))
+                    .or(col("a").eq(lit(3.0f64)))
+                    .or(col("a").eq(lit(4u32))),
+            )?
+            .build()?;
+
+        // 1.5 and -1 are not UInt32 values and stay separate comparisons
+        let expected = "Filter: #a Eq Float64(1.5) \
+        Or #a IN ([UInt32(2), UInt32(3), UInt32(4)]) Or #a Eq Int64(-1)\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn short_or_chain_unchanged() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(&table_scan)
+            .filter(col("a").eq(lit(1u32)).or(col("a").eq(lit(2u32))))?
+            .build()?;
+
+        let expected = "Filter: #a Eq UInt32(1) Or #a Eq UInt32(2)\
+        \n  TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Optimizer rule removing the partitions of partitioned tables from scans
+//! whose rows can not pass the filters on their partition columns.
+use std::collections::HashSet;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::{and, Expr, LogicalPlan, Operator};
+use crate::optimizer::optimizer::OptimizerRule;
+
+use super::utils;
+
+/// Optimization rule evaluating the predicates of filters directly above a
+/// table scan that only refer to partition columns of the table (see
+/// [`TableProvider::partition_columns`]) against the partitions of the
+/// table, replacing the table of the scan with one without the partitions
+/// for which they are not true, see [`TableProvider::prune_partitions`].
+///
+/// The filters are kept, as they may also refer to other columns. This rule
+/// is expected to run after the filters were pushed down to the scans.
+///
+/// [`TableProvider::partition_columns`]: crate::datasource::TableProvider::partition_columns
+/// [`TableProvider::prune_partitions`]: crate::datasource::TableProvider::prune_partitions
+pub struct PartitionPruning;
+
+impl PartitionPruning {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for PartitionPruning {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter { predicate, input } => match input.as_ref() {
+                LogicalPlan::TableScan {
+                    table_name,
+                    source,
+                    projection,
+                    projected_schema,
+                    filters,
+                    limit,
+                } => {
+                    let partition_columns: HashSet<String> =
+                        source.partition_columns().into_iter().collect();
+                    if partition_columns.is_empty() {
+                        return Ok(plan.clone());
+                    }
+
+                    let mut predicates = vec![];
+                    split_conjunction(predicate, &mut predicates);
+                    let mut partition_filter = None;
+                    for predicate in predicates {
+                        let mut columns = HashSet::new();
+                        utils::expr_to_column_names(predicate, &mut columns)?;
+                        if columns.is_empty() || !columns.is_subset(&partition_columns) {
+                            continue;
+                        }
+                        partition_filter = Some(match partition_filter {
+                            Some(filter) => and(filter, predicate.clone()),
+                            None => predicate.clone(),
+                        });
+                    }
+                    let source = match partition_filter {
+                        Some(filter) => source.prune_partitions(&filter)?,
+                        None => None,
+                    };
+                    match source {
+                        Some(source) => Ok(LogicalPlan::Filter {
+                            predicate: predicate.clone(),
+                            input: std::sync::Arc::new(LogicalPlan::TableScan {
+                                table_name: table_name.clone(),
+                                source,
+                                projection: projection.clone(),
+                                projected_schema: projected_schema.clone(),
+                                filters: filters.clone(),
+                                limit: *limit,
+                            }),
+                        }),
+                        None => Ok(plan.clone()),
+                    }
+                }
+                _ => utils::optimize_children(self, plan, execution_props),
+            },
+            _ => utils::optimize_children(self, plan, execution_props),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "partition_pruning"
+    }
+}
+
+/// Adds the predicates of the conjunction `expr` to `predicates`
+fn split_conjunction<'a>(expr: &'a Expr, predicates: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(left, predicates);
+            split_conjunction(right, predicates);
+        }
+        other => predicates.push(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::datasource::csv::{CsvFile, CsvReadOptions};
+    use crate::datasource::partitioned::PartitionedTable;
+    use crate::logical_plan::{col, lit, LogicalPlanBuilder};
+    use crate::test::create_hive_partitioned_csv;
+
+    /// The number of partitions of the partitioned table scanned by `plan`
+    fn scanned_partitions(plan: &LogicalPlan) -> usize {
+        match plan {
+            LogicalPlan::TableScan { source, .. } => source
+                .as_any()
+                .downcast_ref::<PartitionedTable>()
+                .unwrap()
+                .num_partitions(),
+            other => scanned_partitions(other.inputs()[0]),
+        }
+    }
+
+    #[test]
+    fn prune_partitions_by_partition_column_predicates() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_hive_partitioned_csv(dir.path())?;
+        let table = PartitionedTable::try_new(dir.path().to_str().unwrap(), |path| {
+            Ok(Arc::new(CsvFile::try_new(path, CsvReadOptions::new())?))
+        })?;
+        let scan = LogicalPlanBuilder::scan("t", Arc::new(table), None)?.build()?;
+        let rule = PartitionPruning::new();
+
+        // the predicate on a column of the files is ignored
+        let plan = LogicalPlanBuilder::from(&scan)
+            .filter(
+                col("year")
+                    .eq(lit("2020"))
+                    .and(col("a").gt(lit(1)))
+                    .and(col("month").eq(lit("01"))),
+            )?
+            .build()?;
+        let optimized = rule.optimize(&plan, &ExecutionProps::new())?;
+        assert_eq!(scanned_partitions(&optimized), 1);
+        assert_eq!(format!("{:?}", plan), format!("{:?}", optimized));
+
+        // predicates mixing partition and file columns can not be evaluated
+        let plan = LogicalPlanBuilder::from(&scan)
+            .filter(col("year").eq(lit("2021")).or(col("a").gt(lit(1))))?
+            .build()?;
+        let optimized = rule.optimize(&plan, &ExecutionProps::new())?;
+        assert_eq!(scanned_partitions(&optimized), 3);
+
+        Ok(())
+    }
+}
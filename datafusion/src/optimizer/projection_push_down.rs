@@ -337,14 +337,44 @@ fn optimize_plan(
                 execution_props,
             )
         }
+        LogicalPlan::Analyze {
+            verbose,
+            input,
+            schema,
+        } => {
+            // the output of the input is discarded, but all of its columns
+            // are still produced when running it
+            let required_columns = input
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<HashSet<String>>();
+            Ok(LogicalPlan::Analyze {
+                verbose: *verbose,
+                input: Arc::new(optimize_plan(
+                    optimizer,
+                    input,
+                    &required_columns,
+                    false,
+                    execution_props,
+                )?),
+                schema: schema.clone(),
+            })
+        }
         // all other nodes: Add any additional columns used by
         // expressions in this node to the list of required columns
         LogicalPlan::Limit { .. }
+        | LogicalPlan::Sample { .. }
+        | LogicalPlan::Deduplicate { .. }
         | LogicalPlan::Filter { .. }
         | LogicalPlan::Repartition { .. }
+        | LogicalPlan::Unnest { .. }
         | LogicalPlan::EmptyRelation { .. }
+        | LogicalPlan::Values { .. }
         | LogicalPlan::Sort { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::SetVariable { .. }
         | LogicalPlan::Union { .. }
         | LogicalPlan::CrossJoin { .. }
         | LogicalPlan::Extension { .. } => {
@@ -24,8 +24,8 @@ use arrow::datatypes::Schema;
 use super::optimizer::OptimizerRule;
 use crate::execution::context::ExecutionProps;
 use crate::logical_plan::{
-    Expr, LogicalPlan, Operator, Partitioning, PlanType, Recursion, StringifiedPlan,
-    ToDFSchema,
+    Expr, LogicalPlan, LogicalPlanBuilder, Operator, Partitioning, PlanType, Recursion,
+    StringifiedPlan, ToDFSchema,
 };
 use crate::prelude::lit;
 use crate::scalar::ScalarValue;
@@ -239,6 +239,27 @@ pub fn from_plan(
             n: *n,
             input: Arc::new(inputs[0].clone()),
         }),
+        LogicalPlan::Deduplicate { columns, keep, .. } => Ok(LogicalPlan::Deduplicate {
+            input: Arc::new(inputs[0].clone()),
+            columns: columns.clone(),
+            keep: *keep,
+        }),
+        LogicalPlan::Sample { method, seed, .. } => Ok(LogicalPlan::Sample {
+            input: Arc::new(inputs[0].clone()),
+            method: *method,
+            seed: *seed,
+        }),
+        LogicalPlan::Analyze {
+            verbose, schema, ..
+        } => Ok(LogicalPlan::Analyze {
+            verbose: *verbose,
+            input: Arc::new(inputs[0].clone()),
+            schema: schema.clone(),
+        }),
+        // the input may have lost columns to projection push down
+        LogicalPlan::Unnest { column, .. } => {
+            LogicalPlanBuilder::from(&inputs[0]).unnest(column)?.build()
+        }
         LogicalPlan::Extension { node } => Ok(LogicalPlan::Extension {
             node: node.from_template(expr, inputs),
         }),
@@ -247,9 +268,17 @@ pub fn from_plan(
             schema: schema.clone(),
             alias: alias.clone(),
         }),
+        LogicalPlan::Values { schema, .. } => Ok(LogicalPlan::Values {
+            schema: schema.clone(),
+            values: expr
+                .chunks(schema.fields().len())
+                .map(|row| row.to_vec())
+                .collect(),
+        }),
         LogicalPlan::EmptyRelation { .. }
         | LogicalPlan::TableScan { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::SetVariable { .. }
         | LogicalPlan::Explain { .. } => Ok(plan.clone()),
     }
 }
@@ -0,0 +1,544 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Encoding and decoding of the PostgreSQL frontend/backend protocol (v3) messages
+//! supported by the server.
+
+use std::convert::TryInto;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::{DataFusionError, Result};
+
+/// Protocol version 3.0, sent in the startup message
+pub const PROTOCOL_VERSION: i32 = 196608;
+/// Request code of an `SSLRequest`
+pub const SSL_REQUEST_CODE: i32 = 80877103;
+/// Request code of a `GSSENCRequest`
+pub const GSSENC_REQUEST_CODE: i32 = 80877104;
+/// Request code of a `CancelRequest`
+pub const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// Upper bound on the size of a single frontend message
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// The first message a client sends on a new connection
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartupMessage {
+    /// Starts a session with the given parameters (`user`, `database`, ...)
+    Startup { parameters: Vec<(String, String)> },
+    /// Asks whether the server supports SSL
+    SslRequest,
+    /// Asks whether the server supports GSSAPI encryption
+    GssEncRequest,
+    /// Cancels a query running on another connection
+    CancelRequest,
+}
+
+/// A message sent by the client once the session is established
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontendMessage {
+    /// Simple query protocol: one or more statements in a string
+    Query(String),
+    /// Creates a prepared statement
+    Parse {
+        name: String,
+        query: String,
+        param_types: Vec<i32>,
+    },
+    /// Creates a portal from a prepared statement and parameter values
+    Bind {
+        portal: String,
+        statement: String,
+        param_formats: Vec<i16>,
+        params: Vec<Option<Vec<u8>>>,
+        result_formats: Vec<i16>,
+    },
+    /// Describes a prepared statement (`'S'`) or a portal (`'P'`)
+    Describe { kind: u8, name: String },
+    /// Executes a portal, returning at most `max_rows` rows (0 means all)
+    Execute { portal: String, max_rows: i32 },
+    /// Closes a prepared statement (`'S'`) or a portal (`'P'`)
+    Close { kind: u8, name: String },
+    /// Ends an extended query cycle
+    Sync,
+    /// Asks the server to deliver any pending output
+    Flush,
+    /// Ends the session
+    Terminate,
+}
+
+/// A message sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendMessage {
+    AuthenticationOk,
+    ParameterStatus(String, String),
+    ReadyForQuery,
+    RowDescription(Vec<FieldDescription>),
+    /// A row of text-encoded values, `None` being NULL
+    DataRow(Vec<Option<String>>),
+    CommandComplete(String),
+    EmptyQueryResponse,
+    ErrorResponse {
+        code: String,
+        message: String,
+    },
+    ParseComplete,
+    BindComplete,
+    CloseComplete,
+    NoData,
+    PortalSuspended,
+    ParameterDescription(Vec<i32>),
+}
+
+/// Description of a result column, sent in a `RowDescription`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescription {
+    pub name: String,
+    pub type_oid: i32,
+    pub type_len: i16,
+    pub format: i16,
+}
+
+impl BackendMessage {
+    /// Appends the wire encoding of this message to `buf`
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        let mut body = Vec::new();
+        let tag = match self {
+            BackendMessage::AuthenticationOk => {
+                put_i32(&mut body, 0);
+                b'R'
+            }
+            BackendMessage::ParameterStatus(name, value) => {
+                put_cstr(&mut body, name);
+                put_cstr(&mut body, value);
+                b'S'
+            }
+            // the server never keeps a transaction open
+            BackendMessage::ReadyForQuery => {
+                body.push(b'I');
+                b'Z'
+            }
+            BackendMessage::RowDescription(fields) => {
+                put_i16(&mut body, fields.len() as i16);
+                for field in fields {
+                    put_cstr(&mut body, &field.name);
+                    // table oid and column attribute number
+                    put_i32(&mut body, 0);
+                    put_i16(&mut body, 0);
+                    put_i32(&mut body, field.type_oid);
+                    put_i16(&mut body, field.type_len);
+                    // type modifier
+                    put_i32(&mut body, -1);
+                    put_i16(&mut body, field.format);
+                }
+                b'T'
+            }
+            BackendMessage::DataRow(values) => {
+                put_i16(&mut body, values.len() as i16);
+                for value in values {
+                    match value {
+                        Some(value) => {
+                            put_i32(&mut body, value.len() as i32);
+                            body.extend_from_slice(value.as_bytes());
+                        }
+                        None => put_i32(&mut body, -1),
+                    }
+                }
+                b'D'
+            }
+            BackendMessage::CommandComplete(tag) => {
+                put_cstr(&mut body, tag);
+                b'C'
+            }
+            BackendMessage::EmptyQueryResponse => b'I',
+            BackendMessage::ErrorResponse { code, message } => {
+                for (field, value) in &[
+                    (b'S', "ERROR"),
+                    (b'V', "ERROR"),
+                    (b'C', code.as_str()),
+                    (b'M', message.as_str()),
+                ] {
+                    body.push(*field);
+                    put_cstr(&mut body, value);
+                }
+                body.push(0);
+                b'E'
+            }
+            BackendMessage::ParseComplete => b'1',
+            BackendMessage::BindComplete => b'2',
+            BackendMessage::CloseComplete => b'3',
+            BackendMessage::NoData => b'n',
+            BackendMessage::PortalSuspended => b's',
+            BackendMessage::ParameterDescription(types) => {
+                put_i16(&mut body, types.len() as i16);
+                for oid in types {
+                    put_i32(&mut body, *oid);
+                }
+                b't'
+            }
+        };
+        buf.push(tag);
+        put_i32(buf, body.len() as i32 + 4);
+        buf.extend_from_slice(&body);
+    }
+}
+
+/// Reads the startup message a client sends when it connects
+pub async fn read_startup<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<StartupMessage> {
+    let len = reader.read_i32().await?;
+    let body = read_body(reader, len).await?;
+    decode_startup(&body)
+}
+
+/// Reads the next frontend message, returning `None` once the client closed
+/// the connection
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<Option<FrontendMessage>> {
+    let tag = match reader.read_u8().await {
+        Ok(tag) => tag,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let len = reader.read_i32().await?;
+    let body = read_body(reader, len).await?;
+    decode_message(tag, &body).map(Some)
+}
+
+async fn read_body<R: AsyncRead + Unpin>(reader: &mut R, len: i32) -> Result<Vec<u8>> {
+    // the length includes itself
+    let len = len as i64 - 4;
+    if len < 0 || len as usize > MAX_MESSAGE_LEN {
+        return Err(protocol_err(format!("invalid message length {}", len + 4)));
+    }
+    let mut body = vec![0; len as usize];
+    reader.read_exact(&mut body).await?;
+    Ok(body)
+}
+
+fn decode_startup(body: &[u8]) -> Result<StartupMessage> {
+    let mut reader = BodyReader::new(body);
+    match reader.i32()? {
+        PROTOCOL_VERSION => {
+            let mut parameters = vec![];
+            loop {
+                let name = reader.cstr()?;
+                if name.is_empty() {
+                    break;
+                }
+                let value = reader.cstr()?;
+                parameters.push((name, value));
+            }
+            Ok(StartupMessage::Startup { parameters })
+        }
+        SSL_REQUEST_CODE => Ok(StartupMessage::SslRequest),
+        GSSENC_REQUEST_CODE => Ok(StartupMessage::GssEncRequest),
+        CANCEL_REQUEST_CODE => Ok(StartupMessage::CancelRequest),
+        code => Err(protocol_err(format!(
+            "unsupported protocol version {}.{}",
+            code >> 16,
+            code & 0xffff
+        ))),
+    }
+}
+
+fn decode_message(tag: u8, body: &[u8]) -> Result<FrontendMessage> {
+    let mut reader = BodyReader::new(body);
+    let message = match tag {
+        b'Q' => FrontendMessage::Query(reader.cstr()?),
+        b'P' => {
+            let name = reader.cstr()?;
+            let query = reader.cstr()?;
+            let count = reader.i16()?;
+            let param_types = (0..count).map(|_| reader.i32()).collect::<Result<_>>()?;
+            FrontendMessage::Parse {
+                name,
+                query,
+                param_types,
+            }
+        }
+        b'B' => {
+            let portal = reader.cstr()?;
+            let statement = reader.cstr()?;
+            let count = reader.i16()?;
+            let param_formats =
+                (0..count).map(|_| reader.i16()).collect::<Result<_>>()?;
+            let count = reader.i16()?;
+            let params = (0..count)
+                .map(|_| match reader.i32()? {
+                    -1 => Ok(None),
+                    len => reader.bytes(len).map(Some),
+                })
+                .collect::<Result<_>>()?;
+            let count = reader.i16()?;
+            let result_formats =
+                (0..count).map(|_| reader.i16()).collect::<Result<_>>()?;
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                param_formats,
+                params,
+                result_formats,
+            }
+        }
+        b'D' => FrontendMessage::Describe {
+            kind: reader.u8()?,
+            name: reader.cstr()?,
+        },
+        b'E' => FrontendMessage::Execute {
+            portal: reader.cstr()?,
+            max_rows: reader.i32()?,
+        },
+        b'C' => FrontendMessage::Close {
+            kind: reader.u8()?,
+            name: reader.cstr()?,
+        },
+        b'S' => FrontendMessage::Sync,
+        b'H' => FrontendMessage::Flush,
+        b'X' => FrontendMessage::Terminate,
+        tag => {
+            return Err(protocol_err(format!(
+                "unsupported frontend message '{}'",
+                tag as char
+            )))
+        }
+    };
+    Ok(message)
+}
+
+/// Creates the error returned for malformed or unsupported messages
+pub fn protocol_err(message: impl Into<String>) -> DataFusionError {
+    DataFusionError::Execution(format!("pg wire protocol error: {}", message.into()))
+}
+
+/// Cursor over the body of a message
+struct BodyReader<'a> {
+    body: &'a [u8],
+}
+
+impl<'a> BodyReader<'a> {
+    fn new(body: &'a [u8]) -> Self {
+        Self { body }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.body.len() < len {
+            return Err(protocol_err("unexpected end of message"));
+        }
+        let (head, tail) = self.body.split_at(len);
+        self.body = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self, len: i32) -> Result<Vec<u8>> {
+        if len < 0 {
+            return Err(protocol_err(format!("invalid value length {}", len)));
+        }
+        Ok(self.take(len as usize)?.to_vec())
+    }
+
+    fn cstr(&mut self) -> Result<String> {
+        let end = self
+            .body
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| protocol_err("unterminated string"))?;
+        let s = std::str::from_utf8(&self.body[..end])
+            .map_err(|e| protocol_err(format!("invalid UTF-8 string: {}", e)))?
+            .to_owned();
+        self.body = &self.body[end + 1..];
+        Ok(s)
+    }
+}
+
+fn put_i16(buf: &mut Vec<u8>, value: i16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_i32(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_cstr(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut buf = vec![tag];
+        put_i32(&mut buf, body.len() as i32 + 4);
+        buf.extend_from_slice(body);
+        buf
+    }
+
+    #[tokio::test]
+    async fn read_startup_message() -> Result<()> {
+        let mut body = vec![];
+        put_i32(&mut body, PROTOCOL_VERSION);
+        put_cstr(&mut body, "user");
+        put_cstr(&mut body, "alice");
+        put_cstr(&mut body, "database");
+        put_cstr(&mut body, "test");
+        body.push(0);
+        let mut buf = vec![];
+        put_i32(&mut buf, body.len() as i32 + 4);
+        buf.extend_from_slice(&body);
+
+        let message = read_startup(&mut buf.as_slice()).await?;
+        assert_eq!(
+            message,
+            StartupMessage::Startup {
+                parameters: vec![
+                    ("user".to_owned(), "alice".to_owned()),
+                    ("database".to_owned(), "test".to_owned())
+                ]
+            }
+        );
+
+        let mut buf = vec![];
+        put_i32(&mut buf, 8);
+        put_i32(&mut buf, SSL_REQUEST_CODE);
+        let message = read_startup(&mut buf.as_slice()).await?;
+        assert_eq!(message, StartupMessage::SslRequest);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_extended_query_messages() -> Result<()> {
+        let mut parse = vec![];
+        put_cstr(&mut parse, "s1");
+        put_cstr(&mut parse, "SELECT $1");
+        put_i16(&mut parse, 1);
+        put_i32(&mut parse, 23);
+
+        let mut bind = vec![];
+        put_cstr(&mut bind, "");
+        put_cstr(&mut bind, "s1");
+        put_i16(&mut bind, 0);
+        put_i16(&mut bind, 2);
+        put_i32(&mut bind, 2);
+        bind.extend_from_slice(b"42");
+        put_i32(&mut bind, -1);
+        put_i16(&mut bind, 1);
+        put_i16(&mut bind, 0);
+
+        let mut execute = vec![];
+        put_cstr(&mut execute, "");
+        put_i32(&mut execute, 10);
+
+        let mut buf = frame(b'P', &parse);
+        buf.extend(frame(b'B', &bind));
+        buf.extend(frame(b'E', &execute));
+        buf.extend(frame(b'S', &[]));
+        let mut reader = buf.as_slice();
+
+        assert_eq!(
+            read_message(&mut reader).await?,
+            Some(FrontendMessage::Parse {
+                name: "s1".to_owned(),
+                query: "SELECT $1".to_owned(),
+                param_types: vec![23],
+            })
+        );
+        assert_eq!(
+            read_message(&mut reader).await?,
+            Some(FrontendMessage::Bind {
+                portal: "".to_owned(),
+                statement: "s1".to_owned(),
+                param_formats: vec![],
+                params: vec![Some(b"42".to_vec()), None],
+                result_formats: vec![0],
+            })
+        );
+        assert_eq!(
+            read_message(&mut reader).await?,
+            Some(FrontendMessage::Execute {
+                portal: "".to_owned(),
+                max_rows: 10,
+            })
+        );
+        assert_eq!(
+            read_message(&mut reader).await?,
+            Some(FrontendMessage::Sync)
+        );
+        assert_eq!(read_message(&mut reader).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_truncated_message() {
+        // the Query string is missing its terminator
+        let buf = frame(b'Q', b"SELECT 1");
+        let err = read_message(&mut buf.as_slice()).await.unwrap_err();
+        assert!(err.to_string().contains("unterminated string"));
+    }
+
+    #[test]
+    fn encode_data_row() {
+        let mut buf = vec![];
+        BackendMessage::DataRow(vec![Some("ab".to_owned()), None]).encode(&mut buf);
+        assert_eq!(
+            buf,
+            vec![b'D', 0, 0, 0, 16, 0, 2, 0, 0, 0, 2, b'a', b'b', 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn encode_row_description() {
+        let mut buf = vec![];
+        BackendMessage::RowDescription(vec![FieldDescription {
+            name: "a".to_owned(),
+            type_oid: 23,
+            type_len: 4,
+            format: 0,
+        }])
+        .encode(&mut buf);
+        assert_eq!(
+            buf,
+            vec![
+                b'T', 0, 0, 0, 26, 0, 1, b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 23, 0, 4,
+                255, 255, 255, 255, 0, 0
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_ready_for_query() {
+        let mut buf = vec![];
+        BackendMessage::ReadyForQuery.encode(&mut buf);
+        assert_eq!(buf, vec![b'Z', 0, 0, 0, 5, b'I']);
+    }
+}
@@ -0,0 +1,647 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Serving an [`ExecutionContext`] over the PostgreSQL wire protocol
+
+use std::collections::HashMap;
+
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use futures::StreamExt;
+use log::{debug, warn};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+use super::messages::{
+    read_message, read_startup, BackendMessage, FrontendMessage, StartupMessage,
+};
+use super::types::{self, oid};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionContext;
+use crate::logical_plan::LogicalPlan;
+use crate::physical_plan::SendableRecordBatchStream;
+use crate::sql::parser::DFParser;
+
+/// Output is sent to the client once this many bytes are buffered
+const FLUSH_THRESHOLD: usize = 64 * 1024;
+
+/// Serves queries against an [`ExecutionContext`] to PostgreSQL clients such as
+/// `psql` or BI tools, supporting both the simple and the extended query
+/// protocol.
+///
+/// All connections share the tables registered with the context. Clients
+/// are not authenticated, results are sent in text format and bound
+/// parameters are substituted into the query as literals.
+///
+/// ```no_run
+/// # use datafusion::prelude::*;
+/// # use datafusion::error::Result;
+/// # use datafusion::pgwire::PgWireServer;
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let mut ctx = ExecutionContext::new();
+/// ctx.register_csv("example", "tests/example.csv", CsvReadOptions::new())?;
+/// PgWireServer::new(ctx).serve("127.0.0.1:5432").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PgWireServer {
+    ctx: ExecutionContext,
+}
+
+impl PgWireServer {
+    /// Creates a server answering queries with `ctx`
+    pub fn new(ctx: ExecutionContext) -> Self {
+        Self { ctx }
+    }
+
+    /// Listens on `addr`, serving every client on its own task
+    pub async fn serve(&self, addr: impl ToSocketAddrs) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let (socket, peer) = listener.accept().await?;
+            debug!("Accepted pg wire connection from {}", peer);
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(socket).await {
+                    warn!("pg wire connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    /// Runs a session with a client connected through `stream`, returning once
+    /// it disconnects
+    pub async fn handle_connection<S>(&self, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        Connection {
+            ctx: self.ctx.clone(),
+            stream: BufReader::new(stream),
+            out: vec![],
+            statements: HashMap::new(),
+            portals: HashMap::new(),
+        }
+        .run()
+        .await
+    }
+}
+
+/// A statement created by a `Parse`
+struct PreparedStatement {
+    query: String,
+    param_types: Vec<i32>,
+    /// Schema of the results, `None` for statements that return no rows
+    schema: Option<Schema>,
+}
+
+/// A statement with bound parameters created by a `Bind`
+struct Portal {
+    query: String,
+    schema: Option<Schema>,
+    /// Results not yet sent to the client, once the portal was executed
+    rows: Option<PendingRows>,
+}
+
+/// The unsent results of a query
+struct PendingRows {
+    stream: SendableRecordBatchStream,
+    batch: Option<RecordBatch>,
+    offset: usize,
+    sent: usize,
+}
+
+struct Connection<S> {
+    ctx: ExecutionContext,
+    stream: BufReader<S>,
+    out: Vec<u8>,
+    statements: HashMap<String, PreparedStatement>,
+    portals: HashMap<String, Portal>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    async fn run(mut self) -> Result<()> {
+        loop {
+            match read_startup(&mut self.stream).await? {
+                // encryption is not supported, the client may continue in
+                // plain text
+                StartupMessage::SslRequest | StartupMessage::GssEncRequest => {
+                    self.stream.write_all(b"N").await?;
+                    self.stream.flush().await?;
+                }
+                // queries run to completion, there is nothing to cancel
+                StartupMessage::CancelRequest => return Ok(()),
+                StartupMessage::Startup { parameters } => {
+                    debug!("pg wire session started with {:?}", parameters);
+                    break;
+                }
+            }
+        }
+
+        self.send(BackendMessage::AuthenticationOk);
+        for (name, value) in &[
+            ("server_version", "13.0"),
+            ("server_encoding", "UTF8"),
+            ("client_encoding", "UTF8"),
+            ("DateStyle", "ISO, MDY"),
+            ("integer_datetimes", "on"),
+            ("standard_conforming_strings", "on"),
+        ] {
+            self.send(BackendMessage::ParameterStatus(
+                name.to_string(),
+                value.to_string(),
+            ));
+        }
+        self.send(BackendMessage::ReadyForQuery);
+        self.flush().await?;
+
+        // after an error in the extended query protocol, messages are
+        // discarded until the next Sync
+        let mut failed = false;
+        while let Some(message) = read_message(&mut self.stream).await? {
+            match message {
+                FrontendMessage::Terminate => break,
+                FrontendMessage::Sync => {
+                    failed = false;
+                    self.send(BackendMessage::ReadyForQuery);
+                    self.flush().await?;
+                }
+                FrontendMessage::Flush => self.flush().await?,
+                FrontendMessage::Query(query) => {
+                    // a simple query ends any unfinished extended query cycle
+                    failed = false;
+                    if let Err(e) = self.simple_query(&query).await {
+                        self.send_error(&e);
+                    }
+                    self.send(BackendMessage::ReadyForQuery);
+                    self.flush().await?;
+                }
+                _ if failed => {}
+                message => {
+                    if let Err(e) = self.extended_query(message).await {
+                        self.send_error(&e);
+                        failed = true;
+                    }
+                }
+            }
+        }
+        self.flush().await
+    }
+
+    async fn simple_query(&mut self, query: &str) -> Result<()> {
+        if DFParser::parse_sql(query)?.is_empty() {
+            self.send(BackendMessage::EmptyQueryResponse);
+            return Ok(());
+        }
+        // the unnamed portal does not survive a simple query
+        self.portals.remove("");
+
+        let mut portal = Portal {
+            query: query.to_owned(),
+            schema: None,
+            rows: None,
+        };
+        portal.schema = self.describe(query)?;
+        if let Some(schema) = &portal.schema {
+            self.send(BackendMessage::RowDescription(types::row_description(
+                schema,
+            )));
+        }
+        self.execute(&mut portal, 0).await
+    }
+
+    async fn extended_query(&mut self, message: FrontendMessage) -> Result<()> {
+        match message {
+            FrontendMessage::Parse {
+                name,
+                query,
+                mut param_types,
+            } => {
+                if !name.is_empty() && self.statements.contains_key(&name) {
+                    return Err(DataFusionError::Plan(format!(
+                        "prepared statement \"{}\" already exists",
+                        name
+                    )));
+                }
+                let count = types::parameter_count(&query).max(param_types.len());
+                param_types.resize(count, 0);
+                // plan with placeholder parameters to report errors and the
+                // schema early
+                let placeholders = types::placeholder_values(&param_types);
+                let planned =
+                    types::bind_parameters(&query, &param_types, &placeholders)?;
+                let schema = self.describe(&planned)?;
+                self.statements.insert(
+                    name,
+                    PreparedStatement {
+                        query,
+                        param_types,
+                        schema,
+                    },
+                );
+                self.send(BackendMessage::ParseComplete);
+            }
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                param_formats,
+                params,
+                result_formats,
+            } => {
+                if param_formats.iter().any(|format| *format != 0) {
+                    return Err(DataFusionError::NotImplemented(
+                        "binary parameter format is not supported".to_owned(),
+                    ));
+                }
+                if result_formats.iter().any(|format| *format != 0) {
+                    return Err(DataFusionError::NotImplemented(
+                        "binary result format is not supported".to_owned(),
+                    ));
+                }
+                let statement = self.statement(&statement)?;
+                let query = types::bind_parameters(
+                    &statement.query,
+                    &statement.param_types,
+                    &params,
+                )?;
+                let schema = statement.schema.clone();
+                self.portals.insert(
+                    portal,
+                    Portal {
+                        query,
+                        schema,
+                        rows: None,
+                    },
+                );
+                self.send(BackendMessage::BindComplete);
+            }
+            FrontendMessage::Describe { kind: b'S', name } => {
+                let statement = self.statement(&name)?;
+                let param_types = statement
+                    .param_types
+                    .iter()
+                    .map(|t| if *t == 0 { oid::TEXT } else { *t })
+                    .collect();
+                let schema = statement.schema.clone();
+                self.send(BackendMessage::ParameterDescription(param_types));
+                self.send_description(schema.as_ref());
+            }
+            FrontendMessage::Describe { kind: b'P', name } => {
+                let schema = self.portal(&name)?.schema.clone();
+                self.send_description(schema.as_ref());
+            }
+            FrontendMessage::Execute { portal, max_rows } => {
+                // take the portal out of the map while it sends its rows
+                let mut p = self.portals.remove(&portal).ok_or_else(|| {
+                    DataFusionError::Plan(format!("portal \"{}\" does not exist", portal))
+                })?;
+                let result = self.execute(&mut p, max_rows.max(0) as usize).await;
+                self.portals.insert(portal, p);
+                result?;
+            }
+            FrontendMessage::Close { kind, name } => {
+                match kind {
+                    b'S' => {
+                        self.statements.remove(&name);
+                    }
+                    _ => {
+                        self.portals.remove(&name);
+                    }
+                }
+                self.send(BackendMessage::CloseComplete);
+            }
+            message => {
+                return Err(DataFusionError::Internal(format!(
+                    "unexpected extended query message {:?}",
+                    message
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends up to `max_rows` rows of `portal`, all of them if 0
+    async fn execute(&mut self, portal: &mut Portal, max_rows: usize) -> Result<()> {
+        if portal.rows.is_none() {
+            let df = self.ctx.sql(&portal.query)?;
+            if portal.schema.is_none() {
+                self.send(BackendMessage::CommandComplete(command_tag(&portal.query)));
+                return Ok(());
+            }
+            portal.rows = Some(PendingRows {
+                stream: df.execute_stream().await?,
+                batch: None,
+                offset: 0,
+                sent: 0,
+            });
+        }
+        let rows = portal.rows.as_mut().unwrap();
+
+        let mut remaining = if max_rows == 0 { usize::MAX } else { max_rows };
+        loop {
+            let batch = match &rows.batch {
+                Some(batch) if rows.offset < batch.num_rows() => batch,
+                _ => match rows.stream.next().await.transpose()? {
+                    Some(batch) => {
+                        rows.batch = Some(batch);
+                        rows.offset = 0;
+                        continue;
+                    }
+                    None => {
+                        let tag = format!("SELECT {}", rows.sent);
+                        self.send(BackendMessage::CommandComplete(tag));
+                        return Ok(());
+                    }
+                },
+            };
+            if remaining == 0 {
+                self.send(BackendMessage::PortalSuspended);
+                return Ok(());
+            }
+            let len = (batch.num_rows() - rows.offset).min(remaining);
+            for row in types::encode_rows(batch, rows.offset, len)? {
+                BackendMessage::DataRow(row).encode(&mut self.out);
+            }
+            rows.offset += len;
+            rows.sent += len;
+            remaining -= len;
+            if self.out.len() >= FLUSH_THRESHOLD {
+                self.flush().await?;
+            }
+        }
+    }
+
+    /// Returns the schema of the results of `query`, `None` if it returns no
+    /// rows
+    fn describe(&self, query: &str) -> Result<Option<Schema>> {
+        let plan = self.ctx.create_logical_plan(query)?;
+        Ok(match plan {
+            LogicalPlan::CreateExternalTable { .. } | LogicalPlan::SetVariable { .. } => {
+                None
+            }
+            plan => Some(plan.schema().as_ref().clone().into()),
+        })
+    }
+
+    fn statement(&self, name: &str) -> Result<&PreparedStatement> {
+        self.statements.get(name).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "prepared statement \"{}\" does not exist",
+                name
+            ))
+        })
+    }
+
+    fn portal(&self, name: &str) -> Result<&Portal> {
+        self.portals.get(name).ok_or_else(|| {
+            DataFusionError::Plan(format!("portal \"{}\" does not exist", name))
+        })
+    }
+
+    fn send(&mut self, message: BackendMessage) {
+        message.encode(&mut self.out);
+    }
+
+    fn send_description(&mut self, schema: Option<&Schema>) {
+        match schema {
+            Some(schema) => self.send(BackendMessage::RowDescription(
+                types::row_description(schema),
+            )),
+            None => self.send(BackendMessage::NoData),
+        }
+    }
+
+    fn send_error(&mut self, e: &DataFusionError) {
+        debug!("pg wire query failed: {}", e);
+        self.send(BackendMessage::ErrorResponse {
+            code: sql_state(e).to_owned(),
+            message: e.to_string(),
+        });
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if !self.out.is_empty() {
+            self.stream.write_all(&self.out).await?;
+            self.out.clear();
+        }
+        self.stream.flush().await?;
+        Ok(())
+    }
+}
+
+/// The tag of the `CommandComplete` sent for a statement that returns no rows
+fn command_tag(query: &str) -> String {
+    let keyword = query
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(';')
+        .to_uppercase();
+    match keyword.as_str() {
+        "CREATE" => "CREATE TABLE".to_owned(),
+        _ => keyword,
+    }
+}
+
+/// The SQLSTATE error code reported for `e`
+fn sql_state(e: &DataFusionError) -> &'static str {
+    match e {
+        // syntax_error
+        DataFusionError::SQL(_) => "42601",
+        // feature_not_supported
+        DataFusionError::NotImplemented(_) => "0A000",
+        // syntax_error_or_access_rule_violation
+        DataFusionError::Plan(_) => "42000",
+        // program_limit_exceeded
+        DataFusionError::ResourcesExhausted(_) => "54000",
+        // internal_error
+        _ => "XX000",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use std::convert::TryInto;
+    use tokio::io::{AsyncReadExt, DuplexStream};
+
+    /// A minimal client recording the messages the server sends
+    struct TestClient {
+        stream: DuplexStream,
+    }
+
+    impl TestClient {
+        async fn connect() -> Result<Self> {
+            let mut ctx = ExecutionContext::new();
+            ctx.register_table("t", test::table_with_sequence(1, 5)?)?;
+            let (client, server) = tokio::io::duplex(1024);
+            tokio::spawn(async move {
+                PgWireServer::new(ctx).handle_connection(server).await
+            });
+
+            let mut client = Self { stream: client };
+            let mut body = vec![];
+            body.extend_from_slice(&196608i32.to_be_bytes());
+            body.extend_from_slice(b"user\0test\0\0");
+            let mut startup = ((body.len() + 4) as i32).to_be_bytes().to_vec();
+            startup.extend(body);
+            client.stream.write_all(&startup).await?;
+
+            let tags: String = client.receive_until_ready().await?.0;
+            assert_eq!(tags, "RSSSSSSZ");
+            Ok(client)
+        }
+
+        async fn send(&mut self, tag: u8, body: &[u8]) -> Result<()> {
+            let mut message = vec![tag];
+            message.extend_from_slice(&((body.len() + 4) as i32).to_be_bytes());
+            message.extend_from_slice(body);
+            self.stream.write_all(&message).await?;
+            Ok(())
+        }
+
+        /// Returns the tags of the messages up to and including the next
+        /// ReadyForQuery, and the values of the rows received
+        async fn receive_until_ready(&mut self) -> Result<(String, Vec<Vec<String>>)> {
+            let mut tags = String::new();
+            let mut rows = vec![];
+            loop {
+                let tag = self.stream.read_u8().await?;
+                let len = self.stream.read_i32().await? as usize - 4;
+                let mut body = vec![0; len];
+                self.stream.read_exact(&mut body).await?;
+                tags.push(tag as char);
+                match tag {
+                    b'D' => rows.push(decode_row(&body)),
+                    b'Z' => return Ok((tags, rows)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn decode_row(body: &[u8]) -> Vec<String> {
+        let count = i16::from_be_bytes(body[0..2].try_into().unwrap());
+        let mut pos = 2;
+        (0..count)
+            .map(|_| {
+                let len = i32::from_be_bytes(body[pos..pos + 4].try_into().unwrap());
+                pos += 4;
+                if len < 0 {
+                    return "NULL".to_owned();
+                }
+                let value = String::from_utf8(body[pos..pos + len as usize].to_vec());
+                pos += len as usize;
+                value.unwrap()
+            })
+            .collect()
+    }
+
+    fn cstr(s: &str) -> Vec<u8> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.push(0);
+        bytes
+    }
+
+    #[tokio::test]
+    async fn simple_query() -> Result<()> {
+        let mut client = TestClient::connect().await?;
+
+        client
+            .send(b'Q', &cstr("SELECT i, i * 2 FROM t WHERE i > 3 ORDER BY i"))
+            .await?;
+        let (tags, rows) = client.receive_until_ready().await?;
+        assert_eq!(tags, "TDDCZ");
+        assert_eq!(rows, vec![vec!["4", "8"], vec!["5", "10"]]);
+
+        client.send(b'Q', &cstr(";")).await?;
+        assert_eq!(client.receive_until_ready().await?.0, "IZ");
+
+        client.send(b'Q', &cstr("SELECT x FROM t")).await?;
+        assert_eq!(client.receive_until_ready().await?.0, "EZ");
+
+        client.send(b'X', &[]).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn extended_query() -> Result<()> {
+        let mut client = TestClient::connect().await?;
+
+        let mut parse = cstr("s1");
+        parse.extend(cstr("SELECT i FROM t WHERE i >= $1 ORDER BY i"));
+        parse.extend_from_slice(&1i16.to_be_bytes());
+        parse.extend_from_slice(&oid::INT4.to_be_bytes());
+        client.send(b'P', &parse).await?;
+
+        let mut describe = vec![b'S'];
+        describe.extend(cstr("s1"));
+        client.send(b'D', &describe).await?;
+
+        let mut bind = cstr("");
+        bind.extend(cstr("s1"));
+        bind.extend_from_slice(&0i16.to_be_bytes());
+        bind.extend_from_slice(&1i16.to_be_bytes());
+        bind.extend_from_slice(&1i32.to_be_bytes());
+        bind.extend_from_slice(b"2");
+        bind.extend_from_slice(&0i16.to_be_bytes());
+        client.send(b'B', &bind).await?;
+
+        // fetch the rows in two round trips
+        let mut execute = cstr("");
+        execute.extend_from_slice(&3i32.to_be_bytes());
+        client.send(b'E', &execute).await?;
+        client.send(b'E', &execute).await?;
+        client.send(b'S', &[]).await?;
+
+        let (tags, rows) = client.receive_until_ready().await?;
+        assert_eq!(tags, "1tT2DDDsDCZ");
+        assert_eq!(rows, vec![vec!["2"], vec!["3"], vec!["4"], vec!["5"]]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn extended_query_error_skips_until_sync() -> Result<()> {
+        let mut client = TestClient::connect().await?;
+
+        let mut parse = cstr("");
+        parse.extend(cstr("SELECT x FROM t"));
+        parse.extend_from_slice(&0i16.to_be_bytes());
+        client.send(b'P', &parse).await?;
+
+        let mut bind = cstr("");
+        bind.extend(cstr(""));
+        bind.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        client.send(b'B', &bind).await?;
+        client.send(b'S', &[]).await?;
+
+        assert_eq!(client.receive_until_ready().await?.0, "EZ");
+        Ok(())
+    }
+
+    #[test]
+    fn command_tags() {
+        assert_eq!(
+            command_tag("CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 'x'"),
+            "CREATE TABLE"
+        );
+        assert_eq!(command_tag(" set datafusion.batch_size = 10;"), "SET");
+    }
+}
@@ -0,0 +1,371 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Mapping of Arrow data to PostgreSQL types and their text representation
+
+use arrow::array::{Array, ArrayRef, BinaryArray, BooleanArray, LargeBinaryArray};
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+
+use super::messages::{protocol_err, FieldDescription};
+use crate::error::{DataFusionError, Result};
+
+/// Object ids of the PostgreSQL types results are reported as
+pub mod oid {
+    pub const BOOL: i32 = 16;
+    pub const BYTEA: i32 = 17;
+    pub const INT8: i32 = 20;
+    pub const INT2: i32 = 21;
+    pub const INT4: i32 = 23;
+    pub const TEXT: i32 = 25;
+    pub const FLOAT4: i32 = 700;
+    pub const FLOAT8: i32 = 701;
+    pub const DATE: i32 = 1082;
+    pub const TIME: i32 = 1083;
+    pub const TIMESTAMP: i32 = 1114;
+    pub const TIMESTAMPTZ: i32 = 1184;
+    pub const INTERVAL: i32 = 1186;
+    pub const NUMERIC: i32 = 1700;
+}
+
+/// Returns the PostgreSQL type oid and length `data_type` is reported as.
+/// Types without a PostgreSQL counterpart are sent as `text`.
+pub fn pg_type(data_type: &DataType) -> (i32, i16) {
+    match data_type {
+        DataType::Boolean => (oid::BOOL, 1),
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => (oid::INT2, 2),
+        DataType::Int32 | DataType::UInt16 => (oid::INT4, 4),
+        DataType::Int64 | DataType::UInt32 | DataType::UInt64 => (oid::INT8, 8),
+        DataType::Float16 | DataType::Float32 => (oid::FLOAT4, 4),
+        DataType::Float64 => (oid::FLOAT8, 8),
+        DataType::Decimal(_, _) => (oid::NUMERIC, -1),
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            (oid::BYTEA, -1)
+        }
+        DataType::Date32 | DataType::Date64 => (oid::DATE, 4),
+        DataType::Time32(_) | DataType::Time64(_) => (oid::TIME, 8),
+        DataType::Timestamp(_, None) => (oid::TIMESTAMP, 8),
+        DataType::Timestamp(_, Some(_)) => (oid::TIMESTAMPTZ, 8),
+        DataType::Interval(_) => (oid::INTERVAL, 16),
+        DataType::Dictionary(_, value_type) => pg_type(value_type),
+        _ => (oid::TEXT, -1),
+    }
+}
+
+/// Describes the columns of `schema`, all of which are sent in text format
+pub fn row_description(schema: &Schema) -> Vec<FieldDescription> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let (type_oid, type_len) = pg_type(field.data_type());
+            FieldDescription {
+                name: field.name().clone(),
+                type_oid,
+                type_len,
+                format: 0,
+            }
+        })
+        .collect()
+}
+
+/// Converts the rows `offset..offset + len` of `batch` to their PostgreSQL text
+/// representation
+pub fn encode_rows(
+    batch: &RecordBatch,
+    offset: usize,
+    len: usize,
+) -> Result<Vec<Vec<Option<String>>>> {
+    (offset..offset + len)
+        .map(|row| {
+            batch
+                .columns()
+                .iter()
+                .map(|column| encode_value(column, row))
+                .collect()
+        })
+        .collect()
+}
+
+fn encode_value(column: &ArrayRef, row: usize) -> Result<Option<String>> {
+    if column.is_null(row) {
+        return Ok(None);
+    }
+    let value = match column.data_type() {
+        DataType::Boolean => {
+            let array = column.as_any().downcast_ref::<BooleanArray>().unwrap();
+            if array.value(row) { "t" } else { "f" }.to_owned()
+        }
+        DataType::Float32 | DataType::Float64 => {
+            float_to_string(array_value_to_string(column, row)?)
+        }
+        DataType::Binary => {
+            let array = column.as_any().downcast_ref::<BinaryArray>().unwrap();
+            bytea_to_string(array.value(row))
+        }
+        DataType::LargeBinary => {
+            let array = column.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
+            bytea_to_string(array.value(row))
+        }
+        _ => array_value_to_string(column, row)?,
+    };
+    Ok(Some(value))
+}
+
+/// PostgreSQL spells infinite floats differently than Rust
+fn float_to_string(value: String) -> String {
+    match value.as_str() {
+        "inf" => "Infinity".to_owned(),
+        "-inf" => "-Infinity".to_owned(),
+        _ => value,
+    }
+}
+
+/// Hex format of `bytea` values
+fn bytea_to_string(value: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + value.len() * 2);
+    s.push_str("\\x");
+    for b in value {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+/// Returns the number of `$n` parameter placeholders `query` refers to
+pub fn parameter_count(query: &str) -> usize {
+    let mut count = 0;
+    scan_placeholders(query, |n| {
+        count = count.max(n);
+        Ok(String::new())
+    })
+    .unwrap();
+    count
+}
+
+/// Replaces the `$n` placeholders of `query` with the text-format parameter
+/// values of a `Bind`, as SQL literals of the declared parameter types
+pub fn bind_parameters(
+    query: &str,
+    param_types: &[i32],
+    params: &[Option<Vec<u8>>],
+) -> Result<String> {
+    scan_placeholders(query, |n| {
+        let value = params.get(n - 1).ok_or_else(|| {
+            protocol_err(format!(
+                "query refers to parameter ${} but only {} were bound",
+                n,
+                params.len()
+            ))
+        })?;
+        let value = match value {
+            Some(value) => std::str::from_utf8(value).map_err(|e| {
+                protocol_err(format!("invalid UTF-8 in parameter ${}: {}", n, e))
+            })?,
+            None => return Ok("NULL".to_owned()),
+        };
+        let param_type = param_types.get(n - 1).copied().unwrap_or(0);
+        parameter_literal(param_type, value).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "invalid value for parameter ${}: {}",
+                n, value
+            ))
+        })
+    })
+}
+
+fn parameter_literal(param_type: i32, value: &str) -> Option<String> {
+    match param_type {
+        oid::INT2 | oid::INT4 | oid::INT8 => {
+            value.trim().parse::<i64>().ok().map(|v| v.to_string())
+        }
+        oid::FLOAT4 | oid::FLOAT8 | oid::NUMERIC => {
+            let value = value.trim();
+            match value.parse::<f64>() {
+                Ok(v) if v.is_finite() => Some(value.to_owned()),
+                _ => None,
+            }
+        }
+        oid::BOOL => match value.trim().to_lowercase().as_str() {
+            "t" | "true" | "y" | "yes" | "on" | "1" => Some("true".to_owned()),
+            "f" | "false" | "n" | "no" | "off" | "0" => Some("false".to_owned()),
+            _ => None,
+        },
+        // parameters of unspecified type are numbers if they look like one
+        0 if parameter_literal(oid::FLOAT8, value).is_some() => {
+            Some(value.trim().to_owned())
+        }
+        _ => Some(format!("'{}'", value.replace('\'', "''"))),
+    }
+}
+
+/// Returns values of the given parameter types to plan a query with before its
+/// parameters are bound
+pub fn placeholder_values(param_types: &[i32]) -> Vec<Option<Vec<u8>>> {
+    param_types
+        .iter()
+        .map(|param_type| match *param_type {
+            oid::INT2
+            | oid::INT4
+            | oid::INT8
+            | oid::FLOAT4
+            | oid::FLOAT8
+            | oid::NUMERIC => Some(b"0".to_vec()),
+            oid::BOOL => Some(b"f".to_vec()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Calls `f` for every `$n` placeholder outside of literals and quoted
+/// identifiers, replacing the placeholder with its result
+fn scan_placeholders(
+    query: &str,
+    mut f: impl FnMut(usize) -> Result<String>,
+) -> Result<String> {
+    let mut output = String::with_capacity(query.len());
+    let mut quote = None;
+    let mut chars = query.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (None, '$') => {
+                let mut digits = String::new();
+                while let Some((_, d)) = chars.peek().filter(|(_, d)| d.is_ascii_digit())
+                {
+                    digits.push(*d);
+                    chars.next();
+                }
+                if let Ok(n) = digits.parse::<usize>() {
+                    if n > 0 {
+                        output.push_str(&f(n)?);
+                        continue;
+                    }
+                }
+                output.push(c);
+                output.push_str(&digits);
+                continue;
+            }
+            _ => {}
+        }
+        output.push(c);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::Field;
+    use std::sync::Arc;
+
+    #[test]
+    fn encode_batch_rows() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Boolean, true),
+            Field::new("d", DataType::Float64, true),
+            Field::new("e", DataType::Binary, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None])),
+                Arc::new(StringArray::from(vec![Some("x"), Some("y")])),
+                Arc::new(BooleanArray::from(vec![Some(true), Some(false)])),
+                Arc::new(Float64Array::from(vec![1.5, f64::NEG_INFINITY])),
+                Arc::new(BinaryArray::from(vec![&b"\x01\xab"[..], &b""[..]])),
+            ],
+        )?;
+
+        let rows = encode_rows(&batch, 0, 2)?;
+        let expected: Vec<Vec<Option<String>>> = vec![
+            vec![
+                Some("1".to_owned()),
+                Some("x".to_owned()),
+                Some("t".to_owned()),
+                Some("1.5".to_owned()),
+                Some("\\x01ab".to_owned()),
+            ],
+            vec![
+                None,
+                Some("y".to_owned()),
+                Some("f".to_owned()),
+                Some("-Infinity".to_owned()),
+                Some("\\x".to_owned()),
+            ],
+        ];
+        assert_eq!(rows, expected);
+
+        let fields = row_description(&schema);
+        let types: Vec<_> = fields.iter().map(|f| f.type_oid).collect();
+        assert_eq!(
+            types,
+            vec![oid::INT4, oid::TEXT, oid::BOOL, oid::FLOAT8, oid::BYTEA]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bind_query_parameters() -> Result<()> {
+        let query = "SELECT $1, '$2', \"$2\", $2, $10 FROM t WHERE c = $3";
+        assert_eq!(parameter_count(query), 10);
+
+        let params: Vec<Option<Vec<u8>>> = vec![
+            Some(b"42".to_vec()),
+            Some(b"it's".to_vec()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(b"true".to_vec()),
+        ];
+        let mut types = vec![oid::INT4, oid::TEXT];
+        types.resize(9, 0);
+        types.push(oid::BOOL);
+        assert_eq!(
+            bind_parameters(query, &types, &params)?,
+            "SELECT 42, '$2', \"$2\", 'it''s', true FROM t WHERE c = NULL"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bind_invalid_parameters() {
+        let err =
+            bind_parameters("SELECT $1", &[oid::INT8], &[Some(b"1; DROP".to_vec())])
+                .unwrap_err();
+        assert!(err.to_string().contains("invalid value for parameter $1"));
+
+        let err = bind_parameters("SELECT $2", &[], &[None]).unwrap_err();
+        assert!(err.to_string().contains("only 1 were bound"));
+
+        // untyped parameters are numbers if they look like one
+        let params = vec![Some(b"1.5".to_vec()), Some(b"1.5 x".to_vec())];
+        assert_eq!(
+            bind_parameters("SELECT $1, $2", &[], &params).unwrap(),
+            "SELECT 1.5, '1.5 x'"
+        );
+    }
+}
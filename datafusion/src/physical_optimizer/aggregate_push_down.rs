@@ -0,0 +1,462 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! AggregatePushDown moves partial aggregations below UNION ALL and the probe
+//! side of inner hash joins, so that fewer rows flow through them.
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use crate::error::Result;
+use crate::execution::context::ExecutionConfig;
+use crate::physical_plan::expressions::{
+    col, format_state_name, Column, Count, Literal, Max, Min, Sum,
+};
+use crate::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
+use crate::physical_plan::hash_join::HashJoinExec;
+use crate::physical_plan::hash_utils::JoinType;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::union::UnionExec;
+use crate::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr};
+
+/// Pushes partial [`HashAggregateExec`]s below the operators they aggregate:
+///
+/// * a partial aggregation of a [`UnionExec`] whose branches share its schema
+///   becomes a union of the partial aggregations of every branch.
+/// * a grouped partial aggregation of an inner [`HashJoinExec`] first
+///   aggregates the right (probe) side grouped by its join keys and the
+///   grouping columns of the right side, then merges the resulting states
+///   above the join. This is only done if all aggregates are `SUM`, `COUNT`,
+///   `MIN` or `MAX` of columns of the right side or literals.
+///
+/// The output of the rewritten plan has the same schema as the partial
+/// aggregation it replaces, so the final aggregation is left unchanged.
+pub struct AggregatePushDown {}
+
+impl AggregatePushDown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for AggregatePushDown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        push_down_aggregates(plan)
+    }
+
+    fn name(&self) -> &str {
+        "aggregate_push_down"
+    }
+}
+
+fn push_down_aggregates(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    if let Some(aggregate) = plan.as_any().downcast_ref::<HashAggregateExec>() {
+        if matches!(aggregate.mode(), AggregateMode::Partial) {
+            let input = aggregate.input().as_any();
+            let pushed = if let Some(union) = input.downcast_ref::<UnionExec>() {
+                push_below_union(aggregate, union)?
+            } else if let Some(join) = input.downcast_ref::<HashJoinExec>() {
+                push_below_join(aggregate, join)?
+            } else {
+                None
+            };
+            if let Some(pushed) = pushed {
+                return Ok(pushed);
+            }
+        }
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(plan);
+    }
+    let children = children
+        .into_iter()
+        .map(push_down_aggregates)
+        .collect::<Result<Vec<_>>>()?;
+    plan.with_new_children(children)
+}
+
+fn push_below_union(
+    aggregate: &HashAggregateExec,
+    union: &UnionExec,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    let branches = union.children();
+    // branches coerced to the schema of the union would be aggregated
+    // with different types
+    if !branches
+        .iter()
+        .all(|branch| branch.schema() == union.schema())
+    {
+        return Ok(None);
+    }
+
+    let branches = branches
+        .into_iter()
+        .map(|branch| {
+            push_down_aggregates(partial_aggregate(
+                aggregate,
+                aggregate.group_expr().to_vec(),
+                aggregate.aggr_expr().to_vec(),
+                branch,
+            )?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(Arc::new(UnionExec::try_new(branches)?)))
+}
+
+fn push_below_join(
+    aggregate: &HashAggregateExec,
+    join: &HashJoinExec,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    // without groups, the partial aggregation outputs a row even if the
+    // join does not, which the merged states cannot reproduce
+    if *join.join_type() != JoinType::Inner || aggregate.group_expr().is_empty() {
+        return Ok(None);
+    }
+
+    let left_schema = join.left().schema();
+    let right_schema = join.right().schema();
+    let is_right_column = |name: &str| {
+        right_schema.index_of(name).is_ok() && left_schema.index_of(name).is_err()
+    };
+
+    // every group of the right side joins the same rows of the left side,
+    // as long as the groups are refined by the join keys
+    let mut right_groups = join
+        .on()
+        .iter()
+        .map(|(_, right)| right.clone())
+        .collect::<Vec<_>>();
+    for (expr, _) in aggregate.group_expr() {
+        let name = match expr.as_any().downcast_ref::<Column>() {
+            Some(column) => column.name(),
+            None => return Ok(None),
+        };
+        if left_schema.index_of(name).is_ok() {
+            continue;
+        }
+        if !is_right_column(name) {
+            return Ok(None);
+        }
+        if !right_groups.iter().any(|group| group == name) {
+            right_groups.push(name.to_string());
+        }
+    }
+
+    let mut right_aggr_expr = vec![];
+    let mut merged_aggr_expr: Vec<Arc<dyn AggregateExpr>> = vec![];
+    let mut merges_counts = false;
+    for expr in aggregate.aggr_expr() {
+        let on_right_side = expr.expressions().iter().all(|arg| {
+            match arg.as_any().downcast_ref::<Column>() {
+                Some(column) => is_right_column(column.name()),
+                None => arg.as_any().is::<Literal>(),
+            }
+        });
+        if !on_right_side {
+            return Ok(None);
+        }
+
+        let name = expr.name().to_string();
+        let data_type = expr.field()?.data_type().clone();
+        let any = expr.as_any();
+        let merged: Arc<dyn AggregateExpr> = if any.is::<Sum>() {
+            Arc::new(Sum::new(state_column(&name, "sum"), name, data_type))
+        } else if any.is::<Count>() {
+            merges_counts = true;
+            Arc::new(Sum::new(state_column(&name, "count"), name, data_type))
+        } else if any.is::<Min>() {
+            Arc::new(Min::new(state_column(&name, "min"), name, data_type))
+        } else if any.is::<Max>() {
+            Arc::new(Max::new(state_column(&name, "max"), name, data_type))
+        } else {
+            return Ok(None);
+        };
+        for field in expr.state_fields()? {
+            if left_schema.index_of(field.name()).is_ok() {
+                return Ok(None);
+            }
+        }
+        right_aggr_expr.push(expr.clone());
+        merged_aggr_expr.push(merged);
+    }
+
+    let right_groups = right_groups
+        .into_iter()
+        .map(|name| (col(&name), name))
+        .collect();
+    let right = push_down_aggregates(partial_aggregate(
+        aggregate,
+        right_groups,
+        right_aggr_expr,
+        join.right().clone(),
+    )?)?;
+    let left = push_down_aggregates(join.left().clone())?;
+    let join = join.with_new_children(vec![left, right])?;
+
+    let merged = partial_aggregate(
+        aggregate,
+        aggregate.group_expr().to_vec(),
+        merged_aggr_expr,
+        join,
+    )?;
+    if !merges_counts {
+        return Ok(Some(merged));
+    }
+
+    // the states of counts merged by `SUM` are named after the sum instead
+    // of the count
+    let expr = merged
+        .schema()
+        .fields()
+        .iter()
+        .zip(aggregate.schema().fields())
+        .map(|(merged, field)| (col(merged.name()), field.name().clone()))
+        .collect();
+    Ok(Some(Arc::new(ProjectionExec::try_new(expr, merged)?)))
+}
+
+fn state_column(name: &str, state_name: &str) -> Arc<dyn PhysicalExpr> {
+    col(&format_state_name(name, state_name))
+}
+
+/// A partial aggregation of `input` configured like `aggregate`
+fn partial_aggregate(
+    aggregate: &HashAggregateExec,
+    group_expr: Vec<(Arc<dyn PhysicalExpr>, String)>,
+    aggr_expr: Vec<Arc<dyn AggregateExpr>>,
+    input: Arc<dyn ExecutionPlan>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let input_schema = input.schema();
+    let mut partial = HashAggregateExec::try_new(
+        AggregateMode::Partial,
+        group_expr,
+        aggr_expr,
+        input,
+        input_schema,
+    )?
+    .with_num_shards(aggregate.num_shards());
+    if let Some(output_batch_size) = aggregate.output_batch_size() {
+        partial = partial.with_output_batch_size(output_batch_size);
+    }
+    Ok(Arc::new(partial))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::hash_join::PartitionMode;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::test::build_table_i32;
+    use arrow::datatypes::DataType;
+    use arrow::record_batch::RecordBatch;
+
+    fn memory_exec(batch: RecordBatch) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()]],
+            batch.schema(),
+            None,
+        )?))
+    }
+
+    fn aggregates() -> Vec<Arc<dyn AggregateExpr>> {
+        vec![
+            Arc::new(Sum::new(col("x"), "SUM(x)".to_string(), DataType::Int64)),
+            Arc::new(Count::new(
+                col("x"),
+                "COUNT(x)".to_string(),
+                DataType::UInt64,
+            )),
+            Arc::new(Min::new(col("x"), "MIN(x)".to_string(), DataType::Int32)),
+            Arc::new(Max::new(col("x"), "MAX(x)".to_string(), DataType::Int32)),
+        ]
+    }
+
+    /// Aggregates `input` grouped by `group` with the partial aggregation
+    /// optimized, returning the optimized partial aggregation and the result
+    async fn aggregate(
+        group: &str,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<(Arc<dyn ExecutionPlan>, Vec<RecordBatch>)> {
+        let input_schema = input.schema();
+        let partial = Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![(col(group), group.to_string())],
+            aggregates(),
+            input,
+            input_schema.clone(),
+        )?);
+        let schema = partial.schema();
+        let optimized =
+            AggregatePushDown::new().optimize(partial, &ExecutionConfig::new())?;
+        assert_eq!(optimized.schema(), schema);
+
+        let merged = Arc::new(MemoryExec::try_new(
+            &[collect(optimized.clone()).await?],
+            schema,
+            None,
+        )?);
+        let fin = Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Final,
+            vec![(col(group), group.to_string())],
+            aggregates(),
+            merged,
+            input_schema,
+        )?);
+        Ok((optimized, collect(fin).await?))
+    }
+
+    #[tokio::test]
+    async fn push_below_union() -> Result<()> {
+        let first = build_table_i32(
+            ("a", &vec![1, 2, 1]),
+            ("x", &vec![1, 2, 3]),
+            ("y", &vec![0; 3]),
+        );
+        let second =
+            build_table_i32(("a", &vec![2, 3]), ("x", &vec![4, 5]), ("y", &vec![0; 2]));
+        let union = Arc::new(UnionExec::try_new(vec![
+            memory_exec(first)?,
+            memory_exec(second)?,
+        ])?);
+
+        let (optimized, batches) = aggregate("a", union).await?;
+
+        let union = optimized.as_any().downcast_ref::<UnionExec>().unwrap();
+        assert_eq!(union.children().len(), 2);
+        for branch in union.children() {
+            assert!(branch.as_any().is::<HashAggregateExec>());
+        }
+        let expected = vec![
+            "+---+--------+----------+--------+--------+",
+            "| a | SUM(x) | COUNT(x) | MIN(x) | MAX(x) |",
+            "+---+--------+----------+--------+--------+",
+            "| 1 | 4      | 2        | 1      | 3      |",
+            "| 2 | 6      | 2        | 2      | 4      |",
+            "| 3 | 5      | 1        | 5      | 5      |",
+            "+---+--------+----------+--------+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn push_below_join() -> Result<()> {
+        let left = build_table_i32(
+            ("a", &vec![1, 2, 3]),
+            ("b", &vec![1, 1, 2]),
+            ("c", &vec![7, 8, 9]),
+        );
+        let right = build_table_i32(
+            ("b2", &vec![1, 1, 1, 2, 3]),
+            ("x", &vec![1, 2, 3, 4, 5]),
+            ("y", &vec![0; 5]),
+        );
+        let join = Arc::new(HashJoinExec::try_new(
+            memory_exec(left)?,
+            memory_exec(right)?,
+            &[("b".to_string(), "b2".to_string())],
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+        )?);
+
+        let (optimized, batches) = aggregate("a", join).await?;
+
+        // the states of the count are renamed above the merging aggregation
+        let projection = optimized.as_any().downcast_ref::<ProjectionExec>().unwrap();
+        let merged = projection
+            .input()
+            .as_any()
+            .downcast_ref::<HashAggregateExec>()
+            .unwrap();
+        let join = merged
+            .input()
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .unwrap();
+        let right = join
+            .right()
+            .as_any()
+            .downcast_ref::<HashAggregateExec>()
+            .unwrap();
+        let right_groups = right
+            .group_expr()
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(right_groups, vec!["b2"]);
+
+        let expected = vec![
+            "+---+--------+----------+--------+--------+",
+            "| a | SUM(x) | COUNT(x) | MIN(x) | MAX(x) |",
+            "+---+--------+----------+--------+--------+",
+            "| 1 | 6      | 3        | 1      | 3      |",
+            "| 2 | 6      | 3        | 1      | 3      |",
+            "| 3 | 4      | 1        | 4      | 4      |",
+            "+---+--------+----------+--------+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_aggregates_of_the_left_side() -> Result<()> {
+        let left = build_table_i32(
+            ("a", &vec![1, 2, 3]),
+            ("b", &vec![1, 1, 2]),
+            ("x", &vec![7, 8, 9]),
+        );
+        let right = build_table_i32(
+            ("b2", &vec![1, 1, 2]),
+            ("c", &vec![1, 2, 3]),
+            ("y", &vec![0; 3]),
+        );
+        let join = Arc::new(HashJoinExec::try_new(
+            memory_exec(left)?,
+            memory_exec(right)?,
+            &[("b".to_string(), "b2".to_string())],
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+        )?);
+
+        let (optimized, batches) = aggregate("a", join).await?;
+
+        let partial = optimized
+            .as_any()
+            .downcast_ref::<HashAggregateExec>()
+            .unwrap();
+        assert!(partial.input().as_any().is::<HashJoinExec>());
+        let expected = vec![
+            "+---+--------+----------+--------+--------+",
+            "| a | SUM(x) | COUNT(x) | MIN(x) | MAX(x) |",
+            "+---+--------+----------+--------+--------+",
+            "| 1 | 14     | 2        | 7      | 7      |",
+            "| 2 | 16     | 2        | 8      | 8      |",
+            "| 3 | 9      | 1        | 9      | 9      |",
+            "+---+--------+----------+--------+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+}
@@ -75,7 +75,10 @@ impl PhysicalOptimizerRule for CoalesceBatches {
                 // implemented. For now, we choose half the configured batch size to avoid copies
                 // when a small number of rows are removed from a batch
                 let target_batch_size = config.batch_size / 2;
-                Arc::new(CoalesceBatchesExec::new(plan.clone(), target_batch_size))
+                Arc::new(
+                    CoalesceBatchesExec::new(plan.clone(), target_batch_size)
+                        .with_policy(config.coalesce_policy),
+                )
             } else {
                 plan.clone()
             })
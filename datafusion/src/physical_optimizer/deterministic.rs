@@ -0,0 +1,186 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! DeterministicExecution makes the output of a plan independent of how its
+//! partitions are scheduled, if enabled by
+//! [`ExecutionConfig::with_deterministic`].
+use std::sync::Arc;
+
+use ahash::RandomState;
+
+use super::optimizer::PhysicalOptimizerRule;
+use crate::error::Result;
+use crate::execution::context::ExecutionConfig;
+use crate::physical_plan::cross_join::CrossJoinExec;
+use crate::physical_plan::hash_aggregate::HashAggregateExec;
+use crate::physical_plan::hash_join::{HashJoinExec, PartitionMode};
+use crate::physical_plan::interleave::InterleaveExec;
+use crate::physical_plan::merge::MergeExec;
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::ExecutionPlan;
+
+/// Rewrites a plan so that executing it twice over the same input produces the
+/// same batches in the same order:
+///
+/// * operators combining partitions emit them in partition order
+/// * hash aggregations use fixed seeds, so groups are emitted in the same order
+/// * the build side of joins collecting it into a single partition is merged
+///   in partition order
+///
+/// Does nothing unless [`ExecutionConfig::deterministic`] is set.
+pub struct DeterministicExecution {}
+
+impl DeterministicExecution {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for DeterministicExecution {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if config.deterministic {
+            make_deterministic(plan)
+        } else {
+            Ok(plan)
+        }
+    }
+
+    fn name(&self) -> &str {
+        "deterministic_execution"
+    }
+}
+
+fn make_deterministic(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let mut children = plan
+        .children()
+        .into_iter()
+        .map(make_deterministic)
+        .collect::<Result<Vec<_>>>()?;
+    if children.is_empty() {
+        // leaf node, children cannot be replaced
+        return Ok(plan);
+    }
+
+    let plan_any = plan.as_any();
+    if plan_any.downcast_ref::<MergeExec>().is_some() {
+        return Ok(Arc::new(
+            MergeExec::new(children.remove(0)).with_preserve_order(true),
+        ));
+    }
+    if let Some(repartition) = plan_any.downcast_ref::<RepartitionExec>() {
+        return Ok(Arc::new(
+            RepartitionExec::try_new(
+                children.remove(0),
+                repartition.partitioning().clone(),
+            )?
+            .with_preserve_order(true)
+            .with_hash_seeds(repartition.hash_seeds()),
+        ));
+    }
+    if plan_any.downcast_ref::<InterleaveExec>().is_some() {
+        return Ok(Arc::new(
+            InterleaveExec::try_new(children)?.with_preserve_order(true),
+        ));
+    }
+    if let Some(aggregate) = plan_any.downcast_ref::<HashAggregateExec>() {
+        let mut deterministic = HashAggregateExec::try_new(
+            *aggregate.mode(),
+            aggregate.group_expr().to_vec(),
+            aggregate.aggr_expr().to_vec(),
+            children.remove(0),
+            aggregate.input_schema(),
+        )?
+        .with_random_state(RandomState::with_seeds(0, 0, 0, 0))
+        .with_num_shards(aggregate.num_shards());
+        if let Some(output_batch_size) = aggregate.output_batch_size() {
+            deterministic = deterministic.with_output_batch_size(output_batch_size);
+        }
+        return Ok(Arc::new(deterministic));
+    }
+
+    // joins merging their left side internally do so in partition order if
+    // it is already a single partition
+    let collects_left = plan_any.downcast_ref::<CrossJoinExec>().is_some()
+        || plan_any
+            .downcast_ref::<HashJoinExec>()
+            .map(|join| *join.partition_mode() == PartitionMode::CollectLeft)
+            .unwrap_or(false);
+    if collects_left && children[0].output_partitioning().partition_count() > 1 {
+        children[0] =
+            Arc::new(MergeExec::new(children[0].clone()).with_preserve_order(true));
+    }
+
+    plan.with_new_children(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, Partitioning};
+    use arrow::array::UInt32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use arrow::util::pretty::pretty_format_batches;
+
+    #[tokio::test]
+    async fn repeated_execution_is_stable() -> Result<()> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, false)]));
+        let partitions = (0..4u32)
+            .map(|i| {
+                let values = (0..100).map(|v| v * 7 + i).collect::<Vec<u32>>();
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(UInt32Array::from(values))],
+                )?;
+                Ok(vec![batch; 3])
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let memory = Arc::new(MemoryExec::try_new(&partitions, schema, None)?);
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(RepartitionExec::try_new(
+            memory,
+            Partitioning::Hash(vec![col("a")], 3),
+        )?);
+
+        let config = ExecutionConfig::new().with_deterministic(true);
+        let plan = DeterministicExecution::new().optimize(plan, &config)?;
+        let repartition = plan
+            .as_any()
+            .downcast_ref::<RepartitionExec>()
+            .expect("repartition");
+        assert!(repartition.preserve_order());
+
+        let expected = collect_in_order(plan.clone()).await?;
+        for _ in 0..5 {
+            assert_eq!(collect_in_order(plan.clone()).await?, expected);
+        }
+        Ok(())
+    }
+
+    async fn collect_in_order(plan: Arc<dyn ExecutionPlan>) -> Result<String> {
+        let merged = MergeExec::new(plan).with_preserve_order(true);
+        let batches = collect(Arc::new(merged)).await?;
+        Ok(pretty_format_batches(&batches)?)
+    }
+}
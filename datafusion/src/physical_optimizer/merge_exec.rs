@@ -17,11 +17,16 @@
 
 //! AddMergeExec adds MergeExec to merge plans
 //! with more partitions into one partition when the node
-//! needs a single partition
+//! needs a single partition. The partitions merged into a
+//! GlobalLimitExec are limited by a LocalLimitExec each.
 use super::optimizer::PhysicalOptimizerRule;
 use crate::{
     error::Result,
-    physical_plan::{merge::MergeExec, Distribution},
+    physical_plan::{
+        limit::{GlobalLimitExec, LocalLimitExec},
+        merge::MergeExec,
+        Distribution, ExecutionPlan,
+    },
 };
 use std::sync::Arc;
 
@@ -60,7 +65,7 @@ impl PhysicalOptimizerRule for AddMergeExec {
                             if child.output_partitioning().partition_count() == 1 {
                                 child.clone()
                             } else {
-                                Arc::new(MergeExec::new(child.clone()))
+                                Arc::new(MergeExec::new(limit_partitions(&plan, child)))
                             }
                         })
                         .collect(),
@@ -73,3 +78,52 @@ impl PhysicalOptimizerRule for AddMergeExec {
         "add_merge_exec"
     }
 }
+
+/// Limits each partition of `child` to the limit of `plan`, if `plan` is a
+/// [`GlobalLimitExec`], so that partitions stop producing rows that are not
+/// needed as soon as possible
+fn limit_partitions(
+    plan: &Arc<dyn ExecutionPlan>,
+    child: &Arc<dyn ExecutionPlan>,
+) -> Arc<dyn ExecutionPlan> {
+    match plan.as_any().downcast_ref::<GlobalLimitExec>() {
+        Some(limit) if !child.as_any().is::<LocalLimitExec>() => {
+            Arc::new(LocalLimitExec::new(child.clone(), limit.limit()))
+        }
+        _ => child.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::context::ExecutionConfig;
+    use crate::physical_plan::{displayable, memory::MemoryExec};
+    use crate::test;
+
+    #[test]
+    fn limit_partitions_before_merge() -> Result<()> {
+        let batch = test::make_partition(5);
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch]],
+            schema,
+            None,
+        )?);
+        let plan = Arc::new(GlobalLimitExec::new(input, 3));
+
+        let optimized = AddMergeExec::new().optimize(plan, &ExecutionConfig::new())?;
+        let plan_string = displayable(optimized.as_ref()).indent().to_string();
+        let actual: Vec<&str> = plan_string.lines().collect();
+        assert_eq!(
+            actual,
+            vec![
+                "GlobalLimitExec: limit=3",
+                "  MergeExec",
+                "    LocalLimitExec: limit=3",
+                "      MemoryExec: partitions=2, partition_sizes=[1, 1]",
+            ]
+        );
+        Ok(())
+    }
+}
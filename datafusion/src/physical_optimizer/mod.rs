@@ -18,8 +18,12 @@
 //! This module contains a query optimizer that operates against a physical plan and applies
 //! rules to a physical plan, such as "Repartition".
 
+pub mod aggregate_push_down;
 pub mod coalesce_batches;
+pub mod deterministic;
 pub mod merge_exec;
 pub mod optimizer;
+pub mod pipeline_checker;
 pub mod pruning;
 pub mod repartition;
+pub mod runtime_filter;
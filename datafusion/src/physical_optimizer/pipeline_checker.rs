@@ -0,0 +1,275 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! PipelineChecker checks that every operator of a plan can execute over
+//! the (possibly unbounded) output of its children, and swaps the inputs of
+//! hash joins whose build side is unbounded where possible.
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionConfig;
+use crate::physical_plan::expressions::col;
+use crate::physical_plan::hash_join::HashJoinExec;
+use crate::physical_plan::hash_utils::JoinType;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::{displayable, ExecutionPlan};
+
+/// Checks plans for operators that can not execute over unbounded inputs,
+/// so that such plans fail during planning instead of hanging at runtime.
+///
+/// See [`ExecutionPlan::unbounded_output`].
+pub struct PipelineChecker {}
+
+impl PipelineChecker {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the rewritten plan and whether it produces unbounded output
+    fn check(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+    ) -> Result<(Arc<dyn ExecutionPlan>, bool)> {
+        if plan.children().is_empty() {
+            let unbounded = plan.unbounded_output(&[])?;
+            return Ok((plan, unbounded));
+        }
+
+        let (children, unbounded): (Vec<_>, Vec<_>) = plan
+            .children()
+            .into_iter()
+            .map(|child| self.check(child))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let plan = plan.with_new_children(children)?;
+
+        match plan.unbounded_output(&unbounded) {
+            Ok(unbounded) => Ok((plan, unbounded)),
+            Err(e) => {
+                if let Some(swapped) = swap_join_inputs(&plan, &unbounded)? {
+                    // the unbounded input is now probed, so the output is unbounded
+                    return Ok((swapped, true));
+                }
+                let reason = match e {
+                    DataFusionError::Plan(reason) => reason,
+                    other => return Err(other),
+                };
+                Err(DataFusionError::Plan(format!(
+                    "{} can not execute over unbounded inputs: {}",
+                    displayable(plan.as_ref()).one_line(),
+                    reason
+                )))
+            }
+        }
+    }
+}
+
+/// If `plan` is a hash join with an unbounded build side and a bounded probe
+/// side, returns an equivalent plan that builds the hash table from the
+/// probe side instead, followed by a projection restoring the column order.
+fn swap_join_inputs(
+    plan: &Arc<dyn ExecutionPlan>,
+    unbounded: &[bool],
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    let join = match plan.as_any().downcast_ref::<HashJoinExec>() {
+        Some(join) if unbounded[0] && !unbounded[1] => join,
+        _ => return Ok(None),
+    };
+    // a full join only produces the unmatched rows of its build side once the
+    // probe side ended, and semi and anti joins can not be swapped
+    if matches!(
+        join.join_type(),
+        JoinType::Full | JoinType::Semi | JoinType::Anti
+    ) {
+        return Ok(None);
+    }
+    let swapped = join.with_swapped_inputs()?;
+
+    let expr = join
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| (col(field.name()), field.name().clone()))
+        .collect::<Vec<_>>();
+    Ok(Some(Arc::new(ProjectionExec::try_new(
+        expr,
+        Arc::new(swapped),
+    )?)))
+}
+
+impl PhysicalOptimizerRule for PipelineChecker {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self.check(plan)?.0)
+    }
+
+    fn name(&self) -> &str {
+        "pipeline_checker"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::PhysicalSortExpr;
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::hash_join::PartitionMode;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::sort::SortExec;
+    use crate::physical_plan::{
+        expressions::lit, Partitioning, SendableRecordBatchStream,
+    };
+    use crate::scalar::ScalarValue;
+    use crate::test::build_table_i32;
+    use arrow::datatypes::SchemaRef;
+    use async_trait::async_trait;
+    use std::any::Any;
+
+    /// A leaf node that never stops producing batches
+    #[derive(Debug)]
+    struct UnboundedExec {
+        schema: SchemaRef,
+    }
+
+    #[async_trait]
+    impl ExecutionPlan for UnboundedExec {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.schema.clone()
+        }
+
+        fn output_partitioning(&self) -> Partitioning {
+            Partitioning::UnknownPartitioning(1)
+        }
+
+        fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+            vec![]
+        }
+
+        fn with_new_children(
+            &self,
+            _children: Vec<Arc<dyn ExecutionPlan>>,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            unimplemented!("UnboundedExec::with_new_children");
+        }
+
+        async fn execute(&self, _partition: usize) -> Result<SendableRecordBatchStream> {
+            unimplemented!("UnboundedExec::execute");
+        }
+
+        fn unbounded_output(&self, _children: &[bool]) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    fn unbounded(a: &str, b: &str, c: &str) -> Arc<dyn ExecutionPlan> {
+        let batch = build_table_i32((a, &vec![]), (b, &vec![]), (c, &vec![]));
+        Arc::new(UnboundedExec {
+            schema: batch.schema(),
+        })
+    }
+
+    fn bounded(a: &str, b: &str, c: &str) -> Arc<dyn ExecutionPlan> {
+        let batch = build_table_i32((a, &vec![1]), (b, &vec![2]), (c, &vec![3]));
+        let schema = batch.schema();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    fn check(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+        PipelineChecker::new().optimize(plan, &ExecutionConfig::new())
+    }
+
+    #[test]
+    fn streaming_operators_over_unbounded_input() -> Result<()> {
+        let plan = Arc::new(FilterExec::try_new(
+            lit(ScalarValue::Boolean(Some(true))),
+            unbounded("a", "b", "c"),
+        )?);
+        let checked = check(plan)?;
+        assert!(checked.as_any().downcast_ref::<FilterExec>().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn sort_over_unbounded_input() -> Result<()> {
+        let input = unbounded("a", "b", "c");
+        let sort_expr = PhysicalSortExpr {
+            expr: col("a"),
+            options: Default::default(),
+        };
+        let plan = Arc::new(SortExec::try_new(vec![sort_expr], input)?);
+
+        let err = check(plan).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("SortExec: [a ASC] can not execute over unbounded inputs"));
+        Ok(())
+    }
+
+    #[test]
+    fn swap_join_with_unbounded_build_side() -> Result<()> {
+        let on = vec![("b1".to_string(), "b2".to_string())];
+        let plan = Arc::new(HashJoinExec::try_new(
+            unbounded("a1", "b1", "c1"),
+            bounded("a2", "b2", "c2"),
+            &on,
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+        )?);
+        let schema = plan.schema();
+
+        let checked = check(plan)?;
+        assert_eq!(checked.schema(), schema);
+        let projection = checked
+            .as_any()
+            .downcast_ref::<ProjectionExec>()
+            .expect("projection");
+        let join = projection.children()[0].clone();
+        let join = join
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .expect("hash join");
+        assert_eq!(join.on(), &[("b2".to_string(), "b1".to_string())]);
+        assert!(join.left().as_any().downcast_ref::<MemoryExec>().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn full_join_with_unbounded_build_side() -> Result<()> {
+        let on = vec![("b1".to_string(), "b2".to_string())];
+        let plan = Arc::new(HashJoinExec::try_new(
+            unbounded("a1", "b1", "c1"),
+            bounded("a2", "b2", "c2"),
+            &on,
+            &JoinType::Full,
+            PartitionMode::CollectLeft,
+        )?);
+
+        let err = check(plan).unwrap_err();
+        assert!(err.to_string().contains("unbounded left input"));
+        Ok(())
+    }
+}
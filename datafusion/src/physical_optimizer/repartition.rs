@@ -20,7 +20,8 @@ use std::sync::Arc;
 
 use super::optimizer::PhysicalOptimizerRule;
 use crate::physical_plan::{
-    empty::EmptyExec, repartition::RepartitionExec, ExecutionPlan,
+    empty::EmptyExec, placeholder_row::PlaceholderRowExec, repartition::RepartitionExec,
+    ExecutionPlan,
 };
 use crate::physical_plan::{Distribution, Partitioning::*};
 use crate::{error::Result, execution::context::ExecutionConfig};
@@ -72,9 +73,9 @@ fn optimize_concurrency(
         Hash(_, _) => false,
     };
 
-    // TODO: EmptyExec causes failures with RepartitionExec
-    // But also not very useful to inlude
-    let is_empty_exec = plan.as_any().downcast_ref::<EmptyExec>().is_some();
+    // relations of at most one row are not worth repartitioning
+    let is_empty_exec =
+        plan.as_any().is::<EmptyExec>() || plan.as_any().is::<PlaceholderRowExec>();
 
     if perform_repartition && !requires_single_partition && !is_empty_exec {
         Ok(Arc::new(RepartitionExec::try_new(
@@ -0,0 +1,182 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! RuntimeFilterPushDown hands the probe filters of hash joins to the
+//! parquet scans of their right side, so that the scans skip the row groups
+//! without keys matching the build side.
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use crate::error::Result;
+use crate::execution::context::ExecutionConfig;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::expressions::Column;
+use crate::physical_plan::filter::FilterExec;
+use crate::physical_plan::hash_join::HashJoinExec;
+use crate::physical_plan::parquet::ParquetExec;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::runtime_filter::RuntimeFilter;
+use crate::physical_plan::ExecutionPlan;
+
+/// Adds the [`probe_filters`](HashJoinExec::probe_filters) of hash joins to
+/// the parquet scans their right keys are read from. The filters are only
+/// followed through operators that pass the key column on unchanged.
+///
+/// Does nothing unless parquet pruning is enabled, see
+/// [`ExecutionConfig::with_parquet_pruning`].
+pub struct RuntimeFilterPushDown {}
+
+impl RuntimeFilterPushDown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for RuntimeFilterPushDown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if config.parquet_pruning {
+            push_down_probe_filters(plan)
+        } else {
+            Ok(plan)
+        }
+    }
+
+    fn name(&self) -> &str {
+        "runtime_filter_push_down"
+    }
+}
+
+fn push_down_probe_filters(
+    plan: Arc<dyn ExecutionPlan>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if plan.children().is_empty() {
+        return Ok(plan);
+    }
+
+    let mut children = plan
+        .children()
+        .into_iter()
+        .map(push_down_probe_filters)
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+        for filter in join.probe_filters() {
+            children[1] = push_down_filter(children[1].clone(), filter)?;
+        }
+    }
+    plan.with_new_children(children)
+}
+
+/// Adds `filter` to the parquet scans below `plan` that its column is read
+/// from
+fn push_down_filter(
+    plan: Arc<dyn ExecutionPlan>,
+    filter: &Arc<RuntimeFilter>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let plan_any = plan.as_any();
+    if let Some(scan) = plan_any.downcast_ref::<ParquetExec>() {
+        if scan.schema().field_with_name(filter.column()).is_err() {
+            return Ok(plan);
+        }
+        return Ok(Arc::new(scan.clone().with_runtime_filter(filter.clone())));
+    }
+
+    let passes_column_on =
+        if let Some(projection) = plan_any.downcast_ref::<ProjectionExec>() {
+            projection.expr().iter().any(|(expr, name)| {
+                name == filter.column()
+                    && expr
+                        .as_any()
+                        .downcast_ref::<Column>()
+                        .map(|column| column.name() == filter.column())
+                        .unwrap_or(false)
+            })
+        } else {
+            plan_any.downcast_ref::<FilterExec>().is_some()
+                || plan_any.downcast_ref::<CoalesceBatchesExec>().is_some()
+                || plan_any.downcast_ref::<RepartitionExec>().is_some()
+        };
+    if !passes_column_on {
+        return Ok(plan);
+    }
+
+    let children = plan
+        .children()
+        .into_iter()
+        .map(|child| push_down_filter(child, filter))
+        .collect::<Result<Vec<_>>>()?;
+    plan.with_new_children(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::lit;
+    use crate::physical_plan::hash_join::PartitionMode;
+    use crate::physical_plan::hash_utils::JoinType;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::scalar::ScalarValue;
+    use crate::test::build_table_i32;
+
+    #[test]
+    fn filters_reach_parquet_scans_of_the_right_side() -> Result<()> {
+        let testdata = arrow::util::test_util::parquet_test_data();
+        let filename = format!("{}/alltypes_plain.parquet", testdata);
+        let scan = ParquetExec::try_from_path(&filename, None, None, 1024, 1, None)?;
+        let right = Arc::new(FilterExec::try_new(
+            lit(ScalarValue::Boolean(Some(true))),
+            Arc::new(scan),
+        )?);
+
+        let batch =
+            build_table_i32(("a", &vec![1, 2]), ("b", &vec![3, 4]), ("c", &vec![5, 6]));
+        let left = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()]],
+            batch.schema(),
+            None,
+        )?);
+        let join = HashJoinExec::try_new(
+            left,
+            right,
+            &[("b".to_string(), "id".to_string())],
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+        )?;
+        let filter = join.probe_filters()[0].clone();
+
+        let optimized = RuntimeFilterPushDown::new()
+            .optimize(Arc::new(join), &ExecutionConfig::new())?;
+
+        let join = optimized.as_any().downcast_ref::<HashJoinExec>().unwrap();
+        assert!(Arc::ptr_eq(&join.probe_filters()[0], &filter));
+        let filter_exec = join.right().as_any().downcast_ref::<FilterExec>().unwrap();
+        let scan = filter_exec
+            .input()
+            .as_any()
+            .downcast_ref::<ParquetExec>()
+            .unwrap();
+        assert_eq!(scan.runtime_filters().len(), 1);
+        assert!(Arc::ptr_eq(&scan.runtime_filters()[0], &filter));
+
+        Ok(())
+    }
+}
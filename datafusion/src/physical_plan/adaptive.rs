@@ -0,0 +1,321 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Experimental hooks to re-plan the remainder of a running plan based on the
+//! cardinalities operators observe while executing it.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use log::debug;
+
+use super::expressions::col;
+use super::hash_join::HashJoinExec;
+use super::hash_utils::JoinType;
+use super::memory::MemoryExec;
+use super::projection::ProjectionExec;
+use super::ExecutionPlan;
+use crate::error::Result;
+
+/// Re-plans the part of a plan that did not execute yet, based on the
+/// cardinalities observed by its operators so far. This is experimental.
+///
+/// See [`ExecutionConfig::with_replan_hook`](crate::execution::context::ExecutionConfig::with_replan_hook)
+pub trait ReplanHook: Debug + Send + Sync {
+    /// Called once `join` collected `build_side`, before any partition of its
+    /// right (probe) side is executed. The number of rows of `build_side` is
+    /// the observed cardinality of the left side.
+    ///
+    /// Returns the plan that produces the output of `join` instead, which
+    /// must have the same schema and number of output partitions, or `None`
+    /// to continue executing `join`. The replacement may read `build_side`
+    /// instead of executing the left side again.
+    ///
+    /// Only joins with
+    /// [`PartitionMode::CollectLeft`](super::hash_join::PartitionMode::CollectLeft)
+    /// consult the hook, as their build side is shared by all output
+    /// partitions.
+    fn replan_join(
+        &self,
+        join: &HashJoinExec,
+        build_side: &RecordBatch,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>>;
+}
+
+/// A [`ReplanHook`] flipping the build and probe sides of inner joins whose
+/// build side turned out to have more than `max_build_rows` rows, see
+/// [`swap_join_sides`]. The right side is expected to be the smaller one
+/// then, e.g. because the estimates the join order was chosen by were off.
+#[derive(Debug)]
+pub struct SwapJoinSides {
+    max_build_rows: usize,
+}
+
+impl SwapJoinSides {
+    /// Create a hook swapping the sides of joins building more than
+    /// `max_build_rows` rows
+    pub fn new(max_build_rows: usize) -> Self {
+        Self { max_build_rows }
+    }
+}
+
+impl ReplanHook for SwapJoinSides {
+    fn replan_join(
+        &self,
+        join: &HashJoinExec,
+        build_side: &RecordBatch,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        if build_side.num_rows() <= self.max_build_rows
+            || *join.join_type() != JoinType::Inner
+        {
+            return Ok(None);
+        }
+        // swapping does not help if the right side is known to be at least as
+        // large, e.g. because it holds the former build side of a join that
+        // was swapped before
+        if let Some(right) = join.right().as_any().downcast_ref::<MemoryExec>() {
+            let right_rows = right
+                .partitions()
+                .iter()
+                .flatten()
+                .map(|batch| batch.num_rows())
+                .sum::<usize>();
+            if right_rows >= build_side.num_rows() {
+                return Ok(None);
+            }
+        }
+        // the columns of the swapped join are put back in order by name
+        let schema = join.schema();
+        let mut names = HashSet::new();
+        if !schema
+            .fields()
+            .iter()
+            .all(|field| names.insert(field.name()))
+        {
+            return Ok(None);
+        }
+
+        debug!(
+            "Swapping the sides of a hash join on {:?} with a build side of {} rows",
+            join.on(),
+            build_side.num_rows()
+        );
+        swap_join_sides(join, build_side).map(Some)
+    }
+}
+
+/// Returns a plan producing the output of the inner `join`, whose left side
+/// was already collected into `build_side`, that builds the right side
+/// instead and probes it with the rows of `build_side`. The rows of
+/// `build_side` are split into as many partitions as the right side has.
+pub fn swap_join_sides(
+    join: &HashJoinExec,
+    build_side: &RecordBatch,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let num_partitions = join.right().output_partitioning().partition_count().max(1);
+    let num_rows = build_side.num_rows();
+    let chunk_size = (num_rows + num_partitions - 1) / num_partitions;
+    let partitions = (0..num_partitions)
+        .map(|i| {
+            let offset = (i * chunk_size).min(num_rows);
+            let length = chunk_size.min(num_rows - offset);
+            if length == 0 {
+                return Ok(vec![]);
+            }
+            let columns = build_side
+                .columns()
+                .iter()
+                .map(|column| column.slice(offset, length))
+                .collect();
+            Ok(vec![RecordBatch::try_new(build_side.schema(), columns)?])
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let probe = Arc::new(MemoryExec::try_new(&partitions, build_side.schema(), None)?);
+
+    // the swapped join keeps the settings of `join`, and probes the rows of
+    // `build_side` instead of executing the left side again
+    let swapped = join
+        .with_swapped_inputs()?
+        .with_new_children(vec![join.right().clone(), probe])?;
+
+    let expr = join
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| (col(field.name()), field.name().clone()))
+        .collect();
+    Ok(Arc::new(ProjectionExec::try_new(expr, swapped)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::hash_join::PartitionMode;
+    use crate::test::build_table_i32;
+    use std::sync::Mutex;
+
+    /// Records the build side rows it observed and delegates to `inner`
+    #[derive(Debug)]
+    struct RecordingHook {
+        inner: Option<SwapJoinSides>,
+        observed: Mutex<Vec<usize>>,
+    }
+
+    impl ReplanHook for RecordingHook {
+        fn replan_join(
+            &self,
+            join: &HashJoinExec,
+            build_side: &RecordBatch,
+        ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+            self.observed.lock().unwrap().push(build_side.num_rows());
+            match &self.inner {
+                Some(inner) => inner.replan_join(join, build_side),
+                None => Ok(None),
+            }
+        }
+    }
+
+    fn join(hook: Arc<RecordingHook>) -> Result<HashJoinExec> {
+        let left = build_table_i32(
+            ("a1", &vec![1, 2, 3, 4]),
+            ("b1", &vec![4, 5, 5, 7]),
+            ("c1", &vec![7, 8, 9, 10]),
+        );
+        let right_1 =
+            build_table_i32(("a2", &vec![10]), ("b1", &vec![4]), ("c2", &vec![70]));
+        let right_2 = build_table_i32(
+            ("a2", &vec![20, 30]),
+            ("b1", &vec![5, 6]),
+            ("c2", &vec![80, 90]),
+        );
+        let left = Arc::new(MemoryExec::try_new(
+            &[vec![left.clone()]],
+            left.schema(),
+            None,
+        )?);
+        let right = Arc::new(MemoryExec::try_new(
+            &[vec![right_1.clone()], vec![right_2]],
+            right_1.schema(),
+            None,
+        )?);
+        Ok(HashJoinExec::try_new(
+            left,
+            right,
+            &[("b1".to_string(), "b1".to_string())],
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+        )?
+        .with_output_batch_size(2)
+        .with_replan_hook(hook))
+    }
+
+    fn expected() -> Vec<&'static str> {
+        vec![
+            "+----+----+----+----+----+",
+            "| a1 | b1 | c1 | a2 | c2 |",
+            "+----+----+----+----+----+",
+            "| 1  | 4  | 7  | 10 | 70 |",
+            "| 2  | 5  | 8  | 20 | 80 |",
+            "| 3  | 5  | 9  | 20 | 80 |",
+            "+----+----+----+----+----+",
+        ]
+    }
+
+    #[tokio::test]
+    async fn hook_observes_build_side_once() -> Result<()> {
+        let hook = Arc::new(RecordingHook {
+            inner: None,
+            observed: Mutex::new(vec![]),
+        });
+        let join = Arc::new(join(hook.clone())?);
+
+        let batches = collect(join.clone()).await?;
+        assert_batches_sorted_eq!(expected(), &batches);
+        assert_eq!(*hook.observed.lock().unwrap(), vec![4]);
+
+        // the build side is observed again once it is built again
+        join.reset();
+        collect(join).await?;
+        assert_eq!(*hook.observed.lock().unwrap(), vec![4, 4]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn swap_sides_of_large_build_sides() -> Result<()> {
+        let hook = Arc::new(RecordingHook {
+            inner: Some(SwapJoinSides::new(3)),
+            observed: Mutex::new(vec![]),
+        });
+        let join = join(hook.clone())?;
+        let swapped = swap_join_sides(
+            &join,
+            &build_table_i32(("a1", &vec![1]), ("b1", &vec![4]), ("c1", &vec![7])),
+        )?;
+        assert_eq!(swapped.schema(), join.schema());
+        assert_eq!(swapped.output_partitioning().partition_count(), 2);
+
+        // the swapped join keeps the settings of the join
+        let swapped_join = swapped.children()[0].clone();
+        let swapped_join = swapped_join
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .unwrap();
+        assert_eq!(swapped_join.output_batch_size(), Some(2));
+        assert!(swapped_join.replan_hook().is_some());
+
+        // the swapped join observes its build side, the right side of the
+        // join, but keeps it
+        let batches = collect(Arc::new(join)).await?;
+        assert_batches_sorted_eq!(expected(), &batches);
+        assert_eq!(*hook.observed.lock().unwrap(), vec![4, 3]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_small_build_sides() -> Result<()> {
+        let join = join(Arc::new(RecordingHook {
+            inner: None,
+            observed: Mutex::new(vec![]),
+        }))?;
+        let build_side =
+            build_table_i32(("a1", &vec![1]), ("b1", &vec![4]), ("c1", &vec![7]));
+        let replanned = SwapJoinSides::new(1).replan_join(&join, &build_side)?;
+        assert!(replanned.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_build_sides_smaller_than_right_side() -> Result<()> {
+        let join = join(Arc::new(RecordingHook {
+            inner: None,
+            observed: Mutex::new(vec![]),
+        }))?;
+        // the right side has 3 rows
+        let build_side = build_table_i32(
+            ("a1", &vec![1, 2]),
+            ("b1", &vec![4, 5]),
+            ("c1", &vec![7, 8]),
+        );
+        let replanned = SwapJoinSides::new(1).replan_join(&join, &build_side)?;
+        assert!(replanned.is_none());
+        Ok(())
+    }
+}
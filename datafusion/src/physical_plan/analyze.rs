@@ -0,0 +1,251 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Defines the ANALYZE operator
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::{array::StringBuilder, datatypes::SchemaRef, record_batch::RecordBatch};
+use async_trait::async_trait;
+use futures::StreamExt;
+use instant::Instant;
+
+use super::{
+    common::SizedRecordBatchStream, display::DisplayableExecutionPlan,
+    execute_stream_partitioned, DisplayFormatType, ExecutionPlan, MetricsSet,
+    Partitioning, SQLMetric, SendableRecordBatchStream,
+};
+use crate::error::{DataFusionError, Result};
+
+/// Analyze execution plan operator. This operator executes its input to
+/// completion, discarding its output, and then produces a single batch
+/// with the text of the input plan annotated with the metrics each of its
+/// operators collected, in the same format as [`ExplainExec`](super::explain::ExplainExec).
+#[derive(Debug)]
+pub struct AnalyzeExec {
+    /// Should the total number of rows and the duration be included?
+    verbose: bool,
+    /// The plan being analyzed
+    input: Arc<dyn ExecutionPlan>,
+    /// The schema that this exec plan node outputs
+    schema: SchemaRef,
+    /// Number of rows produced by the input
+    output_rows: Arc<SQLMetric>,
+    /// Wall clock time spent executing the input, in nanoseconds
+    elapsed: Arc<SQLMetric>,
+}
+
+impl AnalyzeExec {
+    /// Create a new AnalyzeExec
+    pub fn new(verbose: bool, input: Arc<dyn ExecutionPlan>, schema: SchemaRef) -> Self {
+        Self {
+            verbose,
+            input,
+            schema,
+            output_rows: SQLMetric::counter(),
+            elapsed: SQLMetric::time_nanos(),
+        }
+    }
+
+    /// Should the total number of rows and the duration be included?
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// The plan being analyzed
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for AnalyzeExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(AnalyzeExec::new(
+                self.verbose,
+                children[0].clone(),
+                self.schema.clone(),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "AnalyzeExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "AnalyzeExec invalid partition {}",
+                partition
+            )));
+        }
+
+        // run all partitions of the input to completion, so that the
+        // metrics of all operators are complete
+        let start = Instant::now();
+        let streams = execute_stream_partitioned(self.input.clone()).await?;
+        let partition_rows = futures::future::try_join_all(streams.into_iter().map(
+            |mut stream| async move {
+                let mut rows = 0;
+                while let Some(batch) = stream.next().await {
+                    rows += batch?.num_rows();
+                }
+                Ok::<_, DataFusionError>(rows)
+            },
+        ))
+        .await?;
+        self.elapsed.add_elapsed(start);
+        self.output_rows.add(partition_rows.iter().sum());
+
+        let mut type_builder = StringBuilder::new(3);
+        let mut plan_builder = StringBuilder::new(3);
+
+        type_builder.append_value("Plan with Metrics")?;
+        plan_builder.append_value(
+            &DisplayableExecutionPlan::with_metrics(self.input.as_ref())
+                .indent()
+                .to_string(),
+        )?;
+
+        if self.verbose {
+            type_builder.append_value("Output Rows")?;
+            plan_builder.append_value(&self.output_rows.value().to_string())?;
+
+            type_builder.append_value("Duration")?;
+            plan_builder.append_value(&format!(
+                "{:?}",
+                std::time::Duration::from_nanos(self.elapsed.value() as u64)
+            ))?;
+        }
+
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(type_builder.finish()),
+                Arc::new(plan_builder.finish()),
+            ],
+        )?;
+
+        Ok(Box::pin(SizedRecordBatchStream::new(
+            self.schema.clone(),
+            vec![Arc::new(record_batch)],
+        )))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "AnalyzeExec: verbose={}", self.verbose)
+            }
+        }
+    }
+
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.output_rows);
+        metrics.push("duration", &self.elapsed);
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::LogicalPlan;
+    use crate::physical_plan::expressions::{col, PhysicalSortExpr};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::sort::SortExec;
+    use crate::physical_plan::{collect, merge::MergeExec};
+    use crate::test;
+    use arrow::array::StringArray;
+
+    #[tokio::test]
+    async fn analyze_sort() -> Result<()> {
+        let batch = test::make_partition(3);
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch]],
+            schema,
+            None,
+        )?);
+        let expr = vec![PhysicalSortExpr {
+            expr: col("i"),
+            options: Default::default(),
+        }];
+        let sort = Arc::new(SortExec::try_new(expr, Arc::new(MergeExec::new(input)))?);
+        let analyze =
+            Arc::new(AnalyzeExec::new(true, sort, LogicalPlan::explain_schema()));
+
+        let batches = collect(analyze.clone()).await?;
+        assert_eq!(batches.len(), 1);
+        let plan_types = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let plans = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(plan_types.value(0), "Plan with Metrics");
+        assert!(
+            plans
+                .value(0)
+                .starts_with("SortExec: [i ASC], metrics=[elapsed_compute="),
+            "{}",
+            plans.value(0)
+        );
+        assert!(
+            plans.value(0).contains("output_rows=6"),
+            "{}",
+            plans.value(0)
+        );
+        assert_eq!(plan_types.value(1), "Output Rows");
+        assert_eq!(plans.value(1), "6");
+        assert_eq!(plan_types.value(2), "Duration");
+
+        assert_eq!(analyze.metrics().output_rows(), Some(6));
+        Ok(())
+    }
+}
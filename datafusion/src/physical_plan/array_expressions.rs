@@ -18,8 +18,12 @@
 //! Array expressions
 
 use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
 use arrow::array::*;
-use arrow::datatypes::DataType;
+use arrow::buffer::Buffer;
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field};
+use std::ops::Range;
 use std::sync::Arc;
 
 use super::ColumnarValue;
@@ -125,3 +129,326 @@ pub static SUPPORTED_ARRAY_TYPES: &[DataType] = &[
     DataType::Utf8,
     DataType::LargeUtf8,
 ];
+
+/// Downcasts the list argument of the function `name`
+pub(crate) fn list_arg<'a, O: OffsetSizeTrait>(
+    array: &'a ArrayRef,
+    name: &str,
+) -> Result<&'a GenericListArray<O>> {
+    array
+        .as_any()
+        .downcast_ref::<GenericListArray<O>>()
+        .ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "Invalid data type {:?} for function {}",
+                array.data_type(),
+                name
+            ))
+        })
+}
+
+/// Returns the range of the values of the list at `index`
+pub(crate) fn list_range<O: OffsetSizeTrait>(
+    list: &GenericListArray<O>,
+    index: usize,
+) -> Range<usize> {
+    let offsets = list.value_offsets();
+    offsets[index].to_usize().unwrap()..offsets[index + 1].to_usize().unwrap()
+}
+
+pub(crate) fn list_offset<O: OffsetSizeTrait>(length: usize, name: &str) -> Result<O> {
+    O::from_usize(length).ok_or_else(|| {
+        DataFusionError::Execution(format!("{} produced a list that is too large", name))
+    })
+}
+
+/// Builds a list array of `data_type` from its `values` and the offsets and
+/// validity of its rows
+pub(crate) fn build_list<O: OffsetSizeTrait>(
+    data_type: DataType,
+    values: ArrayData,
+    offsets: Vec<O>,
+    mut validity: BooleanBufferBuilder,
+) -> ArrayRef {
+    let data = ArrayData::builder(data_type)
+        .len(offsets.len() - 1)
+        .add_buffer(Buffer::from_slice_ref(&offsets))
+        .add_child_data(values)
+        .null_bit_buffer(validity.finish())
+        .build();
+    // `make_array` of this module is the SQL function
+    arrow::array::make_array(data)
+}
+
+/// Returns for each row the 1-based position of the first item of the list
+/// `args[0]` equal to the element `args[1]`, `None` if either is null
+fn element_positions<O: OffsetSizeTrait>(
+    args: &[ArrayRef],
+    name: &str,
+) -> Result<Vec<Option<Option<usize>>>> {
+    let list = list_arg::<O>(&args[0], name)?;
+    let elements = cast(&args[1], &list.value_type())?;
+    let values = list.values();
+    (0..list.len())
+        .map(|i| {
+            if list.is_null(i) || elements.is_null(i) {
+                return Ok(None);
+            }
+            let element = ScalarValue::try_from_array(&elements, i)?;
+            for (position, j) in list_range(list, i).enumerate() {
+                if values.is_valid(j)
+                    && ScalarValue::try_from_array(&values, j)? == element
+                {
+                    return Ok(Some(Some(position + 1)));
+                }
+            }
+            Ok(Some(None))
+        })
+        .collect()
+}
+
+/// Builds a list of the arguments.
+/// make_array(1, 2, 3) = [1, 2, 3]
+pub fn make_array(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let name = "make_array";
+    let rows = args[0].len();
+    let mut mutable = MutableArrayData::new(
+        args.iter().map(|arg| arg.data()).collect(),
+        true,
+        rows * args.len(),
+    );
+    let mut offsets = vec![list_offset::<i32>(0, name)?];
+    let mut validity = BooleanBufferBuilder::new(rows);
+    for i in 0..rows {
+        for index in 0..args.len() {
+            mutable.extend(index, i, i + 1);
+        }
+        validity.append(true);
+        offsets.push(list_offset((i + 1) * args.len(), name)?);
+    }
+    let data_type = DataType::List(Box::new(Field::new(
+        "item",
+        args[0].data_type().clone(),
+        true,
+    )));
+    Ok(build_list(data_type, mutable.freeze(), offsets, validity))
+}
+
+/// Number of items of a list.
+/// array_length([1, 2, 3]) = 3
+pub fn array_length<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let list = list_arg::<O>(&args[0], "array_length")?;
+    let result = (0..list.len())
+        .map(|i| {
+            if list.is_null(i) {
+                None
+            } else {
+                Some(list_range(list, i).len() as i64)
+            }
+        })
+        .collect::<Int64Array>();
+    Ok(Arc::new(result))
+}
+
+/// Whether a list contains an element.
+/// array_contains([1, 2, 3], 2) = true
+pub fn array_contains<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let result = element_positions::<O>(args, "array_contains")?
+        .into_iter()
+        .map(|position| position.map(|p| p.is_some()))
+        .collect::<BooleanArray>();
+    Ok(Arc::new(result))
+}
+
+/// 1-based position of the first occurrence of an element in a list, null if
+/// the list does not contain it.
+/// array_position([1, 2, 3], 2) = 2
+pub fn array_position<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let result = element_positions::<O>(args, "array_position")?
+        .into_iter()
+        .map(|position| position.flatten().map(|p| p as i64))
+        .collect::<Int64Array>();
+    Ok(Arc::new(result))
+}
+
+/// Appends an element to the end of a list.
+/// array_append([1, 2], 3) = [1, 2, 3]
+pub fn array_append<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let name = "array_append";
+    let list = list_arg::<O>(&args[0], name)?;
+    let elements = cast(&args[1], &list.value_type())?;
+    let values = list.values();
+
+    let mut mutable = MutableArrayData::new(
+        vec![values.data(), elements.data()],
+        true,
+        values.len() + elements.len(),
+    );
+    let mut offsets = vec![list_offset::<O>(0, name)?];
+    let mut validity = BooleanBufferBuilder::new(list.len());
+    let mut length = 0;
+    for i in 0..list.len() {
+        if list.is_valid(i) {
+            let range = list_range(list, i);
+            length += range.len() + 1;
+            mutable.extend(0, range.start, range.end);
+            mutable.extend(1, i, i + 1);
+        }
+        validity.append(list.is_valid(i));
+        offsets.push(list_offset(length, name)?);
+    }
+    Ok(build_list(
+        list.data_type().clone(),
+        mutable.freeze(),
+        offsets,
+        validity,
+    ))
+}
+
+/// Concatenates lists, skipping null lists.
+/// array_concat([1, 2], [3]) = [1, 2, 3]
+pub fn array_concat<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let name = "array_concat";
+    let lists = args
+        .iter()
+        .map(|arg| list_arg::<O>(arg, name))
+        .collect::<Result<Vec<_>>>()?;
+    let values = lists.iter().map(|list| list.values()).collect::<Vec<_>>();
+    let capacity = values.iter().map(|v| v.len()).sum();
+
+    let mut mutable =
+        MutableArrayData::new(values.iter().map(|v| v.data()).collect(), true, capacity);
+    let mut offsets = vec![list_offset::<O>(0, name)?];
+    let mut validity = BooleanBufferBuilder::new(lists[0].len());
+    let mut length = 0;
+    for i in 0..lists[0].len() {
+        let mut valid = false;
+        for (index, list) in lists.iter().enumerate() {
+            if list.is_valid(i) {
+                let range = list_range(list, i);
+                length += range.len();
+                mutable.extend(index, range.start, range.end);
+                valid = true;
+            }
+        }
+        validity.append(valid);
+        offsets.push(list_offset(length, name)?);
+    }
+    Ok(build_list(
+        lists[0].data_type().clone(),
+        mutable.freeze(),
+        offsets,
+        validity,
+    ))
+}
+
+/// The items of a list from the 1-based position `args[1]` to `args[2]`,
+/// inclusive. Positions outside of the list are ignored.
+/// array_slice([1, 2, 3, 4], 2, 3) = [2, 3]
+pub fn array_slice<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let name = "array_slice";
+    let list = list_arg::<O>(&args[0], name)?;
+    let from = cast(&args[1], &DataType::Int64)?;
+    let from = from.as_any().downcast_ref::<Int64Array>().unwrap();
+    let to = cast(&args[2], &DataType::Int64)?;
+    let to = to.as_any().downcast_ref::<Int64Array>().unwrap();
+    let values = list.values();
+
+    let mut mutable = MutableArrayData::new(vec![values.data()], true, values.len());
+    let mut offsets = vec![list_offset::<O>(0, name)?];
+    let mut validity = BooleanBufferBuilder::new(list.len());
+    let mut length = 0;
+    for i in 0..list.len() {
+        let valid = list.is_valid(i) && from.is_valid(i) && to.is_valid(i);
+        if valid {
+            let range = list_range(list, i);
+            let start = from.value(i).max(1) as usize - 1;
+            let end = to.value(i).min(range.len() as i64);
+            if end > start as i64 {
+                let end = end as usize;
+                length += end - start;
+                mutable.extend(0, range.start + start, range.start + end);
+            }
+        }
+        validity.append(valid);
+        offsets.push(list_offset(length, name)?);
+    }
+    Ok(build_list(
+        list.data_type().clone(),
+        mutable.freeze(),
+        offsets,
+        validity,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::Int32Type;
+
+    fn list(values: Vec<Option<Vec<Option<i32>>>>) -> ArrayRef {
+        Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(values))
+    }
+
+    #[test]
+    fn test_list_properties() -> Result<()> {
+        let lists = list(vec![
+            Some(vec![Some(1), Some(2), Some(3)]),
+            Some(vec![Some(4), None]),
+            None,
+            Some(vec![]),
+        ]);
+        let elements: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(3), Some(5), Some(1), Some(1)]));
+
+        let result = array_length::<i32>(&[lists.clone()])?;
+        let expected: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(3), Some(2), None, Some(0)]));
+        assert_eq!(result.data(), expected.data());
+
+        let result = array_contains::<i32>(&[lists.clone(), elements.clone()])?;
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            None,
+            Some(false),
+        ]));
+        assert_eq!(result.data(), expected.data());
+
+        let result = array_position::<i32>(&[lists, elements])?;
+        let expected: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(3), None, None, None]));
+        assert_eq!(result.data(), expected.data());
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_construction() -> Result<()> {
+        let lists = list(vec![Some(vec![Some(1), Some(2)]), None, Some(vec![])]);
+        let elements: ArrayRef = Arc::new(Int32Array::from(vec![Some(3), Some(4), None]));
+
+        let result = array_append::<i32>(&[lists.clone(), elements])?;
+        let expected = list(vec![
+            Some(vec![Some(1), Some(2), Some(3)]),
+            None,
+            Some(vec![None]),
+        ]);
+        assert_eq!(result.data(), expected.data());
+
+        let others = list(vec![Some(vec![Some(5)]), Some(vec![Some(6)]), None]);
+        let result = array_concat::<i32>(&[lists.clone(), others])?;
+        let expected = list(vec![
+            Some(vec![Some(1), Some(2), Some(5)]),
+            Some(vec![Some(6)]),
+            Some(vec![]),
+        ]);
+        assert_eq!(result.data(), expected.data());
+
+        let from: ArrayRef = Arc::new(Int64Array::from(vec![2, 1, 1]));
+        let to: ArrayRef = Arc::new(Int64Array::from(vec![5, 1, 1]));
+        let result = array_slice::<i32>(&[lists, from, to])?;
+        let expected = list(vec![Some(vec![Some(2)]), None, Some(vec![])]);
+        assert_eq!(result.data(), expected.data());
+        Ok(())
+    }
+}
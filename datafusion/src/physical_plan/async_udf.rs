@@ -0,0 +1,426 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Support for scalar UDFs whose implementation is asynchronous, e.g. because
+//! it calls out to an external service or runs model inference.
+//!
+//! Calls of async UDFs are not evaluated by [`ScalarFunctionExpr`]: the
+//! physical planner moves them into an [`AsyncFuncExec`] below the projection
+//! or filter using them, which evaluates them in batches of a configurable
+//! size with a configurable number of concurrent invocations.
+//!
+//! [`ScalarFunctionExpr`]: super::functions::ScalarFunctionExpr
+
+use std::any::Any;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use arrow::array::{new_null_array, ArrayRef, NullArray};
+use arrow::compute::concat;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+
+use super::functions::{ReturnTypeFunction, ScalarFunctionImplementation, Signature};
+use super::udf::ScalarUDF;
+use super::{
+    ColumnarValue, DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use crate::error::{DataFusionError, Result};
+
+/// Asynchronous implementation of a scalar function. It is called with the
+/// arguments of a batch of rows, which are either arrays of the batch's
+/// length or scalars, and returns a future of the result for these rows.
+pub type AsyncScalarFunctionImplementation = Arc<
+    dyn Fn(Vec<ColumnarValue>) -> BoxFuture<'static, Result<ColumnarValue>> + Send + Sync,
+>;
+
+/// Logical representation of an asynchronous scalar UDF.
+///
+/// It is registered as a [`ScalarUDF`] (see [`AsyncScalarUDF::into_scalar_udf`])
+/// and can be used wherever a projection or filter expression is allowed.
+#[derive(Clone)]
+pub struct AsyncScalarUDF {
+    /// name
+    pub name: String,
+    /// signature
+    pub signature: Signature,
+    /// Return type
+    pub return_type: ReturnTypeFunction,
+    /// actual implementation
+    ///
+    /// Zero argument functions are passed a single null array indicating the
+    /// number of rows, like synchronous UDFs.
+    pub fun: AsyncScalarFunctionImplementation,
+    /// The maximum number of rows passed to a single invocation of `fun`, or
+    /// `None` to pass all rows of a record batch at once
+    pub batch_size: Option<usize>,
+    /// The maximum number of invocations of `fun` running at the same time
+    pub max_concurrency: usize,
+}
+
+impl fmt::Debug for AsyncScalarUDF {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncScalarUDF")
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .field("batch_size", &self.batch_size)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("fun", &"<FUNC>")
+            .finish()
+    }
+}
+
+impl AsyncScalarUDF {
+    /// Create a new AsyncScalarUDF, which is invoked once per record batch,
+    /// one invocation at a time
+    pub fn new(
+        name: &str,
+        signature: &Signature,
+        return_type: &ReturnTypeFunction,
+        fun: &AsyncScalarFunctionImplementation,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            signature: signature.clone(),
+            return_type: return_type.clone(),
+            fun: fun.clone(),
+            batch_size: None,
+            max_concurrency: 1,
+        }
+    }
+
+    /// Returns this UDF invoked with at most `batch_size` rows at a time
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size.max(1));
+        self
+    }
+
+    /// Returns this UDF with up to `max_concurrency` invocations running at
+    /// the same time
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Returns the [`ScalarUDF`] calling this function, to be used in logical
+    /// plans and registered with an execution context
+    pub fn into_scalar_udf(self) -> ScalarUDF {
+        let name = self.name.clone();
+        let fun: ScalarFunctionImplementation = Arc::new(move |_| {
+            Err(DataFusionError::Internal(format!(
+                "Async UDF '{}' must be evaluated by an AsyncFuncExec",
+                name
+            )))
+        });
+        let mut udf =
+            ScalarUDF::new(&self.name, &self.signature, &self.return_type, &fun);
+        udf.async_udf = Some(Arc::new(self));
+        udf
+    }
+}
+
+/// A call of an asynchronous UDF with physical arguments
+#[derive(Debug, Clone)]
+pub struct AsyncFunctionExpr {
+    udf: Arc<AsyncScalarUDF>,
+    args: Vec<Arc<dyn PhysicalExpr>>,
+    return_type: DataType,
+}
+
+impl AsyncFunctionExpr {
+    /// Create a call of `udf` with arguments `args`, which must already be
+    /// coerced to its signature, returning `return_type`
+    pub fn new(
+        udf: Arc<AsyncScalarUDF>,
+        args: Vec<Arc<dyn PhysicalExpr>>,
+        return_type: DataType,
+    ) -> Self {
+        Self {
+            udf,
+            args,
+            return_type,
+        }
+    }
+
+    /// The called function
+    pub fn udf(&self) -> &Arc<AsyncScalarUDF> {
+        &self.udf
+    }
+
+    /// The arguments of the call
+    pub fn args(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.args
+    }
+
+    /// The type of the result
+    pub fn return_type(&self) -> &DataType {
+        &self.return_type
+    }
+
+    /// Evaluates the call for all rows of `batch`, invoking the function on
+    /// chunks of at most `batch_size` rows
+    pub async fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(new_null_array(&self.return_type, 0));
+        }
+        let args = if self.args.is_empty() {
+            vec![ColumnarValue::Array(Arc::new(NullArray::new(num_rows)))]
+        } else {
+            self.args
+                .iter()
+                .map(|arg| arg.evaluate(batch))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let chunk_size = self.udf.batch_size.unwrap_or(num_rows);
+        let invocations = (0..num_rows).step_by(chunk_size).map(|offset| {
+            let length = chunk_size.min(num_rows - offset);
+            let chunk = args
+                .iter()
+                .map(|arg| match arg {
+                    ColumnarValue::Array(array) => {
+                        ColumnarValue::Array(array.slice(offset, length))
+                    }
+                    ColumnarValue::Scalar(scalar) => {
+                        ColumnarValue::Scalar(scalar.clone())
+                    }
+                })
+                .collect::<Vec<_>>();
+            let result = (self.udf.fun)(chunk);
+            async move { Ok::<_, DataFusionError>(result.await?.into_array(length)) }
+        });
+        let results = stream::iter(invocations)
+            .buffered(self.udf.max_concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        for result in &results {
+            if result.data_type() != &self.return_type {
+                return Err(DataFusionError::Execution(format!(
+                    "Async UDF '{}' returned {:?} instead of {:?}",
+                    self.udf.name,
+                    result.data_type(),
+                    self.return_type
+                )));
+            }
+        }
+        if results.len() == 1 {
+            return Ok(results[0].clone());
+        }
+        Ok(concat(
+            &results.iter().map(|a| a.as_ref()).collect::<Vec<_>>(),
+        )?)
+    }
+}
+
+impl fmt::Display for AsyncFunctionExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.udf.name,
+            self.args
+                .iter()
+                .map(|e| format!("{}", e))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Execution plan evaluating calls of asynchronous UDFs. Its output consists
+/// of the columns of its input followed by one column per call, which are
+/// evaluated in order so that the arguments of a call can refer to the
+/// results of the previous ones.
+#[derive(Debug)]
+pub struct AsyncFuncExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The calls to evaluate, with the names of their output columns
+    exprs: Vec<(AsyncFunctionExpr, String)>,
+    /// The output schema
+    schema: SchemaRef,
+}
+
+impl AsyncFuncExec {
+    /// Create an AsyncFuncExec evaluating `exprs` on `input`
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        exprs: Vec<(AsyncFunctionExpr, String)>,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        let fields = input_schema
+            .fields()
+            .iter()
+            .cloned()
+            .chain(
+                exprs
+                    .iter()
+                    .map(|(e, name)| Field::new(name, e.return_type.clone(), true)),
+            )
+            .collect();
+        Ok(Self {
+            input,
+            exprs,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// The calls to evaluate, with the names of their output columns
+    pub fn exprs(&self) -> &[(AsyncFunctionExpr, String)] {
+        &self.exprs
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for AsyncFuncExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(AsyncFuncExec::try_new(
+                children[0].clone(),
+                self.exprs.clone(),
+            )?)),
+            _ => Err(DataFusionError::Internal(
+                "AsyncFuncExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition).await?;
+        let exprs = Arc::new(self.exprs.clone());
+        let schema = self.schema.clone();
+        let stream = input
+            .then(move |batch| {
+                let exprs = exprs.clone();
+                let schema = schema.clone();
+                async move { evaluate_batch(batch?, &exprs, schema).await }
+            })
+            .boxed();
+        Ok(Box::pin(AsyncFuncStream {
+            schema: self.schema.clone(),
+            stream: Mutex::new(stream),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let exprs: Vec<String> = self
+                    .exprs
+                    .iter()
+                    .map(|(e, alias)| format!("{} as {}", e, alias))
+                    .collect();
+                write!(f, "AsyncFuncExec: expr=[{}]", exprs.join(", "))
+            }
+        }
+    }
+}
+
+/// Appends the results of `exprs` to `batch`, evaluating each call against
+/// the batch extended by the results of the previous ones
+async fn evaluate_batch(
+    batch: RecordBatch,
+    exprs: &[(AsyncFunctionExpr, String)],
+    schema: SchemaRef,
+) -> ArrowResult<RecordBatch> {
+    let input_columns = batch.num_columns();
+    let mut batch = batch;
+    for (i, (expr, _)) in exprs.iter().enumerate() {
+        let result = expr
+            .evaluate(&batch)
+            .await
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+        let mut columns = batch.columns().to_vec();
+        columns.push(result);
+        let fields = schema.fields()[..input_columns + i + 1].to_vec();
+        batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    }
+    RecordBatch::try_new(schema, batch.columns().to_vec())
+}
+
+/// The AsyncFuncExec stream evaluates the calls for each batch of its input
+struct AsyncFuncStream {
+    /// Output schema
+    schema: SchemaRef,
+    /// The input stream with the calls evaluated, in a mutex as record batch
+    /// streams are `Sync`
+    stream: Mutex<BoxStream<'static, ArrowResult<RecordBatch>>>,
+}
+
+impl Stream for AsyncFuncStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.stream.get_mut().unwrap().poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // same number of record batches
+        self.stream.lock().unwrap().size_hint()
+    }
+}
+
+impl RecordBatchStream for AsyncFuncStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
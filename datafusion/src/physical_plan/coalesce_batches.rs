@@ -24,6 +24,7 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::error::{DataFusionError, Result};
+use crate::physical_plan::common::batch_memory_size;
 use crate::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
     SendableRecordBatchStream,
@@ -37,6 +38,27 @@ use async_trait::async_trait;
 use futures::stream::{Stream, StreamExt};
 use log::debug;
 
+/// Policy deciding when [`CoalesceBatchesExec`] has buffered enough rows to emit a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchSizePolicy {
+    /// Emit batches once they contain at least the target number of rows
+    Rows,
+    /// Emit batches once they contain the target number of rows or roughly
+    /// `target_bytes` bytes, whichever is reached first. The number of rows is
+    /// derived from the average row width seen so far, so batches of wide rows
+    /// (e.g. long strings) hold fewer rows than batches of narrow integers.
+    Adaptive {
+        /// Desired memory size of the emitted batches
+        target_bytes: usize,
+    },
+}
+
+impl Default for BatchSizePolicy {
+    fn default() -> Self {
+        BatchSizePolicy::Rows
+    }
+}
+
 /// CoalesceBatchesExec combines small batches into larger batches for more efficient use of
 /// vectorized processing by upstream operators.
 #[derive(Debug)]
@@ -45,6 +67,8 @@ pub struct CoalesceBatchesExec {
     input: Arc<dyn ExecutionPlan>,
     /// Minimum number of rows for coalesces batches
     target_batch_size: usize,
+    /// How the size of the coalesced batches is chosen
+    policy: BatchSizePolicy,
 }
 
 impl CoalesceBatchesExec {
@@ -53,9 +77,16 @@ impl CoalesceBatchesExec {
         Self {
             input,
             target_batch_size,
+            policy: BatchSizePolicy::Rows,
         }
     }
 
+    /// Sets the policy used to choose the size of the coalesced batches
+    pub fn with_policy(mut self, policy: BatchSizePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// The input plan
     pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
         &self.input
@@ -65,6 +96,11 @@ impl CoalesceBatchesExec {
     pub fn target_batch_size(&self) -> usize {
         self.target_batch_size
     }
+
+    /// How the size of the coalesced batches is chosen
+    pub fn policy(&self) -> BatchSizePolicy {
+        self.policy
+    }
 }
 
 #[async_trait]
@@ -84,6 +120,10 @@ impl ExecutionPlan for CoalesceBatchesExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
         // The coalesce batches operator does not make any changes to the partitioning of its input
@@ -95,10 +135,10 @@ impl ExecutionPlan for CoalesceBatchesExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(CoalesceBatchesExec::new(
-                children[0].clone(),
-                self.target_batch_size,
-            ))),
+            1 => Ok(Arc::new(
+                CoalesceBatchesExec::new(children[0].clone(), self.target_batch_size)
+                    .with_policy(self.policy),
+            )),
             _ => Err(DataFusionError::Internal(
                 "CoalesceBatchesExec wrong number of children".to_string(),
             )),
@@ -110,6 +150,9 @@ impl ExecutionPlan for CoalesceBatchesExec {
             input: self.input.execute(partition).await?,
             schema: self.input.schema(),
             target_batch_size: self.target_batch_size,
+            policy: self.policy,
+            seen_rows: 0,
+            seen_bytes: 0,
             buffer: Vec::new(),
             buffered_rows: 0,
             is_closed: false,
@@ -127,7 +170,13 @@ impl ExecutionPlan for CoalesceBatchesExec {
                     f,
                     "CoalesceBatchesExec: target_batch_size={}",
                     self.target_batch_size
-                )
+                )?;
+                match self.policy {
+                    BatchSizePolicy::Rows => Ok(()),
+                    BatchSizePolicy::Adaptive { target_bytes } => {
+                        write!(f, ", target_bytes={}", target_bytes)
+                    }
+                }
             }
         }
     }
@@ -140,6 +189,12 @@ struct CoalesceBatchesStream {
     schema: SchemaRef,
     /// Minimum number of rows for coalesces batches
     target_batch_size: usize,
+    /// How the size of the coalesced batches is chosen
+    policy: BatchSizePolicy,
+    /// Number of rows received from the input so far
+    seen_rows: usize,
+    /// Memory size of the batches received from the input so far
+    seen_bytes: usize,
     /// Buffered batches
     buffer: Vec<RecordBatch>,
     /// Buffered row count
@@ -148,6 +203,24 @@ struct CoalesceBatchesStream {
     is_closed: bool,
 }
 
+impl CoalesceBatchesStream {
+    /// Number of rows to buffer before emitting a batch, given the rows seen so far
+    fn current_target(&self) -> usize {
+        match self.policy {
+            BatchSizePolicy::Rows => self.target_batch_size,
+            BatchSizePolicy::Adaptive { target_bytes } => {
+                if self.seen_rows == 0 {
+                    return self.target_batch_size;
+                }
+                let row_width = (self.seen_bytes / self.seen_rows).max(1);
+                (target_bytes / row_width)
+                    .max(1)
+                    .min(self.target_batch_size)
+            }
+        }
+    }
+}
+
 impl Stream for CoalesceBatchesStream {
     type Item = ArrowResult<RecordBatch>;
 
@@ -163,8 +236,12 @@ impl Stream for CoalesceBatchesStream {
             match input_batch {
                 Poll::Ready(x) => match x {
                     Some(Ok(ref batch)) => {
-                        if batch.num_rows() >= self.target_batch_size
-                            && self.buffer.is_empty()
+                        if let BatchSizePolicy::Adaptive { .. } = self.policy {
+                            self.seen_rows += batch.num_rows();
+                            self.seen_bytes += batch_memory_size(batch);
+                        }
+                        let target_batch_size = self.current_target();
+                        if batch.num_rows() >= target_batch_size && self.buffer.is_empty()
                         {
                             return Poll::Ready(Some(Ok(batch.clone())));
                         } else if batch.num_rows() == 0 {
@@ -174,7 +251,7 @@ impl Stream for CoalesceBatchesStream {
                             self.buffer.push(batch.clone());
                             self.buffered_rows += batch.num_rows();
                             // check to see if we have enough batches yet
-                            if self.buffered_rows >= self.target_batch_size {
+                            if self.buffered_rows >= target_batch_size {
                                 // combine the batches and return
                                 let batch = concat_batches(
                                     &self.schema,
@@ -304,17 +381,60 @@ mod tests {
         .unwrap()
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_adaptive_batch_size() -> Result<()> {
+        let schema = test_schema();
+        let partitions = vec![create_vec_batches(&schema, 10)];
+
+        // a generous byte target falls back to the row target
+        let policy = BatchSizePolicy::Adaptive {
+            target_bytes: 1024 * 1024,
+        };
+        let output_partitions =
+            coalesce_batches_with_policy(&schema, partitions.clone(), 21, policy).await?;
+        let row_counts: Vec<_> =
+            output_partitions[0].iter().map(|b| b.num_rows()).collect();
+        assert_eq!(vec![24, 24, 24, 8], row_counts);
+
+        // rows wider than the byte target are emitted without coalescing
+        let policy = BatchSizePolicy::Adaptive { target_bytes: 1 };
+        let output_partitions =
+            coalesce_batches_with_policy(&schema, partitions, 21, policy).await?;
+        let row_counts: Vec<_> =
+            output_partitions[0].iter().map(|b| b.num_rows()).collect();
+        assert_eq!(vec![8; 10], row_counts);
+
+        Ok(())
+    }
+
     async fn coalesce_batches(
         schema: &SchemaRef,
         input_partitions: Vec<Vec<RecordBatch>>,
         target_batch_size: usize,
+    ) -> Result<Vec<Vec<RecordBatch>>> {
+        coalesce_batches_with_policy(
+            schema,
+            input_partitions,
+            target_batch_size,
+            BatchSizePolicy::Rows,
+        )
+        .await
+    }
+
+    async fn coalesce_batches_with_policy(
+        schema: &SchemaRef,
+        input_partitions: Vec<Vec<RecordBatch>>,
+        target_batch_size: usize,
+        policy: BatchSizePolicy,
     ) -> Result<Vec<Vec<RecordBatch>>> {
         // create physical plan
         let exec = MemoryExec::try_new(&input_partitions, schema.clone(), None)?;
         let exec =
             RepartitionExec::try_new(Arc::new(exec), Partitioning::RoundRobinBatch(1))?;
-        let exec: Arc<dyn ExecutionPlan> =
-            Arc::new(CoalesceBatchesExec::new(Arc::new(exec), target_batch_size));
+        let exec: Arc<dyn ExecutionPlan> = Arc::new(
+            CoalesceBatchesExec::new(Arc::new(exec), target_batch_size)
+                .with_policy(policy),
+        );
 
         // execute and collect results
         let output_partition_count = exec.output_partitioning().partition_count();
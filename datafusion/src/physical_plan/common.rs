@@ -17,6 +17,7 @@
 
 //! Defines common code used in execution plans
 
+use std::collections::HashSet;
 use std::fs;
 use std::fs::metadata;
 use std::sync::Arc;
@@ -25,10 +26,19 @@ use std::task::{Context, Poll};
 use super::{RecordBatchStream, SendableRecordBatchStream};
 use crate::error::{DataFusionError, Result};
 
-use arrow::datatypes::SchemaRef;
+use arrow::array::ArrayData;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
-use futures::{Stream, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The default number of batches file scans read ahead of their consumer
+pub const DEFAULT_READ_AHEAD: usize = 2;
 
 /// Stream of record batches
 pub struct SizedRecordBatchStream {
@@ -78,38 +88,410 @@ pub async fn collect(stream: SendableRecordBatchStream) -> Result<Vec<RecordBatc
         .map_err(DataFusionError::from)
 }
 
+/// Runs the CPU intensive, synchronous function `f` on the blocking thread
+/// pool of the tokio runtime. Operators use this for long running
+/// computations, so that polls on the async worker threads stay short and
+/// other streams (and queries) keep making progress in the meantime.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn spawn_compute<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("Compute task failed: {}", e)))?
+}
+
+/// Runs the CPU intensive, synchronous function `f` inline, as wasm32 has no
+/// threads to run it on
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn spawn_compute<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    f()
+}
+
+/// Reads up to `buffer` batches of `input` ahead of the consumer on the
+/// blocking thread pool of the tokio runtime, so that reading and decoding the
+/// next batches overlaps with processing the current one. `input` is expected
+/// to block in its polls rather than waiting on the runtime, like the streams
+/// of file scans. With a `buffer` of 0, `input` is returned as is and read on
+/// demand, as it is on wasm32, which has no threads to read ahead on.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_ahead(
+    mut input: SendableRecordBatchStream,
+    buffer: usize,
+) -> SendableRecordBatchStream {
+    if buffer == 0 {
+        return input;
+    }
+
+    let schema = input.schema();
+    let (sender, receiver) = mpsc::channel(buffer);
+    tokio::task::spawn_blocking(move || {
+        while let Some(item) = futures::executor::block_on(input.next()) {
+            // stop reading once the receiving side was dropped
+            if sender.blocking_send(item).is_err() {
+                return;
+            }
+        }
+    });
+
+    Box::pin(ReadAheadStream {
+        schema,
+        inner: ReceiverStream::new(receiver),
+    })
+}
+
+/// wasm32 has no threads to read ahead on, so `input` is read on demand
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn read_ahead(
+    input: SendableRecordBatchStream,
+    _buffer: usize,
+) -> SendableRecordBatchStream {
+    input
+}
+
+/// Stream of the batches read ahead by [`read_ahead`]
+#[cfg(not(target_arch = "wasm32"))]
+struct ReadAheadStream {
+    schema: SchemaRef,
+    inner: ReceiverStream<ArrowResult<RecordBatch>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Stream for ReadAheadStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RecordBatchStream for ReadAheadStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Aborts a spawned task when dropped.
+///
+/// Operators keep the tasks they spawn in the output stream they return, so
+/// that dropping the stream, e.g. because the consumer cancelled the query,
+/// does not leave tasks behind that keep executing the input.
+#[derive(Debug)]
+pub struct AbortOnDropSingle<T>(JoinHandle<T>);
+
+impl<T> AbortOnDropSingle<T> {
+    /// Abort `join_handle` once the returned value is dropped
+    pub fn new(join_handle: JoinHandle<T>) -> Self {
+        Self(join_handle)
+    }
+}
+
+impl<T> Drop for AbortOnDropSingle<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Aborts a set of spawned tasks when dropped, see [`AbortOnDropSingle`]
+#[derive(Debug)]
+pub struct AbortOnDropMany<T>(pub Vec<JoinHandle<T>>);
+
+impl<T> Drop for AbortOnDropMany<T> {
+    fn drop(&mut self) {
+        for join_handle in &self.0 {
+            join_handle.abort();
+        }
+    }
+}
+
+/// Returns the memory used by the buffers of the arrays of `batch`, in bytes.
+///
+/// Unlike summing up [`Array::get_array_memory_size`], buffers shared by several
+/// arrays, such as the buffers of slices of the same array, are counted once.
+pub fn batch_memory_size(batch: &RecordBatch) -> usize {
+    batches_memory_size(std::slice::from_ref(batch))
+}
+
+/// Returns the memory used by the buffers of the arrays of `batches`, in bytes.
+/// Buffers shared by several arrays, also across batches, are counted once.
+pub fn batches_memory_size(batches: &[RecordBatch]) -> usize {
+    let mut seen = HashSet::new();
+    batches
+        .iter()
+        .flat_map(|batch| batch.columns())
+        .map(|array| array_data_memory_size(array.data(), &mut seen))
+        .sum()
+}
+
+/// Returns the capacity of the buffers of `data` and its children that are not
+/// in `seen` yet, adding them to `seen`
+fn array_data_memory_size(data: &ArrayData, seen: &mut HashSet<*const u8>) -> usize {
+    let mut size = 0;
+    for buffer in data.buffers().iter().chain(data.null_buffer()) {
+        if seen.insert(buffer.as_ptr()) {
+            size += buffer.capacity();
+        }
+    }
+    for child in data.child_data() {
+        size += array_data_memory_size(child, seen);
+    }
+    size
+}
+
+/// Options for listing the files of a directory
+#[derive(Debug, Copy, Clone)]
+pub struct ListingOptions {
+    /// Whether the files of subdirectories are listed. Defaults to true.
+    pub recursive: bool,
+    /// Whether files and directories whose names start with `.` or `_`, such
+    /// as `.crc` files or `_temporary` directories, are skipped. Defaults to
+    /// true.
+    pub ignore_hidden: bool,
+    /// The maximum number of files to list, above which listing fails.
+    /// Defaults to no limit.
+    pub max_files: Option<usize>,
+}
+
+impl Default for ListingOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            ignore_hidden: true,
+            max_files: None,
+        }
+    }
+}
+
+impl ListingOptions {
+    /// Create listing options with default presets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the files of subdirectories are listed
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Whether files and directories starting with `.` or `_` are skipped
+    pub fn with_ignore_hidden(mut self, ignore_hidden: bool) -> Self {
+        self.ignore_hidden = ignore_hidden;
+        self
+    }
+
+    /// Fail listing if more than `max_files` files are found
+    pub fn with_max_files(mut self, max_files: Option<usize>) -> Self {
+        self.max_files = max_files;
+        self
+    }
+}
+
+/// Resolves the types of a schema inferred from CSV or JSON files. Columns
+/// named in `hints` take the type of their hint, integer columns become
+/// `Float64` if `integers_as_floats`, and date columns become `Utf8` unless
+/// `infer_temporal`.
+pub(crate) fn resolve_inferred_schema(
+    schema: &Schema,
+    hints: Option<&Schema>,
+    integers_as_floats: bool,
+    infer_temporal: bool,
+) -> Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let hint = hints.and_then(|hints| hints.field_with_name(field.name()).ok());
+            let data_type = match hint {
+                Some(hint) => hint.data_type().clone(),
+                None => match field.data_type() {
+                    DataType::Int8
+                    | DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::UInt8
+                    | DataType::UInt16
+                    | DataType::UInt32
+                    | DataType::UInt64
+                        if integers_as_floats =>
+                    {
+                        DataType::Float64
+                    }
+                    DataType::Date32 | DataType::Date64 | DataType::Timestamp(_, _)
+                        if !infer_temporal =>
+                    {
+                        DataType::Utf8
+                    }
+                    data_type => data_type.clone(),
+                },
+            };
+            Field::new(field.name(), data_type, field.is_nullable())
+        })
+        .collect();
+    Schema::new(fields)
+}
+
 /// Recursively builds a list of files in a directory with a given extension
 pub fn build_file_list(dir: &str, ext: &str) -> Result<Vec<String>> {
-    let mut filenames: Vec<String> = Vec::new();
-    build_file_list_recurse(dir, &mut filenames, ext)?;
-    Ok(filenames)
+    build_file_list_with_options(dir, ext, &ListingOptions::default())
 }
 
-/// Recursively build a list of files in a directory with a given extension with an accumulator list
-fn build_file_list_recurse(
+/// Builds a list of files in a directory with a given extension, as
+/// configured by `options`
+pub fn build_file_list_with_options(
     dir: &str,
-    filenames: &mut Vec<String>,
     ext: &str,
-) -> Result<()> {
+    options: &ListingOptions,
+) -> Result<Vec<String>> {
+    let mut filenames: Vec<String> = Vec::new();
     let metadata = metadata(dir)?;
     if metadata.is_file() {
         if dir.ends_with(ext) {
             filenames.push(dir.to_string());
         }
     } else {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if let Some(path_name) = path.to_str() {
-                if path.is_dir() {
-                    build_file_list_recurse(path_name, filenames, ext)?;
-                } else if path_name.ends_with(ext) {
-                    filenames.push(path_name.to_string());
+        build_file_list_recurse(dir, &mut filenames, ext, options)?;
+    }
+    Ok(filenames)
+}
+
+/// Build a list of files in a directory with a given extension with an accumulator list
+fn build_file_list_recurse(
+    dir: &str,
+    filenames: &mut Vec<String>,
+    ext: &str,
+    options: &ListingOptions,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if options.ignore_hidden {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || name.starts_with('_') {
+                continue;
+            }
+        }
+        let path = entry.path();
+        if let Some(path_name) = path.to_str() {
+            if path.is_dir() {
+                if options.recursive {
+                    build_file_list_recurse(path_name, filenames, ext, options)?;
+                }
+            } else if path_name.ends_with(ext) {
+                filenames.push(path_name.to_string());
+                if let Some(max_files) = options.max_files {
+                    if filenames.len() > max_files {
+                        return Err(DataFusionError::Plan(format!(
+                            "More than {} files found at {}",
+                            max_files, dir
+                        )));
+                    }
                 }
-            } else {
-                return Err(DataFusionError::Plan("Invalid path".to_string()));
             }
+        } else {
+            return Err(DataFusionError::Plan("Invalid path".to_string()));
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    #[test]
+    fn shared_buffers_counted_once() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(3), Some(4)])),
+                Arc::new(StringArray::from(vec!["a", "bb", "ccc", "dddd"])),
+            ],
+        )?;
+        let size = batch_memory_size(&batch);
+        assert!(size > 0);
+
+        // the same batch twice, or slices of it, use no additional memory
+        assert_eq!(batches_memory_size(&[batch.clone(), batch.clone()]), size);
+        let slices = (0..batch.num_rows())
+            .map(|i| {
+                let columns = batch
+                    .columns()
+                    .iter()
+                    .map(|array| array.slice(i, 1))
+                    .collect();
+                RecordBatch::try_new(batch.schema(), columns)
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+        assert_eq!(batches_memory_size(&slices), size);
+        Ok(())
+    }
+
+    #[test]
+    fn build_file_list_options() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("nested").join("deeper"))?;
+        fs::create_dir_all(root.join("_temporary"))?;
+        for file in &[
+            "a.csv",
+            "b.json",
+            ".c.csv",
+            "_SUCCESS.csv",
+            "nested/d.csv",
+            "nested/deeper/e.csv",
+            "_temporary/f.csv",
+        ] {
+            fs::write(root.join(file), "")?;
+        }
+        let root = root.to_str().unwrap();
+        let list = |options: ListingOptions| -> Result<Vec<String>> {
+            let mut files = build_file_list_with_options(root, ".csv", &options)?
+                .into_iter()
+                .map(|f| f[root.len() + 1..].to_string())
+                .collect::<Vec<_>>();
+            files.sort();
+            Ok(files)
+        };
+
+        assert_eq!(
+            list(ListingOptions::new())?,
+            vec!["a.csv", "nested/d.csv", "nested/deeper/e.csv"]
+        );
+        assert_eq!(
+            list(ListingOptions::new().with_recursive(false))?,
+            vec!["a.csv"]
+        );
+        assert_eq!(
+            list(ListingOptions::new().with_ignore_hidden(false))?,
+            vec![
+                ".c.csv",
+                "_SUCCESS.csv",
+                "_temporary/f.csv",
+                "a.csv",
+                "nested/d.csv",
+                "nested/deeper/e.csv"
+            ]
+        );
+        assert!(list(ListingOptions::new().with_max_files(Some(3))).is_ok());
+        assert!(list(ListingOptions::new().with_max_files(Some(2))).is_err());
+
+        Ok(())
+    }
+}
@@ -33,7 +33,7 @@ use crate::{
     scalar::ScalarValue,
 };
 use async_trait::async_trait;
-use std::time::Instant;
+use instant::Instant;
 
 use super::{
     coalesce_batches::concat_batches, memory::MemoryStream, DisplayFormatType,
@@ -114,6 +114,16 @@ impl ExecutionPlan for CrossJoinExec {
         vec![self.left.clone(), self.right.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        if children[0] {
+            Err(DataFusionError::Plan(
+                "Cross join can not collect an unbounded left input".to_string(),
+            ))
+        } else {
+            Ok(children[1])
+        }
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -18,13 +18,15 @@
 //! Execution plan for reading CSV files
 
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::ExecutionPlan;
-use crate::physical_plan::{common, source::Source, Partitioning};
+use crate::physical_plan::common::{self, ListingOptions};
+use crate::physical_plan::{retry::RetryPolicy, source::Source, Partitioning};
+use crate::physical_plan::{ExecutionPlan, MetricsSet, SQLMetric};
 use arrow::csv;
 use arrow::datatypes::{Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 use futures::Stream;
+use instant::Instant;
 use std::any::Any;
 use std::fs::File;
 use std::io::Read;
@@ -54,6 +56,16 @@ pub struct CsvReadOptions<'a> {
     /// File extension; only files with this extension are selected for data input.
     /// Defaults to ".csv".
     pub file_extension: &'a str,
+    /// How the files of a directory are listed
+    pub listing: ListingOptions,
+    /// An optional schema whose columns override the types of the inferred
+    /// columns of the same name
+    pub schema_hints: Option<&'a Schema>,
+    /// Infer integer columns as `Float64`. Defaults to false.
+    pub integers_as_floats: bool,
+    /// Infer columns of dates as `Date32` or `Date64` rather than `Utf8`.
+    /// Defaults to true.
+    pub infer_temporal: bool,
 }
 
 impl<'a> CsvReadOptions<'a> {
@@ -65,6 +77,10 @@ impl<'a> CsvReadOptions<'a> {
             schema_infer_max_records: 1000,
             delimiter: b',',
             file_extension: ".csv",
+            listing: ListingOptions::default(),
+            schema_hints: None,
+            integers_as_floats: false,
+            infer_temporal: true,
         }
     }
 
@@ -86,6 +102,12 @@ impl<'a> CsvReadOptions<'a> {
         self
     }
 
+    /// Specify how the files of a directory are listed
+    pub fn listing(mut self, listing: ListingOptions) -> Self {
+        self.listing = listing;
+        self
+    }
+
     /// Configure delimiter setting with Option, None value will be ignored
     pub fn delimiter_option(mut self, delimiter: Option<u8>) -> Self {
         if let Some(d) = delimiter {
@@ -105,6 +127,35 @@ impl<'a> CsvReadOptions<'a> {
         self.schema_infer_max_records = max_records;
         self
     }
+
+    /// Override the inferred types of the columns of `schema_hints`
+    pub fn schema_hints(mut self, schema_hints: &'a Schema) -> Self {
+        self.schema_hints = Some(schema_hints);
+        self
+    }
+
+    /// Configure whether integer columns are inferred as `Float64`
+    pub fn integers_as_floats(mut self, integers_as_floats: bool) -> Self {
+        self.integers_as_floats = integers_as_floats;
+        self
+    }
+
+    /// Configure whether columns of dates are inferred as dates or strings
+    pub fn infer_temporal(mut self, infer_temporal: bool) -> Self {
+        self.infer_temporal = infer_temporal;
+        self
+    }
+
+    /// Resolve the types of a schema inferred from CSV files according to
+    /// these options
+    pub(crate) fn resolve_inferred_schema(&self, schema: &Schema) -> Schema {
+        common::resolve_inferred_schema(
+            schema,
+            self.schema_hints,
+            self.integers_as_floats,
+            self.infer_temporal,
+        )
+    }
 }
 
 /// Execution plan for scanning a CSV file
@@ -128,6 +179,16 @@ pub struct CsvExec {
     batch_size: usize,
     /// Limit in nr. of rows
     limit: Option<usize>,
+    /// Number of rows read from all partitions
+    output_rows: Arc<SQLMetric>,
+    /// Time spent parsing batches, in nanoseconds
+    elapsed_compute: Arc<SQLMetric>,
+    /// Policy for retrying to open files
+    retry_policy: RetryPolicy,
+    /// Number of times opening a file was retried
+    read_retries: Arc<SQLMetric>,
+    /// Number of batches read ahead of the consumer
+    read_ahead: usize,
 }
 
 impl CsvExec {
@@ -141,7 +202,11 @@ impl CsvExec {
     ) -> Result<Self> {
         let file_extension = String::from(options.file_extension);
 
-        let filenames = common::build_file_list(path, file_extension.as_str())?;
+        let filenames = common::build_file_list_with_options(
+            path,
+            file_extension.as_str(),
+            &options.listing,
+        )?;
         if filenames.is_empty() {
             return Err(DataFusionError::Execution(format!(
                 "No files found at {path} with file extension {file_extension}",
@@ -173,6 +238,11 @@ impl CsvExec {
             projected_schema: Arc::new(projected_schema),
             batch_size,
             limit,
+            output_rows: SQLMetric::counter(),
+            elapsed_compute: SQLMetric::time_nanos(),
+            retry_policy: RetryPolicy::default(),
+            read_retries: SQLMetric::counter(),
+            read_ahead: common::DEFAULT_READ_AHEAD,
         })
     }
     /// Create a new execution plan for reading from a reader
@@ -208,6 +278,11 @@ impl CsvExec {
             projected_schema: Arc::new(projected_schema),
             batch_size,
             limit,
+            output_rows: SQLMetric::counter(),
+            elapsed_compute: SQLMetric::time_nanos(),
+            retry_policy: RetryPolicy::default(),
+            read_retries: SQLMetric::counter(),
+            read_ahead: common::DEFAULT_READ_AHEAD,
         })
     }
 
@@ -256,17 +331,43 @@ impl CsvExec {
         self.limit
     }
 
+    /// Customize the policy for retrying to open files, which does not retry
+    /// by default
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Policy for retrying to open files
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Customize the number of batches each partition reads ahead of its
+    /// consumer, [`common::DEFAULT_READ_AHEAD`] by default. With 0, batches
+    /// are read on demand.
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Number of batches each partition reads ahead of its consumer
+    pub fn read_ahead(&self) -> usize {
+        self.read_ahead
+    }
+
     /// Infer schema for given CSV dataset
     pub fn try_infer_schema(
         filenames: &[String],
         options: &CsvReadOptions,
     ) -> Result<Schema> {
-        Ok(csv::infer_schema_from_files(
+        let schema = csv::infer_schema_from_files(
             filenames,
             options.delimiter,
             Some(options.schema_infer_max_records),
             options.has_header,
-        )?)
+        )?;
+        Ok(options.resolve_inferred_schema(&schema))
     }
 }
 
@@ -312,15 +413,25 @@ impl ExecutionPlan for CsvExec {
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         match &self.source {
             Source::PartitionedFiles { filenames, .. } => {
-                Ok(Box::pin(CsvStream::try_new(
-                    &filenames[partition],
+                let path = filenames[partition].clone();
+                let file = self
+                    .retry_policy
+                    .run(&filenames[partition], &self.read_retries, move || {
+                        Ok(File::open(&path)?)
+                    })
+                    .await?;
+                let stream = CsvStream::try_new_from_reader(
+                    file,
                     self.schema.clone(),
                     self.has_header,
                     self.delimiter,
                     &self.projection,
                     self.batch_size,
                     self.limit,
-                )?))
+                )?;
+                let stream = stream
+                    .with_metrics(self.output_rows.clone(), self.elapsed_compute.clone());
+                Ok(common::read_ahead(Box::pin(stream), self.read_ahead))
             }
             Source::Reader(rdr) => {
                 if partition != 0 {
@@ -329,7 +440,7 @@ impl ExecutionPlan for CsvExec {
                             .to_string(),
                     ))
                 } else if let Some(rdr) = rdr.lock().unwrap().take() {
-                    Ok(Box::pin(CsvStream::try_new_from_reader(
+                    let stream = CsvStream::try_new_from_reader(
                         rdr,
                         self.schema.clone(),
                         self.has_header,
@@ -337,7 +448,12 @@ impl ExecutionPlan for CsvExec {
                         &self.projection,
                         self.batch_size,
                         self.limit,
-                    )?))
+                    )?;
+                    let stream = stream.with_metrics(
+                        self.output_rows.clone(),
+                        self.elapsed_compute.clone(),
+                    );
+                    Ok(common::read_ahead(Box::pin(stream), self.read_ahead))
                 } else {
                     Err(DataFusionError::Execution(
                         "Error reading CSV: Data can only be read a single time when the source is a reader"
@@ -363,12 +479,24 @@ impl ExecutionPlan for CsvExec {
             }
         }
     }
+
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.output_rows);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.elapsed_compute);
+        metrics.push(MetricsSet::READ_RETRIES, &self.read_retries);
+        metrics
+    }
 }
 
 /// Iterator over batches
 struct CsvStream<R: Read> {
     /// Arrow CSV reader
     reader: csv::Reader<R>,
+    /// Number of rows read
+    output_rows: Arc<SQLMetric>,
+    /// Time spent parsing batches
+    elapsed_compute: Arc<SQLMetric>,
 }
 impl CsvStream<File> {
     /// Create an iterator for a CSV file
@@ -411,7 +539,22 @@ impl<R: Read> CsvStream<R> {
             projection.clone(),
         );
 
-        Ok(Self { reader })
+        Ok(Self {
+            reader,
+            output_rows: SQLMetric::counter(),
+            elapsed_compute: SQLMetric::time_nanos(),
+        })
+    }
+
+    /// Record the metrics of this stream in `output_rows` and `elapsed_compute`
+    fn with_metrics(
+        mut self,
+        output_rows: Arc<SQLMetric>,
+        elapsed_compute: Arc<SQLMetric>,
+    ) -> Self {
+        self.output_rows = output_rows;
+        self.elapsed_compute = elapsed_compute;
+        self
     }
 }
 
@@ -422,7 +565,13 @@ impl<R: Read + Unpin> Stream for CsvStream<R> {
         mut self: Pin<&mut Self>,
         _: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        Poll::Ready(self.reader.next())
+        let now = Instant::now();
+        let next = self.reader.next();
+        self.elapsed_compute.add_elapsed(now);
+        if let Some(Ok(batch)) = &next {
+            self.output_rows.add(batch.num_rows());
+        }
+        Poll::Ready(next)
     }
 }
 
@@ -437,6 +586,7 @@ impl<R: Read + Unpin> RecordBatchStream for CsvStream<R> {
 mod tests {
     use super::*;
     use crate::test::aggr_test_schema;
+    use arrow::datatypes::{DataType, Field};
     use futures::StreamExt;
 
     #[tokio::test]
@@ -464,6 +614,65 @@ mod tests {
         assert_eq!("c1", batch_schema.field(0).name());
         assert_eq!("c3", batch_schema.field(1).name());
         assert_eq!("c5", batch_schema.field(2).name());
+        assert_eq!(Some(100), csv.metrics().output_rows());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn csv_exec_read_ahead() -> Result<()> {
+        let schema = aggr_test_schema();
+        let testdata = arrow::util::test_util::arrow_test_data();
+        let path = format!("{}/csv/aggregate_test_100.csv", testdata);
+        for read_ahead in &[0, 1, 3] {
+            let csv = CsvExec::try_new(
+                &path,
+                CsvReadOptions::new().schema(&schema),
+                None,
+                10,
+                None,
+            )?
+            .with_read_ahead(*read_ahead);
+            assert_eq!(*read_ahead, csv.read_ahead());
+
+            let batches = common::collect(csv.execute(0).await?).await?;
+            assert_eq!(10, batches.len());
+            assert!(batches.iter().all(|batch| batch.num_rows() == 10));
+            assert_eq!(Some(100), csv.metrics().output_rows());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn csv_infer_schema_options() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data.csv");
+        std::fs::write(&path, "a,b,c,d\n1,2,2021-01-01,x\n3,4,2021-01-02,y\n")?;
+        let filenames = vec![path.to_str().unwrap().to_string()];
+
+        let schema = CsvExec::try_infer_schema(&filenames, &CsvReadOptions::new())?;
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(3).data_type(), &DataType::Utf8);
+
+        let hints = Schema::new(vec![Field::new("b", DataType::Int32, true)]);
+        let options = CsvReadOptions::new()
+            .schema_hints(&hints)
+            .integers_as_floats(true)
+            .infer_temporal(false);
+        let schema = CsvExec::try_infer_schema(&filenames, &options)?;
+        let types = schema
+            .fields()
+            .iter()
+            .map(|f| f.data_type().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                DataType::Float64,
+                DataType::Int32,
+                DataType::Utf8,
+                DataType::Utf8
+            ]
+        );
         Ok(())
     }
 
@@ -29,12 +29,14 @@ use arrow::{
 };
 use arrow::{
     array::{
-        Date32Array, Date64Array, TimestampMicrosecondArray, TimestampMillisecondArray,
-        TimestampNanosecondArray, TimestampSecondArray,
+        Date32Array, Date64Array, Int64Array, TimestampMicrosecondArray,
+        TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray,
     },
-    compute::kernels::temporal,
     datatypes::TimeUnit,
-    temporal_conversions::timestamp_ns_to_datetime,
+    temporal_conversions::{
+        date32_to_datetime, date64_to_datetime, timestamp_ms_to_datetime,
+        timestamp_ns_to_datetime, timestamp_s_to_datetime, timestamp_us_to_datetime,
+    },
 };
 use chrono::prelude::*;
 use chrono::Duration;
@@ -285,8 +287,9 @@ pub fn make_now(
     }
 }
 
-fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
-    let value = timestamp_ns_to_datetime(value).with_nanosecond(0);
+/// Truncates `value` to the start of the `granularity` it falls into
+fn truncate_naive(granularity: &str, value: NaiveDateTime) -> Result<NaiveDateTime> {
+    let value = value.with_nanosecond(0);
     let value = match granularity {
         "second" => value,
         "minute" => value.and_then(|d| d.with_second(0)),
@@ -307,6 +310,12 @@ fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
             .and_then(|d| d.with_minute(0))
             .and_then(|d| d.with_hour(0))
             .and_then(|d| d.with_day0(0)),
+        "quarter" => value
+            .and_then(|d| d.with_second(0))
+            .and_then(|d| d.with_minute(0))
+            .and_then(|d| d.with_hour(0))
+            .and_then(|d| d.with_day0(0))
+            .and_then(|d| d.with_month0(d.month0() / 3 * 3)),
         "year" => value
             .and_then(|d| d.with_second(0))
             .and_then(|d| d.with_minute(0))
@@ -320,100 +329,419 @@ fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
             )));
         }
     };
-    // `with_x(0)` are infalible because `0` are always a valid
-    Ok(value.unwrap().timestamp_nanos())
+    // `with_x(0)` are infalible because `0` are always a valid, as is the first
+    // month of a quarter
+    Ok(value.unwrap())
+}
+
+/// Truncates the timestamp `value` in nanoseconds as seen in the timezone with
+/// the UTC `offset`
+fn date_trunc_with_offset(
+    granularity: &str,
+    value: i64,
+    offset: &FixedOffset,
+) -> Result<i64> {
+    let offset = Duration::seconds(offset.local_minus_utc() as i64);
+    let local = timestamp_ns_to_datetime(value) + offset;
+    Ok((truncate_naive(granularity, local)? - offset).timestamp_nanos())
+}
+
+/// Truncates `value`, given as a multiple of `nanos_per_unit` nanoseconds
+fn date_trunc_in_unit(
+    granularity: &str,
+    value: i64,
+    nanos_per_unit: i64,
+    offset: &FixedOffset,
+) -> Result<i64> {
+    let nanos = value.checked_mul(nanos_per_unit).ok_or_else(|| {
+        DataFusionError::Execution(format!(
+            "Value {} is out of range for date_trunc",
+            value
+        ))
+    })?;
+    // granularities are at least a second, so the result is a whole unit
+    Ok(date_trunc_with_offset(granularity, nanos, offset)? / nanos_per_unit)
+}
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+pub(crate) fn nanos_per_unit(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1_000_000_000,
+        TimeUnit::Millisecond => 1_000_000,
+        TimeUnit::Microsecond => 1_000,
+        TimeUnit::Nanosecond => 1,
+    }
+}
+
+/// Parses the timezone of a timestamp type. Only UTC and fixed offsets such as
+/// `+02:00`, `+0200` or `-05` are supported.
+fn parse_timezone(tz: &str) -> Result<FixedOffset> {
+    let unsupported = || {
+        DataFusionError::NotImplemented(format!(
+            "Unsupported timezone {} for date_trunc, only UTC and fixed offsets \
+             such as +02:00 are supported",
+            tz
+        ))
+    };
+    if tz.eq_ignore_ascii_case("utc") || tz == "Z" {
+        return Ok(FixedOffset::east(0));
+    }
+    let (sign, offset) = match tz.as_bytes().first() {
+        Some(b'+') => (1, &tz[1..]),
+        Some(b'-') => (-1, &tz[1..]),
+        _ => return Err(unsupported()),
+    };
+    let digits = offset.replace(':', "");
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.as_str(), "0"),
+        4 => (&digits[..2], &digits[2..]),
+        _ => return Err(unsupported()),
+    };
+    let hours = hours.parse::<i32>().map_err(|_| unsupported())?;
+    let minutes = minutes.parse::<i32>().map_err(|_| unsupported())?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(unsupported)
+}
+
+/// Applies `$FN` to every non-null value of `$ARRAY`, a `$ARRAY_TYPE`
+macro_rules! date_trunc_array {
+    ($ARRAY:expr, $ARRAY_TYPE:ident, $FN:expr) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        array
+            .iter()
+            .map(|v| v.map($FN).transpose())
+            .collect::<Result<Vec<_>>>()?
+    }};
 }
 
 /// date_trunc SQL function
+///
+/// Truncates timestamps of any unit, as well as dates, to the start of the
+/// given granularity. Timestamps with a timezone are truncated as seen in
+/// that timezone, e.g. to the start of the local day.
 pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
     let (granularity, array) = (&args[0], &args[1]);
 
     let granularity =
         if let ColumnarValue::Scalar(ScalarValue::Utf8(Some(v))) = granularity {
-            v
+            v.to_lowercase()
         } else {
             return Err(DataFusionError::Execution(
                 "Granularity of `date_trunc` must be non-null scalar Utf8".to_string(),
             ));
         };
-
-    let f = |x: Option<i64>| x.map(|x| date_trunc_single(granularity, x)).transpose();
+    let granularity = granularity.as_str();
+    let utc = FixedOffset::east(0);
 
     Ok(match array {
         ColumnarValue::Scalar(scalar) => {
-            if let ScalarValue::TimestampNanosecond(v) = scalar {
-                ColumnarValue::Scalar(ScalarValue::TimestampNanosecond((f)(*v)?))
-            } else {
-                return Err(DataFusionError::Execution(
-                    "array of `date_trunc` must be non-null scalar Utf8".to_string(),
-                ));
-            }
+            let f = |unit: TimeUnit| {
+                move |v: i64| {
+                    date_trunc_in_unit(granularity, v, nanos_per_unit(&unit), &utc)
+                }
+            };
+            ColumnarValue::Scalar(match scalar {
+                ScalarValue::TimestampSecond(v) => {
+                    ScalarValue::TimestampSecond(v.map(f(TimeUnit::Second)).transpose()?)
+                }
+                ScalarValue::TimestampMillisecond(v) => {
+                    ScalarValue::TimestampMillisecond(
+                        v.map(f(TimeUnit::Millisecond)).transpose()?,
+                    )
+                }
+                ScalarValue::TimestampMicrosecond(v) => {
+                    ScalarValue::TimestampMicrosecond(
+                        v.map(f(TimeUnit::Microsecond)).transpose()?,
+                    )
+                }
+                ScalarValue::TimestampNanosecond(v) => ScalarValue::TimestampNanosecond(
+                    v.map(f(TimeUnit::Nanosecond)).transpose()?,
+                ),
+                ScalarValue::Date32(v) => ScalarValue::Date32(
+                    v.map(|v| {
+                        date_trunc_in_unit(granularity, v as i64, NANOS_PER_DAY, &utc)
+                            .map(|v| v as i32)
+                    })
+                    .transpose()?,
+                ),
+                ScalarValue::Date64(v) => {
+                    ScalarValue::Date64(v.map(f(TimeUnit::Millisecond)).transpose()?)
+                }
+                other => {
+                    return Err(DataFusionError::Execution(format!(
+                    "Second argument of `date_trunc` must be a timestamp or date, got {}",
+                    other.get_datatype()
+                )))
+                }
+            })
         }
         ColumnarValue::Array(array) => {
-            let array = array
-                .as_any()
-                .downcast_ref::<TimestampNanosecondArray>()
-                .unwrap();
-            let array = array
-                .iter()
-                .map(f)
-                .collect::<Result<TimestampNanosecondArray>>()?;
-
-            ColumnarValue::Array(Arc::new(array))
+            let array: ArrayRef = match array.data_type() {
+                DataType::Timestamp(unit, tz) => {
+                    let offset = match tz {
+                        Some(tz) => parse_timezone(tz)?,
+                        None => utc,
+                    };
+                    let nanos_per_unit = nanos_per_unit(unit);
+                    let f = |v: i64| {
+                        date_trunc_in_unit(granularity, v, nanos_per_unit, &offset)
+                    };
+                    match unit {
+                        TimeUnit::Second => Arc::new(TimestampSecondArray::from_opt_vec(
+                            date_trunc_array!(array, TimestampSecondArray, f),
+                            tz.clone(),
+                        )),
+                        TimeUnit::Millisecond => {
+                            Arc::new(TimestampMillisecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampMillisecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                        TimeUnit::Microsecond => {
+                            Arc::new(TimestampMicrosecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampMicrosecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                        TimeUnit::Nanosecond => {
+                            Arc::new(TimestampNanosecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampNanosecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                    }
+                }
+                DataType::Date32 => Arc::new(Date32Array::from(date_trunc_array!(
+                    array,
+                    Date32Array,
+                    |v: i32| {
+                        date_trunc_in_unit(granularity, v as i64, NANOS_PER_DAY, &utc)
+                            .map(|v| v as i32)
+                    }
+                ))),
+                DataType::Date64 => Arc::new(Date64Array::from(date_trunc_array!(
+                    array,
+                    Date64Array,
+                    |v: i64| {
+                        date_trunc_in_unit(
+                            granularity,
+                            v,
+                            nanos_per_unit(&TimeUnit::Millisecond),
+                            &utc,
+                        )
+                    }
+                ))),
+                other => {
+                    return Err(DataFusionError::Execution(format!(
+                    "Second argument of `date_trunc` must be a timestamp or date, got {}",
+                    other
+                )))
+                }
+            };
+            ColumnarValue::Array(array)
         }
     })
 }
 
-macro_rules! extract_date_part {
-    ($ARRAY: expr, $FN:expr) => {
-        match $ARRAY.data_type() {
-            DataType::Date32 => {
-                let array = $ARRAY.as_any().downcast_ref::<Date32Array>().unwrap();
-                Ok($FN(array)?)
+/// Bins `value`, given as a multiple of `nanos_per_unit` nanoseconds, into
+/// `stride` nanoseconds wide buckets aligned to `origin` nanoseconds
+fn date_bin_in_unit(
+    stride: i64,
+    value: i64,
+    nanos_per_unit: i64,
+    origin: i64,
+) -> Result<i64> {
+    let out_of_range = || {
+        DataFusionError::Execution(format!(
+            "Value {} is out of range for date_bin",
+            value
+        ))
+    };
+    let nanos = value.checked_mul(nanos_per_unit).ok_or_else(out_of_range)?;
+    let delta = nanos.checked_sub(origin).ok_or_else(out_of_range)?;
+    // round towards negative infinity so that values before the origin are
+    // binned to the start of their bucket as well
+    let binned = origin + delta.div_euclid(stride) * stride;
+    Ok(binned.div_euclid(nanos_per_unit))
+}
+
+/// Returns the value of a timestamp scalar in nanoseconds
+fn timestamp_scalar_nanos(value: &ScalarValue) -> Option<i64> {
+    match value {
+        ScalarValue::TimestampSecond(Some(v)) => Some(v * 1_000_000_000),
+        ScalarValue::TimestampMillisecond(Some(v)) => Some(v * 1_000_000),
+        ScalarValue::TimestampMicrosecond(Some(v)) => Some(v * 1_000),
+        ScalarValue::TimestampNanosecond(Some(v)) => Some(*v),
+        _ => None,
+    }
+}
+
+/// date_bin SQL function
+///
+/// Bins timestamps of any unit into buckets of the width given by the
+/// interval, e.g. `date_bin(INTERVAL '15 minutes', ts)`. Buckets are aligned
+/// to the optional third argument, a timestamp which defaults to the unix
+/// epoch.
+pub fn date_bin(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let stride = match &args[0] {
+        ColumnarValue::Scalar(ScalarValue::IntervalDayTime(Some(v))) => {
+            let days = v >> 32;
+            let millis = (*v as i32) as i64;
+            days * NANOS_PER_DAY + millis * 1_000_000
+        }
+        ColumnarValue::Scalar(ScalarValue::IntervalYearMonth(Some(_))) => {
+            return Err(DataFusionError::NotImplemented(
+                "`date_bin` does not support intervals of months or years".to_string(),
+            ))
+        }
+        _ => {
+            return Err(DataFusionError::Execution(
+                "Stride of `date_bin` must be a non-null scalar interval".to_string(),
+            ))
+        }
+    };
+    if stride <= 0 {
+        return Err(DataFusionError::Execution(
+            "Stride of `date_bin` must be positive".to_string(),
+        ));
+    }
+
+    let origin = match args.get(2) {
+        None => 0,
+        Some(ColumnarValue::Scalar(origin)) => timestamp_scalar_nanos(origin)
+            .ok_or_else(|| {
+                DataFusionError::Execution(
+                    "Origin of `date_bin` must be a non-null scalar timestamp"
+                        .to_string(),
+                )
+            })?,
+        Some(ColumnarValue::Array(_)) => {
+            return Err(DataFusionError::NotImplemented(
+                "Origin of `date_bin` must be a scalar".to_string(),
+            ))
+        }
+    };
+
+    let f = |unit: &TimeUnit| {
+        let nanos_per_unit = nanos_per_unit(unit);
+        move |v: i64| date_bin_in_unit(stride, v, nanos_per_unit, origin)
+    };
+
+    Ok(match &args[1] {
+        ColumnarValue::Scalar(scalar) => ColumnarValue::Scalar(match scalar {
+            ScalarValue::TimestampSecond(v) => {
+                ScalarValue::TimestampSecond(v.map(f(&TimeUnit::Second)).transpose()?)
             }
-            DataType::Date64 => {
-                let array = $ARRAY.as_any().downcast_ref::<Date64Array>().unwrap();
-                Ok($FN(array)?)
+            ScalarValue::TimestampMillisecond(v) => ScalarValue::TimestampMillisecond(
+                v.map(f(&TimeUnit::Millisecond)).transpose()?,
+            ),
+            ScalarValue::TimestampMicrosecond(v) => ScalarValue::TimestampMicrosecond(
+                v.map(f(&TimeUnit::Microsecond)).transpose()?,
+            ),
+            ScalarValue::TimestampNanosecond(v) => ScalarValue::TimestampNanosecond(
+                v.map(f(&TimeUnit::Nanosecond)).transpose()?,
+            ),
+            other => {
+                return Err(DataFusionError::Execution(format!(
+                    "Second argument of `date_bin` must be a timestamp, got {}",
+                    other.get_datatype()
+                )))
             }
-            DataType::Timestamp(time_unit, None) => match time_unit {
-                TimeUnit::Second => {
-                    let array = $ARRAY
-                        .as_any()
-                        .downcast_ref::<TimestampSecondArray>()
-                        .unwrap();
-                    Ok($FN(array)?)
-                }
-                TimeUnit::Millisecond => {
-                    let array = $ARRAY
-                        .as_any()
-                        .downcast_ref::<TimestampMillisecondArray>()
-                        .unwrap();
-                    Ok($FN(array)?)
-                }
-                TimeUnit::Microsecond => {
-                    let array = $ARRAY
-                        .as_any()
-                        .downcast_ref::<TimestampMicrosecondArray>()
-                        .unwrap();
-                    Ok($FN(array)?)
+        }),
+        ColumnarValue::Array(array) => {
+            let array: ArrayRef = match array.data_type() {
+                DataType::Timestamp(unit, tz) => {
+                    let f = f(unit);
+                    match unit {
+                        TimeUnit::Second => Arc::new(TimestampSecondArray::from_opt_vec(
+                            date_trunc_array!(array, TimestampSecondArray, f),
+                            tz.clone(),
+                        )),
+                        TimeUnit::Millisecond => {
+                            Arc::new(TimestampMillisecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampMillisecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                        TimeUnit::Microsecond => {
+                            Arc::new(TimestampMicrosecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampMicrosecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                        TimeUnit::Nanosecond => {
+                            Arc::new(TimestampNanosecondArray::from_opt_vec(
+                                date_trunc_array!(array, TimestampNanosecondArray, f),
+                                tz.clone(),
+                            ))
+                        }
+                    }
                 }
-                TimeUnit::Nanosecond => {
-                    let array = $ARRAY
-                        .as_any()
-                        .downcast_ref::<TimestampNanosecondArray>()
-                        .unwrap();
-                    Ok($FN(array)?)
+                other => {
+                    return Err(DataFusionError::Execution(format!(
+                        "Second argument of `date_bin` must be a timestamp, got {}",
+                        other
+                    )))
                 }
-            },
-            datatype => Err(DataFusionError::Internal(format!(
-                "Extract does not support datatype {:?}",
-                datatype
-            ))),
+            };
+            ColumnarValue::Array(array)
+        }
+    })
+}
+
+/// Applies `$FN` to the datetime of every non-null value of `$ARRAY`, a
+/// `$ARRAY_TYPE` whose values are converted with `$TO_DATETIME`
+macro_rules! extract_date_part {
+    ($ARRAY:expr, $ARRAY_TYPE:ident, $TO_DATETIME:expr, $FN:expr) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        Int64Array::from(
+            array
+                .iter()
+                .map(|v| v.map(|v| $FN(&$TO_DATETIME(v))))
+                .collect::<Vec<_>>(),
+        )
+    }};
+}
+
+/// Returns the function extracting the field `date_part` from a datetime.
+///
+/// Sub-second fields include the seconds, e.g. `millisecond` of `00:00:12.345`
+/// is `12345`, and `epoch` is the number of whole seconds since the unix epoch.
+fn date_part_fn(date_part: &str) -> Result<fn(&NaiveDateTime) -> i64> {
+    let f: fn(&NaiveDateTime) -> i64 = match date_part.to_lowercase().as_str() {
+        "year" => |d| d.year() as i64,
+        "quarter" => |d| (d.month0() / 3 + 1) as i64,
+        "month" => |d| d.month() as i64,
+        "week" => |d| d.iso_week().week() as i64,
+        "day" => |d| d.day() as i64,
+        "dow" => |d| d.weekday().num_days_from_sunday() as i64,
+        "doy" => |d| d.ordinal() as i64,
+        "hour" => |d| d.hour() as i64,
+        "minute" => |d| d.minute() as i64,
+        "second" => |d| d.second() as i64,
+        "millisecond" => {
+            |d| d.second() as i64 * 1_000 + d.nanosecond() as i64 / 1_000_000
+        }
+        "microsecond" => {
+            |d| d.second() as i64 * 1_000_000 + d.nanosecond() as i64 / 1_000
+        }
+        "nanosecond" => |d| d.second() as i64 * 1_000_000_000 + d.nanosecond() as i64,
+        "epoch" => |d| d.timestamp(),
+        _ => {
+            return Err(DataFusionError::Execution(format!(
+                "Date part '{}' not supported",
+                date_part
+            )))
         }
     };
+    Ok(f)
 }
 
 /// DATE_PART SQL function
+///
+/// Supports the fields `year`, `quarter`, `month`, `week` (ISO 8601), `day`,
+/// `dow` (0 is Sunday), `doy`, `hour`, `minute`, `second`, `millisecond`,
+/// `microsecond`, `nanosecond` and `epoch` of dates and timestamps.
 pub fn date_part(args: &[ColumnarValue]) -> Result<ColumnarValue> {
     if args.len() != 2 {
         return Err(DataFusionError::Execution(
@@ -429,6 +757,7 @@ pub fn date_part(args: &[ColumnarValue]) -> Result<ColumnarValue> {
             "First argument of `DATE_PART` must be non-null scalar Utf8".to_string(),
         ));
     };
+    let f = date_part_fn(date_part)?;
 
     let is_scalar = matches!(array, ColumnarValue::Scalar(_));
 
@@ -437,14 +766,41 @@ pub fn date_part(args: &[ColumnarValue]) -> Result<ColumnarValue> {
         ColumnarValue::Scalar(scalar) => scalar.to_array(),
     };
 
-    let arr = match date_part.to_lowercase().as_str() {
-        "hour" => extract_date_part!(array, temporal::hour),
-        "year" => extract_date_part!(array, temporal::year),
-        _ => Err(DataFusionError::Execution(format!(
-            "Date part '{}' not supported",
-            date_part
-        ))),
-    }?;
+    let arr = match array.data_type() {
+        DataType::Date32 => {
+            extract_date_part!(array, Date32Array, date32_to_datetime, f)
+        }
+        DataType::Date64 => {
+            extract_date_part!(array, Date64Array, date64_to_datetime, f)
+        }
+        DataType::Timestamp(TimeUnit::Second, None) => {
+            extract_date_part!(array, TimestampSecondArray, timestamp_s_to_datetime, f)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, None) => extract_date_part!(
+            array,
+            TimestampMillisecondArray,
+            timestamp_ms_to_datetime,
+            f
+        ),
+        DataType::Timestamp(TimeUnit::Microsecond, None) => extract_date_part!(
+            array,
+            TimestampMicrosecondArray,
+            timestamp_us_to_datetime,
+            f
+        ),
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => extract_date_part!(
+            array,
+            TimestampNanosecondArray,
+            timestamp_ns_to_datetime,
+            f
+        ),
+        datatype => {
+            return Err(DataFusionError::Internal(format!(
+                "Extract does not support datatype {:?}",
+                datatype
+            )))
+        }
+    };
 
     Ok(if is_scalar {
         ColumnarValue::Scalar(ScalarValue::try_from_array(
@@ -524,6 +880,11 @@ mod tests {
                 "month",
                 "2020-09-01T00:00:00.000000Z",
             ),
+            (
+                "2020-09-08T13:42:29.190855Z",
+                "quarter",
+                "2020-07-01T00:00:00.000000Z",
+            ),
             (
                 "2020-09-08T13:42:29.190855Z",
                 "year",
@@ -544,11 +905,215 @@ mod tests {
         cases.iter().for_each(|(original, granularity, expected)| {
             let original = string_to_timestamp_nanos(original).unwrap();
             let expected = string_to_timestamp_nanos(expected).unwrap();
-            let result = date_trunc_single(granularity, original).unwrap();
+            let result =
+                date_trunc_with_offset(granularity, original, &FixedOffset::east(0))
+                    .unwrap();
             assert_eq!(result, expected);
         });
     }
 
+    #[test]
+    fn date_trunc_timezone() -> Result<()> {
+        let ts = string_to_timestamp_nanos("2020-09-08T23:42:29.190855Z")?;
+        let array = TimestampNanosecondArray::from_opt_vec(
+            vec![Some(ts), None],
+            Some("+02:00".to_string()),
+        );
+        let args = vec![
+            ColumnarValue::Scalar(ScalarValue::Utf8(Some("DAY".to_string()))),
+            ColumnarValue::Array(Arc::new(array)),
+        ];
+        let result = match date_trunc(&args)? {
+            ColumnarValue::Array(array) => array,
+            _ => panic!("Expected an array"),
+        };
+        assert_eq!(
+            result.data_type(),
+            &DataType::Timestamp(TimeUnit::Nanosecond, Some("+02:00".to_string()))
+        );
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        // 23:42 UTC is 01:42 on the next day in +02:00
+        assert_eq!(
+            result.value(0),
+            string_to_timestamp_nanos("2020-09-08T22:00:00Z")?
+        );
+        assert!(result.is_null(1));
+
+        assert_eq!(parse_timezone("UTC")?, FixedOffset::east(0));
+        assert_eq!(parse_timezone("-0530")?, FixedOffset::west(5 * 3600 + 1800));
+        assert_eq!(parse_timezone("+02")?, FixedOffset::east(2 * 3600));
+        assert!(parse_timezone("Europe/Berlin").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn date_trunc_dates_and_units() -> Result<()> {
+        let granularity =
+            ColumnarValue::Scalar(ScalarValue::Utf8(Some("month".to_string())));
+
+        // 2020-09-08 is 18513 days after the epoch, 2020-09-01 is 18506
+        let args = vec![
+            granularity.clone(),
+            ColumnarValue::Array(Arc::new(Date32Array::from(vec![Some(18513)]))),
+        ];
+        match date_trunc(&args)? {
+            ColumnarValue::Array(array) => {
+                let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
+                assert_eq!(array.value(0), 18506);
+            }
+            _ => panic!("Expected an array"),
+        }
+
+        let ts = string_to_timestamp_nanos("2020-09-08T13:42:29Z")? / 1_000_000_000;
+        let expected = string_to_timestamp_nanos("2020-09-01T00:00:00Z")? / 1_000_000_000;
+        let args = vec![
+            granularity,
+            ColumnarValue::Scalar(ScalarValue::TimestampSecond(Some(ts))),
+        ];
+        match date_trunc(&args)? {
+            ColumnarValue::Scalar(ScalarValue::TimestampSecond(Some(v))) => {
+                assert_eq!(v, expected)
+            }
+            _ => panic!("Expected a TimestampSecond scalar"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn date_bin_test() -> Result<()> {
+        // 15 minutes
+        let stride =
+            ColumnarValue::Scalar(ScalarValue::IntervalDayTime(Some(15 * 60 * 1000)));
+        let array = TimestampNanosecondArray::from_opt_vec(
+            vec![
+                Some(string_to_timestamp_nanos("2020-09-08T13:42:29.190855Z")?),
+                Some(string_to_timestamp_nanos("2020-09-08T13:45:00Z")?),
+                None,
+                Some(string_to_timestamp_nanos("1969-12-31T23:59:59Z")?),
+            ],
+            None,
+        );
+        let args = vec![stride.clone(), ColumnarValue::Array(Arc::new(array))];
+        let result = match date_bin(&args)? {
+            ColumnarValue::Array(array) => array,
+            _ => panic!("Expected an array"),
+        };
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(
+            result.value(0),
+            string_to_timestamp_nanos("2020-09-08T13:30:00Z")?
+        );
+        assert_eq!(
+            result.value(1),
+            string_to_timestamp_nanos("2020-09-08T13:45:00Z")?
+        );
+        assert!(result.is_null(2));
+        assert_eq!(
+            result.value(3),
+            string_to_timestamp_nanos("1969-12-31T23:45:00Z")?
+        );
+
+        // buckets aligned to an origin of 00:05
+        let origin = string_to_timestamp_nanos("2020-01-01T00:05:00Z")? / 1_000;
+        let ts = string_to_timestamp_nanos("2020-09-08T13:42:29Z")? / 1_000_000_000;
+        let expected = string_to_timestamp_nanos("2020-09-08T13:35:00Z")? / 1_000_000_000;
+        let args = vec![
+            stride,
+            ColumnarValue::Scalar(ScalarValue::TimestampSecond(Some(ts))),
+            ColumnarValue::Scalar(ScalarValue::TimestampMicrosecond(Some(origin))),
+        ];
+        match date_bin(&args)? {
+            ColumnarValue::Scalar(ScalarValue::TimestampSecond(Some(v))) => {
+                assert_eq!(v, expected)
+            }
+            _ => panic!("Expected a TimestampSecond scalar"),
+        }
+
+        // a day
+        let args = vec![
+            ColumnarValue::Scalar(ScalarValue::IntervalDayTime(Some(1 << 32))),
+            ColumnarValue::Scalar(ScalarValue::TimestampMillisecond(Some(
+                string_to_timestamp_nanos("2020-09-08T13:42:29Z")? / 1_000_000,
+            ))),
+        ];
+        match date_bin(&args)? {
+            ColumnarValue::Scalar(ScalarValue::TimestampMillisecond(Some(v))) => {
+                assert_eq!(
+                    v,
+                    string_to_timestamp_nanos("2020-09-08T00:00:00Z")? / 1_000_000
+                )
+            }
+            _ => panic!("Expected a TimestampMillisecond scalar"),
+        }
+
+        let args = vec![
+            ColumnarValue::Scalar(ScalarValue::IntervalYearMonth(Some(1))),
+            ColumnarValue::Scalar(ScalarValue::TimestampSecond(Some(ts))),
+        ];
+        assert!(date_bin(&args).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn date_part_fields() -> Result<()> {
+        let ts = string_to_timestamp_nanos("2020-09-08T12:30:45.123456789Z")?;
+        let cases = vec![
+            ("year", 2020),
+            ("quarter", 3),
+            ("month", 9),
+            ("week", 37),
+            ("day", 8),
+            ("dow", 2),
+            ("doy", 252),
+            ("hour", 12),
+            ("minute", 30),
+            ("second", 45),
+            ("millisecond", 45_123),
+            ("microsecond", 45_123_456),
+            ("nanosecond", 45_123_456_789),
+            ("epoch", 1_599_568_245),
+        ];
+        for (field, expected) in cases {
+            let args = vec![
+                ColumnarValue::Scalar(ScalarValue::Utf8(Some(field.to_string()))),
+                ColumnarValue::Array(Arc::new(TimestampNanosecondArray::from_opt_vec(
+                    vec![Some(ts), None],
+                    None,
+                ))),
+            ];
+            let result = match date_part(&args)? {
+                ColumnarValue::Array(array) => array,
+                _ => panic!("Expected an array"),
+            };
+            let result = result.as_any().downcast_ref::<Int64Array>().unwrap();
+            assert_eq!(result.value(0), expected, "date part {}", field);
+            assert!(result.is_null(1));
+        }
+
+        // 2020-09-08 is 18513 days after the epoch
+        let args = vec![
+            ColumnarValue::Scalar(ScalarValue::Utf8(Some("DOY".to_string()))),
+            ColumnarValue::Scalar(ScalarValue::Date32(Some(18513))),
+        ];
+        match date_part(&args)? {
+            ColumnarValue::Scalar(ScalarValue::Int64(Some(v))) => assert_eq!(v, 252),
+            _ => panic!("Expected an Int64 scalar"),
+        }
+
+        let args = vec![
+            ColumnarValue::Scalar(ScalarValue::Utf8(Some("century".to_string()))),
+            ColumnarValue::Scalar(ScalarValue::Date32(Some(18513))),
+        ];
+        assert!(date_part(&args).is_err());
+        Ok(())
+    }
+
     #[test]
     fn to_timestamp_invalid_input_type() -> Result<()> {
         // pass the wrong type of input array to to_timestamp and test
@@ -0,0 +1,515 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the deduplication operator, which keeps one row per distinct value
+//! of a subset of the columns
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::coalesce_batches::concat_batches;
+use super::group_scalar::GroupByScalar;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::DuplicateKeep;
+use crate::physical_plan::{
+    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+};
+use crate::scalar::ScalarValue;
+use arrow::array::UInt32Array;
+use arrow::compute::take;
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+
+use futures::stream::{Stream, StreamExt};
+
+/// The values of the key columns of a row, null values are `None`
+type Key = Vec<Option<GroupByScalar>>;
+
+/// DeduplicateExec keeps the first or the last row of every distinct value
+/// of its key columns, in the order of its input. If the input is sorted by
+/// the key columns, duplicates are adjacent and the rows are deduplicated
+/// while streaming. Otherwise the keys are remembered in a hash set, which
+/// also streams when keeping the first rows, but has to consume the whole
+/// input when keeping the last rows.
+#[derive(Debug)]
+pub struct DeduplicateExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The indices of the key columns
+    key_columns: Vec<usize>,
+    /// Which row of duplicates to keep
+    keep: DuplicateKeep,
+    /// Whether the input is sorted by the key columns
+    sorted: bool,
+}
+
+impl DeduplicateExec {
+    /// Create a DeduplicateExec keeping one row of `input` per distinct
+    /// value of `key_columns`
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        key_columns: Vec<usize>,
+        keep: DuplicateKeep,
+    ) -> Result<Self> {
+        if let Some(i) = key_columns
+            .iter()
+            .find(|i| **i >= input.schema().fields().len())
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Deduplication column {} is out of bounds",
+                i
+            )));
+        }
+        Ok(Self {
+            input,
+            key_columns,
+            keep,
+            sorted: false,
+        })
+    }
+
+    /// Declare whether the input is sorted by the key columns, so that
+    /// duplicates are adjacent
+    pub fn with_sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The indices of the key columns
+    pub fn key_columns(&self) -> &[usize] {
+        &self.key_columns
+    }
+
+    /// Which row of duplicates is kept
+    pub fn keep(&self) -> DuplicateKeep {
+        self.keep
+    }
+
+    /// Whether the input is sorted by the key columns
+    pub fn sorted(&self) -> bool {
+        self.sorted
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for DeduplicateExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        // duplicates may be in any partition
+        Distribution::SinglePartition
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        if children[0] && !self.sorted && self.keep == DuplicateKeep::Last {
+            Err(DataFusionError::Plan(
+                "Keeping the last duplicates of an unsorted unbounded input is not supported"
+                    .to_string(),
+            ))
+        } else {
+            Ok(children[0])
+        }
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(
+                DeduplicateExec::try_new(
+                    children[0].clone(),
+                    self.key_columns.clone(),
+                    self.keep,
+                )?
+                .with_sorted(self.sorted),
+            )),
+            _ => Err(DataFusionError::Internal(
+                "DeduplicateExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "DeduplicateExec invalid partition {}",
+                partition
+            )));
+        }
+
+        // DeduplicateExec requires a single input partition
+        if 1 != self.input.output_partitioning().partition_count() {
+            return Err(DataFusionError::Internal(
+                "DeduplicateExec requires a single input partition".to_owned(),
+            ));
+        }
+
+        Ok(Box::pin(DeduplicateStream {
+            schema: self.schema(),
+            input: Some(self.input.execute(0).await?),
+            key_columns: self.key_columns.clone(),
+            keep: self.keep,
+            sorted: self.sorted,
+            previous: None,
+            seen: HashSet::new(),
+            batches: vec![],
+            last_rows: HashMap::new(),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let keys = self
+                    .key_columns
+                    .iter()
+                    .map(|i| self.schema().field(*i).name().clone())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "DeduplicateExec: keys=[{}], keep={:?}, sorted={}",
+                    keys.join(", "),
+                    self.keep,
+                    self.sorted
+                )
+            }
+        }
+    }
+}
+
+struct DeduplicateStream {
+    schema: SchemaRef,
+    /// The input, until it is exhausted
+    input: Option<SendableRecordBatchStream>,
+    key_columns: Vec<usize>,
+    keep: DuplicateKeep,
+    sorted: bool,
+    /// For sorted inputs, the key of the last row of the previous batch and
+    /// when keeping the last rows, that row itself
+    previous: Option<(Key, Option<RecordBatch>)>,
+    /// For unsorted inputs when keeping the first rows, the keys seen so far
+    seen: HashSet<Key>,
+    /// For unsorted inputs when keeping the last rows, the input batches
+    batches: Vec<RecordBatch>,
+    /// For unsorted inputs when keeping the last rows, the batch and row of
+    /// the last row of every key
+    last_rows: HashMap<Key, (usize, usize)>,
+}
+
+impl DeduplicateStream {
+    /// The keys of the rows of `batch`
+    fn keys(&self, batch: &RecordBatch) -> Result<Vec<Key>> {
+        (0..batch.num_rows())
+            .map(|row| {
+                self.key_columns
+                    .iter()
+                    .map(|i| {
+                        let value = ScalarValue::try_from_array(batch.column(*i), row)?;
+                        if value.is_null() {
+                            Ok(None)
+                        } else {
+                            GroupByScalar::try_from(&value).map(Some)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Deduplicates the rows of `batch`, returning the rows to output so far
+    fn process(&mut self, batch: RecordBatch) -> Result<Option<RecordBatch>> {
+        if batch.num_rows() == 0 {
+            return Ok(None);
+        }
+        let keys = self.keys(&batch)?;
+        match (self.sorted, self.keep) {
+            (true, DuplicateKeep::First) => {
+                let previous = self.previous.take().map(|(key, _)| key);
+                let rows = (0..keys.len())
+                    .filter(|row| {
+                        let before = if *row == 0 {
+                            previous.as_ref()
+                        } else {
+                            Some(&keys[row - 1])
+                        };
+                        before != Some(&keys[*row])
+                    })
+                    .collect::<Vec<_>>();
+                self.previous = keys.last().cloned().map(|key| (key, None));
+                take_rows(&batch, rows).map(Some)
+            }
+            (true, DuplicateKeep::Last) => {
+                // the last row of the batch is only known to be the last of
+                // its key once the next batch starts with another key
+                let mut output = vec![];
+                if let Some((key, Some(row))) = self.previous.take() {
+                    if key != keys[0] {
+                        output.push(row);
+                    }
+                }
+                let rows = (0..keys.len() - 1)
+                    .filter(|row| keys[*row] != keys[row + 1])
+                    .collect::<Vec<_>>();
+                output.push(take_rows(&batch, rows)?);
+                let last = batch.num_rows() - 1;
+                let last_row = RecordBatch::try_new(
+                    batch.schema(),
+                    batch
+                        .columns()
+                        .iter()
+                        .map(|column| column.slice(last, 1))
+                        .collect(),
+                )?;
+                self.previous = Some((keys[last].clone(), Some(last_row)));
+                let num_rows = output.iter().map(|batch| batch.num_rows()).sum();
+                Ok(Some(concat_batches(&self.schema, &output, num_rows)?))
+            }
+            (false, DuplicateKeep::First) => {
+                let mut rows = vec![];
+                for (row, key) in keys.into_iter().enumerate() {
+                    if self.seen.insert(key) {
+                        rows.push(row);
+                    }
+                }
+                take_rows(&batch, rows).map(Some)
+            }
+            (false, DuplicateKeep::Last) => {
+                let index = self.batches.len();
+                for (row, key) in keys.into_iter().enumerate() {
+                    self.last_rows.insert(key, (index, row));
+                }
+                self.batches.push(batch);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the rows that are only known to be output once the input is
+    /// exhausted
+    fn finish(&mut self) -> Result<Option<RecordBatch>> {
+        if let Some((_, Some(row))) = self.previous.take() {
+            return Ok(Some(row));
+        }
+        if self.last_rows.is_empty() {
+            return Ok(None);
+        }
+
+        // output the last rows in the order of the input
+        let mut positions = self.last_rows.drain().map(|(_, p)| p).collect::<Vec<_>>();
+        positions.sort_unstable();
+        let mut output = vec![];
+        for (index, batch) in self.batches.iter().enumerate() {
+            let rows = positions
+                .iter()
+                .filter(|(i, _)| *i == index)
+                .map(|(_, row)| *row)
+                .collect::<Vec<_>>();
+            if !rows.is_empty() {
+                output.push(take_rows(batch, rows)?);
+            }
+        }
+        self.batches.clear();
+        let num_rows = output.iter().map(|batch| batch.num_rows()).sum();
+        Ok(Some(concat_batches(&self.schema, &output, num_rows)?))
+    }
+}
+
+/// The rows `rows` of `batch`
+fn take_rows(batch: &RecordBatch, rows: Vec<usize>) -> Result<RecordBatch> {
+    if rows.len() == batch.num_rows() {
+        return Ok(batch.clone());
+    }
+    let indices =
+        UInt32Array::from(rows.into_iter().map(|row| row as u32).collect::<Vec<_>>());
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| take(column.as_ref(), &indices, None))
+        .collect::<ArrowResult<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(batch.schema(), columns)?)
+}
+
+impl Stream for DeduplicateStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        loop {
+            let input = match self.input.as_mut() {
+                Some(input) => input,
+                None => return Poll::Ready(None),
+            };
+            let result = match input.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(batch))) => self.process(batch),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => {
+                    self.input = None;
+                    self.finish()
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            match result {
+                Ok(Some(batch)) if batch.num_rows() > 0 => {
+                    return Poll::Ready(Some(Ok(batch)))
+                }
+                Ok(_) => continue,
+                Err(e) => return Poll::Ready(Some(Err(e.into_arrow_external_error()))),
+            }
+        }
+    }
+}
+
+impl RecordBatchStream for DeduplicateStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// The rows of `keys` and `values` split into batches of two rows
+    fn input(
+        keys: Vec<Option<&str>>,
+        values: Vec<i32>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Utf8, true),
+            Field::new("v", DataType::Int32, false),
+        ]));
+        let batches = keys
+            .chunks(2)
+            .zip(values.chunks(2))
+            .map(|(keys, values)| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(StringArray::from(keys.to_vec())),
+                        Arc::new(Int32Array::from(values.to_vec())),
+                    ],
+                )
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+        Ok(Arc::new(MemoryExec::try_new(&[batches], schema, None)?))
+    }
+
+    async fn deduplicate(
+        input: Arc<dyn ExecutionPlan>,
+        keep: DuplicateKeep,
+        sorted: bool,
+    ) -> Result<Vec<i32>> {
+        let exec = DeduplicateExec::try_new(input, vec![0], keep)?.with_sorted(sorted);
+        let mut values = vec![];
+        for batch in common::collect(exec.execute(0).await?).await? {
+            let column = batch
+                .column(1)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            values.extend(column.iter().map(|v| v.unwrap()));
+        }
+        Ok(values)
+    }
+
+    #[tokio::test]
+    async fn deduplicate_unsorted() -> Result<()> {
+        let keys = vec![
+            Some("a"),
+            Some("b"),
+            Some("a"),
+            None,
+            Some("c"),
+            None,
+            Some("b"),
+        ];
+        let values = (0..7).collect::<Vec<_>>();
+
+        let first = input(keys.clone(), values.clone())?;
+        let first = deduplicate(first, DuplicateKeep::First, false).await?;
+        assert_eq!(first, vec![0, 1, 3, 4]);
+
+        let last = input(keys, values)?;
+        let last = deduplicate(last, DuplicateKeep::Last, false).await?;
+        assert_eq!(last, vec![2, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deduplicate_sorted() -> Result<()> {
+        // duplicates span batches of two rows
+        let keys = vec![
+            None,
+            Some("a"),
+            Some("a"),
+            Some("a"),
+            Some("b"),
+            Some("c"),
+            Some("c"),
+        ];
+        let values = (0..7).collect::<Vec<_>>();
+
+        let first = input(keys.clone(), values.clone())?;
+        let first = deduplicate(first, DuplicateKeep::First, true).await?;
+        assert_eq!(first, vec![0, 1, 4, 5]);
+
+        let last = input(keys, values)?;
+        let last = deduplicate(last, DuplicateKeep::Last, true).await?;
+        assert_eq!(last, vec![0, 3, 4, 6]);
+        Ok(())
+    }
+}
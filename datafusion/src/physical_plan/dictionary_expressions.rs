@@ -0,0 +1,248 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Evaluation of functions of dictionary-encoded arrays on the dictionary
+//! values, i.e. once per distinct value rather than once per row
+
+use std::sync::Arc;
+
+use crate::error::{DataFusionError, Result};
+use arrow::array::*;
+use arrow::compute::{cast, take};
+use arrow::datatypes::*;
+
+use super::functions::ScalarFunctionImplementation;
+use super::ColumnarValue;
+
+fn typed_dictionary_values<K: ArrowDictionaryKeyType>(array: &ArrayRef) -> ArrayRef {
+    let dictionary = array.as_any().downcast_ref::<DictionaryArray<K>>().unwrap();
+    dictionary.values()
+}
+
+fn typed_take_keys<K: ArrowDictionaryKeyType>(
+    array: &ArrayRef,
+    values: &ArrayRef,
+) -> Result<ArrayRef> {
+    let dictionary = array.as_any().downcast_ref::<DictionaryArray<K>>().unwrap();
+    let keys = dictionary.keys_array();
+    let indices = (0..keys.len())
+        .map(|i| {
+            if keys.is_null(i) {
+                return Ok(None);
+            }
+            keys.value(i)
+                .to_usize()
+                .map(|key| Some(key as u32))
+                .ok_or_else(|| {
+                    DataFusionError::Internal(format!(
+                        "Can not convert index to usize in dictionary of type {:?}",
+                        keys.data_type()
+                    ))
+                })
+        })
+        .collect::<Result<UInt32Array>>()?;
+    Ok(take(values.as_ref(), &indices, None)?)
+}
+
+macro_rules! dictionary_dispatch {
+    ($ARRAY:expr, $FUNC:ident $(, $ARGS:expr)*) => {
+        match $ARRAY.data_type() {
+            DataType::Dictionary(key_type, _) => match key_type.as_ref() {
+                DataType::Int8 => $FUNC::<Int8Type>($ARRAY $(, $ARGS)*),
+                DataType::Int16 => $FUNC::<Int16Type>($ARRAY $(, $ARGS)*),
+                DataType::Int32 => $FUNC::<Int32Type>($ARRAY $(, $ARGS)*),
+                DataType::Int64 => $FUNC::<Int64Type>($ARRAY $(, $ARGS)*),
+                DataType::UInt8 => $FUNC::<UInt8Type>($ARRAY $(, $ARGS)*),
+                DataType::UInt16 => $FUNC::<UInt16Type>($ARRAY $(, $ARGS)*),
+                DataType::UInt32 => $FUNC::<UInt32Type>($ARRAY $(, $ARGS)*),
+                DataType::UInt64 => $FUNC::<UInt64Type>($ARRAY $(, $ARGS)*),
+                other => {
+                    return Err(DataFusionError::Internal(format!(
+                        "Unsupported dictionary key type {:?}",
+                        other
+                    )))
+                }
+            },
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "Expected a dictionary, got {:?}",
+                    other
+                )))
+            }
+        }
+    };
+}
+
+/// Returns the values of the dictionary-encoded `array`
+pub fn dictionary_values(array: &ArrayRef) -> Result<ArrayRef> {
+    Ok(dictionary_dispatch!(array, typed_dictionary_values))
+}
+
+/// Returns for each row of the dictionary-encoded `array` the item of
+/// `values` at the row's key, i.e. decodes the dictionary with `values` in
+/// place of its own values
+pub fn take_keys(array: &ArrayRef, values: &ArrayRef) -> Result<ArrayRef> {
+    dictionary_dispatch!(array, typed_take_keys, values)
+}
+
+/// Returns the dictionary-encoded `array` with `values` in place of its own
+/// values, which must have as many items as its own values
+fn replace_values(array: &ArrayRef, values: &ArrayRef) -> Result<ArrayRef> {
+    let key_type = match array.data_type() {
+        DataType::Dictionary(key_type, _) => key_type.clone(),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Expected a dictionary, got {:?}",
+                other
+            )))
+        }
+    };
+    let data = array.data();
+    let data_type = DataType::Dictionary(key_type, Box::new(values.data_type().clone()));
+    let mut builder = ArrayData::builder(data_type)
+        .len(data.len())
+        .offset(data.offset())
+        .buffers(data.buffers().to_vec())
+        .add_child_data(values.data().clone());
+    if let Some(nulls) = data.null_buffer() {
+        builder = builder.null_bit_buffer(nulls.clone());
+    }
+    Ok(make_array(builder.build()))
+}
+
+/// Returns the type of the result of a function of a dictionary-encoded
+/// argument with keys of `key_type`, whose result for the dictionary values
+/// is of `return_type`. Strings stay dictionary-encoded.
+pub fn dictionary_return_type(key_type: &DataType, return_type: DataType) -> DataType {
+    match return_type {
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            DataType::Dictionary(Box::new(key_type.clone()), Box::new(return_type))
+        }
+        return_type => return_type,
+    }
+}
+
+/// Wraps `fun` to accept a dictionary-encoded first argument, whose values are
+/// cast to `value_type`, and to return `return_type`. If the other arguments are
+/// scalars, `fun` is evaluated once per dictionary value, otherwise once per
+/// row of the decoded dictionary.
+pub fn dictionary_function(
+    fun: ScalarFunctionImplementation,
+    value_type: DataType,
+    return_type: DataType,
+) -> ScalarFunctionImplementation {
+    Arc::new(move |args: &[ColumnarValue]| {
+        let dictionary = match &args[0] {
+            ColumnarValue::Array(array)
+                if matches!(array.data_type(), DataType::Dictionary(_, _)) =>
+            {
+                array
+            }
+            _ => return fun(args),
+        };
+        let constant = args[1..]
+            .iter()
+            .all(|arg| matches!(arg, ColumnarValue::Scalar(_)));
+        let result = if constant {
+            let values = cast(&dictionary_values(dictionary)?, &value_type)?;
+            let mut value_args = vec![ColumnarValue::Array(values.clone())];
+            value_args.extend(args[1..].iter().cloned());
+            let result = fun(&value_args)?.into_array(values.len());
+            // null values referenced by valid keys would not be seen as nulls
+            if matches!(return_type, DataType::Dictionary(_, _))
+                && result.null_count() == 0
+            {
+                replace_values(dictionary, &result)?
+            } else {
+                cast(&take_keys(dictionary, &result)?, &return_type)?
+            }
+        } else {
+            let mut args = args.to_vec();
+            args[0] = ColumnarValue::Array(cast(dictionary, &value_type)?);
+            let result = fun(&args)?.into_array(dictionary.len());
+            cast(&result, &return_type)?
+        };
+        Ok(ColumnarValue::Array(result))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::functions::make_scalar_function;
+    use crate::physical_plan::string_expressions;
+    use crate::scalar::ScalarValue;
+
+    fn dictionary() -> ArrayRef {
+        let array: DictionaryArray<Int32Type> =
+            vec![Some("a"), Some("bb"), None, Some("a")]
+                .into_iter()
+                .collect();
+        Arc::new(array)
+    }
+
+    #[test]
+    fn test_take_keys() -> Result<()> {
+        let values = dictionary_values(&dictionary())?;
+        assert_eq!(values.len(), 2);
+        let result = take_keys(&dictionary(), &values)?;
+        let expected = StringArray::from(vec![Some("a"), Some("bb"), None, Some("a")]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_function() -> Result<()> {
+        let fun: ScalarFunctionImplementation = Arc::new(string_expressions::upper);
+        let return_type = dictionary_return_type(&DataType::Int32, DataType::Utf8);
+        let fun = dictionary_function(fun, DataType::Utf8, return_type.clone());
+        let result = fun(&[ColumnarValue::Array(dictionary())])?.into_array(4);
+        assert_eq!(result.data_type(), &return_type);
+        let result = cast(&result, &DataType::Utf8)?;
+        let expected = StringArray::from(vec![Some("A"), Some("BB"), None, Some("A")]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_function_of_arrays() -> Result<()> {
+        let fun = make_scalar_function(string_expressions::starts_with::<i32>);
+        let fun = dictionary_function(fun, DataType::Utf8, DataType::Boolean);
+
+        // evaluated on the dictionary values
+        let result = fun(&[
+            ColumnarValue::Array(dictionary()),
+            ColumnarValue::Scalar(ScalarValue::Utf8(Some("b".to_string()))),
+        ])?
+        .into_array(4);
+        let expected =
+            BooleanArray::from(vec![Some(false), Some(true), None, Some(false)]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        // evaluated on the decoded dictionary
+        let prefixes: ArrayRef = Arc::new(StringArray::from(vec!["a", "b", "x", "b"]));
+        let result = fun(&[
+            ColumnarValue::Array(dictionary()),
+            ColumnarValue::Array(prefixes),
+        ])?
+        .into_array(4);
+        let expected =
+            BooleanArray::from(vec![Some(true), Some(true), None, Some(false)]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+}
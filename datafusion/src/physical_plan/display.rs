@@ -21,7 +21,9 @@
 
 use std::fmt;
 
-use super::{accept, ExecutionPlan, ExecutionPlanVisitor};
+use crate::logical_plan::display::GraphvizBuilder;
+
+use super::{accept, ExecutionPlan, ExecutionPlanVisitor, MetricsSet};
 
 /// Options for controlling how each [`ExecutionPlan`] should format itself
 #[derive(Debug, Clone, Copy)]
@@ -33,13 +35,29 @@ pub enum DisplayFormatType {
 /// Wraps an `ExecutionPlan` with various ways to display this plan
 pub struct DisplayableExecutionPlan<'a> {
     inner: &'a dyn ExecutionPlan,
+    /// If true, includes the metrics of each node
+    show_metrics: bool,
 }
 
 impl<'a> DisplayableExecutionPlan<'a> {
     /// Create a wrapper around an [`'ExecutionPlan'] which can be
     /// pretty printed in a variety of ways
     pub fn new(inner: &'a dyn ExecutionPlan) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            show_metrics: false,
+        }
+    }
+
+    /// Create a wrapper around an [`'ExecutionPlan'] which can be
+    /// pretty printed in a variety of ways, including the
+    /// [`MetricsSet`] of each node. This is typically used after the
+    /// plan was executed, similar to `EXPLAIN ANALYZE`.
+    pub fn with_metrics(inner: &'a dyn ExecutionPlan) -> Self {
+        Self {
+            inner,
+            show_metrics: true,
+        }
     }
 
     /// Return a `format`able structure that produces a single line
@@ -53,12 +71,116 @@ impl<'a> DisplayableExecutionPlan<'a> {
     ///         CsvExec: source=...",
     /// ```
     pub fn indent(&self) -> impl fmt::Display + 'a {
-        struct Wrapper<'a>(&'a dyn ExecutionPlan);
+        struct Wrapper<'a> {
+            plan: &'a dyn ExecutionPlan,
+            show_metrics: bool,
+        }
         impl<'a> fmt::Display for Wrapper<'a> {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 let t = DisplayFormatType::Default;
-                let mut visitor = IndentVisitor { t, f, indent: 0 };
-                accept(self.0, &mut visitor)
+                let mut visitor = IndentVisitor {
+                    t,
+                    f,
+                    indent: 0,
+                    show_metrics: self.show_metrics,
+                };
+                accept(self.plan, &mut visitor)
+            }
+        }
+        Wrapper {
+            plan: self.inner,
+            show_metrics: self.show_metrics,
+        }
+    }
+
+    /// Return a `format`able structure that produces lines meant for
+    /// graphical display using the `DOT` language. This format can be
+    /// visualized using software from
+    /// [`graphviz`](https://graphviz.org/)
+    ///
+    /// ```text
+    /// // Begin DataFusion GraphViz Plan (see https://graphviz.org)
+    /// digraph {
+    ///   subgraph cluster_1
+    ///   {
+    ///     graph[label="ExecutionPlan"]
+    ///     2[shape=box label="ProjectionExec: expr=[a]"]
+    ///     3[shape=box label="FilterExec: a < 5"]
+    ///     2 -> 3 [arrowhead=none, arrowtail=normal, dir=back]
+    ///     ...
+    /// ```
+    pub fn graphviz(&self) -> impl fmt::Display + 'a {
+        struct Wrapper<'a> {
+            plan: &'a dyn ExecutionPlan,
+            show_metrics: bool,
+        }
+        impl<'a> fmt::Display for Wrapper<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                writeln!(
+                    f,
+                    "// Begin DataFusion GraphViz Plan (see https://graphviz.org)"
+                )?;
+                writeln!(f, "digraph {{")?;
+
+                let mut visitor = GraphvizVisitor {
+                    t: DisplayFormatType::Default,
+                    f,
+                    graphviz_builder: GraphvizBuilder::default(),
+                    show_metrics: self.show_metrics,
+                    parent_ids: vec![],
+                };
+                visitor
+                    .graphviz_builder
+                    .start_cluster(visitor.f, "ExecutionPlan")?;
+                accept(self.plan, &mut visitor)?;
+                visitor.graphviz_builder.end_cluster(visitor.f)?;
+
+                writeln!(f, "}}")?;
+                writeln!(f, "// End DataFusion GraphViz Plan")
+            }
+        }
+        Wrapper {
+            plan: self.inner,
+            show_metrics: self.show_metrics,
+        }
+    }
+
+    /// Return a `format`able structure that produces a machine
+    /// readable JSON tree, with one object per node:
+    ///
+    /// ```text
+    /// {"description":"FilterExec: a < 5","metrics":{"output_rows":2},"children":[...]}
+    /// ```
+    ///
+    /// The `metrics` member is only present when created with
+    /// [`DisplayableExecutionPlan::with_metrics`].
+    pub fn json(&self) -> impl fmt::Display + 'a {
+        struct Wrapper<'a> {
+            plan: &'a dyn ExecutionPlan,
+            show_metrics: bool,
+        }
+        impl<'a> fmt::Display for Wrapper<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_json_node(self.plan, self.show_metrics, f)
+            }
+        }
+        Wrapper {
+            plan: self.inner,
+            show_metrics: self.show_metrics,
+        }
+    }
+
+    /// Return a `format`able structure that produces a single line
+    /// for the wrapped node only, without its children.
+    ///
+    /// ```text
+    /// FilterExec: a < 5
+    /// ```
+    pub fn one_line(&self) -> impl fmt::Display + 'a {
+        struct Wrapper<'a>(&'a dyn ExecutionPlan);
+        impl<'a> fmt::Display for Wrapper<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt_as(DisplayFormatType::Default, f)
             }
         }
         Wrapper(self.inner)
@@ -73,6 +195,8 @@ struct IndentVisitor<'a, 'b> {
     f: &'a mut fmt::Formatter<'b>,
     ///with_schema: bool,
     indent: usize,
+    /// If true, appends the metrics of each node
+    show_metrics: bool,
 }
 
 impl<'a, 'b> ExecutionPlanVisitor for IndentVisitor<'a, 'b> {
@@ -83,6 +207,9 @@ impl<'a, 'b> ExecutionPlanVisitor for IndentVisitor<'a, 'b> {
     ) -> std::result::Result<bool, Self::Error> {
         write!(self.f, "{:indent$}", "", indent = self.indent * 2)?;
         plan.fmt_as(self.t, self.f)?;
+        if self.show_metrics {
+            write!(self.f, ", metrics=[{}]", DisplayMetrics(&plan.metrics()))?;
+        }
         writeln!(self.f)?;
         self.indent += 1;
         Ok(true)
@@ -93,3 +220,244 @@ impl<'a, 'b> ExecutionPlanVisitor for IndentVisitor<'a, 'b> {
         Ok(true)
     }
 }
+
+/// Formats plans using the `DOT` language, with one graph node per
+/// plan node.
+struct GraphvizVisitor<'a, 'b> {
+    /// How to format each node
+    t: DisplayFormatType,
+    /// Write to this formatter
+    f: &'a mut fmt::Formatter<'b>,
+    graphviz_builder: GraphvizBuilder,
+    /// If true, includes the metrics of each node in its label
+    show_metrics: bool,
+    /// Holds the ids (as generated from `graphviz_builder`) of all
+    /// parent nodes
+    parent_ids: Vec<usize>,
+}
+
+impl<'a, 'b> ExecutionPlanVisitor for GraphvizVisitor<'a, 'b> {
+    type Error = fmt::Error;
+
+    fn pre_visit(
+        &mut self,
+        plan: &dyn ExecutionPlan,
+    ) -> std::result::Result<bool, Self::Error> {
+        let id = self.graphviz_builder.next_id();
+
+        let mut label = DisplayAs(plan, self.t).to_string();
+        if self.show_metrics {
+            label = format!("{}\\nMetrics: [{}]", label, DisplayMetrics(&plan.metrics()));
+        }
+        writeln!(
+            self.f,
+            "    {}[shape=box label={}]",
+            id,
+            GraphvizBuilder::quoted(&label)
+        )?;
+
+        // Create an edge to our parent node, if any
+        if let Some(parent_id) = self.parent_ids.last() {
+            writeln!(
+                self.f,
+                "    {} -> {} [arrowhead=none, arrowtail=normal, dir=back]",
+                parent_id, id
+            )?;
+        }
+
+        self.parent_ids.push(id);
+        Ok(true)
+    }
+
+    fn post_visit(&mut self, _plan: &dyn ExecutionPlan) -> Result<bool, Self::Error> {
+        // always non-empty as pre_visit always pushes
+        self.parent_ids.pop().unwrap();
+        Ok(true)
+    }
+}
+
+/// Formats a single node using [`ExecutionPlan::fmt_as`]
+struct DisplayAs<'a>(&'a dyn ExecutionPlan, DisplayFormatType);
+
+impl<'a> fmt::Display for DisplayAs<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_as(self.1, f)
+    }
+}
+
+/// Formats metrics as `name1=value1, name2=value2`, ordered by name
+pub(crate) struct DisplayMetrics<'a>(pub(crate) &'a MetricsSet);
+
+impl<'a> fmt::Display for DisplayMetrics<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (name, metric)) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", name, metric.value())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `plan` and its children as a JSON object
+fn write_json_node(
+    plan: &dyn ExecutionPlan,
+    show_metrics: bool,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    write!(f, "{{\"description\":")?;
+    write_json_string(&DisplayAs(plan, DisplayFormatType::Default).to_string(), f)?;
+
+    if show_metrics {
+        write!(f, ",\"metrics\":{{")?;
+        for (idx, (name, metric)) in plan.metrics().iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write_json_string(name, f)?;
+            write!(f, ":{}", metric.value())?;
+        }
+        write!(f, "}}")?;
+    }
+
+    write!(f, ",\"children\":[")?;
+    for (idx, child) in plan.children().iter().enumerate() {
+        if idx > 0 {
+            write!(f, ",")?;
+        }
+        write_json_node(child.as_ref(), show_metrics, f)?;
+    }
+    write!(f, "]}}")
+}
+
+/// Writes `s` as a quoted and escaped JSON string
+fn write_json_string(s: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::error::Result;
+    use crate::physical_plan::expressions::{col, PhysicalSortExpr};
+    use crate::physical_plan::{
+        collect, displayable, memory::MemoryExec, sort::SortExec,
+    };
+    use crate::test;
+
+    fn sort_plan() -> Result<Arc<dyn ExecutionPlan>> {
+        let batch = test::make_partition(3);
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        let expr = vec![PhysicalSortExpr {
+            expr: col("i"),
+            options: Default::default(),
+        }];
+        Ok(Arc::new(SortExec::try_new(expr, input)?))
+    }
+
+    #[test]
+    fn display_graphviz() -> Result<()> {
+        let plan = sort_plan()?;
+        let graphviz = displayable(plan.as_ref()).graphviz().to_string();
+
+        let expected = vec![
+            "// Begin DataFusion GraphViz Plan (see https://graphviz.org)",
+            "digraph {",
+            "  subgraph cluster_1",
+            "  {",
+            "    graph[label=\"ExecutionPlan\"]",
+            "    2[shape=box label=\"SortExec: [i ASC]\"]",
+            "    3[shape=box label=\"MemoryExec: partitions=1, partition_sizes=[1]\"]",
+            "    2 -> 3 [arrowhead=none, arrowtail=normal, dir=back]",
+            "  }",
+            "}",
+            "// End DataFusion GraphViz Plan",
+        ];
+        let actual: Vec<&str> = graphviz.lines().collect();
+        assert_eq!(expected, actual, "\n{}", graphviz);
+        Ok(())
+    }
+
+    #[test]
+    fn display_json() -> Result<()> {
+        let plan = sort_plan()?;
+        let json = displayable(plan.as_ref()).json().to_string();
+
+        assert_eq!(
+            "{\"description\":\"SortExec: [i ASC]\",\"children\":[\
+             {\"description\":\"MemoryExec: partitions=1, partition_sizes=[1]\",\
+             \"children\":[]}]}",
+            json
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn display_with_metrics() -> Result<()> {
+        let plan = sort_plan()?;
+        collect(plan.clone()).await?;
+
+        let display = DisplayableExecutionPlan::with_metrics(plan.as_ref());
+
+        let indent = display.indent().to_string();
+        assert!(
+            indent.starts_with("SortExec: [i ASC], metrics=[elapsed_compute="),
+            "{}",
+            indent
+        );
+        assert!(
+            indent.contains("input_batches=1, output_rows=3"),
+            "{}",
+            indent
+        );
+        assert!(indent
+            .contains("  MemoryExec: partitions=1, partition_sizes=[1], metrics=[]"));
+
+        let graphviz = display.graphviz().to_string();
+        assert!(
+            graphviz.contains("label=\"SortExec: [i ASC]\\nMetrics: [elapsed_compute="),
+            "{}",
+            graphviz
+        );
+
+        let json = display.json().to_string();
+        assert!(
+            json.contains("\"input_batches\":1,\"output_rows\":3"),
+            "{}",
+            json
+        );
+        assert!(json.contains("\"metrics\":{},\"children\":[]"), "{}", json);
+        Ok(())
+    }
+
+    #[test]
+    fn json_string_escaping() {
+        struct Wrapper<'a>(&'a str);
+        impl<'a> fmt::Display for Wrapper<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_json_string(self.0, f)
+            }
+        }
+
+        assert_eq!(
+            r#""a \"b\" \\ c\n\u0001""#,
+            Wrapper("a \"b\" \\ c\n\u{1}").to_string()
+        );
+    }
+}
@@ -24,35 +24,25 @@ use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     memory::MemoryStream, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
 };
-use arrow::array::NullArray;
-use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use arrow::record_batch::RecordBatch;
+use arrow::datatypes::SchemaRef;
 
 use super::SendableRecordBatchStream;
 
 use async_trait::async_trait;
 
-/// Execution plan for empty relation (produces no rows)
+/// Execution plan for empty relation (produces no rows). See
+/// [`PlaceholderRowExec`](super::placeholder_row::PlaceholderRowExec) for
+/// the relation of a single row.
 #[derive(Debug)]
 pub struct EmptyExec {
-    /// Specifies whether this exec produces a row or not
-    produce_one_row: bool,
-    /// The schema for the produced row
+    /// The schema of the (empty) output
     schema: SchemaRef,
 }
 
 impl EmptyExec {
     /// Create a new EmptyExec
-    pub fn new(produce_one_row: bool, schema: SchemaRef) -> Self {
-        EmptyExec {
-            produce_one_row,
-            schema,
-        }
-    }
-
-    /// Specifies whether this exec produces a row or not
-    pub fn produce_one_row(&self) -> bool {
-        self.produce_one_row
+    pub fn new(schema: SchemaRef) -> Self {
+        EmptyExec { schema }
     }
 }
 
@@ -85,7 +75,7 @@ impl ExecutionPlan for EmptyExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            0 => Ok(Arc::new(EmptyExec::new(false, self.schema.clone()))),
+            0 => Ok(Arc::new(EmptyExec::new(self.schema.clone()))),
             _ => Err(DataFusionError::Internal(
                 "EmptyExec wrong number of children".to_string(),
             )),
@@ -93,7 +83,7 @@ impl ExecutionPlan for EmptyExec {
     }
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
-        // GlobalLimitExec has a single output partition
+        // EmptyExec has a single output partition
         if 0 != partition {
             return Err(DataFusionError::Internal(format!(
                 "EmptyExec invalid partition {} (expected 0)",
@@ -101,22 +91,8 @@ impl ExecutionPlan for EmptyExec {
             )));
         }
 
-        // Makes a stream only contains one null element if needed
-        let data = if self.produce_one_row {
-            vec![RecordBatch::try_new(
-                Arc::new(Schema::new(vec![Field::new(
-                    "placeholder",
-                    DataType::Null,
-                    true,
-                )])),
-                vec![Arc::new(NullArray::new(1))],
-            )?]
-        } else {
-            vec![]
-        };
-
         Ok(Box::pin(MemoryStream::try_new(
-            data,
+            vec![],
             self.schema.clone(),
             None,
         )?))
@@ -129,7 +105,7 @@ impl ExecutionPlan for EmptyExec {
     ) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default => {
-                write!(f, "EmptyExec: produce_one_row={}", self.produce_one_row)
+                write!(f, "EmptyExec")
             }
         }
     }
@@ -145,7 +121,7 @@ mod tests {
     async fn empty() -> Result<()> {
         let schema = test::aggr_test_schema();
 
-        let empty = EmptyExec::new(false, schema.clone());
+        let empty = EmptyExec::new(schema.clone());
         assert_eq!(empty.schema(), schema);
 
         // we should have no results
@@ -159,7 +135,7 @@ mod tests {
     #[test]
     fn with_new_children() -> Result<()> {
         let schema = test::aggr_test_schema();
-        let empty = EmptyExec::new(false, schema);
+        let empty = EmptyExec::new(schema);
 
         let empty2 = empty.with_new_children(vec![])?;
         assert_eq!(empty.schema(), empty2.schema());
@@ -175,25 +151,11 @@ mod tests {
     #[tokio::test]
     async fn invalid_execute() -> Result<()> {
         let schema = test::aggr_test_schema();
-        let empty = EmptyExec::new(false, schema);
+        let empty = EmptyExec::new(schema);
 
         // ask for the wrong partition
         assert!(empty.execute(1).await.is_err());
         assert!(empty.execute(20).await.is_err());
         Ok(())
     }
-
-    #[tokio::test]
-    async fn produce_one_row() -> Result<()> {
-        let schema = test::aggr_test_schema();
-        let empty = EmptyExec::new(true, schema);
-
-        let iter = empty.execute(0).await?;
-        let batches = common::collect(iter).await?;
-
-        // should have one item
-        assert_eq!(batches.len(), 1);
-
-        Ok(())
-    }
 }
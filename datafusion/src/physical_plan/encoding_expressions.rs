@@ -0,0 +1,232 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Encoding expressions
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::{
+    error::{DataFusionError, Result},
+    scalar::ScalarValue,
+};
+use arrow::{
+    array::{Array, ArrayRef, BinaryArray, BinaryBuilder, LargeStringArray, StringArray},
+    datatypes::DataType,
+};
+
+use super::ColumnarValue;
+
+/// The encodings supported by [`encode`] and [`decode`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Base64,
+    Hex,
+}
+
+impl FromStr for Encoding {
+    type Err = DataFusionError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Ok(match name.to_lowercase().as_str() {
+            "base64" => Encoding::Base64,
+            "hex" => Encoding::Hex,
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "There is no built-in encoding named '{}', currently supported encodings are: base64, hex",
+                    name
+                )))
+            }
+        })
+    }
+}
+
+impl Encoding {
+    fn encode(self, value: &[u8]) -> String {
+        match self {
+            Encoding::Base64 => base64::encode(value),
+            Encoding::Hex => hex::encode(value),
+        }
+    }
+
+    fn decode(self, value: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Base64 => base64::decode(value).map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Failed to decode value using base64: {}",
+                    e
+                ))
+            }),
+            Encoding::Hex => hex::decode(value).map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Failed to decode value using hex: {}",
+                    e
+                ))
+            }),
+        }
+    }
+}
+
+/// Returns the encoding named by the second argument of `name`
+fn encoding_arg(args: &[ColumnarValue], name: &str) -> Result<Encoding> {
+    if args.len() != 2 {
+        return Err(DataFusionError::Internal(format!(
+            "{:?} args were supplied but {} takes exactly two arguments",
+            args.len(),
+            name,
+        )));
+    }
+    match &args[1] {
+        ColumnarValue::Scalar(ScalarValue::Utf8(Some(method))) => method.parse(),
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Second argument of {} must be a non-null constant string",
+            name
+        ))),
+    }
+}
+
+/// Returns the bytes of every value of a string or binary `array`
+fn array_values<'a>(array: &'a ArrayRef, name: &str) -> Result<Vec<Option<&'a [u8]>>> {
+    Ok(match array.data_type() {
+        DataType::Utf8 => {
+            let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+            array.iter().map(|v| v.map(str::as_bytes)).collect()
+        }
+        DataType::LargeUtf8 => {
+            let array = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            array.iter().map(|v| v.map(str::as_bytes)).collect()
+        }
+        DataType::Binary => {
+            let array = array.as_any().downcast_ref::<BinaryArray>().unwrap();
+            (0..array.len())
+                .map(|i| {
+                    if array.is_null(i) {
+                        None
+                    } else {
+                        Some(array.value(i))
+                    }
+                })
+                .collect()
+        }
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function {}",
+                other, name,
+            )))
+        }
+    })
+}
+
+/// Returns the bytes of a string or binary `scalar`
+fn scalar_value<'a>(scalar: &'a ScalarValue, name: &str) -> Result<Option<&'a [u8]>> {
+    match scalar {
+        ScalarValue::Utf8(v) | ScalarValue::LargeUtf8(v) => {
+            Ok(v.as_ref().map(|v| v.as_bytes()))
+        }
+        ScalarValue::Binary(v) => Ok(v.as_deref()),
+        other => Err(DataFusionError::Internal(format!(
+            "Unsupported data type {:?} for function {}",
+            other.get_datatype(),
+            name,
+        ))),
+    }
+}
+
+/// Encodes a string or binary value into text using `base64` or `hex`.
+/// encode('hello', 'hex') = '68656c6c6f'
+pub fn encode(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let encoding = encoding_arg(args, "encode")?;
+    Ok(match &args[0] {
+        ColumnarValue::Array(array) => {
+            let result = array_values(array, "encode")?
+                .into_iter()
+                .map(|v| v.map(|v| encoding.encode(v)))
+                .collect::<StringArray>();
+            ColumnarValue::Array(Arc::new(result))
+        }
+        ColumnarValue::Scalar(scalar) => ColumnarValue::Scalar(ScalarValue::Utf8(
+            scalar_value(scalar, "encode")?.map(|v| encoding.encode(v)),
+        )),
+    })
+}
+
+/// Decodes binary data from its textual `base64` or `hex` representation.
+/// decode('aGVsbG8=', 'base64') = 'hello'::bytea
+pub fn decode(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let encoding = encoding_arg(args, "decode")?;
+    Ok(match &args[0] {
+        ColumnarValue::Array(array) => {
+            let values = array_values(array, "decode")?;
+            let mut builder = BinaryBuilder::new(values.len());
+            for value in values {
+                match value {
+                    Some(value) => builder.append_value(&encoding.decode(value)?)?,
+                    None => builder.append_null()?,
+                }
+            }
+            ColumnarValue::Array(Arc::new(builder.finish()))
+        }
+        ColumnarValue::Scalar(scalar) => ColumnarValue::Scalar(ScalarValue::Binary(
+            scalar_value(scalar, "decode")?
+                .map(|v| encoding.decode(v))
+                .transpose()?,
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_round_trip() -> Result<()> {
+        let array: ArrayRef = Arc::new(BinaryArray::from(vec![
+            Some(b"hello".as_ref()),
+            None,
+            Some(b"\x00\xff".as_ref()),
+        ]));
+        for (method, expected) in vec![
+            ("hex", vec![Some("68656c6c6f"), None, Some("00ff")]),
+            ("base64", vec![Some("aGVsbG8="), None, Some("AP8=")]),
+        ] {
+            let method = ColumnarValue::Scalar(ScalarValue::Utf8(Some(method.into())));
+            let encoded =
+                match encode(&[ColumnarValue::Array(array.clone()), method.clone()])? {
+                    ColumnarValue::Array(encoded) => encoded,
+                    _ => panic!("Expected an array"),
+                };
+            let strings = encoded.as_any().downcast_ref::<StringArray>().unwrap();
+            assert_eq!(strings.iter().collect::<Vec<_>>(), expected);
+
+            let decoded = match decode(&[ColumnarValue::Array(encoded), method])? {
+                ColumnarValue::Array(decoded) => decoded,
+                _ => panic!("Expected an array"),
+            };
+            assert_eq!(decoded.data(), array.data());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decode_errors() {
+        let method = |m: &str| ColumnarValue::Scalar(ScalarValue::Utf8(Some(m.into())));
+        let value = ColumnarValue::Scalar(ScalarValue::Utf8(Some("zz".to_string())));
+        assert!(decode(&[value.clone(), method("hex")]).is_err());
+        assert!(decode(&[value.clone(), method("base32")]).is_err());
+        assert!(encode(&[value, method("escape")]).is_err());
+    }
+}
@@ -27,7 +27,7 @@ use crate::scalar::ScalarValue;
 use arrow::compute;
 use arrow::datatypes::DataType;
 use arrow::{
-    array::{ArrayRef, UInt64Array},
+    array::{ArrayBuilder, ArrayRef, UInt64Array},
     datatypes::Field,
 };
 
@@ -138,6 +138,11 @@ impl Accumulator for AvgAccumulator {
         Ok(vec![ScalarValue::from(self.count), self.sum.clone()])
     }
 
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        ScalarValue::from(self.count).append_to_builder(builders[0].as_mut())?;
+        self.sum.append_to_builder(builders[1].as_mut())
+    }
+
     fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
         let values = &values[0];
 
@@ -22,6 +22,7 @@ use arrow::compute::kernels::arithmetic::{
     add, divide, divide_scalar, multiply, subtract,
 };
 use arrow::compute::kernels::boolean::{and_kleene, or_kleene};
+use arrow::compute::kernels::cast::cast;
 use arrow::compute::kernels::comparison::{eq, gt, gt_eq, lt, lt_eq, neq};
 use arrow::compute::kernels::comparison::{
     eq_scalar, gt_eq_scalar, gt_scalar, lt_eq_scalar, lt_scalar, neq_scalar,
@@ -39,11 +40,17 @@ use arrow::record_batch::RecordBatch;
 
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Operator;
+use crate::physical_plan::dictionary_expressions::{dictionary_values, take_keys};
 use crate::physical_plan::expressions::try_cast;
+use crate::physical_plan::selection::{evaluate_selected, SelectionVector};
 use crate::physical_plan::{ColumnarValue, PhysicalExpr};
 use crate::scalar::ScalarValue;
 
+use super::bytes::{bytes_comparison, compare_bytes};
 use super::coercion::{eq_coercion, numerical_coercion, order_coercion, string_coercion};
+use super::decimal::{decimal_arithmetic, decimal_arithmetic_type};
+use super::interval::{interval_arithmetic, interval_arithmetic_type};
+use super::like::like_scalar;
 
 /// Binary expression
 #[derive(Debug)]
@@ -325,6 +332,18 @@ fn common_binary_type(
     rhs_type: &DataType,
 ) -> Result<DataType> {
     // This result MUST be compatible with `binary_coerce`
+    // decimal arithmetic derives the precision and scale of its result from both sides
+    if let Some(t) = decimal_arithmetic_type(lhs_type, op, rhs_type) {
+        return Ok(t);
+    }
+    // interval arithmetic keeps the interval type and scales it by numbers of any type
+    if let Some(t) = interval_arithmetic_type(lhs_type, op, rhs_type) {
+        return Ok(t);
+    }
+    // binary values of different binary types are compared without casting
+    if bytes_comparison(lhs_type, op, rhs_type) {
+        return Ok(lhs_type.clone());
+    }
     let result = match op {
         Operator::And | Operator::Or => match (lhs_type, rhs_type) {
             // logical binary boolean operators can only be evaluated in bools
@@ -363,6 +382,44 @@ fn common_binary_type(
     }
 }
 
+/// Returns the value type of a dictionary of strings on one side of the
+/// comparison `op` with a string on the other side, which is evaluated on the
+/// dictionary values rather than on the decoded dictionary
+fn dictionary_comparison_type(
+    lhs_type: &DataType,
+    op: &Operator,
+    rhs_type: &DataType,
+) -> Option<DataType> {
+    if !matches!(
+        op,
+        Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::Like
+            | Operator::NotLike
+    ) {
+        return None;
+    }
+    let (value_type, other_type) = match (lhs_type, rhs_type) {
+        (DataType::Dictionary(_, _), DataType::Dictionary(_, _)) => return None,
+        (DataType::Dictionary(_, value_type), other_type)
+        | (other_type, DataType::Dictionary(_, value_type)) => {
+            (value_type.as_ref(), other_type)
+        }
+        _ => return None,
+    };
+    if !matches!(value_type, DataType::Utf8 | DataType::LargeUtf8) {
+        return None;
+    }
+    match common_binary_type(value_type, op, other_type) {
+        Ok(common_type) if &common_type == value_type => Some(common_type),
+        _ => None,
+    }
+}
+
 /// Returns the return type of a binary operator or an error when the binary operator cannot
 /// perform the computation between the argument's types, even after type coercion.
 ///
@@ -372,6 +429,10 @@ pub fn binary_operator_data_type(
     op: &Operator,
     rhs_type: &DataType,
 ) -> Result<DataType> {
+    if dictionary_comparison_type(lhs_type, op, rhs_type).is_some() {
+        return Ok(DataType::Boolean);
+    }
+
     // validate that it is possible to perform the operation on incoming types.
     // (or the return datatype cannot be infered)
     let common_type = common_binary_type(lhs_type, op, rhs_type)?;
@@ -392,9 +453,8 @@ pub fn binary_operator_data_type(
         Operator::Plus | Operator::Minus | Operator::Divide | Operator::Multiply => {
             Ok(common_type)
         }
-        Operator::Modulus => Err(DataFusionError::NotImplemented(
-            "Modulus operator is still not supported".to_string(),
-        )),
+        // only decimal modulus is supported, see `common_binary_type`
+        Operator::Modulus => Ok(common_type),
     }
 }
 
@@ -417,11 +477,155 @@ impl PhysicalExpr for BinaryExpr {
     }
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let left_value = self.left.evaluate(batch)?;
-        let right_value = self.right.evaluate(batch)?;
+        self.evaluate_rows(batch, None)
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        match self.op {
+            // comparisons and logical operators cannot fail for the rows that
+            // are not selected
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::Like
+            | Operator::NotLike
+            | Operator::And
+            | Operator::Or => self.evaluate_rows(batch, Some(selection)),
+            _ => evaluate_selected(self, batch, selection),
+        }
+    }
+}
+
+impl BinaryExpr {
+    /// Evaluates the expression for all rows, or for the rows of `selection`
+    /// and any other rows that are cheaper to evaluate than to leave out
+    fn evaluate_rows(
+        &self,
+        batch: &RecordBatch,
+        selection: Option<&SelectionVector>,
+    ) -> Result<ColumnarValue> {
+        let left_value = match selection {
+            Some(selection) => self.left.evaluate_selection(batch, selection)?,
+            None => self.left.evaluate(batch)?,
+        };
+        // the right side of AND and OR is only evaluated for the rows whose
+        // result is not decided by the left side
+        let undecided =
+            undecided_rows(&self.op, &left_value, batch.num_rows()).map(|rows| {
+                match selection {
+                    Some(selection) => selection.and(&rows),
+                    None => SelectionVector::new(&rows),
+                }
+            });
+        let right_value = match (&undecided, selection) {
+            (Some(rows), _) | (None, Some(rows)) => {
+                self.right.evaluate_selection(batch, rows)?
+            }
+            (None, None) => self.right.evaluate(batch)?,
+        };
+        if dictionary_comparison_type(
+            &left_value.data_type(),
+            &self.op,
+            &right_value.data_type(),
+        )
+        .is_some()
+        {
+            return self.evaluate_dictionary(left_value, right_value, batch.num_rows());
+        }
+        self.evaluate_values(left_value, right_value, batch.num_rows())
+    }
+    /// Evaluates a comparison of a dictionary of strings with a string, once per
+    /// dictionary value if the string is a scalar
+    fn evaluate_dictionary(
+        &self,
+        left_value: ColumnarValue,
+        right_value: ColumnarValue,
+        num_rows: usize,
+    ) -> Result<ColumnarValue> {
+        match (&left_value, &right_value) {
+            (ColumnarValue::Array(dictionary), ColumnarValue::Scalar(_)) => {
+                let values = dictionary_values(dictionary)?;
+                let result = self
+                    .evaluate_values(
+                        ColumnarValue::Array(values.clone()),
+                        right_value.clone(),
+                        values.len(),
+                    )?
+                    .into_array(values.len());
+                Ok(ColumnarValue::Array(take_keys(dictionary, &result)?))
+            }
+            (ColumnarValue::Scalar(_), ColumnarValue::Array(dictionary)) => {
+                let values = dictionary_values(dictionary)?;
+                let result = self
+                    .evaluate_values(
+                        left_value.clone(),
+                        ColumnarValue::Array(values.clone()),
+                        values.len(),
+                    )?
+                    .into_array(values.len());
+                Ok(ColumnarValue::Array(take_keys(dictionary, &result)?))
+            }
+            _ => {
+                let decode = |value: ColumnarValue| -> Result<ColumnarValue> {
+                    match value {
+                        ColumnarValue::Array(array) => match array.data_type() {
+                            DataType::Dictionary(_, value_type) => {
+                                Ok(ColumnarValue::Array(cast(&array, value_type)?))
+                            }
+                            _ => Ok(ColumnarValue::Array(array)),
+                        },
+                        scalar => Ok(scalar),
+                    }
+                };
+                self.evaluate_values(
+                    decode(left_value.clone())?,
+                    decode(right_value.clone())?,
+                    num_rows,
+                )
+            }
+        }
+    }
+
+    /// Evaluates the operator for the values of both sides
+    fn evaluate_values(
+        &self,
+        left_value: ColumnarValue,
+        right_value: ColumnarValue,
+        num_rows: usize,
+    ) -> Result<ColumnarValue> {
         let left_data_type = left_value.data_type();
         let right_data_type = right_value.data_type();
 
+        // decimal arithmetic accepts integers on either side and rescales the values
+        if let Some(result_type) =
+            decimal_arithmetic_type(&left_data_type, &self.op, &right_data_type)
+        {
+            let left = left_value.into_array(num_rows);
+            let right = right_value.into_array(num_rows);
+            return decimal_arithmetic(&left, &self.op, &right, &result_type)
+                .map(ColumnarValue::Array);
+        }
+        if let Some(result_type) =
+            interval_arithmetic_type(&left_data_type, &self.op, &right_data_type)
+        {
+            let left = left_value.into_array(num_rows);
+            let right = right_value.into_array(num_rows);
+            return interval_arithmetic(&left, &self.op, &right, &result_type)
+                .map(ColumnarValue::Array);
+        }
+        if bytes_comparison(&left_data_type, &self.op, &right_data_type) {
+            let left = left_value.into_array(num_rows);
+            let right = right_value.into_array(num_rows);
+            return compare_bytes(&left, &self.op, &right).map(ColumnarValue::Array);
+        }
+
         if left_data_type != right_data_type {
             return Err(DataFusionError::Internal(format!(
                 "Cannot evaluate binary expression {:?} with types {:?} and {:?}",
@@ -445,12 +649,18 @@ impl PhysicalExpr for BinaryExpr {
                     Operator::NotEq => {
                         binary_array_op_scalar!(array, scalar.clone(), neq)
                     }
-                    Operator::Like => {
-                        binary_string_array_op_scalar!(array, scalar.clone(), like)
-                    }
-                    Operator::NotLike => {
-                        binary_string_array_op_scalar!(array, scalar.clone(), nlike)
-                    }
+                    Operator::Like => match like_scalar(array, scalar, false) {
+                        Some(result) => Some(result),
+                        None => {
+                            binary_string_array_op_scalar!(array, scalar.clone(), like)
+                        }
+                    },
+                    Operator::NotLike => match like_scalar(array, scalar, true) {
+                        Some(result) => Some(result),
+                        None => {
+                            binary_string_array_op_scalar!(array, scalar.clone(), nlike)
+                        }
+                    },
                     Operator::Divide => {
                         binary_primitive_array_op_scalar!(array, scalar.clone(), divide)
                     }
@@ -486,8 +696,8 @@ impl PhysicalExpr for BinaryExpr {
 
         // if both arrays or both literals - extract arrays and continue execution
         let (left, right) = (
-            left_value.into_array(batch.num_rows()),
-            right_value.into_array(batch.num_rows()),
+            left_value.into_array(num_rows),
+            right_value.into_array(num_rows),
         );
 
         let result: Result<ArrayRef> = match &self.op {
@@ -533,6 +743,31 @@ impl PhysicalExpr for BinaryExpr {
     }
 }
 
+/// The rows of a boolean `left` side of AND and OR whose result depends on the
+/// right side: false AND x is false and true OR x is true for any x, including
+/// null, so that the right side evaluates to null for the other rows.
+fn undecided_rows(
+    op: &Operator,
+    left: &ColumnarValue,
+    num_rows: usize,
+) -> Option<BooleanArray> {
+    let decided = match op {
+        Operator::And => false,
+        Operator::Or => true,
+        _ => return None,
+    };
+    if left.data_type() != DataType::Boolean {
+        return None;
+    }
+    let left = left.clone().into_array(num_rows);
+    let left = left.as_any().downcast_ref::<BooleanArray>()?;
+    Some(
+        (0..num_rows)
+            .map(|i| Some(!(left.is_valid(i) && left.value(i) == decided)))
+            .collect(),
+    )
+}
+
 /// return two physical expressions that are optionally coerced to a
 /// common type that the binary operator supports.
 fn binary_cast(
@@ -544,6 +779,23 @@ fn binary_cast(
     let lhs_type = &lhs.data_type(input_schema)?;
     let rhs_type = &rhs.data_type(input_schema)?;
 
+    // decimal and interval arithmetic, and comparisons of binary values
+    // operate on the original types of each side
+    if decimal_arithmetic_type(lhs_type, op, rhs_type).is_some()
+        || interval_arithmetic_type(lhs_type, op, rhs_type).is_some()
+        || bytes_comparison(lhs_type, op, rhs_type)
+    {
+        return Ok((lhs, rhs));
+    }
+
+    // comparisons of a dictionary of strings keep the dictionary
+    if let Some(value_type) = dictionary_comparison_type(lhs_type, op, rhs_type) {
+        return Ok(match lhs_type {
+            DataType::Dictionary(_, _) => (lhs, try_cast(rhs, input_schema, value_type)?),
+            _ => (try_cast(lhs, input_schema, value_type)?, rhs),
+        });
+    }
+
     let cast_type = common_binary_type(lhs_type, op, rhs_type)?;
 
     Ok((
@@ -572,7 +824,7 @@ mod tests {
 
     use super::*;
     use crate::error::Result;
-    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::expressions::{col, lit};
 
     // Create a binary expression without coercion. Used here when we do not want to coerce the expressions
     // to valid types. Usage can result in an execution (after plan) error.
@@ -871,6 +1123,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dictionary_comparison_with_scalar() -> Result<()> {
+        let dict_array: DictionaryArray<Int32Type> =
+            vec![Some("one"), None, Some("three"), Some("one")]
+                .into_iter()
+                .collect();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "dict",
+            dict_array.data_type().clone(),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(dict_array)])?;
+
+        let cases = vec![
+            (
+                col("dict"),
+                Operator::Eq,
+                lit(ScalarValue::from("one")),
+                "true\n\nfalse\ntrue",
+            ),
+            (
+                lit(ScalarValue::from("one")),
+                Operator::NotEq,
+                col("dict"),
+                "false\n\ntrue\nfalse",
+            ),
+            (
+                col("dict"),
+                Operator::Gt,
+                lit(ScalarValue::from("p")),
+                "false\n\ntrue\nfalse",
+            ),
+            (
+                col("dict"),
+                Operator::Like,
+                lit(ScalarValue::from("t%")),
+                "false\n\ntrue\nfalse",
+            ),
+        ];
+        for (left, op, right, expected) in cases {
+            let expression = binary(left, op, right, &schema)?;
+            assert_eq!(expression.data_type(&schema)?, DataType::Boolean);
+
+            // the dictionary is not cast to its value type
+            let binary = expression.as_any().downcast_ref::<BinaryExpr>().unwrap();
+            assert!(
+                matches!(
+                    binary.left().data_type(&schema)?,
+                    DataType::Dictionary(_, _)
+                ) || matches!(
+                    binary.right().data_type(&schema)?,
+                    DataType::Dictionary(_, _)
+                )
+            );
+
+            let result = expression.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(expected, array_to_string(&result)?);
+        }
+        Ok(())
+    }
+
     // Convert the array to a newline delimited string of pretty printed values
     fn array_to_string(array: &ArrayRef) -> Result<String> {
         let s = (0..array.len())
@@ -964,6 +1277,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn decimal_modulus_with_integer() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Decimal(5, 2), false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let mut builder = DecimalBuilder::new(2, 5, 2);
+        builder.append_value(725)?;
+        builder.append_value(-300)?;
+        let a = Arc::new(builder.finish());
+        let b = Arc::new(Int32Array::from(vec![2, 3]));
+
+        let expr = binary(col("a"), Operator::Modulus, col("b"), &schema)?;
+        assert_eq!(expr.data_type(&schema)?, DataType::Decimal(5, 2));
+
+        let batch = RecordBatch::try_new(schema, vec![a, b])?;
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(result.value(0), 125);
+        assert_eq!(result.value(1), 0);
+        Ok(())
+    }
+
     fn apply_arithmetic<T: ArrowNumericType>(
         schema: SchemaRef,
         data: Vec<ArrayRef>,
@@ -994,6 +1330,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn and_or_short_circuit() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let a = Int32Array::from(vec![4, 6, 8]);
+        let b = Int32Array::from(vec![2, 0, 4]);
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(a), Arc::new(b)])?;
+
+        // a / b is only evaluated for the rows where b != 0
+        let b_not_zero = binary_simple(col("b"), Operator::NotEq, lit(0i32.into()));
+        let b_zero = binary_simple(col("b"), Operator::Eq, lit(0i32.into()));
+        let quotient = binary_simple(
+            binary_simple(col("a"), Operator::Divide, col("b")),
+            Operator::Eq,
+            lit(2i32.into()),
+        );
+
+        let expr = binary_simple(b_not_zero, Operator::And, quotient.clone());
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected = BooleanArray::from(vec![Some(true), Some(false), Some(true)]);
+        assert_eq!(result.as_ref(), &expected);
+
+        let expr = binary_simple(b_zero, Operator::Or, quotient);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected = BooleanArray::from(vec![Some(true), Some(true), Some(true)]);
+        assert_eq!(result.as_ref(), &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn and_with_nulls_op() -> Result<()> {
         let schema = Schema::new(vec![
@@ -0,0 +1,206 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Comparisons and sorting of binary arrays (Binary, LargeBinary and
+//! FixedSizeBinary), for which arrow has no kernels. Values of the different
+//! binary types can be compared with each other without casting them.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, FixedSizeBinaryArray, LargeBinaryArray,
+    UInt64Array,
+};
+use arrow::datatypes::DataType;
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
+
+/// Whether `data_type` is one of the binary types
+pub(crate) fn is_binary(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_)
+    )
+}
+
+/// Whether `lhs_type op rhs_type` is a comparison of binary values
+pub(crate) fn bytes_comparison(
+    lhs_type: &DataType,
+    op: &Operator,
+    rhs_type: &DataType,
+) -> bool {
+    matches!(
+        op,
+        Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+    ) && is_binary(lhs_type)
+        && is_binary(rhs_type)
+}
+
+/// Returns the values of the binary array `array`
+pub(crate) fn byte_values(array: &ArrayRef) -> Result<Vec<Option<&[u8]>>> {
+    macro_rules! values {
+        ($ARRAY_TYPE:ident) => {{
+            let array = array.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+            (0..array.len())
+                .map(|i| {
+                    if array.is_null(i) {
+                        None
+                    } else {
+                        Some(array.value(i))
+                    }
+                })
+                .collect()
+        }};
+    }
+    Ok(match array.data_type() {
+        DataType::Binary => values!(BinaryArray),
+        DataType::LargeBinary => values!(LargeBinaryArray),
+        DataType::FixedSizeBinary(_) => values!(FixedSizeBinaryArray),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Expected a binary array, got {:?}",
+                other
+            )))
+        }
+    })
+}
+
+/// Compares the binary arrays `left` and `right` of the same length
+/// element-wise, comparing values lexicographically byte by byte
+pub(crate) fn compare_bytes(
+    left: &ArrayRef,
+    op: &Operator,
+    right: &ArrayRef,
+) -> Result<ArrayRef> {
+    let cmp: fn(&[u8], &[u8]) -> bool = match op {
+        Operator::Eq => |l, r| l == r,
+        Operator::NotEq => |l, r| l != r,
+        Operator::Lt => |l, r| l < r,
+        Operator::LtEq => |l, r| l <= r,
+        Operator::Gt => |l, r| l > r,
+        Operator::GtEq => |l, r| l >= r,
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Operator {} is not a comparison of binary values",
+                other
+            )))
+        }
+    };
+    let result = byte_values(left)?
+        .into_iter()
+        .zip(byte_values(right)?)
+        .map(|values| match values {
+            (Some(l), Some(r)) => Some(cmp(l, r)),
+            _ => None,
+        })
+        .collect::<BooleanArray>();
+    Ok(Arc::new(result))
+}
+
+/// Returns the ranks of the values of the binary array `array` in ascending
+/// order, with equal values having the same rank and nulls remaining null.
+/// Sorting the ranks sorts the values, so arrow's sort kernels can be used.
+pub(crate) fn bytes_sort_ranks(array: &ArrayRef) -> Result<ArrayRef> {
+    let values = byte_values(array)?;
+    let mut order = (0..values.len())
+        .filter(|i| values[*i].is_some())
+        .collect::<Vec<_>>();
+    order.sort_by(|a, b| values[*a].cmp(&values[*b]));
+
+    let mut ranks = vec![None; values.len()];
+    let mut rank = 0;
+    for (n, i) in order.iter().enumerate() {
+        if n > 0 && values[order[n - 1]] != values[*i] {
+            rank += 1;
+        }
+        ranks[*i] = Some(rank);
+    }
+    Ok(Arc::new(UInt64Array::from(ranks)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::FixedSizeBinaryBuilder;
+
+    fn binary(values: Vec<Option<&str>>) -> ArrayRef {
+        let values = values
+            .into_iter()
+            .map(|v| v.map(|v| v.as_bytes()))
+            .collect::<Vec<_>>();
+        Arc::new(BinaryArray::from(values))
+    }
+
+    #[test]
+    fn compare_binary_types() -> Result<()> {
+        let left = binary(vec![Some("ab"), Some("ab"), None, Some("b\0")]);
+        let mut builder = FixedSizeBinaryBuilder::new(4, 2);
+        for value in &["ab", "ac", "ab", "ab"] {
+            builder.append_value(value.as_bytes())?;
+        }
+        let right: ArrayRef = Arc::new(builder.finish());
+        let cases = vec![
+            (
+                Operator::Eq,
+                vec![Some(true), Some(false), None, Some(false)],
+            ),
+            (
+                Operator::NotEq,
+                vec![Some(false), Some(true), None, Some(true)],
+            ),
+            (
+                Operator::Lt,
+                vec![Some(false), Some(true), None, Some(false)],
+            ),
+            (
+                Operator::GtEq,
+                vec![Some(true), Some(false), None, Some(true)],
+            ),
+        ];
+        for (op, expected) in cases {
+            let result = compare_bytes(&left, &op, &right)?;
+            let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+            assert_eq!(result.iter().collect::<Vec<_>>(), expected, "{}", op);
+        }
+        assert!(compare_bytes(&left, &Operator::Plus, &right).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sort_ranks() -> Result<()> {
+        let array = binary(vec![
+            Some("b"),
+            None,
+            Some(""),
+            Some("ab"),
+            Some("b"),
+            Some("a"),
+        ]);
+        let ranks = bytes_sort_ranks(&array)?;
+        let ranks = ranks.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(
+            ranks.iter().collect::<Vec<_>>(),
+            vec![Some(3), None, Some(0), Some(2), Some(3), Some(1)]
+        );
+        Ok(())
+    }
+}
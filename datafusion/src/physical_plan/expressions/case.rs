@@ -18,7 +18,7 @@
 use std::{any::Any, sync::Arc};
 
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::{ColumnarValue, PhysicalExpr};
+use crate::physical_plan::{selection::SelectionVector, ColumnarValue, PhysicalExpr};
 use arrow::array::{self, *};
 use arrow::compute::{eq, eq_utf8};
 use arrow::datatypes::{DataType, Schema};
@@ -103,135 +103,31 @@ impl CaseExpr {
     }
 }
 
-macro_rules! if_then_else {
-    ($BUILDER_TYPE:ty, $ARRAY_TYPE:ty, $BOOLS:expr, $TRUE:expr, $FALSE:expr) => {{
-        let true_values = $TRUE
-            .as_ref()
-            .as_any()
-            .downcast_ref::<$ARRAY_TYPE>()
-            .expect("true_values downcast failed");
-
-        let false_values = $FALSE
-            .as_ref()
-            .as_any()
-            .downcast_ref::<$ARRAY_TYPE>()
-            .expect("false_values downcast failed");
-
-        let mut builder = <$BUILDER_TYPE>::new($BOOLS.len());
-        for i in 0..$BOOLS.len() {
-            if $BOOLS.is_null(i) {
-                if false_values.is_null(i) {
-                    builder.append_null()?;
-                } else {
-                    builder.append_value(false_values.value(i))?;
-                }
-            } else if $BOOLS.value(i) {
-                if true_values.is_null(i) {
-                    builder.append_null()?;
-                } else {
-                    builder.append_value(true_values.value(i))?;
-                }
-            } else {
-                if false_values.is_null(i) {
-                    builder.append_null()?;
-                } else {
-                    builder.append_value(false_values.value(i))?;
-                }
-            }
+/// Builds the result of a CASE from the `results` of its branches, taking each
+/// row from the result `choices[row]`
+pub(super) fn merge(results: &[ArrayRef], choices: &[usize]) -> Result<ArrayRef> {
+    let data_type = results[0].data_type();
+    if let Some(other) = results.iter().find(|r| r.data_type() != data_type) {
+        return Err(DataFusionError::Execution(format!(
+            "CASE requires all results to be of the same type, got '{:?}' and '{:?}'",
+            data_type,
+            other.data_type()
+        )));
+    }
+    let mut mutable = MutableArrayData::new(
+        results.iter().map(|r| r.data()).collect(),
+        true,
+        choices.len(),
+    );
+    // copy the runs of rows that are taken from the same result at once
+    let mut start = 0;
+    for end in 1..=choices.len() {
+        if end == choices.len() || choices[end] != choices[start] {
+            mutable.extend(choices[start], start, end);
+            start = end;
         }
-        Ok(Arc::new(builder.finish()))
-    }};
-}
-
-fn if_then_else(
-    bools: &BooleanArray,
-    true_values: ArrayRef,
-    false_values: ArrayRef,
-    data_type: &DataType,
-) -> Result<ArrayRef> {
-    match data_type {
-        DataType::UInt8 => if_then_else!(
-            array::UInt8Builder,
-            array::UInt8Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::UInt16 => if_then_else!(
-            array::UInt16Builder,
-            array::UInt16Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::UInt32 => if_then_else!(
-            array::UInt32Builder,
-            array::UInt32Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::UInt64 => if_then_else!(
-            array::UInt64Builder,
-            array::UInt64Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Int8 => if_then_else!(
-            array::Int8Builder,
-            array::Int8Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Int16 => if_then_else!(
-            array::Int16Builder,
-            array::Int16Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Int32 => if_then_else!(
-            array::Int32Builder,
-            array::Int32Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Int64 => if_then_else!(
-            array::Int64Builder,
-            array::Int64Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Float32 => if_then_else!(
-            array::Float32Builder,
-            array::Float32Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Float64 => if_then_else!(
-            array::Float64Builder,
-            array::Float64Array,
-            bools,
-            true_values,
-            false_values
-        ),
-        DataType::Utf8 => if_then_else!(
-            array::StringBuilder,
-            array::StringArray,
-            bools,
-            true_values,
-            false_values
-        ),
-        other => Err(DataFusionError::Execution(format!(
-            "CASE does not support '{:?}'",
-            other
-        ))),
     }
+    Ok(make_array(mutable.freeze()))
 }
 
 macro_rules! array_equals {
@@ -306,42 +202,24 @@ impl CaseExpr {
     ///     [WHEN ...]
     ///     [ELSE result]
     /// END
-    fn case_when_with_expr(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let return_type = self.when_then_expr[0].1.data_type(&batch.schema())?;
+    fn case_when_with_expr(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
         let expr = self.expr.as_ref().unwrap();
-        let base_value = expr.evaluate(batch)?;
+        let base_value = expr.evaluate_selection(batch, selection)?;
         let base_type = expr.data_type(&batch.schema())?;
         let base_value = base_value.into_array(batch.num_rows());
 
-        // start with the else condition, or nulls
-        let mut current_value: Option<ArrayRef> = if let Some(e) = &self.else_expr {
-            Some(e.evaluate(batch)?.into_array(batch.num_rows()))
-        } else {
-            Some(new_null_array(&return_type, batch.num_rows()))
-        };
-
-        // walk backwards through the when/then expressions
-        for i in (0..self.when_then_expr.len()).rev() {
-            let i = i as usize;
-
-            let when_value = self.when_then_expr[i].0.evaluate(batch)?;
-            let when_value = when_value.into_array(batch.num_rows());
-
-            let then_value = self.when_then_expr[i].1.evaluate(batch)?;
-            let then_value = then_value.into_array(batch.num_rows());
+        self.evaluate_branches(batch, selection, |when, remainder| {
+            let when_value = when
+                .evaluate_selection(batch, remainder)?
+                .into_array(batch.num_rows());
 
             // build boolean array representing which rows match the "when" value
-            let when_match = array_equals(&base_type, when_value, base_value.clone())?;
-
-            current_value = Some(if_then_else(
-                &when_match,
-                then_value,
-                current_value.unwrap(),
-                &return_type,
-            )?);
-        }
-
-        Ok(ColumnarValue::Array(current_value.unwrap()))
+            array_equals(&base_type, when_value, base_value.clone())
+        })
     }
 
     /// This function evaluates the form of CASE where each WHEN expression is a boolean
@@ -351,41 +229,82 @@ impl CaseExpr {
     ///      [WHEN ...]
     ///      [ELSE result]
     /// END
-    fn case_when_no_expr(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let return_type = self.when_then_expr[0].1.data_type(&batch.schema())?;
-
-        // start with the else condition, or nulls
-        let mut current_value: Option<ArrayRef> = if let Some(e) = &self.else_expr {
-            Some(e.evaluate(batch)?.into_array(batch.num_rows()))
-        } else {
-            Some(new_null_array(&return_type, batch.num_rows()))
-        };
-
-        // walk backwards through the when/then expressions
-        for i in (0..self.when_then_expr.len()).rev() {
-            let i = i as usize;
-
-            let when_value = self.when_then_expr[i].0.evaluate(batch)?;
-            let when_value = when_value.into_array(batch.num_rows());
+    fn case_when_no_expr(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        self.evaluate_branches(batch, selection, |when, remainder| {
+            let when_value = when
+                .evaluate_selection(batch, remainder)?
+                .into_array(batch.num_rows());
             let when_value = when_value
-                .as_ref()
                 .as_any()
                 .downcast_ref::<BooleanArray>()
                 .expect("WHEN expression did not return a BooleanArray");
+            Ok(BooleanArray::from(when_value.data().clone()))
+        })
+    }
 
-            let then_value = self.when_then_expr[i].1.evaluate(batch)?;
-            let then_value = then_value.into_array(batch.num_rows());
-
-            current_value = Some(if_then_else(
-                &when_value,
-                then_value,
-                current_value.unwrap(),
-                &return_type,
-            )?);
+    /// Evaluates the when/then expressions in order for the rows of
+    /// `selection`, where `when_match` returns which of the rows `remainder`
+    /// that did not match a previous "when" expression match the given one.
+    /// Each "then" expression and the "else" expression are only evaluated
+    /// for the rows that take them.
+    fn evaluate_branches<F>(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+        when_match: F,
+    ) -> Result<ColumnarValue>
+    where
+        F: Fn(&Arc<dyn PhysicalExpr>, &SelectionVector) -> Result<BooleanArray>,
+    {
+        let num_rows = batch.num_rows();
+        let return_type = self.data_type(&batch.schema())?;
+
+        // the rows that match no branch are null
+        let mut results = vec![new_null_array(&return_type, num_rows)];
+        let mut choices = vec![0; num_rows];
+        let mut remainder = selection.clone();
+
+        for (when, then) in &self.when_then_expr {
+            if remainder.selected_count() == 0 {
+                break;
+            }
+            let when_value = when_match(when, &remainder)?;
+            let matched = remainder.and(&when_value);
+            take_branch(then, batch, &matched, &mut results, &mut choices)?;
+            remainder = remainder.and_not(&when_value);
+        }
+        if let Some(e) = &self.else_expr {
+            take_branch(e, batch, &remainder, &mut results, &mut choices)?;
         }
 
-        Ok(ColumnarValue::Array(current_value.unwrap()))
+        merge(&results, &choices).map(ColumnarValue::Array)
+    }
+}
+
+/// Evaluates `expr` for the `rows` that take its branch and records them in
+/// `choices`
+fn take_branch(
+    expr: &Arc<dyn PhysicalExpr>,
+    batch: &RecordBatch,
+    rows: &SelectionVector,
+    results: &mut Vec<ArrayRef>,
+    choices: &mut [usize],
+) -> Result<()> {
+    if rows.selected_count() == 0 {
+        return Ok(());
+    }
+    let result = expr
+        .evaluate_selection(batch, rows)?
+        .into_array(batch.num_rows());
+    results.push(result);
+    for i in rows.indices() {
+        choices[*i as usize] = results.len() - 1;
     }
+    Ok(())
 }
 
 impl PhysicalExpr for CaseExpr {
@@ -415,14 +334,22 @@ impl PhysicalExpr for CaseExpr {
     }
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        self.evaluate_selection(batch, &SelectionVector::all(batch.num_rows()))
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
         if self.expr.is_some() {
             // this use case evaluates "expr" and then compares the values with the "when"
             // values
-            self.case_when_with_expr(batch)
+            self.case_when_with_expr(batch, selection)
         } else {
             // The "when" conditions all evaluate to boolean in this use case and can be
             // arbitrary expressions
-            self.case_when_no_expr(batch)
+            self.case_when_no_expr(batch, selection)
         }
     }
 }
@@ -442,7 +369,7 @@ mod tests {
     use crate::{
         error::Result,
         logical_plan::Operator,
-        physical_plan::expressions::{binary, col, lit},
+        physical_plan::expressions::{binary, col, is_null, lit},
         scalar::ScalarValue,
     };
     use arrow::array::StringArray;
@@ -572,6 +499,98 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn case_short_circuit() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]);
+        let a = Int32Array::from(vec![4, 6, 8]);
+        let b = Int32Array::from(vec![2, 0, 4]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // CASE WHEN b = 0 THEN -1 ELSE a / b END, where a / b is only
+        // evaluated for the rows where b is not zero
+        let when = binary(
+            col("b"),
+            Operator::Eq,
+            lit(ScalarValue::Int32(Some(0))),
+            &batch.schema(),
+        )?;
+        let then = lit(ScalarValue::Int32(Some(-1)));
+        let else_value = binary(col("a"), Operator::Divide, col("b"), &batch.schema())?;
+
+        let expr = case(None, &[(when, then)], Some(else_value))?;
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .expect("failed to downcast to Int32Array");
+
+        let expected = &Int32Array::from(vec![Some(2), Some(-1), Some(2)]);
+
+        assert_eq!(expected, result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_case_selection() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("b", DataType::Int32, true)]);
+        let b = Int32Array::from(vec![Some(2), Some(0), None, Some(10)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(b)])?;
+        let schema = batch.schema();
+
+        // CASE WHEN b IS NULL OR b = 0 THEN 0
+        //      ELSE CASE WHEN 10 / b > 2 THEN 1 ELSE 2 END END,
+        // where the inner CASE only sees the rows where b is neither null nor 0
+        let when = binary(
+            is_null(col("b"))?,
+            Operator::Or,
+            binary(
+                col("b"),
+                Operator::Eq,
+                lit(ScalarValue::Int32(Some(0))),
+                &schema,
+            )?,
+            &schema,
+        )?;
+        let quotient = binary(
+            lit(ScalarValue::Int32(Some(10))),
+            Operator::Divide,
+            col("b"),
+            &schema,
+        )?;
+        let inner = case(
+            None,
+            &[(
+                binary(
+                    quotient,
+                    Operator::Gt,
+                    lit(ScalarValue::Int32(Some(2))),
+                    &schema,
+                )?,
+                lit(ScalarValue::Int32(Some(1))),
+            )],
+            Some(lit(ScalarValue::Int32(Some(2)))),
+        )?;
+        let expr = case(
+            None,
+            &[(when, lit(ScalarValue::Int32(Some(0))))],
+            Some(inner),
+        )?;
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .expect("failed to downcast to Int32Array");
+        assert_eq!(result, &Int32Array::from(vec![1, 0, 0, 2]));
+
+        Ok(())
+    }
+
     fn case_test_batch() -> Result<RecordBatch> {
         let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
         let a = StringArray::from(vec![Some("foo"), Some("baz"), None, Some("bar")]);
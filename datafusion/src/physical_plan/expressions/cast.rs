@@ -19,10 +19,13 @@ use std::any::Any;
 use std::fmt;
 use std::sync::Arc;
 
+use super::coercion::is_numeric;
 use super::ColumnarValue;
 use crate::error::{DataFusionError, Result};
+use crate::physical_plan::datetime_expressions::nanos_per_unit;
 use crate::physical_plan::PhysicalExpr;
 use crate::scalar::ScalarValue;
+use arrow::array::{ArrayRef, Int64Array};
 use arrow::compute;
 use arrow::compute::kernels;
 use arrow::compute::CastOptions;
@@ -92,20 +95,26 @@ impl PhysicalExpr for CastExpr {
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
         let value = self.expr.evaluate(batch)?;
         match value {
-            ColumnarValue::Array(array) => {
-                Ok(ColumnarValue::Array(kernels::cast::cast_with_options(
-                    &array,
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(cast_column(
+                &array,
+                &self.cast_type,
+                &self.cast_options,
+            )?)),
+            // there are no dictionary scalars, so the scalar is cast as an
+            // array to keep the type of the cast
+            ColumnarValue::Scalar(scalar)
+                if matches!(self.cast_type, DataType::Dictionary(_, _)) =>
+            {
+                Ok(ColumnarValue::Array(cast_column(
+                    &scalar.to_array_of_size(batch.num_rows()),
                     &self.cast_type,
                     &self.cast_options,
                 )?))
             }
             ColumnarValue::Scalar(scalar) => {
                 let scalar_array = scalar.to_array();
-                let cast_array = kernels::cast::cast_with_options(
-                    &scalar_array,
-                    &self.cast_type,
-                    &self.cast_options,
-                )?;
+                let cast_array =
+                    cast_column(&scalar_array, &self.cast_type, &self.cast_options)?;
                 let cast_scalar = ScalarValue::try_from_array(&cast_array, 0)?;
                 Ok(ColumnarValue::Scalar(cast_scalar))
             }
@@ -113,6 +122,62 @@ impl PhysicalExpr for CastExpr {
     }
 }
 
+/// Casts `array` to `cast_type`, checking that the values fit the target type.
+///
+/// Numbers that overflow the target type, and timestamps that overflow or
+/// lose precision when converted to another unit, are NULL if
+/// `cast_options.safe` is set and an error otherwise, rather than wrapping
+/// around or being truncated.
+pub fn cast_column(
+    array: &ArrayRef,
+    cast_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef> {
+    let cast_array = match (array.data_type(), cast_type) {
+        (DataType::Timestamp(from_unit, _), DataType::Timestamp(to_unit, _))
+            if from_unit != to_unit =>
+        {
+            let (from, to) = (nanos_per_unit(from_unit), nanos_per_unit(to_unit));
+            let values = kernels::cast::cast(array, &DataType::Int64)?;
+            let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+            let converted = values
+                .iter()
+                .map(|v| {
+                    v.and_then(|v| {
+                        if from >= to {
+                            v.checked_mul(from / to)
+                        } else if v % (to / from) == 0 {
+                            Some(v / (to / from))
+                        } else {
+                            None
+                        }
+                    })
+                })
+                .collect::<Int64Array>();
+            kernels::cast::cast(&(Arc::new(converted) as ArrayRef), cast_type)?
+        }
+        _ => kernels::cast::cast_with_options(array, cast_type, cast_options)?,
+    };
+
+    let checked = match (array.data_type(), cast_type) {
+        (DataType::Timestamp(_, _), DataType::Timestamp(_, _)) => true,
+        (from, to) => is_numeric(from) && is_numeric(to),
+    };
+    if checked && !cast_options.safe && cast_array.null_count() > array.null_count() {
+        // the kernels return NULL for values that do not fit the target type
+        let i = (0..array.len())
+            .find(|i| array.is_valid(*i) && cast_array.is_null(*i))
+            .unwrap();
+        return Err(DataFusionError::Execution(format!(
+            "Cannot cast {} from {:?} to {:?} without overflow or loss of precision",
+            ScalarValue::try_from_array(array, i)?,
+            array.data_type(),
+            cast_type
+        )));
+    }
+    Ok(cast_array)
+}
+
 /// Return a PhysicalExpression representing `expr` casted to
 /// `cast_type`, if any casting is needed.
 ///
@@ -158,9 +223,12 @@ mod tests {
     use super::*;
     use crate::error::Result;
     use crate::physical_plan::expressions::col;
-    use arrow::array::{StringArray, Time64NanosecondArray};
+    use arrow::array::{
+        Array, Int8Array, StringArray, Time64NanosecondArray, TimestampMillisecondArray,
+        TimestampSecondArray,
+    };
     use arrow::{
-        array::{Array, Int32Array, Int64Array, TimestampNanosecondArray, UInt32Array},
+        array::{Int32Array, TimestampNanosecondArray, UInt32Array},
         datatypes::*,
     };
 
@@ -298,4 +366,54 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn cast_overflow() -> Result<()> {
+        let array: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(300)]));
+
+        let err = cast_column(&array, &DataType::Int8, &DEFAULT_DATAFUSION_CAST_OPTIONS)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: Cannot cast 300 from Int64 to Int8 without overflow or loss of precision"
+        );
+
+        let result = cast_column(&array, &DataType::Int8, &CastOptions { safe: true })?;
+        let result = result.as_any().downcast_ref::<Int8Array>().unwrap();
+        assert_eq!(result.iter().collect::<Vec<_>>(), vec![Some(1), None, None]);
+        Ok(())
+    }
+
+    #[test]
+    fn cast_timestamp_units() -> Result<()> {
+        let array: ArrayRef = Arc::new(TimestampSecondArray::from(vec![
+            Some(1),
+            None,
+            Some(i64::MAX / 10),
+        ]));
+        let to_millis = DataType::Timestamp(TimeUnit::Millisecond, None);
+        cast_column(&array, &to_millis, &DEFAULT_DATAFUSION_CAST_OPTIONS)
+            .expect_err("expected overflow");
+        let result = cast_column(&array, &to_millis, &CastOptions { safe: true })?;
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(
+            result.iter().collect::<Vec<_>>(),
+            vec![Some(1000), None, None]
+        );
+
+        let array: ArrayRef = Arc::new(TimestampMillisecondArray::from(vec![2000, 2500]));
+        let to_seconds = DataType::Timestamp(TimeUnit::Second, None);
+        cast_column(&array, &to_seconds, &DEFAULT_DATAFUSION_CAST_OPTIONS)
+            .expect_err("expected loss of precision");
+        let result = cast_column(&array, &to_seconds, &CastOptions { safe: true })?;
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampSecondArray>()
+            .unwrap();
+        assert_eq!(result.iter().collect::<Vec<_>>(), vec![Some(2), None]);
+        Ok(())
+    }
 }
@@ -0,0 +1,187 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! COALESCE expression
+
+use std::{any::Any, sync::Arc};
+
+use arrow::array::new_null_array;
+use arrow::compute;
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+
+use super::case::merge;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{selection::SelectionVector, ColumnarValue, PhysicalExpr};
+
+/// COALESCE(expr1, expr2, ...) returns the first of its arguments that is not
+/// null. Each argument is evaluated once, and only for the rows whose previous
+/// arguments are null. All arguments must be of the same type.
+#[derive(Debug)]
+pub struct CoalesceExpr {
+    args: Vec<Arc<dyn PhysicalExpr>>,
+}
+
+impl CoalesceExpr {
+    /// Create a new COALESCE expression
+    pub fn try_new(args: Vec<Arc<dyn PhysicalExpr>>) -> Result<Self> {
+        if args.is_empty() {
+            Err(DataFusionError::Plan(
+                "coalesce requires at least one argument".to_string(),
+            ))
+        } else {
+            Ok(Self { args })
+        }
+    }
+
+    /// The arguments, in the order in which they are checked
+    pub fn args(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.args
+    }
+}
+
+impl std::fmt::Display for CoalesceExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "coalesce({})", args.join(", "))
+    }
+}
+
+impl PhysicalExpr for CoalesceExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, input_schema: &Schema) -> Result<DataType> {
+        self.args[0].data_type(input_schema)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        // the result is only null if all arguments are null
+        for arg in &self.args {
+            if !arg.nullable(input_schema)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        self.evaluate_selection(batch, &SelectionVector::all(batch.num_rows()))
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        let num_rows = batch.num_rows();
+        let return_type = self.data_type(&batch.schema())?;
+
+        // the rows for which all arguments are null are null
+        let mut results = vec![new_null_array(&return_type, num_rows)];
+        let mut choices = vec![0; num_rows];
+        let mut remainder = selection.clone();
+
+        for arg in &self.args {
+            if remainder.selected_count() == 0 {
+                break;
+            }
+            let value = arg
+                .evaluate_selection(batch, &remainder)?
+                .into_array(num_rows);
+            let is_valid = compute::is_not_null(value.as_ref())?;
+            let taken = remainder.and(&is_valid);
+            if taken.selected_count() > 0 {
+                results.push(value);
+                for i in taken.indices() {
+                    choices[*i as usize] = results.len() - 1;
+                }
+            }
+            remainder = remainder.and_not(&is_valid);
+        }
+
+        merge(&results, &choices).map(ColumnarValue::Array)
+    }
+}
+
+/// Create a COALESCE expression
+pub fn coalesce(args: Vec<Arc<dyn PhysicalExpr>>) -> Result<Arc<dyn PhysicalExpr>> {
+    Ok(Arc::new(CoalesceExpr::try_new(args)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::Operator;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::scalar::ScalarValue;
+    use arrow::array::{ArrayRef, Int32Array};
+    use arrow::datatypes::Field;
+
+    #[test]
+    fn coalesce_int32() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]);
+        let a = Int32Array::from(vec![Some(1), None, None, Some(4)]);
+        let b = Int32Array::from(vec![Some(5), Some(6), None, None]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        let expr = coalesce(vec![col("a"), col("b"), lit(ScalarValue::Int32(Some(10)))])?;
+        assert!(!expr.nullable(&batch.schema())?);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef =
+            Arc::new(Int32Array::from(vec![Some(1), Some(6), Some(10), Some(4)]));
+        assert_eq!(&result, &expected);
+        Ok(())
+    }
+
+    #[test]
+    fn coalesce_short_circuit() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, false),
+        ]);
+        let a = Int32Array::from(vec![Some(1), None, Some(3)]);
+        let b = Int32Array::from(vec![0, 2, 0]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // COALESCE(a, 4 / b), where 4 / b is only evaluated for the rows
+        // where a is null
+        let fallback = binary(
+            lit(ScalarValue::Int32(Some(4))),
+            Operator::Divide,
+            col("b"),
+            &batch.schema(),
+        )?;
+        let expr = coalesce(vec![col("a"), fallback])?;
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef =
+            Arc::new(Int32Array::from(vec![Some(1), Some(2), Some(3)]));
+        assert_eq!(&result, &expected);
+        Ok(())
+    }
+}
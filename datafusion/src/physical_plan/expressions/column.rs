@@ -25,7 +25,7 @@ use arrow::{
 };
 
 use crate::error::Result;
-use crate::physical_plan::{ColumnarValue, PhysicalExpr};
+use crate::physical_plan::{selection::SelectionVector, ColumnarValue, PhysicalExpr};
 
 /// Represents the column at a given index in a RecordBatch
 #[derive(Debug)]
@@ -78,6 +78,15 @@ impl PhysicalExpr for Column {
             batch.column(batch.schema().index_of(&self.name)?).clone(),
         ))
     }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        _selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        // the column as it is is cheaper than only its selected rows
+        self.evaluate(batch)
+    }
 }
 
 /// Create a column expression
@@ -26,7 +26,7 @@ use crate::scalar::ScalarValue;
 use arrow::compute;
 use arrow::datatypes::DataType;
 use arrow::{
-    array::{ArrayRef, UInt64Array},
+    array::{ArrayBuilder, ArrayRef, UInt64Array},
     datatypes::Field,
 };
 
@@ -139,6 +139,10 @@ impl Accumulator for CountAccumulator {
         Ok(vec![ScalarValue::UInt64(Some(self.count))])
     }
 
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        ScalarValue::UInt64(Some(self.count)).append_to_builder(builders[0].as_mut())
+    }
+
     fn evaluate(&self) -> Result<ScalarValue> {
         Ok(ScalarValue::UInt64(Some(self.count)))
     }
@@ -0,0 +1,368 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Arithmetic on decimal arrays, whose values are stored as `i128` scaled by
+//! `10^scale`
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, DecimalArray, DecimalBuilder, Int16Array, Int32Array, Int64Array,
+    Int8Array, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow::datatypes::DataType;
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
+
+/// The maximum precision of a 128 bit decimal
+pub const MAX_DECIMAL_PRECISION: usize = 38;
+
+/// Returns the precision and scale of a decimal, or of the decimal that can hold
+/// every value of an integer type
+pub(crate) fn decimal_precision_scale(data_type: &DataType) -> Option<(usize, usize)> {
+    match data_type {
+        DataType::Decimal(precision, scale) => Some((*precision, *scale)),
+        DataType::Int8 | DataType::UInt8 => Some((3, 0)),
+        DataType::Int16 | DataType::UInt16 => Some((5, 0)),
+        DataType::Int32 | DataType::UInt32 => Some((10, 0)),
+        DataType::Int64 => Some((19, 0)),
+        DataType::UInt64 => Some((20, 0)),
+        _ => None,
+    }
+}
+
+/// Caps `precision` to [`MAX_DECIMAL_PRECISION`], giving up digits of the
+/// fraction but keeping a scale of at least 6 where possible
+fn bounded_decimal(precision: usize, scale: usize) -> DataType {
+    if precision <= MAX_DECIMAL_PRECISION {
+        return DataType::Decimal(precision, scale);
+    }
+    let integer_digits = precision - scale;
+    let min_scale = scale.min(6);
+    let scale = MAX_DECIMAL_PRECISION
+        .saturating_sub(integer_digits)
+        .max(min_scale);
+    DataType::Decimal(MAX_DECIMAL_PRECISION, scale)
+}
+
+/// Returns the result type of the arithmetic operator `op` when at least one
+/// side is a decimal and the other is a decimal or an integer, following the
+/// SQL rules for precision and scale. Returns `None` if this is not decimal
+/// arithmetic.
+pub(crate) fn decimal_arithmetic_type(
+    lhs_type: &DataType,
+    op: &Operator,
+    rhs_type: &DataType,
+) -> Option<DataType> {
+    if !matches!(lhs_type, DataType::Decimal(_, _))
+        && !matches!(rhs_type, DataType::Decimal(_, _))
+    {
+        return None;
+    }
+    let (p1, s1) = decimal_precision_scale(lhs_type)?;
+    let (p2, s2) = decimal_precision_scale(rhs_type)?;
+    let (precision, scale) = match op {
+        Operator::Plus | Operator::Minus => {
+            let scale = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+        }
+        Operator::Multiply => (p1 + p2 + 1, s1 + s2),
+        Operator::Divide => {
+            let scale = (s1 + p2 + 1).max(6);
+            (p1 - s1 + s2 + scale, scale)
+        }
+        Operator::Modulus => {
+            let scale = s1.max(s2);
+            ((p1 - s1).min(p2 - s2) + scale, scale)
+        }
+        _ => return None,
+    };
+    Some(bounded_decimal(precision, scale))
+}
+
+/// Returns the values of a decimal or integer array as `i128`
+fn decimal_values(array: &ArrayRef) -> Result<Vec<Option<i128>>> {
+    macro_rules! integer_values {
+        ($ARRAY_TYPE:ident) => {{
+            let array = array.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+            array.iter().map(|v| v.map(|v| v as i128)).collect()
+        }};
+    }
+    Ok(match array.data_type() {
+        DataType::Decimal(_, _) => {
+            let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+            (0..array.len())
+                .map(|i| {
+                    if array.is_null(i) {
+                        None
+                    } else {
+                        Some(array.value(i))
+                    }
+                })
+                .collect()
+        }
+        DataType::Int8 => integer_values!(Int8Array),
+        DataType::Int16 => integer_values!(Int16Array),
+        DataType::Int32 => integer_values!(Int32Array),
+        DataType::Int64 => integer_values!(Int64Array),
+        DataType::UInt8 => integer_values!(UInt8Array),
+        DataType::UInt16 => integer_values!(UInt16Array),
+        DataType::UInt32 => integer_values!(UInt32Array),
+        DataType::UInt64 => integer_values!(UInt64Array),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Data type {:?} not supported for decimal arithmetic",
+                other
+            )))
+        }
+    })
+}
+
+fn overflow(op: &Operator) -> DataFusionError {
+    DataFusionError::Execution(format!("Decimal overflow in {} operation", op))
+}
+
+/// Changes the scale of `value` from `from` to `to`, rounding half away from
+/// zero when digits are dropped
+pub(crate) fn rescale(value: i128, from: usize, to: usize) -> Option<i128> {
+    if to >= from {
+        10i128
+            .checked_pow((to - from) as u32)
+            .and_then(|factor| value.checked_mul(factor))
+    } else {
+        let factor = 10i128.checked_pow((from - to) as u32)?;
+        let (quotient, remainder) = (value / factor, value % factor);
+        if remainder.abs() * 2 >= factor {
+            Some(quotient + value.signum())
+        } else {
+            Some(quotient)
+        }
+    }
+}
+
+/// Divides `numerator` by `denominator`, rounding half away from zero
+fn div_round(numerator: i128, denominator: i128) -> Option<i128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = numerator % denominator;
+    if remainder.checked_abs()?.checked_mul(2)? >= denominator.checked_abs()? {
+        let sign = if (numerator < 0) == (denominator < 0) {
+            1
+        } else {
+            -1
+        };
+        Some(quotient + sign)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Evaluates the arithmetic operator `op` on a decimal and a decimal or integer
+/// array, producing a decimal array of `result_type` as returned by
+/// [`decimal_arithmetic_type`]. Errors if a result does not fit the precision
+/// or on a division by zero.
+pub(crate) fn decimal_arithmetic(
+    left: &ArrayRef,
+    op: &Operator,
+    right: &ArrayRef,
+    result_type: &DataType,
+) -> Result<ArrayRef> {
+    let (_, s1) = decimal_precision_scale(left.data_type()).unwrap_or((0, 0));
+    let (_, s2) = decimal_precision_scale(right.data_type()).unwrap_or((0, 0));
+    let (precision, scale) = match result_type {
+        DataType::Decimal(precision, scale) => (*precision, *scale),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Decimal arithmetic can not produce {:?}",
+                other
+            )))
+        }
+    };
+    let max_value = 10i128.pow(precision as u32);
+
+    let compute = |l: i128, r: i128| -> Result<i128> {
+        let value = match op {
+            Operator::Plus | Operator::Minus | Operator::Modulus => {
+                let l = rescale(l, s1, scale).ok_or_else(|| overflow(op))?;
+                let r = rescale(r, s2, scale).ok_or_else(|| overflow(op))?;
+                match op {
+                    Operator::Plus => l.checked_add(r),
+                    Operator::Minus => l.checked_sub(r),
+                    _ if r == 0 => {
+                        return Err(DataFusionError::Execution(
+                            "Division by zero".to_string(),
+                        ))
+                    }
+                    _ => Some(l % r),
+                }
+            }
+            Operator::Multiply => {
+                l.checked_mul(r).and_then(|v| rescale(v, s1 + s2, scale))
+            }
+            Operator::Divide => {
+                if r == 0 {
+                    return Err(DataFusionError::Execution(
+                        "Division by zero".to_string(),
+                    ));
+                }
+                // l / r has a scale of s1 - s2, so shift l to reach `scale`
+                let shift = scale + s2;
+                if shift >= s1 {
+                    rescale(l, s1, shift).and_then(|l| div_round(l, r))
+                } else {
+                    div_round(l, r).and_then(|v| rescale(v, s1 - s2, scale))
+                }
+            }
+            _ => {
+                return Err(DataFusionError::Internal(format!(
+                    "Operator {} is not a decimal arithmetic operator",
+                    op
+                )))
+            }
+        }
+        .ok_or_else(|| overflow(op))?;
+        if value.checked_abs().map_or(true, |v| v >= max_value) {
+            return Err(overflow(op));
+        }
+        Ok(value)
+    };
+
+    let left = decimal_values(left)?;
+    let right = decimal_values(right)?;
+    let mut builder = DecimalBuilder::new(left.len(), precision, scale);
+    for (l, r) in left.into_iter().zip(right) {
+        match (l, r) {
+            (Some(l), Some(r)) => builder.append_value(compute(l, r)?)?,
+            _ => builder.append_null()?,
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal_array(
+        values: Vec<Option<i128>>,
+        precision: usize,
+        scale: usize,
+    ) -> ArrayRef {
+        let mut builder = DecimalBuilder::new(values.len(), precision, scale);
+        for value in values {
+            match value {
+                Some(v) => builder.append_value(v).unwrap(),
+                None => builder.append_null().unwrap(),
+            }
+        }
+        Arc::new(builder.finish())
+    }
+
+    fn evaluate(left: &ArrayRef, op: Operator, right: &ArrayRef) -> Result<ArrayRef> {
+        let result_type =
+            decimal_arithmetic_type(left.data_type(), &op, right.data_type()).unwrap();
+        decimal_arithmetic(left, &op, right, &result_type)
+    }
+
+    #[test]
+    fn result_types() {
+        let d = |p, s| DataType::Decimal(p, s);
+        let cases = vec![
+            (d(5, 2), Operator::Plus, d(7, 3), d(8, 3)),
+            (d(5, 2), Operator::Minus, DataType::Int32, d(13, 2)),
+            (d(5, 2), Operator::Multiply, d(7, 3), d(13, 5)),
+            (d(5, 2), Operator::Divide, d(7, 3), d(16, 10)),
+            (d(5, 2), Operator::Modulus, d(7, 3), d(6, 3)),
+            (d(38, 10), Operator::Multiply, d(38, 10), d(38, 6)),
+        ];
+        for (lhs, op, rhs, expected) in cases {
+            assert_eq!(
+                decimal_arithmetic_type(&lhs, &op, &rhs),
+                Some(expected),
+                "{:?} {} {:?}",
+                lhs,
+                op,
+                rhs
+            );
+        }
+        assert_eq!(
+            decimal_arithmetic_type(&DataType::Int32, &Operator::Plus, &DataType::Int32),
+            None
+        );
+        assert_eq!(
+            decimal_arithmetic_type(&d(5, 2), &Operator::Plus, &DataType::Float64),
+            None
+        );
+    }
+
+    #[test]
+    fn arithmetic() -> Result<()> {
+        // 1.50, -2.25, NULL
+        let left = decimal_array(vec![Some(150), Some(-225), None], 5, 2);
+        // 0.125, 2.000, 1.000
+        let right = decimal_array(vec![Some(125), Some(2000), Some(1000)], 7, 3);
+
+        let check = |op: Operator, expected: Vec<Option<i128>>, scale: usize| {
+            let result = evaluate(&left, op, &right).unwrap();
+            let result = result.as_any().downcast_ref::<DecimalArray>().unwrap();
+            assert_eq!(result.scale(), scale);
+            let values = (0..result.len())
+                .map(|i| {
+                    if result.is_null(i) {
+                        None
+                    } else {
+                        Some(result.value(i))
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(values, expected, "{}", op);
+        };
+        check(Operator::Plus, vec![Some(1625), Some(-250), None], 3);
+        check(Operator::Minus, vec![Some(1375), Some(-4250), None], 3);
+        check(
+            Operator::Multiply,
+            vec![Some(18750), Some(-450000), None],
+            5,
+        );
+        // 1.5 / 0.125 = 12, -2.25 / 2 = -1.125
+        check(
+            Operator::Divide,
+            vec![Some(120_000_000_000), Some(-11_250_000_000), None],
+            10,
+        );
+        check(Operator::Modulus, vec![Some(0), Some(-250), None], 3);
+
+        // decimal and integer
+        let integers: ArrayRef = Arc::new(Int32Array::from(vec![2, 3, 4]));
+        let result = evaluate(&left, Operator::Multiply, &integers)?;
+        let result = result.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(result.value(0), 300);
+        assert_eq!(result.value(1), -675);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let left = decimal_array(vec![Some(150)], 5, 2);
+        let zero = decimal_array(vec![Some(0)], 5, 2);
+        assert!(evaluate(&left, Operator::Divide, &zero).is_err());
+        assert!(evaluate(&left, Operator::Modulus, &zero).is_err());
+
+        let max = decimal_array(vec![Some(10i128.pow(38) - 1)], 38, 0);
+        assert!(evaluate(&max, Operator::Plus, &max).is_err());
+    }
+}
@@ -20,27 +20,221 @@
 use std::any::Any;
 use std::sync::Arc;
 
+use std::collections::HashSet;
+
 use arrow::array::GenericStringArray;
 use arrow::array::{
     ArrayRef, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
-    Int64Array, Int8Array, StringOffsetSizeTrait, UInt16Array, UInt32Array, UInt64Array,
-    UInt8Array,
+    Int64Array, Int8Array, LargeStringArray, StringArray, StringOffsetSizeTrait,
+    UInt16Array, UInt32Array, UInt64Array, UInt8Array,
 };
+use arrow::compute::kernels::cast::cast;
 use arrow::{
     datatypes::{DataType, Schema},
     record_batch::RecordBatch,
 };
+use ordered_float::OrderedFloat;
 
+use super::bytes::byte_values;
+use super::{CastExpr, Literal};
 use crate::error::Result;
 use crate::physical_plan::{ColumnarValue, PhysicalExpr};
 use crate::scalar::ScalarValue;
 
+/// Lists of more constants than this are tested with a hash set
+const STATIC_FILTER_THRESHOLD: usize = 8;
+
 /// InList
 #[derive(Debug)]
 pub struct InListExpr {
     expr: Arc<dyn PhysicalExpr>,
     list: Vec<Arc<dyn PhysicalExpr>>,
     negated: bool,
+    /// The hashed values of a long list of constants, built once and used
+    /// for all batches instead of scanning the list for every row
+    static_filter: Option<StaticFilter>,
+}
+
+/// The non-null values of a list of constants of one type, in a hash set
+#[derive(Debug)]
+enum StaticFilterValues {
+    Boolean(HashSet<bool>),
+    Int8(HashSet<i8>),
+    Int16(HashSet<i16>),
+    Int32(HashSet<i32>),
+    Int64(HashSet<i64>),
+    UInt8(HashSet<u8>),
+    UInt16(HashSet<u16>),
+    UInt32(HashSet<u32>),
+    UInt64(HashSet<u64>),
+    Float32(HashSet<OrderedFloat<f32>>),
+    Float64(HashSet<OrderedFloat<f64>>),
+    Utf8(HashSet<String>),
+}
+
+/// A hash set of the values of a list of constants
+#[derive(Debug)]
+struct StaticFilter {
+    values: StaticFilterValues,
+    /// Whether the list contains a null, in which case values not in the
+    /// list are compared to null
+    contains_null: bool,
+}
+
+macro_rules! collect_static_values {
+    ($SCALARS:expr, $SCALAR_VALUE:ident, $MAP:expr) => {{
+        let mut values = HashSet::new();
+        for scalar in $SCALARS {
+            match scalar {
+                ScalarValue::$SCALAR_VALUE(Some(v)) => {
+                    values.insert($MAP(v));
+                }
+                _ => return None,
+            }
+        }
+        StaticFilterValues::$SCALAR_VALUE(values)
+    }};
+}
+
+/// The key of a float in a hash set of floats, that is equal for -0.0 and 0.0
+/// as they compare equal when the list is scanned
+fn f32_key(v: f32) -> OrderedFloat<f32> {
+    OrderedFloat::from(if v == 0.0 { 0.0 } else { v })
+}
+
+/// The key of a float in a hash set of floats, that is equal for -0.0 and 0.0
+/// as they compare equal when the list is scanned
+fn f64_key(v: f64) -> OrderedFloat<f64> {
+    OrderedFloat::from(if v == 0.0 { 0.0 } else { v })
+}
+
+impl StaticFilter {
+    /// Builds the hash set of the values of `list` if it only consists of
+    /// constants of a supported type
+    fn try_new(list: &[Arc<dyn PhysicalExpr>]) -> Option<Self> {
+        let scalars = list
+            .iter()
+            .map(constant_value)
+            .collect::<Option<Vec<_>>>()?;
+        let contains_null = scalars.iter().any(|s| s.is_null());
+        let non_null = scalars
+            .into_iter()
+            .filter(|s| !s.is_null())
+            .collect::<Vec<_>>();
+        let data_type = non_null.first()?.get_datatype();
+        let values = match data_type {
+            DataType::Boolean => collect_static_values!(non_null, Boolean, |v| v),
+            DataType::Int8 => collect_static_values!(non_null, Int8, |v| v),
+            DataType::Int16 => collect_static_values!(non_null, Int16, |v| v),
+            DataType::Int32 => collect_static_values!(non_null, Int32, |v| v),
+            DataType::Int64 => collect_static_values!(non_null, Int64, |v| v),
+            DataType::UInt8 => collect_static_values!(non_null, UInt8, |v| v),
+            DataType::UInt16 => collect_static_values!(non_null, UInt16, |v| v),
+            DataType::UInt32 => collect_static_values!(non_null, UInt32, |v| v),
+            DataType::UInt64 => collect_static_values!(non_null, UInt64, |v| v),
+            DataType::Float32 => {
+                collect_static_values!(non_null, Float32, f32_key)
+            }
+            DataType::Float64 => {
+                collect_static_values!(non_null, Float64, f64_key)
+            }
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                let mut values = HashSet::new();
+                for scalar in non_null {
+                    match scalar {
+                        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                            values.insert(v);
+                        }
+                        _ => return None,
+                    }
+                }
+                StaticFilterValues::Utf8(values)
+            }
+            _ => return None,
+        };
+        Some(Self {
+            values,
+            contains_null,
+        })
+    }
+
+    /// Tests the values of `array`, returning `None` if its type does not
+    /// match the type of the values of the list
+    fn evaluate(&self, array: &ArrayRef, negated: bool) -> Option<BooleanArray> {
+        macro_rules! contains {
+            ($ARRAY_TYPE:ident, $CONTAINS:expr) => {{
+                let array = array.as_any().downcast_ref::<$ARRAY_TYPE>()?;
+                array.iter().map(|x| x.map($CONTAINS)).collect::<Vec<_>>()
+            }};
+        }
+        let contains = match (&self.values, array.data_type()) {
+            (StaticFilterValues::Boolean(set), DataType::Boolean) => {
+                contains!(BooleanArray, |v| set.contains(&v))
+            }
+            (StaticFilterValues::Int8(set), DataType::Int8) => {
+                contains!(Int8Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::Int16(set), DataType::Int16) => {
+                contains!(Int16Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::Int32(set), DataType::Int32) => {
+                contains!(Int32Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::Int64(set), DataType::Int64) => {
+                contains!(Int64Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::UInt8(set), DataType::UInt8) => {
+                contains!(UInt8Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::UInt16(set), DataType::UInt16) => {
+                contains!(UInt16Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::UInt32(set), DataType::UInt32) => {
+                contains!(UInt32Array, |v| set.contains(&v))
+            }
+            (StaticFilterValues::UInt64(set), DataType::UInt64) => {
+                contains!(UInt64Array, |v| set.contains(&v))
+            }
+            // NaN is not equal to any value of the list, not even NaN
+            (StaticFilterValues::Float32(set), DataType::Float32) => {
+                contains!(Float32Array, |v: f32| !v.is_nan()
+                    && set.contains(&f32_key(v)))
+            }
+            (StaticFilterValues::Float64(set), DataType::Float64) => {
+                contains!(Float64Array, |v: f64| !v.is_nan()
+                    && set.contains(&f64_key(v)))
+            }
+            (StaticFilterValues::Utf8(set), DataType::Utf8) => {
+                contains!(StringArray, |v: &str| set.contains(v))
+            }
+            (StaticFilterValues::Utf8(set), DataType::LargeUtf8) => {
+                contains!(LargeStringArray, |v: &str| set.contains(v))
+            }
+            _ => return None,
+        };
+        Some(
+            contains
+                .into_iter()
+                .map(|contains| match contains {
+                    Some(true) => Some(!negated),
+                    Some(false) if self.contains_null => None,
+                    Some(false) => Some(negated),
+                    None => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Returns the value of `expr` if it is a literal, or a cast of a literal
+fn constant_value(expr: &Arc<dyn PhysicalExpr>) -> Option<ScalarValue> {
+    if let Some(literal) = expr.as_any().downcast_ref::<Literal>() {
+        return Some(literal.value().clone());
+    }
+    let cast_expr = expr.as_any().downcast_ref::<CastExpr>()?;
+    let literal = cast_expr.expr().as_any().downcast_ref::<Literal>()?;
+    let array = cast(&literal.value().to_array(), cast_expr.cast_type()).ok()?;
+    ScalarValue::try_from_array(&array, 0).ok()
 }
 
 macro_rules! make_contains {
@@ -106,10 +300,16 @@ impl InListExpr {
         list: Vec<Arc<dyn PhysicalExpr>>,
         negated: bool,
     ) -> Self {
+        let static_filter = if list.len() > STATIC_FILTER_THRESHOLD {
+            StaticFilter::try_new(&list)
+        } else {
+            None
+        };
         Self {
             expr,
             list,
             negated,
+            static_filter,
         }
     }
 
@@ -192,6 +392,49 @@ impl InListExpr {
                 .collect::<BooleanArray>(),
         )))
     }
+
+    /// Compare for binary types, whose values can be compared with values of
+    /// any other binary type
+    fn compare_binary(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        negated: bool,
+    ) -> Result<ColumnarValue> {
+        let mut contains_null = false;
+        let values = list_values
+            .iter()
+            .flat_map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                        Some(v.as_slice())
+                    }
+                    ScalarValue::Binary(None)
+                    | ScalarValue::LargeBinary(None)
+                    | ScalarValue::Utf8(None) => {
+                        contains_null = true;
+                        None
+                    }
+                    datatype => unimplemented!("Unexpected type {} for InList", datatype),
+                },
+                ColumnarValue::Array(_) => {
+                    unimplemented!("InList does not yet support nested columns.")
+                }
+            })
+            .collect::<Vec<&[u8]>>();
+
+        Ok(ColumnarValue::Array(Arc::new(
+            byte_values(&array)?
+                .into_iter()
+                .map(|x| match x.map(|x| values.contains(&x)) {
+                    Some(true) => Some(!negated),
+                    Some(false) if contains_null => None,
+                    Some(false) => Some(negated),
+                    None => None,
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
 }
 
 impl std::fmt::Display for InListExpr {
@@ -221,6 +464,14 @@ impl PhysicalExpr for InListExpr {
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
         let value = self.expr.evaluate(batch)?;
         let value_data_type = value.data_type();
+
+        if let Some(static_filter) = &self.static_filter {
+            let array = value.clone().into_array(batch.num_rows());
+            if let Some(result) = static_filter.evaluate(&array, self.negated) {
+                return Ok(ColumnarValue::Array(Arc::new(result)));
+            }
+        }
+
         let list_values = self
             .list
             .iter()
@@ -270,6 +521,9 @@ impl PhysicalExpr for InListExpr {
             DataType::LargeUtf8 => {
                 self.compare_utf8::<i64>(array, list_values, self.negated)
             }
+            DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+                self.compare_binary(array, list_values, self.negated)
+            }
             datatype => {
                 unimplemented!("InList does not support datatype {:?}.", datatype)
             }
@@ -288,11 +542,14 @@ pub fn in_list(
 
 #[cfg(test)]
 mod tests {
-    use arrow::{array::StringArray, datatypes::Field};
+    use arrow::{
+        array::{FixedSizeBinaryBuilder, StringArray},
+        datatypes::Field,
+    };
 
     use super::*;
     use crate::error::Result;
-    use crate::physical_plan::expressions::{col, lit};
+    use crate::physical_plan::expressions::{cast, col, lit};
 
     // applies the in_list expr to an input batch and list
     macro_rules! in_list {
@@ -455,4 +712,120 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn in_list_fixed_size_binary() -> Result<()> {
+        let schema =
+            Schema::new(vec![Field::new("a", DataType::FixedSizeBinary(2), true)]);
+        let mut builder = FixedSizeBinaryBuilder::new(3, 2);
+        builder.append_value(b"ab")?;
+        builder.append_value(b"cd")?;
+        builder.append_null()?;
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(builder.finish())])?;
+
+        // expression: "a in (x'6162', x'6566')"
+        let list = vec![
+            lit(ScalarValue::Binary(Some(b"ab".to_vec()))),
+            lit(ScalarValue::LargeBinary(Some(b"ef".to_vec()))),
+        ];
+        in_list!(batch, list, &false, vec![Some(true), Some(false), None]);
+
+        // expression: "a not in (x'6162', NULL)"
+        let list = vec![
+            lit(ScalarValue::Binary(Some(b"ab".to_vec()))),
+            lit(ScalarValue::Binary(None)),
+        ];
+        in_list!(batch, list, &true, vec![Some(false), None, None]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_static_filter() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let a = Int32Array::from(vec![Some(3), Some(30), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // expression: "a in (0, 1, ..., 19)" with Int64 literals cast to Int32
+        let list = (0..20)
+            .map(|i| cast(lit(ScalarValue::Int64(Some(i))), &schema, DataType::Int32))
+            .collect::<Result<Vec<_>>>()?;
+        let expr = InListExpr::new(col("a"), list.clone(), false);
+        assert!(expr.static_filter.is_some());
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(false), None]
+        );
+        in_list!(batch, list, &true, vec![Some(false), Some(true), None]);
+
+        // expression: "a in (0, 1, ..., 19, NULL)"
+        let mut list = (0..20)
+            .map(|i| lit(ScalarValue::Int32(Some(i))))
+            .collect::<Vec<_>>();
+        list.push(lit(ScalarValue::Int32(None)));
+        in_list!(batch, list, &false, vec![Some(true), None, None]);
+
+        // expression: "a in (0, 1, ..., 19, a)" is not static
+        let mut list = (0..20)
+            .map(|i| lit(ScalarValue::Int32(Some(i))))
+            .collect::<Vec<_>>();
+        list.push(col("a"));
+        assert!(InListExpr::new(col("a"), list, false)
+            .static_filter
+            .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_float64_nan_and_zero() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let a = Float64Array::from(vec![Some(f64::NAN), Some(-0.0), Some(0.0)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // the list is scanned for short lists and hashed for long ones, both of
+        // which must compare NaN and -0.0 like `==`
+        for len in [2, STATIC_FILTER_THRESHOLD + 2] {
+            // expression: "a in (NaN, 0.0, 1.0, ...)"
+            let list = [f64::NAN, 0.0]
+                .iter()
+                .copied()
+                .chain((1_i32..).map(f64::from))
+                .take(len)
+                .map(|v| lit(ScalarValue::Float64(Some(v))))
+                .collect::<Vec<_>>();
+            let expr = InListExpr::new(col("a"), list.clone(), false);
+            assert_eq!(expr.static_filter.is_some(), len > STATIC_FILTER_THRESHOLD);
+            in_list!(
+                batch,
+                list.clone(),
+                &false,
+                vec![Some(false), Some(true), Some(true)]
+            );
+            in_list!(
+                batch,
+                list,
+                &true,
+                vec![Some(true), Some(false), Some(false)]
+            );
+
+            // expression: "a in (-0.0, 1.0, ...)"
+            let list = std::iter::once(-0.0)
+                .chain((1_i32..).map(f64::from))
+                .take(len)
+                .map(|v| lit(ScalarValue::Float64(Some(v))))
+                .collect::<Vec<_>>();
+            in_list!(
+                batch,
+                list,
+                &false,
+                vec![Some(false), Some(true), Some(true)]
+            );
+        }
+
+        Ok(())
+    }
 }
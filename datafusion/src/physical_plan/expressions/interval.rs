@@ -0,0 +1,339 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Arithmetic on interval arrays. Like the month-day-nano interval of SQL, the
+//! components of an interval are kept apart: a month has no fixed number of days
+//! and a day no fixed number of milliseconds.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, Float64Array, IntervalDayTimeArray, IntervalYearMonthArray,
+};
+use arrow::compute::kernels::cast::cast;
+use arrow::datatypes::{DataType, IntervalUnit};
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
+
+use super::coercion::is_numeric;
+
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+/// Returns the interval type of adding or subtracting intervals of the same
+/// unit, or of multiplying or dividing an interval by a number. Returns `None`
+/// if this is not interval arithmetic.
+pub(crate) fn interval_arithmetic_type(
+    lhs_type: &DataType,
+    op: &Operator,
+    rhs_type: &DataType,
+) -> Option<DataType> {
+    let additive = matches!(op, Operator::Plus | Operator::Minus);
+    let multiplicative = matches!(op, Operator::Multiply | Operator::Divide);
+    match (lhs_type, rhs_type) {
+        (DataType::Interval(l), DataType::Interval(r)) if additive && l == r => {
+            Some(lhs_type.clone())
+        }
+        (DataType::Interval(_), other) if multiplicative && is_numeric(other) => {
+            Some(lhs_type.clone())
+        }
+        (other, DataType::Interval(_))
+            if *op == Operator::Multiply && is_numeric(other) =>
+        {
+            Some(rhs_type.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Splits a day-time interval into its days and milliseconds
+fn days_millis(value: i64) -> (i32, i32) {
+    ((value >> 32) as i32, value as i32)
+}
+
+fn day_time(days: i32, millis: i32) -> i64 {
+    ((days as i64) << 32) | (millis as u32 as i64)
+}
+
+fn overflow(op: &Operator) -> DataFusionError {
+    DataFusionError::Execution(format!("Interval overflow in {} operation", op))
+}
+
+/// Converts a rounded `f64` to `i32`, if it is in range
+fn to_i32(value: f64) -> Option<i32> {
+    let value = value.round();
+    if value.is_finite() && value >= i32::MIN as f64 && value <= i32::MAX as f64 {
+        Some(value as i32)
+    } else {
+        None
+    }
+}
+
+/// Adds or subtracts the intervals of `left` and `right` component by component
+fn add_intervals(
+    left: &ArrayRef,
+    op: &Operator,
+    right: &ArrayRef,
+    unit: &IntervalUnit,
+) -> Result<ArrayRef> {
+    let combine = |l: i32, r: i32| match op {
+        Operator::Plus => l.checked_add(r),
+        _ => l.checked_sub(r),
+    };
+    match unit {
+        IntervalUnit::YearMonth => {
+            let left = left
+                .as_any()
+                .downcast_ref::<IntervalYearMonthArray>()
+                .unwrap();
+            let right = right
+                .as_any()
+                .downcast_ref::<IntervalYearMonthArray>()
+                .unwrap();
+            let result = left
+                .iter()
+                .zip(right.iter())
+                .map(|(l, r)| match (l, r) {
+                    (Some(l), Some(r)) => {
+                        combine(l, r).map(Some).ok_or_else(|| overflow(op))
+                    }
+                    _ => Ok(None),
+                })
+                .collect::<Result<IntervalYearMonthArray>>()?;
+            Ok(Arc::new(result))
+        }
+        IntervalUnit::DayTime => {
+            let left = left
+                .as_any()
+                .downcast_ref::<IntervalDayTimeArray>()
+                .unwrap();
+            let right = right
+                .as_any()
+                .downcast_ref::<IntervalDayTimeArray>()
+                .unwrap();
+            let result = left
+                .iter()
+                .zip(right.iter())
+                .map(|(l, r)| match (l, r) {
+                    (Some(l), Some(r)) => {
+                        let (l_days, l_millis) = days_millis(l);
+                        let (r_days, r_millis) = days_millis(r);
+                        let days = combine(l_days, r_days).ok_or_else(|| overflow(op))?;
+                        let millis =
+                            combine(l_millis, r_millis).ok_or_else(|| overflow(op))?;
+                        Ok(Some(day_time(days, millis)))
+                    }
+                    _ => Ok(None),
+                })
+                .collect::<Result<IntervalDayTimeArray>>()?;
+            Ok(Arc::new(result))
+        }
+    }
+}
+
+/// Multiplies or divides the intervals of `intervals` by the numbers of
+/// `numbers`. The fraction of a day carries over into milliseconds, while a
+/// fraction of a month is rounded as it has no fixed number of days.
+fn scale_intervals(
+    intervals: &ArrayRef,
+    op: &Operator,
+    numbers: &ArrayRef,
+    unit: &IntervalUnit,
+) -> Result<ArrayRef> {
+    let numbers = cast(numbers, &DataType::Float64)?;
+    let numbers = numbers.as_any().downcast_ref::<Float64Array>().unwrap();
+    if *op == Operator::Divide && numbers.iter().any(|n| n == Some(0.0)) {
+        return Err(DataFusionError::Execution("Division by zero".to_string()));
+    }
+    let apply = |value: f64, number: f64| match op {
+        Operator::Divide => value / number,
+        _ => value * number,
+    };
+
+    match unit {
+        IntervalUnit::YearMonth => {
+            let intervals = intervals
+                .as_any()
+                .downcast_ref::<IntervalYearMonthArray>()
+                .unwrap();
+            let result = intervals
+                .iter()
+                .zip(numbers.iter())
+                .map(|(months, number)| match (months, number) {
+                    (Some(months), Some(number)) => to_i32(apply(months as f64, number))
+                        .map(Some)
+                        .ok_or_else(|| overflow(op)),
+                    _ => Ok(None),
+                })
+                .collect::<Result<IntervalYearMonthArray>>()?;
+            Ok(Arc::new(result))
+        }
+        IntervalUnit::DayTime => {
+            let intervals = intervals
+                .as_any()
+                .downcast_ref::<IntervalDayTimeArray>()
+                .unwrap();
+            let result = intervals
+                .iter()
+                .zip(numbers.iter())
+                .map(|(interval, number)| match (interval, number) {
+                    (Some(interval), Some(number)) => {
+                        let (days, millis) = days_millis(interval);
+                        let days = apply(days as f64, number);
+                        let whole_days = days.trunc();
+                        let millis = apply(millis as f64, number)
+                            + (days - whole_days) * MILLIS_PER_DAY;
+                        match (to_i32(whole_days), to_i32(millis)) {
+                            (Some(days), Some(millis)) => {
+                                Ok(Some(day_time(days, millis)))
+                            }
+                            _ => Err(overflow(op)),
+                        }
+                    }
+                    _ => Ok(None),
+                })
+                .collect::<Result<IntervalDayTimeArray>>()?;
+            Ok(Arc::new(result))
+        }
+    }
+}
+
+/// Evaluates the arithmetic operator `op` on intervals, producing an interval
+/// array of `result_type` as returned by [`interval_arithmetic_type`]
+pub(crate) fn interval_arithmetic(
+    left: &ArrayRef,
+    op: &Operator,
+    right: &ArrayRef,
+    result_type: &DataType,
+) -> Result<ArrayRef> {
+    let unit = match result_type {
+        DataType::Interval(unit) => unit,
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Interval arithmetic can not produce {:?}",
+                other
+            )))
+        }
+    };
+    match (left.data_type(), right.data_type()) {
+        (DataType::Interval(_), DataType::Interval(_)) => {
+            add_intervals(left, op, right, unit)
+        }
+        (DataType::Interval(_), _) => scale_intervals(left, op, right, unit),
+        _ => scale_intervals(right, op, left, unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+
+    fn evaluate(left: ArrayRef, op: Operator, right: ArrayRef) -> Result<ArrayRef> {
+        let result_type =
+            interval_arithmetic_type(left.data_type(), &op, right.data_type()).unwrap();
+        interval_arithmetic(&left, &op, &right, &result_type)
+    }
+
+    #[test]
+    fn result_types() {
+        let day_time = DataType::Interval(IntervalUnit::DayTime);
+        let year_month = DataType::Interval(IntervalUnit::YearMonth);
+        assert_eq!(
+            interval_arithmetic_type(&day_time, &Operator::Plus, &day_time),
+            Some(day_time.clone())
+        );
+        assert_eq!(
+            interval_arithmetic_type(&DataType::Int64, &Operator::Multiply, &year_month),
+            Some(year_month.clone())
+        );
+        assert_eq!(
+            interval_arithmetic_type(&day_time, &Operator::Plus, &year_month),
+            None
+        );
+        assert_eq!(
+            interval_arithmetic_type(&DataType::Int64, &Operator::Divide, &day_time),
+            None
+        );
+    }
+
+    #[test]
+    fn day_time_arithmetic() -> Result<()> {
+        let left: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(1, 3_600_000)),
+            Some(day_time(3, 0)),
+            None,
+        ]));
+        let right: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(2, 1_000)),
+            Some(day_time(0, 60_000)),
+            Some(day_time(1, 0)),
+        ]));
+
+        let result = evaluate(left.clone(), Operator::Plus, right.clone())?;
+        let expected: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(3, 3_601_000)),
+            Some(day_time(3, 60_000)),
+            None,
+        ]));
+        assert_eq!(result.data(), expected.data());
+
+        let result = evaluate(left.clone(), Operator::Minus, right)?;
+        let expected: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(-1, 3_599_000)),
+            Some(day_time(3, -60_000)),
+            None,
+        ]));
+        assert_eq!(result.data(), expected.data());
+
+        // 3 days / 2 = 1 day 12 hours
+        let numbers: ArrayRef = Arc::new(Int32Array::from(vec![2, 2, 2]));
+        let result = evaluate(left.clone(), Operator::Divide, numbers.clone())?;
+        let expected: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(0, 43_200_000 + 1_800_000)),
+            Some(day_time(1, 43_200_000)),
+            None,
+        ]));
+        assert_eq!(result.data(), expected.data());
+
+        let result = evaluate(numbers, Operator::Multiply, left.clone())?;
+        let expected: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![
+            Some(day_time(2, 7_200_000)),
+            Some(day_time(6, 0)),
+            None,
+        ]));
+        assert_eq!(result.data(), expected.data());
+
+        let zero: ArrayRef = Arc::new(Int32Array::from(vec![0, 1, 1]));
+        assert!(evaluate(left, Operator::Divide, zero).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn year_month_arithmetic() -> Result<()> {
+        let left: ArrayRef =
+            Arc::new(IntervalYearMonthArray::from(vec![Some(14), Some(i32::MAX)]));
+        let numbers: ArrayRef = Arc::new(Float64Array::from(vec![1.5, 1.0]));
+        let result = evaluate(left.clone(), Operator::Multiply, numbers)?;
+        let expected: ArrayRef =
+            Arc::new(IntervalYearMonthArray::from(vec![Some(21), Some(i32::MAX)]));
+        assert_eq!(result.data(), expected.data());
+
+        assert!(evaluate(left.clone(), Operator::Plus, left).is_err());
+        Ok(())
+    }
+}
@@ -25,7 +25,7 @@ use arrow::{
     record_batch::RecordBatch,
 };
 
-use crate::physical_plan::{ColumnarValue, PhysicalExpr};
+use crate::physical_plan::{selection::SelectionVector, ColumnarValue, PhysicalExpr};
 use crate::{error::Result, scalar::ScalarValue};
 
 /// IS NOT NULL expression
@@ -68,7 +68,20 @@ impl PhysicalExpr for IsNotNullExpr {
     }
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let arg = self.arg.evaluate(batch)?;
+        self.evaluate_arg(self.arg.evaluate(batch)?)
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        self.evaluate_arg(self.arg.evaluate_selection(batch, selection)?)
+    }
+}
+
+impl IsNotNullExpr {
+    fn evaluate_arg(&self, arg: ColumnarValue) -> Result<ColumnarValue> {
         match arg {
             ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
                 compute::is_not_null(array.as_ref())?,
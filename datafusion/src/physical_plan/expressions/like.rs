@@ -0,0 +1,194 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Evaluation of LIKE with patterns that only have wildcards at their start
+//! or end, which covers most patterns in practice, without translating them
+//! to a regular expression.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, GenericStringArray, StringOffsetSizeTrait};
+use arrow::datatypes::DataType;
+use memchr::memmem;
+
+use crate::error::Result;
+use crate::scalar::ScalarValue;
+
+/// A LIKE pattern that can be matched without a regular expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SimplePattern {
+    /// `'%'`, matching any string
+    Any,
+    /// `'abc'`
+    Exact(String),
+    /// `'abc%'`
+    Prefix(String),
+    /// `'%abc'`
+    Suffix(String),
+    /// `'%abc%'`
+    Contains(String),
+}
+
+impl SimplePattern {
+    /// Parses `pattern`, returning `None` if it has wildcards other than `%`
+    /// at its start and end, or escapes
+    pub(crate) fn parse(pattern: &str) -> Option<Self> {
+        let leading = pattern.starts_with('%');
+        let inner = pattern.trim_start_matches('%');
+        let trailing = inner.ends_with('%');
+        let inner = inner.trim_end_matches('%');
+        if inner.contains(&['%', '_', '\\'][..]) {
+            return None;
+        }
+        let inner = inner.to_owned();
+        Some(match (leading, trailing) {
+            _ if inner.is_empty() && (leading || trailing) => SimplePattern::Any,
+            (false, false) => SimplePattern::Exact(inner),
+            (false, true) => SimplePattern::Prefix(inner),
+            (true, false) => SimplePattern::Suffix(inner),
+            (true, true) => SimplePattern::Contains(inner),
+        })
+    }
+}
+
+/// Evaluates `array LIKE pattern`, or `array NOT LIKE pattern` if `negated`,
+/// for string arrays and simple patterns. Returns `None` if the kernels of
+/// arrow have to be used instead.
+pub(crate) fn like_scalar(
+    array: &ArrayRef,
+    pattern: &ScalarValue,
+    negated: bool,
+) -> Option<Result<ArrayRef>> {
+    let pattern = match pattern {
+        ScalarValue::Utf8(Some(pattern)) | ScalarValue::LargeUtf8(Some(pattern)) => {
+            SimplePattern::parse(pattern)?
+        }
+        _ => return None,
+    };
+    match array.data_type() {
+        DataType::Utf8 => Some(Ok(like_simple::<i32>(array, &pattern, negated))),
+        DataType::LargeUtf8 => Some(Ok(like_simple::<i64>(array, &pattern, negated))),
+        _ => None,
+    }
+}
+
+fn like_simple<O: StringOffsetSizeTrait>(
+    array: &ArrayRef,
+    pattern: &SimplePattern,
+    negated: bool,
+) -> ArrayRef {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericStringArray<O>>()
+        .unwrap();
+    let evaluate = |f: &dyn Fn(&str) -> bool| -> BooleanArray {
+        array
+            .iter()
+            .map(|value| value.map(|value| f(value) != negated))
+            .collect()
+    };
+    let result = match pattern {
+        SimplePattern::Any => evaluate(&|_: &str| true),
+        SimplePattern::Exact(s) => evaluate(&|value: &str| value == s.as_str()),
+        SimplePattern::Prefix(s) => {
+            evaluate(&|value: &str| value.starts_with(s.as_str()))
+        }
+        SimplePattern::Suffix(s) => evaluate(&|value: &str| value.ends_with(s.as_str())),
+        SimplePattern::Contains(s) => {
+            let finder = memmem::Finder::new(s.as_bytes());
+            evaluate(&|value: &str| finder.find(value.as_bytes()).is_some())
+        }
+    };
+    Arc::new(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{LargeStringArray, StringArray};
+
+    #[test]
+    fn parse_patterns() {
+        let cases = vec![
+            ("%", Some(SimplePattern::Any)),
+            ("%%", Some(SimplePattern::Any)),
+            ("", Some(SimplePattern::Exact("".to_string()))),
+            ("abc", Some(SimplePattern::Exact("abc".to_string()))),
+            ("abc%", Some(SimplePattern::Prefix("abc".to_string()))),
+            ("%abc", Some(SimplePattern::Suffix("abc".to_string()))),
+            ("%abc%", Some(SimplePattern::Contains("abc".to_string()))),
+            ("%%abc%%", Some(SimplePattern::Contains("abc".to_string()))),
+            ("a%c", None),
+            ("a_c%", None),
+            ("%a\\%", None),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(SimplePattern::parse(pattern), expected, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn like_simple_patterns() -> Result<()> {
+        let array: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("apple"),
+            None,
+            Some("pineapple"),
+            Some("app"),
+        ]));
+        let cases = vec![
+            (
+                "app%",
+                false,
+                vec![Some(true), None, Some(false), Some(true)],
+            ),
+            (
+                "%apple",
+                false,
+                vec![Some(true), None, Some(true), Some(false)],
+            ),
+            (
+                "%nea%",
+                false,
+                vec![Some(false), None, Some(true), Some(false)],
+            ),
+            (
+                "app",
+                false,
+                vec![Some(false), None, Some(false), Some(true)],
+            ),
+            ("%", false, vec![Some(true), None, Some(true), Some(true)]),
+            (
+                "%pp%",
+                true,
+                vec![Some(false), None, Some(false), Some(false)],
+            ),
+        ];
+        for (pattern, negated, expected) in cases {
+            let result =
+                like_scalar(&array, &ScalarValue::from(pattern), negated).unwrap()?;
+            let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+            assert_eq!(result.iter().collect::<Vec<_>>(), expected, "{}", pattern);
+        }
+
+        assert!(like_scalar(&array, &ScalarValue::from("a_p%"), false).is_none());
+
+        let array: ArrayRef = Arc::new(LargeStringArray::from(vec!["apple", "pear"]));
+        let result = like_scalar(&array, &ScalarValue::from("%ar"), false).unwrap()?;
+        assert_eq!(result.len(), 2);
+        assert!(result.is_valid(1));
+        Ok(())
+    }
+}
@@ -25,7 +25,7 @@ use arrow::{
     record_batch::RecordBatch,
 };
 
-use crate::physical_plan::{ColumnarValue, PhysicalExpr};
+use crate::physical_plan::{selection::SelectionVector, ColumnarValue, PhysicalExpr};
 use crate::{error::Result, scalar::ScalarValue};
 
 /// Represents a literal value
@@ -69,6 +69,14 @@ impl PhysicalExpr for Literal {
     fn evaluate(&self, _batch: &RecordBatch) -> Result<ColumnarValue> {
         Ok(ColumnarValue::Scalar(self.value.clone()))
     }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        _selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        self.evaluate(batch)
+    }
 }
 
 /// Create a literal expression
@@ -28,8 +28,8 @@ use arrow::compute;
 use arrow::datatypes::{DataType, TimeUnit};
 use arrow::{
     array::{
-        ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
-        Int8Array, LargeStringArray, StringArray, TimestampMicrosecondArray,
+        ArrayBuilder, ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array,
+        Int64Array, Int8Array, LargeStringArray, StringArray, TimestampMicrosecondArray,
         TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray,
         UInt16Array, UInt32Array, UInt64Array, UInt8Array,
     },
@@ -166,7 +166,7 @@ macro_rules! min_max_batch {
 }
 
 /// dynamically-typed min(array) -> ScalarValue
-fn min_batch(values: &ArrayRef) -> Result<ScalarValue> {
+pub(crate) fn min_batch(values: &ArrayRef) -> Result<ScalarValue> {
     Ok(match values.data_type() {
         DataType::Utf8 => {
             typed_min_max_batch_string!(values, StringArray, Utf8, min_string)
@@ -179,7 +179,7 @@ fn min_batch(values: &ArrayRef) -> Result<ScalarValue> {
 }
 
 /// dynamically-typed max(array) -> ScalarValue
-fn max_batch(values: &ArrayRef) -> Result<ScalarValue> {
+pub(crate) fn max_batch(values: &ArrayRef) -> Result<ScalarValue> {
     Ok(match values.data_type() {
         DataType::Utf8 => {
             typed_min_max_batch_string!(values, StringArray, Utf8, max_string)
@@ -287,12 +287,12 @@ macro_rules! min_max {
 }
 
 /// the minimum of two scalar values
-fn min(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
+pub(crate) fn min(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
     min_max!(lhs, rhs, min)
 }
 
 /// the maximum of two scalar values
-fn max(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
+pub(crate) fn max(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
     min_max!(lhs, rhs, max)
 }
 
@@ -336,6 +336,10 @@ impl Accumulator for MaxAccumulator {
         Ok(vec![self.max.clone()])
     }
 
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        self.max.append_to_builder(builders[0].as_mut())
+    }
+
     fn evaluate(&self) -> Result<ScalarValue> {
         Ok(self.max.clone())
     }
@@ -416,6 +420,10 @@ impl Accumulator for MinAccumulator {
         Ok(vec![self.min.clone()])
     }
 
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        self.min.append_to_builder(builders[0].as_mut())
+    }
+
     fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
         let values = &values[0];
         let delta = &min_batch(values)?;
@@ -28,39 +28,54 @@ use arrow::record_batch::RecordBatch;
 mod average;
 #[macro_use]
 mod binary;
+mod bytes;
 mod case;
 mod cast;
+mod coalesce;
 mod coercion;
 mod column;
 mod count;
+mod decimal;
 mod in_list;
+mod interval;
 mod is_not_null;
 mod is_null;
+mod like;
 mod literal;
 mod min_max;
+mod monotonic_id;
 mod negative;
 mod not;
 mod nth_value;
 mod nullif;
+mod nvl;
 mod row_number;
 mod sum;
 mod try_cast;
 
 pub use average::{avg_return_type, Avg, AvgAccumulator};
 pub use binary::{binary, binary_operator_data_type, BinaryExpr};
+pub(crate) use bytes::is_binary;
 pub use case::{case, CaseExpr};
-pub use cast::{cast, cast_with_options, CastExpr};
+pub use cast::{cast, cast_column, cast_with_options, CastExpr};
+pub use coalesce::{coalesce, CoalesceExpr};
+pub(crate) use coercion::{is_numeric, order_coercion};
 pub use column::{col, Column};
 pub use count::Count;
+pub use decimal::MAX_DECIMAL_PRECISION;
 pub use in_list::{in_list, InListExpr};
 pub use is_not_null::{is_not_null, IsNotNullExpr};
 pub use is_null::{is_null, IsNullExpr};
 pub use literal::{lit, Literal};
+pub(crate) use min_max::{max, max_batch, min, min_batch};
 pub use min_max::{Max, Min};
+pub(crate) use monotonic_id::with_partition;
+pub use monotonic_id::MonotonicIdExpr;
 pub use negative::{negative, NegativeExpr};
 pub use not::{not, NotExpr};
 pub use nth_value::{FirstValue, LastValue, NthValue};
 pub use nullif::{nullif_func, SUPPORTED_NULLIF_TYPES};
+pub use nvl::{nvl2_func, nvl_func, SUPPORTED_NVL_TYPES};
 pub use row_number::RowNumber;
 pub use sum::{sum_return_type, Sum};
 pub use try_cast::{try_cast, TryCastExpr};
@@ -93,6 +108,9 @@ impl std::fmt::Display for PhysicalSortExpr {
 
 impl PhysicalSortExpr {
     /// evaluate the sort expression into SortColumn that can be passed into arrow sort kernel
+    ///
+    /// Binary values, which arrow can not sort, are replaced by their ranks
+    /// within `batch`, so the columns of different batches can't be compared.
     pub fn evaluate_to_sort_column(&self, batch: &RecordBatch) -> Result<SortColumn> {
         let value_to_sort = self.expr.evaluate(batch)?;
         let array_to_sort = match value_to_sort {
@@ -104,6 +122,11 @@ impl PhysicalSortExpr {
                 )));
             }
         };
+        let array_to_sort = if bytes::is_binary(array_to_sort.data_type()) {
+            bytes::bytes_sort_ranks(&array_to_sort)?
+        } else {
+            array_to_sort
+        };
         Ok(SortColumn {
             values: array_to_sort,
             options: Some(self.options),
@@ -0,0 +1,167 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Monotonically increasing id expression
+
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::ColumnarValue;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::PhysicalExpr;
+use arrow::array::Int64Array;
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+
+/// The number of low bits of an id that hold the number of the row within its
+/// partition, the partition index is stored in the bits above
+const ROW_BITS: u32 = 33;
+
+/// The number of partitions whose index fits into the bits above `ROW_BITS` of
+/// a positive `i64`
+const MAX_PARTITIONS: usize = 1 << (63 - ROW_BITS);
+
+thread_local! {
+    /// The partition of the batch that is evaluated on this thread
+    static PARTITION: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// Runs `f`, which evaluates expressions on a batch of partition `partition`.
+/// Expressions whose values depend on the partition, such as
+/// [`MonotonicIdExpr`], can only be evaluated within `f`.
+pub(crate) fn with_partition<T>(partition: usize, f: impl FnOnce() -> T) -> T {
+    let previous = PARTITION.with(|p| p.replace(Some(partition)));
+    let result = f();
+    PARTITION.with(|p| p.set(previous));
+    result
+}
+
+/// Assigns unique ids to rows, that increase with the rows of each
+/// partition. The index of the partition is stored in the high bits of the
+/// ids and the number of the row within the partition in the low 33 bits, so
+/// the ids are neither consecutive nor increasing across partitions.
+///
+/// The partition is provided by the projection evaluating the expression, it
+/// can not be evaluated elsewhere.
+#[derive(Debug, Default)]
+pub struct MonotonicIdExpr {
+    /// The number of rows that ids were assigned to, per partition
+    next_rows: Mutex<HashMap<usize, u64>>,
+}
+
+impl MonotonicIdExpr {
+    /// Create new monotonic id expression
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Display for MonotonicIdExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "monotonic_id()")
+    }
+}
+
+impl PhysicalExpr for MonotonicIdExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn nullable(&self, _input_schema: &Schema) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let partition = PARTITION.with(Cell::get).ok_or_else(|| {
+            DataFusionError::Execution(
+                "monotonic_id() can only be evaluated in a projection".to_owned(),
+            )
+        })?;
+        if partition >= MAX_PARTITIONS {
+            return Err(DataFusionError::Execution(format!(
+                "monotonic_id() supports at most {} partitions",
+                MAX_PARTITIONS
+            )));
+        }
+
+        let num_rows = batch.num_rows() as u64;
+        let first = {
+            let mut next_rows = self.next_rows.lock().unwrap();
+            let next = next_rows.entry(partition).or_insert(0);
+            let first = *next;
+            *next += num_rows;
+            first
+        };
+        if first + num_rows > 1 << ROW_BITS {
+            return Err(DataFusionError::Execution(format!(
+                "monotonic_id() supports at most {} rows per partition",
+                1_u64 << ROW_BITS
+            )));
+        }
+
+        let high = (partition as u64) << ROW_BITS;
+        let ids = (first..first + num_rows)
+            .map(|row| (high | row) as i64)
+            .collect::<Vec<_>>();
+        Ok(ColumnarValue::Array(Arc::new(Int64Array::from(ids))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::Field;
+
+    fn ids(expr: &MonotonicIdExpr, batch: &RecordBatch) -> Result<Vec<i64>> {
+        let array = expr.evaluate(batch)?.into_array(batch.num_rows());
+        let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+        Ok(array.iter().map(|id| id.unwrap()).collect())
+    }
+
+    #[test]
+    fn monotonic_id() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int32Array::from(vec![7, 8, 9]))],
+        )?;
+        let expr = MonotonicIdExpr::new();
+
+        assert_eq!(with_partition(0, || ids(&expr, &batch))?, vec![0, 1, 2]);
+        let high = 1_i64 << ROW_BITS;
+        assert_eq!(
+            with_partition(1, || ids(&expr, &batch))?,
+            vec![high, high + 1, high + 2]
+        );
+        // the rows of each partition are counted separately
+        assert_eq!(with_partition(0, || ids(&expr, &batch))?, vec![3, 4, 5]);
+
+        // the partition is unknown outside of `with_partition`
+        assert!(expr.evaluate(&batch).is_err());
+        Ok(())
+    }
+}
@@ -23,7 +23,7 @@ use std::sync::Arc;
 
 use super::ColumnarValue;
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::PhysicalExpr;
+use crate::physical_plan::{selection::SelectionVector, PhysicalExpr};
 use crate::scalar::ScalarValue;
 use arrow::array::BooleanArray;
 use arrow::datatypes::{DataType, Schema};
@@ -69,7 +69,20 @@ impl PhysicalExpr for NotExpr {
     }
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let arg = self.arg.evaluate(batch)?;
+        self.evaluate_arg(self.arg.evaluate(batch)?)
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        self.evaluate_arg(self.arg.evaluate_selection(batch, selection)?)
+    }
+}
+
+impl NotExpr {
+    fn evaluate_arg(&self, arg: ColumnarValue) -> Result<ColumnarValue> {
         match arg {
             ColumnarValue::Array(array) => {
                 let array =
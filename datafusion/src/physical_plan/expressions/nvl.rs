@@ -0,0 +1,123 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use arrow::array::{make_array, ArrayRef, MutableArrayData};
+use arrow::datatypes::DataType;
+
+use crate::error::{DataFusionError, Result};
+
+/// Builds an array from the rows of `if_true` for which `mask` is true and the
+/// rows of `if_false` for the others
+fn select(
+    mask: impl Fn(usize) -> bool,
+    if_true: &ArrayRef,
+    if_false: &ArrayRef,
+) -> Result<ArrayRef> {
+    if if_true.data_type() != if_false.data_type() {
+        return Err(DataFusionError::Internal(format!(
+            "Cannot select between arrays of the types {:?} and {:?}",
+            if_true.data_type(),
+            if_false.data_type()
+        )));
+    }
+    let len = if_true.len();
+    let mut mutable =
+        MutableArrayData::new(vec![if_true.data(), if_false.data()], true, len);
+    // copy the runs of rows that are taken from the same array at once
+    let mut start = 0;
+    for end in 1..=len {
+        if end == len || mask(end) != mask(start) {
+            mutable.extend(if mask(start) { 0 } else { 1 }, start, end);
+            start = end;
+        }
+    }
+    Ok(make_array(mutable.freeze()))
+}
+
+/// Implements NVL(expr1, expr2), also called IFNULL
+/// Args: 0 - the value that is returned if it is not null
+///       1 - the value that is returned otherwise
+pub fn nvl_func(args: &[ArrayRef]) -> Result<ArrayRef> {
+    select(|i| args[0].is_valid(i), &args[0], &args[1])
+}
+
+/// Implements NVL2(expr1, expr2, expr3)
+/// Args: 0 - the value that is checked for null, of any type
+///       1 - the value that is returned if the first argument is not null
+///       2 - the value that is returned otherwise
+pub fn nvl2_func(args: &[ArrayRef]) -> Result<ArrayRef> {
+    select(|i| args[0].is_valid(i), &args[1], &args[2])
+}
+
+/// Currently supported types by the nvl and nvl2 functions.
+/// The order of these types correspond to the order on which coercion applies
+/// This should thus be from least informative to most informative
+pub static SUPPORTED_NVL_TYPES: &[DataType] = &[
+    DataType::Boolean,
+    DataType::UInt8,
+    DataType::UInt16,
+    DataType::UInt32,
+    DataType::UInt64,
+    DataType::Int8,
+    DataType::Int16,
+    DataType::Int32,
+    DataType::Int64,
+    DataType::Float32,
+    DataType::Float64,
+    DataType::Utf8,
+    DataType::LargeUtf8,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use std::sync::Arc;
+
+    #[test]
+    fn nvl_int32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, None, Some(4)]));
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![Some(5), Some(6), None, None]));
+
+        let result = nvl_func(&[a, b])?;
+        let expected: ArrayRef =
+            Arc::new(Int32Array::from(vec![Some(1), Some(6), None, Some(4)]));
+        assert_eq!(&result, &expected);
+        Ok(())
+    }
+
+    #[test]
+    fn nvl2_utf8() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+        let b: ArrayRef = Arc::new(StringArray::from(vec![Some("a"), Some("b"), None]));
+        let c: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("x"), Some("y"), Some("z")]));
+
+        let result = nvl2_func(&[a, b, c])?;
+        let expected: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("a"), Some("y"), None]));
+        assert_eq!(&result, &expected);
+        Ok(())
+    }
+
+    #[test]
+    fn nvl_different_types() {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(1)]));
+        let b: ArrayRef = Arc::new(StringArray::from(vec![Some("a")]));
+        assert!(nvl_func(&[a, b]).is_err());
+    }
+}
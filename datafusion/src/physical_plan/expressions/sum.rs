@@ -28,8 +28,8 @@ use arrow::compute;
 use arrow::datatypes::DataType;
 use arrow::{
     array::{
-        ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
-        Int8Array, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+        ArrayBuilder, ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array,
+        Int64Array, Int8Array, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
     },
     datatypes::Field,
 };
@@ -266,6 +266,10 @@ impl Accumulator for SumAccumulator {
         Ok(vec![self.sum.clone()])
     }
 
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        self.sum.append_to_builder(builders[0].as_mut())
+    }
+
     fn evaluate(&self) -> Result<ScalarValue> {
         Ok(self.sum.clone())
     }
@@ -19,17 +19,22 @@ use std::any::Any;
 use std::fmt;
 use std::sync::Arc;
 
+use super::cast::cast_column;
 use super::ColumnarValue;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::PhysicalExpr;
 use crate::scalar::ScalarValue;
 use arrow::compute;
-use arrow::compute::kernels;
+use arrow::compute::CastOptions;
 use arrow::datatypes::{DataType, Schema};
 use arrow::record_batch::RecordBatch;
 use compute::can_cast_types;
 
+/// Options of TRY_CAST, which never errors on values that can not be cast
+pub(crate) const TRY_CAST_OPTIONS: CastOptions = CastOptions { safe: true };
+
 /// TRY_CAST expression casts an expression to a specific data type and retuns NULL on invalid cast
+/// and for values that do not fit the data type
 #[derive(Debug)]
 pub struct TryCastExpr {
     /// The expression to cast
@@ -78,13 +83,26 @@ impl PhysicalExpr for TryCastExpr {
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
         let value = self.expr.evaluate(batch)?;
         match value {
-            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(kernels::cast::cast(
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(cast_column(
                 &array,
                 &self.cast_type,
+                &TRY_CAST_OPTIONS,
             )?)),
+            // there are no dictionary scalars, so the scalar is cast as an
+            // array to keep the type of the cast
+            ColumnarValue::Scalar(scalar)
+                if matches!(self.cast_type, DataType::Dictionary(_, _)) =>
+            {
+                Ok(ColumnarValue::Array(cast_column(
+                    &scalar.to_array_of_size(batch.num_rows()),
+                    &self.cast_type,
+                    &TRY_CAST_OPTIONS,
+                )?))
+            }
             ColumnarValue::Scalar(scalar) => {
                 let scalar_array = scalar.to_array();
-                let cast_array = kernels::cast::cast(&scalar_array, &self.cast_type)?;
+                let cast_array =
+                    cast_column(&scalar_array, &self.cast_type, &TRY_CAST_OPTIONS)?;
                 let cast_scalar = ScalarValue::try_from_array(&cast_array, 0)?;
                 Ok(ColumnarValue::Scalar(cast_scalar))
             }
@@ -121,7 +139,10 @@ mod tests {
     use crate::physical_plan::expressions::col;
     use arrow::array::{StringArray, Time64NanosecondArray};
     use arrow::{
-        array::{Array, Int32Array, Int64Array, TimestampNanosecondArray, UInt32Array},
+        array::{
+            Array, Int32Array, Int64Array, Int8Array, TimestampNanosecondArray,
+            UInt32Array,
+        },
         datatypes::*,
     };
 
@@ -244,4 +265,17 @@ mod tests {
         let result = try_cast(col("a"), &schema, DataType::LargeBinary);
         result.expect_err("expected Invalid CAST");
     }
+
+    #[test]
+    fn test_try_cast_overflow() -> Result<()> {
+        generic_test_cast!(
+            Int64Array,
+            DataType::Int64,
+            vec![1, 128, -129, 5],
+            Int8Array,
+            DataType::Int8,
+            vec![Some(1_i8), None, None, Some(5)]
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,1069 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Exchange of record batch streams with other runtimes through the
+//! [Arrow C stream interface](https://arrow.apache.org/docs/format/CStreamInterface.html).
+//!
+//! [`FFI_ArrowArrayStream::new`] exports the results of a query, and
+//! [`ArrowArrayStreamReader`] imports a stream produced by a non-Rust host
+//! (e.g. `pyarrow.RecordBatchReader._export_to_c`), which
+//! [`ArrowArrayStreamExec`] scans. Record batches are exchanged as struct
+//! arrays whose children are the columns, whose buffers are shared rather
+//! than copied.
+
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+use arrow::array::{make_array, make_array_from_raw, ArrayRef, MutableArrayData};
+use arrow::datatypes::{DataType, Field, IntervalUnit, Schema, SchemaRef, TimeUnit};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::ffi::ArrowArray;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+use futures::stream::{Stream, StreamExt};
+
+/// Flag of a nullable field in `ArrowSchema.flags`
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+/// errno codes returned by the stream callbacks
+const EINVAL: c_int = 22;
+const EIO: c_int = 5;
+
+/// The `ArrowSchema` struct of the C data interface
+#[repr(C)]
+#[derive(Debug)]
+struct RawSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut RawSchema,
+    dictionary: *mut RawSchema,
+    release: Option<unsafe extern "C" fn(*mut RawSchema)>,
+    private_data: *mut c_void,
+}
+
+/// The `ArrowArray` struct of the C data interface
+#[repr(C)]
+#[derive(Debug)]
+struct RawArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut RawArray,
+    dictionary: *mut RawArray,
+    release: Option<unsafe extern "C" fn(*mut RawArray)>,
+    private_data: *mut c_void,
+}
+
+/// The `ArrowArrayStream` struct of the C stream interface
+#[repr(C)]
+#[derive(Debug)]
+#[allow(non_camel_case_types)]
+pub struct FFI_ArrowArrayStream {
+    get_schema:
+        Option<unsafe extern "C" fn(*mut FFI_ArrowArrayStream, *mut RawSchema) -> c_int>,
+    get_next:
+        Option<unsafe extern "C" fn(*mut FFI_ArrowArrayStream, *mut RawArray) -> c_int>,
+    get_last_error:
+        Option<unsafe extern "C" fn(*mut FFI_ArrowArrayStream) -> *const c_char>,
+    release: Option<unsafe extern "C" fn(*mut FFI_ArrowArrayStream)>,
+    private_data: *mut c_void,
+}
+
+impl RawSchema {
+    fn empty() -> Self {
+        Self {
+            format: ptr::null(),
+            name: ptr::null(),
+            metadata: ptr::null(),
+            flags: 0,
+            n_children: 0,
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+
+    /// The children of a schema, which must not have been released
+    unsafe fn child(&self, i: usize) -> &RawSchema {
+        &**self.children.add(i)
+    }
+}
+
+impl Drop for RawSchema {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) };
+        }
+    }
+}
+
+impl RawArray {
+    fn empty() -> Self {
+        Self {
+            length: 0,
+            null_count: 0,
+            offset: 0,
+            n_buffers: 0,
+            n_children: 0,
+            buffers: ptr::null_mut(),
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+}
+
+impl Drop for RawArray {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) };
+        }
+    }
+}
+
+/// Private data of the schemas exported by [`export_schema`]
+struct SchemaPrivate {
+    format: CString,
+    name: CString,
+    children: Vec<*mut RawSchema>,
+    dictionary: *mut RawSchema,
+}
+
+impl Drop for SchemaPrivate {
+    fn drop(&mut self) {
+        unsafe {
+            for child in self.children.iter() {
+                drop(Box::from_raw(*child));
+            }
+            if !self.dictionary.is_null() {
+                drop(Box::from_raw(self.dictionary));
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn release_schema(schema: *mut RawSchema) {
+    if schema.is_null() {
+        return;
+    }
+    let schema = &mut *schema;
+    drop(Box::from_raw(schema.private_data as *mut SchemaPrivate));
+    schema.release = None;
+}
+
+/// Exports the type of a field named `name`. arrow only exports the types of
+/// arrays, without field names, so schemas are exported here.
+fn export_schema(name: &str, data_type: &DataType, nullable: bool) -> Result<RawSchema> {
+    // the format of a dictionary encoded field describes its keys
+    let (data_type, value_type) = match data_type {
+        DataType::Dictionary(key_type, value_type) => {
+            (key_type.as_ref(), Some(value_type.as_ref()))
+        }
+        _ => (data_type, None),
+    };
+    let children = match data_type {
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _) => vec![field.as_ref().clone()],
+        DataType::Struct(fields) => fields.clone(),
+        _ => vec![],
+    };
+    let to_cstring = |s: &str| {
+        CString::new(s).map_err(|e| {
+            DataFusionError::Execution(format!("Invalid ArrowSchema string: {}", e))
+        })
+    };
+    let mut private = Box::new(SchemaPrivate {
+        format: to_cstring(&format_of(data_type)?)?,
+        name: to_cstring(name)?,
+        children: Vec::with_capacity(children.len()),
+        dictionary: ptr::null_mut(),
+    });
+    if let Some(value_type) = value_type {
+        let dictionary = export_schema("", value_type, true)?;
+        private.dictionary = Box::into_raw(Box::new(dictionary));
+    }
+    for child in &children {
+        let child = export_schema(child.name(), child.data_type(), child.is_nullable())?;
+        private.children.push(Box::into_raw(Box::new(child)));
+    }
+
+    Ok(RawSchema {
+        format: private.format.as_ptr(),
+        name: private.name.as_ptr(),
+        metadata: ptr::null(),
+        flags: if nullable { ARROW_FLAG_NULLABLE } else { 0 },
+        n_children: private.children.len() as i64,
+        children: private.children.as_mut_ptr(),
+        dictionary: private.dictionary,
+        release: Some(release_schema),
+        private_data: Box::into_raw(private) as *mut c_void,
+    })
+}
+
+/// The format string of `data_type`, see
+/// <https://arrow.apache.org/docs/format/CDataInterface.html#data-type-description-format-strings>
+fn format_of(data_type: &DataType) -> Result<String> {
+    let time_unit = |unit: &TimeUnit| match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Millisecond => "m",
+        TimeUnit::Microsecond => "u",
+        TimeUnit::Nanosecond => "n",
+    };
+    let format = match data_type {
+        DataType::Null => "n",
+        DataType::Boolean => "b",
+        DataType::Int8 => "c",
+        DataType::UInt8 => "C",
+        DataType::Int16 => "s",
+        DataType::UInt16 => "S",
+        DataType::Int32 => "i",
+        DataType::UInt32 => "I",
+        DataType::Int64 => "l",
+        DataType::UInt64 => "L",
+        DataType::Float16 => "e",
+        DataType::Float32 => "f",
+        DataType::Float64 => "g",
+        DataType::Binary => "z",
+        DataType::LargeBinary => "Z",
+        DataType::Utf8 => "u",
+        DataType::LargeUtf8 => "U",
+        DataType::Date32 => "tdD",
+        DataType::Date64 => "tdm",
+        DataType::Time32(TimeUnit::Second) => "tts",
+        DataType::Time32(TimeUnit::Millisecond) => "ttm",
+        DataType::Time64(TimeUnit::Microsecond) => "ttu",
+        DataType::Time64(TimeUnit::Nanosecond) => "ttn",
+        DataType::Interval(IntervalUnit::YearMonth) => "tiM",
+        DataType::Interval(IntervalUnit::DayTime) => "tiD",
+        DataType::List(_) => "+l",
+        DataType::LargeList(_) => "+L",
+        DataType::Struct(_) => "+s",
+        DataType::Timestamp(unit, tz) => {
+            return Ok(format!(
+                "ts{}:{}",
+                time_unit(unit),
+                tz.as_deref().unwrap_or_default()
+            ))
+        }
+        DataType::FixedSizeBinary(size) => return Ok(format!("w:{}", size)),
+        DataType::FixedSizeList(_, size) => return Ok(format!("+w:{}", size)),
+        DataType::Decimal(precision, scale) => {
+            return Ok(format!("d:{},{}", precision, scale))
+        }
+        other => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Exporting {:?} through the C data interface is not supported",
+                other
+            )))
+        }
+    };
+    Ok(format.to_owned())
+}
+
+/// Exports `array` through arrow's C data interface, moving the resulting
+/// array struct out of the allocation arrow made for it
+fn export_array(array: &ArrayRef) -> Result<RawArray> {
+    // arrow sizes the buffers of imported arrays by their length only, so
+    // sliced arrays are copied to start at offset zero
+    let array = if array.offset() != 0 {
+        let mut copy = MutableArrayData::new(vec![array.data()], false, array.len());
+        copy.extend(0, 0, array.len());
+        make_array(copy.freeze())
+    } else {
+        array.clone()
+    };
+    let (array_ptr, schema_ptr) = array.to_raw()?;
+    unsafe {
+        let raw = ptr::read(array_ptr as *const RawArray);
+        // the moved struct is owned by the caller now, the allocation is freed
+        // without releasing it
+        (*(array_ptr as *mut RawArray)).release = None;
+        drop(ArrowArray::try_from_raw(array_ptr, schema_ptr)?);
+        Ok(raw)
+    }
+}
+
+/// Imports the array described by `array` and `schema` through arrow's C data
+/// interface, moving the array struct out of its current location
+unsafe fn import_array(array: &mut RawArray, schema: RawSchema) -> Result<ArrayRef> {
+    let (array_ptr, schema_ptr) = ArrowArray::into_raw(ArrowArray::empty());
+    ptr::write(array_ptr as *mut RawArray, ptr::read(array));
+    array.release = None;
+    ptr::write(schema_ptr as *mut RawSchema, schema);
+    Ok(make_array_from_raw(array_ptr, schema_ptr)?)
+}
+
+/// Private data of an exported struct array
+struct StructArrayPrivate {
+    buffers: Vec<*const c_void>,
+    children: Vec<*mut RawArray>,
+}
+
+impl Drop for StructArrayPrivate {
+    fn drop(&mut self) {
+        for child in self.children.iter() {
+            drop(unsafe { Box::from_raw(*child) });
+        }
+    }
+}
+
+unsafe extern "C" fn release_struct_array(array: *mut RawArray) {
+    if array.is_null() {
+        return;
+    }
+    let array = &mut *array;
+    drop(Box::from_raw(array.private_data as *mut StructArrayPrivate));
+    array.release = None;
+}
+
+/// Exports `batch` as a struct array with a child per column
+fn export_batch(batch: &RecordBatch) -> Result<RawArray> {
+    let mut private = Box::new(StructArrayPrivate {
+        // a struct array only has a validity buffer, which is omitted
+        buffers: vec![ptr::null()],
+        children: Vec::with_capacity(batch.num_columns()),
+    });
+    for column in batch.columns() {
+        let child = export_array(column)?;
+        private.children.push(Box::into_raw(Box::new(child)));
+    }
+
+    Ok(RawArray {
+        length: batch.num_rows() as i64,
+        null_count: 0,
+        offset: 0,
+        n_buffers: 1,
+        n_children: private.children.len() as i64,
+        buffers: private.buffers.as_mut_ptr(),
+        children: private.children.as_mut_ptr(),
+        dictionary: ptr::null_mut(),
+        release: Some(release_struct_array),
+        private_data: Box::into_raw(private) as *mut c_void,
+    })
+}
+
+/// Private data of an exported stream
+struct ExportedStream {
+    stream: SendableRecordBatchStream,
+    last_error: Option<CString>,
+}
+
+impl ExportedStream {
+    fn set_error(&mut self, e: impl ToString, code: c_int) -> c_int {
+        // the message may not contain interior nul bytes
+        let message = e.to_string().replace('\0', " ");
+        self.last_error = Some(CString::new(message).unwrap());
+        code
+    }
+}
+
+unsafe fn exported_stream<'a>(
+    stream: *mut FFI_ArrowArrayStream,
+) -> &'a mut ExportedStream {
+    &mut *((*stream).private_data as *mut ExportedStream)
+}
+
+unsafe extern "C" fn get_schema(
+    stream: *mut FFI_ArrowArrayStream,
+    out: *mut RawSchema,
+) -> c_int {
+    let exported = exported_stream(stream);
+    let schema = exported.stream.schema();
+    match export_schema("", &DataType::Struct(schema.fields().clone()), false) {
+        Ok(schema) => {
+            ptr::write(out, schema);
+            0
+        }
+        Err(e) => exported.set_error(e, EINVAL),
+    }
+}
+
+unsafe extern "C" fn get_next(
+    stream: *mut FFI_ArrowArrayStream,
+    out: *mut RawArray,
+) -> c_int {
+    let exported = exported_stream(stream);
+    // the C interface is synchronous, the host's thread waits for the next
+    // batch to be computed
+    match futures::executor::block_on(exported.stream.next()) {
+        // the end of the stream is signalled with a released array
+        None => {
+            ptr::write(out, RawArray::empty());
+            0
+        }
+        Some(batch) => match batch
+            .map_err(DataFusionError::from)
+            .and_then(|b| export_batch(&b))
+        {
+            Ok(array) => {
+                ptr::write(out, array);
+                0
+            }
+            Err(e) => exported.set_error(e, EIO),
+        },
+    }
+}
+
+unsafe extern "C" fn get_last_error(stream: *mut FFI_ArrowArrayStream) -> *const c_char {
+    match &exported_stream(stream).last_error {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+unsafe extern "C" fn release_stream(stream: *mut FFI_ArrowArrayStream) {
+    if stream.is_null() {
+        return;
+    }
+    let stream = &mut *stream;
+    drop(Box::from_raw(stream.private_data as *mut ExportedStream));
+    stream.release = None;
+}
+
+impl FFI_ArrowArrayStream {
+    /// Exports `stream`, e.g. the results of
+    /// [`DataFrame::execute_stream`](crate::dataframe::DataFrame::execute_stream),
+    /// to be consumed by another runtime. The consumer computes batches by
+    /// calling `get_next`, which blocks until the next batch is produced.
+    pub fn new(stream: SendableRecordBatchStream) -> Self {
+        let private = Box::new(ExportedStream {
+            stream,
+            last_error: None,
+        });
+        Self {
+            get_schema: Some(get_schema),
+            get_next: Some(get_next),
+            get_last_error: Some(get_last_error),
+            release: Some(release_stream),
+            private_data: Box::into_raw(private) as *mut c_void,
+        }
+    }
+
+    /// Creates a released stream, for a host to export a stream into
+    pub fn empty() -> Self {
+        Self {
+            get_schema: None,
+            get_next: None,
+            get_last_error: None,
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+
+    /// Takes ownership of the stream `stream` points to, marking the original
+    /// as released
+    ///
+    /// # Safety
+    /// `stream` must point to a valid `ArrowArrayStream`
+    pub unsafe fn from_raw(stream: *mut FFI_ArrowArrayStream) -> Self {
+        let result = ptr::read(stream);
+        (*stream).release = None;
+        result
+    }
+
+    /// Whether the stream was released
+    pub fn is_released(&self) -> bool {
+        self.release.is_none()
+    }
+}
+
+impl Drop for FFI_ArrowArrayStream {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) };
+        }
+    }
+}
+
+/// Reads the record batches of an [`FFI_ArrowArrayStream`] produced by another
+/// runtime
+pub struct ArrowArrayStreamReader {
+    stream: FFI_ArrowArrayStream,
+    schema: SchemaRef,
+}
+
+// the C stream interface allows a stream to be used from any thread, as long
+// as its callbacks are not called concurrently, which `&mut self` guarantees
+unsafe impl Send for ArrowArrayStreamReader {}
+
+impl std::fmt::Debug for ArrowArrayStreamReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArrowArrayStreamReader")
+            .field("schema", &self.schema)
+            .finish()
+    }
+}
+
+impl ArrowArrayStreamReader {
+    /// Creates a reader of `stream`, whose batches must be struct arrays
+    pub fn try_new(mut stream: FFI_ArrowArrayStream) -> Result<Self> {
+        if stream.is_released() {
+            return Err(DataFusionError::Execution(
+                "Cannot read a released ArrowArrayStream".to_string(),
+            ));
+        }
+        let raw = Self::raw_schema(&mut stream)?;
+        let schema = unsafe { struct_schema(&raw) }?;
+        Ok(Self {
+            stream,
+            schema: Arc::new(schema),
+        })
+    }
+
+    /// The schema of the batches of the stream
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn raw_schema(stream: &mut FFI_ArrowArrayStream) -> Result<RawSchema> {
+        let mut schema = RawSchema::empty();
+        let get_schema = stream.get_schema.ok_or_else(missing_callback)?;
+        let code = unsafe { get_schema(stream, &mut schema) };
+        if code != 0 {
+            return Err(Self::last_error(stream, code));
+        }
+        Ok(schema)
+    }
+
+    fn last_error(stream: &mut FFI_ArrowArrayStream, code: c_int) -> DataFusionError {
+        let message = stream
+            .get_last_error
+            .map(|get_last_error| unsafe { get_last_error(stream) })
+            .filter(|message| !message.is_null())
+            .map(|message| unsafe { CStr::from_ptr(message) }.to_string_lossy())
+            .unwrap_or_else(|| "unknown error".into());
+        DataFusionError::Execution(format!(
+            "ArrowArrayStream failed with error code {}: {}",
+            code, message
+        ))
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut array = RawArray::empty();
+        let get_next = self.stream.get_next.ok_or_else(missing_callback)?;
+        let code = unsafe { get_next(&mut self.stream, &mut array) };
+        if code != 0 {
+            return Err(Self::last_error(&mut self.stream, code));
+        }
+        if array.release.is_none() {
+            return Ok(None);
+        }
+        unsafe { self.import_batch(&mut array) }.map(Some)
+    }
+
+    /// Imports the columns of the struct array `array`, taking ownership of
+    /// its children
+    unsafe fn import_batch(&self, array: &mut RawArray) -> Result<RecordBatch> {
+        let num_columns = self.schema.fields().len();
+        if array.n_children as usize != num_columns {
+            return Err(DataFusionError::Execution(format!(
+                "Expected a struct array with {} children in ArrowArrayStream",
+                num_columns
+            )));
+        }
+        if array.null_count != 0 {
+            return Err(DataFusionError::NotImplemented(
+                "Struct arrays with nulls in ArrowArrayStream are not supported"
+                    .to_string(),
+            ));
+        }
+        let (offset, length) = (array.offset as usize, array.length as usize);
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                // the schema of each column is exported again, as arrow takes
+                // ownership of it
+                let schema =
+                    export_schema(field.name(), field.data_type(), field.is_nullable())?;
+                let column = import_array(&mut **array.children.add(i), schema)?;
+                // the children of a sliced struct array are not sliced
+                Ok(if offset != 0 || column.len() != length {
+                    column.slice(offset, length)
+                } else {
+                    column
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+impl Iterator for ArrowArrayStreamReader {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch()
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+            .transpose()
+    }
+}
+
+fn missing_callback() -> DataFusionError {
+    DataFusionError::Execution("ArrowArrayStream is missing a callback".to_string())
+}
+
+/// Reads the fields of the struct schema `schema`
+unsafe fn struct_schema(schema: &RawSchema) -> Result<Schema> {
+    if format(schema)? != "+s" {
+        return Err(DataFusionError::Execution(format!(
+            "Expected the struct schema of record batches in ArrowArrayStream, got '{}'",
+            format(schema)?
+        )));
+    }
+    let fields = (0..schema.n_children as usize)
+        .map(|i| field(schema.child(i)))
+        .collect::<Result<_>>()?;
+    Ok(Schema::new(fields))
+}
+
+unsafe fn format(schema: &RawSchema) -> Result<&str> {
+    if schema.format.is_null() {
+        return Err(DataFusionError::Execution(
+            "ArrowSchema without format".to_string(),
+        ));
+    }
+    CStr::from_ptr(schema.format).to_str().map_err(|e| {
+        DataFusionError::Execution(format!("Invalid ArrowSchema format: {}", e))
+    })
+}
+
+unsafe fn field(schema: &RawSchema) -> Result<Field> {
+    let name = if schema.name.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(schema.name).to_string_lossy().into_owned()
+    };
+    let nullable = schema.flags & ARROW_FLAG_NULLABLE != 0;
+    Ok(Field::new(&name, data_type(schema)?, nullable))
+}
+
+/// Reads the data type of `schema` from its format string, see
+/// <https://arrow.apache.org/docs/format/CDataInterface.html#data-type-description-format-strings>
+unsafe fn data_type(schema: &RawSchema) -> Result<DataType> {
+    let format = format(schema)?;
+    let child = |i: usize| -> Result<Box<Field>> {
+        if i >= schema.n_children as usize {
+            return Err(DataFusionError::Execution(format!(
+                "ArrowSchema of format '{}' is missing children",
+                format
+            )));
+        }
+        Ok(Box::new(field(schema.child(i))?))
+    };
+    let parse = |s: &str| -> Result<i32> {
+        s.parse().map_err(|_| {
+            DataFusionError::Execution(format!("Invalid ArrowSchema format '{}'", format))
+        })
+    };
+    let format_type = match format {
+        "n" => DataType::Null,
+        "b" => DataType::Boolean,
+        "c" => DataType::Int8,
+        "C" => DataType::UInt8,
+        "s" => DataType::Int16,
+        "S" => DataType::UInt16,
+        "i" => DataType::Int32,
+        "I" => DataType::UInt32,
+        "l" => DataType::Int64,
+        "L" => DataType::UInt64,
+        "e" => DataType::Float16,
+        "f" => DataType::Float32,
+        "g" => DataType::Float64,
+        "z" => DataType::Binary,
+        "Z" => DataType::LargeBinary,
+        "u" => DataType::Utf8,
+        "U" => DataType::LargeUtf8,
+        "tdD" => DataType::Date32,
+        "tdm" => DataType::Date64,
+        "tts" => DataType::Time32(TimeUnit::Second),
+        "ttm" => DataType::Time32(TimeUnit::Millisecond),
+        "ttu" => DataType::Time64(TimeUnit::Microsecond),
+        "ttn" => DataType::Time64(TimeUnit::Nanosecond),
+        "tiM" => DataType::Interval(IntervalUnit::YearMonth),
+        "tiD" => DataType::Interval(IntervalUnit::DayTime),
+        "+l" => DataType::List(child(0)?),
+        "+L" => DataType::LargeList(child(0)?),
+        "+s" => DataType::Struct(
+            (0..schema.n_children as usize)
+                .map(|i| child(i).map(|f| *f))
+                .collect::<Result<_>>()?,
+        ),
+        _ if format.starts_with("ts") && format.len() >= 4 => {
+            let unit = match &format[2..3] {
+                "s" => TimeUnit::Second,
+                "m" => TimeUnit::Millisecond,
+                "u" => TimeUnit::Microsecond,
+                "n" => TimeUnit::Nanosecond,
+                _ => return Err(unsupported_format(format)),
+            };
+            let tz = &format[4..];
+            let tz = if tz.is_empty() {
+                None
+            } else {
+                Some(tz.to_owned())
+            };
+            DataType::Timestamp(unit, tz)
+        }
+        _ if format.starts_with("w:") => DataType::FixedSizeBinary(parse(&format[2..])?),
+        _ if format.starts_with("+w:") => {
+            DataType::FixedSizeList(child(0)?, parse(&format[3..])?)
+        }
+        _ if format.starts_with("d:") => {
+            let mut parts = format[2..].split(',');
+            let precision = parse(parts.next().unwrap_or_default())?;
+            let scale = parse(parts.next().unwrap_or_default())?;
+            DataType::Decimal(precision as usize, scale as usize)
+        }
+        _ => return Err(unsupported_format(format)),
+    };
+    // the format of a dictionary encoded array describes its keys
+    if schema.dictionary.is_null() {
+        Ok(format_type)
+    } else {
+        let value_type = data_type(&*schema.dictionary)?;
+        Ok(DataType::Dictionary(
+            Box::new(format_type),
+            Box::new(value_type),
+        ))
+    }
+}
+
+fn unsupported_format(format: &str) -> DataFusionError {
+    DataFusionError::NotImplemented(format!(
+        "Unsupported ArrowSchema format '{}'",
+        format
+    ))
+}
+
+/// Execution plan scanning the record batches of an [`ArrowArrayStreamReader`].
+/// The stream can only be read once.
+#[derive(Debug, Clone)]
+pub struct ArrowArrayStreamExec {
+    /// The stream, until it is executed
+    reader: Arc<Mutex<Option<ArrowArrayStreamReader>>>,
+    /// The indices of the columns to produce, all of them if `None`
+    projection: Option<Vec<usize>>,
+    /// The output schema, after the projection
+    schema: SchemaRef,
+}
+
+impl ArrowArrayStreamExec {
+    /// Create a new ArrowArrayStreamExec reading the columns `projection`
+    /// of `reader`
+    pub fn new(
+        reader: Arc<Mutex<Option<ArrowArrayStreamReader>>>,
+        schema: SchemaRef,
+        projection: Option<Vec<usize>>,
+    ) -> Self {
+        let schema = match &projection {
+            Some(columns) => Arc::new(Schema::new(
+                columns.iter().map(|i| schema.field(*i).clone()).collect(),
+            )),
+            None => schema,
+        };
+        Self {
+            reader,
+            projection,
+            schema,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for ArrowArrayStreamExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(Arc::new(self.clone()))
+        } else {
+            Err(DataFusionError::Internal(format!(
+                "Children cannot be replaced in {:?}",
+                self
+            )))
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "ArrowArrayStreamExec invalid partition {}",
+                partition
+            )));
+        }
+        let reader = self.reader.lock().unwrap().take().ok_or_else(|| {
+            DataFusionError::Execution(
+                "The ArrowArrayStream was already read".to_string(),
+            )
+        })?;
+        Ok(Box::pin(ArrowArrayStreamStream {
+            reader: Mutex::new(reader),
+            projection: self.projection.clone(),
+            schema: self.schema.clone(),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "ArrowArrayStreamExec: projection={:?}", self.projection)
+            }
+        }
+    }
+}
+
+/// The ArrowArrayStreamExec stream reads the batches of the imported stream
+struct ArrowArrayStreamStream {
+    /// The imported stream, in a mutex as record batch streams are `Sync`
+    reader: Mutex<ArrowArrayStreamReader>,
+    projection: Option<Vec<usize>>,
+    schema: SchemaRef,
+}
+
+impl Stream for ArrowArrayStreamStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let batch = self.reader.get_mut().unwrap().next();
+        Poll::Ready(batch.map(|batch| {
+            let batch = batch?;
+            match &self.projection {
+                Some(columns) => RecordBatch::try_new(
+                    self.schema.clone(),
+                    columns.iter().map(|i| batch.column(*i).clone()).collect(),
+                ),
+                None => Ok(batch),
+            }
+        }))
+    }
+}
+
+impl RecordBatchStream for ArrowArrayStreamStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::array::{Array, Int32Array, StringArray};
+
+    fn test_batches() -> Result<(SchemaRef, Vec<RecordBatch>)> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+            ],
+        )?;
+        // a sliced batch has non-zero offsets in its columns
+        let sliced = RecordBatch::try_new(
+            schema.clone(),
+            batch.columns().iter().map(|c| c.slice(1, 2)).collect(),
+        )?;
+        Ok((schema, vec![batch, sliced]))
+    }
+
+    #[tokio::test]
+    async fn roundtrip_stream() -> Result<()> {
+        let (schema, batches) = test_batches()?;
+        let exec = MemoryExec::try_new(&[batches.clone()], schema.clone(), None)?;
+        let stream = FFI_ArrowArrayStream::new(exec.execute(0).await?);
+
+        let reader = ArrowArrayStreamReader::try_new(stream)?;
+        assert_eq!(reader.schema(), schema);
+        let imported = reader.collect::<ArrowResult<Vec<_>>>()?;
+        assert_eq!(imported.len(), 2);
+        for (imported, batch) in imported.iter().zip(batches.iter()) {
+            assert_eq!(imported.schema(), schema);
+            for i in 0..schema.fields().len() {
+                assert_eq!(imported.column(i).data(), batch.column(i).data());
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scan_projected_stream() -> Result<()> {
+        let (schema, batches) = test_batches()?;
+        let exec = MemoryExec::try_new(&[batches], schema, None)?;
+        let stream = FFI_ArrowArrayStream::new(exec.execute(0).await?);
+        let reader = ArrowArrayStreamReader::try_new(stream)?;
+
+        let schema = reader.schema();
+        let exec = Arc::new(ArrowArrayStreamExec::new(
+            Arc::new(Mutex::new(Some(reader))),
+            schema,
+            Some(vec![1]),
+        ));
+        let results = collect(exec.clone()).await?;
+        let values = results
+            .iter()
+            .flat_map(|batch| {
+                let column = batch.column(0);
+                let column = column.as_any().downcast_ref::<StringArray>().unwrap();
+                (0..column.len())
+                    .map(|i| Some(column.value(i)).filter(|_| column.is_valid(i)))
+                    .map(|v| v.map(|v| v.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Some("x".to_owned()),
+                None,
+                Some("z".to_owned()),
+                None,
+                Some("z".to_owned())
+            ]
+        );
+
+        // the stream can only be read once
+        let err = collect(exec).await.unwrap_err();
+        assert!(err.to_string().contains("already read"));
+        Ok(())
+    }
+
+    /// Fails after its first batch
+    struct FailingStream {
+        batch: Option<RecordBatch>,
+    }
+
+    impl Stream for FailingStream {
+        type Item = ArrowResult<RecordBatch>;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            Poll::Ready(Some(self.batch.take().ok_or_else(|| {
+                ArrowError::ComputeError("stream failed".to_string())
+            })))
+        }
+    }
+
+    impl RecordBatchStream for FailingStream {
+        fn schema(&self) -> SchemaRef {
+            test_batches().unwrap().0
+        }
+    }
+
+    #[test]
+    fn stream_error() -> Result<()> {
+        let (_, batches) = test_batches()?;
+        let stream = FFI_ArrowArrayStream::new(Box::pin(FailingStream {
+            batch: Some(batches[0].clone()),
+        }));
+        let mut reader = ArrowArrayStreamReader::try_new(stream)?;
+        assert_eq!(reader.next().unwrap()?.num_rows(), 3);
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("stream failed"));
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_schema() -> Result<()> {
+        let fields = vec![
+            Field::new("a", DataType::Float64, false),
+            Field::new(
+                "b",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+                true,
+            ),
+            Field::new(
+                "c",
+                DataType::List(Box::new(Field::new("item", DataType::Date32, true))),
+                true,
+            ),
+            Field::new(
+                "d",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("e", DataType::Decimal(10, 2), true),
+            Field::new(
+                "f",
+                DataType::Struct(vec![
+                    Field::new("x", DataType::FixedSizeBinary(4), false),
+                    Field::new("y", DataType::Time64(TimeUnit::Nanosecond), true),
+                ]),
+                false,
+            ),
+        ];
+        let raw = export_schema("", &DataType::Struct(fields.clone()), false)?;
+        assert_eq!(unsafe { struct_schema(&raw) }?, Schema::new(fields));
+
+        let err = format_of(&DataType::Union(vec![])).unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+        Ok(())
+    }
+}
@@ -25,11 +25,12 @@ use std::task::{Context, Poll};
 
 use super::{RecordBatchStream, SendableRecordBatchStream};
 use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
 use crate::physical_plan::{
-    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    expressions::BinaryExpr, selection::SelectionVector, DisplayFormatType,
+    ExecutionPlan, Partitioning, PhysicalExpr,
 };
 use arrow::array::BooleanArray;
-use arrow::compute::filter_record_batch;
 use arrow::datatypes::{DataType, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
@@ -94,6 +95,10 @@ impl ExecutionPlan for FilterExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
         self.input.output_partitioning()
@@ -146,27 +151,53 @@ struct FilterExecStream {
     input: SendableRecordBatchStream,
 }
 
+/// Evaluates the conjuncts of the predicate one after the other, each only
+/// for the rows selected by the previous ones, and copies the selected rows
+/// once at the end
 fn batch_filter(
     batch: &RecordBatch,
     predicate: &Arc<dyn PhysicalExpr>,
 ) -> ArrowResult<RecordBatch> {
-    predicate
-        .evaluate(&batch)
-        .map(|v| v.into_array(batch.num_rows()))
+    let mut conjuncts = vec![];
+    split_conjunction(predicate, &mut conjuncts);
+
+    let mut selection = SelectionVector::all(batch.num_rows());
+    for conjunct in conjuncts {
+        if selection.selected_count() == 0 {
+            break;
+        }
+        let value = conjunct
+            .evaluate_selection(batch, &selection)
+            .map_err(DataFusionError::into_arrow_external_error)?
+            .into_array(batch.num_rows());
+        let mask = value
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .ok_or_else(|| {
+                DataFusionError::Internal(
+                    "Filter predicate evaluated to non-boolean value".to_string(),
+                )
+                .into_arrow_external_error()
+            })?;
+        selection = selection.and(mask);
+    }
+    selection
+        .filter_batch(batch)
         .map_err(DataFusionError::into_arrow_external_error)
-        .and_then(|array| {
-            array
-                .as_any()
-                .downcast_ref::<BooleanArray>()
-                .ok_or_else(|| {
-                    DataFusionError::Internal(
-                        "Filter predicate evaluated to non-boolean value".to_string(),
-                    )
-                    .into_arrow_external_error()
-                })
-                // apply filter array to record batch
-                .and_then(|filter_array| filter_record_batch(batch, filter_array))
-        })
+}
+
+/// Collects the expressions that are combined with AND in `predicate`
+fn split_conjunction<'a>(
+    predicate: &'a Arc<dyn PhysicalExpr>,
+    conjuncts: &mut Vec<&'a Arc<dyn PhysicalExpr>>,
+) {
+    match predicate.as_any().downcast_ref::<BinaryExpr>() {
+        Some(binary) if binary.op() == &Operator::And => {
+            split_conjunction(binary.left(), conjuncts);
+            split_conjunction(binary.right(), conjuncts);
+        }
+        _ => conjuncts.push(predicate),
+    }
 }
 
 impl Stream for FilterExecStream {
@@ -200,10 +231,13 @@ mod tests {
     use super::*;
     use crate::physical_plan::csv::{CsvExec, CsvReadOptions};
     use crate::physical_plan::expressions::*;
+    use crate::physical_plan::memory::MemoryExec;
     use crate::physical_plan::ExecutionPlan;
     use crate::scalar::ScalarValue;
     use crate::test;
     use crate::{logical_plan::Operator, physical_plan::collect};
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
     use std::iter::Iterator;
 
     #[tokio::test]
@@ -251,4 +285,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn conjuncts_only_see_selected_rows() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![0, 2, 0, 5, 10])),
+                Arc::new(Int32Array::from(vec![
+                    None,
+                    Some(1),
+                    Some(1),
+                    Some(1),
+                    None,
+                ])),
+            ],
+        )?;
+        let memory = MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?;
+
+        // the division would fail for the rows where a is 0
+        let predicate = binary(
+            binary(
+                col("a"),
+                Operator::NotEq,
+                lit(ScalarValue::Int32(Some(0))),
+                &schema,
+            )?,
+            Operator::And,
+            binary(
+                binary(
+                    lit(ScalarValue::Int32(Some(10))),
+                    Operator::Divide,
+                    col("a"),
+                    &schema,
+                )?,
+                Operator::GtEq,
+                lit(ScalarValue::Int32(Some(2))),
+                &schema,
+            )?,
+            &schema,
+        )?;
+        let predicate =
+            binary(predicate, Operator::And, is_not_null(col("b"))?, &schema)?;
+
+        let filter = FilterExec::try_new(predicate, Arc::new(memory))?;
+        let results = collect(Arc::new(filter)).await?;
+        assert_eq!(results.len(), 1);
+        let a = results[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(a, &Int32Array::from(vec![2, 5]));
+        Ok(())
+    }
 }
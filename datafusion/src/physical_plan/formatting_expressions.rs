@@ -0,0 +1,477 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Formatting expressions
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use super::ColumnarValue;
+use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
+use arrow::{
+    array::{
+        ArrayRef, Date32Array, Date64Array, Float64Array, Int64Array, StringArray,
+        TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+        TimestampSecondArray,
+    },
+    datatypes::{DataType, TimeUnit},
+    temporal_conversions::{
+        date32_to_datetime, date64_to_datetime, timestamp_ms_to_datetime,
+        timestamp_ns_to_datetime, timestamp_s_to_datetime, timestamp_us_to_datetime,
+    },
+};
+use chrono::prelude::*;
+
+/// A part of a datetime format pattern
+#[derive(Debug, Clone, PartialEq)]
+enum DateTimeToken {
+    /// Text copied to the output as is
+    Literal(String),
+    /// A field rendered with the given `chrono` format specifier
+    Field(&'static str),
+    /// A field rendered with the given `chrono` format specifier, in upper case
+    UpperField(&'static str),
+    /// The quarter of the year, 1 to 4
+    Quarter,
+}
+
+/// The supported template patterns, longest first so that e.g. `HH24` is
+/// not taken as `HH` followed by the literal `24`
+const DATETIME_PATTERNS: &[(&str, DateTimeToken)] = &[
+    ("HH24", DateTimeToken::Field("%H")),
+    ("HH12", DateTimeToken::Field("%I")),
+    ("YYYY", DateTimeToken::Field("%Y")),
+    ("MONTH", DateTimeToken::UpperField("%B")),
+    ("Month", DateTimeToken::Field("%B")),
+    ("DDD", DateTimeToken::Field("%j")),
+    ("DAY", DateTimeToken::UpperField("%A")),
+    ("Day", DateTimeToken::Field("%A")),
+    ("MON", DateTimeToken::UpperField("%b")),
+    ("Mon", DateTimeToken::Field("%b")),
+    ("HH", DateTimeToken::Field("%I")),
+    ("YY", DateTimeToken::Field("%y")),
+    ("MM", DateTimeToken::Field("%m")),
+    ("MI", DateTimeToken::Field("%M")),
+    ("MS", DateTimeToken::Field("%3f")),
+    ("US", DateTimeToken::Field("%6f")),
+    ("SS", DateTimeToken::Field("%S")),
+    ("DD", DateTimeToken::Field("%d")),
+    ("DY", DateTimeToken::UpperField("%a")),
+    ("Dy", DateTimeToken::Field("%a")),
+    ("AM", DateTimeToken::Field("%p")),
+    ("PM", DateTimeToken::Field("%p")),
+    ("am", DateTimeToken::Field("%P")),
+    ("pm", DateTimeToken::Field("%P")),
+    ("Q", DateTimeToken::Quarter),
+];
+
+/// Parses a PostgreSQL style datetime template such as `YYYY-MM-DD HH24:MI:SS`.
+/// Text in double quotes and characters that are not part of a pattern are
+/// copied to the output.
+fn parse_datetime_format(format: &str) -> Vec<DateTimeToken> {
+    let mut tokens = vec![];
+    let mut literal = String::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let quoted = &rest[1..];
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            literal.push_str(&quoted[..end]);
+            rest = &quoted[(end + 1).min(quoted.len())..];
+            continue;
+        }
+        match DATETIME_PATTERNS
+            .iter()
+            .find(|(pattern, _)| rest.starts_with(pattern))
+        {
+            Some((pattern, token)) => {
+                if !literal.is_empty() {
+                    tokens.push(DateTimeToken::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(token.clone());
+                rest = &rest[pattern.len()..];
+            }
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(DateTimeToken::Literal(literal));
+    }
+    tokens
+}
+
+fn format_datetime(tokens: &[DateTimeToken], datetime: &NaiveDateTime) -> String {
+    let mut result = String::new();
+    for token in tokens {
+        match token {
+            DateTimeToken::Literal(s) => result.push_str(s),
+            DateTimeToken::Field(f) => write!(result, "{}", datetime.format(f)).unwrap(),
+            DateTimeToken::UpperField(f) => {
+                result.push_str(&datetime.format(f).to_string().to_uppercase())
+            }
+            DateTimeToken::Quarter => {
+                write!(result, "{}", datetime.month0() / 3 + 1).unwrap()
+            }
+        }
+    }
+    result
+}
+
+/// A digit position of a numeric format pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DigitToken {
+    /// `9`, a digit that is left out when it is a leading zero
+    Digit,
+    /// `0`, a digit that is always rendered
+    Zero,
+    /// `,` or `G`, rendered only between digits
+    Group,
+}
+
+/// A parsed PostgreSQL style numeric template such as `FM999,990.00`
+#[derive(Debug, Clone, PartialEq)]
+struct NumberFormat {
+    /// `FM` prefix: no padding and no space for the sign of positive numbers
+    fill_mode: bool,
+    integer: Vec<DigitToken>,
+    /// Whether the template has a decimal point, `.` or `D`
+    point: bool,
+    fraction: Vec<DigitToken>,
+}
+
+fn parse_number_format(format: &str) -> Result<NumberFormat> {
+    let (fill_mode, template) = match format.strip_prefix("FM") {
+        Some(template) => (true, template),
+        None => (false, format),
+    };
+    let mut number_format = NumberFormat {
+        fill_mode,
+        integer: vec![],
+        point: false,
+        fraction: vec![],
+    };
+    for c in template.chars() {
+        let digits = if number_format.point {
+            &mut number_format.fraction
+        } else {
+            &mut number_format.integer
+        };
+        match c {
+            '9' => digits.push(DigitToken::Digit),
+            '0' => digits.push(DigitToken::Zero),
+            ',' | 'G' if !number_format.point => digits.push(DigitToken::Group),
+            '.' | 'D' if !number_format.point => number_format.point = true,
+            other => {
+                return Err(DataFusionError::Execution(format!(
+                    "Unsupported character '{}' in to_char numeric format '{}'",
+                    other, format
+                )))
+            }
+        }
+    }
+    Ok(number_format)
+}
+
+/// Renders the decimal digits of a number, `integer` and `fraction`, which
+/// must already be rounded to the number of fraction digits of `format`
+fn format_digits(
+    format: &NumberFormat,
+    negative: bool,
+    integer: &str,
+    fraction: &str,
+) -> String {
+    let positions = format
+        .integer
+        .iter()
+        .filter(|t| **t != DigitToken::Group)
+        .count();
+    // zero integer parts are left out in favour of the fraction
+    let integer = if integer == "0" && format.point && !format.fraction.is_empty() {
+        ""
+    } else {
+        integer
+    };
+
+    let mut result = String::new();
+    if integer.len() > positions {
+        // PostgreSQL renders numbers that do not fit the template as `#`
+        result.push(' ');
+        for token in &format.integer {
+            result.push(if *token == DigitToken::Group {
+                ','
+            } else {
+                '#'
+            });
+        }
+        if format.point {
+            result.push('.');
+            result.extend(format.fraction.iter().map(|_| '#'));
+        }
+        return result;
+    }
+
+    // the first `0` forces all digit positions to its right to be rendered
+    let first_zero = format
+        .integer
+        .iter()
+        .filter(|t| **t != DigitToken::Group)
+        .position(|t| *t == DigitToken::Zero)
+        .unwrap_or(positions);
+    let skipped = positions - integer.len();
+    let mut digits = integer.chars();
+    let mut padding = String::new();
+    let mut body = String::new();
+    let mut position = 0;
+    for token in &format.integer {
+        match token {
+            DigitToken::Group if body.is_empty() => padding.push(' '),
+            DigitToken::Group => body.push(','),
+            _ => {
+                if position >= skipped {
+                    body.push(digits.next().unwrap());
+                } else if position >= first_zero {
+                    body.push('0');
+                } else {
+                    padding.push(' ');
+                }
+                position += 1;
+            }
+        }
+    }
+    if format.point {
+        body.push('.');
+        let mut fraction = fraction.to_string();
+        if format.fill_mode {
+            // trailing zeros are left out for `9` positions
+            while format.fraction.get(fraction.len().wrapping_sub(1))
+                == Some(&DigitToken::Digit)
+                && fraction.ends_with('0')
+            {
+                fraction.pop();
+            }
+        }
+        body.push_str(&fraction);
+    }
+
+    if !format.fill_mode {
+        result.push_str(&padding);
+    }
+    if negative {
+        result.push('-');
+    } else if !format.fill_mode {
+        result.push(' ');
+    }
+    result.push_str(&body);
+    result
+}
+
+fn format_i64(format: &NumberFormat, value: i64) -> String {
+    let fraction = "0".repeat(format.fraction.len());
+    format_digits(
+        format,
+        value < 0,
+        &value.unsigned_abs().to_string(),
+        &fraction,
+    )
+}
+
+fn format_f64(format: &NumberFormat, value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let rounded = format!("{:.*}", format.fraction.len(), value.abs());
+    let (integer, fraction) = match rounded.find('.') {
+        Some(point) => (&rounded[..point], &rounded[point + 1..]),
+        None => (rounded.as_str(), ""),
+    };
+    let negative = value < 0.0 && rounded.chars().any(|c| c.is_ascii_digit() && c != '0');
+    format_digits(format, negative, integer, fraction)
+}
+
+/// Applies `$FN` to the datetime of every non-null value of `$ARRAY`, a
+/// `$ARRAY_TYPE` whose values are converted with `$TO_DATETIME`
+macro_rules! format_datetimes {
+    ($ARRAY:expr, $ARRAY_TYPE:ident, $TO_DATETIME:expr, $TOKENS:expr) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        array
+            .iter()
+            .map(|v| v.map(|v| format_datetime($TOKENS, &$TO_DATETIME(v))))
+            .collect::<StringArray>()
+    }};
+}
+
+/// TO_CHAR SQL function
+///
+/// Renders dates and timestamps with a PostgreSQL style template such as
+/// `YYYY-MM-DD HH24:MI:SS`, supporting the patterns `YYYY`, `YY`, `Q`, `MM`,
+/// `Month`, `Mon`, `DD`, `DDD`, `Day`, `Dy`, `HH24`, `HH12`, `HH`, `MI`,
+/// `SS`, `MS`, `US` and `AM`/`PM`, and numbers with templates made of `9`,
+/// `0`, `,`/`G`, `.`/`D` and an optional `FM` prefix.
+pub fn to_char(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    if args.len() != 2 {
+        return Err(DataFusionError::Execution(
+            "Expected two arguments in TO_CHAR".to_string(),
+        ));
+    }
+    let (value, format) = (&args[0], &args[1]);
+
+    let format = match format {
+        ColumnarValue::Scalar(ScalarValue::Utf8(Some(v))) => v,
+        ColumnarValue::Scalar(ScalarValue::Utf8(None)) => {
+            return Ok(ColumnarValue::Scalar(ScalarValue::Utf8(None)))
+        }
+        _ => {
+            return Err(DataFusionError::Execution(
+                "Second argument of `TO_CHAR` must be a scalar Utf8".to_string(),
+            ))
+        }
+    };
+
+    let is_scalar = matches!(value, ColumnarValue::Scalar(_));
+
+    let array = match value {
+        ColumnarValue::Array(array) => array.clone(),
+        ColumnarValue::Scalar(scalar) => scalar.to_array(),
+    };
+
+    let arr = match array.data_type() {
+        DataType::Int64 => {
+            let number_format = parse_number_format(format)?;
+            let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+            array
+                .iter()
+                .map(|v| v.map(|v| format_i64(&number_format, v)))
+                .collect::<StringArray>()
+        }
+        DataType::Float64 => {
+            let number_format = parse_number_format(format)?;
+            let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
+            array
+                .iter()
+                .map(|v| v.map(|v| format_f64(&number_format, v)))
+                .collect::<StringArray>()
+        }
+        DataType::Date32 => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(array, Date32Array, date32_to_datetime, &tokens)
+        }
+        DataType::Date64 => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(array, Date64Array, date64_to_datetime, &tokens)
+        }
+        DataType::Timestamp(TimeUnit::Second, None) => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(
+                array,
+                TimestampSecondArray,
+                timestamp_s_to_datetime,
+                &tokens
+            )
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(
+                array,
+                TimestampMillisecondArray,
+                timestamp_ms_to_datetime,
+                &tokens
+            )
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(
+                array,
+                TimestampMicrosecondArray,
+                timestamp_us_to_datetime,
+                &tokens
+            )
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            let tokens = parse_datetime_format(format);
+            format_datetimes!(
+                array,
+                TimestampNanosecondArray,
+                timestamp_ns_to_datetime,
+                &tokens
+            )
+        }
+        datatype => {
+            return Err(DataFusionError::Internal(format!(
+                "TO_CHAR does not support datatype {:?}",
+                datatype
+            )))
+        }
+    };
+
+    Ok(if is_scalar {
+        ColumnarValue::Scalar(ScalarValue::try_from_array(
+            &(Arc::new(arr) as ArrayRef),
+            0,
+        )?)
+    } else {
+        ColumnarValue::Array(Arc::new(arr))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(format: &str, value: f64) -> String {
+        format_f64(&parse_number_format(format).unwrap(), value)
+    }
+
+    #[test]
+    fn datetime_patterns() {
+        let datetime = NaiveDate::from_ymd(2021, 9, 8).and_hms_milli(14, 5, 6, 78);
+        let cases = vec![
+            ("YYYY-MM-DD HH24:MI", "2021-09-08 14:05"),
+            ("YYYY-MM-DD HH24:MI:SS.MS", "2021-09-08 14:05:06.078"),
+            ("DD/MM/YY HH12:MI AM", "08/09/21 02:05 PM"),
+            ("Day, DD Month YYYY", "Wednesday, 08 September 2021"),
+            ("DY MON DD", "WED SEP 08"),
+            ("YYYY\"Q\"Q", "2021Q3"),
+            ("DDD", "251"),
+        ];
+        for (format, expected) in cases {
+            let tokens = parse_datetime_format(format);
+            assert_eq!(format_datetime(&tokens, &datetime), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn number_patterns() {
+        assert_eq!(number("999", 5.0), "   5");
+        assert_eq!(number("999", -5.0), "  -5");
+        assert_eq!(number("FM999", -5.0), "-5");
+        assert_eq!(number("0999", 5.0), " 0005");
+        assert_eq!(number("9,999.99", 1234.5678), " 1,234.57");
+        assert_eq!(number("9,999", 12.0), "    12");
+        assert_eq!(number("FM9,990.00", 0.5), "0.50");
+        assert_eq!(number("FM9.99", 1.5), "1.5");
+        assert_eq!(number("99", 123.0), " ##");
+        assert_eq!(
+            format_i64(&parse_number_format("FM999,999,999").unwrap(), 1234567),
+            "1,234,567"
+        );
+        assert!(parse_number_format("99x").is_err());
+    }
+}
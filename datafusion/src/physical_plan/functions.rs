@@ -36,9 +36,16 @@ use super::{
 use crate::execution::context::ExecutionContextState;
 use crate::physical_plan::array_expressions;
 use crate::physical_plan::datetime_expressions;
-use crate::physical_plan::expressions::{nullif_func, SUPPORTED_NULLIF_TYPES};
+use crate::physical_plan::dictionary_expressions;
+use crate::physical_plan::expressions::{
+    nullif_func, nvl2_func, nvl_func, try_cast, CoalesceExpr, Literal, MonotonicIdExpr,
+    SUPPORTED_NULLIF_TYPES, SUPPORTED_NVL_TYPES,
+};
+use crate::physical_plan::formatting_expressions;
+use crate::physical_plan::map_expressions;
 use crate::physical_plan::math_expressions;
 use crate::physical_plan::string_expressions;
+use crate::physical_plan::struct_expressions;
 use crate::{
     error::{DataFusionError, Result},
     scalar::ScalarValue,
@@ -64,6 +71,9 @@ pub enum Signature {
     // A function such as `array` is `VariadicEqual`
     // The first argument decides the type used for coercion
     VariadicEqual,
+    /// arbitrary number of arguments of arbitrary types
+    // A function such as `struct` is `VariadicAny`
+    VariadicAny,
     /// fixed number of arguments of an arbitrary but equal type out of a list of valid types
     // A function of one argument of f64 is `Uniform(1, vec![DataType::Float64])`
     // A function of one argument of f64 or f32 is `Uniform(1, vec![DataType::Float32, DataType::Float64])`
@@ -72,6 +82,12 @@ pub enum Signature {
     Exact(Vec<DataType>),
     /// fixed number of arguments of arbitrary types
     Any(usize),
+    /// fixed number of arguments of an arbitrary but equal numeric type
+    // A function such as `greatest(a, b)` is `Numeric(2)`
+    // The arguments are coerced to the narrowest numeric type that can hold all of them
+    Numeric(usize),
+    /// arbitrary number of arguments of an arbitrary but equal numeric type
+    VariadicNumeric,
     /// One of a list of signatures
     OneOf(Vec<Signature>),
 }
@@ -91,6 +107,12 @@ pub type ScalarFunctionImplementation =
 pub type ReturnTypeFunction =
     Arc<dyn Fn(&[DataType]) -> Result<Arc<DataType>> + Send + Sync>;
 
+/// A function's return type computed from the types of its arguments and the
+/// values of its arguments that are literals, `None` for the other arguments
+pub type ReturnTypeFromArgsFunction = Arc<
+    dyn Fn(&[DataType], &[Option<&ScalarValue>]) -> Result<Arc<DataType>> + Send + Sync,
+>;
+
 /// Enum of all built-in scalar functions
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BuiltinScalarFunction {
@@ -103,22 +125,40 @@ pub enum BuiltinScalarFunction {
     Asin,
     /// atan
     Atan,
+    /// atan2
+    Atan2,
+    /// cbrt
+    Cbrt,
     /// ceil
     Ceil,
     /// cos
     Cos,
+    /// degrees
+    Degrees,
     /// exp
     Exp,
+    /// factorial
+    Factorial,
     /// floor
     Floor,
+    /// gcd
+    Gcd,
+    /// lcm
+    Lcm,
     /// ln, Natural logarithm
     Ln,
-    /// log, same as log10
+    /// log, same as log10 with a single argument, otherwise `log(base, x)`
     Log,
     /// log10
     Log10,
     /// log2
     Log2,
+    /// pi
+    Pi,
+    /// power
+    Power,
+    /// radians
+    Radians,
     /// round
     Round,
     /// signum
@@ -135,6 +175,20 @@ pub enum BuiltinScalarFunction {
     // string functions
     /// construct an array from columns
     Array,
+    /// array_append
+    ArrayAppend,
+    /// array_concat
+    ArrayConcat,
+    /// array_contains
+    ArrayContains,
+    /// array_length
+    ArrayLength,
+    /// array_position
+    ArrayPosition,
+    /// array_slice
+    ArraySlice,
+    /// arrow_typeof
+    ArrowTypeof,
     /// ascii
     Ascii,
     /// bit_length
@@ -145,30 +199,70 @@ pub enum BuiltinScalarFunction {
     CharacterLength,
     /// chr
     Chr,
+    /// coalesce
+    Coalesce,
     /// concat
     Concat,
     /// concat_ws
     ConcatWithSeparator,
+    /// date_bin
+    DateBin,
     /// date_part
     DatePart,
     /// date_trunc
     DateTrunc,
+    /// decode
+    Decode,
+    /// element_at
+    ElementAt,
+    /// encode
+    Encode,
+    /// fuzzy_match
+    FuzzyMatch,
     /// initcap
     InitCap,
+    /// json_get
+    JsonGet,
+    /// json_get_bool
+    JsonGetBool,
+    /// json_get_float
+    JsonGetFloat,
+    /// json_get_int
+    JsonGetInt,
+    /// json_get_str
+    JsonGetStr,
     /// left
     Left,
+    /// levenshtein
+    Levenshtein,
     /// lpad
     Lpad,
     /// lower
     Lower,
     /// ltrim
     Ltrim,
+    /// make_array
+    MakeArray,
+    /// map_keys
+    MapKeys,
+    /// map_values
+    MapValues,
     /// md5
     MD5,
+    /// monotonic_id
+    MonotonicId,
+    /// named_struct
+    NamedStruct,
     /// nullif
     NullIf,
+    /// nvl, also called ifnull
+    Nvl,
+    /// nvl2
+    Nvl2,
     /// octet_length
     OctetLength,
+    /// overlay
+    Overlay,
     /// random
     Random,
     /// regexp_replace
@@ -193,14 +287,20 @@ pub enum BuiltinScalarFunction {
     SHA384,
     /// Sha512
     SHA512,
+    /// soundex
+    Soundex,
     /// split_part
     SplitPart,
     /// starts_with
     StartsWith,
     /// strpos
     Strpos,
+    /// struct
+    Struct,
     /// substr
     Substr,
+    /// to_char
+    ToChar,
     /// to_hex
     ToHex,
     /// to_timestamp
@@ -213,19 +313,98 @@ pub enum BuiltinScalarFunction {
     Trim,
     /// upper
     Upper,
+    /// uuid
+    Uuid,
     /// regexp_match
     RegexpMatch,
 }
 
+/// Whether a function returns the same result when evaluated again with the same
+/// arguments, which determines whether optimizations may change how often it
+/// is evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Volatility {
+    /// Always returns the same result for the same arguments, e.g. `abs`
+    Immutable,
+    /// Returns the same result for the same arguments within a query, e.g. `now`
+    Stable,
+    /// May return a different result on every evaluation, e.g. `random`. Such
+    /// functions must be neither constant folded nor have their evaluations
+    /// deduplicated or duplicated.
+    Volatile,
+}
+
 impl BuiltinScalarFunction {
     /// an allowlist of functions to take zero arguments, so that they will get special treatment
     /// while executing.
     fn supports_zero_argument(&self) -> bool {
         matches!(
             self,
-            BuiltinScalarFunction::Random | BuiltinScalarFunction::Now
+            BuiltinScalarFunction::Random
+                | BuiltinScalarFunction::Now
+                | BuiltinScalarFunction::Pi
+                | BuiltinScalarFunction::Uuid
+        )
+    }
+
+    /// Whether this function of a string first argument returns null for a null
+    /// string, so that it can be evaluated once per distinct value of a
+    /// dictionary-encoded first argument
+    fn supports_dictionary(&self) -> bool {
+        matches!(
+            self,
+            BuiltinScalarFunction::Ascii
+                | BuiltinScalarFunction::BitLength
+                | BuiltinScalarFunction::Btrim
+                | BuiltinScalarFunction::CharacterLength
+                | BuiltinScalarFunction::InitCap
+                | BuiltinScalarFunction::JsonGet
+                | BuiltinScalarFunction::JsonGetBool
+                | BuiltinScalarFunction::JsonGetFloat
+                | BuiltinScalarFunction::JsonGetInt
+                | BuiltinScalarFunction::JsonGetStr
+                | BuiltinScalarFunction::FuzzyMatch
+                | BuiltinScalarFunction::Left
+                | BuiltinScalarFunction::Levenshtein
+                | BuiltinScalarFunction::Lower
+                | BuiltinScalarFunction::Lpad
+                | BuiltinScalarFunction::Ltrim
+                | BuiltinScalarFunction::MD5
+                | BuiltinScalarFunction::OctetLength
+                | BuiltinScalarFunction::Overlay
+                | BuiltinScalarFunction::RegexpMatch
+                | BuiltinScalarFunction::RegexpReplace
+                | BuiltinScalarFunction::Repeat
+                | BuiltinScalarFunction::Replace
+                | BuiltinScalarFunction::Reverse
+                | BuiltinScalarFunction::Right
+                | BuiltinScalarFunction::Rpad
+                | BuiltinScalarFunction::Rtrim
+                | BuiltinScalarFunction::SHA224
+                | BuiltinScalarFunction::SHA256
+                | BuiltinScalarFunction::SHA384
+                | BuiltinScalarFunction::SHA512
+                | BuiltinScalarFunction::Soundex
+                | BuiltinScalarFunction::SplitPart
+                | BuiltinScalarFunction::StartsWith
+                | BuiltinScalarFunction::Strpos
+                | BuiltinScalarFunction::Substr
+                | BuiltinScalarFunction::Translate
+                | BuiltinScalarFunction::Trim
+                | BuiltinScalarFunction::Upper
         )
     }
+
+    /// Returns the [`Volatility`] of this function
+    pub fn volatility(&self) -> Volatility {
+        match self {
+            BuiltinScalarFunction::MonotonicId
+            | BuiltinScalarFunction::Random
+            | BuiltinScalarFunction::Uuid => Volatility::Volatile,
+            BuiltinScalarFunction::Now => Volatility::Stable,
+            _ => Volatility::Immutable,
+        }
+    }
 }
 
 impl fmt::Display for BuiltinScalarFunction {
@@ -244,14 +423,23 @@ impl FromStr for BuiltinScalarFunction {
             "acos" => BuiltinScalarFunction::Acos,
             "asin" => BuiltinScalarFunction::Asin,
             "atan" => BuiltinScalarFunction::Atan,
+            "atan2" => BuiltinScalarFunction::Atan2,
+            "cbrt" => BuiltinScalarFunction::Cbrt,
             "ceil" => BuiltinScalarFunction::Ceil,
             "cos" => BuiltinScalarFunction::Cos,
+            "degrees" => BuiltinScalarFunction::Degrees,
             "exp" => BuiltinScalarFunction::Exp,
+            "factorial" => BuiltinScalarFunction::Factorial,
             "floor" => BuiltinScalarFunction::Floor,
+            "gcd" => BuiltinScalarFunction::Gcd,
+            "lcm" => BuiltinScalarFunction::Lcm,
             "ln" => BuiltinScalarFunction::Ln,
             "log" => BuiltinScalarFunction::Log,
             "log10" => BuiltinScalarFunction::Log10,
             "log2" => BuiltinScalarFunction::Log2,
+            "pi" => BuiltinScalarFunction::Pi,
+            "power" | "pow" => BuiltinScalarFunction::Power,
+            "radians" => BuiltinScalarFunction::Radians,
             "round" => BuiltinScalarFunction::Round,
             "signum" => BuiltinScalarFunction::Signum,
             "sin" => BuiltinScalarFunction::Sin,
@@ -261,6 +449,14 @@ impl FromStr for BuiltinScalarFunction {
 
             // string functions
             "array" => BuiltinScalarFunction::Array,
+            "array_append" => BuiltinScalarFunction::ArrayAppend,
+            "array_concat" => BuiltinScalarFunction::ArrayConcat,
+            "array_contains" => BuiltinScalarFunction::ArrayContains,
+            "array_has" => BuiltinScalarFunction::ArrayContains,
+            "array_length" => BuiltinScalarFunction::ArrayLength,
+            "array_position" => BuiltinScalarFunction::ArrayPosition,
+            "array_slice" => BuiltinScalarFunction::ArraySlice,
+            "arrow_typeof" => BuiltinScalarFunction::ArrowTypeof,
             "ascii" => BuiltinScalarFunction::Ascii,
             "bit_length" => BuiltinScalarFunction::BitLength,
             "btrim" => BuiltinScalarFunction::Btrim,
@@ -269,17 +465,37 @@ impl FromStr for BuiltinScalarFunction {
             "concat" => BuiltinScalarFunction::Concat,
             "concat_ws" => BuiltinScalarFunction::ConcatWithSeparator,
             "chr" => BuiltinScalarFunction::Chr,
+            "coalesce" => BuiltinScalarFunction::Coalesce,
+            "date_bin" => BuiltinScalarFunction::DateBin,
+            "decode" => BuiltinScalarFunction::Decode,
+            "element_at" => BuiltinScalarFunction::ElementAt,
+            "encode" => BuiltinScalarFunction::Encode,
+            "fuzzy_match" => BuiltinScalarFunction::FuzzyMatch,
             "date_part" => BuiltinScalarFunction::DatePart,
             "date_trunc" => BuiltinScalarFunction::DateTrunc,
             "initcap" => BuiltinScalarFunction::InitCap,
+            "json_get" => BuiltinScalarFunction::JsonGet,
+            "json_get_bool" => BuiltinScalarFunction::JsonGetBool,
+            "json_get_float" => BuiltinScalarFunction::JsonGetFloat,
+            "json_get_int" => BuiltinScalarFunction::JsonGetInt,
+            "json_get_str" => BuiltinScalarFunction::JsonGetStr,
             "left" => BuiltinScalarFunction::Left,
+            "levenshtein" => BuiltinScalarFunction::Levenshtein,
             "length" => BuiltinScalarFunction::CharacterLength,
             "lower" => BuiltinScalarFunction::Lower,
             "lpad" => BuiltinScalarFunction::Lpad,
             "ltrim" => BuiltinScalarFunction::Ltrim,
+            "make_array" => BuiltinScalarFunction::MakeArray,
+            "map_keys" => BuiltinScalarFunction::MapKeys,
+            "map_values" => BuiltinScalarFunction::MapValues,
             "md5" => BuiltinScalarFunction::MD5,
+            "monotonic_id" => BuiltinScalarFunction::MonotonicId,
+            "named_struct" => BuiltinScalarFunction::NamedStruct,
             "nullif" => BuiltinScalarFunction::NullIf,
+            "nvl" | "ifnull" => BuiltinScalarFunction::Nvl,
+            "nvl2" => BuiltinScalarFunction::Nvl2,
             "octet_length" => BuiltinScalarFunction::OctetLength,
+            "overlay" => BuiltinScalarFunction::Overlay,
             "random" => BuiltinScalarFunction::Random,
             "regexp_replace" => BuiltinScalarFunction::RegexpReplace,
             "repeat" => BuiltinScalarFunction::Repeat,
@@ -292,16 +508,20 @@ impl FromStr for BuiltinScalarFunction {
             "sha256" => BuiltinScalarFunction::SHA256,
             "sha384" => BuiltinScalarFunction::SHA384,
             "sha512" => BuiltinScalarFunction::SHA512,
+            "soundex" => BuiltinScalarFunction::Soundex,
             "split_part" => BuiltinScalarFunction::SplitPart,
             "starts_with" => BuiltinScalarFunction::StartsWith,
             "strpos" => BuiltinScalarFunction::Strpos,
-            "substr" => BuiltinScalarFunction::Substr,
+            "struct" => BuiltinScalarFunction::Struct,
+            "substr" | "substring" => BuiltinScalarFunction::Substr,
+            "to_char" => BuiltinScalarFunction::ToChar,
             "to_hex" => BuiltinScalarFunction::ToHex,
             "to_timestamp" => BuiltinScalarFunction::ToTimestamp,
             "now" => BuiltinScalarFunction::Now,
             "translate" => BuiltinScalarFunction::Translate,
             "trim" => BuiltinScalarFunction::Trim,
             "upper" => BuiltinScalarFunction::Upper,
+            "uuid" => BuiltinScalarFunction::Uuid,
             "regexp_match" => BuiltinScalarFunction::RegexpMatch,
             _ => {
                 return Err(DataFusionError::Plan(format!(
@@ -335,6 +555,63 @@ make_utf8_to_return_type!(utf8_to_str_type, DataType::LargeUtf8, DataType::Utf8)
 make_utf8_to_return_type!(utf8_to_int_type, DataType::Int64, DataType::Int32);
 make_utf8_to_return_type!(utf8_to_binary_type, DataType::Binary, DataType::Binary);
 
+/// Returns `return_type` if `arg_types` are a JSON document followed by a path
+/// of keys and indexes, the arguments of the JSON function `fun`
+fn json_to_type(
+    arg_types: &[DataType],
+    fun: &BuiltinScalarFunction,
+    return_type: DataType,
+) -> Result<DataType> {
+    match arg_types.first() {
+        Some(DataType::Utf8) => {}
+        other => {
+            return Err(DataFusionError::Plan(format!(
+                "The function {} expects a Utf8 JSON document, got {:?}",
+                fun, other
+            )))
+        }
+    }
+    for arg_type in &arg_types[1..] {
+        if !matches!(arg_type, DataType::Utf8 | DataType::Int64) {
+            return Err(DataFusionError::Plan(format!(
+                "The function {} expects a path of Utf8 keys and Int64 indexes, got {:?}",
+                fun, arg_type
+            )));
+        }
+    }
+    Ok(return_type)
+}
+
+/// Returns the key and value fields of `arg_type` if it is a map, the first
+/// argument of the map function `fun`
+fn map_to_fields<'a>(
+    arg_type: &'a DataType,
+    fun: &BuiltinScalarFunction,
+) -> Result<(&'a Field, &'a Field)> {
+    map_expressions::map_fields(arg_type).ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "The function {} expects a map, got {:?}",
+            fun, arg_type
+        ))
+    })
+}
+
+/// Returns `return_type` if `arg_type` is a list, the first argument of the
+/// list function `fun`
+fn list_to_type(
+    arg_type: &DataType,
+    fun: &BuiltinScalarFunction,
+    return_type: DataType,
+) -> Result<DataType> {
+    match arg_type {
+        DataType::List(_) | DataType::LargeList(_) => Ok(return_type),
+        other => Err(DataFusionError::Plan(format!(
+            "The function {} expects a list, got {:?}",
+            fun, other
+        ))),
+    }
+}
+
 /// Returns the datatype of the scalar function
 pub fn return_type(
     fun: &BuiltinScalarFunction,
@@ -343,8 +620,16 @@ pub fn return_type(
     // Note that this function *must* return the same type that the respective physical expression returns
     // or the execution panics.
 
+    // functions of dictionary-encoded strings are evaluated on the dictionary values
+    if let Some((key_type, value_types)) = dictionary_value_types(fun, arg_types) {
+        return Ok(dictionary_expressions::dictionary_return_type(
+            key_type,
+            return_type(fun, &value_types)?,
+        ));
+    }
+
     // verify that this is a valid set of data types for this function
-    data_types(&arg_types, &signature(fun))?;
+    data_types(&arg_types, &signature_for(fun, arg_types))?;
 
     // the return type of the built in function.
     // Some built-in functions' return type depends on the incoming type.
@@ -353,6 +638,18 @@ pub fn return_type(
             Box::new(Field::new("item", arg_types[0].clone(), true)),
             arg_types.len() as i32,
         )),
+        BuiltinScalarFunction::ArrayAppend
+        | BuiltinScalarFunction::ArrayConcat
+        | BuiltinScalarFunction::ArraySlice => {
+            list_to_type(&arg_types[0], fun, arg_types[0].clone())
+        }
+        BuiltinScalarFunction::ArrayContains => {
+            list_to_type(&arg_types[0], fun, DataType::Boolean)
+        }
+        BuiltinScalarFunction::ArrayLength | BuiltinScalarFunction::ArrayPosition => {
+            list_to_type(&arg_types[0], fun, DataType::Int64)
+        }
+        BuiltinScalarFunction::ArrowTypeof => Ok(DataType::Utf8),
         BuiltinScalarFunction::Ascii => Ok(DataType::Int32),
         BuiltinScalarFunction::BitLength => utf8_to_int_type(&arg_types[0], "bit_length"),
         BuiltinScalarFunction::Btrim => utf8_to_str_type(&arg_types[0], "btrim"),
@@ -360,23 +657,112 @@ pub fn return_type(
             utf8_to_int_type(&arg_types[0], "character_length")
         }
         BuiltinScalarFunction::Chr => Ok(DataType::Utf8),
+        BuiltinScalarFunction::Coalesce => {
+            // the arguments are coerced to a common type
+            let coerced_types = data_types(arg_types, &signature(fun))?;
+            coerced_types.first().cloned().ok_or_else(|| {
+                DataFusionError::Plan(
+                    "coalesce requires at least one argument".to_string(),
+                )
+            })
+        }
         BuiltinScalarFunction::Concat => Ok(DataType::Utf8),
         BuiltinScalarFunction::ConcatWithSeparator => Ok(DataType::Utf8),
-        BuiltinScalarFunction::DatePart => Ok(DataType::Int32),
-        BuiltinScalarFunction::DateTrunc => {
-            Ok(DataType::Timestamp(TimeUnit::Nanosecond, None))
+        BuiltinScalarFunction::DateBin => match (&arg_types[0], &arg_types[1]) {
+            (DataType::Interval(_), DataType::Timestamp(_, _)) => {
+                Ok(arg_types[1].clone())
+            }
+            (DataType::Interval(_), other) => Err(DataFusionError::Plan(format!(
+                "The second argument of date_bin must be a timestamp, got {:?}",
+                other
+            ))),
+            (other, _) => Err(DataFusionError::Plan(format!(
+                "The first argument of date_bin must be an interval, got {:?}",
+                other
+            ))),
+        },
+        BuiltinScalarFunction::DatePart => Ok(DataType::Int64),
+        BuiltinScalarFunction::DateTrunc => match &arg_types[1] {
+            DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64 => {
+                Ok(arg_types[1].clone())
+            }
+            other => Err(DataFusionError::Plan(format!(
+                "The second argument of date_trunc must be a timestamp or date, got {:?}",
+                other
+            ))),
+        },
+        BuiltinScalarFunction::Decode => Ok(DataType::Binary),
+        BuiltinScalarFunction::ElementAt => {
+            Ok(map_to_fields(&arg_types[0], fun)?.1.data_type().clone())
         }
+        BuiltinScalarFunction::Encode => Ok(DataType::Utf8),
+        BuiltinScalarFunction::FuzzyMatch => Ok(DataType::Boolean),
         BuiltinScalarFunction::InitCap => utf8_to_str_type(&arg_types[0], "initcap"),
+        BuiltinScalarFunction::JsonGet | BuiltinScalarFunction::JsonGetStr => {
+            json_to_type(arg_types, fun, DataType::Utf8)
+        }
+        BuiltinScalarFunction::JsonGetBool => {
+            json_to_type(arg_types, fun, DataType::Boolean)
+        }
+        BuiltinScalarFunction::JsonGetFloat => {
+            json_to_type(arg_types, fun, DataType::Float64)
+        }
+        BuiltinScalarFunction::JsonGetInt => {
+            json_to_type(arg_types, fun, DataType::Int64)
+        }
         BuiltinScalarFunction::Left => utf8_to_str_type(&arg_types[0], "left"),
+        BuiltinScalarFunction::Levenshtein => {
+            utf8_to_int_type(&arg_types[0], "levenshtein")
+        }
         BuiltinScalarFunction::Lower => utf8_to_str_type(&arg_types[0], "lower"),
         BuiltinScalarFunction::Lpad => utf8_to_str_type(&arg_types[0], "lpad"),
+        BuiltinScalarFunction::Overlay => utf8_to_str_type(&arg_types[0], "overlay"),
         BuiltinScalarFunction::Ltrim => utf8_to_str_type(&arg_types[0], "ltrim"),
+        BuiltinScalarFunction::MakeArray => match arg_types.first() {
+            Some(data_type) => Ok(DataType::List(Box::new(Field::new(
+                "item",
+                data_type.clone(),
+                true,
+            )))),
+            None => Err(DataFusionError::Plan(
+                "make_array requires at least one argument".to_string(),
+            )),
+        },
+        BuiltinScalarFunction::MapKeys => {
+            let (key, _) = map_to_fields(&arg_types[0], fun)?;
+            Ok(map_expressions::map_column_type(
+                &arg_types[0],
+                key.data_type(),
+            ))
+        }
+        BuiltinScalarFunction::MapValues => {
+            let (_, value) = map_to_fields(&arg_types[0], fun)?;
+            Ok(map_expressions::map_column_type(
+                &arg_types[0],
+                value.data_type(),
+            ))
+        }
         BuiltinScalarFunction::MD5 => utf8_to_str_type(&arg_types[0], "md5"),
+        BuiltinScalarFunction::MonotonicId => Ok(DataType::Int64),
+        // the field names are literal arguments, see `named_struct_names`
+        BuiltinScalarFunction::NamedStruct => Err(DataFusionError::Internal(
+            "The type of named_struct depends on the values of its field names"
+                .to_string(),
+        )),
         BuiltinScalarFunction::NullIf => {
             // NULLIF has two args and they might get coerced, get a preview of this
             let coerced_types = data_types(arg_types, &signature(fun));
             coerced_types.map(|typs| typs[0].clone())
         }
+        BuiltinScalarFunction::Nvl => {
+            let coerced_types = data_types(arg_types, &signature(fun));
+            coerced_types.map(|typs| typs[0].clone())
+        }
+        BuiltinScalarFunction::Nvl2 => {
+            // the result is of the common type of the second and third argument
+            let coerced_types = data_types(arg_types, &signature_for(fun, arg_types));
+            coerced_types.map(|typs| typs[1].clone())
+        }
         BuiltinScalarFunction::OctetLength => {
             utf8_to_int_type(&arg_types[0], "octet_length")
         }
@@ -394,10 +780,23 @@ pub fn return_type(
         BuiltinScalarFunction::SHA256 => utf8_to_binary_type(&arg_types[0], "sha256"),
         BuiltinScalarFunction::SHA384 => utf8_to_binary_type(&arg_types[0], "sha384"),
         BuiltinScalarFunction::SHA512 => utf8_to_binary_type(&arg_types[0], "sha512"),
+        BuiltinScalarFunction::Soundex => utf8_to_str_type(&arg_types[0], "soundex"),
         BuiltinScalarFunction::SplitPart => utf8_to_str_type(&arg_types[0], "split_part"),
         BuiltinScalarFunction::StartsWith => Ok(DataType::Boolean),
         BuiltinScalarFunction::Strpos => utf8_to_int_type(&arg_types[0], "strpos"),
+        BuiltinScalarFunction::Struct => {
+            if arg_types.is_empty() {
+                return Err(DataFusionError::Plan(
+                    "struct requires at least one argument".to_string(),
+                ));
+            }
+            Ok(struct_expressions::struct_type(
+                &struct_expressions::struct_names(arg_types.len()),
+                arg_types,
+            ))
+        }
         BuiltinScalarFunction::Substr => utf8_to_str_type(&arg_types[0], "substr"),
+        BuiltinScalarFunction::ToChar => Ok(DataType::Utf8),
         BuiltinScalarFunction::ToHex => Ok(match arg_types[0] {
             DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
                 DataType::Utf8
@@ -416,6 +815,7 @@ pub fn return_type(
         BuiltinScalarFunction::Translate => utf8_to_str_type(&arg_types[0], "translate"),
         BuiltinScalarFunction::Trim => utf8_to_str_type(&arg_types[0], "trim"),
         BuiltinScalarFunction::Upper => utf8_to_str_type(&arg_types[0], "upper"),
+        BuiltinScalarFunction::Uuid => Ok(DataType::Utf8),
         BuiltinScalarFunction::RegexpMatch => Ok(match arg_types[0] {
             DataType::LargeUtf8 => {
                 DataType::List(Box::new(Field::new("item", DataType::LargeUtf8, true)))
@@ -431,24 +831,45 @@ pub fn return_type(
             }
         }),
 
-        BuiltinScalarFunction::Abs
-        | BuiltinScalarFunction::Acos
+        BuiltinScalarFunction::Factorial
+        | BuiltinScalarFunction::Gcd
+        | BuiltinScalarFunction::Lcm => Ok(DataType::Int64),
+
+        // decimals keep their scale when rounded to a number of places
+        BuiltinScalarFunction::Abs => match &arg_types[0] {
+            DataType::Decimal(_, _) => Ok(arg_types[0].clone()),
+            _ => Ok(DataType::Float64),
+        },
+        BuiltinScalarFunction::Ceil
+        | BuiltinScalarFunction::Floor
+        | BuiltinScalarFunction::Round
+        | BuiltinScalarFunction::Trunc => match &arg_types[0] {
+            DataType::Decimal(_, _) => math_expressions::decimal_rounding_type(
+                &arg_types[0],
+                arg_types.len() > 1,
+            ),
+            _ => Ok(DataType::Float64),
+        },
+
+        BuiltinScalarFunction::Acos
         | BuiltinScalarFunction::Asin
         | BuiltinScalarFunction::Atan
-        | BuiltinScalarFunction::Ceil
+        | BuiltinScalarFunction::Atan2
+        | BuiltinScalarFunction::Cbrt
         | BuiltinScalarFunction::Cos
+        | BuiltinScalarFunction::Degrees
         | BuiltinScalarFunction::Exp
-        | BuiltinScalarFunction::Floor
         | BuiltinScalarFunction::Log
         | BuiltinScalarFunction::Ln
         | BuiltinScalarFunction::Log10
         | BuiltinScalarFunction::Log2
-        | BuiltinScalarFunction::Round
+        | BuiltinScalarFunction::Pi
+        | BuiltinScalarFunction::Power
+        | BuiltinScalarFunction::Radians
         | BuiltinScalarFunction::Signum
         | BuiltinScalarFunction::Sin
         | BuiltinScalarFunction::Sqrt
-        | BuiltinScalarFunction::Tan
-        | BuiltinScalarFunction::Trunc => Ok(DataType::Float64),
+        | BuiltinScalarFunction::Tan => Ok(DataType::Float64),
     }
 }
 
@@ -472,6 +893,46 @@ macro_rules! invoke_if_crypto_expressions_feature_flag {
     };
 }
 
+#[cfg(feature = "encoding_expressions")]
+macro_rules! invoke_if_encoding_expressions_feature_flag {
+    ($FUNC:ident, $NAME:expr) => {{
+        use crate::physical_plan::encoding_expressions;
+        encoding_expressions::$FUNC
+    }};
+}
+
+#[cfg(not(feature = "encoding_expressions"))]
+macro_rules! invoke_if_encoding_expressions_feature_flag {
+    ($FUNC:ident, $NAME:expr) => {
+        |_: &[ColumnarValue]| -> Result<ColumnarValue> {
+            Err(DataFusionError::Internal(format!(
+                "function {} requires compilation with feature flag: encoding_expressions.",
+                $NAME
+            )))
+        }
+    };
+}
+
+#[cfg(feature = "json_expressions")]
+macro_rules! invoke_if_json_expressions_feature_flag {
+    ($FUNC:ident, $NAME:expr) => {{
+        use crate::physical_plan::json_expressions;
+        json_expressions::$FUNC
+    }};
+}
+
+#[cfg(not(feature = "json_expressions"))]
+macro_rules! invoke_if_json_expressions_feature_flag {
+    ($FUNC:ident, $NAME:expr) => {
+        |_: &[ArrayRef]| -> Result<ArrayRef> {
+            Err(DataFusionError::Internal(format!(
+                "function {} requires compilation with feature flag: json_expressions.",
+                $NAME
+            )))
+        }
+    };
+}
+
 #[cfg(feature = "regex_expressions")]
 macro_rules! invoke_if_regex_expressions_feature_flag {
     ($FUNC:ident, $T:tt, $NAME:expr) => {{
@@ -522,28 +983,190 @@ pub fn create_physical_expr(
 ) -> Result<Arc<dyn PhysicalExpr>> {
     let fun_expr: ScalarFunctionImplementation = Arc::new(match fun {
         // math functions
-        BuiltinScalarFunction::Abs => math_expressions::abs,
+        BuiltinScalarFunction::Abs => |args| match args[0].data_type() {
+            DataType::Decimal(_, _) => {
+                make_scalar_function(math_expressions::decimal_abs)(args)
+            }
+            _ => math_expressions::abs(args),
+        },
         BuiltinScalarFunction::Acos => math_expressions::acos,
         BuiltinScalarFunction::Asin => math_expressions::asin,
         BuiltinScalarFunction::Atan => math_expressions::atan,
-        BuiltinScalarFunction::Ceil => math_expressions::ceil,
+        BuiltinScalarFunction::Atan2 => {
+            |args| make_scalar_function(math_expressions::atan2)(args)
+        }
+        BuiltinScalarFunction::Cbrt => math_expressions::cbrt,
+        BuiltinScalarFunction::Ceil => |args| match args[0].data_type() {
+            DataType::Decimal(_, _) => {
+                make_scalar_function(math_expressions::decimal_ceil)(args)
+            }
+            _ => math_expressions::ceil(args),
+        },
         BuiltinScalarFunction::Cos => math_expressions::cos,
+        BuiltinScalarFunction::Degrees => math_expressions::degrees,
         BuiltinScalarFunction::Exp => math_expressions::exp,
-        BuiltinScalarFunction::Floor => math_expressions::floor,
-        BuiltinScalarFunction::Log => math_expressions::log10,
+        BuiltinScalarFunction::Factorial => {
+            |args| make_scalar_function(math_expressions::factorial)(args)
+        }
+        BuiltinScalarFunction::Floor => |args| match args[0].data_type() {
+            DataType::Decimal(_, _) => {
+                make_scalar_function(math_expressions::decimal_floor)(args)
+            }
+            _ => math_expressions::floor(args),
+        },
+        BuiltinScalarFunction::Gcd => {
+            |args| make_scalar_function(math_expressions::gcd)(args)
+        }
+        BuiltinScalarFunction::Lcm => {
+            |args| make_scalar_function(math_expressions::lcm)(args)
+        }
+        BuiltinScalarFunction::Log => |args| match args.len() {
+            1 => math_expressions::log10(args),
+            _ => make_scalar_function(math_expressions::log)(args),
+        },
         BuiltinScalarFunction::Ln => math_expressions::ln,
         BuiltinScalarFunction::Log10 => math_expressions::log10,
         BuiltinScalarFunction::Log2 => math_expressions::log2,
+        BuiltinScalarFunction::Pi => math_expressions::pi,
+        BuiltinScalarFunction::Power => {
+            |args| make_scalar_function(math_expressions::power)(args)
+        }
+        BuiltinScalarFunction::Radians => math_expressions::radians,
         BuiltinScalarFunction::Random => math_expressions::random,
-        BuiltinScalarFunction::Round => math_expressions::round,
+        BuiltinScalarFunction::Round => |args| match args[0].data_type() {
+            DataType::Decimal(_, _) => {
+                make_scalar_function(math_expressions::decimal_round)(args)
+            }
+            _ => math_expressions::round(args),
+        },
         BuiltinScalarFunction::Signum => math_expressions::signum,
         BuiltinScalarFunction::Sin => math_expressions::sin,
         BuiltinScalarFunction::Sqrt => math_expressions::sqrt,
         BuiltinScalarFunction::Tan => math_expressions::tan,
-        BuiltinScalarFunction::Trunc => math_expressions::trunc,
+        BuiltinScalarFunction::Trunc => |args| match (args[0].data_type(), args.len()) {
+            (DataType::Decimal(_, _), _) => {
+                make_scalar_function(math_expressions::decimal_trunc)(args)
+            }
+            (_, 1) => math_expressions::trunc(args),
+            _ => make_scalar_function(math_expressions::trunc_to_places)(args),
+        },
 
         // string functions
         BuiltinScalarFunction::Array => array_expressions::array,
+        BuiltinScalarFunction::ArrayAppend => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_append::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_append::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_append",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArrayConcat => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_concat::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_concat::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_concat",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArrayContains => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_contains::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_contains::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_contains",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArrayLength => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_length::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_length::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_length",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArrayPosition => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_position::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_position::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_position",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArraySlice => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(array_expressions::array_slice::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(array_expressions::array_slice::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function array_slice",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ElementAt => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(map_expressions::element_at::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(map_expressions::element_at::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function element_at",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::MapKeys => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(map_expressions::map_keys::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(map_expressions::map_keys::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function map_keys",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::MapValues => |args| match args[0].data_type() {
+            DataType::List(_) => {
+                make_scalar_function(map_expressions::map_values::<i32>)(args)
+            }
+            DataType::LargeList(_) => {
+                make_scalar_function(map_expressions::map_values::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function map_values",
+                other,
+            ))),
+        },
+        BuiltinScalarFunction::ArrowTypeof => |args| {
+            Ok(ColumnarValue::Scalar(ScalarValue::Utf8(Some(format!(
+                "{:?}",
+                args[0].data_type()
+            )))))
+        },
         BuiltinScalarFunction::Ascii => |args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::ascii::<i32>)(args)
@@ -605,11 +1228,37 @@ pub fn create_physical_expr(
         BuiltinScalarFunction::Chr => {
             |args| make_scalar_function(string_expressions::chr)(args)
         }
+        BuiltinScalarFunction::Coalesce => {
+            // each argument is only evaluated for the rows whose previous
+            // arguments are null
+            let args = coerce(args, input_schema, &signature(fun))?;
+            return Ok(Arc::new(CoalesceExpr::try_new(args)?));
+        }
         BuiltinScalarFunction::Concat => string_expressions::concat,
         BuiltinScalarFunction::ConcatWithSeparator => {
             |args| make_scalar_function(string_expressions::concat_ws)(args)
         }
+        BuiltinScalarFunction::DateBin => datetime_expressions::date_bin,
+        BuiltinScalarFunction::Decode => {
+            invoke_if_encoding_expressions_feature_flag!(decode, "decode")
+        }
+        BuiltinScalarFunction::Encode => {
+            invoke_if_encoding_expressions_feature_flag!(encode, "encode")
+        }
+        BuiltinScalarFunction::FuzzyMatch => |args| match args[0].data_type() {
+            DataType::Utf8 => {
+                make_scalar_function(string_expressions::fuzzy_match::<i32>)(args)
+            }
+            DataType::LargeUtf8 => {
+                make_scalar_function(string_expressions::fuzzy_match::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function fuzzy_match",
+                other,
+            ))),
+        },
         BuiltinScalarFunction::DatePart => datetime_expressions::date_part,
+        BuiltinScalarFunction::ToChar => formatting_expressions::to_char,
         BuiltinScalarFunction::DateTrunc => datetime_expressions::date_trunc,
         BuiltinScalarFunction::Now => {
             // bind value for now at plan time
@@ -627,6 +1276,35 @@ pub fn create_physical_expr(
                 &return_type(&fun, &arg_types)?,
             )));
         }
+        BuiltinScalarFunction::JsonGet => |args| {
+            make_scalar_function(invoke_if_json_expressions_feature_flag!(
+                json_get, "json_get"
+            ))(args)
+        },
+        BuiltinScalarFunction::JsonGetBool => |args| {
+            make_scalar_function(invoke_if_json_expressions_feature_flag!(
+                json_get_bool,
+                "json_get_bool"
+            ))(args)
+        },
+        BuiltinScalarFunction::JsonGetFloat => |args| {
+            make_scalar_function(invoke_if_json_expressions_feature_flag!(
+                json_get_float,
+                "json_get_float"
+            ))(args)
+        },
+        BuiltinScalarFunction::JsonGetInt => |args| {
+            make_scalar_function(invoke_if_json_expressions_feature_flag!(
+                json_get_int,
+                "json_get_int"
+            ))(args)
+        },
+        BuiltinScalarFunction::JsonGetStr => |args| {
+            make_scalar_function(invoke_if_json_expressions_feature_flag!(
+                json_get_str,
+                "json_get_str"
+            ))(args)
+        },
         BuiltinScalarFunction::InitCap => |args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::initcap::<i32>)(args)
@@ -653,6 +1331,18 @@ pub fn create_physical_expr(
                 other,
             ))),
         },
+        BuiltinScalarFunction::Levenshtein => |args| match args[0].data_type() {
+            DataType::Utf8 => {
+                make_scalar_function(string_expressions::levenshtein::<Int32Type>)(args)
+            }
+            DataType::LargeUtf8 => {
+                make_scalar_function(string_expressions::levenshtein::<Int64Type>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function levenshtein",
+                other,
+            ))),
+        },
         BuiltinScalarFunction::Lower => string_expressions::lower,
         BuiltinScalarFunction::Lpad => |args| match args[0].data_type() {
             DataType::Utf8 => {
@@ -668,6 +1358,22 @@ pub fn create_physical_expr(
                 other,
             ))),
         },
+        BuiltinScalarFunction::Overlay => |args| match args[0].data_type() {
+            DataType::Utf8 => {
+                let func =
+                    invoke_if_unicode_expressions_feature_flag!(overlay, i32, "overlay");
+                make_scalar_function(func)(args)
+            }
+            DataType::LargeUtf8 => {
+                let func =
+                    invoke_if_unicode_expressions_feature_flag!(overlay, i64, "overlay");
+                make_scalar_function(func)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function overlay",
+                other,
+            ))),
+        },
         BuiltinScalarFunction::Ltrim => |args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::ltrim::<i32>)(args)
@@ -683,7 +1389,40 @@ pub fn create_physical_expr(
         BuiltinScalarFunction::MD5 => {
             invoke_if_crypto_expressions_feature_flag!(md5, "md5")
         }
+        BuiltinScalarFunction::MonotonicId => {
+            // the ids are counted per expression and partition
+            return Ok(Arc::new(MonotonicIdExpr::new()));
+        }
+        BuiltinScalarFunction::MakeArray => {
+            |args| make_scalar_function(array_expressions::make_array)(args)
+        }
+        BuiltinScalarFunction::NamedStruct => {
+            // bind the literal field names at plan time, the values are the
+            // only arguments of the physical expression
+            let literals = args
+                .iter()
+                .map(|arg| arg.as_any().downcast_ref::<Literal>().map(|l| l.value()))
+                .collect::<Vec<_>>();
+            let names = struct_expressions::named_struct_names(&literals)?;
+            let args = args.iter().skip(1).step_by(2).cloned().collect::<Vec<_>>();
+            let arg_types = args
+                .iter()
+                .map(|e| e.data_type(input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            let data_type = struct_expressions::struct_type(&names, &arg_types);
+            let fun_expr = make_scalar_function(move |args: &[ArrayRef]| {
+                struct_expressions::named_struct(&names, args)
+            });
+            return Ok(Arc::new(ScalarFunctionExpr::new(
+                &format!("{}", fun),
+                fun_expr,
+                args,
+                &data_type,
+            )));
+        }
         BuiltinScalarFunction::NullIf => nullif_func,
+        BuiltinScalarFunction::Nvl => |args| make_scalar_function(nvl_func)(args),
+        BuiltinScalarFunction::Nvl2 => |args| make_scalar_function(nvl2_func)(args),
         BuiltinScalarFunction::OctetLength => |args| match &args[0] {
             ColumnarValue::Array(v) => Ok(ColumnarValue::Array(length(v.as_ref())?)),
             ColumnarValue::Scalar(v) => match v {
@@ -719,6 +1458,23 @@ pub fn create_physical_expr(
             ))),
         },
         BuiltinScalarFunction::RegexpReplace => |args| match args[0].data_type() {
+            // a literal pattern, replacement and flags only need to be compiled once
+            DataType::Utf8 if has_scalar_pattern(args) => {
+                let func = invoke_if_regex_expressions_feature_flag!(
+                    regexp_replace_static_pattern,
+                    i32,
+                    "regexp_replace"
+                );
+                invoke_with_scalar_pattern(func, args)
+            }
+            DataType::LargeUtf8 if has_scalar_pattern(args) => {
+                let func = invoke_if_regex_expressions_feature_flag!(
+                    regexp_replace_static_pattern,
+                    i64,
+                    "regexp_replace"
+                );
+                invoke_with_scalar_pattern(func, args)
+            }
             DataType::Utf8 => {
                 let func = invoke_if_regex_expressions_feature_flag!(
                     regexp_replace,
@@ -834,6 +1590,18 @@ pub fn create_physical_expr(
         BuiltinScalarFunction::SHA512 => {
             invoke_if_crypto_expressions_feature_flag!(sha512, "sha512")
         }
+        BuiltinScalarFunction::Soundex => |args| match args[0].data_type() {
+            DataType::Utf8 => {
+                make_scalar_function(string_expressions::soundex::<i32>)(args)
+            }
+            DataType::LargeUtf8 => {
+                make_scalar_function(string_expressions::soundex::<i64>)(args)
+            }
+            other => Err(DataFusionError::Internal(format!(
+                "Unsupported data type {:?} for function soundex",
+                other,
+            ))),
+        },
         BuiltinScalarFunction::SplitPart => |args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::split_part::<i32>)(args)
@@ -858,6 +1626,9 @@ pub fn create_physical_expr(
                 other,
             ))),
         },
+        BuiltinScalarFunction::Struct => {
+            |args| make_scalar_function(struct_expressions::struct_expr)(args)
+        }
         BuiltinScalarFunction::Strpos => |args| match args[0].data_type() {
             DataType::Utf8 => {
                 let func = invoke_if_unicode_expressions_feature_flag!(
@@ -940,9 +1711,44 @@ pub fn create_physical_expr(
             ))),
         },
         BuiltinScalarFunction::Upper => string_expressions::upper,
+        BuiltinScalarFunction::Uuid => string_expressions::uuid,
     });
     // coerce
-    let args = coerce(args, input_schema, &signature(fun))?;
+    let current_types = args
+        .iter()
+        .map(|e| e.data_type(input_schema))
+        .collect::<Result<Vec<_>>>()?;
+
+    // a dictionary-encoded first argument is passed as is, its values are
+    // coerced when the function is evaluated on them
+    if let Some((_, value_types)) = dictionary_value_types(fun, &current_types) {
+        let new_types = data_types(&value_types, &signature_for(fun, &value_types))?;
+        let args = args
+            .iter()
+            .zip(new_types.iter())
+            .enumerate()
+            .map(|(i, (arg, new_type))| match i {
+                0 => Ok(arg.clone()),
+                _ => try_cast(arg.clone(), input_schema, new_type.clone()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut arg_types = new_types.clone();
+        arg_types[0] = current_types[0].clone();
+        let data_type = return_type(fun, &arg_types)?;
+        let fun_expr = dictionary_expressions::dictionary_function(
+            fun_expr,
+            new_types[0].clone(),
+            data_type.clone(),
+        );
+        return Ok(Arc::new(ScalarFunctionExpr::new(
+            &format!("{}", fun),
+            fun_expr,
+            args,
+            &data_type,
+        )));
+    }
+
+    let args = coerce(args, input_schema, &signature_for(fun, &current_types))?;
 
     let arg_types = args
         .iter()
@@ -957,18 +1763,93 @@ pub fn create_physical_expr(
     )))
 }
 
+/// Returns the key type of a dictionary of strings that is the first of
+/// `arg_types` and `arg_types` with the dictionary's value type in its place,
+/// if `fun` supports dictionaries
+fn dictionary_value_types<'a>(
+    fun: &BuiltinScalarFunction,
+    arg_types: &'a [DataType],
+) -> Option<(&'a DataType, Vec<DataType>)> {
+    match arg_types.first() {
+        Some(DataType::Dictionary(key_type, value_type))
+            if fun.supports_dictionary()
+                && matches!(
+                    value_type.as_ref(),
+                    DataType::Utf8 | DataType::LargeUtf8
+                ) =>
+        {
+            let mut value_types = arg_types.to_vec();
+            value_types[0] = value_type.as_ref().clone();
+            Some((key_type.as_ref(), value_types))
+        }
+        _ => None,
+    }
+}
+
+/// the signatures supported by the function `fun` for arguments of `arg_types`.
+/// Decimals are parameterized by their precision and scale and can not be listed
+/// in [`signature`], so the rounding functions accept the decimal they are given.
+/// Likewise, the first argument of `nvl2` may be of any type.
+fn signature_for(fun: &BuiltinScalarFunction, arg_types: &[DataType]) -> Signature {
+    if let (BuiltinScalarFunction::Nvl2, [checked, _, _]) = (fun, arg_types) {
+        return Signature::OneOf(
+            SUPPORTED_NVL_TYPES
+                .iter()
+                .map(|t| Signature::Exact(vec![checked.clone(), t.clone(), t.clone()]))
+                .collect(),
+        );
+    }
+    match arg_types.first() {
+        Some(decimal @ DataType::Decimal(_, _)) => match fun {
+            BuiltinScalarFunction::Abs
+            | BuiltinScalarFunction::Ceil
+            | BuiltinScalarFunction::Floor => Signature::Exact(vec![decimal.clone()]),
+            BuiltinScalarFunction::Round | BuiltinScalarFunction::Trunc => {
+                Signature::OneOf(vec![
+                    Signature::Exact(vec![decimal.clone()]),
+                    Signature::Exact(vec![decimal.clone(), DataType::Int64]),
+                ])
+            }
+            _ => signature(fun),
+        },
+        _ => signature(fun),
+    }
+}
+
 /// the signatures supported by the function `fun`.
 fn signature(fun: &BuiltinScalarFunction) -> Signature {
     // note: the physical expression must accept the type returned by this function or the execution panics.
 
     // for now, the list is small, as we do not have many built-in functions.
     match fun {
-        BuiltinScalarFunction::Array => {
+        BuiltinScalarFunction::Array | BuiltinScalarFunction::MakeArray => {
             Signature::Variadic(array_expressions::SUPPORTED_ARRAY_TYPES.to_vec())
         }
+        // the field names of `named_struct` are checked by `named_struct_names`
+        BuiltinScalarFunction::JsonGet
+        | BuiltinScalarFunction::JsonGetBool
+        | BuiltinScalarFunction::JsonGetFloat
+        | BuiltinScalarFunction::JsonGetInt
+        | BuiltinScalarFunction::JsonGetStr
+        | BuiltinScalarFunction::NamedStruct
+        | BuiltinScalarFunction::Struct => Signature::VariadicAny,
         BuiltinScalarFunction::Concat | BuiltinScalarFunction::ConcatWithSeparator => {
             Signature::Variadic(vec![DataType::Utf8])
         }
+        // the list functions accept lists of any item type, checked by `return_type`
+        BuiltinScalarFunction::ArrayLength => Signature::Any(1),
+        BuiltinScalarFunction::ArrayAppend
+        | BuiltinScalarFunction::ArrayContains
+        | BuiltinScalarFunction::ArrayPosition => Signature::Any(2),
+        BuiltinScalarFunction::ArrayConcat => Signature::VariadicEqual,
+        BuiltinScalarFunction::ArraySlice => Signature::Any(3),
+        BuiltinScalarFunction::ArrowTypeof => Signature::Any(1),
+        // the map functions accept maps of any key and value types, checked by
+        // `return_type`
+        BuiltinScalarFunction::MapKeys | BuiltinScalarFunction::MapValues => {
+            Signature::Any(1)
+        }
+        BuiltinScalarFunction::ElementAt => Signature::Any(2),
         BuiltinScalarFunction::Ascii
         | BuiltinScalarFunction::BitLength
         | BuiltinScalarFunction::CharacterLength
@@ -981,6 +1862,7 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
         | BuiltinScalarFunction::SHA256
         | BuiltinScalarFunction::SHA384
         | BuiltinScalarFunction::SHA512
+        | BuiltinScalarFunction::Soundex
         | BuiltinScalarFunction::Trim
         | BuiltinScalarFunction::Upper => {
             Signature::Uniform(1, vec![DataType::Utf8, DataType::LargeUtf8])
@@ -994,6 +1876,13 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
         BuiltinScalarFunction::Chr | BuiltinScalarFunction::ToHex => {
             Signature::Uniform(1, vec![DataType::Int64])
         }
+        BuiltinScalarFunction::Decode | BuiltinScalarFunction::Encode => {
+            Signature::OneOf(vec![
+                Signature::Exact(vec![DataType::Utf8, DataType::Utf8]),
+                Signature::Exact(vec![DataType::LargeUtf8, DataType::Utf8]),
+                Signature::Exact(vec![DataType::Binary, DataType::Utf8]),
+            ])
+        }
         BuiltinScalarFunction::Lpad | BuiltinScalarFunction::Rpad => {
             Signature::OneOf(vec![
                 Signature::Exact(vec![DataType::Utf8, DataType::Int64]),
@@ -1022,11 +1911,25 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
             Signature::Exact(vec![DataType::Utf8, DataType::Int64]),
             Signature::Exact(vec![DataType::LargeUtf8, DataType::Int64]),
         ]),
-        BuiltinScalarFunction::ToTimestamp => Signature::Uniform(1, vec![DataType::Utf8]),
-        BuiltinScalarFunction::DateTrunc => Signature::Exact(vec![
-            DataType::Utf8,
-            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        BuiltinScalarFunction::Levenshtein => Signature::OneOf(vec![
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8]),
+            Signature::Exact(vec![DataType::LargeUtf8, DataType::LargeUtf8]),
+        ]),
+        BuiltinScalarFunction::FuzzyMatch => Signature::OneOf(vec![
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Int64]),
+            Signature::Exact(vec![
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::Int64,
+            ]),
         ]),
+        BuiltinScalarFunction::ToTimestamp => Signature::Uniform(1, vec![DataType::Utf8]),
+        // timestamps of any unit and timezone are accepted, see `return_type`
+        BuiltinScalarFunction::DateTrunc => Signature::Any(2),
+        // the origin is optional, see `return_type` for the accepted types
+        BuiltinScalarFunction::DateBin => {
+            Signature::OneOf(vec![Signature::Any(2), Signature::Any(3)])
+        }
         BuiltinScalarFunction::DatePart => Signature::OneOf(vec![
             Signature::Exact(vec![DataType::Utf8, DataType::Date32]),
             Signature::Exact(vec![DataType::Utf8, DataType::Date64]),
@@ -1047,6 +1950,28 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                 DataType::Timestamp(TimeUnit::Nanosecond, None),
             ]),
         ]),
+        BuiltinScalarFunction::ToChar => Signature::OneOf(vec![
+            Signature::Exact(vec![DataType::Int64, DataType::Utf8]),
+            Signature::Exact(vec![DataType::Float64, DataType::Utf8]),
+            Signature::Exact(vec![DataType::Date32, DataType::Utf8]),
+            Signature::Exact(vec![DataType::Date64, DataType::Utf8]),
+            Signature::Exact(vec![
+                DataType::Timestamp(TimeUnit::Second, None),
+                DataType::Utf8,
+            ]),
+            Signature::Exact(vec![
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                DataType::Utf8,
+            ]),
+            Signature::Exact(vec![
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                DataType::Utf8,
+            ]),
+            Signature::Exact(vec![
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                DataType::Utf8,
+            ]),
+        ]),
         BuiltinScalarFunction::SplitPart => Signature::OneOf(vec![
             Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Int64]),
             Signature::Exact(vec![DataType::LargeUtf8, DataType::Utf8, DataType::Int64]),
@@ -1067,6 +1992,26 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
             ])
         }
 
+        BuiltinScalarFunction::Overlay => Signature::OneOf(vec![
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Int64]),
+            Signature::Exact(vec![
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::Int64,
+            ]),
+            Signature::Exact(vec![
+                DataType::Utf8,
+                DataType::Utf8,
+                DataType::Int64,
+                DataType::Int64,
+            ]),
+            Signature::Exact(vec![
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::Int64,
+                DataType::Int64,
+            ]),
+        ]),
         BuiltinScalarFunction::Substr => Signature::OneOf(vec![
             Signature::Exact(vec![DataType::Utf8, DataType::Int64]),
             Signature::Exact(vec![DataType::LargeUtf8, DataType::Int64]),
@@ -1083,24 +2028,59 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
         }
         BuiltinScalarFunction::RegexpReplace => Signature::OneOf(vec![
             Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Utf8]),
+            Signature::Exact(vec![
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+            ]),
             Signature::Exact(vec![
                 DataType::Utf8,
                 DataType::Utf8,
                 DataType::Utf8,
                 DataType::Utf8,
             ]),
+            Signature::Exact(vec![
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+                DataType::LargeUtf8,
+            ]),
         ]),
 
         BuiltinScalarFunction::NullIf => {
             Signature::Uniform(2, SUPPORTED_NULLIF_TYPES.to_vec())
         }
+        BuiltinScalarFunction::Nvl => Signature::Uniform(2, SUPPORTED_NVL_TYPES.to_vec()),
+        BuiltinScalarFunction::Coalesce => {
+            Signature::Variadic(SUPPORTED_NVL_TYPES.to_vec())
+        }
+        // the first argument may be of any type, see `signature_for`
+        BuiltinScalarFunction::Nvl2 => Signature::Any(3),
         BuiltinScalarFunction::RegexpMatch => Signature::OneOf(vec![
             Signature::Exact(vec![DataType::Utf8, DataType::Utf8]),
             Signature::Exact(vec![DataType::LargeUtf8, DataType::Utf8]),
             Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Utf8]),
             Signature::Exact(vec![DataType::LargeUtf8, DataType::Utf8, DataType::Utf8]),
         ]),
-        BuiltinScalarFunction::Random => Signature::Exact(vec![]),
+        BuiltinScalarFunction::MonotonicId
+        | BuiltinScalarFunction::Pi
+        | BuiltinScalarFunction::Random
+        | BuiltinScalarFunction::Uuid => Signature::Exact(vec![]),
+        BuiltinScalarFunction::Atan2 | BuiltinScalarFunction::Power => {
+            Signature::Uniform(2, vec![DataType::Float64])
+        }
+        BuiltinScalarFunction::Log => Signature::OneOf(vec![
+            Signature::Uniform(1, vec![DataType::Float64, DataType::Float32]),
+            Signature::Uniform(2, vec![DataType::Float64]),
+        ]),
+        BuiltinScalarFunction::Trunc => Signature::OneOf(vec![
+            Signature::Uniform(1, vec![DataType::Float64, DataType::Float32]),
+            Signature::Exact(vec![DataType::Float64, DataType::Int64]),
+        ]),
+        BuiltinScalarFunction::Factorial => Signature::Uniform(1, vec![DataType::Int64]),
+        BuiltinScalarFunction::Gcd | BuiltinScalarFunction::Lcm => {
+            Signature::Uniform(2, vec![DataType::Int64])
+        }
         // math expressions expect 1 argument of type f64 or f32
         // priority is given to f64 because e.g. `sqrt(1i32)` is in IR (real numbers) and thus we
         // return the best approximation for it (in f64).
@@ -1264,6 +2244,31 @@ where
     })
 }
 
+/// Returns true if `args[0]` is an array and all other arguments are scalars,
+/// e.g. the literal pattern of a regular expression function
+fn has_scalar_pattern(args: &[ColumnarValue]) -> bool {
+    matches!(args[0], ColumnarValue::Array(_))
+        && args[1..]
+            .iter()
+            .all(|arg| matches!(arg, ColumnarValue::Scalar(_)))
+}
+
+/// Invokes `inner` with the array `args[0]` and the remaining scalar arguments
+/// as arrays of length one, see [`has_scalar_pattern`]
+fn invoke_with_scalar_pattern<F>(
+    inner: F,
+    args: &[ColumnarValue],
+) -> Result<ColumnarValue>
+where
+    F: Fn(&[ArrayRef]) -> Result<ArrayRef>,
+{
+    let args = args
+        .iter()
+        .map(|arg| arg.clone().into_array(1))
+        .collect::<Vec<_>>();
+    inner(&args).map(ColumnarValue::Array)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1709,6 +2714,44 @@ mod tests {
             Float64,
             Float64Array
         );
+        test_function!(
+            FuzzyMatch,
+            &[
+                lit(ScalarValue::Utf8(Some("kitten".to_string()))),
+                lit(ScalarValue::Utf8(Some("sitting".to_string()))),
+                lit(ScalarValue::Int64(Some(2))),
+            ],
+            Ok(Some(false)),
+            bool,
+            Boolean,
+            BooleanArray
+        );
+        test_function!(
+            FuzzyMatch,
+            &[
+                lit(ScalarValue::Utf8(Some("kitten".to_string()))),
+                lit(ScalarValue::Utf8(Some("sitting".to_string()))),
+                lit(ScalarValue::Int64(Some(3))),
+            ],
+            Ok(Some(true)),
+            bool,
+            Boolean,
+            BooleanArray
+        );
+        test_function!(
+            FuzzyMatch,
+            &[
+                lit(ScalarValue::Utf8(Some("kitten".to_string()))),
+                lit(ScalarValue::Utf8(Some("sitting".to_string()))),
+                lit(ScalarValue::Int64(Some(-1))),
+            ],
+            Err(DataFusionError::Execution(
+                "max_distance must not be negative".to_string(),
+            )),
+            bool,
+            Boolean,
+            BooleanArray
+        );
         test_function!(
             InitCap,
             &[lit(ScalarValue::Utf8(Some("hi THOMAS".to_string())))],
@@ -1863,6 +2906,39 @@ mod tests {
             Utf8,
             StringArray
         );
+        test_function!(
+            Levenshtein,
+            &[
+                lit(ScalarValue::Utf8(Some("kitten".to_string()))),
+                lit(ScalarValue::Utf8(Some("sitting".to_string()))),
+            ],
+            Ok(Some(3)),
+            i32,
+            Int32,
+            Int32Array
+        );
+        test_function!(
+            Levenshtein,
+            &[
+                lit(ScalarValue::Utf8(Some("josé".to_string()))),
+                lit(ScalarValue::Utf8(Some("jose".to_string()))),
+            ],
+            Ok(Some(1)),
+            i32,
+            Int32,
+            Int32Array
+        );
+        test_function!(
+            Levenshtein,
+            &[
+                lit(ScalarValue::Utf8(Some("kitten".to_string()))),
+                lit(ScalarValue::Utf8(None)),
+            ],
+            Ok(None),
+            i32,
+            Int32,
+            Int32Array
+        );
         #[cfg(feature = "unicode_expressions")]
         test_function!(
             Lpad,
@@ -2960,6 +4036,38 @@ mod tests {
             Binary,
             BinaryArray
         );
+        test_function!(
+            Soundex,
+            &[lit(ScalarValue::Utf8(Some("Robert".to_string())))],
+            Ok(Some("R163")),
+            &str,
+            Utf8,
+            StringArray
+        );
+        test_function!(
+            Soundex,
+            &[lit(ScalarValue::Utf8(Some("Tymczak".to_string())))],
+            Ok(Some("T522")),
+            &str,
+            Utf8,
+            StringArray
+        );
+        test_function!(
+            Soundex,
+            &[lit(ScalarValue::Utf8(Some("123".to_string())))],
+            Ok(Some("")),
+            &str,
+            Utf8,
+            StringArray
+        );
+        test_function!(
+            Soundex,
+            &[lit(ScalarValue::Utf8(None))],
+            Ok(None),
+            &str,
+            Utf8,
+            StringArray
+        );
         test_function!(
             SplitPart,
             &[
@@ -3319,6 +4427,48 @@ mod tests {
             StringArray
         );
         #[cfg(feature = "unicode_expressions")]
+        test_function!(
+            Overlay,
+            &[
+                lit(ScalarValue::Utf8(Some("Txxxxas".to_string()))),
+                lit(ScalarValue::Utf8(Some("hom".to_string()))),
+                lit(ScalarValue::Int64(Some(2))),
+                lit(ScalarValue::Int64(Some(4))),
+            ],
+            Ok(Some("Thomas")),
+            &str,
+            Utf8,
+            StringArray
+        );
+        #[cfg(feature = "unicode_expressions")]
+        test_function!(
+            Overlay,
+            &[
+                lit(ScalarValue::Utf8(Some("joséésoj".to_string()))),
+                lit(ScalarValue::Utf8(Some("XY".to_string()))),
+                lit(ScalarValue::Int64(Some(4))),
+            ],
+            Ok(Some("josXYsoj")),
+            &str,
+            Utf8,
+            StringArray
+        );
+        #[cfg(feature = "unicode_expressions")]
+        test_function!(
+            Overlay,
+            &[
+                lit(ScalarValue::Utf8(Some("alphabet".to_string()))),
+                lit(ScalarValue::Utf8(Some("x".to_string()))),
+                lit(ScalarValue::Int64(Some(0))),
+            ],
+            Err(DataFusionError::Execution(
+                "overlay start position must be positive".to_string(),
+            )),
+            &str,
+            Utf8,
+            StringArray
+        );
+        #[cfg(feature = "unicode_expressions")]
         test_function!(
             Translate,
             &[
@@ -3457,6 +4607,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_uuid() -> Result<()> {
+        let ctx_state = ExecutionContextState::new();
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+
+        let expr =
+            create_physical_expr(&BuiltinScalarFunction::Uuid, &[], &schema, &ctx_state)?;
+        assert_eq!(expr.data_type(&schema)?, DataType::Utf8);
+
+        let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1, 2, 3]))];
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<StringArray>().unwrap();
+
+        // every row gets its own uuid
+        let values = result
+            .iter()
+            .map(|v| v.unwrap().to_string())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(values.len(), 3);
+        assert!(values.iter().all(|v| v.len() == 36));
+        Ok(())
+    }
+
     #[test]
     fn test_concat_error() -> Result<()> {
         let ctx_state = ExecutionContextState::new();
@@ -3552,6 +4726,70 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_make_array_and_named_struct() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::UInt32, false),
+            Field::new("b", DataType::UInt64, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from(vec![1u32, 2])),
+            Arc::new(UInt64Array::from(vec![3u64, 4])),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+        let ctx_state = ExecutionContextState::new();
+
+        let expr = create_physical_expr(
+            &BuiltinScalarFunction::MakeArray,
+            &[col("a"), col("b")],
+            &schema,
+            &ctx_state,
+        )?;
+        assert_eq!(
+            expr.data_type(&schema)?,
+            DataType::List(Box::new(Field::new("item", DataType::UInt64, true)))
+        );
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(
+            format!("{:?}", result.value(1)),
+            "PrimitiveArray<UInt64>\n[\n  2,\n  4,\n]"
+        );
+
+        let expr = create_physical_expr(
+            &BuiltinScalarFunction::NamedStruct,
+            &[
+                lit(ScalarValue::from("x")),
+                col("a"),
+                lit(ScalarValue::from("y")),
+                col("b"),
+            ],
+            &schema,
+            &ctx_state,
+        )?;
+        assert_eq!(
+            expr.data_type(&schema)?,
+            DataType::Struct(vec![
+                Field::new("x", DataType::UInt32, true),
+                Field::new("y", DataType::UInt64, true),
+            ])
+        );
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        assert_eq!(result.data_type(), &expr.data_type(&schema)?);
+        assert_eq!(result.len(), 2);
+
+        // the field names must be literals
+        let result = create_physical_expr(
+            &BuiltinScalarFunction::NamedStruct,
+            &[col("a"), col("b")],
+            &schema,
+            &ctx_state,
+        );
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "regex_expressions")]
     fn test_regexp_match() -> Result<()> {
@@ -0,0 +1,565 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the gap filling operator, which adds the rows of the time buckets
+//! that are missing from the output of a time series aggregation
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::group_scalar::GroupByScalar;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+};
+use crate::scalar::ScalarValue;
+use arrow::array::{new_null_array, Array, ArrayRef, Int64Array};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+
+use futures::stream::{Stream, StreamExt};
+
+/// How the other columns of the rows filling missing buckets are set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillStrategy {
+    /// All other columns are null
+    Null,
+    /// Numeric columns are zero, all other columns are null
+    Zero,
+}
+
+/// GapFillExec densifies the output of a time series aggregation. The time
+/// column is divided into the buckets `range.start`, `range.start + stride`,
+/// ... up to `range.end` (exclusive), and for every group of the input
+/// without rows in a bucket, a row with the group's values and the start of
+/// the bucket is added after the input's rows.
+#[derive(Debug)]
+pub struct GapFillExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The indices of the columns identifying a series
+    group_columns: Vec<usize>,
+    /// The index of the time column
+    time_column: usize,
+    /// The range of the buckets
+    range: Range<i64>,
+    /// The width of the buckets
+    stride: i64,
+    /// How the other columns of added rows are set
+    fill: FillStrategy,
+    /// The output schema, in which the other columns are nullable
+    schema: SchemaRef,
+}
+
+impl GapFillExec {
+    /// Create a GapFillExec filling the buckets of `stride` in `range` of the
+    /// column `time_column` for each group of `group_columns` of `input`
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        group_columns: Vec<usize>,
+        time_column: usize,
+        range: Range<i64>,
+        stride: i64,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        if stride <= 0 {
+            return Err(DataFusionError::Plan(format!(
+                "Gap filling requires a positive stride, got {}",
+                stride
+            )));
+        }
+        if let Some(i) = group_columns
+            .iter()
+            .chain(std::iter::once(&time_column))
+            .find(|i| **i >= input_schema.fields().len())
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Gap filling column {} is out of bounds",
+                i
+            )));
+        }
+        if group_columns.contains(&time_column) {
+            return Err(DataFusionError::Plan(
+                "The time column of gap filling can not be a group column".to_string(),
+            ));
+        }
+        match input_schema.field(time_column).data_type() {
+            DataType::Int32
+            | DataType::Int64
+            | DataType::Date64
+            | DataType::Timestamp(_, _) => {}
+            other => {
+                return Err(DataFusionError::Plan(format!(
+                    "Gap filling is not supported for time columns of type {:?}",
+                    other
+                )))
+            }
+        }
+
+        let fields = input_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if i == time_column || group_columns.contains(&i) {
+                    field.clone()
+                } else {
+                    Field::new(field.name(), field.data_type().clone(), true)
+                }
+            })
+            .collect();
+        Ok(Self {
+            input,
+            group_columns,
+            time_column,
+            range,
+            stride,
+            fill: FillStrategy::Null,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// Customize how the other columns of the added rows are set, which are
+    /// null by default
+    pub fn with_fill(mut self, fill: FillStrategy) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The indices of the columns identifying a series
+    pub fn group_columns(&self) -> &[usize] {
+        &self.group_columns
+    }
+
+    /// The index of the time column
+    pub fn time_column(&self) -> usize {
+        self.time_column
+    }
+
+    /// The range of the buckets
+    pub fn range(&self) -> &Range<i64> {
+        &self.range
+    }
+
+    /// The width of the buckets
+    pub fn stride(&self) -> i64 {
+        self.stride
+    }
+
+    /// How the other columns of added rows are set
+    pub fn fill(&self) -> FillStrategy {
+        self.fill
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for GapFillExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        // the groups of all rows need to be known to find the missing buckets
+        Distribution::SinglePartition
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(
+                GapFillExec::try_new(
+                    children[0].clone(),
+                    self.group_columns.clone(),
+                    self.time_column,
+                    self.range.clone(),
+                    self.stride,
+                )?
+                .with_fill(self.fill),
+            )),
+            _ => Err(DataFusionError::Internal(
+                "GapFillExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "GapFillExec invalid partition {}",
+                partition
+            )));
+        }
+
+        // GapFillExec requires a single input partition
+        if 1 != self.input.output_partitioning().partition_count() {
+            return Err(DataFusionError::Internal(
+                "GapFillExec requires a single input partition".to_owned(),
+            ));
+        }
+
+        let num_buckets =
+            ((self.range.end - self.range.start).max(0) + self.stride - 1) / self.stride;
+        let mut buckets = Buckets {
+            group_columns: self.group_columns.clone(),
+            time_column: self.time_column,
+            range: self.range.clone(),
+            stride: self.stride,
+            num_buckets: num_buckets as usize,
+            groups: vec![],
+            group_indices: HashMap::new(),
+            seen: vec![],
+        };
+        if self.group_columns.is_empty() {
+            // without group columns, all buckets are filled even for an
+            // empty input
+            buckets.group_index(vec![])?;
+        }
+
+        Ok(Box::pin(GapFillStream {
+            schema: self.schema.clone(),
+            input: Some(self.input.execute(0).await?),
+            buckets,
+            fill: self.fill,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let groups = self
+                    .group_columns
+                    .iter()
+                    .map(|i| self.schema.field(*i).name().as_str())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "GapFillExec: groups=[{}], time={}, range={:?}, stride={}, fill={:?}",
+                    groups.join(", "),
+                    self.schema.field(self.time_column).name(),
+                    self.range,
+                    self.stride,
+                    self.fill
+                )
+            }
+        }
+    }
+}
+
+/// The buckets each group of the input has rows in
+struct Buckets {
+    group_columns: Vec<usize>,
+    time_column: usize,
+    range: Range<i64>,
+    stride: i64,
+    num_buckets: usize,
+    /// The values of the group columns of each group, in order of appearance
+    groups: Vec<Vec<ScalarValue>>,
+    /// The index of each group in `groups`, null values are `None`
+    group_indices: HashMap<Vec<Option<GroupByScalar>>, usize>,
+    /// Whether each group has rows in each bucket
+    seen: Vec<Vec<bool>>,
+}
+
+impl Buckets {
+    /// Returns the index of the group of `values`, adding the group if it is
+    /// new
+    fn group_index(&mut self, values: Vec<ScalarValue>) -> Result<usize> {
+        let key = values
+            .iter()
+            .map(|value| {
+                if value.is_null() {
+                    Ok(None)
+                } else {
+                    GroupByScalar::try_from(value).map(Some)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(index) = self.group_indices.get(&key) {
+            return Ok(*index);
+        }
+        let index = self.groups.len();
+        self.group_indices.insert(key, index);
+        self.groups.push(values);
+        self.seen.push(vec![false; self.num_buckets]);
+        Ok(index)
+    }
+
+    /// Marks the buckets of the rows of `batch` as seen
+    fn update(&mut self, batch: &RecordBatch) -> Result<()> {
+        let times = cast(batch.column(self.time_column), &DataType::Int64)?;
+        let times = times.as_any().downcast_ref::<Int64Array>().unwrap();
+        for row in 0..batch.num_rows() {
+            let values = self
+                .group_columns
+                .iter()
+                .map(|i| ScalarValue::try_from_array(batch.column(*i), row))
+                .collect::<Result<Vec<_>>>()?;
+            let group = self.group_index(values)?;
+            if times.is_null(row) {
+                continue;
+            }
+            let time = times.value(row);
+            if self.range.contains(&time) {
+                let bucket = ((time - self.range.start) / self.stride) as usize;
+                self.seen[group][bucket] = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the rows of the buckets missing from each group, if any
+    fn missing_rows(
+        &self,
+        schema: &SchemaRef,
+        fill: FillStrategy,
+    ) -> Result<Option<RecordBatch>> {
+        let mut groups = vec![];
+        let mut times = vec![];
+        for (group, seen) in self.seen.iter().enumerate() {
+            for (bucket, _) in seen.iter().enumerate().filter(|(_, seen)| !**seen) {
+                groups.push(group);
+                times.push(self.range.start + bucket as i64 * self.stride);
+            }
+        }
+        if groups.is_empty() {
+            return Ok(None);
+        }
+
+        let num_rows = groups.len();
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if i == self.time_column {
+                    let times: ArrayRef = Arc::new(Int64Array::from(times.clone()));
+                    return Ok(cast(&times, field.data_type())?);
+                }
+                if let Some(position) = self.group_columns.iter().position(|c| *c == i) {
+                    return ScalarValue::iter_to_array(
+                        groups
+                            .iter()
+                            .map(|group| self.groups[*group][position].clone()),
+                    );
+                }
+                fill_array(field.data_type(), num_rows, fill)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(RecordBatch::try_new(schema.clone(), columns)?))
+    }
+}
+
+/// Creates an array of `num_rows` values of `data_type` for the other
+/// columns of the added rows
+fn fill_array(
+    data_type: &DataType,
+    num_rows: usize,
+    fill: FillStrategy,
+) -> Result<ArrayRef> {
+    let is_numeric = matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+    );
+    match fill {
+        FillStrategy::Zero if is_numeric => {
+            let zeros: ArrayRef = Arc::new(Int64Array::from(vec![0; num_rows]));
+            Ok(cast(&zeros, data_type)?)
+        }
+        _ => Ok(new_null_array(data_type, num_rows)),
+    }
+}
+
+struct GapFillStream {
+    schema: SchemaRef,
+    /// The input, until it is exhausted
+    input: Option<SendableRecordBatchStream>,
+    buckets: Buckets,
+    fill: FillStrategy,
+}
+
+impl Stream for GapFillStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let input = match self.input.as_mut() {
+            Some(input) => input,
+            None => return Poll::Ready(None),
+        };
+        match input.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(batch))) => {
+                let result = self
+                    .buckets
+                    .update(&batch)
+                    .and_then(|_| {
+                        let columns = batch.columns().to_vec();
+                        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+                    })
+                    .map_err(DataFusionError::into_arrow_external_error);
+                Poll::Ready(Some(result))
+            }
+            Poll::Ready(None) => {
+                self.input = None;
+                let missing = self
+                    .buckets
+                    .missing_rows(&self.schema, self.fill)
+                    .map_err(DataFusionError::into_arrow_external_error)
+                    .transpose();
+                Poll::Ready(missing)
+            }
+            other => other,
+        }
+    }
+}
+
+impl RecordBatchStream for GapFillStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::array::StringArray;
+
+    fn input() -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("series", DataType::Utf8, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("value", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "a", "b", "b"])),
+                Arc::new(Int64Array::from(vec![0, 20, 10, 45])),
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            ],
+        )?;
+        Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?))
+    }
+
+    #[tokio::test]
+    async fn gap_fill_groups() -> Result<()> {
+        let gap_fill = GapFillExec::try_new(input()?, vec![0], 1, 0..40, 10)?
+            .with_fill(FillStrategy::Zero);
+        assert!(gap_fill.schema().field(2).is_nullable());
+
+        let batches = common::collect(gap_fill.execute(0).await?).await?;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].num_rows(), 4);
+
+        // the rows outside of the range are kept, but do not fill buckets
+        let missing = &batches[1];
+        let series = missing
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(series, &StringArray::from(vec!["a", "a", "b", "b", "b"]));
+        let times = missing
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(times, &Int64Array::from(vec![10, 30, 0, 20, 30]));
+        let values = missing
+            .column(2)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(values, &Int64Array::from(vec![0; 5]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn gap_fill_without_groups() -> Result<()> {
+        let gap_fill = GapFillExec::try_new(input()?, vec![], 1, 0..60, 20)?;
+        assert_eq!(gap_fill.fill(), FillStrategy::Null);
+
+        // the buckets of all series are merged
+        let batches = common::collect(gap_fill.execute(0).await?).await?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 4);
+
+        let gap_fill = GapFillExec::try_new(input()?, vec![], 1, 0..80, 20)?;
+        let batches = common::collect(gap_fill.execute(0).await?).await?;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].num_rows(), 1);
+        assert!(batches[1].column(0).is_null(0));
+        assert!(batches[1].column(2).is_null(0));
+        Ok(())
+    }
+
+    #[test]
+    fn gap_fill_invalid() -> Result<()> {
+        assert!(GapFillExec::try_new(input()?, vec![0], 1, 0..40, 0).is_err());
+        assert!(GapFillExec::try_new(input()?, vec![1], 1, 0..40, 10).is_err());
+        assert!(GapFillExec::try_new(input()?, vec![0], 0, 0..40, 10).is_err());
+        assert!(GapFillExec::try_new(input()?, vec![3], 1, 0..40, 10).is_err());
+        Ok(())
+    }
+}
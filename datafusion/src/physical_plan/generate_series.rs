@@ -0,0 +1,208 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Execution plan generating a series of integers, see
+//! [`crate::datasource::generate_series::GenerateSeriesTable`]
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+use arrow::array::Int64Array;
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+use futures::stream::Stream;
+
+/// Execution plan producing the `len` integers `start`, `start + step`, ...
+/// as a single Int64 column in batches of `batch_size` rows
+#[derive(Debug, Clone)]
+pub struct GenerateSeriesExec {
+    /// The output schema, with a single Int64 column
+    schema: SchemaRef,
+    /// The first value
+    start: i64,
+    /// The difference between consecutive values
+    step: i64,
+    /// The number of values
+    len: usize,
+    /// The maximum number of rows per batch
+    batch_size: usize,
+}
+
+impl GenerateSeriesExec {
+    /// Create a new GenerateSeriesExec
+    pub fn new(
+        schema: SchemaRef,
+        start: i64,
+        step: i64,
+        len: usize,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            schema,
+            start,
+            step,
+            len,
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for GenerateSeriesExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(Arc::new(self.clone()))
+        } else {
+            Err(DataFusionError::Internal(format!(
+                "Children cannot be replaced in {:?}",
+                self
+            )))
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "GenerateSeriesExec invalid partition {}",
+                partition
+            )));
+        }
+        Ok(Box::pin(GenerateSeriesStream {
+            plan: self.clone(),
+            offset: 0,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "GenerateSeriesExec: start={}, step={}, len={}",
+                    self.start, self.step, self.len
+                )
+            }
+        }
+    }
+}
+
+/// The GenerateSeriesExec stream produces the values of the series
+struct GenerateSeriesStream {
+    /// The plan with the series to produce
+    plan: GenerateSeriesExec,
+    /// The index of the next value to produce
+    offset: usize,
+}
+
+impl Stream for GenerateSeriesStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let plan = &self.plan;
+        if self.offset >= plan.len {
+            return Poll::Ready(None);
+        }
+        let end = plan.len.min(self.offset + plan.batch_size);
+        let values = (self.offset..end)
+            // values that are in range may have out of range offsets
+            .map(|i| plan.start.wrapping_add((i as i64).wrapping_mul(plan.step)))
+            .collect::<Vec<_>>();
+        let values = Int64Array::from(values);
+        let batch = RecordBatch::try_new(plan.schema.clone(), vec![Arc::new(values)]);
+        self.offset = end;
+        Poll::Ready(Some(batch))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.plan.len - self.offset;
+        let batches = (remaining + self.plan.batch_size - 1) / self.plan.batch_size;
+        (batches, Some(batches))
+    }
+}
+
+impl RecordBatchStream for GenerateSeriesStream {
+    fn schema(&self) -> SchemaRef {
+        self.plan.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::collect;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    #[tokio::test]
+    async fn generate_series_in_batches() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Int64,
+            false,
+        )]));
+        let exec = GenerateSeriesExec::new(schema, 10, -3, 5, 2);
+        let batches = collect(Arc::new(exec)).await?;
+        let values = batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![vec![10, 7], vec![4, 1], vec![-2]]);
+        Ok(())
+    }
+}
@@ -45,6 +45,18 @@ pub(crate) enum GroupByScalar {
     Date32(i32),
 }
 
+impl GroupByScalar {
+    /// Returns the memory allocated by this scalar outside of its own size, in bytes
+    pub fn heap_size(&self) -> usize {
+        match self {
+            GroupByScalar::Utf8(v) | GroupByScalar::LargeUtf8(v) => {
+                std::mem::size_of::<String>() + v.capacity()
+            }
+            _ => 0,
+        }
+    }
+}
+
 impl TryFrom<&ScalarValue> for GroupByScalar {
     type Error = DataFusionError;
 
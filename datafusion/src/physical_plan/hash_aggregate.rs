@@ -17,13 +17,16 @@
 
 //! Defines the execution plan for the hash aggregate operation
 
+use instant::Instant;
 use std::any::Any;
+use std::hash::{BuildHasher, Hash, Hasher};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::vec;
 
 use ahash::RandomState;
 use futures::{
+    sink::SinkExt,
     stream::{Stream, StreamExt},
     Future,
 };
@@ -31,7 +34,7 @@ use futures::{
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     Accumulator, AggregateExpr, DisplayFormatType, Distribution, ExecutionPlan,
-    Partitioning, PhysicalExpr, SQLMetric,
+    MemoryUsage, MetricsSet, Partitioning, PhysicalExpr, SQLMetric,
 };
 use crate::scalar::ScalarValue;
 
@@ -69,8 +72,10 @@ use arrow::array::{
 use async_trait::async_trait;
 
 use super::{
-    expressions::Column, group_scalar::GroupByScalar, RecordBatchStream,
-    SendableRecordBatchStream,
+    common::{spawn_compute, AbortOnDropSingle},
+    expressions::Column,
+    group_scalar::GroupByScalar,
+    RecordBatchStream, SendableRecordBatchStream,
 };
 
 /// Hash aggregate modes
@@ -108,6 +113,18 @@ pub struct HashAggregateExec {
     input_schema: SchemaRef,
     /// Metric to track number of output rows
     output_rows: Arc<SQLMetric>,
+    /// Metric to track number of input batches
+    input_batches: Arc<SQLMetric>,
+    /// Metric to track time spent aggregating, in nanoseconds
+    elapsed_compute: Arc<SQLMetric>,
+    /// Memory used by the aggregation state of all partitions
+    memory: Arc<MemoryUsage>,
+    /// Hashes the group keys
+    random_state: RandomState,
+    /// Maximum number of rows of output batches with grouping, if any
+    output_batch_size: Option<usize>,
+    /// Number of shards the groups are split into
+    num_shards: usize,
 }
 
 fn create_schema(
@@ -156,8 +173,6 @@ impl HashAggregateExec {
 
         let schema = Arc::new(schema);
 
-        let output_rows = SQLMetric::counter();
-
         Ok(HashAggregateExec {
             mode,
             group_expr,
@@ -165,10 +180,52 @@ impl HashAggregateExec {
             input,
             schema,
             input_schema,
-            output_rows,
+            output_rows: SQLMetric::counter(),
+            input_batches: SQLMetric::counter(),
+            elapsed_compute: SQLMetric::time_nanos(),
+            memory: MemoryUsage::new(),
+            random_state: RandomState::new(),
+            output_batch_size: None,
+            num_shards: 1,
         })
     }
 
+    /// Customize the state used to hash the group keys. The order of the output
+    /// groups only depends on the order of the input if the seeds are fixed.
+    pub fn with_random_state(mut self, random_state: RandomState) -> Self {
+        self.random_state = random_state;
+        self
+    }
+
+    /// Returns this aggregate producing the groups in batches of at most
+    /// `output_batch_size` rows. The state of the groups of a batch is
+    /// dropped once the batch is produced.
+    pub fn with_output_batch_size(mut self, output_batch_size: usize) -> Self {
+        self.output_batch_size = Some(output_batch_size.max(1));
+        self
+    }
+
+    /// The maximum number of rows of output batches, if any. Without a
+    /// maximum, all groups are produced in a single batch.
+    pub fn output_batch_size(&self) -> Option<usize> {
+        self.output_batch_size
+    }
+
+    /// Returns this aggregate splitting its groups into `num_shards` shards,
+    /// rounded up to a power of two, by the hash of their keys. Every shard
+    /// is a separate hash table, and the shards are updated concurrently
+    /// for every input batch, which benefits aggregations with very many
+    /// groups.
+    pub fn with_num_shards(mut self, num_shards: usize) -> Self {
+        self.num_shards = num_shards.max(1).next_power_of_two();
+        self
+    }
+
+    /// The number of shards the groups are split into
+    pub fn num_shards(&self) -> usize {
+        self.num_shards
+    }
+
     /// Aggregation mode (full, partial)
     pub fn mode(&self) -> &AggregateMode {
         &self.mode
@@ -193,6 +250,24 @@ impl HashAggregateExec {
     pub fn input_schema(&self) -> SchemaRef {
         self.input_schema.clone()
     }
+
+    fn aggregation_metrics(&self) -> AggregationMetrics {
+        AggregationMetrics {
+            output_rows: self.output_rows.clone(),
+            input_batches: self.input_batches.clone(),
+            elapsed_compute: self.elapsed_compute.clone(),
+            memory: self.memory.clone(),
+        }
+    }
+}
+
+/// Metrics shared between a [`HashAggregateExec`] and its streams
+#[derive(Debug, Clone)]
+struct AggregationMetrics {
+    output_rows: Arc<SQLMetric>,
+    input_batches: Arc<SQLMetric>,
+    elapsed_compute: Arc<SQLMetric>,
+    memory: Arc<MemoryUsage>,
 }
 
 #[async_trait]
@@ -235,6 +310,7 @@ impl ExecutionPlan for HashAggregateExec {
                 self.schema.clone(),
                 self.aggr_expr.clone(),
                 input,
+                self.aggregation_metrics(),
             )))
         } else {
             Ok(Box::pin(GroupedHashAggregateStream::new(
@@ -243,7 +319,10 @@ impl ExecutionPlan for HashAggregateExec {
                 group_expr,
                 self.aggr_expr.clone(),
                 input,
-                self.output_rows.clone(),
+                self.random_state.clone(),
+                self.output_batch_size,
+                self.num_shards,
+                self.aggregation_metrics(),
             )))
         }
     }
@@ -253,22 +332,31 @@ impl ExecutionPlan for HashAggregateExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(HashAggregateExec::try_new(
-                self.mode,
-                self.group_expr.clone(),
-                self.aggr_expr.clone(),
-                children[0].clone(),
-                self.input_schema.clone(),
-            )?)),
+            1 => {
+                let mut aggregate = HashAggregateExec::try_new(
+                    self.mode,
+                    self.group_expr.clone(),
+                    self.aggr_expr.clone(),
+                    children[0].clone(),
+                    self.input_schema.clone(),
+                )?
+                .with_random_state(self.random_state.clone());
+                aggregate.output_batch_size = self.output_batch_size;
+                aggregate.num_shards = self.num_shards;
+                Ok(Arc::new(aggregate))
+            }
             _ => Err(DataFusionError::Internal(
                 "HashAggregateExec wrong number of children".to_string(),
             )),
         }
     }
 
-    fn metrics(&self) -> HashMap<String, SQLMetric> {
-        let mut metrics = HashMap::new();
-        metrics.insert("outputRows".to_owned(), (*self.output_rows).clone());
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.output_rows);
+        metrics.push(MetricsSet::INPUT_BATCHES, &self.input_batches);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.elapsed_compute);
+        metrics.push(MetricsSet::PEAK_MEM_USED, self.memory.peak());
         metrics
     }
 
@@ -334,28 +422,30 @@ Example: average
 pin_project! {
     struct GroupedHashAggregateStream {
         schema: SchemaRef,
+        // output batches, followed by `None` once all groups are produced
         #[pin]
-        output: futures::channel::oneshot::Receiver<ArrowResult<RecordBatch>>,
+        output: futures::channel::mpsc::Receiver<Option<ArrowResult<RecordBatch>>>,
         finished: bool,
         output_rows: Arc<SQLMetric>,
+        _drop_helper: AbortOnDropSingle<()>,
     }
 }
 
+/// Updates the groups of `accumulators` with the `rows` of `batch`, or all
+/// of its rows if `None`
 fn group_aggregate_batch(
     mode: &AggregateMode,
-    group_expr: &[Arc<dyn PhysicalExpr>],
     aggr_expr: &[Arc<dyn AggregateExpr>],
-    batch: RecordBatch,
+    batch: &GroupedBatch,
+    rows: Option<&[u32]>,
     mut accumulators: Accumulators,
-    aggregate_expressions: &[Vec<Arc<dyn PhysicalExpr>>],
 ) -> Result<Accumulators> {
-    // evaluate the grouping expressions
-    let group_values = evaluate(group_expr, &batch)?;
-
-    // evaluate the aggregation expressions.
-    // We could evaluate them after the `take`, but since we need to evaluate all
-    // of them anyways, it is more performant to do it while they are together.
-    let aggr_input_values = evaluate_many(aggregate_expressions, &batch)?;
+    let GroupedBatch {
+        group_values,
+        aggr_input_values,
+        keys,
+        ..
+    } = batch;
 
     // create vector large enough to hold the grouping key
     // this is an optimization to avoid allocating `key` on every row.
@@ -367,30 +457,29 @@ fn group_aggregate_batch(
 
     let mut group_by_values = group_by_values.into_boxed_slice();
 
-    let mut key = Vec::with_capacity(group_values.len());
+    // The first row of every group of this batch, whose key identifies the group
+    let mut batch_groups: Vec<usize> = vec![];
 
-    // 1.1 construct the key from the group values
+    // 1.1 look up the key of the row
     // 1.2 construct the mapping key if it does not exist
     // 1.3 add the row' index to `indices`
 
     // Make sure we can create the accumulators or otherwise return an error
     create_accumulators(aggr_expr).map_err(DataFusionError::into_arrow_external_error)?;
 
-    // Keys received in this batch
-    let mut batch_keys = vec![];
-
-    for row in 0..batch.num_rows() {
+    let num_rows = rows.map_or(keys.hashes.len(), <[u32]>::len);
+    for i in 0..num_rows {
+        let row = rows.map_or(i, |rows| rows[i] as usize);
         // 1.1
-        create_key(&group_values, row, &mut key)
-            .map_err(DataFusionError::into_arrow_external_error)?;
+        let key = keys.key(row);
 
         accumulators
             .raw_entry_mut()
-            .from_key(&key)
+            .from_key_hashed_nocheck(keys.hashes[row], key)
             // 1.3
             .and_modify(|_, (_, _, v)| {
                 if v.is_empty() {
-                    batch_keys.push(key.clone())
+                    batch_groups.push(row);
                 };
                 v.push(row as u32)
             })
@@ -398,10 +487,10 @@ fn group_aggregate_batch(
             .or_insert_with(|| {
                 // We can safely unwrap here as we checked we can create an accumulator before
                 let accumulator_set = create_accumulators(aggr_expr).unwrap();
-                batch_keys.push(key.clone());
-                let _ = create_group_by_values(&group_values, row, &mut group_by_values);
+                batch_groups.push(row);
+                let _ = create_group_by_values(group_values, row, &mut group_by_values);
                 (
-                    key.clone(),
+                    key.to_vec(),
                     (group_by_values.clone(), accumulator_set, vec![row as u32]),
                 )
             });
@@ -411,8 +500,8 @@ fn group_aggregate_batch(
     let mut batch_indices: UInt32Builder = UInt32Builder::new(0);
     let mut offsets = vec![0];
     let mut offset_so_far = 0;
-    for key in batch_keys.iter() {
-        let (_, _, indices) = accumulators.get_mut(key).unwrap();
+    for &row in &batch_groups {
+        let (_, _, indices) = accumulators.get_mut(keys.key(row)).unwrap();
         batch_indices.append_slice(&indices)?;
         offset_so_far += indices.len();
         offsets.push(offset_so_far);
@@ -443,11 +532,12 @@ fn group_aggregate_batch(
     // 2.3 `slice` from each of its arrays the keys' values
     // 2.4 update / merge the accumulator with the values
     // 2.5 clear indices
-    batch_keys
-        .iter_mut()
+    batch_groups
+        .iter()
         .zip(offsets.windows(2))
-        .try_for_each(|(key, offsets)| {
-            let (_, accumulator_set, indices) = accumulators.get_mut(key).unwrap();
+        .try_for_each(|(&row, offsets)| {
+            let (_, accumulator_set, indices) =
+                accumulators.get_mut(keys.key(row)).unwrap();
             // 2.2
             accumulator_set
                 .iter_mut()
@@ -480,6 +570,113 @@ fn group_aggregate_batch(
     Ok(accumulators)
 }
 
+/// An input batch of a grouped aggregation, evaluated for updating the groups
+struct GroupedBatch {
+    /// The values of the grouping expressions
+    group_values: Vec<ArrayRef>,
+    /// The values of the expressions of every aggregation
+    aggr_input_values: Vec<Vec<ArrayRef>>,
+    /// The keys of the rows and their hashes
+    keys: GroupKeyBuffers,
+    /// The rows of every shard, empty with a single shard
+    shard_rows: Vec<Vec<u32>>,
+}
+
+impl GroupedBatch {
+    fn try_new(
+        group_expr: &[Arc<dyn PhysicalExpr>],
+        aggregate_expressions: &[Vec<Arc<dyn PhysicalExpr>>],
+        batch: &RecordBatch,
+        mut keys: GroupKeyBuffers,
+        random_state: &RandomState,
+        num_shards: usize,
+    ) -> Result<Self> {
+        // evaluate the grouping expressions
+        let group_values = evaluate(group_expr, batch)?;
+
+        // evaluate the aggregation expressions.
+        // We could evaluate them after the `take`, but since we need to evaluate all
+        // of them anyways, it is more performant to do it while they are together.
+        let aggr_input_values = evaluate_many(aggregate_expressions, batch)?;
+
+        keys.update(&group_values, batch.num_rows(), random_state)?;
+
+        let mut shard_rows = vec![];
+        if num_shards > 1 {
+            shard_rows = vec![vec![]; num_shards];
+            for (row, hash) in keys.hashes.iter().enumerate() {
+                shard_rows[shard_of(*hash, num_shards)].push(row as u32);
+            }
+        }
+
+        Ok(Self {
+            group_values,
+            aggr_input_values,
+            keys,
+            shard_rows,
+        })
+    }
+}
+
+/// The keys of the rows of an input batch and their hashes, written into
+/// buffers that are reused for all batches instead of allocating a key for
+/// every row or group of every batch
+#[derive(Debug, Default)]
+struct GroupKeyBuffers {
+    /// The keys of the rows, one after the other
+    keys: Vec<u8>,
+    /// The end of the key of every row in `keys`
+    key_ends: Vec<usize>,
+    /// The hash of the key of every row
+    hashes: Vec<u64>,
+}
+
+impl GroupKeyBuffers {
+    /// Replaces the keys with those of the first `num_rows` rows of
+    /// `group_values`
+    fn update(
+        &mut self,
+        group_values: &[ArrayRef],
+        num_rows: usize,
+        random_state: &RandomState,
+    ) -> Result<()> {
+        self.keys.clear();
+        self.key_ends.clear();
+        self.hashes.clear();
+        for row in 0..num_rows {
+            let start = self.keys.len();
+            for col in group_values {
+                create_key_for_col(col, row, &mut self.keys)?;
+            }
+            self.key_ends.push(self.keys.len());
+            self.hashes
+                .push(hash_key(random_state, &self.keys[start..]));
+        }
+        Ok(())
+    }
+
+    /// The key of `row`
+    fn key(&self, row: usize) -> &[u8] {
+        let start = if row == 0 { 0 } else { self.key_ends[row - 1] };
+        &self.keys[start..self.key_ends[row]]
+    }
+}
+
+/// Hashes `key` the same way the accumulators hash their keys
+fn hash_key(random_state: &RandomState, key: &[u8]) -> u64 {
+    let mut hasher = random_state.build_hasher();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The shard of a group with key hash `hash`, out of `num_shards` shards,
+/// a power of two. hashbrown picks buckets by the lowest bits of the hash
+/// and tags them with the highest seven, so the shard is picked by bits in
+/// between that stay distinct within a shard.
+fn shard_of(hash: u64, num_shards: usize) -> usize {
+    (hash >> 32) as usize & (num_shards - 1)
+}
+
 /// Appends a sequence of [u8] bytes for the value in `col[row]` to
 /// `vec` to be used as a key into the hash map for a dictionary type
 ///
@@ -643,26 +840,15 @@ fn create_key_for_col(col: &ArrayRef, row: usize, vec: &mut Vec<u8>) -> Result<(
     Ok(())
 }
 
-/// Create a key `Vec<u8>` that is used as key for the hashmap
-pub(crate) fn create_key(
-    group_by_keys: &[ArrayRef],
-    row: usize,
-    vec: &mut Vec<u8>,
-) -> Result<()> {
-    vec.clear();
-    for col in group_by_keys {
-        create_key_for_col(col, row, vec)?
-    }
-    Ok(())
-}
-
 async fn compute_grouped_hash_aggregate(
     mode: AggregateMode,
-    schema: SchemaRef,
     group_expr: Vec<Arc<dyn PhysicalExpr>>,
     aggr_expr: Vec<Arc<dyn AggregateExpr>>,
     mut input: SendableRecordBatchStream,
-) -> ArrowResult<RecordBatch> {
+    random_state: RandomState,
+    num_shards: usize,
+    metrics: &AggregationMetrics,
+) -> ArrowResult<Vec<Accumulators>> {
     // the expressions to evaluate the batch, one vec of expressions per aggregation
     let aggregate_expressions = aggregate_expressions(&aggr_expr, &mode)
         .map_err(DataFusionError::into_arrow_external_error)?;
@@ -673,47 +859,197 @@ async fn compute_grouped_hash_aggregate(
     // * the indexes are `clear`ed at the end of each batch
     //let mut accumulators: Accumulators = FnvHashMap::default();
 
-    // iterate over all input batches and update the accumulators
-    let mut accumulators = Accumulators::default();
+    // iterate over all input batches and update the accumulators. Updating
+    // them is CPU intensive, so it runs off the async worker threads while
+    // the accumulators are moved back and forth.
+    let group_expr = Arc::new(group_expr);
+    let aggr_expr = Arc::new(aggr_expr);
+    let aggregate_expressions = Arc::new(aggregate_expressions);
+    let mut shards: Vec<Accumulators> = (0..num_shards)
+        .map(|_| Accumulators::with_hasher(random_state.clone()))
+        .collect();
+    let mut keys = GroupKeyBuffers::default();
     while let Some(batch) = input.next().await {
         let batch = batch?;
-        accumulators = group_aggregate_batch(
-            &mode,
-            &group_expr,
-            &aggr_expr,
-            batch,
-            accumulators,
-            &aggregate_expressions,
-        )
-        .map_err(DataFusionError::into_arrow_external_error)?;
+        metrics.input_batches.add(1);
+        let now = Instant::now();
+        let group_expr = group_expr.clone();
+        let aggregate_expressions = aggregate_expressions.clone();
+        let random_state = random_state.clone();
+        let evaluate_batch = move || {
+            GroupedBatch::try_new(
+                &group_expr,
+                &aggregate_expressions,
+                &batch,
+                keys,
+                &random_state,
+                num_shards,
+            )
+        };
+        if num_shards == 1 {
+            let aggr_expr = aggr_expr.clone();
+            let accumulators = shards.pop().unwrap();
+            let (accumulators, new_keys) = spawn_compute(move || {
+                let batch = evaluate_batch()?;
+                let accumulators =
+                    group_aggregate_batch(&mode, &aggr_expr, &batch, None, accumulators)?;
+                Ok((accumulators, batch.keys))
+            })
+            .await
+            .map_err(DataFusionError::into_arrow_external_error)?;
+            shards.push(accumulators);
+            keys = new_keys;
+        } else {
+            // the shards are independent of each other, so they are updated
+            // concurrently once the batch is evaluated
+            let batch = Arc::new(
+                spawn_compute(evaluate_batch)
+                    .await
+                    .map_err(DataFusionError::into_arrow_external_error)?,
+            );
+            let updates = shards.into_iter().enumerate().map(|(shard, accumulators)| {
+                let aggr_expr = aggr_expr.clone();
+                let batch = batch.clone();
+                spawn_compute(move || {
+                    let rows = Some(batch.shard_rows[shard].as_slice());
+                    group_aggregate_batch(&mode, &aggr_expr, &batch, rows, accumulators)
+                })
+            });
+            shards = futures::future::try_join_all(updates)
+                .await
+                .map_err(DataFusionError::into_arrow_external_error)?;
+            // all updates are done, so the key buffers can be reused
+            keys = Arc::try_unwrap(batch)
+                .map(|batch| batch.keys)
+                .unwrap_or_default();
+        }
+        metrics.elapsed_compute.add_elapsed(now);
     }
+    Ok(shards)
+}
 
-    create_batch_from_map(&mode, &accumulators, group_expr.len(), &schema)
+/// Sends the groups of all `shards` to `output` in batches of at most
+/// `output_batch_size` rows, followed by `None`. The groups are drained
+/// from the shards, so the state of the groups of a batch is dropped as
+/// soon as the batch is created.
+async fn send_grouped_output(
+    mode: AggregateMode,
+    schema: SchemaRef,
+    num_group_expr: usize,
+    shards: Vec<Accumulators>,
+    output_batch_size: Option<usize>,
+    metrics: AggregationMetrics,
+    output: &mut futures::channel::mpsc::Sender<Option<ArrowResult<RecordBatch>>>,
+) {
+    // the state only grows while consuming the input, so it is the largest
+    // right before the groups are produced
+    let memory_size = shards.iter().map(accumulators_memory_size).sum();
+    metrics.memory.grow(memory_size);
+
+    let num_groups = shards.iter().map(Accumulators::len).sum();
+    let output_batch_size = output_batch_size.unwrap_or(num_groups);
+    let mut groups = shards.into_iter().flatten();
+    let mut num_batches = 0;
+    loop {
+        let now = Instant::now();
+        let batch_groups = groups
+            .by_ref()
+            .take(output_batch_size.max(1))
+            .collect::<Vec<_>>();
+        // without groups, a single empty batch is produced
+        if batch_groups.is_empty() && num_batches > 0 {
+            break;
+        }
+        num_batches += 1;
+        let batch =
+            create_batch_from_groups(&mode, &batch_groups, num_group_expr, &schema);
+        drop(batch_groups);
+        metrics.elapsed_compute.add_elapsed(now);
+
+        let failed = batch.is_err();
+        // stop early if the stream was dropped
+        if output.send(Some(batch)).await.is_err() || failed {
+            metrics.memory.shrink(memory_size);
+            return;
+        }
+    }
+    metrics.memory.shrink(memory_size);
+    output.send(None).await.ok();
+}
+
+/// Estimates the memory used by the groups and accumulators in `accumulators`,
+/// including the strings of the group by values
+fn accumulators_memory_size(accumulators: &Accumulators) -> usize {
+    let entry_size = std::mem::size_of::<(
+        Vec<u8>,
+        (Box<[GroupByScalar]>, Vec<AccumulatorItem>, Vec<u32>),
+    )>();
+    let groups_size: usize = accumulators
+        .iter()
+        .map(|(key, (group_by_values, accumulators, indices))| {
+            key.capacity()
+                + group_by_values.len() * std::mem::size_of::<GroupByScalar>()
+                + group_by_values
+                    .iter()
+                    .map(GroupByScalar::heap_size)
+                    .sum::<usize>()
+                + accumulators.capacity() * std::mem::size_of::<AccumulatorItem>()
+                + indices.capacity() * std::mem::size_of::<u32>()
+        })
+        .sum();
+    accumulators.capacity() * entry_size + groups_size
 }
 
 impl GroupedHashAggregateStream {
     /// Create a new HashAggregateStream
-    pub fn new(
+    #[allow(clippy::too_many_arguments)]
+    fn new(
         mode: AggregateMode,
         schema: SchemaRef,
         group_expr: Vec<Arc<dyn PhysicalExpr>>,
         aggr_expr: Vec<Arc<dyn AggregateExpr>>,
         input: SendableRecordBatchStream,
-        output_rows: Arc<SQLMetric>,
+        random_state: RandomState,
+        output_batch_size: Option<usize>,
+        num_shards: usize,
+        metrics: AggregationMetrics,
     ) -> Self {
-        let (tx, rx) = futures::channel::oneshot::channel();
+        // at most one batch is buffered, so that the groups of later batches
+        // are only produced once the previous ones are consumed
+        let (mut tx, rx) = futures::channel::mpsc::channel(1);
 
         let schema_clone = schema.clone();
-        tokio::spawn(async move {
+        let output_rows = metrics.output_rows.clone();
+        let join_handle = tokio::spawn(async move {
+            let num_group_expr = group_expr.len();
             let result = compute_grouped_hash_aggregate(
                 mode,
-                schema_clone,
                 group_expr,
                 aggr_expr,
                 input,
+                random_state,
+                num_shards,
+                &metrics,
             )
             .await;
-            tx.send(result)
+            match result {
+                Ok(shards) => {
+                    send_grouped_output(
+                        mode,
+                        schema_clone,
+                        num_group_expr,
+                        shards,
+                        output_batch_size,
+                        metrics,
+                        &mut tx,
+                    )
+                    .await
+                }
+                Err(e) => {
+                    // failing here is OK, the receiver is gone and does not care
+                    tx.send(Some(Err(e))).await.ok();
+                }
+            }
         });
 
         Self {
@@ -721,13 +1057,16 @@ impl GroupedHashAggregateStream {
             output: rx,
             finished: false,
             output_rows,
+            _drop_helper: AbortOnDropSingle::new(join_handle),
         }
     }
 }
 
 type AccumulatorItem = Box<dyn Accumulator>;
-type Accumulators =
-    HashMap<Vec<u8>, (Box<[GroupByScalar]>, Vec<AccumulatorItem>, Vec<u32>), RandomState>;
+/// The group by values, accumulators and indices of the rows of the current
+/// batch of a group
+type GroupState = (Box<[GroupByScalar]>, Vec<AccumulatorItem>, Vec<u32>);
+type Accumulators = HashMap<Vec<u8>, GroupState, RandomState>;
 
 impl Stream for GroupedHashAggregateStream {
     type Item = ArrowResult<RecordBatch>;
@@ -742,25 +1081,29 @@ impl Stream for GroupedHashAggregateStream {
 
         let output_rows = self.output_rows.clone();
 
-        // is the output ready?
+        // is the next output batch ready?
         let this = self.project();
-        let output_poll = this.output.poll(cx);
+        let output_poll = this.output.poll_next(cx);
 
         match output_poll {
-            Poll::Ready(result) => {
+            Poll::Ready(Some(Some(Ok(batch)))) => {
+                output_rows.add(batch.num_rows());
+                Poll::Ready(Some(Ok(batch)))
+            }
+            Poll::Ready(Some(Some(Err(e)))) => {
                 *this.finished = true;
-
-                // check for error in receiving channel and unwrap actual result
-                let result = match result {
-                    Err(e) => Err(ArrowError::ExternalError(Box::new(e))), // error receiving
-                    Ok(result) => result,
-                };
-
-                if let Ok(batch) = &result {
-                    output_rows.add(batch.num_rows())
-                }
-
-                Poll::Ready(Some(result))
+                Poll::Ready(Some(Err(e)))
+            }
+            Poll::Ready(Some(None)) => {
+                *this.finished = true;
+                Poll::Ready(None)
+            }
+            // the aggregation stopped before producing all groups
+            Poll::Ready(None) => {
+                *this.finished = true;
+                Poll::Ready(Some(Err(ArrowError::ComputeError(
+                    "Hash aggregation terminated unexpectedly".to_string(),
+                ))))
             }
             Poll::Pending => Poll::Pending,
         }
@@ -835,6 +1178,8 @@ pin_project! {
         #[pin]
         output: futures::channel::oneshot::Receiver<ArrowResult<RecordBatch>>,
         finished: bool,
+        output_rows: Arc<SQLMetric>,
+        _drop_helper: AbortOnDropSingle<()>,
     }
 }
 
@@ -843,6 +1188,7 @@ async fn compute_hash_aggregate(
     schema: SchemaRef,
     aggr_expr: Vec<Arc<dyn AggregateExpr>>,
     mut input: SendableRecordBatchStream,
+    metrics: AggregationMetrics,
 ) -> ArrowResult<RecordBatch> {
     let mut accumulators = create_accumulators(&aggr_expr)
         .map_err(DataFusionError::into_arrow_external_error)?;
@@ -856,37 +1202,49 @@ async fn compute_hash_aggregate(
     // future is ready when all batches are computed
     while let Some(batch) = input.next().await {
         let batch = batch?;
+        metrics.input_batches.add(1);
+        let now = Instant::now();
         aggregate_batch(&mode, &batch, &mut accumulators, &expressions)
             .map_err(DataFusionError::into_arrow_external_error)?;
+        metrics.elapsed_compute.add_elapsed(now);
     }
 
     // 2. convert values to a record batch
-    finalize_aggregation(&accumulators, &mode)
+    let now = Instant::now();
+    let batch = finalize_aggregation(&accumulators, &mode)
         .map(|columns| RecordBatch::try_new(schema.clone(), columns))
-        .map_err(DataFusionError::into_arrow_external_error)?
+        .map_err(DataFusionError::into_arrow_external_error)?;
+    metrics.elapsed_compute.add_elapsed(now);
+    batch
 }
 
 impl HashAggregateStream {
     /// Create a new HashAggregateStream
-    pub fn new(
+    fn new(
         mode: AggregateMode,
         schema: SchemaRef,
         aggr_expr: Vec<Arc<dyn AggregateExpr>>,
         input: SendableRecordBatchStream,
+        metrics: AggregationMetrics,
     ) -> Self {
         let (tx, rx) = futures::channel::oneshot::channel();
 
         let schema_clone = schema.clone();
-        tokio::spawn(async move {
+        let output_rows = metrics.output_rows.clone();
+        let join_handle = tokio::spawn(async move {
             let result =
-                compute_hash_aggregate(mode, schema_clone, aggr_expr, input).await;
-            tx.send(result)
+                compute_hash_aggregate(mode, schema_clone, aggr_expr, input, metrics)
+                    .await;
+            // failing here is OK, the receiver is gone and does not care about the result
+            tx.send(result).ok();
         });
 
         Self {
             schema,
             output: rx,
             finished: false,
+            output_rows,
+            _drop_helper: AbortOnDropSingle::new(join_handle),
         }
     }
 }
@@ -934,6 +1292,8 @@ impl Stream for HashAggregateStream {
             return Poll::Ready(None);
         }
 
+        let output_rows = self.output_rows.clone();
+
         // is the output ready?
         let this = self.project();
         let output_poll = this.output.poll(cx);
@@ -948,6 +1308,10 @@ impl Stream for HashAggregateStream {
                     Ok(result) => result,
                 };
 
+                if let Ok(batch) = &result {
+                    output_rows.add(batch.num_rows())
+                }
+
                 Poll::Ready(Some(result))
             }
             Poll::Pending => Poll::Pending,
@@ -961,17 +1325,17 @@ impl RecordBatchStream for HashAggregateStream {
     }
 }
 
-/// Create a RecordBatch with all group keys and accumulator' states or values.
-fn create_batch_from_map(
+/// Create a RecordBatch with the keys and accumulator' states or values of `groups`.
+fn create_batch_from_groups(
     mode: &AggregateMode,
-    accumulators: &Accumulators,
+    groups: &[(Vec<u8>, GroupState)],
     num_group_expr: usize,
     output_schema: &Schema,
 ) -> ArrowResult<RecordBatch> {
-    if accumulators.is_empty() {
+    if groups.is_empty() {
         return Ok(RecordBatch::new_empty(Arc::new(output_schema.to_owned())));
     }
-    let (_, (_, accs, _)) = accumulators.iter().next().unwrap();
+    let (_, (_, accs, _)) = &groups[0];
     let mut acc_data_types: Vec<usize> = vec![];
 
     // Calculate number/shape of state arrays
@@ -991,7 +1355,7 @@ fn create_batch_from_map(
 
     let mut columns = (0..num_group_expr)
         .map(|i| {
-            ScalarValue::iter_to_array(accumulators.into_iter().map(
+            ScalarValue::iter_to_array(groups.iter().map(
                 |(_, (group_by_values, _, _))| ScalarValue::from(&group_by_values[i]),
             ))
         })
@@ -999,27 +1363,24 @@ fn create_batch_from_map(
         .map_err(|x| x.into_arrow_external_error())?;
 
     // add state / evaluated arrays
+    let mut state_offset = num_group_expr;
     for (x, &state_len) in acc_data_types.iter().enumerate() {
-        for y in 0..state_len {
-            match mode {
-                AggregateMode::Partial => {
-                    let res = ScalarValue::iter_to_array(accumulators.into_iter().map(
-                        |(_, (_, accumulator, _))| {
-                            let x = accumulator[x].state().unwrap();
-                            x[y].clone()
-                        },
-                    ))
+        match mode {
+            AggregateMode::Partial => {
+                let state_fields =
+                    &output_schema.fields()[state_offset..state_offset + state_len];
+                state_offset += state_len;
+                let states = create_state_arrays(groups, x, state_fields)
                     .map_err(DataFusionError::into_arrow_external_error)?;
-
-                    columns.push(res);
-                }
-                AggregateMode::Final | AggregateMode::FinalPartitioned => {
-                    let res = ScalarValue::iter_to_array(accumulators.into_iter().map(
+                columns.extend(states);
+            }
+            AggregateMode::Final | AggregateMode::FinalPartitioned => {
+                let res =
+                    ScalarValue::iter_to_array(groups.iter().map(
                         |(_, (_, accumulator, _))| accumulator[x].evaluate().unwrap(),
                     ))
                     .map_err(DataFusionError::into_arrow_external_error)?;
-                    columns.push(res);
-                }
+                columns.push(res);
             }
         }
     }
@@ -1036,6 +1397,43 @@ fn create_batch_from_map(
     RecordBatch::try_new(Arc::new(output_schema.to_owned()), columns)
 }
 
+/// Creates the arrays of the state of the `x`th aggregation of `groups`, one
+/// per field of `state_fields`. States of types that can be built directly
+/// are appended to builders by the accumulators, other states, such as the
+/// lists of distinct aggregations, are converted from their scalar values.
+fn create_state_arrays(
+    groups: &[(Vec<u8>, GroupState)],
+    x: usize,
+    state_fields: &[Field],
+) -> Result<Vec<ArrayRef>> {
+    if state_fields
+        .iter()
+        .all(|field| ScalarValue::can_append_to_builder(field.data_type()))
+    {
+        let mut builders = state_fields
+            .iter()
+            .map(|field| ScalarValue::make_builder(field.data_type(), groups.len()))
+            .collect::<Result<Vec<_>>>()?;
+        for (_, (_, accumulator, _)) in groups {
+            accumulator[x].append_state(&mut builders)?;
+        }
+        Ok(builders
+            .iter_mut()
+            .map(|builder| builder.finish())
+            .collect())
+    } else {
+        let states = groups
+            .iter()
+            .map(|(_, (_, accumulator, _))| accumulator[x].state())
+            .collect::<Result<Vec<_>>>()?;
+        (0..state_fields.len())
+            .map(|y| {
+                ScalarValue::iter_to_array(states.iter().map(|state| state[y].clone()))
+            })
+            .collect()
+    }
+}
+
 fn create_accumulators(
     aggr_expr: &[Arc<dyn AggregateExpr>],
 ) -> Result<Vec<AccumulatorItem>> {
@@ -1284,7 +1682,7 @@ mod tests {
         ];
         assert_batches_sorted_eq!(expected, &result);
 
-        let merge = Arc::new(MergeExec::new(partial_aggregate));
+        let merge = Arc::new(MergeExec::new(partial_aggregate.clone()));
 
         let final_group: Vec<Arc<dyn PhysicalExpr>> =
             (0..groups.len()).map(|i| col(&groups[i].1)).collect();
@@ -1321,8 +1719,11 @@ mod tests {
         assert_batches_sorted_eq!(&expected, &result);
 
         let metrics = merged_aggregate.metrics();
-        let output_rows = metrics.get("outputRows").unwrap();
-        assert_eq!(3, output_rows.value());
+        assert_eq!(Some(3), metrics.output_rows());
+        assert_eq!(Some(1), metrics.input_batches());
+        // the partial aggregate was executed by itself and below the merge
+        assert_eq!(Some(4), partial_aggregate.metrics().input_batches());
+        assert!(metrics.peak_mem_used().unwrap() > 0);
 
         Ok(())
     }
@@ -1430,4 +1831,79 @@ mod tests {
 
         check_aggregates(input).await
     }
+
+    #[tokio::test]
+    async fn grouped_output_in_batches() -> Result<()> {
+        let input: Arc<dyn ExecutionPlan> =
+            Arc::new(TestYieldingExec { yield_first: false });
+        let input_schema = input.schema();
+        let aggregate = HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![(col("a"), "a".to_string())],
+            vec![Arc::new(Avg::new(
+                col("b"),
+                "AVG(b)".to_string(),
+                DataType::Float64,
+            ))],
+            input,
+            input_schema,
+        )?
+        .with_output_batch_size(2);
+
+        let result = common::collect(aggregate.execute(0).await?).await?;
+        assert_eq!(
+            result.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        let expected = vec![
+            "+---+---------------+-------------+",
+            "| a | AVG(b)[count] | AVG(b)[sum] |",
+            "+---+---------------+-------------+",
+            "| 2 | 2             | 2           |",
+            "| 3 | 3             | 7           |",
+            "| 4 | 3             | 11          |",
+            "+---+---------------+-------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+        assert_eq!(Some(3), aggregate.metrics().output_rows());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn grouped_aggregate_in_shards() -> Result<()> {
+        let input: Arc<dyn ExecutionPlan> =
+            Arc::new(TestYieldingExec { yield_first: false });
+        let input_schema = input.schema();
+        let aggregate = HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![(col("a"), "a".to_string())],
+            vec![Arc::new(Avg::new(
+                col("b"),
+                "AVG(b)".to_string(),
+                DataType::Float64,
+            ))],
+            input,
+            input_schema,
+        )?
+        .with_num_shards(3);
+        assert_eq!(4, aggregate.num_shards());
+
+        let result = common::collect(aggregate.execute(0).await?).await?;
+
+        let expected = vec![
+            "+---+---------------+-------------+",
+            "| a | AVG(b)[count] | AVG(b)[sum] |",
+            "+---+---------------+-------------+",
+            "| 2 | 2             | 2           |",
+            "| 3 | 3             | 7           |",
+            "| 4 | 3             | 11          |",
+            "+---+---------------+-------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+        assert_eq!(Some(3), aggregate.metrics().output_rows());
+
+        Ok(())
+    }
 }
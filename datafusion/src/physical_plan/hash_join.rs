@@ -18,33 +18,39 @@
 //! Defines the join plan for executing partitions in parallel and then joining the results
 //! into a set of partitions.
 
-use ahash::CallHasher;
 use ahash::RandomState;
 
 use arrow::{
     array::{
-        ArrayData, ArrayRef, BooleanArray, Date32Array, Date64Array, Float32Array,
-        Float64Array, LargeStringArray, PrimitiveArray, TimestampMicrosecondArray,
-        TimestampMillisecondArray, TimestampNanosecondArray, UInt32BufferBuilder,
-        UInt32Builder, UInt64BufferBuilder, UInt64Builder,
+        ArrayData, ArrayRef, BooleanArray, LargeStringArray, PrimitiveArray,
+        UInt32BufferBuilder, UInt32Builder, UInt64BufferBuilder, UInt64Builder,
     },
     compute,
-    datatypes::{TimeUnit, UInt32Type, UInt64Type},
+    datatypes::{UInt32Type, UInt64Type},
 };
+use instant::Instant;
 use smallvec::{smallvec, SmallVec};
+use std::fs::File;
+use std::vec;
 use std::{any::Any, usize};
-use std::{hash::Hasher, sync::Arc};
-use std::{time::Instant, vec};
+use std::{
+    hash::Hasher,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::{Arc, Mutex, RwLock},
+};
 
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
 use futures::{Stream, StreamExt, TryStreamExt};
 use hashbrown::HashMap;
-use tokio::sync::Mutex;
+use tempfile::NamedTempFile;
+use tokio::sync::OnceCell;
 
 use arrow::array::Array;
 use arrow::datatypes::DataType;
 use arrow::datatypes::{Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
+use arrow::ipc::{reader::FileReader, writer::FileWriter};
 use arrow::record_batch::RecordBatch;
 
 use arrow::array::{
@@ -52,20 +58,32 @@ use arrow::array::{
     UInt64Array, UInt8Array,
 };
 
-use super::expressions::col;
+use super::expressions::{col, max, max_batch, min, min_batch};
 use super::{
+    adaptive::ReplanHook,
     hash_utils::{build_join_schema, check_join_is_valid, JoinOn, JoinType},
+    hashing::BatchHasher,
     merge::MergeExec,
+    runtime_filter::RuntimeFilter,
 };
 use crate::error::{DataFusionError, Result};
+use crate::execution::disk_manager::DiskManager;
+use crate::execution::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation};
+use crate::execution::runtime_env::RuntimeEnv;
+use crate::scalar::ScalarValue;
 
 use super::{
-    DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
-    SendableRecordBatchStream,
+    DisplayFormatType, ExecutionPlan, MemoryUsage, MetricsSet, Partitioning,
+    RecordBatchStream, SQLMetric, SendableRecordBatchStream,
 };
 use crate::physical_plan::coalesce_batches::concat_batches;
+use crate::physical_plan::common::{
+    batch_memory_size, batches_memory_size, spawn_compute, SizedRecordBatchStream,
+};
 use log::debug;
 
+pub use super::hashing::create_hashes;
+
 // Maps a `u64` hash value based on the left ["on" values] to a list of indices with this key's value.
 //
 // Note that the `u64` keys are not stored in the hashmap (hence the `()` as key), but are only used
@@ -79,7 +97,111 @@ use log::debug;
 // TODO: speed up collission check and move away from using a hashbrown HashMap
 // https://github.com/apache/arrow-datafusion/issues/50
 type JoinHashMap = HashMap<(), SmallVec<[u64; 1]>, IdHashBuilder>;
-type JoinLeftData = Arc<(JoinHashMap, RecordBatch)>;
+type JoinLeftData = Arc<(JoinIndex, RecordBatch)>;
+
+/// Index from the join keys of the build side to its rows
+#[derive(Debug)]
+enum JoinIndex {
+    /// Rows by the hash of their keys, whose values are compared on lookup
+    /// because of hash collisions
+    Hash(JoinHashMap),
+    /// Rows by the value of their single small integer key, which is used
+    /// as position in the vector after subtracting the minimum value of its
+    /// type. This needs neither hashing nor collision checks. Rows with null
+    /// keys are not indexed.
+    Direct(Vec<SmallVec<[u64; 1]>>),
+}
+
+/// The build sides of a [`HashJoinExec`]: a single one shared by all output
+/// partitions with [`PartitionMode::CollectLeft`], and one per partition
+/// with [`PartitionMode::Partitioned`]. Each is built exactly once, when
+/// first needed, and kept until [`HashJoinExec::reset`] is called. Partitions
+/// waiting for a build side that fails to build all get its error.
+type BuildSides = Vec<Arc<OnceCell<BuildSideResult>>>;
+
+/// The outcome of building a build side, shared by all partitions using it
+type BuildSideResult = std::result::Result<BuildSide, Arc<DataFusionError>>;
+
+/// A build side, held in memory if the memory pool of the join allows it
+#[derive(Debug, Clone)]
+enum BuildSide {
+    /// The index and rows of the build side, with the memory reserved for
+    /// them, which is freed once the build side is dropped
+    InMemory(JoinLeftData, Option<Arc<MemoryReservation>>),
+    /// The rows of the build side, spilled to disk because they did not fit
+    /// in memory
+    Spilled(Arc<SpilledBuildSide>),
+}
+
+/// A build side spilled to disk, split into spill partitions by the hash of
+/// the join keys. The probe side is split the same way when it is executed,
+/// so that each spill partition of the build side only needs to be joined
+/// with the same spill partition of the probe side, which is done one spill
+/// partition at a time (as in a grace hash join).
+#[derive(Debug)]
+struct SpilledBuildSide {
+    /// The spill file of each spill partition
+    partitions: Vec<SpillFile>,
+    /// The rows of each spill partition matched so far, for the join types
+    /// that produce rows of the left side after probing
+    visited: Vec<Arc<VisitedLeftSide>>,
+}
+
+/// A spill file holding the rows of one spill partition of a join input,
+/// which is deleted when dropped
+#[derive(Debug)]
+struct SpillFile {
+    file: NamedTempFile,
+    num_rows: usize,
+}
+
+/// The rows of a build side matched by any of the partitions probing it.
+/// Which rows have (or have no) matches is only known once all of these
+/// partitions finished, so the last one to finish produces them.
+#[derive(Debug)]
+struct VisitedLeftSide {
+    // TODO: use a more memory efficient data structure, https://github.com/apache/arrow-datafusion/issues/240
+    rows: Vec<AtomicBool>,
+    /// Number of partitions probing the build side
+    num_partitions: usize,
+    /// Number of partitions probing the build side that did not finish yet
+    running: AtomicUsize,
+}
+
+impl VisitedLeftSide {
+    fn new(num_rows: usize, num_partitions: usize) -> Self {
+        Self {
+            rows: (0..num_rows).map(|_| AtomicBool::new(false)).collect(),
+            num_partitions,
+            running: AtomicUsize::new(num_partitions),
+        }
+    }
+
+    fn is_visited(&self, row: usize) -> bool {
+        self.rows[row].load(Ordering::Relaxed)
+    }
+
+    fn visit(&self, row: usize) {
+        self.rows[row].store(true, Ordering::Relaxed)
+    }
+
+    /// Marks a partition probing the build side as finished. Returns which
+    /// rows were visited if it was the last one, after which the rows are
+    /// unvisited again for the partitions to be executed again.
+    fn finish(&self) -> Option<Vec<bool>> {
+        // the visits of every partition happen before it finishes
+        if self.running.fetch_sub(1, Ordering::AcqRel) != 1 {
+            return None;
+        }
+        let visited = self
+            .rows
+            .iter()
+            .map(|row| row.swap(false, Ordering::Relaxed))
+            .collect();
+        self.running.store(self.num_partitions, Ordering::Release);
+        Some(visited)
+    }
+}
 
 /// join execution plan executes partitions in parallel and combines them into a set of
 /// partitions.
@@ -95,12 +217,74 @@ pub struct HashJoinExec {
     join_type: JoinType,
     /// The schema once the join is applied
     schema: SchemaRef,
-    /// Build-side
-    build_side: Arc<Mutex<Option<JoinLeftData>>>,
+    /// Build-side(s)
+    build_sides: RwLock<BuildSides>,
+    /// The rows of each build side matched so far, for the join types that
+    /// produce rows of the left side after probing
+    visited_left_sides: Mutex<Vec<Option<Arc<VisitedLeftSide>>>>,
     /// Shares the `RandomState` for the hashing algorithm
     random_state: RandomState,
     /// Partitioning mode to use
     mode: PartitionMode,
+    /// Metrics shared by all partitions of the join
+    metrics: HashJoinMetrics,
+    /// Maximum number of rows of output batches, if any
+    output_batch_size: Option<usize>,
+    /// Filters on the keys of the right side, set to the bounds of the keys
+    /// of the build side once it is built
+    probe_filters: Vec<Arc<RuntimeFilter>>,
+    /// Asked for a plan replacing this join once its build side is built
+    replan_hook: Option<Arc<dyn ReplanHook>>,
+    /// The answer of the replan hook, `None` until it was asked
+    replanned: Mutex<Option<Option<Arc<dyn ExecutionPlan>>>>,
+    /// Memory pool to reserve the memory of the build sides from, and disk
+    /// manager to spill them with if the pool runs out of memory. The
+    /// memory of the build sides is not limited if `None`.
+    runtime: Option<Arc<RuntimeEnv>>,
+    /// Number of spill partitions spilled build sides are split into
+    spill_partitions: usize,
+}
+
+/// The default number of spill partitions of [`HashJoinExec`]
+pub const DEFAULT_SPILL_PARTITIONS: usize = 16;
+
+/// Metrics for [`HashJoinExec`], shared by all of its output streams
+#[derive(Debug, Clone)]
+struct HashJoinMetrics {
+    /// Total time spent building the hash table(s) of the build side
+    build_time: Arc<SQLMetric>,
+    /// Number of probe-side batches consumed
+    input_batches: Arc<SQLMetric>,
+    /// Number of probe-side rows consumed
+    input_rows: Arc<SQLMetric>,
+    /// Number of batches produced
+    output_batches: Arc<SQLMetric>,
+    /// Number of rows produced
+    output_rows: Arc<SQLMetric>,
+    /// Total time joining probe-side batches to the build side
+    join_time: Arc<SQLMetric>,
+    /// Memory used by the build side(s)
+    memory: Arc<MemoryUsage>,
+    /// Number of build sides and probe-side partitions spilled to disk
+    spill_count: Arc<SQLMetric>,
+    /// Number of bytes spilled to disk
+    spilled_bytes: Arc<SQLMetric>,
+}
+
+impl HashJoinMetrics {
+    fn new() -> Self {
+        Self {
+            build_time: SQLMetric::time_nanos(),
+            input_batches: SQLMetric::counter(),
+            input_rows: SQLMetric::counter(),
+            output_batches: SQLMetric::counter(),
+            output_rows: SQLMetric::counter(),
+            join_time: SQLMetric::time_nanos(),
+            memory: MemoryUsage::new(),
+            spill_count: SQLMetric::counter(),
+            spilled_bytes: SQLMetric::counter(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -113,6 +297,7 @@ pub enum PartitionMode {
 }
 
 /// Information about the index and placement (left or right) of the columns
+#[derive(Clone)]
 struct ColumnIndex {
     /// Index of the column
     index: usize,
@@ -142,23 +327,389 @@ impl HashJoinExec {
             &join_type,
         ));
 
-        let on = on
+        let on: Vec<(String, String)> = on
             .iter()
             .map(|(l, r)| (l.to_string(), r.to_string()))
             .collect();
 
         let random_state = RandomState::with_seeds(0, 0, 0, 0);
 
+        // the right side can only be filtered by the keys of a build side
+        // that all partitions share, and if its rows without matches are
+        // not part of the output
+        let probe_filters = match (partition_mode, *join_type) {
+            (PartitionMode::CollectLeft, JoinType::Inner)
+            | (PartitionMode::CollectLeft, JoinType::Left)
+            | (PartitionMode::CollectLeft, JoinType::Semi)
+            | (PartitionMode::CollectLeft, JoinType::Anti) => on
+                .iter()
+                .map(|(_, right)| Arc::new(RuntimeFilter::new(right.clone())))
+                .collect(),
+            _ => vec![],
+        };
+
+        let build_sides = match partition_mode {
+            PartitionMode::CollectLeft => new_build_sides(1),
+            PartitionMode::Partitioned => {
+                new_build_sides(left.output_partitioning().partition_count())
+            }
+        };
+        let visited_left_sides = vec![None; build_sides.len()];
+
         Ok(HashJoinExec {
             left,
             right,
             on,
             join_type: *join_type,
             schema,
-            build_side: Arc::new(Mutex::new(None)),
+            build_sides: RwLock::new(build_sides),
+            visited_left_sides: Mutex::new(visited_left_sides),
             random_state,
             mode: partition_mode,
+            metrics: HashJoinMetrics::new(),
+            output_batch_size: None,
+            probe_filters,
+            replan_hook: None,
+            replanned: Mutex::new(None),
+            runtime: None,
+            spill_partitions: DEFAULT_SPILL_PARTITIONS,
+        })
+    }
+
+    /// Returns this join producing batches of at most `output_batch_size`
+    /// rows. The columns of an output batch are only gathered when the batch
+    /// is polled, so rows of a probe-side batch that aren't consumed, e.g.
+    /// because of a downstream limit, are never gathered.
+    pub fn with_output_batch_size(mut self, output_batch_size: usize) -> Self {
+        self.output_batch_size = Some(output_batch_size.max(1));
+        self
+    }
+
+    /// The maximum number of rows of output batches, if any. Without a
+    /// maximum, one batch is produced per probe-side batch.
+    pub fn output_batch_size(&self) -> Option<usize> {
+        self.output_batch_size
+    }
+
+    /// Filters on the keys of the right side, one per key, which are set to
+    /// the minimum and maximum key of the build side once it is built and
+    /// before the right side is executed. Scans of the right side holding
+    /// them can skip data whose keys are out of these bounds. Empty unless
+    /// the join uses [`PartitionMode::CollectLeft`] and only produces right
+    /// rows with matches.
+    pub fn probe_filters(&self) -> &[Arc<RuntimeFilter>] {
+        &self.probe_filters
+    }
+
+    /// Returns this join asking `replan_hook` for a plan to execute instead
+    /// once its build side is built, before its right side is executed.
+    /// Only used with [`PartitionMode::CollectLeft`].
+    pub fn with_replan_hook(mut self, replan_hook: Arc<dyn ReplanHook>) -> Self {
+        self.replan_hook = Some(replan_hook);
+        self
+    }
+
+    /// The hook asked for a plan replacing this join, if any
+    pub fn replan_hook(&self) -> Option<&Arc<dyn ReplanHook>> {
+        self.replan_hook.as_ref()
+    }
+
+    /// Returns this join reserving the memory of its build sides from the
+    /// memory pool of `runtime`. Build sides that do not fit into the pool
+    /// are spilled to the disk manager of `runtime` instead of failing the
+    /// query.
+    pub fn with_runtime(mut self, runtime: Arc<RuntimeEnv>) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Returns this join splitting spilled build sides into
+    /// `spill_partitions` spill partitions, of which one at a time is read
+    /// back into memory. Defaults to [`DEFAULT_SPILL_PARTITIONS`].
+    pub fn with_spill_partitions(mut self, spill_partitions: usize) -> Self {
+        self.spill_partitions = spill_partitions.max(1);
+        self
+    }
+
+    /// The number of spill partitions spilled build sides are split into
+    pub fn spill_partitions(&self) -> usize {
+        self.spill_partitions
+    }
+
+    /// Returns this join with its left and right inputs swapped, building the
+    /// hash table from the right side instead. The join type is mirrored and
+    /// all other settings are kept: the partition mode, output batch size,
+    /// replan hook, runtime and number of spill partitions. The probe filters
+    /// are created anew for the keys of the new right side.
+    ///
+    /// The columns of the left side come after those of the right side in
+    /// the output of the swapped join. Semi and anti joins can not be
+    /// swapped, as they only produce the rows of their right side.
+    pub fn with_swapped_inputs(&self) -> Result<Self> {
+        let join_type = match self.join_type {
+            JoinType::Inner => JoinType::Inner,
+            JoinType::Left => JoinType::Right,
+            JoinType::Right => JoinType::Left,
+            JoinType::Full => JoinType::Full,
+            JoinType::Semi | JoinType::Anti => {
+                return Err(DataFusionError::Plan(format!(
+                    "The inputs of a hash join of type {:?} can not be swapped",
+                    self.join_type
+                )))
+            }
+        };
+        let on = self
+            .on
+            .iter()
+            .map(|(left, right)| (right.clone(), left.clone()))
+            .collect::<Vec<_>>();
+
+        let mut swapped = HashJoinExec::try_new(
+            self.right.clone(),
+            self.left.clone(),
+            &on,
+            &join_type,
+            self.mode,
+        )?;
+        swapped.output_batch_size = self.output_batch_size;
+        swapped.replan_hook = self.replan_hook.clone();
+        swapped.runtime = self.runtime.clone();
+        swapped.spill_partitions = self.spill_partitions;
+        Ok(swapped)
+    }
+
+    /// The plan the replan hook replaced this join with, if any. The hook is
+    /// asked once, by the first partition to find the build side built.
+    fn replan(&self, left_data: &JoinLeftData) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let replan_hook = match (&self.replan_hook, self.mode) {
+            (Some(replan_hook), PartitionMode::CollectLeft) => replan_hook,
+            _ => return Ok(None),
+        };
+        let mut replanned = self.replanned.lock().unwrap();
+        if replanned.is_none() {
+            *replanned = Some(replan_hook.replan_join(self, &left_data.1)?);
+        }
+        Ok(replanned.clone().flatten())
+    }
+
+    /// Collects the left input and builds the build side for `partition`, or
+    /// for all partitions with [`PartitionMode::CollectLeft`]. The build side
+    /// is spilled to disk if the memory pool of the join refuses to reserve
+    /// the memory for its rows.
+    async fn collect_build_side(
+        &self,
+        partition: usize,
+        on_left: Vec<String>,
+    ) -> Result<BuildSide> {
+        let start = Instant::now();
+
+        let mut stream = match self.mode {
+            // merge all left parts into a single stream
+            PartitionMode::CollectLeft => {
+                MergeExec::new(self.left.clone()).execute(0).await?
+            }
+            // load 1 partition of left side in memory
+            PartitionMode::Partitioned => self.left.execute(partition).await?,
+        };
+
+        let mut reservation = self.runtime.as_ref().map(|runtime| {
+            MemoryConsumer::new(format!("HashJoinExec[{}]", partition))
+                .with_can_spill(true)
+                .register(&runtime.memory_pool)
+        });
+
+        // collect all batches of the build side, from which the
+        // [JoinHashMap] is built afterwards
+        let mut num_rows = 0;
+        let mut batches = vec![];
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            if let (Some(reservation), Some(runtime)) = (&mut reservation, &self.runtime)
+            {
+                // the batches are copied once more when concatenated
+                match reservation.try_grow(2 * batch_memory_size(&batch)) {
+                    Ok(()) => {}
+                    Err(DataFusionError::ResourcesExhausted(_)) => {
+                        batches.push(batch);
+                        // the collected batches are dropped as they are spilled
+                        reservation.free();
+                        return self
+                            .spill_build_side(
+                                partition, on_left, runtime, batches, stream,
+                            )
+                            .await;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            num_rows += batch.num_rows();
+            batches.push(batch);
+        }
+        let batches_size = batches_memory_size(&batches);
+        let (index, single_batch, key_bounds) =
+            self.build_left_side(on_left, batches, num_rows).await?;
+        for (filter, bounds) in self.probe_filters.iter().zip(key_bounds) {
+            filter.set_bounds(bounds);
+        }
+
+        // the build side is kept until the plan is reset
+        let memory_size = build_side_memory_size(&index, &single_batch);
+        self.metrics.memory.grow(batches_size + memory_size);
+        self.metrics.memory.shrink(batches_size);
+        if let Some(reservation) = &mut reservation {
+            // the memory is already allocated, even if the pool is exceeded
+            if memory_size > reservation.size() {
+                reservation.grow(memory_size - reservation.size());
+            } else {
+                reservation.shrink(reservation.size() - memory_size);
+            }
+        }
+
+        self.metrics.build_time.add_elapsed(start);
+        debug!(
+            "Built build-side of partition {} of hash join containing {} rows in {} ms",
+            partition,
+            num_rows,
+            start.elapsed().as_millis()
+        );
+
+        Ok(BuildSide::InMemory(
+            Arc::new((index, single_batch)),
+            reservation.map(Arc::new),
+        ))
+    }
+
+    /// Spills the build side for `partition` to disk, split into spill
+    /// partitions by the hash of the join keys: the already collected
+    /// `batches` as well as the rest of the left input in `stream`.
+    async fn spill_build_side(
+        &self,
+        partition: usize,
+        on_left: Vec<String>,
+        runtime: &RuntimeEnv,
+        batches: Vec<RecordBatch>,
+        mut stream: SendableRecordBatchStream,
+    ) -> Result<BuildSide> {
+        let start = Instant::now();
+        let mut writer = SpillWriter::try_new(
+            &runtime.disk_manager,
+            &self.left.schema(),
+            on_left,
+            self.spill_partitions,
+        )?;
+        for batch in batches {
+            writer.write(&batch)?;
+        }
+        while let Some(batch) = stream.next().await {
+            writer.write(&batch?)?;
+        }
+        let partitions = writer.finish(&self.metrics, &runtime.disk_manager)?;
+
+        // all partitions probe the spill partitions of a collected left side
+        let num_partitions = match self.mode {
+            PartitionMode::CollectLeft => {
+                self.right.output_partitioning().partition_count()
+            }
+            PartitionMode::Partitioned => 1,
+        };
+        let visited = partitions
+            .iter()
+            .map(|spill_file| {
+                let num_rows = match self.join_type {
+                    JoinType::Left | JoinType::Full | JoinType::Semi | JoinType::Anti => {
+                        spill_file.num_rows
+                    }
+                    JoinType::Inner | JoinType::Right => 0,
+                };
+                Arc::new(VisitedLeftSide::new(num_rows, num_partitions))
+            })
+            .collect();
+
+        self.metrics.build_time.add_elapsed(start);
+        debug!(
+            "Spilled build-side of partition {} of hash join containing {} rows into {} spill partitions in {} ms",
+            partition,
+            partitions.iter().map(|spill_file| spill_file.num_rows).sum::<usize>(),
+            partitions.len(),
+            start.elapsed().as_millis()
+        );
+
+        Ok(BuildSide::Spilled(Arc::new(SpilledBuildSide {
+            partitions,
+            visited,
+        })))
+    }
+
+    /// Drops the build sides kept from previous executions, so that they
+    /// are built again from the left input when partitions are executed
+    /// again, e.g. because the left input changed.
+    ///
+    /// Executing a partition again without resetting the plan reuses its
+    /// build side, including with [`PartitionMode::Partitioned`].
+    pub fn reset(&self) {
+        let mut build_sides = self.build_sides.write().unwrap();
+        for build_side in build_sides.iter() {
+            if let Some(Ok(BuildSide::InMemory(left_data, _))) = build_side.get() {
+                self.metrics
+                    .memory
+                    .shrink(build_side_memory_size(&left_data.0, &left_data.1));
+            }
+        }
+        *build_sides = new_build_sides(build_sides.len());
+        *self.visited_left_sides.lock().unwrap() = vec![None; build_sides.len()];
+        for filter in &self.probe_filters {
+            filter.set_bounds(None);
+        }
+        *self.replanned.lock().unwrap() = None;
+    }
+
+    /// Builds the index over the `batches` of the build side and
+    /// concatenates them into a single batch, and computes the bounds of
+    /// the keys if there are probe filters. This is CPU intensive, so it
+    /// runs off the async worker threads.
+    async fn build_left_side(
+        &self,
+        on_left: Vec<String>,
+        batches: Vec<RecordBatch>,
+        num_rows: usize,
+    ) -> Result<(JoinIndex, RecordBatch, Vec<KeyBounds>)> {
+        let schema = self.left.schema();
+        let direct_index_size = self.direct_index_size()?;
+        let hasher = key_hasher(&schema, &on_left, &self.random_state)?;
+        let filter_probe_side = !self.probe_filters.is_empty();
+        spawn_compute(move || {
+            let key_bounds = if filter_probe_side {
+                key_bounds(&on_left, &batches)
+            } else {
+                vec![]
+            };
+            let (index, single_batch) = build_index(
+                &schema,
+                &on_left,
+                &batches,
+                num_rows,
+                direct_index_size,
+                &hasher,
+            )?;
+            Ok((index, single_batch, key_bounds))
         })
+        .await
+    }
+
+    /// Returns the number of slots of a [`JoinIndex::Direct`] of the build
+    /// side if the join is on a single key of a small integer type, which is
+    /// the same on both sides.
+    fn direct_index_size(&self) -> Result<Option<usize>> {
+        if let [(left, right)] = self.on.as_slice() {
+            let left_schema = self.left.schema();
+            let right_schema = self.right.schema();
+            let left_type = left_schema.field_with_name(left)?.data_type();
+            let right_type = right_schema.field_with_name(right)?.data_type();
+            if left_type == right_type {
+                return Ok(key_domain_size(left_type));
+            }
+        }
+        Ok(None)
     }
 
     /// left (build) side which gets hashed
@@ -181,12 +732,98 @@ impl HashJoinExec {
         &self.join_type
     }
 
+    /// Partitioning mode to use
+    pub fn partition_mode(&self) -> &PartitionMode {
+        &self.mode
+    }
+
+    /// The rows of build side `index` matched so far, shared by all
+    /// partitions probing it
+    fn visited_left_side(&self, index: usize, num_rows: usize) -> Arc<VisitedLeftSide> {
+        let mut visited_left_sides = self.visited_left_sides.lock().unwrap();
+        visited_left_sides[index]
+            .get_or_insert_with(|| {
+                let num_partitions = match self.mode {
+                    PartitionMode::CollectLeft => {
+                        self.right.output_partitioning().partition_count()
+                    }
+                    PartitionMode::Partitioned => 1,
+                };
+                Arc::new(VisitedLeftSide::new(num_rows, num_partitions))
+            })
+            .clone()
+    }
+
+    /// Executes `partition` with a spilled build side: the partition of the
+    /// right input is split into spill partitions like the build side, after
+    /// which one spill partition after the other is read back and joined.
+    async fn execute_spilled(
+        &self,
+        partition: usize,
+        build_side: Arc<SpilledBuildSide>,
+    ) -> Result<SendableRecordBatchStream> {
+        let runtime = self.runtime.clone().ok_or_else(|| {
+            DataFusionError::Internal(
+                "HashJoinExec spilled its build side without a runtime".to_string(),
+            )
+        })?;
+        let on_left = self.on.iter().map(|on| on.0.clone()).collect::<Vec<_>>();
+        let on_right = self.on.iter().map(|on| on.1.clone()).collect::<Vec<_>>();
+
+        let right_schema = self.right.schema();
+        let mut writer = SpillWriter::try_new(
+            &runtime.disk_manager,
+            &right_schema,
+            on_right.clone(),
+            build_side.partitions.len(),
+        )?;
+        let mut stream = self.right.execute(partition).await?;
+        while let Some(batch) = stream.next().await {
+            writer.write(&batch?)?;
+        }
+        let probe_files = writer.finish(&self.metrics, &runtime.disk_manager)?;
+
+        let join = Arc::new(SpilledJoin {
+            partition,
+            build_side,
+            probe_files,
+            schema: self.schema.clone(),
+            left_schema: self.left.schema(),
+            right_schema,
+            on_left,
+            on_right,
+            join_type: self.join_type,
+            column_indices: self.column_indices_from_schema()?,
+            random_state: self.random_state.clone(),
+            direct_index_size: self.direct_index_size()?,
+            metrics: self.metrics.clone(),
+            output_batch_size: self.output_batch_size,
+            memory_pool: runtime.memory_pool.clone(),
+        });
+        let input = stream::iter(0..join.probe_files.len())
+            .then(move |index| {
+                let join = join.clone();
+                async move {
+                    spawn_compute(move || join.join_partition(index))
+                        .await
+                        .map_err(DataFusionError::into_arrow_external_error)
+                }
+            })
+            .try_flatten();
+        Ok(Box::pin(SpilledJoinStream {
+            schema: self.schema.clone(),
+            input: Mutex::new(input.boxed()),
+        }))
+    }
+
     /// Calculates column indices and left/right placement on input / output schemas and jointype
     fn column_indices_from_schema(&self) -> ArrowResult<Vec<ColumnIndex>> {
         let (primary_is_left, primary_schema, secondary_schema) = match self.join_type {
-            JoinType::Inner | JoinType::Left | JoinType::Full => {
-                (true, self.left.schema(), self.right.schema())
-            }
+            JoinType::Inner
+            | JoinType::Left
+            | JoinType::Full
+            | JoinType::Semi
+            | JoinType::Anti => (true, self.left.schema(), self.right.schema()),
             JoinType::Right => (false, self.right.schema(), self.left.schema()),
         };
         let mut column_indices = Vec::with_capacity(self.schema.fields().len());
@@ -226,18 +863,38 @@ impl ExecutionPlan for HashJoinExec {
         vec![self.left.clone(), self.right.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        if children[0] {
+            Err(DataFusionError::Plan(
+                "Hash join can not build its hash table from an unbounded left input"
+                    .to_string(),
+            ))
+        } else {
+            Ok(children[1])
+        }
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            2 => Ok(Arc::new(HashJoinExec::try_new(
-                children[0].clone(),
-                children[1].clone(),
-                &self.on,
-                &self.join_type,
-                self.mode,
-            )?)),
+            2 => {
+                let mut join = HashJoinExec::try_new(
+                    children[0].clone(),
+                    children[1].clone(),
+                    &self.on,
+                    &self.join_type,
+                    self.mode,
+                )?;
+                join.output_batch_size = self.output_batch_size;
+                // scans of the right side may hold the filters already
+                join.probe_filters = self.probe_filters.clone();
+                join.replan_hook = self.replan_hook.clone();
+                join.runtime = self.runtime.clone();
+                join.spill_partitions = self.spill_partitions;
+                Ok(Arc::new(join))
+            }
             _ => Err(DataFusionError::Internal(
                 "HashJoinExec wrong number of children".to_string(),
             )),
@@ -250,123 +907,43 @@ impl ExecutionPlan for HashJoinExec {
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         let on_left = self.on.iter().map(|on| on.0.clone()).collect::<Vec<_>>();
-        // we only want to compute the build side once for PartitionMode::CollectLeft
-        let left_data = {
-            match self.mode {
-                PartitionMode::CollectLeft => {
-                    let mut build_side = self.build_side.lock().await;
-
-                    match build_side.as_ref() {
-                        Some(stream) => stream.clone(),
-                        None => {
-                            let start = Instant::now();
-
-                            // merge all left parts into a single stream
-                            let merge = MergeExec::new(self.left.clone());
-                            let stream = merge.execute(0).await?;
-
-                            // This operation performs 2 steps at once:
-                            // 1. creates a [JoinHashMap] of all batches from the stream
-                            // 2. stores the batches in a vector.
-                            let initial = (0, Vec::new());
-                            let (num_rows, batches) = stream
-                                .try_fold(initial, |mut acc, batch| async {
-                                    acc.0 += batch.num_rows();
-                                    acc.1.push(batch);
-                                    Ok(acc)
-                                })
-                                .await?;
-                            let mut hashmap = JoinHashMap::with_capacity_and_hasher(
-                                num_rows,
-                                IdHashBuilder {},
-                            );
-                            let mut hashes_buffer = Vec::new();
-                            let mut offset = 0;
-                            for batch in batches.iter() {
-                                hashes_buffer.clear();
-                                hashes_buffer.resize(batch.num_rows(), 0);
-                                update_hash(
-                                    &on_left,
-                                    &batch,
-                                    &mut hashmap,
-                                    offset,
-                                    &self.random_state,
-                                    &mut hashes_buffer,
-                                )?;
-                                offset += batch.num_rows();
-                            }
-                            // Merge all batches into a single batch, so we
-                            // can directly index into the arrays
-                            let single_batch =
-                                concat_batches(&self.left.schema(), &batches, num_rows)?;
-
-                            let left_side = Arc::new((hashmap, single_batch));
-
-                            *build_side = Some(left_side.clone());
-
-                            debug!(
-                            "Built build-side of hash join containing {} rows in {} ms",
-                            num_rows,
-                            start.elapsed().as_millis()
-                        );
-
-                            left_side
-                        }
-                    }
-                }
-                PartitionMode::Partitioned => {
-                    let start = Instant::now();
-
-                    // Load 1 partition of left side in memory
-                    let stream = self.left.execute(partition).await?;
-
-                    // This operation performs 2 steps at once:
-                    // 1. creates a [JoinHashMap] of all batches from the stream
-                    // 2. stores the batches in a vector.
-                    let initial = (0, Vec::new());
-                    let (num_rows, batches) = stream
-                        .try_fold(initial, |mut acc, batch| async {
-                            acc.0 += batch.num_rows();
-                            acc.1.push(batch);
-                            Ok(acc)
-                        })
-                        .await?;
-                    let mut hashmap =
-                        JoinHashMap::with_capacity_and_hasher(num_rows, IdHashBuilder {});
-                    let mut hashes_buffer = Vec::new();
-                    let mut offset = 0;
-                    for batch in batches.iter() {
-                        hashes_buffer.clear();
-                        hashes_buffer.resize(batch.num_rows(), 0);
-                        update_hash(
-                            &on_left,
-                            &batch,
-                            &mut hashmap,
-                            offset,
-                            &self.random_state,
-                            &mut hashes_buffer,
-                        )?;
-                        offset += batch.num_rows();
-                    }
-                    // Merge all batches into a single batch, so we
-                    // can directly index into the arrays
-                    let single_batch =
-                        concat_batches(&self.left.schema(), &batches, num_rows)?;
-
-                    let left_side = Arc::new((hashmap, single_batch));
-
-                    debug!(
-                        "Built build-side {} of hash join containing {} rows in {} ms",
-                        partition,
-                        num_rows,
-                        start.elapsed().as_millis()
-                    );
-
-                    left_side
-                }
+        // partitions only contend on the lock while `reset` replaces the
+        // build sides
+        let index = match self.mode {
+            PartitionMode::CollectLeft => 0,
+            PartitionMode::Partitioned => partition,
+        };
+        let build_side = {
+            let build_sides = self.build_sides.read().unwrap();
+            build_sides.get(index).cloned().ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "HashJoinExec has no build side for partition {}",
+                    partition
+                ))
+            })?
+        };
+        // the build side is only computed once, see `HashJoinExec::reset`,
+        // while other partitions using it wait for the outcome
+        let left_data = build_side
+            .get_or_init(|| async {
+                self.collect_build_side(partition, on_left.clone())
+                    .await
+                    .map_err(Arc::new)
+            })
+            .await
+            .clone()
+            .map_err(|e| shared_error(&e))?;
+        let left_data = match left_data {
+            BuildSide::InMemory(left_data, _) => left_data,
+            BuildSide::Spilled(build_side) => {
+                return self.execute_spilled(partition, build_side).await
             }
         };
 
+        if let Some(replanned) = self.replan(&left_data)? {
+            return replanned.execute(partition).await;
+        }
+
         // we have the batches and the hash map with their keys. We can how create a stream
         // over the right that uses this information to issue new batches.
 
@@ -374,10 +951,13 @@ impl ExecutionPlan for HashJoinExec {
         let on_right = self.on.iter().map(|on| on.1.clone()).collect::<Vec<_>>();
 
         let column_indices = self.column_indices_from_schema()?;
+        let hasher = key_hasher(&self.right.schema(), &on_right, &self.random_state)?;
         let num_rows = left_data.1.num_rows();
         let visited_left_side = match self.join_type {
-            JoinType::Left | JoinType::Full => vec![false; num_rows],
-            JoinType::Inner | JoinType::Right => vec![],
+            JoinType::Left | JoinType::Full | JoinType::Semi | JoinType::Anti => {
+                self.visited_left_side(index, num_rows)
+            }
+            JoinType::Inner | JoinType::Right => Arc::new(VisitedLeftSide::new(0, 1)),
         };
         Ok(Box::pin(HashJoinStream {
             schema: self.schema.clone(),
@@ -387,14 +967,13 @@ impl ExecutionPlan for HashJoinExec {
             left_data,
             right: stream,
             column_indices,
-            num_input_batches: 0,
-            num_input_rows: 0,
-            num_output_batches: 0,
-            num_output_rows: 0,
-            join_time: 0,
-            random_state: self.random_state.clone(),
+            metrics: self.metrics.clone(),
+            hasher,
             visited_left_side,
+            output_batch_size: self.output_batch_size,
+            pending: None,
             is_exhausted: false,
+            _reservation: None,
         }))
     }
 
@@ -413,6 +992,93 @@ impl ExecutionPlan for HashJoinExec {
             }
         }
     }
+
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::BUILD_TIME, &self.metrics.build_time);
+        metrics.push(MetricsSet::INPUT_BATCHES, &self.metrics.input_batches);
+        metrics.push("input_rows", &self.metrics.input_rows);
+        metrics.push("output_batches", &self.metrics.output_batches);
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.metrics.output_rows);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.metrics.join_time);
+        metrics.push(MetricsSet::PEAK_MEM_USED, self.metrics.memory.peak());
+        metrics.push(MetricsSet::SPILL_COUNT, &self.metrics.spill_count);
+        metrics.push(MetricsSet::SPILLED_BYTES, &self.metrics.spilled_bytes);
+        metrics
+    }
+}
+
+/// The minimum and maximum value of a join key
+type KeyBounds = Option<(ScalarValue, ScalarValue)>;
+
+/// Computes the bounds of each of the `on` columns of `batches`. There are
+/// no bounds for columns without non-null values, or of types whose minimum
+/// and maximum can not be computed.
+fn key_bounds(on: &[String], batches: &[RecordBatch]) -> Vec<KeyBounds> {
+    on.iter()
+        .map(|name| {
+            let mut bounds: KeyBounds = None;
+            for batch in batches {
+                let column = batch.column(batch.schema().index_of(name).ok()?);
+                let batch_min = min_batch(column).ok()?;
+                let batch_max = max_batch(column).ok()?;
+                bounds = Some(match bounds {
+                    None => (batch_min, batch_max),
+                    Some((lower, upper)) => {
+                        (min(&lower, &batch_min).ok()?, max(&upper, &batch_max).ok()?)
+                    }
+                });
+            }
+            bounds.filter(|(lower, upper)| !lower.is_null() && !upper.is_null())
+        })
+        .collect()
+}
+
+/// Builds the index over the `batches` of a build side with `schema` and
+/// concatenates them into a single batch
+fn build_index(
+    schema: &SchemaRef,
+    on_left: &[String],
+    batches: &[RecordBatch],
+    num_rows: usize,
+    direct_index_size: Option<usize>,
+    hasher: &BatchHasher,
+) -> Result<(JoinIndex, RecordBatch)> {
+    let index = match direct_index_size {
+        Some(size) => {
+            let mut rows = vec![SmallVec::new(); size];
+            let mut offset = 0;
+            for batch in batches.iter() {
+                update_direct_index(&on_left[0], batch, &mut rows, offset)?;
+                offset += batch.num_rows();
+            }
+            JoinIndex::Direct(rows)
+        }
+        None => {
+            let mut hashmap =
+                JoinHashMap::with_capacity_and_hasher(num_rows, IdHashBuilder {});
+            let mut hashes_buffer = Vec::new();
+            let mut offset = 0;
+            for batch in batches.iter() {
+                hashes_buffer.clear();
+                hashes_buffer.resize(batch.num_rows(), 0);
+                update_hash(
+                    on_left,
+                    &batch,
+                    &mut hashmap,
+                    offset,
+                    hasher,
+                    &mut hashes_buffer,
+                )?;
+                offset += batch.num_rows();
+            }
+            JoinIndex::Hash(hashmap)
+        }
+    };
+    // Merge all batches into a single batch, so we
+    // can directly index into the arrays
+    let single_batch = concat_batches(schema, batches, num_rows)?;
+    Ok((index, single_batch))
 }
 
 /// Updates `hash` with new entries from [RecordBatch] evaluated against the expressions `on`,
@@ -422,7 +1088,7 @@ fn update_hash(
     batch: &RecordBatch,
     hash: &mut JoinHashMap,
     offset: usize,
-    random_state: &RandomState,
+    hasher: &BatchHasher,
     hashes_buffer: &mut Vec<u64>,
 ) -> Result<()> {
     // evaluate the keys
@@ -432,7 +1098,7 @@ fn update_hash(
         .collect::<Result<Vec<_>>>()?;
 
     // calculate the hash values
-    let hash_values = create_hashes(&keys_values, &random_state, hashes_buffer)?;
+    let hash_values = hasher.hash(&keys_values, hashes_buffer)?;
 
     // insert hashes to key of the hashmap
     for (row, hash_value) in hash_values.iter().enumerate() {
@@ -468,22 +1134,34 @@ struct HashJoinStream {
     right: SendableRecordBatchStream,
     /// Information of index and left / right placement of columns
     column_indices: Vec<ColumnIndex>,
-    /// number of input batches
-    num_input_batches: usize,
-    /// number of input rows
-    num_input_rows: usize,
-    /// number of batches produced
-    num_output_batches: usize,
-    /// number of rows produced
-    num_output_rows: usize,
-    /// total time for joining probe-side batches to the build-side batches
-    join_time: usize,
-    /// Random state used for hashing initialization
-    random_state: RandomState,
+    /// Metrics of the join, shared with the [`HashJoinExec`]
+    metrics: HashJoinMetrics,
+    /// Hashes the keys of the right batches
+    hasher: BatchHasher,
     /// Keeps track of the left side rows whether they are visited
-    visited_left_side: Vec<bool>, // TODO: use a more memory efficient data structure, https://github.com/apache/arrow-datafusion/issues/240
+    visited_left_side: Arc<VisitedLeftSide>,
+    /// Maximum number of rows of output batches, if any
+    output_batch_size: Option<usize>,
+    /// Join output of the last probe-side batch that hasn't been produced yet
+    pending: Option<PendingOutput>,
     /// There is nothing to process anymore and left side is processed in case of left join
     is_exhausted: bool,
+    /// Memory reserved for the spill partitions read back from disk, if
+    /// any, which is freed when the stream is dropped
+    _reservation: Option<MemoryReservation>,
+}
+
+/// The joined rows of a probe-side batch, as indices into both sides. The
+/// columns of the output are only gathered when an output batch is produced.
+struct PendingOutput {
+    /// The probe-side batch
+    right: RecordBatch,
+    /// Build-side row of every output row, null for unmatched probe-side rows
+    left_indices: UInt64Array,
+    /// Probe-side row of every output row
+    right_indices: UInt32Array,
+    /// Number of output rows already produced
+    offset: usize,
 }
 
 impl RecordBatchStream for HashJoinStream {
@@ -492,6 +1170,107 @@ impl RecordBatchStream for HashJoinStream {
     }
 }
 
+/// Creates the [`BatchHasher`] for the join keys `on` of an input with `schema`
+fn key_hasher(
+    schema: &Schema,
+    on: &[String],
+    random_state: &RandomState,
+) -> Result<BatchHasher> {
+    let data_types = on
+        .iter()
+        .map(|name| Ok(schema.field_with_name(name)?.data_type().clone()))
+        .collect::<Result<Vec<_>>>()?;
+    BatchHasher::try_new(&data_types, random_state.clone())
+}
+
+/// Returns an error of the same kind as `e`, which is shared by all
+/// partitions that waited for the same build side
+fn shared_error(e: &DataFusionError) -> DataFusionError {
+    match e {
+        DataFusionError::NotImplemented(msg) => {
+            DataFusionError::NotImplemented(msg.clone())
+        }
+        DataFusionError::Internal(msg) => DataFusionError::Internal(msg.clone()),
+        DataFusionError::Plan(msg) => DataFusionError::Plan(msg.clone()),
+        DataFusionError::Execution(msg) => DataFusionError::Execution(msg.clone()),
+        DataFusionError::ResourcesExhausted(msg) => {
+            DataFusionError::ResourcesExhausted(msg.clone())
+        }
+        other => DataFusionError::Execution(other.to_string()),
+    }
+}
+
+fn new_build_sides(n: usize) -> BuildSides {
+    (0..n).map(|_| Arc::new(OnceCell::new())).collect()
+}
+
+/// Estimates the memory used by the index and batch of a build side
+fn build_side_memory_size(index: &JoinIndex, batch: &RecordBatch) -> usize {
+    let index_size = match index {
+        JoinIndex::Hash(hashmap) => {
+            let entry_size = std::mem::size_of::<((), SmallVec<[u64; 1]>)>();
+            hashmap.capacity() * entry_size
+        }
+        JoinIndex::Direct(rows) => rows.len() * std::mem::size_of::<SmallVec<[u64; 1]>>(),
+    };
+    index_size + batch_memory_size(batch)
+}
+
+/// Returns the number of distinct values of `data_type` if it is small
+/// enough to index the rows of the build side directly by their key
+fn key_domain_size(data_type: &DataType) -> Option<usize> {
+    match data_type {
+        DataType::Int8 | DataType::UInt8 => Some(1 << 8),
+        DataType::Int16 | DataType::UInt16 => Some(1 << 16),
+        _ => None,
+    }
+}
+
+macro_rules! direct_slots {
+    ($array: ident, $array_type: ident, $native: ty) => {{
+        let array = $array.as_any().downcast_ref::<$array_type>().unwrap();
+        array
+            .iter()
+            .map(|key| key.map(|key| (key as i64 - <$native>::MIN as i64) as usize))
+            .collect()
+    }};
+}
+
+/// Returns the slot of every key of `array` in a [`JoinIndex::Direct`], or
+/// `None` for null keys
+fn direct_slots(array: &ArrayRef) -> Result<Vec<Option<usize>>> {
+    Ok(match array.data_type() {
+        DataType::Int8 => direct_slots!(array, Int8Array, i8),
+        DataType::Int16 => direct_slots!(array, Int16Array, i16),
+        DataType::UInt8 => direct_slots!(array, UInt8Array, u8),
+        DataType::UInt16 => direct_slots!(array, UInt16Array, u16),
+        other => {
+            // This is internal because we should have caught this before.
+            return Err(DataFusionError::Internal(format!(
+                "Unsupported key type {:?} of direct join index",
+                other
+            )));
+        }
+    })
+}
+
+/// Adds the rows of `batch`, which start at `offset` in the build side, to
+/// the direct index `rows` by the value of their key column `on`
+fn update_direct_index(
+    on: &str,
+    batch: &RecordBatch,
+    rows: &mut [SmallVec<[u64; 1]>],
+    offset: usize,
+) -> Result<()> {
+    let keys = col(on).evaluate(batch)?.into_array(batch.num_rows());
+    for (row, slot) in direct_slots(&keys)?.into_iter().enumerate() {
+        if let Some(slot) = slot {
+            rows[slot].push((row + offset) as u64);
+        }
+    }
+    Ok(())
+}
+
 /// Returns a new [RecordBatch] by combining the `left` and `right` according to `indices`.
 /// The resulting batch has [Schema] `schema`.
 /// # Error
@@ -501,10 +1280,10 @@ fn build_batch_from_indices(
     schema: &Schema,
     left: &RecordBatch,
     right: &RecordBatch,
-    left_indices: UInt64Array,
-    right_indices: UInt32Array,
+    left_indices: &UInt64Array,
+    right_indices: &UInt32Array,
     column_indices: &[ColumnIndex],
-) -> ArrowResult<(RecordBatch, UInt64Array)> {
+) -> ArrowResult<RecordBatch> {
     // build the columns of the new [RecordBatch]:
     // 1. pick whether the column is from the left or right
     // 2. based on the pick, `take` items from the different RecordBatches
@@ -513,45 +1292,14 @@ fn build_batch_from_indices(
     for column_index in column_indices {
         let array = if column_index.is_left {
             let array = left.column(column_index.index);
-            compute::take(array.as_ref(), &left_indices, None)?
+            compute::take(array.as_ref(), left_indices, None)?
         } else {
             let array = right.column(column_index.index);
-            compute::take(array.as_ref(), &right_indices, None)?
+            compute::take(array.as_ref(), right_indices, None)?
         };
         columns.push(array);
     }
-    RecordBatch::try_new(Arc::new(schema.clone()), columns).map(|x| (x, left_indices))
-}
-
-#[allow(clippy::too_many_arguments)]
-fn build_batch(
-    batch: &RecordBatch,
-    left_data: &JoinLeftData,
-    on_left: &[String],
-    on_right: &[String],
-    join_type: JoinType,
-    schema: &Schema,
-    column_indices: &[ColumnIndex],
-    random_state: &RandomState,
-) -> ArrowResult<(RecordBatch, UInt64Array)> {
-    let (left_indices, right_indices) = build_join_indexes(
-        &left_data,
-        &batch,
-        join_type,
-        on_left,
-        on_right,
-        random_state,
-    )
-    .unwrap();
-
-    build_batch_from_indices(
-        schema,
-        &left_data.1,
-        batch,
-        left_indices,
-        right_indices,
-        column_indices,
-    )
+    RecordBatch::try_new(Arc::new(schema.clone()), columns)
 }
 
 /// returns a vector with (index from left, index from right).
@@ -587,7 +1335,8 @@ fn build_join_indexes(
     join_type: JoinType,
     left_on: &[String],
     right_on: &[String],
-    random_state: &RandomState,
+    hasher: &BatchHasher,
+    visited_left_side: &VisitedLeftSide,
 ) -> Result<(UInt64Array, UInt32Array)> {
     let keys_values = right_on
         .iter()
@@ -601,29 +1350,78 @@ fn build_join_indexes(
                 .into_array(left_data.1.num_rows()))
         })
         .collect::<Result<Vec<_>>>()?;
+    // rows of the build side that may match each row of `right`
     let hashes_buffer = &mut vec![0; keys_values[0].len()];
-    let hash_values = create_hashes(&keys_values, &random_state, hashes_buffer)?;
-    let left = &left_data.0;
+    let candidates: Vec<Option<&[u64]>> = match &left_data.0 {
+        JoinIndex::Hash(left) => hasher
+            .hash(&keys_values, hashes_buffer)?
+            .iter()
+            .map(|hash_value| {
+                left.raw_entry()
+                    .from_hash(*hash_value, |_| true)
+                    .map(|(_, indices)| indices.as_slice())
+            })
+            .collect(),
+        JoinIndex::Direct(left) => direct_slots(&keys_values[0])?
+            .into_iter()
+            .map(|slot| {
+                slot.map(|slot| left[slot].as_slice())
+                    .filter(|indices| !indices.is_empty())
+            })
+            .collect(),
+    };
+    // keys of a direct index can't collide
+    let check_collisions = matches!(left_data.0, JoinIndex::Hash(_));
 
     match join_type {
+        JoinType::Semi | JoinType::Anti => {
+            // only whether a left row has a match matters, so the left rows
+            // are marked as visited on their first match and never compared
+            // again, and each is emitted at most once
+            let mut left_indices = UInt64Builder::new(0);
+            for (row, indices) in candidates.iter().enumerate() {
+                if let Some(indices) = indices {
+                    for &i in indices.iter() {
+                        if visited_left_side.is_visited(i as usize) {
+                            continue;
+                        }
+                        if !check_collisions
+                            || equal_rows(
+                                i as usize,
+                                row,
+                                &left_join_values,
+                                &keys_values,
+                            )?
+                        {
+                            visited_left_side.visit(i as usize);
+                            left_indices.append_value(i)?;
+                        }
+                    }
+                }
+            }
+            Ok((left_indices.finish(), UInt32Builder::new(0).finish()))
+        }
         JoinType::Inner => {
             // Using a buffer builder to avoid slower normal builder
             let mut left_indices = UInt64BufferBuilder::new(0);
             let mut right_indices = UInt32BufferBuilder::new(0);
 
             // Visit all of the right rows
-            for (row, hash_value) in hash_values.iter().enumerate() {
-                // Get the hash and find it in the build index
-
+            for (row, indices) in candidates.iter().enumerate() {
                 // For every item on the left and right we check if it matches
                 // This possibly contains rows with hash collisions,
                 // So we have to check here whether rows are equal or not
-                if let Some((_, indices)) =
-                    left.raw_entry().from_hash(*hash_value, |_| true)
-                {
-                    for &i in indices {
+                if let Some(indices) = indices {
+                    for &i in indices.iter() {
                         // Check hash collisions
-                        if equal_rows(i as usize, row, &left_join_values, &keys_values)? {
+                        if !check_collisions
+                            || equal_rows(
+                                i as usize,
+                                row,
+                                &left_join_values,
+                                &keys_values,
+                            )?
+                        {
                             left_indices.append(i);
                             right_indices.append(row as u32);
                         }
@@ -649,13 +1447,18 @@ fn build_join_indexes(
             let mut right_indices = UInt32Builder::new(0);
 
             // First visit all of the rows
-            for (row, hash_value) in hash_values.iter().enumerate() {
-                if let Some((_, indices)) =
-                    left.raw_entry().from_hash(*hash_value, |_| true)
-                {
-                    for &i in indices {
+            for (row, indices) in candidates.iter().enumerate() {
+                if let Some(indices) = indices {
+                    for &i in indices.iter() {
                         // Collision check
-                        if equal_rows(i as usize, row, &left_join_values, &keys_values)? {
+                        if !check_collisions
+                            || equal_rows(
+                                i as usize,
+                                row,
+                                &left_join_values,
+                                &keys_values,
+                            )?
+                        {
                             left_indices.append_value(i)?;
                             right_indices.append_value(row as u32)?;
                         }
@@ -668,16 +1471,18 @@ fn build_join_indexes(
             let mut left_indices = UInt64Builder::new(0);
             let mut right_indices = UInt32Builder::new(0);
 
-            for (row, hash_value) in hash_values.iter().enumerate() {
-                match left.raw_entry().from_hash(*hash_value, |_| true) {
-                    Some((_, indices)) => {
-                        for &i in indices {
-                            if equal_rows(
-                                i as usize,
-                                row,
-                                &left_join_values,
-                                &keys_values,
-                            )? {
+            for (row, indices) in candidates.iter().enumerate() {
+                match indices {
+                    Some(indices) => {
+                        for &i in indices.iter() {
+                            if !check_collisions
+                                || equal_rows(
+                                    i as usize,
+                                    row,
+                                    &left_join_values,
+                                    &keys_values,
+                                )?
+                            {
                                 left_indices.append_value(i)?;
                                 right_indices.append_value(row as u32)?;
                             } else {
@@ -730,13 +1535,6 @@ impl BuildHasher for IdHashBuilder {
     }
 }
 
-// Combines two hashes into one hash
-#[inline]
-fn combine_hashes(l: u64, r: u64) -> u64 {
-    let hash = (17 * 37u64).wrapping_add(l);
-    hash.wrapping_mul(37).wrapping_add(r)
-}
-
 macro_rules! equal_rows_elem {
     ($array_type:ident, $l: ident, $r: ident, $left: ident, $right: ident) => {{
         let left_array = $l.as_any().downcast_ref::<$array_type>().unwrap();
@@ -788,343 +1586,25 @@ fn equal_rows(
     err.unwrap_or(Ok(res))
 }
 
-macro_rules! hash_array {
-    ($array_type:ident, $column: ident, $ty: ident, $hashes: ident, $random_state: ident, $multi_col: ident) => {
-        let array = $column.as_any().downcast_ref::<$array_type>().unwrap();
-        if array.null_count() == 0 {
-            if $multi_col {
-                for (i, hash) in $hashes.iter_mut().enumerate() {
-                    *hash = combine_hashes(
-                        $ty::get_hash(&array.value(i), $random_state),
-                        *hash,
-                    );
-                }
-            } else {
-                for (i, hash) in $hashes.iter_mut().enumerate() {
-                    *hash = $ty::get_hash(&array.value(i), $random_state);
-                }
-            }
-        } else {
-            if $multi_col {
-                for (i, hash) in $hashes.iter_mut().enumerate() {
-                    if !array.is_null(i) {
-                        *hash = combine_hashes(
-                            $ty::get_hash(&array.value(i), $random_state),
-                            *hash,
-                        );
-                    }
-                }
-            } else {
-                for (i, hash) in $hashes.iter_mut().enumerate() {
-                    if !array.is_null(i) {
-                        *hash = $ty::get_hash(&array.value(i), $random_state);
-                    }
-                }
-            }
-        }
-    };
-}
-
-macro_rules! hash_array_primitive {
-    ($array_type:ident, $column: ident, $ty: ident, $hashes: ident, $random_state: ident, $multi_col: ident) => {
-        let array = $column.as_any().downcast_ref::<$array_type>().unwrap();
-        let values = array.values();
-
-        if array.null_count() == 0 {
-            if $multi_col {
-                for (hash, value) in $hashes.iter_mut().zip(values.iter()) {
-                    *hash = combine_hashes($ty::get_hash(value, $random_state), *hash);
-                }
-            } else {
-                for (hash, value) in $hashes.iter_mut().zip(values.iter()) {
-                    *hash = $ty::get_hash(value, $random_state)
-                }
-            }
-        } else {
-            if $multi_col {
-                for (i, (hash, value)) in
-                    $hashes.iter_mut().zip(values.iter()).enumerate()
-                {
-                    if !array.is_null(i) {
-                        *hash =
-                            combine_hashes($ty::get_hash(value, $random_state), *hash);
-                    }
-                }
-            } else {
-                for (i, (hash, value)) in
-                    $hashes.iter_mut().zip(values.iter()).enumerate()
-                {
-                    if !array.is_null(i) {
-                        *hash = $ty::get_hash(value, $random_state);
-                    }
-                }
-            }
-        }
-    };
-}
-
-macro_rules! hash_array_float {
-    ($array_type:ident, $column: ident, $ty: ident, $hashes: ident, $random_state: ident, $multi_col: ident) => {
-        let array = $column.as_any().downcast_ref::<$array_type>().unwrap();
-        let values = array.values();
-
-        if array.null_count() == 0 {
-            if $multi_col {
-                for (hash, value) in $hashes.iter_mut().zip(values.iter()) {
-                    *hash = combine_hashes(
-                        $ty::get_hash(&value.to_le_bytes(), $random_state),
-                        *hash,
-                    );
-                }
-            } else {
-                for (hash, value) in $hashes.iter_mut().zip(values.iter()) {
-                    *hash = $ty::get_hash(&value.to_le_bytes(), $random_state)
-                }
-            }
-        } else {
-            if $multi_col {
-                for (i, (hash, value)) in
-                    $hashes.iter_mut().zip(values.iter()).enumerate()
-                {
-                    if !array.is_null(i) {
-                        *hash = combine_hashes(
-                            $ty::get_hash(&value.to_le_bytes(), $random_state),
-                            *hash,
-                        );
-                    }
-                }
-            } else {
-                for (i, (hash, value)) in
-                    $hashes.iter_mut().zip(values.iter()).enumerate()
-                {
-                    if !array.is_null(i) {
-                        *hash = $ty::get_hash(&value.to_le_bytes(), $random_state);
-                    }
-                }
-            }
-        }
-    };
-}
-
-/// Creates hash values for every element in the row based on the values in the columns
-pub fn create_hashes<'a>(
-    arrays: &[ArrayRef],
-    random_state: &RandomState,
-    hashes_buffer: &'a mut Vec<u64>,
-) -> Result<&'a mut Vec<u64>> {
-    // combine hashes with `combine_hashes` if we have more than 1 column
-    let multi_col = arrays.len() > 1;
-
-    for col in arrays {
-        match col.data_type() {
-            DataType::UInt8 => {
-                hash_array_primitive!(
-                    UInt8Array,
-                    col,
-                    u8,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::UInt16 => {
-                hash_array_primitive!(
-                    UInt16Array,
-                    col,
-                    u16,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::UInt32 => {
-                hash_array_primitive!(
-                    UInt32Array,
-                    col,
-                    u32,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::UInt64 => {
-                hash_array_primitive!(
-                    UInt64Array,
-                    col,
-                    u64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Int8 => {
-                hash_array_primitive!(
-                    Int8Array,
-                    col,
-                    i8,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Int16 => {
-                hash_array_primitive!(
-                    Int16Array,
-                    col,
-                    i16,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Int32 => {
-                hash_array_primitive!(
-                    Int32Array,
-                    col,
-                    i32,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Int64 => {
-                hash_array_primitive!(
-                    Int64Array,
-                    col,
-                    i64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Float32 => {
-                hash_array_float!(
-                    Float32Array,
-                    col,
-                    u32,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Float64 => {
-                hash_array_float!(
-                    Float64Array,
-                    col,
-                    u64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Timestamp(TimeUnit::Millisecond, None) => {
-                hash_array_primitive!(
-                    TimestampMillisecondArray,
-                    col,
-                    i64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Timestamp(TimeUnit::Microsecond, None) => {
-                hash_array_primitive!(
-                    TimestampMicrosecondArray,
-                    col,
-                    i64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Timestamp(TimeUnit::Nanosecond, None) => {
-                hash_array_primitive!(
-                    TimestampNanosecondArray,
-                    col,
-                    i64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Date32 => {
-                hash_array_primitive!(
-                    Date32Array,
-                    col,
-                    i32,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Date64 => {
-                hash_array_primitive!(
-                    Date64Array,
-                    col,
-                    i64,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Boolean => {
-                hash_array!(
-                    BooleanArray,
-                    col,
-                    u8,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::Utf8 => {
-                hash_array!(
-                    StringArray,
-                    col,
-                    str,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            DataType::LargeUtf8 => {
-                hash_array!(
-                    LargeStringArray,
-                    col,
-                    str,
-                    hashes_buffer,
-                    random_state,
-                    multi_col
-                );
-            }
-            _ => {
-                // This is internal because we should have caught this before.
-                return Err(DataFusionError::Internal(
-                    "Unsupported data type in hasher".to_string(),
-                ));
-            }
-        }
-    }
-    Ok(hashes_buffer)
-}
-
-// Produces a batch for left-side rows that are not marked as being visited during the whole join
-fn produce_unmatched(
+// Produces a batch for left-side rows that are (`visited`) or are not marked as being
+// visited during the whole join
+fn produce_from_left_side(
     visited_left_side: &[bool],
+    visited: bool,
     schema: &SchemaRef,
     column_indices: &[ColumnIndex],
     left_data: &JoinLeftData,
 ) -> ArrowResult<RecordBatch> {
-    // Find indices which didn't match any right row (are false)
-    let unmatched_indices: Vec<u64> = visited_left_side
+    // Find indices which did (are true) or didn't (are false) match any right row
+    let left_indices: Vec<u64> = visited_left_side
         .iter()
         .enumerate()
-        .filter(|&(_, &value)| !value)
+        .filter(|&(_, &value)| value == visited)
         .map(|(index, _)| index as u64)
         .collect();
 
     // generate batches by taking values from the left side and generating columns filled with null on the right side
-    let indices = UInt64Array::from_iter_values(unmatched_indices);
+    let indices = UInt64Array::from_iter_values(left_indices);
     let num_rows = indices.len();
     let mut columns: Vec<Arc<dyn Array>> = Vec::with_capacity(schema.fields().len());
     for (idx, column_index) in column_indices.iter().enumerate() {
@@ -1138,91 +1618,371 @@ fn produce_unmatched(
 
         columns.push(array);
     }
-    RecordBatch::try_new(schema.clone(), columns)
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+impl HashJoinStream {
+    /// Gathers the columns of the next output batch of the pending join
+    /// output, if any
+    fn next_pending_batch(&mut self) -> Option<ArrowResult<RecordBatch>> {
+        let pending = self.pending.as_mut()?;
+        let start = Instant::now();
+
+        let num_rows = pending.left_indices.len();
+        let len = match self.output_batch_size {
+            Some(output_batch_size) => (num_rows - pending.offset).min(output_batch_size),
+            None => num_rows - pending.offset,
+        };
+        // the build-side indices are copied rather than sliced, as `take`
+        // ignores the offset of the null bitmap of sliced indices
+        let left_indices = (pending.offset..pending.offset + len)
+            .map(|i| {
+                Some(pending.left_indices.value(i))
+                    .filter(|_| pending.left_indices.is_valid(i))
+            })
+            .collect::<UInt64Array>();
+        let right_indices = pending.right_indices.slice(pending.offset, len);
+        pending.offset += len;
+        let result = build_batch_from_indices(
+            &self.schema,
+            &self.left_data.1,
+            &pending.right,
+            &left_indices,
+            right_indices
+                .as_any()
+                .downcast_ref::<UInt32Array>()
+                .unwrap(),
+            &self.column_indices,
+        );
+        if pending.offset >= num_rows {
+            self.pending = None;
+        }
+
+        if let Ok(ref batch) = result {
+            self.metrics.join_time.add_elapsed(start);
+            self.metrics.output_batches.add(1);
+            self.metrics.output_rows.add(batch.num_rows());
+        }
+        Some(result)
+    }
+}
+
+impl Stream for HashJoinStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        // produce the rest of the last probe-side batch first
+        if let Some(result) = self.next_pending_batch() {
+            return std::task::Poll::Ready(Some(result));
+        }
+
+        loop {
+            let maybe_batch = match self.right.poll_next_unpin(cx) {
+                std::task::Poll::Ready(maybe_batch) => maybe_batch,
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            };
+            let result = match maybe_batch {
+                Some(Ok(batch)) => {
+                    let start = Instant::now();
+                    // only the join keys are read to find the joined rows
+                    let result = build_join_indexes(
+                        &self.left_data,
+                        &batch,
+                        self.join_type,
+                        &self.on_left,
+                        &self.on_right,
+                        &self.hasher,
+                        &self.visited_left_side,
+                    );
+                    self.metrics.input_batches.add(1);
+                    self.metrics.input_rows.add(batch.num_rows());
+                    let (left_indices, right_indices) = match result {
+                        Ok(indices) => indices,
+                        Err(e) => {
+                            return std::task::Poll::Ready(Some(Err(
+                                e.into_arrow_external_error()
+                            )))
+                        }
+                    };
+                    self.metrics.join_time.add_elapsed(start);
+
+                    match self.join_type {
+                        JoinType::Left | JoinType::Full => {
+                            left_indices.iter().flatten().for_each(|x| {
+                                self.visited_left_side.visit(x as usize);
+                            });
+                        }
+                        // marked as visited while looking for their matches
+                        JoinType::Semi | JoinType::Anti => {}
+                        JoinType::Inner | JoinType::Right => {}
+                    }
+                    // the rows of semi and anti joins are only known once
+                    // the whole probe side has been seen
+                    if matches!(self.join_type, JoinType::Semi | JoinType::Anti) {
+                        continue;
+                    }
+                    self.pending = Some(PendingOutput {
+                        right: batch,
+                        left_indices,
+                        right_indices,
+                        offset: 0,
+                    });
+                    self.next_pending_batch()
+                }
+                other => {
+                    let start = Instant::now();
+                    // produce the remaining rows of the left side: the unmatched
+                    // ones, or the matched ones of a semi join
+                    let visited = match self.join_type {
+                        JoinType::Left | JoinType::Full | JoinType::Anti => false,
+                        JoinType::Semi => true,
+                        JoinType::Inner | JoinType::Right => {
+                            return std::task::Poll::Ready(other)
+                        }
+                    };
+                    if self.is_exhausted {
+                        return std::task::Poll::Ready(other);
+                    }
+                    self.is_exhausted = true;
+                    // only the last partition probing the build side to
+                    // finish knows all rows of the left side with matches
+                    let visited_left_side = match self.visited_left_side.finish() {
+                        Some(visited_left_side) => visited_left_side,
+                        None => return std::task::Poll::Ready(other),
+                    };
+                    let result = produce_from_left_side(
+                        &visited_left_side,
+                        visited,
+                        &self.schema,
+                        &self.column_indices,
+                        &self.left_data,
+                    );
+                    if let Ok(ref batch) = result {
+                        self.metrics.join_time.add_elapsed(start);
+                        self.metrics.output_batches.add(1);
+                        self.metrics.output_rows.add(batch.num_rows());
+                    }
+                    Some(result)
+                }
+            };
+            return std::task::Poll::Ready(result);
+        }
+    }
+}
+
+/// Hashes the join keys of the rows of spilled join inputs to their spill
+/// partitions. Its seeds differ from those of the join index and of hash
+/// repartitioning, so that rows of the same partition of an input spread over
+/// all spill partitions.
+fn spill_random_state() -> RandomState {
+    RandomState::with_seeds(1, 2, 3, 4)
+}
+
+/// Writes the batches of a join input into one spill file per spill
+/// partition, which each row is written to by the hash of its join keys
+struct SpillWriter {
+    /// The join keys
+    on: Vec<String>,
+    /// Hashes the join keys
+    hasher: BatchHasher,
+    hashes_buffer: Vec<u64>,
+    /// The spill file of each spill partition, its writer and its number of
+    /// rows
+    files: Vec<(NamedTempFile, FileWriter<File>, usize)>,
+}
+
+impl SpillWriter {
+    fn try_new(
+        disk_manager: &DiskManager,
+        schema: &Schema,
+        on: Vec<String>,
+        num_partitions: usize,
+    ) -> Result<Self> {
+        let hasher = key_hasher(schema, &on, &spill_random_state())?;
+        let files = (0..num_partitions)
+            .map(|_| {
+                let file = disk_manager.create_tmp_file()?;
+                let writer = FileWriter::try_new(file.as_file().try_clone()?, schema)?;
+                Ok((file, writer, 0))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            on,
+            hasher,
+            hashes_buffer: vec![],
+            files,
+        })
+    }
+
+    /// Writes the rows of `batch` to the spill files of their spill partitions
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let keys_values = self
+            .on
+            .iter()
+            .map(|name| Ok(col(name).evaluate(batch)?.into_array(batch.num_rows())))
+            .collect::<Result<Vec<_>>>()?;
+        self.hashes_buffer.clear();
+        self.hashes_buffer.resize(batch.num_rows(), 0);
+        let hashes = self.hasher.hash(&keys_values, &mut self.hashes_buffer)?;
+
+        let num_partitions = self.files.len();
+        let mut indices = vec![vec![]; num_partitions];
+        for (row, hash) in hashes.iter().enumerate() {
+            indices[(*hash % num_partitions as u64) as usize].push(row as u64);
+        }
+        for ((_, writer, num_rows), indices) in self.files.iter_mut().zip(indices) {
+            if indices.is_empty() {
+                continue;
+            }
+            let indices = UInt64Array::from(indices);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|column| compute::take(column.as_ref(), &indices, None))
+                .collect::<ArrowResult<Vec<_>>>()?;
+            writer.write(&RecordBatch::try_new(batch.schema(), columns)?)?;
+            *num_rows += indices.len();
+        }
+        Ok(())
+    }
+
+    /// Finishes the spill files, recording the spill in `metrics` and
+    /// `disk_manager`
+    fn finish(
+        self,
+        metrics: &HashJoinMetrics,
+        disk_manager: &DiskManager,
+    ) -> Result<Vec<SpillFile>> {
+        let mut spilled_bytes = 0;
+        let files = self
+            .files
+            .into_iter()
+            .map(|(file, mut writer, num_rows)| {
+                writer.finish()?;
+                // flushes the buffered writes
+                drop(writer);
+                spilled_bytes += file.as_file().metadata()?.len() as usize;
+                Ok(SpillFile { file, num_rows })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        metrics.spill_count.add(1);
+        metrics.spilled_bytes.add(spilled_bytes);
+        disk_manager.record_spill(spilled_bytes);
+        Ok(files)
+    }
+}
+
+/// Reads all batches of `spill_file` back into memory
+fn read_spill_file(spill_file: &SpillFile) -> Result<Vec<RecordBatch>> {
+    let reader = FileReader::try_new(spill_file.file.reopen()?)?;
+    Ok(reader.collect::<ArrowResult<Vec<_>>>()?)
+}
+
+/// Joins the spill partitions of a spilled build side with those of a
+/// partition of the right input
+struct SpilledJoin {
+    /// The partition of the right input
+    partition: usize,
+    build_side: Arc<SpilledBuildSide>,
+    /// The spill file of each spill partition of the right input
+    probe_files: Vec<SpillFile>,
+    schema: SchemaRef,
+    left_schema: SchemaRef,
+    right_schema: SchemaRef,
+    on_left: Vec<String>,
+    on_right: Vec<String>,
+    join_type: JoinType,
+    column_indices: Vec<ColumnIndex>,
+    random_state: RandomState,
+    direct_index_size: Option<usize>,
+    metrics: HashJoinMetrics,
+    output_batch_size: Option<usize>,
+    /// Pool to reserve the memory of the spill partitions read back from
+    memory_pool: Arc<dyn MemoryPool>,
+}
+
+impl SpilledJoin {
+    /// Reads spill partition `index` of both sides back into memory and
+    /// joins them. This is CPU intensive and blocks on reading the files.
+    fn join_partition(&self, index: usize) -> Result<HashJoinStream> {
+        let start = Instant::now();
+        let build_file = &self.build_side.partitions[index];
+        let left_batches = read_spill_file(build_file)?;
+        let right_batches = read_spill_file(&self.probe_files[index])?;
+
+        // spill partitions are not split any further, so their memory is
+        // reserved even if this exceeds the pool
+        let mut reservation = MemoryConsumer::new(format!(
+            "HashJoinExec[{}] spill partition {}",
+            self.partition, index
+        ))
+        .register(&self.memory_pool);
+        let left_size = batches_memory_size(&left_batches);
+        reservation.grow(2 * left_size + batches_memory_size(&right_batches));
+
+        let hasher = key_hasher(&self.left_schema, &self.on_left, &self.random_state)?;
+        let (join_index, single_batch) = build_index(
+            &self.left_schema,
+            &self.on_left,
+            &left_batches,
+            build_file.num_rows,
+            self.direct_index_size,
+            &hasher,
+        )?;
+        drop(left_batches);
+        reservation.shrink(2 * left_size);
+        reservation.grow(build_side_memory_size(&join_index, &single_batch));
+        self.metrics.build_time.add_elapsed(start);
+
+        let right = SizedRecordBatchStream::new(
+            self.right_schema.clone(),
+            right_batches.into_iter().map(Arc::new).collect(),
+        );
+        Ok(HashJoinStream {
+            schema: self.schema.clone(),
+            on_left: self.on_left.clone(),
+            on_right: self.on_right.clone(),
+            join_type: self.join_type,
+            left_data: Arc::new((join_index, single_batch)),
+            right: Box::pin(right),
+            column_indices: self.column_indices.clone(),
+            metrics: self.metrics.clone(),
+            hasher: key_hasher(&self.right_schema, &self.on_right, &self.random_state)?,
+            visited_left_side: self.build_side.visited[index].clone(),
+            output_batch_size: self.output_batch_size,
+            pending: None,
+            is_exhausted: false,
+            _reservation: Some(reservation),
+        })
+    }
 }
 
-impl Stream for HashJoinStream {
+/// The output of a partition of a join with a spilled build side, joining
+/// one spill partition after the other
+struct SpilledJoinStream {
+    schema: SchemaRef,
+    /// The joined spill partitions, in a mutex as record batch streams are
+    /// `Sync`
+    input: Mutex<BoxStream<'static, ArrowResult<RecordBatch>>>,
+}
+
+impl Stream for SpilledJoinStream {
     type Item = ArrowResult<RecordBatch>;
 
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        self.right
-            .poll_next_unpin(cx)
-            .map(|maybe_batch| match maybe_batch {
-                Some(Ok(batch)) => {
-                    let start = Instant::now();
-                    let result = build_batch(
-                        &batch,
-                        &self.left_data,
-                        &self.on_left,
-                        &self.on_right,
-                        self.join_type,
-                        &self.schema,
-                        &self.column_indices,
-                        &self.random_state,
-                    );
-                    self.num_input_batches += 1;
-                    self.num_input_rows += batch.num_rows();
-                    if let Ok((ref batch, ref left_side)) = result {
-                        self.join_time += start.elapsed().as_millis() as usize;
-                        self.num_output_batches += 1;
-                        self.num_output_rows += batch.num_rows();
-
-                        match self.join_type {
-                            JoinType::Left | JoinType::Full => {
-                                left_side.iter().flatten().for_each(|x| {
-                                    self.visited_left_side[x as usize] = true;
-                                });
-                            }
-                            JoinType::Inner | JoinType::Right => {}
-                        }
-                    }
-                    Some(result.map(|x| x.0))
-                }
-                other => {
-                    let start = Instant::now();
-                    // For the left join, produce rows for unmatched rows
-                    match self.join_type {
-                        JoinType::Left | JoinType::Full if !self.is_exhausted => {
-                            let result = produce_unmatched(
-                                &self.visited_left_side,
-                                &self.schema,
-                                &self.column_indices,
-                                &self.left_data,
-                            );
-                            if let Ok(ref batch) = result {
-                                self.num_input_batches += 1;
-                                self.num_input_rows += batch.num_rows();
-                                if let Ok(ref batch) = result {
-                                    self.join_time +=
-                                        start.elapsed().as_millis() as usize;
-                                    self.num_output_batches += 1;
-                                    self.num_output_rows += batch.num_rows();
-                                }
-                            }
-                            self.is_exhausted = true;
-                            return Some(result);
-                        }
-                        JoinType::Left
-                        | JoinType::Full
-                        | JoinType::Inner
-                        | JoinType::Right => {}
-                    }
+        self.input.get_mut().unwrap().poll_next_unpin(cx)
+    }
+}
 
-                    debug!(
-                        "Processed {} probe-side input batches containing {} rows and \
-                        produced {} output batches containing {} rows in {} ms",
-                        self.num_input_batches,
-                        self.num_input_rows,
-                        self.num_output_batches,
-                        self.num_output_rows,
-                        self.join_time
-                    );
-                    other
-                }
-            })
+impl RecordBatchStream for SpilledJoinStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
     }
 }
 
@@ -1230,11 +1990,15 @@ impl Stream for HashJoinStream {
 mod tests {
     use crate::{
         assert_batches_sorted_eq,
-        physical_plan::{common, memory::MemoryExec},
+        physical_plan::{common, memory::MemoryExec, stream::StreamExec},
         test::{build_table_i32, columns},
     };
 
     use super::*;
+    use crate::execution::memory_pool::GreedyMemoryPool;
+    use crate::execution::runtime_env::RuntimeConfig;
+    use arrow::datatypes::Field;
+    use arrow::error::ArrowError;
     use std::sync::Arc;
 
     fn build_table(
@@ -1293,6 +2057,92 @@ mod tests {
         ];
         assert_batches_sorted_eq!(expected, &batches);
 
+        let metrics = join.metrics();
+        assert_eq!(metrics.input_batches(), Some(1));
+        assert_eq!(metrics.output_rows(), Some(3));
+        assert!(metrics.build_time().unwrap() > 0);
+        assert!(metrics.peak_mem_used().unwrap() > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn partitioned_build_side_is_reused_until_reset() -> Result<()> {
+        // the left side can only be read once
+        let batch = build_table_i32(
+            ("a1", &vec![1, 2, 3]),
+            ("b1", &vec![4, 5, 5]),
+            ("c1", &vec![7, 8, 9]),
+        );
+        let schema = batch.schema();
+        let stream = futures::stream::iter(vec![Ok(batch)]).boxed();
+        let left = Arc::new(StreamExec::new(
+            Arc::new(std::sync::Mutex::new(Some(stream))),
+            schema,
+            None,
+        ));
+        let right = build_table(
+            ("a2", &vec![10, 20, 30]),
+            ("b1", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let on = vec![("b1".to_string(), "b1".to_string())];
+        let join = HashJoinExec::try_new(
+            left,
+            right,
+            &on,
+            &JoinType::Inner,
+            PartitionMode::Partitioned,
+        )?;
+
+        for _ in 0..2 {
+            let batches = common::collect(join.execute(0).await?).await?;
+            assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+        }
+        assert!(join.metrics().peak_mem_used().unwrap() > 0);
+
+        join.reset();
+        let err = join.execute(0).await.err().expect("build side is rebuilt");
+        assert!(err.to_string().contains("already read"), "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn build_side_error_is_shared() -> Result<()> {
+        // the left side fails, and can only be read once
+        let schema =
+            build_table_i32(("a1", &vec![1]), ("b1", &vec![4]), ("c1", &vec![7]))
+                .schema();
+        let stream = futures::stream::iter(vec![Err(ArrowError::ComputeError(
+            "left side failed".to_string(),
+        ))])
+        .boxed();
+        let left = Arc::new(StreamExec::new(
+            Arc::new(std::sync::Mutex::new(Some(stream))),
+            schema,
+            None,
+        ));
+        let right = build_table_i32(
+            ("a2", &vec![10, 20, 30]),
+            ("b1", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let right_schema = right.schema();
+        let right = Arc::new(MemoryExec::try_new(
+            &[vec![right.clone()], vec![right]],
+            right_schema,
+            None,
+        )?);
+        let join = join(left, right, &[("b1", "b1")], &JoinType::Inner)?;
+
+        // both partitions get the error of building the build side once
+        let (first, second) = futures::join!(join.execute(0), join.execute(1));
+        for result in vec![first, second] {
+            let err = result.err().expect("build side fails");
+            assert!(err.to_string().contains("left side failed"), "{}", err);
+        }
+
         Ok(())
     }
 
@@ -1666,6 +2516,110 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn join_semi_and_anti() -> Result<()> {
+        let left = build_table(
+            ("a1", &vec![1, 2, 3, 4]),
+            ("b1", &vec![4, 5, 7, 5]), // 7 does not exist on the right
+            ("c1", &vec![7, 8, 9, 10]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20, 30, 40]),
+            ("b1", &vec![4, 5, 6, 5]), // 5 matches twice
+            ("c2", &vec![70, 80, 90, 100]),
+        );
+        let on = &[("b1", "b1")];
+
+        let semi_join = join(left.clone(), right.clone(), on, &JoinType::Semi)?;
+
+        assert_eq!(columns(&semi_join.schema()), vec!["a1", "b1", "c1"]);
+
+        let stream = semi_join.execute(0).await?;
+        let batches = common::collect(stream).await?;
+
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 1  | 4  | 7  |",
+            "| 2  | 5  | 8  |",
+            "| 4  | 5  | 10 |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        let anti_join = join(left, right, on, &JoinType::Anti)?;
+
+        assert_eq!(columns(&anti_join.schema()), vec!["a1", "b1", "c1"]);
+
+        let stream = anti_join.execute(0).await?;
+        let batches = common::collect(stream).await?;
+
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 3  | 7  | 9  |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_semi_and_anti_multiple_probe_partitions() -> Result<()> {
+        let left = build_table(
+            ("a1", &vec![1, 2, 3, 4]),
+            ("b1", &vec![4, 5, 7, 6]), // 7 does not exist on the right
+            ("c1", &vec![7, 8, 9, 10]),
+        );
+        // 5 matches in both partitions, 4 and 6 only in one of them
+        let right_1 = build_table_i32(
+            ("a2", &vec![10, 20]),
+            ("b1", &vec![4, 5]),
+            ("c2", &vec![70, 80]),
+        );
+        let right_2 = build_table_i32(
+            ("a2", &vec![30, 40]),
+            ("b1", &vec![5, 6]),
+            ("c2", &vec![90, 100]),
+        );
+        let schema = right_1.schema();
+        let right: Arc<dyn ExecutionPlan> = Arc::new(MemoryExec::try_new(
+            &[vec![right_1], vec![right_2]],
+            schema,
+            None,
+        )?);
+        let on = &[("b1", "b1")];
+
+        let semi_join = join(left.clone(), right.clone(), on, &JoinType::Semi)?;
+        let batches = crate::physical_plan::collect(Arc::new(semi_join)).await?;
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 1  | 4  | 7  |",
+            "| 2  | 5  | 8  |",
+            "| 4  | 6  | 10 |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        let anti_join = join(left, right, on, &JoinType::Anti)?;
+        let batches = crate::physical_plan::collect(Arc::new(anti_join)).await?;
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 3  | 7  | 9  |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn join_right_one() -> Result<()> {
         let left = build_table(
@@ -1740,6 +2694,109 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn join_right_output_batch_size() -> Result<()> {
+        let left = build_table(
+            ("a1", &vec![1, 2, 3]),
+            ("b1", &vec![4, 5, 5]),
+            ("c1", &vec![7, 8, 9]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20, 30]),
+            ("b2", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let join = join(left, right, &[("b1", "b2")], &JoinType::Right)?
+            .with_output_batch_size(2);
+
+        let mut stream = join.execute(0).await?;
+        let first = stream.next().await.unwrap()?;
+        assert_eq!(first.num_rows(), 2);
+        // the rows of later output batches aren't gathered yet
+        assert_eq!(join.metrics().output_rows(), Some(2));
+
+        let mut batches = vec![first];
+        batches.extend(common::collect(stream).await?);
+        assert_eq!(
+            batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            vec![2, 2]
+        );
+
+        let expected = vec![
+            "+----+----+----+----+----+----+",
+            "| a1 | b1 | c1 | a2 | b2 | c2 |",
+            "+----+----+----+----+----+----+",
+            "|    |    |    | 30 | 6  | 90 |",
+            "| 1  | 4  | 7  | 10 | 4  | 70 |",
+            "| 2  | 5  | 8  | 20 | 5  | 80 |",
+            "| 3  | 5  | 9  | 20 | 5  | 80 |",
+            "+----+----+----+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    fn build_table_i16_key(
+        key: (&str, Vec<Option<i16>>),
+        value: (&str, Vec<i32>),
+    ) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(key.0, DataType::Int16, true),
+            Field::new(value.0, DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int16Array::from(key.1)),
+                Arc::new(Int32Array::from(value.1)),
+            ],
+        )
+        .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    #[tokio::test]
+    async fn join_full_small_key() -> Result<()> {
+        let left = build_table_i16_key(
+            ("a1", vec![Some(1), Some(2), None, Some(-3)]),
+            ("b1", vec![10, 20, 30, 40]),
+        );
+        let right = build_table_i16_key(
+            ("a2", vec![Some(1), Some(1), None, Some(5), Some(-3)]),
+            ("b2", vec![100, 200, 300, 400, 500]),
+        );
+        let join = join(left, right, &[("a1", "a2")], &JoinType::Full)?;
+
+        let stream = join.execute(0).await?;
+        let batches = common::collect(stream).await?;
+
+        // the build side is indexed by the values of the key
+        let build_side = join.build_sides.read().unwrap()[0].clone();
+        let left_data = match build_side.get().unwrap().as_ref().unwrap() {
+            BuildSide::InMemory(left_data, _) => left_data.clone(),
+            BuildSide::Spilled(_) => panic!("build side was spilled"),
+        };
+        assert!(matches!(left_data.0, JoinIndex::Direct(_)));
+
+        let expected = vec![
+            "+----+----+----+-----+",
+            "| a1 | b1 | a2 | b2  |",
+            "+----+----+----+-----+",
+            "|    |    |    | 300 |",
+            "|    |    | 5  | 400 |",
+            "|    | 30 |    |     |",
+            "| -3 | 40 | -3 | 500 |",
+            "| 1  | 10 | 1  | 100 |",
+            "| 1  | 10 | 1  | 200 |",
+            "| 2  | 20 |    |     |",
+            "+----+----+----+-----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        Ok(())
+    }
+
     #[test]
     fn join_with_hash_collision() -> Result<()> {
         let mut hashmap_left = HashMap::with_capacity_and_hasher(2, IdHashBuilder {});
@@ -1774,14 +2831,15 @@ mod tests {
             ("c", &vec![30, 40]),
         );
 
-        let left_data = JoinLeftData::new((hashmap_left, left));
+        let left_data = JoinLeftData::new((JoinIndex::Hash(hashmap_left), left));
         let (l, r) = build_join_indexes(
             &left_data,
             &right,
             JoinType::Inner,
             &["a".to_string()],
             &["a".to_string()],
-            &random_state,
+            &BatchHasher::try_new(&[DataType::Int32], random_state)?,
+            &VisitedLeftSide::new(0, 1),
         )?;
 
         let mut left_ids = UInt64Builder::new(0);
@@ -1799,4 +2857,194 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn probe_filters_get_build_key_bounds() -> Result<()> {
+        let left = build_table_two_batches(
+            ("a1", &vec![1, 2, 3]),
+            ("b1", &vec![4, 5, 7]),
+            ("c1", &vec![7, 8, 9]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20, 30]),
+            ("b1", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let on = &[("b1", "b1")];
+
+        // unmatched rows of the right side are part of the output
+        let right_join = join(left.clone(), right.clone(), on, &JoinType::Right)?;
+        assert!(right_join.probe_filters().is_empty());
+
+        let join = join(left, right, on, &JoinType::Inner)?;
+        let filters = join.probe_filters();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].column(), "b1");
+        assert_eq!(filters[0].bounds(), None);
+
+        common::collect(join.execute(0).await?).await?;
+        assert_eq!(
+            filters[0].bounds(),
+            Some((ScalarValue::Int32(Some(4)), ScalarValue::Int32(Some(7))))
+        );
+
+        join.reset();
+        assert_eq!(filters[0].bounds(), None);
+
+        Ok(())
+    }
+
+    /// A runtime spilling to `dir` whose memory pool is too small for any
+    /// build side
+    fn spilling_runtime(dir: &std::path::Path) -> Arc<RuntimeEnv> {
+        Arc::new(RuntimeEnv::new(
+            RuntimeConfig::new()
+                .with_local_dirs(vec![dir.to_owned()])
+                .with_memory_pool(Arc::new(GreedyMemoryPool::new(1))),
+        ))
+    }
+
+    /// The number of spill files in the temporary directories in `dir`
+    fn count_spill_files(dir: &std::path::Path) -> Result<usize> {
+        let mut count = 0;
+        for temp_dir in std::fs::read_dir(dir)? {
+            count += std::fs::read_dir(temp_dir?.path())?.count();
+        }
+        Ok(count)
+    }
+
+    #[tokio::test]
+    async fn spill_build_side_exceeding_memory_pool() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let runtime = spilling_runtime(dir.path());
+        let left = build_table(
+            ("a1", &vec![1, 2, 3]),
+            ("b1", &vec![4, 5, 5]),
+            ("c1", &vec![7, 8, 9]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20, 30]),
+            ("b1", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let join = join(left, right, &[("b1", "b1")], &JoinType::Inner)?
+            .with_runtime(runtime.clone())
+            .with_spill_partitions(4);
+
+        let batches = common::collect(join.execute(0).await?).await?;
+        let expected = vec![
+            "+----+----+----+----+----+",
+            "| a1 | b1 | c1 | a2 | c2 |",
+            "+----+----+----+----+----+",
+            "| 1  | 4  | 7  | 10 | 70 |",
+            "| 2  | 5  | 8  | 20 | 80 |",
+            "| 3  | 5  | 9  | 20 | 80 |",
+            "+----+----+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // the build side and the probe side were spilled
+        let metrics = join.metrics();
+        assert_eq!(metrics.spill_count(), Some(2));
+        let spilled_bytes = metrics.spilled_bytes().unwrap();
+        assert!(spilled_bytes > 0);
+        assert_eq!(runtime.disk_manager.spilled_bytes(), spilled_bytes);
+        assert_eq!(runtime.memory_pool.reserved(), 0);
+
+        // the spilled build side is kept until the join is reset
+        assert_eq!(count_spill_files(dir.path())?, 4);
+        join.reset();
+        assert_eq!(count_spill_files(dir.path())?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spilled_semi_and_anti_multiple_probe_partitions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let runtime = spilling_runtime(dir.path());
+        let left = build_table(
+            ("a1", &vec![1, 2, 3, 4]),
+            ("b1", &vec![4, 5, 7, 6]), // 7 does not exist on the right
+            ("c1", &vec![7, 8, 9, 10]),
+        );
+        // 5 matches in both partitions, 4 and 6 only in one of them
+        let right_1 = build_table_i32(
+            ("a2", &vec![10, 20]),
+            ("b1", &vec![4, 5]),
+            ("c2", &vec![70, 80]),
+        );
+        let right_2 = build_table_i32(
+            ("a2", &vec![30, 40]),
+            ("b1", &vec![5, 6]),
+            ("c2", &vec![90, 100]),
+        );
+        let schema = right_1.schema();
+        let right: Arc<dyn ExecutionPlan> = Arc::new(MemoryExec::try_new(
+            &[vec![right_1], vec![right_2]],
+            schema,
+            None,
+        )?);
+        let on = &[("b1", "b1")];
+
+        let semi_join = join(left.clone(), right.clone(), on, &JoinType::Semi)?
+            .with_runtime(runtime.clone())
+            .with_spill_partitions(3);
+        let semi_join = Arc::new(semi_join);
+        let batches = crate::physical_plan::collect(semi_join.clone()).await?;
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 1  | 4  | 7  |",
+            "| 2  | 5  | 8  |",
+            "| 4  | 6  | 10 |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        // the build side and both probe-side partitions were spilled
+        assert_eq!(semi_join.metrics().spill_count(), Some(3));
+
+        let anti_join = join(left, right, on, &JoinType::Anti)?
+            .with_runtime(runtime.clone())
+            .with_spill_partitions(3);
+        let batches = crate::physical_plan::collect(Arc::new(anti_join)).await?;
+        let expected = vec![
+            "+----+----+----+",
+            "| a1 | b1 | c1 |",
+            "+----+----+----+",
+            "| 3  | 7  | 9  |",
+            "+----+----+----+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        assert_eq!(runtime.memory_pool.reserved(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn build_side_reserves_memory_until_reset() -> Result<()> {
+        let runtime = Arc::new(RuntimeEnv::default());
+        let left = build_table(
+            ("a1", &vec![1, 2, 3]),
+            ("b1", &vec![4, 5, 5]),
+            ("c1", &vec![7, 8, 9]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20, 30]),
+            ("b1", &vec![4, 5, 6]),
+            ("c2", &vec![70, 80, 90]),
+        );
+        let join = join(left, right, &[("b1", "b1")], &JoinType::Inner)?
+            .with_runtime(runtime.clone());
+
+        common::collect(join.execute(0).await?).await?;
+        assert_eq!(join.metrics().spill_count(), Some(0));
+        assert!(runtime.memory_pool.reserved() > 0);
+
+        join.reset();
+        assert_eq!(runtime.memory_pool.reserved(), 0);
+
+        Ok(())
+    }
 }
@@ -18,9 +18,30 @@
 //! Functionality used both on logical and physical plans
 
 use crate::error::{DataFusionError, Result};
+use ahash::RandomState;
 use arrow::datatypes::{Field, Schema};
 use std::collections::HashSet;
 
+pub use super::hashing::{create_hashes, BatchHasher};
+
+/// Seeds of the [`RandomState`] that rows are hashed with to assign them to the
+/// partitions of a [`Partitioning::Hash`](super::Partitioning::Hash).
+///
+/// Rows with equal keys are assigned to the same partition by every process
+/// using the same seeds and number of partitions, which allows engines that
+/// distribute DataFusion plans to co-partition data across processes. The
+/// default seeds are all zero and do not change between releases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HashSeeds(pub [u64; 4]);
+
+impl HashSeeds {
+    /// Returns the [`RandomState`] to pass to [`create_hashes`]
+    pub fn random_state(&self) -> RandomState {
+        let [k0, k1, k2, k3] = self.0;
+        RandomState::with_seeds(k0, k1, k2, k3)
+    }
+}
+
 /// All valid types of joins.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum JoinType {
@@ -32,6 +53,10 @@ pub enum JoinType {
     Right,
     /// Full Join
     Full,
+    /// Semi Join, the rows of the left side that have a match on the right
+    Semi,
+    /// Anti Join, the rows of the left side that have no match on the right
+    Anti,
 }
 
 /// The on clause of the join, as vector of (left, right) columns.
@@ -130,6 +155,11 @@ pub fn build_join_schema(
             // left then right
             left_fields.chain(right_fields).cloned().collect()
         }
+        JoinType::Semi | JoinType::Anti => {
+            // only the rows of the left side are returned, without the
+            // columns of the right side
+            left.fields().clone()
+        }
     };
     Schema::new(fields)
 }
@@ -0,0 +1,444 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Hashing of the rows of [`RecordBatch`](arrow::record_batch::RecordBatch)es by
+//! the values of their key columns, as used by hash joins and hash repartitioning.
+
+use ahash::{CallHasher, RandomState};
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, GenericStringArray, PrimitiveArray,
+    StringOffsetSizeTrait,
+};
+use arrow::datatypes::{
+    ArrowPrimitiveType, DataType, Date32Type, Date64Type, Float32Type, Float64Type,
+    Int16Type, Int32Type, Int64Type, Int8Type, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow::util::bit_util;
+use std::fmt;
+
+use crate::error::{DataFusionError, Result};
+
+/// Number of rows of fixed-width columns that are hashed at once. This is the
+/// number of validity bits in a word of the null bitmap, and small enough for
+/// the hashes of a chunk to stay in registers or L1 cache.
+const CHUNK_SIZE: usize = 64;
+
+/// Hashes the values of a column into the hashes of its rows, combining them
+/// with the existing hashes if the last argument is `true`
+type HashColumn = fn(&dyn Array, &RandomState, &mut [u64], bool);
+
+/// A plan for hashing the rows of batches by key columns of given data types.
+///
+/// The hash function of each column is chosen once when the plan is created,
+/// rather than by matching on the [`DataType`] of every column of every
+/// batch. Rows whose key is null keep their previous hash. Creating a
+/// `BatchHasher` for the key types of an input and reusing it for all of its
+/// batches produces the same hashes as calling [`create_hashes`] per batch.
+#[derive(Clone)]
+pub struct BatchHasher {
+    data_types: Vec<DataType>,
+    columns: Vec<HashColumn>,
+    random_state: RandomState,
+}
+
+impl fmt::Debug for BatchHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchHasher")
+            .field("data_types", &self.data_types)
+            .finish()
+    }
+}
+
+impl BatchHasher {
+    /// Creates a plan for hashing rows by key columns of `data_types`, or
+    /// fails if any of the types can not be hashed.
+    pub fn try_new(data_types: &[DataType], random_state: RandomState) -> Result<Self> {
+        let columns = data_types
+            .iter()
+            .map(hash_column_fn)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            data_types: data_types.to_vec(),
+            columns,
+            random_state,
+        })
+    }
+
+    /// The data types of the key columns
+    pub fn data_types(&self) -> &[DataType] {
+        &self.data_types
+    }
+
+    /// Hashes every row of the key columns `arrays` into `hashes_buffer`,
+    /// which must already have one entry per row.
+    pub fn hash<'a>(
+        &self,
+        arrays: &[ArrayRef],
+        hashes_buffer: &'a mut Vec<u64>,
+    ) -> Result<&'a mut Vec<u64>> {
+        if arrays.len() != self.columns.len() {
+            return Err(DataFusionError::Internal(format!(
+                "Expected {} key columns to hash, got {}",
+                self.columns.len(),
+                arrays.len()
+            )));
+        }
+        // combine hashes with `combine_hashes` if we have more than 1 column
+        let multi_col = arrays.len() > 1;
+
+        for ((array, hash_column), data_type) in
+            arrays.iter().zip(&self.columns).zip(&self.data_types)
+        {
+            if array.len() != hashes_buffer.len() {
+                return Err(DataFusionError::Internal(format!(
+                    "Expected key column of {} rows to hash, got {}",
+                    hashes_buffer.len(),
+                    array.len()
+                )));
+            }
+            if array.data_type() != data_type {
+                return Err(DataFusionError::Internal(format!(
+                    "Expected key column of type {:?} to hash, got {:?}",
+                    data_type,
+                    array.data_type()
+                )));
+            }
+            hash_column(array.as_ref(), &self.random_state, hashes_buffer, multi_col);
+        }
+        Ok(hashes_buffer)
+    }
+}
+
+/// Creates hash values for every element in the row based on the values in the columns
+///
+/// This chooses the hash function of every column for every call; use a
+/// [`BatchHasher`] to hash many batches of the same schema.
+pub fn create_hashes<'a>(
+    arrays: &[ArrayRef],
+    random_state: &RandomState,
+    hashes_buffer: &'a mut Vec<u64>,
+) -> Result<&'a mut Vec<u64>> {
+    let data_types = arrays
+        .iter()
+        .map(|array| array.data_type().clone())
+        .collect::<Vec<_>>();
+    BatchHasher::try_new(&data_types, random_state.clone())?.hash(arrays, hashes_buffer)
+}
+
+// Combines two hashes into one hash
+#[inline]
+pub(crate) fn combine_hashes(l: u64, r: u64) -> u64 {
+    let hash = (17 * 37u64).wrapping_add(l);
+    hash.wrapping_mul(37).wrapping_add(r)
+}
+
+fn hash_column_fn(data_type: &DataType) -> Result<HashColumn> {
+    Ok(match data_type {
+        DataType::UInt8 => hash_fixed_width::<UInt8Type>,
+        DataType::UInt16 => hash_fixed_width::<UInt16Type>,
+        DataType::UInt32 => hash_fixed_width::<UInt32Type>,
+        DataType::UInt64 => hash_fixed_width::<UInt64Type>,
+        DataType::Int8 => hash_fixed_width::<Int8Type>,
+        DataType::Int16 => hash_fixed_width::<Int16Type>,
+        DataType::Int32 => hash_fixed_width::<Int32Type>,
+        DataType::Int64 => hash_fixed_width::<Int64Type>,
+        DataType::Float32 => hash_fixed_width::<Float32Type>,
+        DataType::Float64 => hash_fixed_width::<Float64Type>,
+        DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            hash_fixed_width::<TimestampMillisecondType>
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            hash_fixed_width::<TimestampMicrosecondType>
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            hash_fixed_width::<TimestampNanosecondType>
+        }
+        DataType::Date32 => hash_fixed_width::<Date32Type>,
+        DataType::Date64 => hash_fixed_width::<Date64Type>,
+        DataType::Boolean => hash_boolean,
+        DataType::Utf8 => hash_string::<i32>,
+        DataType::LargeUtf8 => hash_string::<i64>,
+        _ => {
+            // This is internal because we should have caught this before.
+            return Err(DataFusionError::Internal(format!(
+                "Unsupported data type in hasher: {:?}",
+                data_type
+            )));
+        }
+    })
+}
+
+/// Hashing of the native values of fixed-width arrays
+trait HashValue: ArrowPrimitiveType {
+    fn hash_value(value: &Self::Native, random_state: &RandomState) -> u64;
+}
+
+macro_rules! hash_value_integer {
+    ($($arrow_type: ident => $ty: ident),*) => {
+        $(
+            impl HashValue for $arrow_type {
+                #[inline]
+                fn hash_value(value: &$ty, random_state: &RandomState) -> u64 {
+                    $ty::get_hash(value, random_state)
+                }
+            }
+        )*
+    };
+}
+
+hash_value_integer!(
+    UInt8Type => u8,
+    UInt16Type => u16,
+    UInt32Type => u32,
+    UInt64Type => u64,
+    Int8Type => i8,
+    Int16Type => i16,
+    Int32Type => i32,
+    Int64Type => i64,
+    TimestampMillisecondType => i64,
+    TimestampMicrosecondType => i64,
+    TimestampNanosecondType => i64,
+    Date32Type => i32,
+    Date64Type => i64
+);
+
+// floats are hashed by their bytes, as they don't implement `Hash`
+impl HashValue for Float32Type {
+    #[inline]
+    fn hash_value(value: &f32, random_state: &RandomState) -> u64 {
+        u32::get_hash(&value.to_le_bytes(), random_state)
+    }
+}
+
+impl HashValue for Float64Type {
+    #[inline]
+    fn hash_value(value: &f64, random_state: &RandomState) -> u64 {
+        u64::get_hash(&value.to_le_bytes(), random_state)
+    }
+}
+
+/// Hashes a fixed-width column in chunks of [`CHUNK_SIZE`] rows. The hashes
+/// of a chunk are computed for all of its values, including null slots, and
+/// then merged into `hashes` without branching on the validity of each row,
+/// which lets the compiler vectorize the combining and masking.
+fn hash_fixed_width<T: HashValue>(
+    array: &dyn Array,
+    random_state: &RandomState,
+    hashes: &mut [u64],
+    multi_col: bool,
+) {
+    let array = array.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+    let data = array.data_ref();
+    let validity = if array.null_count() == 0 {
+        None
+    } else {
+        data.null_buffer().map(|buffer| buffer.as_slice())
+    };
+    let mut chunk_hashes = [0u64; CHUNK_SIZE];
+
+    let chunks = hashes
+        .chunks_mut(CHUNK_SIZE)
+        .zip(array.values().chunks(CHUNK_SIZE));
+    for (chunk_index, (hashes, values)) in chunks.enumerate() {
+        let chunk_hashes = &mut chunk_hashes[..values.len()];
+        for (chunk_hash, value) in chunk_hashes.iter_mut().zip(values) {
+            *chunk_hash = T::hash_value(value, random_state);
+        }
+        if multi_col {
+            for (chunk_hash, hash) in chunk_hashes.iter_mut().zip(hashes.iter()) {
+                *chunk_hash = combine_hashes(*chunk_hash, *hash);
+            }
+        }
+        match validity {
+            None => hashes.copy_from_slice(chunk_hashes),
+            Some(bits) => {
+                let offset = data.offset() + chunk_index * CHUNK_SIZE;
+                for (i, (hash, chunk_hash)) in
+                    hashes.iter_mut().zip(chunk_hashes.iter()).enumerate()
+                {
+                    // all ones for valid rows, all zeros for null rows
+                    let mask =
+                        (bit_util::get_bit(bits, offset + i) as u64).wrapping_neg();
+                    *hash = (*chunk_hash & mask) | (*hash & !mask);
+                }
+            }
+        }
+    }
+}
+
+fn hash_boolean(
+    array: &dyn Array,
+    random_state: &RandomState,
+    hashes: &mut [u64],
+    multi_col: bool,
+) {
+    let array = array.as_any().downcast_ref::<BooleanArray>().unwrap();
+    for (i, hash) in hashes.iter_mut().enumerate() {
+        if array.is_valid(i) {
+            let value_hash = u8::get_hash(&array.value(i), random_state);
+            *hash = if multi_col {
+                combine_hashes(value_hash, *hash)
+            } else {
+                value_hash
+            };
+        }
+    }
+}
+
+fn hash_string<OffsetSize: StringOffsetSizeTrait>(
+    array: &dyn Array,
+    random_state: &RandomState,
+    hashes: &mut [u64],
+    multi_col: bool,
+) {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericStringArray<OffsetSize>>()
+        .unwrap();
+    for (i, hash) in hashes.iter_mut().enumerate() {
+        if array.is_valid(i) {
+            let value_hash = str::get_hash(&array.value(i), random_state);
+            *hash = if multi_col {
+                combine_hashes(value_hash, *hash)
+            } else {
+                value_hash
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use std::sync::Arc;
+
+    /// Hashes the rows one at a time, as a reference for the chunked hashing
+    fn hash_rows(arrays: &[ArrayRef], random_state: &RandomState) -> Vec<u64> {
+        let mut hashes = vec![0; arrays[0].len()];
+        for (row, hash) in hashes.iter_mut().enumerate() {
+            for array in arrays {
+                if array.is_null(row) {
+                    continue;
+                }
+                let value_hash = match array.data_type() {
+                    DataType::Int32 => {
+                        let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+                        i32::get_hash(&array.value(row), random_state)
+                    }
+                    DataType::Float64 => {
+                        let array =
+                            array.as_any().downcast_ref::<Float64Array>().unwrap();
+                        u64::get_hash(&array.value(row).to_le_bytes(), random_state)
+                    }
+                    DataType::Utf8 => {
+                        let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+                        str::get_hash(&array.value(row), random_state)
+                    }
+                    _ => unreachable!(),
+                };
+                *hash = if arrays.len() > 1 {
+                    combine_hashes(value_hash, *hash)
+                } else {
+                    value_hash
+                };
+            }
+        }
+        hashes
+    }
+
+    fn int_array(len: i32) -> ArrayRef {
+        Arc::new(
+            (0..len)
+                .map(|i| if i % 7 == 3 { None } else { Some(i * 31) })
+                .collect::<Int32Array>(),
+        )
+    }
+
+    #[test]
+    fn fixed_width_with_nulls() -> Result<()> {
+        let random_state = RandomState::with_seeds(0, 0, 0, 0);
+        // more than one chunk, with a partial last chunk
+        let array = int_array(150);
+        let hasher = BatchHasher::try_new(&[DataType::Int32], random_state.clone())?;
+        let mut hashes = vec![0; array.len()];
+        hasher.hash(&[array.clone()], &mut hashes)?;
+        assert_eq!(hashes, hash_rows(&[array.clone()], &random_state));
+        // null rows keep their previous hash
+        assert_eq!(hashes[3], 0);
+
+        // slices whose null bitmap is not aligned to a chunk
+        let sliced = array.slice(5, 100);
+        let mut hashes = vec![0; sliced.len()];
+        hasher.hash(&[sliced.clone()], &mut hashes)?;
+        assert_eq!(hashes, hash_rows(&[sliced], &random_state));
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_columns() -> Result<()> {
+        let random_state = RandomState::with_seeds(1, 2, 3, 4);
+        let strings = (0..100)
+            .map(|i| {
+                if i % 5 == 0 {
+                    None
+                } else {
+                    Some(format!("s{}", i))
+                }
+            })
+            .collect::<StringArray>();
+        let floats = (0..100)
+            .map(|i| {
+                if i % 3 == 0 {
+                    None
+                } else {
+                    Some(i as f64 / 2.0)
+                }
+            })
+            .collect::<Float64Array>();
+        let arrays: Vec<ArrayRef> =
+            vec![int_array(100), Arc::new(strings), Arc::new(floats)];
+        let expected = hash_rows(&arrays, &random_state);
+
+        let hasher = BatchHasher::try_new(
+            &[DataType::Int32, DataType::Utf8, DataType::Float64],
+            random_state.clone(),
+        )?;
+        let mut hashes = vec![0; 100];
+        hasher.hash(&arrays, &mut hashes)?;
+        assert_eq!(hashes, expected);
+
+        let mut hashes = vec![0; 100];
+        create_hashes(&arrays, &random_state, &mut hashes)?;
+        assert_eq!(hashes, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn mismatched_columns() -> Result<()> {
+        let random_state = RandomState::with_seeds(0, 0, 0, 0);
+        assert!(BatchHasher::try_new(&[DataType::Null], random_state.clone()).is_err());
+
+        let hasher = BatchHasher::try_new(&[DataType::Int64], random_state)?;
+        let mut hashes = vec![0; 10];
+        assert!(hasher.hash(&[int_array(10)], &mut hashes).is_err());
+        assert!(hasher.hash(&[], &mut hashes).is_err());
+        Ok(())
+    }
+}
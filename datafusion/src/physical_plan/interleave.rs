@@ -0,0 +1,289 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the interleave plan, which combines inputs that are hash partitioned in
+//! the same way without collapsing their partitions
+
+use std::any::Any;
+use std::sync::Arc;
+
+use futures::stream::{self, StreamExt};
+use futures::Stream;
+
+use async_trait::async_trait;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+
+use pin_project_lite::pin_project;
+
+/// Interleave execution plan combines inputs that share the same hash partitioning.
+///
+/// Output partition `i` contains the batches of partition `i` of every input, so unlike
+/// [`UnionExec`](super::union::UnionExec) the number of partitions stays the same and
+/// the output keeps the hash partitioning of its inputs.
+#[derive(Debug)]
+pub struct InterleaveExec {
+    /// Input execution plans
+    inputs: Vec<Arc<dyn ExecutionPlan>>,
+    /// Whether all batches of an input are emitted before those of the next input
+    preserve_order: bool,
+}
+
+impl InterleaveExec {
+    /// Create a new InterleaveExec, failing if the inputs are not hash partitioned
+    /// in the same way
+    pub fn try_new(inputs: Vec<Arc<dyn ExecutionPlan>>) -> Result<Self> {
+        if !can_interleave(&inputs) {
+            return Err(DataFusionError::Plan(
+                "InterleaveExec requires inputs with the same schema and hash partitioning"
+                    .to_owned(),
+            ));
+        }
+        Ok(InterleaveExec {
+            inputs,
+            preserve_order: false,
+        })
+    }
+
+    /// Emit the batches of each partition in the order of the inputs, rather than
+    /// as they become available
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Whether the order of the inputs is preserved
+    pub fn preserve_order(&self) -> bool {
+        self.preserve_order
+    }
+
+    /// Input execution plans
+    pub fn inputs(&self) -> &[Arc<dyn ExecutionPlan>] {
+        &self.inputs
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for InterleaveExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.inputs[0].schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        self.inputs.clone()
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children.iter().any(|unbounded| *unbounded))
+    }
+
+    /// All inputs share the same partitioning, which is also the output partitioning
+    fn output_partitioning(&self) -> Partitioning {
+        self.inputs[0].output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(
+            InterleaveExec::try_new(children)?.with_preserve_order(self.preserve_order),
+        ))
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let partition_count = self.output_partitioning().partition_count();
+        if partition >= partition_count {
+            return Err(DataFusionError::Internal(format!(
+                "InterleaveExec invalid partition {} (expected less than {})",
+                partition, partition_count
+            )));
+        }
+
+        let mut streams = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            streams.push(input.execute(partition).await?);
+        }
+
+        let input: SendableRecordBatchStream = if self.preserve_order {
+            Box::pin(InterleaveStream {
+                schema: self.schema(),
+                input: stream::iter(streams).flatten(),
+            })
+        } else {
+            Box::pin(InterleaveStream {
+                schema: self.schema(),
+                input: stream::select_all(streams),
+            })
+        };
+        Ok(input)
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "InterleaveExec")
+            }
+        }
+    }
+}
+
+/// Returns true if `inputs` have the same schema and are all hash partitioned on the
+/// same expressions into the same number of partitions, so that they can be combined
+/// by an [`InterleaveExec`]
+pub fn can_interleave(inputs: &[Arc<dyn ExecutionPlan>]) -> bool {
+    let (first, rest) = match inputs.split_first() {
+        Some(split) => split,
+        None => return false,
+    };
+    if rest.iter().any(|input| input.schema() != first.schema()) {
+        return false;
+    }
+
+    let (first_exprs, first_count) = match first.output_partitioning() {
+        Partitioning::Hash(exprs, n) => (exprs, n),
+        _ => return false,
+    };
+    let first_exprs = first_exprs
+        .iter()
+        .map(|expr| expr.to_string())
+        .collect::<Vec<_>>();
+
+    rest.iter().all(|input| match input.output_partitioning() {
+        Partitioning::Hash(exprs, n) => {
+            n == first_count
+                && exprs.len() == first_exprs.len()
+                && exprs
+                    .iter()
+                    .zip(first_exprs.iter())
+                    .all(|(expr, first)| &expr.to_string() == first)
+        }
+        _ => false,
+    })
+}
+
+pin_project! {
+    struct InterleaveStream<S> {
+        schema: SchemaRef,
+        #[pin]
+        input: S,
+    }
+}
+
+impl<S: Stream<Item = ArrowResult<RecordBatch>>> Stream for InterleaveStream<S> {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.input.poll_next(cx)
+    }
+}
+
+impl<S: Stream<Item = ArrowResult<RecordBatch>>> RecordBatchStream
+    for InterleaveStream<S>
+{
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::csv::{CsvExec, CsvReadOptions};
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::repartition::RepartitionExec;
+    use crate::test;
+
+    fn hash_partitioned_csv(
+        column: &str,
+        partitions: usize,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = test::aggr_test_schema();
+        let path = test::create_partitioned_csv("aggregate_test_100.csv", 2)?;
+        let csv = CsvExec::try_new(
+            &path,
+            CsvReadOptions::new().schema(&schema),
+            None,
+            1024,
+            None,
+        )?;
+        Ok(Arc::new(RepartitionExec::try_new(
+            Arc::new(csv),
+            Partitioning::Hash(vec![col(column)], partitions),
+        )?))
+    }
+
+    #[tokio::test]
+    async fn interleave() -> Result<()> {
+        let inputs = vec![
+            hash_partitioned_csv("c1", 3)?,
+            hash_partitioned_csv("c1", 3)?,
+        ];
+        assert!(can_interleave(&inputs));
+
+        let interleave = InterleaveExec::try_new(inputs)?;
+        assert_eq!(interleave.output_partitioning().partition_count(), 3);
+
+        let mut row_count = 0;
+        for partition in 0..3 {
+            let batches = common::collect(interleave.execute(partition).await?).await?;
+            row_count += batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+        }
+        // both inputs contain all 100 rows
+        assert_eq!(row_count, 200);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interleave_mismatched_partitioning() -> Result<()> {
+        let different_count = vec![
+            hash_partitioned_csv("c1", 3)?,
+            hash_partitioned_csv("c1", 4)?,
+        ];
+        assert!(!can_interleave(&different_count));
+
+        let different_exprs = vec![
+            hash_partitioned_csv("c1", 3)?,
+            hash_partitioned_csv("c2", 3)?,
+        ];
+        assert!(!can_interleave(&different_exprs));
+        assert!(InterleaveExec::try_new(different_exprs).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the execution plan that drives its input on a dedicated runtime,
+//! so that reading from files or object stores is not held up by CPU heavy
+//! operators (and vice versa).
+
+use std::any::Any;
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use futures::Stream;
+
+use async_trait::async_trait;
+
+use arrow::record_batch::RecordBatch;
+use arrow::{
+    datatypes::SchemaRef,
+    error::{ArrowError, Result as ArrowResult},
+};
+use tokio::runtime::Handle;
+
+use super::common::AbortOnDropSingle;
+use super::RecordBatchStream;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+
+use super::SendableRecordBatchStream;
+use pin_project_lite::pin_project;
+
+/// Executes its input on a dedicated tokio runtime, typically one reserved
+/// for IO, and forwards the resulting batches to the runtime polling this
+/// plan.
+///
+/// The physical planner wraps table scans in an `IoRuntimeExec` if an IO
+/// runtime is configured in the
+/// [`RuntimeConfig`](crate::execution::runtime_env::RuntimeConfig).
+#[derive(Debug)]
+pub struct IoRuntimeExec {
+    /// Input execution plan
+    input: Arc<dyn ExecutionPlan>,
+    /// Runtime to execute the input on
+    handle: Handle,
+}
+
+impl IoRuntimeExec {
+    /// Create a new IoRuntimeExec executing `input` on the runtime of `handle`
+    pub fn new(input: Arc<dyn ExecutionPlan>, handle: Handle) -> Self {
+        Self { input, handle }
+    }
+
+    /// Input execution plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for IoRuntimeExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(IoRuntimeExec::new(
+                children[0].clone(),
+                self.handle.clone(),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "IoRuntimeExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        // a small buffer lets the input read ahead while batches are processed
+        let (mut sender, receiver) = mpsc::channel::<ArrowResult<RecordBatch>>(2);
+
+        let input = self.input.clone();
+        let join_handle = self.handle.spawn(async move {
+            let mut stream = match input.execute(partition).await {
+                Err(e) => {
+                    // If send fails, plan being torn
+                    // down, no place to send the error
+                    let arrow_error = ArrowError::ExternalError(Box::new(e));
+                    sender.send(Err(arrow_error)).await.ok();
+                    return;
+                }
+                Ok(stream) => stream,
+            };
+
+            while let Some(item) = stream.next().await {
+                // stop reading once the receiving side was dropped
+                if sender.send(item).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Box::pin(IoRuntimeStream {
+            input: receiver,
+            schema: self.schema(),
+            _drop_helper: AbortOnDropSingle::new(join_handle),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "IoRuntimeExec")
+            }
+        }
+    }
+}
+
+pin_project! {
+    struct IoRuntimeStream {
+        schema: SchemaRef,
+        #[pin]
+        input: mpsc::Receiver<ArrowResult<RecordBatch>>,
+        _drop_helper: AbortOnDropSingle<()>,
+    }
+}
+
+impl Stream for IoRuntimeStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.input.poll_next(cx)
+    }
+}
+
+impl RecordBatchStream for IoRuntimeStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::csv::{CsvExec, CsvReadOptions};
+    use crate::test;
+
+    #[tokio::test]
+    async fn execute_on_io_runtime() -> Result<()> {
+        let io_runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("io")
+            .build()?;
+
+        let schema = test::aggr_test_schema();
+        let path = test::create_partitioned_csv("aggregate_test_100.csv", 2)?;
+        let csv = CsvExec::try_new(
+            &path,
+            CsvReadOptions::new().schema(&schema),
+            None,
+            1024,
+            None,
+        )?;
+
+        let exec = IoRuntimeExec::new(Arc::new(csv), io_runtime.handle().clone());
+        assert_eq!(exec.output_partitioning().partition_count(), 2);
+
+        let mut row_count = 0;
+        for partition in 0..2 {
+            let batches = common::collect(exec.execute(partition).await?).await?;
+            row_count += batches.iter().map(|b| b.num_rows()).sum::<usize>();
+        }
+        assert_eq!(row_count, 100);
+
+        // a runtime must not be dropped from within another runtime
+        io_runtime.shutdown_background();
+        Ok(())
+    }
+}
@@ -19,7 +19,8 @@
 use async_trait::async_trait;
 use futures::Stream;
 
-use super::{common, source::Source, ExecutionPlan, Partitioning, RecordBatchStream};
+use super::common::{self, ListingOptions};
+use super::{source::Source, ExecutionPlan, Partitioning, RecordBatchStream};
 use crate::error::{DataFusionError, Result};
 use arrow::json::reader::{infer_json_schema_from_iterator, ValueIter};
 use arrow::{
@@ -49,6 +50,16 @@ pub struct NdJsonReadOptions<'a> {
     /// File extension; only files with this extension are selected for data input.
     /// Defaults to ".json".
     pub file_extension: &'a str,
+
+    /// How the files of a directory are listed
+    pub listing: ListingOptions,
+
+    /// An optional schema whose columns override the types of the inferred
+    /// columns of the same name
+    pub schema_hints: Option<SchemaRef>,
+
+    /// Infer integer columns as `Float64`. Defaults to false.
+    pub integers_as_floats: bool,
 }
 
 impl<'a> Default for NdJsonReadOptions<'a> {
@@ -57,10 +68,26 @@ impl<'a> Default for NdJsonReadOptions<'a> {
             schema: None,
             schema_infer_max_records: 1000,
             file_extension: ".json",
+            listing: ListingOptions::default(),
+            schema_hints: None,
+            integers_as_floats: false,
         }
     }
 }
 
+impl<'a> NdJsonReadOptions<'a> {
+    /// Resolve the types of a schema inferred from JSON files according to
+    /// these options
+    pub(crate) fn resolve_inferred_schema(&self, schema: &Schema) -> Schema {
+        common::resolve_inferred_schema(
+            schema,
+            self.schema_hints.as_deref(),
+            self.integers_as_floats,
+            true,
+        )
+    }
+}
+
 trait SeekRead: Read + Seek {}
 
 impl<T: Seek + Read> SeekRead for T {}
@@ -74,6 +101,7 @@ pub struct NdJsonExec {
     file_extension: String,
     batch_size: usize,
     limit: Option<usize>,
+    read_ahead: usize,
 }
 
 impl NdJsonExec {
@@ -87,7 +115,11 @@ impl NdJsonExec {
     ) -> Result<Self> {
         let file_extension = options.file_extension.to_string();
 
-        let filenames = common::build_file_list(path, &file_extension)?;
+        let filenames = common::build_file_list_with_options(
+            path,
+            &file_extension,
+            &options.listing,
+        )?;
 
         if filenames.is_empty() {
             return Err(DataFusionError::Execution(format!(
@@ -97,12 +129,14 @@ impl NdJsonExec {
             )));
         }
 
-        let schema = match options.schema {
-            Some(s) => s,
-            None => Arc::new(NdJsonExec::try_infer_schema(
-                filenames.clone(),
-                Some(options.schema_infer_max_records),
-            )?),
+        let schema = match &options.schema {
+            Some(s) => s.clone(),
+            None => Arc::new(options.resolve_inferred_schema(
+                &NdJsonExec::try_infer_schema(
+                    filenames.clone(),
+                    Some(options.schema_infer_max_records),
+                )?,
+            )),
         };
 
         let projected_schema = match &projection {
@@ -123,6 +157,7 @@ impl NdJsonExec {
             projected_schema,
             batch_size,
             limit,
+            read_ahead: common::DEFAULT_READ_AHEAD,
         })
     }
     /// Create a new execution plan for reading from a reader
@@ -158,6 +193,7 @@ impl NdJsonExec {
             projected_schema,
             batch_size,
             limit,
+            read_ahead: common::DEFAULT_READ_AHEAD,
         })
     }
 
@@ -196,6 +232,19 @@ impl NdJsonExec {
         self.limit
     }
 
+    /// Customize the number of batches each partition reads ahead of its
+    /// consumer, [`common::DEFAULT_READ_AHEAD`] by default. With 0, batches
+    /// are read on demand.
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Number of batches each partition reads ahead of its consumer
+    pub fn read_ahead(&self) -> usize {
+        self.read_ahead
+    }
+
     /// Infer schema for given CSV dataset
     pub fn try_infer_schema(
         mut filenames: Vec<String>,
@@ -261,6 +310,7 @@ impl ExecutionPlan for NdJsonExec {
                 batch_size: self.batch_size,
                 limit: self.limit,
                 file_extension: self.file_extension.clone(),
+                read_ahead: self.read_ahead,
             }))
         } else {
             Err(DataFusionError::Internal(
@@ -289,10 +339,8 @@ impl ExecutionPlan for NdJsonExec {
             Source::PartitionedFiles { filenames, .. } => {
                 let file = File::open(&filenames[partition])?;
 
-                Ok(Box::pin(NdJsonStream::new(
-                    builder.build(file)?,
-                    self.limit,
-                )))
+                let stream = NdJsonStream::new(builder.build(file)?, self.limit);
+                Ok(common::read_ahead(Box::pin(stream), self.read_ahead))
             }
             Source::Reader(rdr) => {
                 if partition != 0 {
@@ -301,7 +349,8 @@ impl ExecutionPlan for NdJsonExec {
                             .to_string(),
                     ))
                 } else if let Some(rdr) = rdr.lock().unwrap().take() {
-                    Ok(Box::pin(NdJsonStream::new(builder.build(rdr)?, self.limit)))
+                    let stream = NdJsonStream::new(builder.build(rdr)?, self.limit);
+                    Ok(common::read_ahead(Box::pin(stream), self.read_ahead))
                 } else {
                     Err(DataFusionError::Execution(
                         "Error reading CSV: Data can only be read a single time when the source is a reader"
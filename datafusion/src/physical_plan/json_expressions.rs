@@ -0,0 +1,226 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! JSON expressions, which extract values from JSON documents stored in Utf8
+//! columns
+
+use std::sync::Arc;
+
+use crate::error::{DataFusionError, Result};
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray,
+};
+use arrow::datatypes::DataType;
+use serde_json::Value;
+
+/// An element of the path to a value of a JSON document
+enum PathElement<'a> {
+    /// The value of a key of an object
+    Key(&'a str),
+    /// The item at an index of an array, counted from the end if negative
+    Index(i64),
+}
+
+/// Returns the path element of the row `row` of the argument `arg`
+fn path_element(arg: &ArrayRef, row: usize) -> Result<Option<PathElement<'_>>> {
+    if arg.is_null(row) {
+        return Ok(None);
+    }
+    match arg.data_type() {
+        DataType::Utf8 => {
+            let keys = arg.as_any().downcast_ref::<StringArray>().unwrap();
+            Ok(Some(PathElement::Key(keys.value(row))))
+        }
+        DataType::Int64 => {
+            let indexes = arg.as_any().downcast_ref::<Int64Array>().unwrap();
+            Ok(Some(PathElement::Index(indexes.value(row))))
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Unsupported JSON path element of type {:?}",
+            other
+        ))),
+    }
+}
+
+fn get<'a>(value: &'a Value, element: &PathElement) -> Option<&'a Value> {
+    match (value, element) {
+        (Value::Object(object), PathElement::Key(key)) => object.get(*key),
+        (Value::Array(array), PathElement::Index(index)) => {
+            let index = if *index < 0 {
+                array.len() as i64 + index
+            } else {
+                *index
+            };
+            if index < 0 {
+                None
+            } else {
+                array.get(index as usize)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Extracts the value at the path `args[1..]` from each JSON document of
+/// `args[0]` and converts it by `f`. The result is null for null documents,
+/// invalid JSON and paths that do not exist.
+fn json_extract<T>(
+    args: &[ArrayRef],
+    f: impl Fn(&Value) -> Option<T>,
+) -> Result<Vec<Option<T>>> {
+    let documents = args[0]
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal("JSON documents must be of type Utf8".to_string())
+        })?;
+    (0..documents.len())
+        .map(|row| {
+            if documents.is_null(row) {
+                return Ok(None);
+            }
+            let document = match serde_json::from_str::<Value>(documents.value(row)) {
+                Ok(document) => document,
+                Err(_) => return Ok(None),
+            };
+            let mut value = &document;
+            for arg in &args[1..] {
+                value = match path_element(arg, row)? {
+                    Some(element) => match get(value, &element) {
+                        Some(value) => value,
+                        None => return Ok(None),
+                    },
+                    None => return Ok(None),
+                };
+            }
+            Ok(f(value))
+        })
+        .collect()
+}
+
+/// Returns the value at a path of a JSON document as JSON.
+/// json_get('{"a": [1, "b"]}', 'a', 1) = '"b"'
+pub fn json_get(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = json_extract(args, |value| Some(value.to_string()))?;
+    Ok(Arc::new(
+        values.iter().map(|v| v.as_deref()).collect::<StringArray>(),
+    ))
+}
+
+/// Returns the value at a path of a JSON document as text, i.e. strings
+/// without quotes and other values as JSON.
+/// json_get_str('{"a": [1, "b"]}', 'a', 1) = 'b'
+pub fn json_get_str(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = json_extract(args, |value| match value {
+        Value::Null => None,
+        Value::String(value) => Some(value.clone()),
+        value => Some(value.to_string()),
+    })?;
+    Ok(Arc::new(
+        values.iter().map(|v| v.as_deref()).collect::<StringArray>(),
+    ))
+}
+
+/// Returns the integer at a path of a JSON document, null for other values.
+/// json_get_int('{"a": 1}', 'a') = 1
+pub fn json_get_int(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = json_extract(args, Value::as_i64)?;
+    Ok(Arc::new(Int64Array::from(values)))
+}
+
+/// Returns the number at a path of a JSON document, null for other values.
+/// json_get_float('{"a": 1.5}', 'a') = 1.5
+pub fn json_get_float(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = json_extract(args, Value::as_f64)?;
+    Ok(Arc::new(Float64Array::from(values)))
+}
+
+/// Returns the boolean at a path of a JSON document, null for other values.
+/// json_get_bool('{"a": true}', 'a') = true
+pub fn json_get_bool(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = json_extract(args, Value::as_bool)?;
+    Ok(Arc::new(BooleanArray::from(values)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(path: Vec<ArrayRef>) -> Vec<ArrayRef> {
+        let documents: ArrayRef = Arc::new(StringArray::from(vec![
+            Some(r#"{"a": {"b": [1, 2.5, "c", true, null]}}"#),
+            Some(r#"{"a": 1}"#),
+            Some("not json"),
+            None,
+        ]));
+        let mut args = vec![documents];
+        args.extend(path);
+        args
+    }
+
+    fn path(key: &str, index: i64) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(StringArray::from(vec![Some("a"); 4])),
+            Arc::new(StringArray::from(vec![Some(key); 4])),
+            Arc::new(Int64Array::from(vec![Some(index); 4])),
+        ]
+    }
+
+    #[test]
+    fn test_json_get() -> Result<()> {
+        let result =
+            json_get(&args(vec![Arc::new(StringArray::from(vec![Some("a"); 4]))]))?;
+        let expected = StringArray::from(vec![
+            Some(r#"{"b":[1,2.5,"c",true,null]}"#),
+            Some("1"),
+            None,
+            None,
+        ]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        let result = json_get_str(&args(path("b", 2)))?;
+        let expected = StringArray::from(vec![Some("c"), None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        // negative indexes count from the end
+        let result = json_get_str(&args(path("b", -1)))?;
+        let expected = StringArray::from(vec![None, None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_get_typed() -> Result<()> {
+        let result = json_get_int(&args(path("b", 0)))?;
+        let expected = Int64Array::from(vec![Some(1), None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        let result = json_get_float(&args(path("b", 1)))?;
+        let expected = Float64Array::from(vec![Some(2.5), None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        let result = json_get_bool(&args(path("b", -2)))?;
+        let expected = BooleanArray::from(vec![Some(true), None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+
+        // a string is not an integer
+        let result = json_get_int(&args(path("b", 2)))?;
+        let expected = Int64Array::from(vec![None, None, None, None]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+}
@@ -35,6 +35,7 @@ use arrow::datatypes::SchemaRef;
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
+use super::common::SizedRecordBatchStream;
 use super::{RecordBatchStream, SendableRecordBatchStream};
 
 use async_trait::async_trait;
@@ -80,6 +81,11 @@ impl ExecutionPlan for GlobalLimitExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, _children: &[bool]) -> Result<bool> {
+        // input is no longer polled once the limit is reached
+        Ok(false)
+    }
+
     fn required_child_distribution(&self) -> Distribution {
         Distribution::SinglePartition
     }
@@ -120,8 +126,7 @@ impl ExecutionPlan for GlobalLimitExec {
             ));
         }
 
-        let stream = self.input.execute(0).await?;
-        Ok(Box::pin(LimitStream::new(stream, self.limit)))
+        execute_limited(&self.input, 0, self.limit).await
     }
 
     fn fmt_as(
@@ -178,6 +183,11 @@ impl ExecutionPlan for LocalLimitExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, _children: &[bool]) -> Result<bool> {
+        // input is no longer polled once the limit is reached
+        Ok(false)
+    }
+
     fn output_partitioning(&self) -> Partitioning {
         self.input.output_partitioning()
     }
@@ -198,8 +208,7 @@ impl ExecutionPlan for LocalLimitExec {
     }
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
-        let stream = self.input.execute(partition).await?;
-        Ok(Box::pin(LimitStream::new(stream, self.limit)))
+        execute_limited(&self.input, partition, self.limit).await
     }
 
     fn fmt_as(
@@ -215,6 +224,23 @@ impl ExecutionPlan for LocalLimitExec {
     }
 }
 
+/// Executes `partition` of `input`, limited to `limit` rows. The input is
+/// not executed at all if `limit` is zero.
+async fn execute_limited(
+    input: &Arc<dyn ExecutionPlan>,
+    partition: usize,
+    limit: usize,
+) -> Result<SendableRecordBatchStream> {
+    if limit == 0 {
+        return Ok(Box::pin(SizedRecordBatchStream::new(
+            input.schema(),
+            vec![],
+        )));
+    }
+    let stream = input.execute(partition).await?;
+    Ok(Box::pin(LimitStream::new(stream, limit)))
+}
+
 /// Truncate a RecordBatch to maximum of n rows
 pub fn truncate_batch(batch: &RecordBatch, n: usize) -> RecordBatch {
     let limited_columns: Vec<ArrayRef> = (0..batch.num_columns())
@@ -224,7 +250,8 @@ pub fn truncate_batch(batch: &RecordBatch, n: usize) -> RecordBatch {
     RecordBatch::try_new(batch.schema(), limited_columns).unwrap()
 }
 
-/// A Limit stream limits the stream to up to `limit` rows.
+/// A Limit stream limits the stream to up to `limit` rows. The input is no
+/// longer polled once `limit` rows were produced.
 struct LimitStream {
     /// The maximum number of rows to produce
     limit: usize,
@@ -242,24 +269,24 @@ impl LimitStream {
         let schema = input.schema();
         Self {
             limit,
-            input: Some(input),
+            input: if limit > 0 { Some(input) } else { None },
             schema,
             current_len: 0,
         }
     }
 
-    fn stream_limit(&mut self, batch: RecordBatch) -> Option<RecordBatch> {
+    fn stream_limit(&mut self, batch: RecordBatch) -> RecordBatch {
+        let batch_rows = batch.num_rows().min(self.limit - self.current_len);
+        self.current_len += batch_rows;
         if self.current_len == self.limit {
-            self.input = None; // clear input so it can be dropped early
-            None
-        } else if self.current_len + batch.num_rows() <= self.limit {
-            self.current_len += batch.num_rows();
-            Some(batch)
+            // clear input so it can be dropped early, without polling it
+            // for another batch
+            self.input = None;
+        }
+        if batch_rows < batch.num_rows() {
+            truncate_batch(&batch, batch_rows)
         } else {
-            let batch_rows = self.limit - self.current_len;
-            self.current_len = self.limit;
-            self.input = None; // clear input so it can be dropped early
-            Some(truncate_batch(&batch, batch_rows))
+            batch
         }
     }
 }
@@ -273,7 +300,7 @@ impl Stream for LimitStream {
     ) -> Poll<Option<Self::Item>> {
         match &mut self.input {
             Some(input) => input.poll_next_unpin(cx).map(|x| match x {
-                Some(Ok(batch)) => Ok(self.stream_limit(batch)).transpose(),
+                Some(Ok(batch)) => Some(Ok(self.stream_limit(batch))),
                 other => other,
             }),
             // input has been cleared
@@ -361,4 +388,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn limit_reached_at_batch_boundary() -> Result<()> {
+        let batches = vec![
+            test::make_partition(5),
+            test::make_partition(10),
+            test::make_partition(15),
+        ];
+        let input = test::exec::TestStream::new(batches.clone());
+        let index = input.index();
+
+        // the limit is reached with the second batch, so the third one
+        // is never polled
+        let limit_stream = LimitStream::new(Box::pin(input), 15);
+        let results = collect(Box::pin(limit_stream)).await?;
+        let num_rows: usize = results.into_iter().map(|b| b.num_rows()).sum();
+        assert_eq!(num_rows, 15);
+        assert_eq!(index.value(), 2);
+
+        // a limit of zero doesn't poll the input at all
+        let input = test::exec::TestStream::new(batches);
+        let index = input.index();
+        let limit_stream = LimitStream::new(Box::pin(input), 0);
+        assert!(collect(Box::pin(limit_stream)).await?.is_empty());
+        assert_eq!(index.value(), 0);
+
+        Ok(())
+    }
 }
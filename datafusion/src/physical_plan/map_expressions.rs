@@ -0,0 +1,197 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Map expressions. Maps are lists of key/value entries, i.e. lists of structs
+//! with a key and a value field, which is how Arrow lays out map columns.
+
+use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
+use arrow::array::*;
+use arrow::compute::{cast, take};
+use arrow::datatypes::{DataType, Field};
+
+use super::array_expressions::{build_list, list_arg, list_offset, list_range};
+
+/// Returns the key and value fields of `data_type` if it is a map
+pub fn map_fields(data_type: &DataType) -> Option<(&Field, &Field)> {
+    match data_type {
+        DataType::List(entry) | DataType::LargeList(entry) => match entry.data_type() {
+            DataType::Struct(fields) if fields.len() == 2 => {
+                Some((&fields[0], &fields[1]))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the type of a list of the keys or values of `data_type` of a map of
+/// `map_type`
+pub fn map_column_type(map_type: &DataType, data_type: &DataType) -> DataType {
+    let item = Box::new(Field::new("item", data_type.clone(), true));
+    match map_type {
+        DataType::LargeList(_) => DataType::LargeList(item),
+        _ => DataType::List(item),
+    }
+}
+
+/// Downcasts the entries of the map argument of the function `name`
+fn map_entries<'a>(entries: &'a ArrayRef, name: &str) -> Result<&'a StructArray> {
+    match entries.as_any().downcast_ref::<StructArray>() {
+        Some(entries) if entries.num_columns() == 2 => Ok(entries),
+        _ => Err(DataFusionError::Internal(format!(
+            "Invalid map entries of type {:?} for function {}",
+            entries.data_type(),
+            name
+        ))),
+    }
+}
+
+/// Returns for each map of `args[0]` the list of the entries' `column`
+fn map_column<O: OffsetSizeTrait>(
+    args: &[ArrayRef],
+    column: usize,
+    name: &str,
+) -> Result<ArrayRef> {
+    let map = list_arg::<O>(&args[0], name)?;
+    let entries = map.values();
+    let values = map_entries(&entries, name)?.column(column);
+    let mut mutable = MutableArrayData::new(vec![values.data()], true, values.len());
+    let mut offsets = vec![list_offset::<O>(0, name)?];
+    let mut validity = BooleanBufferBuilder::new(map.len());
+    let mut length = 0;
+    for i in 0..map.len() {
+        let range = list_range(map, i);
+        length += range.len();
+        mutable.extend(0, range.start, range.end);
+        validity.append(map.is_valid(i));
+        offsets.push(list_offset(length, name)?);
+    }
+    let data_type = map_column_type(map.data_type(), values.data_type());
+    Ok(build_list(data_type, mutable.freeze(), offsets, validity))
+}
+
+/// The keys of a map.
+/// map_keys({'a': 1, 'b': 2}) = ['a', 'b']
+pub fn map_keys<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_column::<O>(args, 0, "map_keys")
+}
+
+/// The values of a map.
+/// map_values({'a': 1, 'b': 2}) = [1, 2]
+pub fn map_values<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_column::<O>(args, 1, "map_values")
+}
+
+/// The value of a key of a map, null if the map does not contain the key. If
+/// the map contains the key more than once, the last value wins.
+/// element_at({'a': 1, 'b': 2}, 'b') = 2
+pub fn element_at<O: OffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let name = "element_at";
+    let map = list_arg::<O>(&args[0], name)?;
+    let entries = map.values();
+    let entries = map_entries(&entries, name)?;
+    let keys = entries.column(0);
+    let lookups = cast(&args[1], keys.data_type())?;
+    let indices = (0..map.len())
+        .map(|i| {
+            if map.is_null(i) || lookups.is_null(i) {
+                return Ok(None);
+            }
+            let lookup = ScalarValue::try_from_array(&lookups, i)?;
+            let mut index = None;
+            for j in list_range(map, i) {
+                if keys.is_valid(j) && ScalarValue::try_from_array(keys, j)? == lookup {
+                    index = Some(j as u32);
+                }
+            }
+            Ok(index)
+        })
+        .collect::<Result<UInt32Array>>()?;
+    Ok(take(entries.column(1).as_ref(), &indices, None)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// [{'a': 1, 'b': 2}, null, {}, {'a': 3, 'a': 4}]
+    fn map() -> ArrayRef {
+        let keys: ArrayRef = Arc::new(StringArray::from(vec!["a", "b", "a", "a"]));
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![1, 2, 3, 4]));
+        let key = Field::new("key", DataType::Utf8, false);
+        let value = Field::new("value", DataType::Int64, true);
+        let entries =
+            StructArray::from(vec![(key.clone(), keys), (value.clone(), values)]);
+        let data_type = DataType::List(Box::new(Field::new(
+            "entries",
+            DataType::Struct(vec![key, value]),
+            false,
+        )));
+        let mut validity = BooleanBufferBuilder::new(4);
+        for valid in &[true, false, true, true] {
+            validity.append(*valid);
+        }
+        build_list(
+            data_type,
+            entries.data().clone(),
+            vec![0i32, 2, 2, 2, 4],
+            validity,
+        )
+    }
+
+    #[test]
+    fn test_map_fields() {
+        let data_type = map().data_type().clone();
+        let (key, value) = map_fields(&data_type).unwrap();
+        assert_eq!(key.name(), "key");
+        assert_eq!(value.name(), "value");
+        assert!(map_fields(&DataType::Utf8).is_none());
+    }
+
+    #[test]
+    fn test_map_keys_and_values() -> Result<()> {
+        let keys = map_keys::<i32>(&[map()])?;
+        let keys = keys.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(keys.len(), 4);
+        assert!(keys.is_null(1));
+        assert_eq!(keys.value(2).len(), 0);
+        let expected = StringArray::from(vec!["a", "a"]);
+        assert_eq!(keys.value(3).as_ref(), &expected as &dyn Array);
+
+        let values = map_values::<i32>(&[map()])?;
+        let values = values.as_any().downcast_ref::<ListArray>().unwrap();
+        let expected = Int64Array::from(vec![1, 2]);
+        assert_eq!(values.value(0).as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_at() -> Result<()> {
+        let lookups: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("b"),
+            Some("a"),
+            Some("a"),
+            Some("a"),
+        ]));
+        let result = element_at::<i32>(&[map(), lookups])?;
+        let expected = Int64Array::from(vec![Some(2), None, None, Some(4)]);
+        assert_eq!(result.as_ref(), &expected as &dyn Array);
+        Ok(())
+    }
+}
@@ -16,10 +16,14 @@
 // under the License.
 
 //! Math expressions
+use super::expressions::MAX_DECIMAL_PRECISION;
 use super::{ColumnarValue, ScalarValue};
 use crate::error::{DataFusionError, Result};
-use arrow::array::{Float32Array, Float64Array};
-use arrow::datatypes::DataType;
+use arrow::array::{
+    Array, ArrayRef, DecimalArray, DecimalBuilder, Float32Array, Float64Array,
+    PrimitiveArray,
+};
+use arrow::datatypes::{ArrowPrimitiveType, DataType, Float64Type, Int64Type};
 use rand::{thread_rng, Rng};
 use std::iter;
 use std::sync::Arc;
@@ -77,9 +81,12 @@ macro_rules! unary_primitive_array_op {
 
 macro_rules! math_unary_function {
     ($NAME:expr, $FUNC:ident) => {
+        math_unary_function!($NAME, $FUNC, $FUNC);
+    };
+    ($NAME:expr, $FUNC:ident, $OP:ident) => {
         /// mathematical function that accepts f32 or f64 and returns f64
         pub fn $FUNC(args: &[ColumnarValue]) -> Result<ColumnarValue> {
-            unary_primitive_array_op!(&args[0], $NAME, $FUNC)
+            unary_primitive_array_op!(&args[0], $NAME, $OP)
         }
     };
 }
@@ -101,6 +108,304 @@ math_unary_function!("exp", exp);
 math_unary_function!("ln", ln);
 math_unary_function!("log2", log2);
 math_unary_function!("log10", log10);
+math_unary_function!("cbrt", cbrt);
+math_unary_function!("degrees", degrees, to_degrees);
+math_unary_function!("radians", radians, to_radians);
+
+/// Downcasts `$ARRAY` to a `PrimitiveArray<$TYPE>`
+macro_rules! downcast_primitive_arg {
+    ($ARRAY:expr, $NAME:expr, $TYPE:ident) => {{
+        $ARRAY
+            .as_any()
+            .downcast_ref::<PrimitiveArray<$TYPE>>()
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Invalid data type {:?} for function {}",
+                    $ARRAY.data_type(),
+                    $NAME
+                ))
+            })?
+    }};
+}
+
+/// Applies `op` to the values of `args[0]`, returning null for null values
+fn unary_op<I, O, F>(args: &[ArrayRef], name: &str, op: F) -> Result<ArrayRef>
+where
+    I: ArrowPrimitiveType,
+    O: ArrowPrimitiveType,
+    F: Fn(I::Native) -> Result<O::Native>,
+{
+    let array = downcast_primitive_arg!(args[0], name, I);
+    let result = array
+        .iter()
+        .map(|v| v.map(&op).transpose())
+        .collect::<Result<PrimitiveArray<O>>>()?;
+    Ok(Arc::new(result))
+}
+
+/// Applies `op` to the pairs of values of `args[0]` and `args[1]`, returning
+/// null if either value is null
+fn binary_op<L, R, O, F>(args: &[ArrayRef], name: &str, op: F) -> Result<ArrayRef>
+where
+    L: ArrowPrimitiveType,
+    R: ArrowPrimitiveType,
+    O: ArrowPrimitiveType,
+    F: Fn(L::Native, R::Native) -> Result<O::Native>,
+{
+    let left = downcast_primitive_arg!(args[0], name, L);
+    let right = downcast_primitive_arg!(args[1], name, R);
+    let result = left
+        .iter()
+        .zip(right.iter())
+        .map(|(l, r)| match (l, r) {
+            (Some(l), Some(r)) => op(l, r).map(Some),
+            _ => Ok(None),
+        })
+        .collect::<Result<PrimitiveArray<O>>>()?;
+    Ok(Arc::new(result))
+}
+
+/// Raises the base to the given exponent.
+/// power(2, 10) = 1024
+pub fn power(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Float64Type, Float64Type, Float64Type, _>(args, "power", |b, e| {
+        Ok(b.powf(e))
+    })
+}
+
+/// Arc tangent of y / x, in the quadrant given by the signs of both.
+/// atan2(1, 1) = 0.7853981633974483
+pub fn atan2(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Float64Type, Float64Type, Float64Type, _>(args, "atan2", |y, x| {
+        Ok(y.atan2(x))
+    })
+}
+
+/// Logarithm of x to the given base.
+/// log(2, 8) = 3
+pub fn log(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Float64Type, Float64Type, Float64Type, _>(args, "log", |base, x| {
+        Ok(x.log(base))
+    })
+}
+
+/// Truncates towards zero to the given number of decimal places.
+/// trunc(3.14159, 2) = 3.14
+pub fn trunc_to_places(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Float64Type, Int64Type, Float64Type, _>(args, "trunc", |x, places| {
+        let factor = 10f64.powi(places as i32);
+        Ok((x * factor).trunc() / factor)
+    })
+}
+
+/// The constant π
+pub fn pi(_args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    Ok(ColumnarValue::Scalar(ScalarValue::Float64(Some(
+        std::f64::consts::PI,
+    ))))
+}
+
+/// Factorial of a non-negative integer.
+/// factorial(5) = 120
+pub fn factorial(args: &[ArrayRef]) -> Result<ArrayRef> {
+    unary_op::<Int64Type, Int64Type, _>(args, "factorial", |n| {
+        if n < 0 {
+            return Err(DataFusionError::Execution(format!(
+                "factorial of a negative number {} is undefined",
+                n
+            )));
+        }
+        (1..=n)
+            .try_fold(1i64, |acc, i| acc.checked_mul(i))
+            .ok_or_else(|| {
+                DataFusionError::Execution(format!("factorial of {} is out of range", n))
+            })
+    })
+}
+
+/// Greatest common divisor of the absolute values of `a` and `b`
+fn gcd_values(a: i64, b: i64) -> Result<i64> {
+    let (mut a, mut b) = ((a as i128).abs(), (b as i128).abs());
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    if a > i64::MAX as i128 {
+        return Err(DataFusionError::Execution(
+            "gcd is out of range".to_string(),
+        ));
+    }
+    Ok(a as i64)
+}
+
+/// Greatest common divisor, 0 if both arguments are 0.
+/// gcd(12, 18) = 6
+pub fn gcd(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Int64Type, Int64Type, Int64Type, _>(args, "gcd", gcd_values)
+}
+
+/// Least common multiple, 0 if either argument is 0.
+/// lcm(4, 6) = 12
+pub fn lcm(args: &[ArrayRef]) -> Result<ArrayRef> {
+    binary_op::<Int64Type, Int64Type, Int64Type, _>(args, "lcm", |a, b| {
+        if a == 0 || b == 0 {
+            return Ok(0);
+        }
+        let gcd = gcd_values(a, b)?;
+        (a / gcd)
+            .checked_mul(b)
+            .and_then(|v| v.checked_abs())
+            .ok_or_else(|| DataFusionError::Execution("lcm is out of range".to_string()))
+    })
+}
+
+/// Downcasts the decimal argument of the function `name`
+fn decimal_arg<'a>(array: &'a ArrayRef, name: &str) -> Result<&'a DecimalArray> {
+    array
+        .as_any()
+        .downcast_ref::<DecimalArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "Invalid data type {:?} for function {}",
+                array.data_type(),
+                name
+            ))
+        })
+}
+
+/// The precision and scale of rounding a decimal of `precision` and `scale`.
+/// The scale is kept when rounding to a number of places and dropped otherwise,
+/// and a digit is added for the carry.
+fn rounded_precision_scale(
+    precision: usize,
+    scale: usize,
+    keep_scale: bool,
+) -> (usize, usize) {
+    if keep_scale {
+        ((precision + 1).min(MAX_DECIMAL_PRECISION), scale)
+    } else {
+        ((precision - scale + 1).min(MAX_DECIMAL_PRECISION), 0)
+    }
+}
+
+/// Returns the type of `round`, `trunc`, `ceil` and `floor` of a decimal of
+/// `data_type`, with `keep_scale` if a number of places is given
+pub fn decimal_rounding_type(data_type: &DataType, keep_scale: bool) -> Result<DataType> {
+    match data_type {
+        DataType::Decimal(precision, scale) => {
+            let (precision, scale) =
+                rounded_precision_scale(*precision, *scale, keep_scale);
+            Ok(DataType::Decimal(precision, scale))
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Unsupported data type {:?} for decimal rounding",
+            other
+        ))),
+    }
+}
+
+/// Rounds the decimals of `args[0]` to `args[1]` places, or to an integer if no
+/// places are given. `op` returns the quotient of a value and a power of ten,
+/// rounded in the direction of the function `name`.
+fn decimal_rounding<F>(args: &[ArrayRef], name: &str, op: F) -> Result<ArrayRef>
+where
+    F: Fn(i128, i128) -> i128,
+{
+    let array = decimal_arg(&args[0], name)?;
+    let keep_scale = args.len() > 1;
+    let places = if keep_scale {
+        Some(downcast_primitive_arg!(args[1], name, Int64Type))
+    } else {
+        None
+    };
+    let scale = array.scale();
+    let (precision, result_scale) =
+        rounded_precision_scale(array.precision(), scale, keep_scale);
+    let max_value = 10i128.pow(precision as u32);
+
+    let mut builder = DecimalBuilder::new(array.len(), precision, result_scale);
+    for i in 0..array.len() {
+        if array.is_null(i) || places.map_or(false, |places| places.is_null(i)) {
+            builder.append_null()?;
+            continue;
+        }
+        let value = array.value(i);
+        let places = places.map_or(0, |places| places.value(i));
+        // the number of digits of the fraction to drop
+        let digits = scale as i64 - places;
+        let rounded = if digits <= 0 {
+            Some(value)
+        } else if digits > MAX_DECIMAL_PRECISION as i64 {
+            Some(0)
+        } else {
+            let factor = 10i128.pow(digits as u32);
+            let quotient = op(value, factor);
+            if keep_scale {
+                quotient.checked_mul(factor)
+            } else {
+                Some(quotient)
+            }
+        };
+        match rounded {
+            Some(v) if v.abs() < max_value => builder.append_value(v)?,
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "{} of {} is out of range",
+                    name, value
+                )))
+            }
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+/// Rounds a decimal half away from zero, to the given number of places keeping
+/// the scale, or to an integer.
+/// round(2.345, 2) = 2.350
+pub fn decimal_round(args: &[ArrayRef]) -> Result<ArrayRef> {
+    decimal_rounding(args, "round", |value, factor| {
+        let remainder = (value % factor).abs();
+        if remainder >= factor - remainder {
+            value / factor + value.signum()
+        } else {
+            value / factor
+        }
+    })
+}
+
+/// Truncates a decimal towards zero, to the given number of places keeping the
+/// scale, or to an integer.
+/// trunc(-2.345, 2) = -2.340
+pub fn decimal_trunc(args: &[ArrayRef]) -> Result<ArrayRef> {
+    decimal_rounding(args, "trunc", |value, factor| value / factor)
+}
+
+/// Smallest integer not less than a decimal.
+/// ceil(2.1) = 3
+pub fn decimal_ceil(args: &[ArrayRef]) -> Result<ArrayRef> {
+    decimal_rounding(args, "ceil", |value, factor| -(-value).div_euclid(factor))
+}
+
+/// Largest integer not greater than a decimal.
+/// floor(-2.1) = -3
+pub fn decimal_floor(args: &[ArrayRef]) -> Result<ArrayRef> {
+    decimal_rounding(args, "floor", |value, factor| value.div_euclid(factor))
+}
+
+/// Absolute value of a decimal, of the same precision and scale
+pub fn decimal_abs(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let array = decimal_arg(&args[0], "abs")?;
+    let mut builder = DecimalBuilder::new(array.len(), array.precision(), array.scale());
+    for i in 0..array.len() {
+        if array.is_null(i) {
+            builder.append_null()?;
+        } else {
+            builder.append_value(array.value(i).abs())?;
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
 
 /// random SQL function
 pub fn random(args: &[ColumnarValue]) -> Result<ColumnarValue> {
@@ -122,7 +427,7 @@ pub fn random(args: &[ColumnarValue]) -> Result<ColumnarValue> {
 mod tests {
 
     use super::*;
-    use arrow::array::{Float64Array, NullArray};
+    use arrow::array::{Float64Array, Int64Array, NullArray};
 
     #[test]
     fn test_random_expression() {
@@ -133,4 +438,102 @@ mod tests {
         assert_eq!(floats.len(), 1);
         assert!(0.0 <= floats.value(0) && floats.value(0) < 1.0);
     }
+
+    #[test]
+    fn test_integer_functions() -> Result<()> {
+        let a: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(12), Some(-4), None, Some(0)]));
+        let b: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(18), Some(6), Some(1), Some(0)]));
+
+        let result = gcd(&[a.clone(), b.clone()])?;
+        let expected: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(6), Some(2), None, Some(0)]));
+        assert_eq!(result.data(), expected.data());
+
+        let result = lcm(&[a.clone(), b])?;
+        let expected: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(36), Some(12), None, Some(0)]));
+        assert_eq!(result.data(), expected.data());
+
+        let result =
+            factorial(&[Arc::new(Int64Array::from(vec![Some(5), Some(0), None]))])?;
+        let expected: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(120), Some(1), None]));
+        assert_eq!(result.data(), expected.data());
+
+        assert!(factorial(&[Arc::new(Int64Array::from(vec![-1]))]).is_err());
+        assert!(factorial(&[Arc::new(Int64Array::from(vec![21]))]).is_err());
+        assert!(lcm(&[
+            Arc::new(Int64Array::from(vec![i64::MAX])),
+            Arc::new(Int64Array::from(vec![2])),
+        ])
+        .is_err());
+        Ok(())
+    }
+
+    fn decimal_array(
+        values: Vec<Option<i128>>,
+        precision: usize,
+        scale: usize,
+    ) -> ArrayRef {
+        let mut builder = DecimalBuilder::new(values.len(), precision, scale);
+        for value in values {
+            match value {
+                Some(v) => builder.append_value(v).unwrap(),
+                None => builder.append_null().unwrap(),
+            }
+        }
+        Arc::new(builder.finish())
+    }
+
+    #[test]
+    fn test_decimal_rounding() -> Result<()> {
+        // 2.345, -2.345, 9.999, NULL
+        let x = decimal_array(vec![Some(2345), Some(-2345), Some(9999), None], 4, 3);
+        let places: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(2), Some(1), Some(0), Some(1)]));
+
+        let round = decimal_round(&[x.clone(), places.clone()])?;
+        assert_eq!(round.data_type(), &DataType::Decimal(5, 3));
+        let round = round.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(round.value(0), 2350);
+        assert_eq!(round.value(1), -2300);
+        assert_eq!(round.value(2), 10000);
+        assert!(round.is_null(3));
+
+        let trunc = decimal_trunc(&[x.clone(), places])?;
+        let trunc = trunc.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(trunc.value(0), 2340);
+        assert_eq!(trunc.value(1), -2300);
+        assert_eq!(trunc.value(2), 9000);
+
+        let ceil = decimal_ceil(&[x.clone()])?;
+        assert_eq!(ceil.data_type(), &DataType::Decimal(2, 0));
+        let ceil = ceil.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!((ceil.value(0), ceil.value(1), ceil.value(2)), (3, -2, 10));
+
+        let floor = decimal_floor(&[x.clone()])?;
+        let floor = floor.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!((floor.value(0), floor.value(1), floor.value(2)), (2, -3, 9));
+
+        let abs = decimal_abs(&[x])?;
+        assert_eq!(abs.data_type(), &DataType::Decimal(4, 3));
+        let abs = abs.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!((abs.value(0), abs.value(1)), (2345, 2345));
+        assert!(abs.is_null(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_trunc_to_places() -> Result<()> {
+        let x: ArrayRef = Arc::new(Float64Array::from(vec![1.23456, -1.23456, 1234.5]));
+        let places: ArrayRef = Arc::new(Int64Array::from(vec![2, 2, -2]));
+        let result = trunc_to_places(&[x, places])?;
+        let result = result.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert!((result.value(0) - 1.23).abs() < 1e-10);
+        assert!((result.value(1) + 1.23).abs() < 1e-10);
+        assert!((result.value(2) - 1200.0).abs() < 1e-10);
+        Ok(())
+    }
 }
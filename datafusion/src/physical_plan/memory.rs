@@ -125,6 +125,11 @@ impl MemoryExec {
             projection,
         })
     }
+
+    /// The batches of each partition, before the projection is applied
+    pub fn partitions(&self) -> &[Vec<RecordBatch>] {
+        &self.partitions
+    }
 }
 
 /// Iterator over batches
@@ -23,7 +23,7 @@ use std::sync::Arc;
 
 use futures::channel::mpsc;
 use futures::sink::SinkExt;
-use futures::stream::StreamExt;
+use futures::stream::{self, StreamExt};
 use futures::Stream;
 
 use async_trait::async_trait;
@@ -34,25 +34,45 @@ use arrow::{
     error::{ArrowError, Result as ArrowResult},
 };
 
+use super::common::AbortOnDropMany;
 use super::RecordBatchStream;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
 
 use super::SendableRecordBatchStream;
 use pin_project_lite::pin_project;
+use tokio::task::JoinHandle;
 
 /// Merge execution plan executes partitions in parallel and combines them into a single
-/// partition. No guarantees are made about the order of the resulting partition.
+/// partition. No guarantees are made about the order of the resulting partition, unless
+/// the order of the input partitions is preserved.
 #[derive(Debug)]
 pub struct MergeExec {
     /// Input execution plan
     input: Arc<dyn ExecutionPlan>,
+    /// Whether all batches of an input partition are emitted before those of the next
+    preserve_order: bool,
 }
 
 impl MergeExec {
     /// Create a new MergeExec
     pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
-        MergeExec { input }
+        MergeExec {
+            input,
+            preserve_order: false,
+        }
+    }
+
+    /// Emit the batches of the input partitions in partition order rather than as
+    /// they become available. The partitions are still executed in parallel.
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Whether the order of the input partitions is preserved
+    pub fn preserve_order(&self) -> bool {
+        self.preserve_order
     }
 
     /// Input execution plan
@@ -76,6 +96,10 @@ impl ExecutionPlan for MergeExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
         Partitioning::UnknownPartitioning(1)
@@ -86,7 +110,10 @@ impl ExecutionPlan for MergeExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(MergeExec::new(children[0].clone()))),
+            1 => Ok(Arc::new(
+                MergeExec::new(children[0].clone())
+                    .with_preserve_order(self.preserve_order),
+            )),
             _ => Err(DataFusionError::Internal(
                 "MergeExec wrong number of children".to_string(),
             )),
@@ -111,6 +138,23 @@ impl ExecutionPlan for MergeExec {
                 // bypass any threading if there is a single partition
                 self.input.execute(0).await
             }
+            _ if self.preserve_order => {
+                // one channel per input partition, read one after the other
+                let (join_handles, receivers): (Vec<_>, Vec<_>) = (0..input_partitions)
+                    .map(|part_i| {
+                        let (sender, receiver) = mpsc::channel(1);
+                        let join_handle =
+                            spawn_partition(self.input.clone(), part_i, sender);
+                        (join_handle, receiver)
+                    })
+                    .unzip();
+
+                Ok(Box::pin(MergeStream {
+                    input: stream::iter(receivers).flatten(),
+                    schema: self.schema(),
+                    _drop_helper: AbortOnDropMany(join_handles),
+                }))
+            }
             _ => {
                 // use a stream that allows each sender to put in at
                 // least one result in an attempt to maximize
@@ -120,32 +164,16 @@ impl ExecutionPlan for MergeExec {
 
                 // spawn independent tasks whose resulting streams (of batches)
                 // are sent to the channel for consumption.
-                for part_i in 0..input_partitions {
-                    let input = self.input.clone();
-                    let mut sender = sender.clone();
-                    tokio::spawn(async move {
-                        let mut stream = match input.execute(part_i).await {
-                            Err(e) => {
-                                // If send fails, plan being torn
-                                // down, no place to send the error
-                                let arrow_error = ArrowError::ExternalError(Box::new(e));
-                                sender.send(Err(arrow_error)).await.ok();
-                                return;
-                            }
-                            Ok(stream) => stream,
-                        };
-
-                        while let Some(item) = stream.next().await {
-                            // If send fails, plan being torn down,
-                            // there is no place to send the error
-                            sender.send(item).await.ok();
-                        }
-                    });
-                }
+                let join_handles = (0..input_partitions)
+                    .map(|part_i| {
+                        spawn_partition(self.input.clone(), part_i, sender.clone())
+                    })
+                    .collect();
 
                 Ok(Box::pin(MergeStream {
                     input: receiver,
                     schema: self.schema(),
+                    _drop_helper: AbortOnDropMany(join_handles),
                 }))
             }
         }
@@ -158,21 +186,53 @@ impl ExecutionPlan for MergeExec {
     ) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default => {
-                write!(f, "MergeExec")
+                write!(f, "MergeExec")?;
+                if self.preserve_order {
+                    write!(f, ": preserve_order=true")?;
+                }
+                Ok(())
             }
         }
     }
 }
 
+/// Spawns a task executing `partition` of `input`, sending its batches to `sender`
+fn spawn_partition(
+    input: Arc<dyn ExecutionPlan>,
+    partition: usize,
+    mut sender: mpsc::Sender<ArrowResult<RecordBatch>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut stream = match input.execute(partition).await {
+            Err(e) => {
+                // If send fails, plan being torn
+                // down, no place to send the error
+                let arrow_error = ArrowError::ExternalError(Box::new(e));
+                sender.send(Err(arrow_error)).await.ok();
+                return;
+            }
+            Ok(stream) => stream,
+        };
+
+        while let Some(item) = stream.next().await {
+            // If send fails, plan being torn down,
+            // there is no place to send the error
+            sender.send(item).await.ok();
+        }
+    })
+}
+
 pin_project! {
-    struct MergeStream {
+    struct MergeStream<S> {
         schema: SchemaRef,
         #[pin]
-        input: mpsc::Receiver<ArrowResult<RecordBatch>>,
+        input: S,
+        // aborts the tasks executing the input partitions once dropped
+        _drop_helper: AbortOnDropMany<()>,
     }
 }
 
-impl Stream for MergeStream {
+impl<S: Stream<Item = ArrowResult<RecordBatch>>> Stream for MergeStream<S> {
     type Item = ArrowResult<RecordBatch>;
 
     fn poll_next(
@@ -184,7 +244,7 @@ impl Stream for MergeStream {
     }
 }
 
-impl RecordBatchStream for MergeStream {
+impl<S: Stream<Item = ArrowResult<RecordBatch>>> RecordBatchStream for MergeStream<S> {
     fn schema(&self) -> SchemaRef {
         self.schema.clone()
     }
@@ -194,9 +254,13 @@ impl RecordBatchStream for MergeStream {
 mod tests {
 
     use super::*;
-    use crate::physical_plan::common;
     use crate::physical_plan::csv::{CsvExec, CsvReadOptions};
+    use crate::physical_plan::{collect, common};
     use crate::test;
+    use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::util::pretty::pretty_format_batches;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn merge() -> Result<()> {
@@ -233,4 +297,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn merge_preserve_order() -> Result<()> {
+        let schema = test::aggr_test_schema();
+        let num_partitions = 4;
+        let path =
+            test::create_partitioned_csv("aggregate_test_100.csv", num_partitions)?;
+        let csv: Arc<dyn ExecutionPlan> = Arc::new(CsvExec::try_new(
+            &path,
+            CsvReadOptions::new().schema(&schema),
+            None,
+            1024,
+            None,
+        )?);
+
+        let mut expected = vec![];
+        for partition in 0..num_partitions {
+            expected.extend(common::collect(csv.execute(partition).await?).await?);
+        }
+
+        let merge = MergeExec::new(csv).with_preserve_order(true);
+        let batches = common::collect(merge.execute(0).await?).await?;
+        assert_eq!(
+            pretty_format_batches(&batches)?,
+            pretty_format_batches(&expected)?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn drop_cancels_input_tasks() -> Result<()> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, false)]));
+        let blocking_exec = Arc::new(BlockingExec::new(schema, 2));
+        let refs = blocking_exec.refs();
+        let exec = Arc::new(MergeExec::new(blocking_exec));
+
+        // the input never produces a batch, the query is dropped once timed out
+        let result = tokio::time::timeout(Duration::from_millis(10), collect(exec)).await;
+        assert!(result.is_err());
+
+        assert_strong_count_converges_to_zero(refs).await;
+        Ok(())
+    }
 }
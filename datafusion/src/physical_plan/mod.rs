@@ -17,6 +17,7 @@
 
 //! Traits for physical query plan, supporting parallel execution for partitioned relations.
 
+use instant::Instant;
 use std::fmt;
 use std::fmt::{Debug, Display};
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -31,14 +32,18 @@ use crate::{
 use arrow::datatypes::{DataType, Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
-use arrow::{array::ArrayRef, datatypes::Field};
+use arrow::{
+    array::{ArrayBuilder, ArrayRef},
+    datatypes::Field,
+};
 use async_trait::async_trait;
 pub use display::DisplayFormatType;
 use futures::stream::Stream;
 use std::{any::Any, pin::Pin};
 
+use self::selection::{evaluate_selected, SelectionVector};
 use self::{display::DisplayableExecutionPlan, merge::MergeExec};
-use hashbrown::HashMap;
+use std::collections::BTreeMap;
 
 /// Trait for types that stream [arrow::record_batch::RecordBatch]
 pub trait RecordBatchStream: Stream<Item = ArrowResult<RecordBatch>> {
@@ -59,6 +64,8 @@ pub enum MetricType {
     Counter,
     /// Wall clock time in nanoseconds
     TimeNanos,
+    /// Size in bytes
+    Bytes,
 }
 
 /// SQL metric such as counter (number of input or output rows) or timing information about
@@ -94,6 +101,11 @@ impl SQLMetric {
         Arc::new(SQLMetric::new(MetricType::TimeNanos))
     }
 
+    /// Create a new metric for tracking a size in bytes
+    pub fn bytes() -> Arc<SQLMetric> {
+        Arc::new(SQLMetric::new(MetricType::Bytes))
+    }
+
     /// Create a new SQLMetric
     pub fn new(metric_type: MetricType) -> Self {
         Self {
@@ -107,12 +119,164 @@ impl SQLMetric {
         self.value.fetch_add(n, Ordering::Relaxed);
     }
 
+    /// Set the value to `n` if it is larger than the current value
+    pub fn set_max(&self, n: usize) {
+        self.value.fetch_max(n, Ordering::Relaxed);
+    }
+
+    /// Add the time elapsed since `start`, in nanoseconds
+    pub fn add_elapsed(&self, start: Instant) {
+        self.add(start.elapsed().as_nanos() as usize);
+    }
+
     /// Get the current value
     pub fn value(&self) -> usize {
         self.value.load(Ordering::Relaxed)
     }
 }
 
+/// Snapshot of the named [`SQLMetric`]s collected by an [`ExecutionPlan`].
+///
+/// Operators report their metrics under the standard names defined here
+/// where they apply, so that the metrics of different operators can be
+/// compared and aggregated. Operator specific metrics may use other names.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSet {
+    metrics: BTreeMap<String, SQLMetric>,
+}
+
+impl MetricsSet {
+    /// Number of rows produced by the operator
+    pub const OUTPUT_ROWS: &'static str = "output_rows";
+    /// CPU time spent by the operator, in nanoseconds
+    pub const ELAPSED_COMPUTE: &'static str = "elapsed_compute";
+    /// Time spent building the operator's in-memory state (such as the
+    /// hash table of a join) before producing output, in nanoseconds
+    pub const BUILD_TIME: &'static str = "build_time";
+    /// Number of batches consumed from the operator's inputs
+    pub const INPUT_BATCHES: &'static str = "input_batches";
+    /// Number of times the operator spilled its state to disk
+    pub const SPILL_COUNT: &'static str = "spill_count";
+    /// Peak memory used by the operator across all partitions, in bytes
+    pub const PEAK_MEM_USED: &'static str = "peak_mem_used";
+    /// Number of bytes the operator spilled to disk
+    pub const SPILLED_BYTES: &'static str = "spilled_bytes";
+    /// Number of times the operator retried reading a file
+    pub const READ_RETRIES: &'static str = "read_retries";
+
+    /// Create an empty set of metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a snapshot of `metric` under `name`, replacing any metric
+    /// previously registered with the same name
+    pub fn push(&mut self, name: impl Into<String>, metric: &SQLMetric) {
+        self.metrics.insert(name.into(), metric.clone());
+    }
+
+    /// Get the metric registered under `name`
+    pub fn get(&self, name: &str) -> Option<&SQLMetric> {
+        self.metrics.get(name)
+    }
+
+    /// Iterate over all metrics, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &SQLMetric)> {
+        self.metrics.iter()
+    }
+
+    /// Returns true if no metrics were registered
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    /// Number of rows produced, if reported
+    pub fn output_rows(&self) -> Option<usize> {
+        self.value(Self::OUTPUT_ROWS)
+    }
+
+    /// CPU time spent in nanoseconds, if reported
+    pub fn elapsed_compute(&self) -> Option<usize> {
+        self.value(Self::ELAPSED_COMPUTE)
+    }
+
+    /// Build time in nanoseconds, if reported
+    pub fn build_time(&self) -> Option<usize> {
+        self.value(Self::BUILD_TIME)
+    }
+
+    /// Number of input batches consumed, if reported
+    pub fn input_batches(&self) -> Option<usize> {
+        self.value(Self::INPUT_BATCHES)
+    }
+
+    /// Number of spills, if reported
+    pub fn spill_count(&self) -> Option<usize> {
+        self.value(Self::SPILL_COUNT)
+    }
+
+    /// Peak memory used in bytes, if reported
+    pub fn peak_mem_used(&self) -> Option<usize> {
+        self.value(Self::PEAK_MEM_USED)
+    }
+
+    /// Number of bytes spilled to disk, if reported
+    pub fn spilled_bytes(&self) -> Option<usize> {
+        self.value(Self::SPILLED_BYTES)
+    }
+
+    /// Number of retried file reads, if reported
+    pub fn read_retries(&self) -> Option<usize> {
+        self.value(Self::READ_RETRIES)
+    }
+
+    fn value(&self, name: &str) -> Option<usize> {
+        self.get(name).map(|metric| metric.value())
+    }
+}
+
+/// Tracks the memory currently held by an operator across all of its
+/// partitions, recording the peak in a [`SQLMetric`] that can be reported
+/// as [`MetricsSet::PEAK_MEM_USED`].
+#[derive(Debug)]
+pub struct MemoryUsage {
+    /// Bytes currently held
+    current: AtomicUsize,
+    /// Largest number of bytes held at once
+    peak: Arc<SQLMetric>,
+}
+
+impl MemoryUsage {
+    /// Create a new tracker with no memory held
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            current: AtomicUsize::new(0),
+            peak: SQLMetric::bytes(),
+        })
+    }
+
+    /// Record that `bytes` more are held
+    pub fn grow(&self, bytes: usize) {
+        let current = self.current.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.set_max(current);
+    }
+
+    /// Record that `bytes` were released
+    pub fn shrink(&self, bytes: usize) {
+        self.current.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Bytes currently held
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Metric holding the largest number of bytes held at once
+    pub fn peak(&self) -> &SQLMetric {
+        &self.peak
+    }
+}
+
 /// Physical query planner that converts a `LogicalPlan` to an
 /// `ExecutionPlan` suitable for execution.
 pub trait PhysicalPlanner {
@@ -162,8 +326,26 @@ pub trait ExecutionPlan: Debug + Send + Sync {
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream>;
 
     /// Return a snapshot of the metrics collected during execution
-    fn metrics(&self) -> HashMap<String, SQLMetric> {
-        HashMap::new()
+    fn metrics(&self) -> MetricsSet {
+        MetricsSet::new()
+    }
+
+    /// Returns whether this operator produces an unbounded stream of
+    /// batches, given whether each of its `children` does.
+    ///
+    /// Leaf nodes reading from unbounded sources should return `Ok(true)`.
+    /// Operators that can not produce any output before consuming all of
+    /// their input (e.g. sorts and aggregations) return an error if one of
+    /// their children is unbounded, which is the default.
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        if children.iter().any(|unbounded| *unbounded) {
+            Err(DataFusionError::Plan(
+                "Operator needs to consume its entire input before producing output"
+                    .to_string(),
+            ))
+        } else {
+            Ok(false)
+        }
     }
 
     /// Format this `ExecutionPlan` to `f` in the specified type.
@@ -300,18 +482,27 @@ pub fn visit_execution_plan<V: ExecutionPlanVisitor>(
 
 /// Execute the [ExecutionPlan] and collect the results in memory
 pub async fn collect(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<RecordBatch>> {
+    common::collect(execute_stream(plan).await?).await
+}
+
+/// Execute the [ExecutionPlan] and return a single stream of its results,
+/// merging its partitions if there is more than one. Batches are produced as
+/// the stream is polled, rather than collected in memory.
+pub async fn execute_stream(
+    plan: Arc<dyn ExecutionPlan>,
+) -> Result<SendableRecordBatchStream> {
     match plan.output_partitioning().partition_count() {
-        0 => Ok(vec![]),
-        1 => {
-            let it = plan.execute(0).await?;
-            common::collect(it).await
-        }
+        0 => Ok(Box::pin(common::SizedRecordBatchStream::new(
+            plan.schema(),
+            vec![],
+        ))),
+        1 => plan.execute(0).await,
         _ => {
             // merge into a single partition
             let plan = MergeExec::new(plan.clone());
             // MergeExec must produce a single partition
             assert_eq!(1, plan.output_partitioning().partition_count());
-            common::collect(plan.execute(0).await?).await
+            plan.execute(0).await
         }
     }
 }
@@ -320,20 +511,25 @@ pub async fn collect(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<RecordBatch>> {
 pub async fn collect_partitioned(
     plan: Arc<dyn ExecutionPlan>,
 ) -> Result<Vec<Vec<RecordBatch>>> {
-    match plan.output_partitioning().partition_count() {
-        0 => Ok(vec![]),
-        1 => {
-            let it = plan.execute(0).await?;
-            Ok(vec![common::collect(it).await?])
-        }
-        _ => {
-            let mut partitions = vec![];
-            for i in 0..plan.output_partitioning().partition_count() {
-                partitions.push(common::collect(plan.execute(i).await?).await?)
-            }
-            Ok(partitions)
-        }
+    let streams = execute_stream_partitioned(plan).await?;
+    let mut partitions = Vec::with_capacity(streams.len());
+    for stream in streams {
+        partitions.push(common::collect(stream).await?);
     }
+    Ok(partitions)
+}
+
+/// Execute the [ExecutionPlan] and return one stream of results per output
+/// partition, see [`execute_stream`]
+pub async fn execute_stream_partitioned(
+    plan: Arc<dyn ExecutionPlan>,
+) -> Result<Vec<SendableRecordBatchStream>> {
+    let num_partitions = plan.output_partitioning().partition_count();
+    let mut streams = Vec::with_capacity(num_partitions);
+    for i in 0..num_partitions {
+        streams.push(plan.execute(i).await?);
+    }
+    Ok(streams)
 }
 
 /// Partitioning schemes supported by operators.
@@ -410,6 +606,25 @@ pub trait PhysicalExpr: Send + Sync + Display + Debug {
     fn nullable(&self, input_schema: &Schema) -> Result<bool>;
     /// Evaluate an expression against a RecordBatch
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue>;
+    /// Evaluate an expression against the rows of a RecordBatch that are
+    /// selected by `selection`. The result has a value for every row of the
+    /// batch, but the values of the rows that are not selected are
+    /// unspecified and must be ignored by the caller. Conditional expressions
+    /// such as CASE use this to only evaluate a branch for the rows that take
+    /// it, which saves evaluating expensive branches and avoids errors of the
+    /// branches that are not taken, e.g. a division by zero.
+    ///
+    /// By default, the selected rows are copied into a batch of their own.
+    /// Expressions that cannot fail for any row, such as columns and
+    /// comparisons, evaluate all rows instead and pass the selection on to
+    /// their children, so that nested conditions do not copy the batch again.
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &SelectionVector,
+    ) -> Result<ColumnarValue> {
+        evaluate_selected(self, batch, selection)
+    }
 }
 
 /// An aggregate expression that:
@@ -486,6 +701,19 @@ pub trait Accumulator: Send + Sync + Debug {
     // of two values, sum and n.
     fn state(&self) -> Result<Vec<ScalarValue>>;
 
+    /// Appends the state of the accumulator to `builders`, one builder per
+    /// field of the `state_fields` of its aggregation, created by
+    /// [`ScalarValue::make_builder`] for the data type of the
+    /// field. Partial aggregations build their state columns this way, so
+    /// accumulators whose state consists of single values append them
+    /// directly rather than collecting them in a vector first.
+    fn append_state(&self, builders: &mut [Box<dyn ArrayBuilder>]) -> Result<()> {
+        self.state()?
+            .iter()
+            .zip(builders.iter_mut())
+            .try_for_each(|(value, builder)| value.append_to_builder(builder.as_mut()))
+    }
+
     /// updates the accumulator's state from a vector of scalars.
     fn update(&mut self, values: &[ScalarValue]) -> Result<()>;
 
@@ -576,8 +804,11 @@ pub trait WindowAccumulator: Send + Sync + Debug {
     fn evaluate(&self) -> Result<Option<ScalarValue>>;
 }
 
+pub mod adaptive;
 pub mod aggregates;
+pub mod analyze;
 pub mod array_expressions;
+pub mod async_udf;
 pub mod coalesce_batches;
 pub mod common;
 pub mod cross_join;
@@ -585,36 +816,64 @@ pub mod cross_join;
 pub mod crypto_expressions;
 pub mod csv;
 pub mod datetime_expressions;
+pub mod deduplicate;
+pub mod dictionary_expressions;
 pub mod display;
+#[cfg(feature = "encoding_expressions")]
+pub mod encoding_expressions;
 pub mod distinct_expressions;
 pub mod empty;
 pub mod explain;
+pub mod ffi_stream;
 pub mod expressions;
 pub mod filter;
+pub mod formatting_expressions;
 pub mod functions;
+pub mod gap_fill;
+pub mod generate_series;
 pub mod group_scalar;
 pub mod hash_aggregate;
 pub mod hash_join;
 pub mod hash_utils;
+pub mod hashing;
+pub mod interleave;
+pub mod io_runtime;
 pub mod json;
+#[cfg(feature = "json_expressions")]
+pub mod json_expressions;
 pub mod limit;
+pub mod map_expressions;
 pub mod math_expressions;
 pub mod memory;
 pub mod merge;
+pub mod monitor;
 pub mod parquet;
+pub mod placeholder_row;
 pub mod planner;
 pub mod projection;
+pub mod query_limit;
 #[cfg(feature = "regex_expressions")]
 pub mod regex_expressions;
 pub mod repartition;
+pub mod retry;
+pub mod runtime_filter;
+pub mod sample;
+pub mod selection;
 pub mod sort;
 pub mod source;
+pub mod stream;
 pub mod string_expressions;
+pub mod struct_expressions;
+#[cfg(feature = "tracing")]
+pub mod traced;
 pub mod type_coercion;
 pub mod udaf;
 pub mod udf;
+pub mod udtf;
 #[cfg(feature = "unicode_expressions")]
 pub mod unicode_expressions;
 pub mod union;
+pub mod unnest;
+pub mod values;
 pub mod window_functions;
 pub mod windows;
@@ -0,0 +1,382 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Defines the execution plan that counts the rows each partition of its
+//! input produced so far, and [`snapshot`] to take a snapshot of the plan
+//! with these live metrics while it is running, e.g. to report the progress
+//! of long running queries.
+
+use std::any::Any;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+
+use super::display::DisplayMetrics;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    displayable, DisplayFormatType, Distribution, ExecutionPlan, MetricsSet,
+    Partitioning, SQLMetric,
+};
+
+/// Wraps every node of `plan` in a [`MonitoredExec`], so that the progress
+/// of each of its partitions is part of the [`snapshot`] of the plan.
+pub fn monitor_plan(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan
+        .children()
+        .into_iter()
+        .map(monitor_plan)
+        .collect::<Result<Vec<_>>>()?;
+    let plan = if children.is_empty() {
+        plan
+    } else {
+        plan.with_new_children(children)?
+    };
+    Ok(Arc::new(MonitoredExec::new(plan)))
+}
+
+/// Progress of a single output partition of a [`MonitoredExec`]
+#[derive(Debug)]
+struct PartitionProgress {
+    /// Number of rows produced so far
+    output_rows: Arc<SQLMetric>,
+    /// Number of batches produced so far
+    output_batches: Arc<SQLMetric>,
+    /// Whether all batches of the partition were produced
+    finished: Arc<AtomicBool>,
+}
+
+impl PartitionProgress {
+    fn new() -> Self {
+        Self {
+            output_rows: SQLMetric::counter(),
+            output_batches: SQLMetric::counter(),
+            finished: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Executes its input, counting the rows and batches each of its output
+/// partitions produced so far.
+#[derive(Debug)]
+pub struct MonitoredExec {
+    /// Input execution plan
+    input: Arc<dyn ExecutionPlan>,
+    /// Progress of each output partition
+    partitions: Vec<PartitionProgress>,
+}
+
+impl MonitoredExec {
+    /// Create a new MonitoredExec for `input`
+    pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
+        let partitions = (0..input.output_partitioning().partition_count())
+            .map(|_| PartitionProgress::new())
+            .collect();
+        Self { input, partitions }
+    }
+
+    /// Input execution plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// Snapshot of the progress of each output partition
+    pub fn partitions(&self) -> Vec<PartitionSnapshot> {
+        self.partitions
+            .iter()
+            .map(|progress| PartitionSnapshot {
+                output_rows: progress.output_rows.value(),
+                output_batches: progress.output_batches.value(),
+                finished: progress.finished.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for MonitoredExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        self.input.required_child_distribution()
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(MonitoredExec::new(children[0].clone()))),
+            _ => Err(DataFusionError::Internal(
+                "MonitoredExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let progress = self.partitions.get(partition).ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "MonitoredExec invalid partition {}",
+                partition
+            ))
+        })?;
+        let input = self.input.execute(partition).await?;
+
+        Ok(Box::pin(MonitoredStream {
+            input,
+            output_rows: progress.output_rows.clone(),
+            output_batches: progress.output_batches.clone(),
+            finished: progress.finished.clone(),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(f, "MonitoredExec"),
+        }
+    }
+
+    fn metrics(&self) -> MetricsSet {
+        self.input.metrics()
+    }
+}
+
+/// Stream that counts the rows and batches of its input
+struct MonitoredStream {
+    input: SendableRecordBatchStream,
+    output_rows: Arc<SQLMetric>,
+    output_batches: Arc<SQLMetric>,
+    finished: Arc<AtomicBool>,
+}
+
+impl Stream for MonitoredStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.input.poll_next_unpin(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(batch))) => {
+                self.output_rows.add(batch.num_rows());
+                self.output_batches.add(1);
+            }
+            Poll::Ready(None) => self.finished.store(true, Ordering::Relaxed),
+            _ => {}
+        }
+        poll
+    }
+}
+
+impl RecordBatchStream for MonitoredStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+/// Progress of a single output partition of an operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionSnapshot {
+    /// Number of rows produced so far
+    pub output_rows: usize,
+    /// Number of batches produced so far
+    pub output_batches: usize,
+    /// Whether all batches of the partition were produced
+    pub finished: bool,
+}
+
+/// Snapshot of an operator of a (running) plan and its live metrics
+#[derive(Debug, Clone)]
+pub struct PlanSnapshot {
+    /// One line description of the operator, see
+    /// [`DisplayableExecutionPlan::one_line`](super::display::DisplayableExecutionPlan::one_line)
+    pub description: String,
+    /// Metrics the operator collected so far
+    pub metrics: MetricsSet,
+    /// Progress of each output partition. Empty if the operator is not
+    /// wrapped in a [`MonitoredExec`]
+    pub partitions: Vec<PartitionSnapshot>,
+    /// Snapshots of the inputs of the operator
+    pub children: Vec<PlanSnapshot>,
+}
+
+impl PlanSnapshot {
+    /// Number of rows produced so far by all partitions, if the progress
+    /// of the partitions is monitored
+    pub fn output_rows(&self) -> Option<usize> {
+        if self.partitions.is_empty() {
+            None
+        } else {
+            Some(self.partitions.iter().map(|p| p.output_rows).sum())
+        }
+    }
+}
+
+impl fmt::Display for PlanSnapshot {
+    /// Formats the snapshot with a single line per operator, similar to
+    /// [`DisplayableExecutionPlan::indent`](super::display::DisplayableExecutionPlan::indent)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn fmt_node(
+            node: &PlanSnapshot,
+            indent: usize,
+            f: &mut fmt::Formatter,
+        ) -> fmt::Result {
+            write!(f, "{:indent$}", "", indent = indent * 2)?;
+            write!(
+                f,
+                "{}, metrics=[{}]",
+                node.description,
+                DisplayMetrics(&node.metrics)
+            )?;
+            if !node.partitions.is_empty() {
+                write!(f, ", partition_rows=[")?;
+                for (idx, partition) in node.partitions.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", partition.output_rows)?;
+                    if !partition.finished {
+                        write!(f, " (running)")?;
+                    }
+                }
+                write!(f, "]")?;
+            }
+            writeln!(f)?;
+            for child in &node.children {
+                fmt_node(child, indent + 1, f)?;
+            }
+            Ok(())
+        }
+        fmt_node(self, 0, f)
+    }
+}
+
+/// Take a snapshot of `plan` and the metrics its operators collected so
+/// far. Can be called while the plan is executing, e.g. from a status
+/// endpoint of an embedding service. The progress of each partition is
+/// only included for operators wrapped by [`monitor_plan`], and the
+/// [`MonitoredExec`] wrappers themselves are not part of the snapshot.
+pub fn snapshot(plan: &dyn ExecutionPlan) -> PlanSnapshot {
+    let (plan, partitions) = match plan.as_any().downcast_ref::<MonitoredExec>() {
+        Some(monitored) => (monitored.input().as_ref(), monitored.partitions()),
+        None => (plan, vec![]),
+    };
+    PlanSnapshot {
+        description: displayable(plan).one_line().to_string(),
+        metrics: plan.metrics(),
+        partitions,
+        children: plan
+            .children()
+            .iter()
+            .map(|child| snapshot(child.as_ref()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::lit;
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::scalar::ScalarValue;
+    use crate::test::build_table_i32;
+
+    #[tokio::test]
+    async fn snapshot_running_plan() -> Result<()> {
+        let batch = build_table_i32(
+            ("a", &vec![3, 1, 2]),
+            ("b", &vec![1, 2, 3]),
+            ("c", &vec![4, 5, 6]),
+        );
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone(), batch.clone()], vec![batch]],
+            schema,
+            None,
+        )?);
+        let filter = Arc::new(FilterExec::try_new(
+            lit(ScalarValue::Boolean(Some(true))),
+            input,
+        )?);
+        let plan = monitor_plan(filter)?;
+
+        let before = snapshot(plan.as_ref());
+        assert!(before.description.starts_with("FilterExec"));
+        assert_eq!(before.output_rows(), Some(0));
+        assert_eq!(before.children.len(), 1);
+        assert!(before.children[0].children.is_empty());
+
+        // consume the first batch of the first partition only
+        let mut stream = plan.execute(0).await?;
+        stream.next().await.unwrap()?;
+
+        let running = snapshot(plan.as_ref());
+        assert_eq!(
+            running.partitions[0],
+            PartitionSnapshot {
+                output_rows: 3,
+                output_batches: 1,
+                finished: false,
+            }
+        );
+        assert_eq!(running.output_rows(), Some(3));
+
+        while stream.next().await.is_some() {}
+        let done = snapshot(plan.as_ref());
+        assert!(done.partitions[0].finished);
+        assert!(!done.partitions[1].finished);
+        assert_eq!(done.output_rows(), Some(6));
+        assert_eq!(
+            done.to_string().lines().nth(1).unwrap(),
+            "  MemoryExec: partitions=2, partition_sizes=[2, 1], metrics=[], \
+             partition_rows=[6, 0 (running)]"
+        );
+        Ok(())
+    }
+}
@@ -17,8 +17,11 @@
 
 //! Execution plan for reading Parquet files
 
+use instant::Instant;
 use std::fmt;
 use std::fs::File;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::{any::Any, convert::TryInto};
@@ -28,7 +31,8 @@ use crate::{
     logical_plan::Expr,
     physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
     physical_plan::{
-        common, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
+        common, retry::RetryPolicy, runtime_filter::RuntimeFilter, DisplayFormatType,
+        ExecutionPlan, MetricsSet, Partitioning, RecordBatchStream, SQLMetric,
         SendableRecordBatchStream,
     },
     scalar::ScalarValue,
@@ -49,10 +53,13 @@ use parquet::file::{
 use fmt::Debug;
 use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
 
+#[cfg(not(target_arch = "wasm32"))]
 use tokio::{
+    runtime::Handle,
     sync::mpsc::{channel, Receiver, Sender},
     task,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::datasource::datasource::{ColumnStatistics, Statistics};
@@ -74,8 +81,23 @@ pub struct ParquetExec {
     statistics: Statistics,
     /// Optional predicate builder
     predicate_builder: Option<PruningPredicate>,
+    /// Schema of the files, before the projection is applied
+    file_schema: SchemaRef,
+    /// Filters whose bounds are set at execution time, which prune row groups
+    /// like the predicate
+    runtime_filters: Vec<Arc<RuntimeFilter>>,
     /// Optional limit of the number of rows
     limit: Option<usize>,
+    /// Number of rows read from all partitions
+    output_rows: Arc<SQLMetric>,
+    /// Time spent decoding batches, in nanoseconds
+    elapsed_compute: Arc<SQLMetric>,
+    /// Policy for retrying to open files
+    retry_policy: RetryPolicy,
+    /// Number of times opening a file was retried
+    read_retries: Arc<SQLMetric>,
+    /// Number of batches read ahead of the consumer
+    read_ahead: usize,
 }
 
 /// Represents one partition of a Parquet data set and this currently means one Parquet file.
@@ -308,12 +330,57 @@ impl ParquetExec {
             schema: Arc::new(projected_schema),
             projection,
             predicate_builder,
+            file_schema: schema,
+            runtime_filters: vec![],
             batch_size,
             statistics,
             limit,
+            output_rows: SQLMetric::counter(),
+            elapsed_compute: SQLMetric::time_nanos(),
+            retry_policy: RetryPolicy::default(),
+            read_retries: SQLMetric::counter(),
+            read_ahead: common::DEFAULT_READ_AHEAD,
         }
     }
 
+    /// Customize the policy for retrying to open files, which does not retry
+    /// by default
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Policy for retrying to open files
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Customize the number of batches each partition reads ahead of its
+    /// consumer, [`common::DEFAULT_READ_AHEAD`] by default. As the files are
+    /// read on a separate thread, at least one batch is always read ahead.
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Number of batches each partition reads ahead of its consumer
+    pub fn read_ahead(&self) -> usize {
+        self.read_ahead
+    }
+
+    /// Returns this scan also skipping the row groups that can not match
+    /// `filter`, according to the bounds of the filter when a partition is
+    /// executed. Filters on columns that the files do not have are ignored.
+    pub fn with_runtime_filter(mut self, filter: Arc<RuntimeFilter>) -> Self {
+        self.runtime_filters.push(filter);
+        self
+    }
+
+    /// Filters whose bounds are set at execution time
+    pub fn runtime_filters(&self) -> &[Arc<RuntimeFilter>] {
+        &self.runtime_filters
+    }
+
     /// Parquet partitions to read
     pub fn partitions(&self) -> &[ParquetPartition] {
         &self.partitions
@@ -391,35 +458,81 @@ impl ExecutionPlan for ParquetExec {
     }
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
-        // because the parquet implementation is not thread-safe, it is necessary to execute
-        // on a thread and communicate with channels
-        let (response_tx, response_rx): (
-            Sender<ArrowResult<RecordBatch>>,
-            Receiver<ArrowResult<RecordBatch>>,
-        ) = channel(2);
-
         let filenames = self.partitions[partition].filenames.clone();
         let projection = self.projection.clone();
-        let predicate_builder = self.predicate_builder.clone();
+        // the bounds of the runtime filters are known by now, if ever
+        let predicate_builders = self
+            .predicate_builder
+            .iter()
+            .cloned()
+            .chain(self.runtime_filters.iter().filter_map(|filter| {
+                self.file_schema.field_with_name(filter.column()).ok()?;
+                PruningPredicate::try_new(&filter.predicate()?, self.file_schema.clone())
+                    .ok()
+            }))
+            .collect::<Vec<_>>();
         let batch_size = self.batch_size;
         let limit = self.limit;
+        let output_rows = self.output_rows.clone();
+        let elapsed_compute = self.elapsed_compute.clone();
+        let open_file = OpenFile {
+            #[cfg(not(target_arch = "wasm32"))]
+            handle: Handle::current(),
+            retry_policy: self.retry_policy,
+            read_retries: self.read_retries.clone(),
+        };
+
+        // because the parquet implementation is not thread-safe, it is necessary to execute
+        // on a thread and communicate with channels
+        #[cfg(not(target_arch = "wasm32"))]
+        let inner: BatchStream = {
+            let (response_tx, response_rx): (
+                Sender<ArrowResult<RecordBatch>>,
+                Receiver<ArrowResult<RecordBatch>>,
+            ) = channel(self.read_ahead.max(1));
+            task::spawn_blocking(move || {
+                if let Err(e) = read_files(
+                    &filenames,
+                    &projection,
+                    &predicate_builders,
+                    batch_size,
+                    &mut |result| send_result(&response_tx, result),
+                    limit,
+                    output_rows,
+                    elapsed_compute,
+                    open_file,
+                ) {
+                    println!("Parquet reader thread terminated due to error: {:?}", e);
+                }
+            });
+            Box::pin(ReceiverStream::new(response_rx))
+        };
 
-        task::spawn_blocking(move || {
-            if let Err(e) = read_files(
+        // wasm32 has no threads to read on, so the files are read inline. Errors
+        // are sent to the stream by `read_files`.
+        #[cfg(target_arch = "wasm32")]
+        let inner: BatchStream = {
+            let mut results = vec![];
+            let _ = read_files(
                 &filenames,
                 &projection,
-                &predicate_builder,
+                &predicate_builders,
                 batch_size,
-                response_tx,
+                &mut |result| {
+                    results.push(result);
+                    Ok(())
+                },
                 limit,
-            ) {
-                println!("Parquet reader thread terminated due to error: {:?}", e);
-            }
-        });
+                output_rows,
+                elapsed_compute,
+                open_file,
+            );
+            Box::pin(futures::stream::iter(results))
+        };
 
         Ok(Box::pin(ParquetStream {
             schema: self.schema.clone(),
-            inner: ReceiverStream::new(response_rx),
+            inner,
         }))
     }
 
@@ -448,8 +561,53 @@ impl ExecutionPlan for ParquetExec {
             }
         }
     }
+
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.output_rows);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.elapsed_compute);
+        metrics.push(MetricsSet::READ_RETRIES, &self.read_retries);
+        metrics
+    }
+}
+
+/// Opens the files read by a partition of a [`ParquetExec`] on its blocking thread
+struct OpenFile {
+    /// Runtime the retries wait on
+    #[cfg(not(target_arch = "wasm32"))]
+    handle: Handle,
+    retry_policy: RetryPolicy,
+    read_retries: Arc<SQLMetric>,
 }
 
+impl OpenFile {
+    /// Open `filename` and read its metadata, retrying according to the policy
+    fn open(&self, filename: &str) -> Result<SerializedFileReader<File>> {
+        let path = filename.to_owned();
+        let opened = self
+            .retry_policy
+            .run(filename, &self.read_retries, move || {
+                let file = File::open(&path)?;
+                Ok(SerializedFileReader::new(file)?)
+            });
+        self.block_on(opened)
+    }
+
+    /// Waits for `future` on the runtime
+    #[cfg(not(target_arch = "wasm32"))]
+    fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+        self.handle.block_on(future)
+    }
+
+    /// The retries neither sleep nor block on wasm32, so `future` is ready at
+    /// once
+    #[cfg(target_arch = "wasm32")]
+    fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+        futures::executor::block_on(future)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 fn send_result(
     response_tx: &Sender<ArrowResult<RecordBatch>>,
     result: ArrowResult<RecordBatch>,
@@ -568,19 +726,31 @@ fn build_row_group_predicate(
     Box::new(move |_, i| predicate_values[i])
 }
 
+#[allow(clippy::too_many_arguments)]
 fn read_files(
     filenames: &[String],
     projection: &[usize],
-    predicate_builder: &Option<PruningPredicate>,
+    predicate_builders: &[PruningPredicate],
     batch_size: usize,
-    response_tx: Sender<ArrowResult<RecordBatch>>,
+    send: &mut dyn FnMut(ArrowResult<RecordBatch>) -> Result<()>,
     limit: Option<usize>,
+    output_rows: Arc<SQLMetric>,
+    elapsed_compute: Arc<SQLMetric>,
+    open_file: OpenFile,
 ) -> Result<()> {
     let mut total_rows = 0;
     'outer: for filename in filenames {
-        let file = File::open(&filename)?;
-        let mut file_reader = SerializedFileReader::new(file)?;
-        if let Some(predicate_builder) = predicate_builder {
+        let mut file_reader = match open_file.open(filename) {
+            Ok(file_reader) => file_reader,
+            Err(e) => {
+                let err_msg = e.to_string();
+                // send error to operator
+                send(Err(ArrowError::ExternalError(Box::new(e))))?;
+                // terminate thread with error
+                return Err(DataFusionError::Execution(err_msg));
+            }
+        };
+        for predicate_builder in predicate_builders {
             let row_group_predicate = build_row_group_predicate(
                 predicate_builder,
                 file_reader.metadata().row_groups(),
@@ -591,11 +761,15 @@ fn read_files(
         let mut batch_reader = arrow_reader
             .get_record_reader_by_columns(projection.to_owned(), batch_size)?;
         loop {
-            match batch_reader.next() {
+            let now = Instant::now();
+            let next = batch_reader.next();
+            elapsed_compute.add_elapsed(now);
+            match next {
                 Some(Ok(batch)) => {
                     //println!("ParquetExec got new batch from {}", filename);
                     total_rows += batch.num_rows();
-                    send_result(&response_tx, Ok(batch))?;
+                    output_rows.add(batch.num_rows());
+                    send(Ok(batch))?;
                     if limit.map(|l| total_rows >= l).unwrap_or(false) {
                         break 'outer;
                     }
@@ -610,10 +784,7 @@ fn read_files(
                         e.to_string()
                     );
                     // send error to operator
-                    send_result(
-                        &response_tx,
-                        Err(ArrowError::ParquetError(err_msg.clone())),
-                    )?;
+                    send(Err(ArrowError::ParquetError(err_msg.clone())))?;
                     // terminate thread with error
                     return Err(DataFusionError::Execution(err_msg));
                 }
@@ -621,8 +792,7 @@ fn read_files(
         }
     }
 
-    // finished reading files (dropping response_tx will close
-    // channel)
+    // finished reading files (dropping `send` will close the channel)
     Ok(())
 }
 
@@ -634,9 +804,12 @@ fn split_files(filenames: &[String], n: usize) -> Vec<&[String]> {
     filenames.chunks(chunk_size).collect()
 }
 
+/// Batches read by [`read_files`]
+type BatchStream = Pin<Box<dyn Stream<Item = ArrowResult<RecordBatch>> + Send + Sync>>;
+
 struct ParquetStream {
     schema: SchemaRef,
-    inner: ReceiverStream<ArrowResult<RecordBatch>>,
+    inner: BatchStream,
 }
 
 impl Stream for ParquetStream {
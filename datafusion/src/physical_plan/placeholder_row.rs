@@ -0,0 +1,176 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Execution plan for the relation of a single row of nulls
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    memory::MemoryStream, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+};
+use arrow::array::{new_null_array, ArrayRef};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use super::SendableRecordBatchStream;
+
+use async_trait::async_trait;
+
+/// Execution plan for the relation of a single row, in which every column
+/// is null, such as the input of `SELECT 1`. As a batch needs at least one
+/// column, an empty schema is replaced by a single `placeholder` column of
+/// type [`DataType::Null`].
+#[derive(Debug)]
+pub struct PlaceholderRowExec {
+    /// The schema of the produced row
+    schema: SchemaRef,
+}
+
+impl PlaceholderRowExec {
+    /// Create a new PlaceholderRowExec
+    pub fn new(schema: SchemaRef) -> Self {
+        let schema = if schema.fields().is_empty() {
+            Arc::new(Schema::new(vec![Field::new(
+                "placeholder",
+                DataType::Null,
+                true,
+            )]))
+        } else {
+            schema
+        };
+        Self { schema }
+    }
+
+    fn data(&self) -> Result<Vec<RecordBatch>> {
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| new_null_array(field.data_type(), 1))
+            .collect::<Vec<ArrayRef>>();
+        Ok(vec![RecordBatch::try_new(self.schema.clone(), columns)?])
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for PlaceholderRowExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::UnspecifiedDistribution
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            0 => Ok(Arc::new(PlaceholderRowExec::new(self.schema.clone()))),
+            _ => Err(DataFusionError::Internal(
+                "PlaceholderRowExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        // PlaceholderRowExec has a single output partition, so that the
+        // relation has exactly one row
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "PlaceholderRowExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+
+        Ok(Box::pin(MemoryStream::try_new(
+            self.data()?,
+            self.schema.clone(),
+            None,
+        )?))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "PlaceholderRowExec")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::test;
+
+    #[tokio::test]
+    async fn produce_one_row() -> Result<()> {
+        let schema = test::aggr_test_schema();
+        let placeholder = PlaceholderRowExec::new(schema.clone());
+        assert_eq!(placeholder.schema(), schema);
+
+        let iter = placeholder.execute(0).await?;
+        let batches = common::collect(iter).await?;
+
+        // should have one row of nulls
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 1);
+        assert_eq!(batches[0].schema(), schema);
+        assert!(batches[0].columns().iter().all(|c| c.is_null(0)));
+
+        // ask for the wrong partition
+        assert!(placeholder.execute(1).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn placeholder_column_for_empty_schema() -> Result<()> {
+        let placeholder = PlaceholderRowExec::new(Arc::new(Schema::empty()));
+        assert_eq!(placeholder.schema().fields().len(), 1);
+
+        let iter = placeholder.execute(0).await?;
+        let batches = common::collect(iter).await?;
+        assert_eq!(batches[0].num_rows(), 1);
+        assert_eq!(batches[0].schema(), placeholder.schema());
+
+        let placeholder2 = placeholder.with_new_children(vec![])?;
+        assert_eq!(placeholder.schema(), placeholder2.schema());
+        Ok(())
+    }
+}
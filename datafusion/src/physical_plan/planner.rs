@@ -25,20 +25,34 @@ use super::{
 };
 use crate::execution::context::ExecutionContextState;
 use crate::logical_plan::{
-    DFSchema, Expr, LogicalPlan, Operator, Partitioning as LogicalPartitioning, PlanType,
-    StringifiedPlan, UserDefinedLogicalNode,
+    DFSchema, Expr, ExprRewriter, LogicalPlan, Operator,
+    Partitioning as LogicalPartitioning, PlanType, StringifiedPlan,
+    UserDefinedLogicalNode,
 };
+use crate::physical_plan::analyze::AnalyzeExec;
+use crate::physical_plan::async_udf::{AsyncFuncExec, AsyncFunctionExpr};
+use crate::physical_plan::csv::CsvExec;
+use crate::physical_plan::deduplicate::DeduplicateExec;
 use crate::physical_plan::explain::ExplainExec;
 use crate::physical_plan::expressions;
 use crate::physical_plan::expressions::{CaseExpr, Column, Literal, PhysicalSortExpr};
 use crate::physical_plan::filter::FilterExec;
 use crate::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
 use crate::physical_plan::hash_join::HashJoinExec;
+use crate::physical_plan::interleave::{can_interleave, InterleaveExec};
+use crate::physical_plan::io_runtime::IoRuntimeExec;
 use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use crate::physical_plan::parquet::ParquetExec;
+use crate::physical_plan::placeholder_row::PlaceholderRowExec;
 use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::query_limit::QueryLimitExec;
 use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::retry::RetryPolicy;
+use crate::physical_plan::sample::SampleExec;
 use crate::physical_plan::sort::SortExec;
 use crate::physical_plan::udf;
+use crate::physical_plan::unnest::UnnestExec;
+use crate::physical_plan::values::ValuesExec;
 use crate::physical_plan::windows::WindowAggExec;
 use crate::physical_plan::{hash_utils, Partitioning};
 use crate::physical_plan::{
@@ -51,8 +65,8 @@ use crate::{
     error::{DataFusionError, Result},
     physical_plan::displayable,
 };
-use arrow::compute::SortOptions;
-use arrow::datatypes::{Schema, SchemaRef};
+use arrow::compute::{CastOptions, SortOptions};
+use arrow::datatypes::{Field, Schema, SchemaRef};
 use arrow::{compute::can_cast_types, datatypes::DataType};
 use expressions::col;
 use log::debug;
@@ -92,6 +106,18 @@ impl PhysicalPlanner for DefaultPhysicalPlanner {
         logical_plan: &LogicalPlan,
         ctx_state: &ExecutionContextState,
     ) -> Result<Arc<dyn ExecutionPlan>> {
+        // the operators of a query with a memory limit reserve their memory
+        // within the limit of the query
+        let query_state;
+        let ctx_state = if ctx_state.runtime_env.query_limits.max_memory.is_some() {
+            query_state = ExecutionContextState {
+                runtime_env: Arc::new(ctx_state.runtime_env.for_query()),
+                ..ctx_state.clone()
+            };
+            &query_state
+        } else {
+            ctx_state
+        };
         let plan = self.create_initial_plan(logical_plan, ctx_state)?;
         self.optimize_plan(plan, ctx_state)
     }
@@ -122,6 +148,27 @@ impl DefaultPhysicalPlanner {
             new_plan = optimizer.optimize(new_plan, &ctx_state.config)?;
         }
         debug!("Optimized physical plan:\n{:?}", new_plan);
+
+        if ctx_state.config.plan_monitoring {
+            new_plan = crate::physical_plan::monitor::monitor_plan(new_plan)?;
+        }
+
+        #[cfg(feature = "tracing")]
+        if ctx_state.config.tracing {
+            new_plan = crate::physical_plan::traced::instrument_plan(new_plan)?;
+        }
+
+        let runtime_env = &ctx_state.runtime_env;
+        if !runtime_env.query_limits.is_unlimited() {
+            new_plan = Arc::new(
+                QueryLimitExec::new(
+                    new_plan,
+                    runtime_env.query_limits,
+                    runtime_env.admission.clone(),
+                )
+                .with_time_source(ctx_state.config.time_source().clone()),
+            );
+        }
         Ok(new_plan)
     }
 
@@ -140,7 +187,16 @@ impl DefaultPhysicalPlanner {
                 filters,
                 limit,
                 ..
-            } => source.scan(projection, batch_size, filters, *limit),
+            } => {
+                let scan = source.scan(projection, batch_size, filters, *limit)?;
+                let scan = with_read_retry(scan, ctx_state.runtime_env.read_retry);
+                match &ctx_state.runtime_env.io_runtime {
+                    Some(handle) => {
+                        Ok(Arc::new(IoRuntimeExec::new(scan, handle.clone())))
+                    }
+                    None => Ok(scan),
+                }
+            }
             LogicalPlan::Window {
                 input, window_expr, ..
             } => {
@@ -180,6 +236,7 @@ impl DefaultPhysicalPlanner {
                 let input_schema = input_exec.schema();
                 let physical_input_schema = input_exec.as_ref().schema();
                 let logical_input_schema = input.as_ref().schema();
+                let aggregation_shards = ctx_state.config.aggregation_shards;
 
                 let groups = group_expr
                     .iter()
@@ -206,13 +263,17 @@ impl DefaultPhysicalPlanner {
                     })
                     .collect::<Result<Vec<_>>>()?;
 
-                let initial_aggr = Arc::new(HashAggregateExec::try_new(
-                    AggregateMode::Partial,
-                    groups.clone(),
-                    aggregates.clone(),
-                    input_exec,
-                    input_schema.clone(),
-                )?);
+                let initial_aggr = Arc::new(
+                    HashAggregateExec::try_new(
+                        AggregateMode::Partial,
+                        groups.clone(),
+                        aggregates.clone(),
+                        input_exec,
+                        input_schema.clone(),
+                    )?
+                    .with_output_batch_size(batch_size)
+                    .with_num_shards(aggregation_shards),
+                );
 
                 let final_group: Vec<Arc<dyn PhysicalExpr>> =
                     (0..groups.len()).map(|i| col(&groups[i].1)).collect();
@@ -228,53 +289,67 @@ impl DefaultPhysicalPlanner {
                     && ctx_state.config.repartition_aggregations
                     && !contains_dict
                 {
+                    let partitioning = Partitioning::Hash(
+                        final_group.clone(),
+                        ctx_state.config.concurrency,
+                    );
+                    let hash_seeds = ctx_state.config.hash_seeds;
+
                     // Divide partial hash aggregates into multiple partitions by hash key
-                    let hash_repartition = Arc::new(RepartitionExec::try_new(
-                        initial_aggr,
-                        Partitioning::Hash(
-                            final_group.clone(),
-                            ctx_state.config.concurrency,
-                        ),
-                    )?);
+                    let hash_repartition = Arc::new(
+                        RepartitionExec::try_new(initial_aggr, partitioning)?
+                            .with_hash_seeds(hash_seeds),
+                    );
 
                     // Combine hashaggregates within the partition
-                    Ok(Arc::new(HashAggregateExec::try_new(
-                        AggregateMode::FinalPartitioned,
-                        final_group
-                            .iter()
-                            .enumerate()
-                            .map(|(i, expr)| (expr.clone(), groups[i].1.clone()))
-                            .collect(),
-                        aggregates,
-                        hash_repartition,
-                        input_schema,
-                    )?))
+                    Ok(Arc::new(
+                        HashAggregateExec::try_new(
+                            AggregateMode::FinalPartitioned,
+                            final_group
+                                .iter()
+                                .enumerate()
+                                .map(|(i, expr)| (expr.clone(), groups[i].1.clone()))
+                                .collect(),
+                            aggregates,
+                            hash_repartition,
+                            input_schema,
+                        )?
+                        .with_output_batch_size(batch_size)
+                        .with_num_shards(aggregation_shards),
+                    ))
                 } else {
                     // construct a second aggregation, keeping the final column name equal to the first aggregation
                     // and the expressions corresponding to the respective aggregate
 
-                    Ok(Arc::new(HashAggregateExec::try_new(
-                        AggregateMode::Final,
-                        final_group
-                            .iter()
-                            .enumerate()
-                            .map(|(i, expr)| (expr.clone(), groups[i].1.clone()))
-                            .collect(),
-                        aggregates,
-                        initial_aggr,
-                        input_schema,
-                    )?))
+                    Ok(Arc::new(
+                        HashAggregateExec::try_new(
+                            AggregateMode::Final,
+                            final_group
+                                .iter()
+                                .enumerate()
+                                .map(|(i, expr)| (expr.clone(), groups[i].1.clone()))
+                                .collect(),
+                            aggregates,
+                            initial_aggr,
+                            input_schema,
+                        )?
+                        .with_output_batch_size(batch_size)
+                        .with_num_shards(aggregation_shards),
+                    ))
                 }
             }
             LogicalPlan::Projection { input, expr, .. } => {
                 let input_exec = self.create_initial_plan(input, ctx_state)?;
                 let input_schema = input.as_ref().schema();
+                let (input_exec, rewritten_expr) =
+                    self.plan_async_functions(input_exec, expr, ctx_state)?;
                 let runtime_expr = expr
                     .iter()
-                    .map(|e| {
+                    .zip(rewritten_expr.iter())
+                    .map(|(e, rewritten)| {
                         tuple_err((
                             self.create_physical_expr(
-                                e,
+                                rewritten,
                                 &input_exec.schema(),
                                 &ctx_state,
                             ),
@@ -289,16 +364,42 @@ impl DefaultPhysicalPlanner {
             } => {
                 let input = self.create_initial_plan(input, ctx_state)?;
                 let input_schema = input.as_ref().schema();
-                let runtime_expr =
-                    self.create_physical_expr(predicate, &input_schema, ctx_state)?;
-                Ok(Arc::new(FilterExec::try_new(runtime_expr, input)?))
+                let (async_input, mut rewritten) = self.plan_async_functions(
+                    input.clone(),
+                    &[predicate.clone()],
+                    ctx_state,
+                )?;
+                let runtime_expr = self.create_physical_expr(
+                    &rewritten.remove(0),
+                    &async_input.schema(),
+                    ctx_state,
+                )?;
+                let has_async_functions =
+                    async_input.schema().fields().len() > input_schema.fields().len();
+                let filter: Arc<dyn ExecutionPlan> =
+                    Arc::new(FilterExec::try_new(runtime_expr, async_input)?);
+                if !has_async_functions {
+                    return Ok(filter);
+                }
+                // drop the results of async UDFs again
+                let columns = input_schema
+                    .fields()
+                    .iter()
+                    .map(|f| (col(f.name()), f.name().clone()))
+                    .collect();
+                Ok(Arc::new(ProjectionExec::try_new(columns, filter)?))
             }
             LogicalPlan::Union { inputs, .. } => {
                 let physical_plans = inputs
                     .iter()
                     .map(|input| self.create_initial_plan(input, ctx_state))
                     .collect::<Result<Vec<_>>>()?;
-                Ok(Arc::new(UnionExec::new(physical_plans)))
+                if can_interleave(&physical_plans) {
+                    // keep the hash partitioning shared by all inputs
+                    Ok(Arc::new(InterleaveExec::try_new(physical_plans)?))
+                } else {
+                    Ok(Arc::new(UnionExec::try_new(physical_plans)?))
+                }
             }
             LogicalPlan::Repartition {
                 input,
@@ -320,10 +421,10 @@ impl DefaultPhysicalPlanner {
                         Partitioning::Hash(runtime_expr, *n)
                     }
                 };
-                Ok(Arc::new(RepartitionExec::try_new(
-                    input,
-                    physical_partitioning,
-                )?))
+                Ok(Arc::new(
+                    RepartitionExec::try_new(input, physical_partitioning)?
+                        .with_hash_seeds(ctx_state.config.hash_seeds),
+                ))
             }
             LogicalPlan::Sort { expr, input, .. } => {
                 let input = self.create_initial_plan(input, ctx_state)?;
@@ -367,35 +468,57 @@ impl DefaultPhysicalPlanner {
                     JoinType::Left => hash_utils::JoinType::Left,
                     JoinType::Right => hash_utils::JoinType::Right,
                     JoinType::Full => hash_utils::JoinType::Full,
+                    JoinType::Semi => hash_utils::JoinType::Semi,
+                    JoinType::Anti => hash_utils::JoinType::Anti,
                 };
-                if ctx_state.config.concurrency > 1 && ctx_state.config.repartition_joins
+                let join = if ctx_state.config.concurrency > 1
+                    && ctx_state.config.repartition_joins
                 {
                     let left_expr = keys.iter().map(|x| col(&x.0)).collect();
                     let right_expr = keys.iter().map(|x| col(&x.1)).collect();
 
                     // Use hash partition by defualt to parallelize hash joins
-                    Ok(Arc::new(HashJoinExec::try_new(
-                        Arc::new(RepartitionExec::try_new(
-                            left,
-                            Partitioning::Hash(left_expr, ctx_state.config.concurrency),
-                        )?),
-                        Arc::new(RepartitionExec::try_new(
-                            right,
-                            Partitioning::Hash(right_expr, ctx_state.config.concurrency),
-                        )?),
+                    HashJoinExec::try_new(
+                        Arc::new(
+                            RepartitionExec::try_new(
+                                left,
+                                Partitioning::Hash(
+                                    left_expr,
+                                    ctx_state.config.concurrency,
+                                ),
+                            )?
+                            .with_hash_seeds(ctx_state.config.hash_seeds),
+                        ),
+                        Arc::new(
+                            RepartitionExec::try_new(
+                                right,
+                                Partitioning::Hash(
+                                    right_expr,
+                                    ctx_state.config.concurrency,
+                                ),
+                            )?
+                            .with_hash_seeds(ctx_state.config.hash_seeds),
+                        ),
                         &keys,
                         &physical_join_type,
                         PartitionMode::Partitioned,
-                    )?))
+                    )?
                 } else {
-                    Ok(Arc::new(HashJoinExec::try_new(
+                    HashJoinExec::try_new(
                         left,
                         right,
                         &keys,
                         &physical_join_type,
                         PartitionMode::CollectLeft,
-                    )?))
-                }
+                    )?
+                };
+                let join = join
+                    .with_output_batch_size(ctx_state.config.batch_size)
+                    .with_runtime(ctx_state.runtime_env.clone());
+                Ok(Arc::new(match &ctx_state.config.replan_hook {
+                    Some(replan_hook) => join.with_replan_hook(replan_hook.clone()),
+                    None => join,
+                }))
             }
             LogicalPlan::CrossJoin { left, right, .. } => {
                 let left = self.create_initial_plan(left, ctx_state)?;
@@ -405,10 +528,31 @@ impl DefaultPhysicalPlanner {
             LogicalPlan::EmptyRelation {
                 produce_one_row,
                 schema,
-            } => Ok(Arc::new(EmptyExec::new(
-                *produce_one_row,
-                SchemaRef::new(schema.as_ref().to_owned().into()),
-            ))),
+            } => {
+                let schema = SchemaRef::new(schema.as_ref().to_owned().into());
+                if *produce_one_row {
+                    Ok(Arc::new(PlaceholderRowExec::new(schema)))
+                } else {
+                    Ok(Arc::new(EmptyExec::new(schema)))
+                }
+            }
+            LogicalPlan::Values { schema, values } => {
+                let exec_schema = Schema::empty();
+                let exprs = values
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|expr| {
+                                self.create_physical_expr(expr, &exec_schema, ctx_state)
+                            })
+                            .collect::<Result<Vec<_>>>()
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(ValuesExec::try_new(
+                    SchemaRef::new(schema.as_ref().to_owned().into()),
+                    exprs,
+                )?))
+            }
             LogicalPlan::Limit { input, n, .. } => {
                 let limit = *n;
                 let input = self.create_initial_plan(input, ctx_state)?;
@@ -424,6 +568,36 @@ impl DefaultPhysicalPlanner {
 
                 Ok(Arc::new(GlobalLimitExec::new(input, limit)))
             }
+            LogicalPlan::Deduplicate {
+                input: logical_input,
+                columns,
+                keep,
+            } => {
+                let input = self.create_initial_plan(logical_input, ctx_state)?;
+                let key_columns = columns
+                    .iter()
+                    .map(|name| input.schema().index_of(name))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok(Arc::new(
+                    DeduplicateExec::try_new(input, key_columns, *keep)?
+                        .with_sorted(is_sorted_by(logical_input, columns)),
+                ))
+            }
+            LogicalPlan::Sample {
+                input,
+                method,
+                seed,
+            } => {
+                let input = self.create_initial_plan(input, ctx_state)?;
+                Ok(Arc::new(
+                    SampleExec::try_new(input, *method)?.with_seed(*seed),
+                ))
+            }
+            LogicalPlan::Unnest { input, column, .. } => {
+                let input = self.create_initial_plan(input, ctx_state)?;
+                let index = input.schema().index_of(column)?;
+                Ok(Arc::new(UnnestExec::try_new(input, index)?))
+            }
             LogicalPlan::CreateExternalTable { .. } => {
                 // There is no default plan for "CREATE EXTERNAL
                 // TABLE" -- it must be handled at a higher level (so
@@ -433,6 +607,13 @@ impl DefaultPhysicalPlanner {
                     "Unsupported logical plan: CreateExternalTable".to_string(),
                 ))
             }
+            LogicalPlan::SetVariable { .. } => {
+                // "SET" changes the configuration of the context and must
+                // be handled at a higher level as well
+                Err(DataFusionError::Internal(
+                    "Unsupported logical plan: SetVariable".to_string(),
+                ))
+            }
             LogicalPlan::Explain {
                 verbose,
                 plan,
@@ -459,6 +640,18 @@ impl DefaultPhysicalPlanner {
                     stringified_plans,
                 )))
             }
+            LogicalPlan::Analyze {
+                verbose,
+                input,
+                schema,
+            } => {
+                let input = self.create_initial_plan(input, ctx_state)?;
+                Ok(Arc::new(AnalyzeExec::new(
+                    *verbose,
+                    input,
+                    SchemaRef::new(schema.as_ref().to_owned().into()),
+                )))
+            }
             LogicalPlan::Extension { node } => {
                 let inputs = node
                     .inputs()
@@ -592,10 +785,13 @@ impl DefaultPhysicalPlanner {
                     else_expr,
                 )?))
             }
-            Expr::Cast { expr, data_type } => expressions::cast(
+            Expr::Cast { expr, data_type } => expressions::cast_with_options(
                 self.create_physical_expr(expr, input_schema, ctx_state)?,
                 input_schema,
                 data_type.clone(),
+                CastOptions {
+                    safe: ctx_state.config.safe_casts,
+                },
             ),
             Expr::TryCast { expr, data_type } => expressions::try_cast(
                 self.create_physical_expr(expr, input_schema, ctx_state)?,
@@ -685,40 +881,44 @@ impl DefaultPhysicalPlanner {
                         self.create_physical_expr(expr, input_schema, ctx_state)?;
                     let value_expr_data_type = value_expr.data_type(input_schema)?;
 
-                    let list_exprs =
-                        list.iter()
-                            .map(|expr| match expr {
-                                Expr::Literal(ScalarValue::Utf8(None)) => self
-                                    .create_physical_expr(expr, input_schema, ctx_state),
-                                _ => {
-                                    let list_expr = self.create_physical_expr(
-                                        expr,
+                    let list_exprs = list
+                        .iter()
+                        .map(|expr| match expr {
+                            Expr::Literal(ScalarValue::Utf8(None)) => {
+                                self.create_physical_expr(expr, input_schema, ctx_state)
+                            }
+                            _ => {
+                                let list_expr = self.create_physical_expr(
+                                    expr,
+                                    input_schema,
+                                    ctx_state,
+                                )?;
+                                let list_expr_data_type =
+                                    list_expr.data_type(input_schema)?;
+
+                                if list_expr_data_type == value_expr_data_type
+                                    || (expressions::is_binary(&list_expr_data_type)
+                                        && expressions::is_binary(&value_expr_data_type))
+                                {
+                                    Ok(list_expr)
+                                } else if can_cast_types(
+                                    &list_expr_data_type,
+                                    &value_expr_data_type,
+                                ) {
+                                    expressions::cast(
+                                        list_expr,
                                         input_schema,
-                                        ctx_state,
-                                    )?;
-                                    let list_expr_data_type =
-                                        list_expr.data_type(input_schema)?;
-
-                                    if list_expr_data_type == value_expr_data_type {
-                                        Ok(list_expr)
-                                    } else if can_cast_types(
-                                        &list_expr_data_type,
-                                        &value_expr_data_type,
-                                    ) {
-                                        expressions::cast(
-                                            list_expr,
-                                            input_schema,
-                                            value_expr.data_type(input_schema)?,
-                                        )
-                                    } else {
-                                        Err(DataFusionError::Plan(format!(
-                                            "Unsupported CAST from {:?} to {:?}",
-                                            list_expr_data_type, value_expr_data_type
-                                        )))
-                                    }
+                                        value_expr.data_type(input_schema)?,
+                                    )
+                                } else {
+                                    Err(DataFusionError::Plan(format!(
+                                        "Unsupported CAST from {:?} to {:?}",
+                                        list_expr_data_type, value_expr_data_type
+                                    )))
                                 }
-                            })
-                            .collect::<Result<Vec<_>>>()?;
+                            }
+                        })
+                        .collect::<Result<Vec<_>>>()?;
 
                     expressions::in_list(value_expr, list_exprs, negated)
                 }
@@ -813,6 +1013,38 @@ impl DefaultPhysicalPlanner {
         }
     }
 
+    /// Moves the calls of async UDFs in `exprs` into an [`AsyncFuncExec`] on
+    /// top of `input`, returning it and `exprs` with these calls replaced by
+    /// its output columns. Returns `input` itself if there are no such calls.
+    ///
+    /// Note that async UDFs are evaluated for all rows, even if they are
+    /// called in a branch of a CASE or in the right operand of AND or OR.
+    fn plan_async_functions(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        exprs: &[Expr],
+        ctx_state: &ExecutionContextState,
+    ) -> Result<(Arc<dyn ExecutionPlan>, Vec<Expr>)> {
+        let mut rewriter = AsyncFunctionRewriter {
+            planner: self,
+            ctx_state,
+            schema: input.schema().as_ref().clone(),
+            calls: vec![],
+            exprs: vec![],
+        };
+        let exprs = exprs
+            .iter()
+            .map(|e| e.clone().rewrite(&mut rewriter))
+            .collect::<Result<Vec<_>>>()?;
+        if rewriter.exprs.is_empty() {
+            return Ok((input, exprs));
+        }
+        Ok((
+            Arc::new(AsyncFuncExec::try_new(input, rewriter.exprs)?),
+            exprs,
+        ))
+    }
+
     /// Create an aggregate expression from a logical expression
     pub fn create_physical_sort_expr(
         &self,
@@ -828,6 +1060,97 @@ impl DefaultPhysicalPlanner {
     }
 }
 
+/// Apply `retry_policy` to `scan` if it reads files
+fn with_read_retry(
+    scan: Arc<dyn ExecutionPlan>,
+    retry_policy: RetryPolicy,
+) -> Arc<dyn ExecutionPlan> {
+    if retry_policy.is_disabled() {
+        return scan;
+    }
+    if let Some(parquet) = scan.as_any().downcast_ref::<ParquetExec>() {
+        Arc::new(parquet.clone().with_retry_policy(retry_policy))
+    } else if let Some(csv) = scan.as_any().downcast_ref::<CsvExec>() {
+        Arc::new(csv.clone().with_retry_policy(retry_policy))
+    } else {
+        scan
+    }
+}
+
+/// Replaces calls of async UDFs by columns holding their results, collecting
+/// the calls to evaluate. Equal calls are only evaluated once.
+struct AsyncFunctionRewriter<'a> {
+    planner: &'a DefaultPhysicalPlanner,
+    ctx_state: &'a ExecutionContextState,
+    /// The input schema extended by the results of the collected calls
+    schema: Schema,
+    /// The collected calls, with async UDF calls in their arguments replaced
+    calls: Vec<Expr>,
+    /// The physical expressions of the collected calls and their column names
+    exprs: Vec<(AsyncFunctionExpr, String)>,
+}
+
+impl<'a> ExprRewriter for AsyncFunctionRewriter<'a> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        let async_udf = match &expr {
+            Expr::ScalarUDF { fun, .. } => match &fun.async_udf {
+                Some(async_udf) => async_udf.clone(),
+                None => return Ok(expr),
+            },
+            _ => return Ok(expr),
+        };
+        if let Some(i) = self.calls.iter().position(|call| call == &expr) {
+            return Ok(Expr::Column(self.exprs[i].1.clone()));
+        }
+
+        // plan the call like a synchronous UDF to coerce its arguments
+        let physical_expr =
+            self.planner
+                .create_physical_expr(&expr, &self.schema, self.ctx_state)?;
+        let call = physical_expr
+            .as_any()
+            .downcast_ref::<functions::ScalarFunctionExpr>()
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Unexpected physical expression for async UDF '{}'",
+                    async_udf.name
+                ))
+            })?;
+        let name = format!("__async_{}", self.exprs.len());
+        let mut fields = self.schema.fields().clone();
+        fields.push(Field::new(&name, call.return_type().clone(), true));
+        self.schema = Schema::new(fields);
+        self.exprs.push((
+            AsyncFunctionExpr::new(
+                async_udf,
+                call.args().to_vec(),
+                call.return_type().clone(),
+            ),
+            name.clone(),
+        ));
+        self.calls.push(expr);
+        Ok(Expr::Column(name))
+    }
+}
+
+/// Whether `plan` sorts its output by the columns `columns` in any order,
+/// possibly followed by other expressions, so that the rows of equal values
+/// of these columns are adjacent
+fn is_sorted_by(plan: &LogicalPlan, columns: &[String]) -> bool {
+    match plan {
+        LogicalPlan::Sort { expr, .. } if expr.len() >= columns.len() => {
+            expr[..columns.len()].iter().all(|e| match e {
+                Expr::Sort { expr, .. } => match expr.as_ref() {
+                    Expr::Column(name) => columns.contains(name),
+                    _ => false,
+                },
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
 fn tuple_err<T, R>(value: (Result<T>, Result<R>)) -> Result<(T, R)> {
     match value {
         (Ok(e), Ok(e1)) => Ok((e, e1)),
@@ -1115,6 +1438,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn union_of_hash_partitioned_inputs_interleaves() -> Result<()> {
+        let testdata = arrow::util::test_util::arrow_test_data();
+        let path = format!("{}/csv/aggregate_test_100.csv", testdata);
+
+        let options = CsvReadOptions::new().schema_infer_max_records(100);
+        let partitioning = crate::logical_plan::Partitioning::Hash(vec![col("c1")], 4);
+        let logical_plan = LogicalPlanBuilder::scan_csv(&path, options, None)?
+            .repartition(partitioning.clone())?
+            .union(
+                LogicalPlanBuilder::scan_csv(&path, options, None)?
+                    .repartition(partitioning)?
+                    .build()?,
+            )?
+            .build()?;
+
+        let execution_plan = plan(&logical_plan)?;
+        let formatted = displayable(execution_plan.as_ref()).indent().to_string();
+
+        // identically hash partitioned inputs keep their partitioning
+        assert!(formatted.contains("InterleaveExec"), "{}", formatted);
+        assert_eq!(execution_plan.output_partitioning().partition_count(), 4);
+
+        Ok(())
+    }
+
     /// An example extension node that doesn't do anything
     struct NoOpExtensionNode {
         schema: DFSchemaRef,
@@ -26,6 +26,7 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::error::{DataFusionError, Result};
+use crate::physical_plan::expressions::with_partition;
 use crate::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
 };
@@ -105,6 +106,10 @@ impl ExecutionPlan for ProjectionExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
         self.input.output_partitioning()
@@ -130,6 +135,7 @@ impl ExecutionPlan for ProjectionExec {
             schema: self.schema.clone(),
             expr: self.expr.iter().map(|x| x.0.clone()).collect(),
             input: self.input.execute(partition).await?,
+            partition,
         }))
     }
 
@@ -161,12 +167,13 @@ impl ExecutionPlan for ProjectionExec {
 
 fn batch_project(
     batch: &RecordBatch,
+    partition: usize,
     expressions: &[Arc<dyn PhysicalExpr>],
     schema: &SchemaRef,
 ) -> ArrowResult<RecordBatch> {
     expressions
         .iter()
-        .map(|expr| expr.evaluate(&batch))
+        .map(|expr| with_partition(partition, || expr.evaluate(&batch)))
         .map(|r| r.map(|v| v.into_array(batch.num_rows())))
         .collect::<Result<Vec<_>>>()
         .map_or_else(
@@ -180,6 +187,8 @@ struct ProjectionStream {
     schema: SchemaRef,
     expr: Vec<Arc<dyn PhysicalExpr>>,
     input: SendableRecordBatchStream,
+    /// The partition of the input, for expressions that depend on it
+    partition: usize,
 }
 
 impl Stream for ProjectionStream {
@@ -190,7 +199,12 @@ impl Stream for ProjectionStream {
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
         self.input.poll_next_unpin(cx).map(|x| match x {
-            Some(Ok(batch)) => Some(batch_project(&batch, &self.expr, &self.schema)),
+            Some(Ok(batch)) => Some(batch_project(
+                &batch,
+                self.partition,
+                &self.expr,
+                &self.schema,
+            )),
             other => other,
         })
     }
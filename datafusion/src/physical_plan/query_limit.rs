@@ -0,0 +1,581 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the execution plan enforcing the [`QueryLimits`] of a query
+
+use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use tokio::sync::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::{sleep, Sleep};
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::{SystemTimeSource, TimeSource};
+use crate::execution::query_limits::{AdmissionController, AdmissionPermit, QueryLimits};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, MetricsSet, Partitioning};
+
+/// Enforces the [`QueryLimits`] on the plan below it, which is expected to
+/// be the complete plan of a query.
+///
+/// The query is admitted by the [`AdmissionController`], if any, when the
+/// first partition is executed, and keeps its partitions until all output
+/// streams are dropped. Operators reserving memory from the memory pool of
+/// the query, as planned by the
+/// [`DefaultPhysicalPlanner`](super::planner::DefaultPhysicalPlanner), are
+/// refused memory beyond the limit when reserving it. The memory and spilled
+/// bytes reported by the operators of the plan are checked whenever a batch
+/// is produced, failing the query once a limit is exceeded. The query also
+/// fails once it executed for longer than the timeout according to its
+/// [`TimeSource`], even if its input is stuck without producing batches. On
+/// wasm32, which has no timer, the timeout is only checked when the input
+/// produces a batch.
+#[derive(Debug)]
+pub struct QueryLimitExec {
+    /// Input execution plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The operators of the input plan, whose metrics are checked
+    metric_sources: Arc<[Arc<dyn ExecutionPlan>]>,
+    /// Limits to enforce
+    limits: QueryLimits,
+    /// Controller admitting the query, if the number of concurrently
+    /// executing partitions is limited
+    admission: Option<Arc<AdmissionController>>,
+    /// Permit of the query while any of its partitions is executing
+    permit: Mutex<Weak<AdmissionPermit>>,
+    /// When the query times out, set once the first partition is executed
+    deadline: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// Clock the timeout is measured with
+    time_source: Arc<dyn TimeSource>,
+}
+
+impl QueryLimitExec {
+    /// Create a new QueryLimitExec
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        limits: QueryLimits,
+        admission: Option<Arc<AdmissionController>>,
+    ) -> Self {
+        let mut metric_sources = vec![];
+        collect_operators(&input, &mut metric_sources);
+        Self {
+            input,
+            metric_sources: metric_sources.into(),
+            limits,
+            admission,
+            permit: Mutex::new(Weak::new()),
+            deadline: std::sync::Mutex::new(None),
+            time_source: Arc::new(SystemTimeSource {}),
+        }
+    }
+
+    /// Measure the timeout with `time_source` instead of the system clock
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Input execution plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// Limits enforced on the input
+    pub fn limits(&self) -> &QueryLimits {
+        &self.limits
+    }
+
+    /// Admits the query, unless any of its partitions is already executing
+    async fn admit(&self) -> Result<Option<Arc<AdmissionPermit>>> {
+        let admission = match &self.admission {
+            Some(admission) => admission,
+            None => return Ok(None),
+        };
+        let mut permit = self.permit.lock().await;
+        if let Some(permit) = permit.upgrade() {
+            return Ok(Some(permit));
+        }
+        let partitions = concurrent_partitions(self.input.as_ref());
+        let new_permit = Arc::new(admission.admit(partitions).await?);
+        *permit = Arc::downgrade(&new_permit);
+        Ok(Some(new_permit))
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for QueryLimitExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(
+                QueryLimitExec::new(
+                    children[0].clone(),
+                    self.limits,
+                    self.admission.clone(),
+                )
+                .with_time_source(self.time_source.clone()),
+            )),
+            _ => Err(DataFusionError::Internal(
+                "QueryLimitExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let permit = self.admit().await?;
+        let timeout = self.limits.timeout.and_then(|timeout| {
+            let now = self.time_source.now();
+            let mut query_deadline = self.deadline.lock().unwrap();
+            let deadline = match *query_deadline {
+                Some(deadline) => deadline,
+                // a timeout too large to be represented never expires
+                None => {
+                    now.checked_add_signed(chrono::Duration::from_std(timeout).ok()?)?
+                }
+            };
+            *query_deadline = Some(deadline);
+            Some(QueryTimeout::new(
+                timeout,
+                deadline,
+                now,
+                self.time_source.clone(),
+            ))
+        });
+        let input = self.input.execute(partition).await?;
+        Ok(Box::pin(QueryLimitStream {
+            input,
+            metric_sources: self.metric_sources.clone(),
+            limits: self.limits,
+            timeout,
+            _permit: permit,
+            done: false,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let mut limits = vec![];
+                if let Some(max_memory) = self.limits.max_memory {
+                    limits.push(format!("max_memory={}", max_memory));
+                }
+                if let Some(max_spill_bytes) = self.limits.max_spill_bytes {
+                    limits.push(format!("max_spill_bytes={}", max_spill_bytes));
+                }
+                if let Some(max_partitions) = self.limits.max_concurrent_partitions {
+                    limits.push(format!("max_concurrent_partitions={}", max_partitions));
+                }
+                if let Some(timeout) = self.limits.timeout {
+                    limits.push(format!("timeout={:?}", timeout));
+                }
+                write!(f, "QueryLimitExec: {}", limits.join(", "))
+            }
+        }
+    }
+}
+
+/// Largest number of partitions of any operator of `plan`, i.e. the number
+/// of partitions executing at the same time when the plan is executed
+pub fn concurrent_partitions(plan: &dyn ExecutionPlan) -> usize {
+    plan.children()
+        .iter()
+        .map(|child| concurrent_partitions(child.as_ref()))
+        .fold(plan.output_partitioning().partition_count(), usize::max)
+}
+
+/// Appends `plan` and all operators below it to `operators`
+fn collect_operators(
+    plan: &Arc<dyn ExecutionPlan>,
+    operators: &mut Vec<Arc<dyn ExecutionPlan>>,
+) {
+    operators.push(plan.clone());
+    for child in plan.children() {
+        collect_operators(&child, operators);
+    }
+}
+
+/// Sums a metric over `operators`
+fn sum_metric(
+    operators: &[Arc<dyn ExecutionPlan>],
+    metric: fn(&MetricsSet) -> Option<usize>,
+) -> usize {
+    operators
+        .iter()
+        .map(|operator| metric(&operator.metrics()).unwrap_or(0))
+        .sum()
+}
+
+/// Checks the memory and spilled bytes reported by `operators` against
+/// `limits`
+fn check_limits(
+    operators: &[Arc<dyn ExecutionPlan>],
+    limits: &QueryLimits,
+) -> Result<()> {
+    if let Some(max_memory) = limits.max_memory {
+        let memory = sum_metric(operators, MetricsSet::peak_mem_used);
+        if memory > max_memory {
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "Query used {} bytes of memory, more than the limit of {} bytes",
+                memory, max_memory
+            )));
+        }
+    }
+    if let Some(max_spill_bytes) = limits.max_spill_bytes {
+        let spilled = sum_metric(operators, MetricsSet::spilled_bytes);
+        if spilled > max_spill_bytes {
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "Query spilled {} bytes to disk, more than the limit of {} bytes",
+                spilled, max_spill_bytes
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The timeout of a query
+struct QueryTimeout {
+    /// The configured timeout
+    timeout: Duration,
+    /// When the query times out
+    deadline: DateTime<Utc>,
+    /// Clock the deadline is checked against
+    time_source: Arc<dyn TimeSource>,
+    /// Timer waking the stream once the deadline passed, even if the input is
+    /// stuck
+    #[cfg(not(target_arch = "wasm32"))]
+    timer: Pin<Box<Sleep>>,
+}
+
+impl QueryTimeout {
+    fn new(
+        timeout: Duration,
+        deadline: DateTime<Utc>,
+        now: DateTime<Utc>,
+        time_source: Arc<dyn TimeSource>,
+    ) -> Self {
+        Self {
+            timeout,
+            deadline,
+            time_source,
+            #[cfg(not(target_arch = "wasm32"))]
+            timer: Box::pin(sleep(
+                (deadline - now)
+                    .to_std()
+                    .unwrap_or_else(|_| Duration::from_secs(0)),
+            )),
+        }
+    }
+
+    /// Returns true once the deadline passed
+    fn is_exceeded(&mut self, cx: &mut Context<'_>) -> bool {
+        self.timer_fired(cx) || self.time_source.now() >= self.deadline
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn timer_fired(&mut self, cx: &mut Context<'_>) -> bool {
+        self.timer.as_mut().poll(cx).is_ready()
+    }
+
+    /// There is no timer on wasm32
+    #[cfg(target_arch = "wasm32")]
+    fn timer_fired(&mut self, _cx: &mut Context<'_>) -> bool {
+        false
+    }
+}
+
+struct QueryLimitStream {
+    /// Output of the input plan
+    input: SendableRecordBatchStream,
+    /// Operators whose metrics are checked
+    metric_sources: Arc<[Arc<dyn ExecutionPlan>]>,
+    /// Limits to enforce
+    limits: QueryLimits,
+    /// The timeout of the query, if any
+    timeout: Option<QueryTimeout>,
+    /// Keeps the query admitted while the stream is alive
+    _permit: Option<Arc<AdmissionPermit>>,
+    /// Whether a limit was exceeded and no further batches are produced
+    done: bool,
+}
+
+impl Stream for QueryLimitStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        if let Some(timeout) = &mut self.timeout {
+            if timeout.is_exceeded(cx) {
+                let timeout = timeout.timeout;
+                self.done = true;
+                let e = DataFusionError::ResourcesExhausted(format!(
+                    "Query exceeded the timeout of {:?}",
+                    timeout
+                ));
+                return Poll::Ready(Some(Err(ArrowError::ExternalError(Box::new(e)))));
+            }
+        }
+        let poll = self.input.poll_next_unpin(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(_))) | Poll::Ready(None) => {
+                if let Err(e) = check_limits(&self.metric_sources, &self.limits) {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(ArrowError::ExternalError(Box::new(
+                        e,
+                    )))));
+                }
+            }
+            _ => {}
+        }
+        poll
+    }
+}
+
+impl RecordBatchStream for QueryLimitStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::query_limits::AdmissionPolicy;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::expressions::{col, PhysicalSortExpr};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::sort::SortExec;
+    use arrow::array::UInt32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use chrono::TimeZone;
+    use futures::FutureExt;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    fn memory_exec(partitions: usize) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from(vec![3, 1, 2]))],
+        )?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &vec![vec![batch]; partitions],
+            schema,
+            None,
+        )?))
+    }
+
+    #[tokio::test]
+    async fn admission() -> Result<()> {
+        let admission = Arc::new(AdmissionController::new(4, AdmissionPolicy::Queue));
+        let exec = QueryLimitExec::new(
+            memory_exec(3)?,
+            QueryLimits::new().with_max_concurrent_partitions(4),
+            Some(admission.clone()),
+        );
+
+        // partitions of the same query share the admission
+        let stream0 = exec.execute(0).await?;
+        let stream1 = exec.execute(1).await?;
+        assert_eq!(admission.available_partitions(), 1);
+
+        // a second query has to wait for the first one to finish
+        let other = QueryLimitExec::new(
+            memory_exec(2)?,
+            QueryLimits::new().with_max_concurrent_partitions(4),
+            Some(admission.clone()),
+        );
+        assert!(other.execute(0).now_or_never().is_none());
+
+        drop(stream0);
+        drop(stream1);
+        assert_eq!(admission.available_partitions(), 4);
+        other.execute(0).now_or_never().unwrap()?;
+        Ok(())
+    }
+
+    /// A plan whose single partition never produces a batch
+    #[derive(Debug)]
+    struct PendingExec {
+        schema: SchemaRef,
+    }
+
+    #[async_trait]
+    impl ExecutionPlan for PendingExec {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.schema.clone()
+        }
+
+        fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+            vec![]
+        }
+
+        fn output_partitioning(&self) -> Partitioning {
+            Partitioning::UnknownPartitioning(1)
+        }
+
+        fn with_new_children(
+            &self,
+            _: Vec<Arc<dyn ExecutionPlan>>,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            unimplemented!()
+        }
+
+        async fn execute(&self, _: usize) -> Result<SendableRecordBatchStream> {
+            Ok(Box::pin(PendingStream {
+                schema: self.schema.clone(),
+            }))
+        }
+    }
+
+    struct PendingStream {
+        schema: SchemaRef,
+    }
+
+    impl Stream for PendingStream {
+        type Item = ArrowResult<RecordBatch>;
+
+        fn poll_next(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            Poll::Pending
+        }
+    }
+
+    impl RecordBatchStream for PendingStream {
+        fn schema(&self) -> SchemaRef {
+            self.schema.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout() -> Result<()> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, false)]));
+        let exec = Arc::new(QueryLimitExec::new(
+            Arc::new(PendingExec { schema }),
+            QueryLimits::new().with_timeout(Duration::from_millis(10)),
+            None,
+        ));
+
+        let err = collect(exec).await.unwrap_err();
+        assert!(err.to_string().contains("exceeded the timeout"), "{}", err);
+        Ok(())
+    }
+
+    /// A clock that advances by an hour whenever it is read
+    #[derive(Debug, Default)]
+    struct AdvancingTimeSource {
+        reads: AtomicI64,
+    }
+
+    impl TimeSource for AdvancingTimeSource {
+        fn now(&self) -> DateTime<Utc> {
+            let hours = self.reads.fetch_add(1, Ordering::SeqCst);
+            Utc.timestamp(0, 0) + chrono::Duration::hours(hours)
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_from_time_source() -> Result<()> {
+        let exec = Arc::new(
+            QueryLimitExec::new(
+                memory_exec(1)?,
+                QueryLimits::new().with_timeout(Duration::from_secs(60)),
+                None,
+            )
+            .with_time_source(Arc::new(AdvancingTimeSource::default())),
+        );
+
+        // the input produces its batch right away, but the clock passed the
+        // deadline by then
+        let err = collect(exec).await.unwrap_err();
+        assert!(err.to_string().contains("exceeded the timeout"), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn memory_limit() -> Result<()> {
+        let sort = Arc::new(SortExec::try_new(
+            vec![PhysicalSortExpr {
+                expr: col("a"),
+                options: Default::default(),
+            }],
+            memory_exec(1)?,
+        )?);
+        let exec = Arc::new(QueryLimitExec::new(
+            sort,
+            QueryLimits::new().with_max_memory(1),
+            None,
+        ));
+
+        let err = collect(exec).await.unwrap_err();
+        assert!(
+            err.to_string().contains("more than the limit of 1 bytes"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
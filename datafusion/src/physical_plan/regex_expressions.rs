@@ -25,7 +25,9 @@ use std::any::type_name;
 use std::sync::Arc;
 
 use crate::error::{DataFusionError, Result};
-use arrow::array::{ArrayRef, GenericStringArray, StringOffsetSizeTrait};
+use arrow::array::{
+    new_null_array, Array, ArrayRef, GenericStringArray, StringOffsetSizeTrait,
+};
 use arrow::compute;
 use hashbrown::HashMap;
 use regex::Regex;
@@ -49,7 +51,7 @@ pub fn regexp_match<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<Array
     match args.len() {
         2 => compute::regexp_match(downcast_string_arg!(args[0], "string", T), downcast_string_arg!(args[1], "pattern", T), None)
         .map_err(DataFusionError::ArrowError),
-        3 => compute::regexp_match(downcast_string_arg!(args[0], "string", T), downcast_string_arg!(args[1], "pattern", T),  Some(downcast_string_arg!(args[1], "flags", T)))
+        3 => compute::regexp_match(downcast_string_arg!(args[0], "string", T), downcast_string_arg!(args[1], "pattern", T),  Some(downcast_string_arg!(args[2], "flags", T)))
         .map_err(DataFusionError::ArrowError),
         other => Err(DataFusionError::Internal(format!(
             "regexp_match was called with {} arguments. It requires at least 2 and at most 3.",
@@ -69,6 +71,18 @@ fn regex_replace_posix_groups(replacement: &str) -> String {
         .into_owned()
 }
 
+/// formats Postgres style `flags` into the rust `pattern`, returning whether
+/// all matches should be replaced (flag `g`)
+fn pattern_with_flags(pattern: &str, flags: &str) -> (String, bool) {
+    if flags == "g" {
+        (pattern.to_string(), true)
+    } else if flags.contains('g') {
+        (format!("(?{}){}", flags.replace("g", ""), pattern), true)
+    } else {
+        (format!("(?{}){}", flags, pattern), false)
+    }
+}
+
 /// Replaces substring(s) matching a POSIX regular expression.
 ///
 /// example: `regexp_replace('Thomas', '.[mN]a.', 'M') = 'ThM'`
@@ -128,13 +142,7 @@ pub fn regexp_replace<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<Arr
                     let replacement = regex_replace_posix_groups(replacement);
 
                     // format flags into rust pattern
-                    let (pattern, replace_all) = if flags == "g" {
-                        (pattern.to_string(), true)
-                    } else if flags.contains('g') {
-                        (format!("(?{}){}", flags.to_string().replace("g", ""), pattern), true)
-                    } else {
-                        (format!("(?{}){}", flags, pattern), false)
-                    };
+                    let (pattern, replace_all) = pattern_with_flags(pattern, flags);
 
                     // if patterns hashmap already has regexp then use else else create and return
                     let re = match patterns.get(&pattern) {
@@ -170,3 +178,101 @@ pub fn regexp_replace<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<Arr
         ))),
     }
 }
+
+/// Specialization of [`regexp_replace`] for a scalar pattern, replacement and
+/// optional flags, passed as arrays of length one, which compiles the regular
+/// expression only once for the whole string array.
+pub fn regexp_replace_static_pattern<T: StringOffsetSizeTrait>(
+    args: &[ArrayRef],
+) -> Result<ArrayRef> {
+    let string_array = downcast_string_arg!(args[0], "string", T);
+    let pattern = downcast_string_arg!(args[1], "pattern", T);
+    let replacement = downcast_string_arg!(args[2], "replacement", T);
+    let flags = match args.len() {
+        3 => None,
+        4 => Some(downcast_string_arg!(args[3], "flags", T)),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "regexp_replace was called with {} arguments. It requires at least 3 and at most 4.",
+                other
+            )))
+        }
+    };
+
+    // any null argument makes every result null
+    if pattern.is_null(0)
+        || replacement.is_null(0)
+        || flags.map_or(false, |flags| flags.is_null(0))
+    {
+        return Ok(new_null_array(args[0].data_type(), string_array.len()));
+    }
+
+    let (pattern, replace_all) = match flags {
+        Some(flags) => pattern_with_flags(pattern.value(0), flags.value(0)),
+        None => (pattern.value(0).to_string(), false),
+    };
+    let re = Regex::new(&pattern)
+        .map_err(|err| DataFusionError::Execution(err.to_string()))?;
+    let replacement = regex_replace_posix_groups(replacement.value(0));
+
+    let result = string_array
+        .iter()
+        .map(|string| {
+            string.map(|string| {
+                if replace_all {
+                    re.replace_all(string, replacement.as_str())
+                } else {
+                    re.replace(string, replacement.as_str())
+                }
+            })
+        })
+        .collect::<GenericStringArray<T>>();
+
+    Ok(Arc::new(result) as ArrayRef)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+
+    #[test]
+    fn regexp_replace_static_pattern_matches_general() -> Result<()> {
+        let strings: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("foobarbaz"),
+            None,
+            Some("ABCabcABC"),
+        ]));
+        for (pattern, replacement, flags) in vec![
+            ("b(..)", "X\\1Y", "g"),
+            ("(abc)", "X", "i"),
+            ("B..", "X", "gi"),
+        ] {
+            let scalar_args = vec![
+                strings.clone(),
+                Arc::new(StringArray::from(vec![pattern])) as ArrayRef,
+                Arc::new(StringArray::from(vec![replacement])) as ArrayRef,
+                Arc::new(StringArray::from(vec![flags])) as ArrayRef,
+            ];
+            let array_args = vec![
+                strings.clone(),
+                Arc::new(StringArray::from(vec![pattern; 3])) as ArrayRef,
+                Arc::new(StringArray::from(vec![replacement; 3])) as ArrayRef,
+                Arc::new(StringArray::from(vec![flags; 3])) as ArrayRef,
+            ];
+            let expected = regexp_replace::<i32>(&array_args)?;
+            let result = regexp_replace_static_pattern::<i32>(&scalar_args)?;
+            assert_eq!(result.data(), expected.data());
+        }
+
+        let null_args = vec![
+            strings.clone(),
+            Arc::new(StringArray::from(vec![None as Option<&str>])) as ArrayRef,
+            Arc::new(StringArray::from(vec!["X"])) as ArrayRef,
+        ];
+        let result = regexp_replace_static_pattern::<i32>(&null_args)?;
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.null_count(), 3);
+        Ok(())
+    }
+}
@@ -18,23 +18,27 @@
 //! The repartition operator maps N input partitions to M output partitions based on a
 //! partitioning scheme.
 
+use instant::Instant;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Instant;
 use std::{any::Any, vec};
 
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning, SQLMetric};
+use crate::physical_plan::{
+    DisplayFormatType, ExecutionPlan, MetricsSet, Partitioning, SQLMetric,
+};
 use arrow::record_batch::RecordBatch;
 use arrow::{array::Array, error::Result as ArrowResult};
 use arrow::{compute::take, datatypes::SchemaRef};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
-use super::{hash_join::create_hashes, RecordBatchStream, SendableRecordBatchStream};
+use super::common::AbortOnDropMany;
+use super::hash_utils::{BatchHasher, HashSeeds};
+use super::{RecordBatchStream, SendableRecordBatchStream};
 use async_trait::async_trait;
 
-use futures::stream::Stream;
+use futures::stream::{self, Stream};
 use futures::StreamExt;
 use hashbrown::HashMap;
 use tokio::sync::{
@@ -45,27 +49,46 @@ use tokio::task::JoinHandle;
 
 type MaybeBatch = Option<ArrowResult<RecordBatch>>;
 
+/// Channels keyed by output partition and sender, see [`RepartitionState`]
+type Channels =
+    HashMap<(usize, usize), (UnboundedSender<MaybeBatch>, UnboundedReceiver<MaybeBatch>)>;
+
+/// State shared by the output partitions of a [`RepartitionExec`]
+#[derive(Debug, Default)]
+struct RepartitionState {
+    /// Channels for sending batches from input partitions to output partitions.
+    /// Key is the output partition number and the sender, which is always 0
+    /// unless each input partition sends on its own channel to preserve order
+    channels: Channels,
+    /// Aborts the tasks reading the input partitions. Handed to the output
+    /// streams, so that the tasks are aborted once all of them are dropped.
+    abort_helper: Option<Arc<AbortOnDropMany<Result<()>>>>,
+}
+
 /// The repartition operator maps N input partitions to M output partitions based on a
-/// partitioning scheme. No guarantees are made about the order of the resulting partitions.
+/// partitioning scheme. No guarantees are made about the order of the resulting partitions,
+/// unless the order of the input partitions is preserved.
 #[derive(Debug)]
 pub struct RepartitionExec {
     /// Input execution plan
     input: Arc<dyn ExecutionPlan>,
     /// Partitioning scheme to use
     partitioning: Partitioning,
-    /// Channels for sending batches from input partitions to output partitions.
-    /// Key is the partition number
-    channels: Arc<
-        Mutex<
-            HashMap<usize, (UnboundedSender<MaybeBatch>, UnboundedReceiver<MaybeBatch>)>,
-        >,
-    >,
+    /// Channels to the output partitions and the tasks sending on them
+    state: Arc<Mutex<RepartitionState>>,
+    /// Whether each output partition contains the batches of input partition 0
+    /// first, then those of input partition 1 and so on
+    preserve_order: bool,
+    /// Seeds of the hash function of a [`Partitioning::Hash`]
+    hash_seeds: HashSeeds,
     /// Time in nanos to execute child operator and fetch batches
     fetch_time_nanos: Arc<SQLMetric>,
     /// Time in nanos to perform repartitioning
     repart_time_nanos: Arc<SQLMetric>,
     /// Time in nanos for sending resulting batches to channels
     send_time_nanos: Arc<SQLMetric>,
+    /// Number of batches read from all input partitions
+    input_batches: Arc<SQLMetric>,
 }
 
 impl RepartitionExec {
@@ -78,6 +101,31 @@ impl RepartitionExec {
     pub fn partitioning(&self) -> &Partitioning {
         &self.partitioning
     }
+
+    /// Emit the batches of each output partition in the order of the input
+    /// partitions they came from, rather than as they become available
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Whether the order of the input partitions is preserved
+    pub fn preserve_order(&self) -> bool {
+        self.preserve_order
+    }
+
+    /// Customize the seeds rows are hashed with for a [`Partitioning::Hash`].
+    /// Plans repartitioning with the same seeds assign rows with equal keys
+    /// to the same partition.
+    pub fn with_hash_seeds(mut self, hash_seeds: HashSeeds) -> Self {
+        self.hash_seeds = hash_seeds;
+        self
+    }
+
+    /// Seeds rows are hashed with for a [`Partitioning::Hash`]
+    pub fn hash_seeds(&self) -> HashSeeds {
+        self.hash_seeds
+    }
 }
 
 #[async_trait]
@@ -96,15 +144,20 @@ impl ExecutionPlan for RepartitionExec {
         vec![self.input.clone()]
     }
 
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(RepartitionExec::try_new(
-                children[0].clone(),
-                self.partitioning.clone(),
-            )?)),
+            1 => Ok(Arc::new(
+                RepartitionExec::try_new(children[0].clone(), self.partitioning.clone())?
+                    .with_preserve_order(self.preserve_order)
+                    .with_hash_seeds(self.hash_seeds),
+            )),
             _ => Err(DataFusionError::Internal(
                 "RepartitionExec wrong number of children".to_string(),
             )),
@@ -117,15 +170,27 @@ impl ExecutionPlan for RepartitionExec {
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         // lock mutexes
-        let mut channels = self.channels.lock().await;
+        let mut state = self.state.lock().await;
+        let RepartitionState {
+            channels,
+            abort_helper,
+        } = &mut *state;
 
         let num_input_partitions = self.input.output_partitioning().partition_count();
         let num_output_partitions = self.partitioning.partition_count();
+        let num_senders = if self.preserve_order {
+            num_input_partitions
+        } else {
+            1
+        };
 
         // if this is the first partition to be invoked then we need to set up initial state
         if channels.is_empty() {
-            // create one channel per *output* partition
-            for partition in 0..num_output_partitions {
+            // create one channel per *output* partition, or one per *output* and
+            // *input* partition if the order of the input partitions is preserved
+            for (partition, sender_id) in (0..num_output_partitions)
+                .flat_map(|partition| (0..num_senders).map(move |s| (partition, s)))
+            {
                 // Note that this operator uses unbounded channels to avoid deadlocks because
                 // the output partitions can be read in any order and this could cause input
                 // partitions to be blocked when sending data to output UnboundedReceivers that are not
@@ -135,29 +200,46 @@ impl ExecutionPlan for RepartitionExec {
                 let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<
                     Option<ArrowResult<RecordBatch>>,
                 >();
-                channels.insert(partition, (sender, receiver));
+                channels.insert((partition, sender_id), (sender, receiver));
             }
-            // Use fixed random state
-            let random = ahash::RandomState::with_seeds(0, 0, 0, 0);
+            // Use fixed random state, so that equal keys hash the same in every process
+            let random = self.hash_seeds.random_state();
 
             // launch one async task per *input* partition
+            let mut join_handles = Vec::with_capacity(num_input_partitions);
             for i in 0..num_input_partitions {
                 let random_state = random.clone();
                 let input = self.input.clone();
                 let fetch_time = self.fetch_time_nanos.clone();
                 let repart_time = self.repart_time_nanos.clone();
                 let send_time = self.send_time_nanos.clone();
+                let input_batches = self.input_batches.clone();
+                let sender_id = if self.preserve_order { i } else { 0 };
                 let mut txs: HashMap<_, _> = channels
                     .iter()
-                    .map(|(partition, (tx, _rx))| (*partition, tx.clone()))
+                    .filter(|((_, sender), _)| *sender == sender_id)
+                    .map(|((partition, _), (tx, _rx))| (*partition, tx.clone()))
                     .collect();
                 let partitioning = self.partitioning.clone();
-                let _: JoinHandle<Result<()>> = tokio::spawn(async move {
+                let join_handle: JoinHandle<Result<()>> = tokio::spawn(async move {
                     // execute the child operator
                     let now = Instant::now();
                     let mut stream = input.execute(i).await?;
                     fetch_time.add(now.elapsed().as_nanos() as usize);
 
+                    // choose how the keys are hashed once for all batches
+                    let hasher = match &partitioning {
+                        Partitioning::Hash(exprs, _) => {
+                            let schema = input.schema();
+                            let data_types = exprs
+                                .iter()
+                                .map(|expr| expr.data_type(&schema))
+                                .collect::<Result<Vec<_>>>()?;
+                            Some(BatchHasher::try_new(&data_types, random_state)?)
+                        }
+                        _ => None,
+                    };
+
                     let mut counter = 0;
                     let hashes_buf = &mut vec![];
 
@@ -171,6 +253,7 @@ impl ExecutionPlan for RepartitionExec {
                             break;
                         }
                         let result = result.unwrap();
+                        input_batches.add(1);
 
                         match &partitioning {
                             Partitioning::RoundRobinBatch(_) => {
@@ -183,6 +266,7 @@ impl ExecutionPlan for RepartitionExec {
                                 send_time.add(now.elapsed().as_nanos() as usize);
                             }
                             Partitioning::Hash(exprs, _) => {
+                                let hasher = hasher.as_ref().unwrap();
                                 let now = Instant::now();
                                 let input_batch = result?;
                                 let arrays = exprs
@@ -196,8 +280,7 @@ impl ExecutionPlan for RepartitionExec {
                                 hashes_buf.clear();
                                 hashes_buf.resize(arrays[0].len(), 0);
                                 // Hash arrays and compute buckets based on number of partitions
-                                let hashes =
-                                    create_hashes(&arrays, &random_state, hashes_buf)?;
+                                let hashes = hasher.hash(&arrays, hashes_buf)?;
                                 let mut indices = vec![vec![]; num_output_partitions];
                                 for (index, hash) in hashes.iter().enumerate() {
                                     indices
@@ -256,27 +339,41 @@ impl ExecutionPlan for RepartitionExec {
                     }
                     Ok(())
                 });
+                join_handles.push(join_handle);
             }
+            *abort_helper = Some(Arc::new(AbortOnDropMany(join_handles)));
         }
 
         // now return stream for the specified *output* partition which will
-        // read from the channel
+        // read from the channels, one after the other
+        let receivers = (0..num_senders)
+            .map(|sender_id| {
+                let (_tx, rx) = channels.remove(&(partition, sender_id)).unwrap();
+                UnboundedReceiverStream::new(rx)
+            })
+            .collect::<Vec<_>>();
+        // once the last output partition was handed out, only the output streams
+        // keep the input tasks alive
+        let drop_helper = if channels.is_empty() {
+            abort_helper.take()
+        } else {
+            abort_helper.clone()
+        };
         Ok(Box::pin(RepartitionStream {
             num_input_partitions,
             num_input_partitions_processed: 0,
             schema: self.input.schema(),
-            input: UnboundedReceiverStream::new(channels.remove(&partition).unwrap().1),
+            input: stream::iter(receivers).flatten(),
+            _drop_helper: drop_helper,
         }))
     }
 
-    fn metrics(&self) -> HashMap<String, SQLMetric> {
-        let mut metrics = HashMap::new();
-        metrics.insert("fetchTime".to_owned(), (*self.fetch_time_nanos).clone());
-        metrics.insert(
-            "repartitionTime".to_owned(),
-            (*self.repart_time_nanos).clone(),
-        );
-        metrics.insert("sendTime".to_owned(), (*self.send_time_nanos).clone());
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push("fetch_time", &self.fetch_time_nanos);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.repart_time_nanos);
+        metrics.push("send_time", &self.send_time_nanos);
+        metrics.push(MetricsSet::INPUT_BATCHES, &self.input_batches);
         metrics
     }
 
@@ -287,7 +384,14 @@ impl ExecutionPlan for RepartitionExec {
     ) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default => {
-                write!(f, "RepartitionExec: partitioning={:?}", self.partitioning)
+                write!(f, "RepartitionExec: partitioning={:?}", self.partitioning)?;
+                if self.preserve_order {
+                    write!(f, ", preserve_order=true")?;
+                }
+                if self.hash_seeds != HashSeeds::default() {
+                    write!(f, ", hash_seeds={:?}", self.hash_seeds.0)?;
+                }
+                Ok(())
             }
         }
     }
@@ -302,10 +406,13 @@ impl RepartitionExec {
         Ok(RepartitionExec {
             input,
             partitioning,
-            channels: Arc::new(Mutex::new(HashMap::new())),
+            state: Arc::new(Mutex::new(RepartitionState::default())),
+            preserve_order: false,
+            hash_seeds: HashSeeds::default(),
             fetch_time_nanos: SQLMetric::time_nanos(),
             repart_time_nanos: SQLMetric::time_nanos(),
             send_time_nanos: SQLMetric::time_nanos(),
+            input_batches: SQLMetric::counter(),
         })
     }
 }
@@ -317,8 +424,12 @@ struct RepartitionStream {
     num_input_partitions_processed: usize,
     /// Schema
     schema: SchemaRef,
-    /// channel containing the repartitioned batches
-    input: UnboundedReceiverStream<Option<ArrowResult<RecordBatch>>>,
+    /// channels containing the repartitioned batches, read one after the other
+    input:
+        stream::Flatten<stream::Iter<vec::IntoIter<UnboundedReceiverStream<MaybeBatch>>>>,
+    /// Aborts the tasks reading the input partitions once all output streams
+    /// are dropped
+    _drop_helper: Option<Arc<AbortOnDropMany<Result<()>>>>,
 }
 
 impl Stream for RepartitionStream {
@@ -356,10 +467,15 @@ impl RecordBatchStream for RepartitionStream {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::hash_utils::create_hashes;
     use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, common};
+    use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
     use arrow::array::UInt32Array;
     use arrow::datatypes::{DataType, Field, Schema};
     use arrow::record_batch::RecordBatch;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn one_to_many_round_robin() -> Result<()> {
@@ -446,6 +562,74 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn many_to_many_round_robin_preserve_order() -> Result<()> {
+        // input partition i contains 10 batches of the value i
+        let schema = test_schema();
+        let partitions = (0..3u32)
+            .map(|i| {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(UInt32Array::from(vec![i; 4]))],
+                )?;
+                Ok(vec![batch; 10])
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let exec = MemoryExec::try_new(&partitions, schema.clone(), None)?;
+        let exec =
+            RepartitionExec::try_new(Arc::new(exec), Partitioning::RoundRobinBatch(2))?
+                .with_preserve_order(true);
+
+        for i in 0..2 {
+            let batches =
+                crate::physical_plan::common::collect(exec.execute(i).await?).await?;
+            let values = batches
+                .iter()
+                .map(|batch| {
+                    batch
+                        .column(0)
+                        .as_any()
+                        .downcast_ref::<UInt32Array>()
+                        .unwrap()
+                        .value(0)
+                })
+                .collect::<Vec<_>>();
+            // each output partition receives 5 batches of every input partition,
+            // in the order of the input partitions
+            let expected = (0..3).flat_map(|i| vec![i; 5]).collect::<Vec<_>>();
+            assert_eq!(values, expected);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hash_seeds_determine_partitions() -> Result<()> {
+        let schema = test_schema();
+        let partitions = vec![create_vec_batches(&schema, 2)];
+        for hash_seeds in vec![HashSeeds::default(), HashSeeds([1, 2, 3, 4])] {
+            let exec = MemoryExec::try_new(&partitions, schema.clone(), None)?;
+            let exec = RepartitionExec::try_new(
+                Arc::new(exec),
+                Partitioning::Hash(vec![col("c0")], 4),
+            )?
+            .with_hash_seeds(hash_seeds);
+
+            // another process hashing with the same seeds agrees on the partitions
+            let random_state = hash_seeds.random_state();
+            for partition in 0..4 {
+                let batches = common::collect(exec.execute(partition).await?).await?;
+                for batch in batches {
+                    let mut hashes = vec![0; batch.num_rows()];
+                    create_hashes(batch.columns(), &random_state, &mut hashes)?;
+                    assert!(hashes.iter().all(|hash| *hash % 4 == partition as u64));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn test_schema() -> Arc<Schema> {
         Arc::new(Schema::new(vec![Field::new("c0", DataType::UInt32, false)]))
     }
@@ -517,4 +701,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn drop_cancels_input_tasks() -> Result<()> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, false)]));
+        let blocking_exec = Arc::new(BlockingExec::new(schema, 2));
+        let refs = blocking_exec.refs();
+        let exec = Arc::new(RepartitionExec::try_new(
+            blocking_exec,
+            Partitioning::RoundRobinBatch(4),
+        )?);
+
+        // the input never produces a batch, the query is dropped once timed out
+        let result = tokio::time::timeout(Duration::from_millis(10), collect(exec)).await;
+        assert!(result.is_err());
+
+        assert_strong_count_converges_to_zero(refs).await;
+        Ok(())
+    }
 }
@@ -0,0 +1,314 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Retries of file reads, see [`RetryPolicy`]
+
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::SQLMetric;
+
+/// Policy for retrying the opening of the files read by scans such as
+/// [`ParquetExec`](super::parquet::ParquetExec) and
+/// [`CsvExec`](super::csv::CsvExec).
+///
+/// Opening a file on a remote file system may fail transiently. Such failures,
+/// as well as attempts that take longer than the `timeout`, are retried after
+/// an exponentially growing backoff. Failures that are not transient, such as
+/// a file that does not exist, are returned right away.
+///
+/// On wasm32, which has neither threads nor a timer, the attempts run inline,
+/// retries follow each other without a backoff, and the timeout is not
+/// enforced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt failed
+    pub max_retries: usize,
+    /// Time to wait before the first retry, which doubles for every retry
+    pub initial_backoff: Duration,
+    /// Maximum time to wait before a retry
+    pub max_backoff: Duration,
+    /// Time after which an attempt is considered failed, if any
+    pub timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy that does not retry and does not time out
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Customize the maximum number of retries
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Customize the backoff before the first retry, and the maximum backoff
+    /// it grows to
+    pub fn with_backoff(
+        mut self,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Customize the time after which an attempt is considered failed
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns true if this policy neither retries nor times out
+    pub fn is_disabled(&self) -> bool {
+        self.max_retries == 0 && self.timeout.is_none()
+    }
+
+    /// Time to wait before retry number `retry`, starting at 0
+    fn backoff(&self, retry: usize) -> Duration {
+        let factor = 1u32.checked_shl(retry as u32).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Run the blocking `op` until it succeeds, fails with an error that is
+    /// not transient, or the retries are exhausted. Every retry is counted in
+    /// `retries`.
+    ///
+    /// Once the retries are exhausted, the returned error lists the failure of
+    /// every attempt for `description`, e.g. the name of the file.
+    pub async fn run<T, F>(
+        &self,
+        description: &str,
+        retries: &SQLMetric,
+        op: F,
+    ) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn() -> Result<T> + Send + Sync + 'static,
+    {
+        if self.is_disabled() {
+            return op();
+        }
+
+        let op = Arc::new(op);
+        let mut failures = vec![];
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                retries.add(1);
+                sleep(self.backoff(attempt - 1)).await;
+            }
+
+            let result = match run_attempt(op.clone(), self.timeout).await? {
+                Some(result) => result,
+                None => {
+                    failures.push(format!(
+                        "timed out after {:?}",
+                        self.timeout.unwrap_or_default()
+                    ));
+                    continue;
+                }
+            };
+
+            match result {
+                Ok(value) => return Ok(value),
+                Err(e) if is_transient(&e) => failures.push(e.to_string()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let failures = failures
+            .iter()
+            .enumerate()
+            .map(|(i, failure)| format!("attempt {}: {}", i + 1, failure))
+            .collect::<Vec<_>>();
+        Err(DataFusionError::Execution(format!(
+            "Failed to read {} after {} attempts: {}",
+            description,
+            failures.len(),
+            failures.join("; ")
+        )))
+    }
+}
+
+/// Runs one attempt of `op` on the blocking thread pool. Returns `None` if it
+/// did not finish within `timeout`.
+#[cfg(not(target_arch = "wasm32"))]
+async fn run_attempt<T, F>(
+    op: Arc<F>,
+    timeout: Option<Duration>,
+) -> Result<Option<Result<T>>>
+where
+    T: Send + 'static,
+    F: Fn() -> Result<T> + Send + Sync + 'static,
+{
+    let task = tokio::task::spawn_blocking(move || op());
+    let joined = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, task).await {
+            Ok(joined) => joined,
+            Err(_) => return Ok(None),
+        },
+        None => task.await,
+    };
+    joined
+        .map(Some)
+        .map_err(|e| DataFusionError::Execution(format!("Read task failed: {}", e)))
+}
+
+/// Runs one attempt of `op` inline, without a timeout
+#[cfg(target_arch = "wasm32")]
+async fn run_attempt<T, F>(
+    op: Arc<F>,
+    _timeout: Option<Duration>,
+) -> Result<Option<Result<T>>>
+where
+    T: Send + 'static,
+    F: Fn() -> Result<T> + Send + Sync + 'static,
+{
+    Ok(Some(op()))
+}
+
+/// Waits for `duration` before a retry
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+/// Retries right away, as there is no timer to wait on
+#[cfg(target_arch = "wasm32")]
+async fn sleep(_duration: Duration) {}
+
+/// Returns true if retrying the operation that failed with `e` may succeed
+fn is_transient(e: &DataFusionError) -> bool {
+    match e {
+        DataFusionError::IoError(e) => !matches!(
+            e.kind(),
+            ErrorKind::NotFound
+                | ErrorKind::PermissionDenied
+                | ErrorKind::InvalidInput
+                | ErrorKind::InvalidData
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_retries(2)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(2))
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() -> Result<()> {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let retries = SQLMetric::counter();
+
+        let op_attempts = attempts.clone();
+        let value = policy()
+            .run("file.parquet", &retries, move || {
+                if op_attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(io::Error::new(io::ErrorKind::Other, "503 Slow Down").into())
+                } else {
+                    Ok(42)
+                }
+            })
+            .await?;
+        assert_eq!(value, 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(retries.value(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reports_every_attempt() {
+        let retries = SQLMetric::counter();
+        let err = policy()
+            .run("file.parquet", &retries, || -> Result<()> {
+                Err(io::Error::new(io::ErrorKind::Other, "503 Slow Down").into())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: Failed to read file.parquet after 3 attempts: \
+             attempt 1: IO error: 503 Slow Down; attempt 2: IO error: 503 Slow Down; \
+             attempt 3: IO error: 503 Slow Down"
+        );
+        assert_eq!(retries.value(), 2);
+    }
+
+    #[tokio::test]
+    async fn permanent_failure_is_not_retried() {
+        let retries = SQLMetric::counter();
+        let err = policy()
+            .run("file.parquet", &retries, || -> Result<()> {
+                Err(io::Error::new(io::ErrorKind::NotFound, "no such file").into())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "IO error: no such file");
+        assert_eq!(retries.value(), 0);
+    }
+
+    #[tokio::test]
+    async fn attempts_time_out() {
+        let retries = SQLMetric::counter();
+        let err = policy()
+            .with_max_retries(1)
+            .with_timeout(Duration::from_millis(10))
+            .run("file.parquet", &retries, || -> Result<()> {
+                std::thread::sleep(Duration::from_millis(100));
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("after 2 attempts"), "{}", err);
+        assert!(err.to_string().contains("timed out after 10ms"), "{}", err);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = policy();
+        assert_eq!(policy.backoff(0), Duration::from_millis(1));
+        assert_eq!(policy.backoff(1), Duration::from_millis(2));
+        assert_eq!(policy.backoff(10), Duration::from_millis(2));
+        assert_eq!(policy.backoff(100), Duration::from_millis(2));
+    }
+}
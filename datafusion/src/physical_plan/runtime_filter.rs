@@ -0,0 +1,74 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Filters on the columns of scans that only become known while a query
+//! executes, such as the range of the join keys of the build side of a hash
+//! join.
+
+use std::sync::RwLock;
+
+use crate::logical_plan::{col, lit, Expr};
+use crate::scalar::ScalarValue;
+
+/// A filter on a column whose bounds are set while the query executes.
+///
+/// A [`HashJoinExec`](super::hash_join::HashJoinExec) sets the bounds of the
+/// join keys of its build side before it executes its probe side, so that
+/// the scans of the probe side holding the filter can skip the data without
+/// matching keys, e.g. row groups of parquet files.
+#[derive(Debug)]
+pub struct RuntimeFilter {
+    /// The filtered column
+    column: String,
+    /// The minimum and maximum value of the column, if known
+    bounds: RwLock<Option<(ScalarValue, ScalarValue)>>,
+}
+
+impl RuntimeFilter {
+    /// Create a filter on `column`, without bounds
+    pub fn new(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            bounds: RwLock::new(None),
+        }
+    }
+
+    /// The filtered column
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// The minimum and maximum value of the column, if known
+    pub fn bounds(&self) -> Option<(ScalarValue, ScalarValue)> {
+        self.bounds.read().unwrap().clone()
+    }
+
+    /// Sets the minimum and maximum value of the column, or removes them
+    pub fn set_bounds(&self, bounds: Option<(ScalarValue, ScalarValue)>) {
+        *self.bounds.write().unwrap() = bounds;
+    }
+
+    /// The filter as the predicate `column >= min AND column <= max`, if its
+    /// bounds are known
+    pub fn predicate(&self) -> Option<Expr> {
+        self.bounds().map(|(min, max)| {
+            col(&self.column)
+                .gt_eq(lit(min))
+                .and(col(&self.column).lt_eq(lit(max)))
+        })
+    }
+}
@@ -0,0 +1,441 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the sampling operator, which returns a random subset of the rows
+//! of its input
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::coalesce_batches::concat_batches;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::SampleMethod;
+use crate::physical_plan::{
+    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+};
+use arrow::array::{BooleanArray, UInt32Array};
+use arrow::compute::{filter_record_batch, take};
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+
+use futures::stream::{Stream, StreamExt};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// SampleExec returns a random subset of the rows of its input. Bernoulli
+/// sampling keeps each row independently and preserves the partitioning of
+/// the input, while reservoir sampling keeps a fixed number of rows of all
+/// partitions and therefore produces a single partition.
+#[derive(Debug)]
+pub struct SampleExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// How the rows are sampled
+    method: SampleMethod,
+    /// The seed of the random number generator, random if `None`
+    seed: Option<u64>,
+}
+
+impl SampleExec {
+    /// Create a SampleExec sampling the rows of `input` with `method`
+    pub fn try_new(input: Arc<dyn ExecutionPlan>, method: SampleMethod) -> Result<Self> {
+        method.validate()?;
+        Ok(Self {
+            input,
+            method,
+            seed: None,
+        })
+    }
+
+    /// Sample with a random number generator seeded with `seed`, which makes
+    /// the sample reproducible for the same input. Partition `i` of a
+    /// bernoulli sample uses `seed + i`.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// How the rows are sampled
+    pub fn method(&self) -> SampleMethod {
+        self.method
+    }
+
+    /// The seed of the random number generator
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    fn rng(&self, partition: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(partition as u64)),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for SampleExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        match self.method {
+            SampleMethod::Bernoulli { .. } => Distribution::UnspecifiedDistribution,
+            // the reservoir holds the rows of all partitions
+            SampleMethod::Reservoir { .. } => Distribution::SinglePartition,
+        }
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        match self.method {
+            SampleMethod::Bernoulli { .. } => self.input.output_partitioning(),
+            SampleMethod::Reservoir { .. } => Partitioning::UnknownPartitioning(1),
+        }
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        match self.method {
+            SampleMethod::Bernoulli { .. } => Ok(children[0]),
+            SampleMethod::Reservoir { .. } => {
+                if children[0] {
+                    Err(DataFusionError::Plan(
+                        "Reservoir sampling can not be applied to an unbounded input"
+                            .to_string(),
+                    ))
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(
+                SampleExec::try_new(children[0].clone(), self.method)?
+                    .with_seed(self.seed),
+            )),
+            _ => Err(DataFusionError::Internal(
+                "SampleExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        match self.method {
+            SampleMethod::Bernoulli { fraction } => Ok(Box::pin(BernoulliStream {
+                input: self.input.execute(partition).await?,
+                fraction,
+                rng: self.rng(partition),
+            })),
+            SampleMethod::Reservoir { size } => {
+                if 0 != partition {
+                    return Err(DataFusionError::Internal(format!(
+                        "SampleExec invalid partition {}",
+                        partition
+                    )));
+                }
+
+                // reservoir sampling requires a single input partition
+                if 1 != self.input.output_partitioning().partition_count() {
+                    return Err(DataFusionError::Internal(
+                        "SampleExec requires a single input partition".to_owned(),
+                    ));
+                }
+
+                Ok(Box::pin(ReservoirStream {
+                    schema: self.schema(),
+                    input: Some(self.input.execute(0).await?),
+                    size,
+                    rng: self.rng(0),
+                    reservoir: None,
+                    seen: 0,
+                }))
+            }
+        }
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "SampleExec: method={}", self.method)?;
+                if let Some(seed) = self.seed {
+                    write!(f, ", seed={}", seed)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Keeps each row with the probability `fraction`
+struct BernoulliStream {
+    input: SendableRecordBatchStream,
+    fraction: f64,
+    rng: StdRng,
+}
+
+impl Stream for BernoulliStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        this.input.poll_next_unpin(cx).map(|x| {
+            x.map(|batch| {
+                batch.and_then(|batch| {
+                    let mask = (0..batch.num_rows())
+                        .map(|_| Some(this.rng.gen_bool(this.fraction)))
+                        .collect::<BooleanArray>();
+                    filter_record_batch(&batch, &mask)
+                })
+            })
+        })
+    }
+}
+
+impl RecordBatchStream for BernoulliStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+/// Keeps a uniform sample of `size` rows of the input (Algorithm R), which
+/// is returned once the input is exhausted
+struct ReservoirStream {
+    schema: SchemaRef,
+    /// The input, until it is exhausted
+    input: Option<SendableRecordBatchStream>,
+    size: usize,
+    rng: StdRng,
+    /// The rows sampled so far
+    reservoir: Option<RecordBatch>,
+    /// The number of input rows so far
+    seen: usize,
+}
+
+impl ReservoirStream {
+    /// Replaces rows of the reservoir by rows of `batch`
+    fn update(&mut self, batch: RecordBatch) -> ArrowResult<()> {
+        let kept = self.reservoir.as_ref().map(|r| r.num_rows()).unwrap_or(0);
+        // the rows of the new reservoir as indices into the concatenation of
+        // the old reservoir and `batch`
+        let mut slots = (0..kept as u32).collect::<Vec<_>>();
+        let mut changed = false;
+        for row in 0..batch.num_rows() {
+            self.seen += 1;
+            let index = (kept + row) as u32;
+            if slots.len() < self.size {
+                slots.push(index);
+                changed = true;
+            } else {
+                let slot = self.rng.gen_range(0..self.seen);
+                if slot < self.size {
+                    slots[slot] = index;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Ok(());
+        }
+
+        let batches = match self.reservoir.take() {
+            Some(reservoir) => vec![reservoir, batch],
+            None => vec![batch],
+        };
+        let rows = kept + batches.last().unwrap().num_rows();
+        let combined = concat_batches(&self.schema, &batches, rows)?;
+        let indices = UInt32Array::from(slots);
+        let columns = combined
+            .columns()
+            .iter()
+            .map(|column| take(column.as_ref(), &indices, None))
+            .collect::<ArrowResult<Vec<_>>>()?;
+        self.reservoir = Some(RecordBatch::try_new(self.schema.clone(), columns)?);
+        Ok(())
+    }
+}
+
+impl Stream for ReservoirStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        loop {
+            let input = match self.input.as_mut() {
+                Some(input) => input,
+                None => return Poll::Ready(None),
+            };
+            match input.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(batch))) => {
+                    if let Err(e) = self.update(batch) {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => {
+                    self.input = None;
+                    return Poll::Ready(self.reservoir.take().map(Ok));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl RecordBatchStream for ReservoirStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::merge::MergeExec;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// Two partitions of the values 0 to 99 in batches of 10 rows
+    fn input() -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let partitions = (0..2)
+            .map(|partition| {
+                (0..5)
+                    .map(|batch| {
+                        let start = partition * 50 + batch * 10;
+                        RecordBatch::try_new(
+                            schema.clone(),
+                            vec![Arc::new(Int32Array::from(
+                                (start..start + 10).collect::<Vec<_>>(),
+                            ))],
+                        )
+                    })
+                    .collect::<ArrowResult<Vec<_>>>()
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+        Ok(Arc::new(MemoryExec::try_new(&partitions, schema, None)?))
+    }
+
+    async fn sample(
+        input: Arc<dyn ExecutionPlan>,
+        method: SampleMethod,
+        seed: Option<u64>,
+    ) -> Result<Vec<i32>> {
+        let exec = SampleExec::try_new(input, method)?.with_seed(seed);
+        let mut values = vec![];
+        for partition in 0..exec.output_partitioning().partition_count() {
+            for batch in common::collect(exec.execute(partition).await?).await? {
+                let column = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int32Array>()
+                    .unwrap();
+                values.extend((0..column.len()).map(|i| column.value(i)));
+            }
+        }
+        Ok(values)
+    }
+
+    #[tokio::test]
+    async fn bernoulli_sample() -> Result<()> {
+        let none = sample(input()?, SampleMethod::Bernoulli { fraction: 0.0 }, None);
+        assert!(none.await?.is_empty());
+        let all = sample(input()?, SampleMethod::Bernoulli { fraction: 1.0 }, None);
+        assert_eq!(all.await?, (0..100).collect::<Vec<_>>());
+
+        let method = SampleMethod::Bernoulli { fraction: 0.3 };
+        let values = sample(input()?, method, Some(42)).await?;
+        assert!(!values.is_empty() && values.len() < 100);
+        // rows keep their order and are not repeated
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(values, sample(input()?, method, Some(42)).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reservoir_sample() -> Result<()> {
+        let method = SampleMethod::Reservoir { size: 7 };
+        let exec = SampleExec::try_new(input()?, method)?;
+        assert!(matches!(
+            exec.required_child_distribution(),
+            Distribution::SinglePartition
+        ));
+        assert_eq!(exec.output_partitioning().partition_count(), 1);
+
+        let merged = Arc::new(MergeExec::new(input()?));
+
+        let method = SampleMethod::Reservoir { size: 7 };
+        let mut values = sample(merged.clone(), method, Some(7)).await?;
+        values.sort_unstable();
+        values.dedup();
+        assert_eq!(values.len(), 7);
+        assert!(values.iter().all(|v| (0..100).contains(v)));
+
+        let method = SampleMethod::Reservoir { size: 200 };
+        let mut values = sample(merged.clone(), method, None).await?;
+        values.sort_unstable();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+
+        let empty = Arc::new(MemoryExec::try_new(&[vec![]], merged.schema(), None)?);
+        assert!(sample(empty, method, Some(1)).await?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn sample_invalid() -> Result<()> {
+        let method = SampleMethod::Bernoulli { fraction: 1.5 };
+        assert!(SampleExec::try_new(input()?, method).is_err());
+        Ok(())
+    }
+}
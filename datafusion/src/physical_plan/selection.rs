@@ -0,0 +1,234 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Selection vectors, which mark the rows of a batch that an expression is
+//! evaluated for. Conditional expressions such as CASE and AND pass them down
+//! to their children so that the selected rows only need to be copied into a
+//! batch of their own where an expression cannot be evaluated on all rows.
+
+use arrow::array::{Array, ArrayRef, BooleanArray, UInt32Array};
+use arrow::compute::{filter_record_batch, take};
+use arrow::record_batch::RecordBatch;
+
+use super::{ColumnarValue, PhysicalExpr};
+use crate::error::Result;
+
+/// The rows of a batch that are selected, as a mask without nulls together
+/// with the indices of the selected rows
+#[derive(Debug)]
+pub struct SelectionVector {
+    mask: BooleanArray,
+    indices: Vec<u32>,
+}
+
+// arrays are not `Clone`, but share their buffers when created from the
+// same array data
+impl Clone for SelectionVector {
+    fn clone(&self) -> Self {
+        Self {
+            mask: BooleanArray::from(self.mask.data().clone()),
+            indices: self.indices.clone(),
+        }
+    }
+}
+
+impl SelectionVector {
+    /// Selects the rows for which `mask` is true, where null counts as false
+    pub fn new(mask: &BooleanArray) -> Self {
+        let indices = (0..mask.len())
+            .filter(|i| mask.is_valid(*i) && mask.value(*i))
+            .map(|i| i as u32)
+            .collect::<Vec<_>>();
+        Self::from_indices(mask.len(), indices)
+    }
+
+    /// Selects all `num_rows` rows
+    pub fn all(num_rows: usize) -> Self {
+        Self::from_indices(num_rows, (0..num_rows as u32).collect())
+    }
+
+    fn from_indices(num_rows: usize, indices: Vec<u32>) -> Self {
+        let mut selected = vec![false; num_rows];
+        for i in &indices {
+            selected[*i as usize] = true;
+        }
+        Self {
+            mask: BooleanArray::from(selected),
+            indices,
+        }
+    }
+
+    /// The number of rows of the batch, selected or not
+    pub fn len(&self) -> usize {
+        self.mask.len()
+    }
+
+    /// Whether the batch has no rows
+    pub fn is_empty(&self) -> bool {
+        self.mask.is_empty()
+    }
+
+    /// The number of selected rows
+    pub fn selected_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Whether every row of the batch is selected
+    pub fn all_selected(&self) -> bool {
+        self.indices.len() == self.len()
+    }
+
+    /// Whether the row `i` is selected
+    pub fn is_selected(&self, i: usize) -> bool {
+        self.mask.value(i)
+    }
+
+    /// The selection as a mask without nulls
+    pub fn mask(&self) -> &BooleanArray {
+        &self.mask
+    }
+
+    /// The indices of the selected rows, in ascending order
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// The selected rows for which `mask` is true
+    pub fn and(&self, mask: &BooleanArray) -> Self {
+        self.retain(|i| mask.is_valid(i) && mask.value(i))
+    }
+
+    /// The selected rows for which `mask` is false or null
+    pub fn and_not(&self, mask: &BooleanArray) -> Self {
+        self.retain(|i| !(mask.is_valid(i) && mask.value(i)))
+    }
+
+    fn retain(&self, f: impl Fn(usize) -> bool) -> Self {
+        let indices = self
+            .indices
+            .iter()
+            .copied()
+            .filter(|i| f(*i as usize))
+            .collect();
+        Self::from_indices(self.len(), indices)
+    }
+
+    /// Copies the selected rows into a batch of their own
+    pub fn filter_batch(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        if self.all_selected() {
+            return Ok(batch.clone());
+        }
+        Ok(filter_record_batch(batch, &self.mask)?)
+    }
+
+    /// Spreads the `values` of the selected rows, as returned for the batch of
+    /// [`filter_batch`](Self::filter_batch), to those rows, and nulls to the
+    /// other rows
+    pub fn scatter(&self, values: &dyn Array) -> Result<ArrayRef> {
+        let mut next = 0;
+        let indices = (0..self.len())
+            .map(|i| {
+                if self.is_selected(i) {
+                    next += 1;
+                    Some(next - 1)
+                } else {
+                    None
+                }
+            })
+            .collect::<UInt32Array>();
+        Ok(take(values, &indices, None)?)
+    }
+}
+
+/// Evaluates `expr` for the selected rows only, by copying them into a batch
+/// of their own. This is the default for expressions that must not see the
+/// other rows, e.g. because they could fail for them.
+pub fn evaluate_selected<E: PhysicalExpr + ?Sized>(
+    expr: &E,
+    batch: &RecordBatch,
+    selection: &SelectionVector,
+) -> Result<ColumnarValue> {
+    if selection.all_selected() {
+        return expr.evaluate(batch);
+    }
+    if selection.selected_count() == 0 {
+        let data_type = expr.data_type(&batch.schema())?;
+        return Ok(ColumnarValue::Array(arrow::array::new_null_array(
+            &data_type,
+            batch.num_rows(),
+        )));
+    }
+    let selected_batch = selection.filter_batch(batch)?;
+    let values = expr
+        .evaluate(&selected_batch)?
+        .into_array(selected_batch.num_rows());
+    selection.scatter(values.as_ref()).map(ColumnarValue::Array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::Operator;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::scalar::ScalarValue;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn selection_vector_operations() {
+        let selection = SelectionVector::new(&BooleanArray::from(vec![
+            Some(true),
+            None,
+            Some(false),
+            Some(true),
+        ]));
+        assert_eq!(selection.len(), 4);
+        assert_eq!(selection.indices(), &[0, 3]);
+        assert!(!selection.all_selected());
+
+        let mask = BooleanArray::from(vec![Some(false), Some(true), Some(true), None]);
+        assert_eq!(selection.and(&mask).selected_count(), 0);
+        assert_eq!(selection.and_not(&mask).indices(), &[0, 3]);
+        assert!(SelectionVector::all(3).all_selected());
+    }
+
+    #[test]
+    fn evaluate_selected_scatters_nulls() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let a = Int32Array::from(vec![1, 0, 2, 0]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // dividing by the rows that are zero would fail
+        let expr = binary(
+            lit(ScalarValue::Int32(Some(10))),
+            Operator::Divide,
+            col("a"),
+            &schema,
+        )?;
+        let selection =
+            SelectionVector::new(&BooleanArray::from(vec![true, false, true, false]));
+        let result = evaluate_selected(expr.as_ref(), &batch, &selection)?
+            .into_array(batch.num_rows());
+        let expected = Int32Array::from(vec![Some(10), None, Some(5), None]);
+        assert_eq!(
+            result.as_any().downcast_ref::<Int32Array>().unwrap(),
+            &expected
+        );
+        Ok(())
+    }
+}
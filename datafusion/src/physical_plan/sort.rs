@@ -17,16 +17,15 @@
 
 //! Defines the SORT plan
 
+use instant::Instant;
 use std::any::Any;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Instant;
 
 use async_trait::async_trait;
 use futures::stream::Stream;
 use futures::Future;
-use hashbrown::HashMap;
 
 use pin_project_lite::pin_project;
 
@@ -37,11 +36,13 @@ use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 use arrow::{array::ArrayRef, error::ArrowError};
 
+use super::common::AbortOnDropSingle;
 use super::{RecordBatchStream, SendableRecordBatchStream};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::expressions::PhysicalSortExpr;
 use crate::physical_plan::{
-    common, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, SQLMetric,
+    common, DisplayFormatType, Distribution, ExecutionPlan, MemoryUsage, MetricsSet,
+    Partitioning, SQLMetric,
 };
 
 /// Sort execution plan
@@ -55,6 +56,10 @@ pub struct SortExec {
     output_rows: Arc<SQLMetric>,
     /// Time to sort batches
     sort_time_nanos: Arc<SQLMetric>,
+    /// Number of batches read from the input
+    input_batches: Arc<SQLMetric>,
+    /// Memory used by buffered and sorted batches
+    memory: Arc<MemoryUsage>,
     /// Preserve partitions of input plan
     preserve_partitioning: bool,
 }
@@ -81,6 +86,8 @@ impl SortExec {
             preserve_partitioning,
             output_rows: SQLMetric::counter(),
             sort_time_nanos: SQLMetric::time_nanos(),
+            input_batches: SQLMetric::counter(),
+            memory: MemoryUsage::new(),
         }
     }
 
@@ -93,6 +100,11 @@ impl SortExec {
     pub fn expr(&self) -> &[PhysicalSortExpr] {
         &self.expr
     }
+
+    /// Whether each input partition is sorted separately
+    pub fn preserve_partitioning(&self) -> bool {
+        self.preserve_partitioning
+    }
 }
 
 #[async_trait]
@@ -166,6 +178,8 @@ impl ExecutionPlan for SortExec {
             self.expr.clone(),
             self.output_rows.clone(),
             self.sort_time_nanos.clone(),
+            self.input_batches.clone(),
+            self.memory.clone(),
         )))
     }
 
@@ -182,10 +196,12 @@ impl ExecutionPlan for SortExec {
         }
     }
 
-    fn metrics(&self) -> HashMap<String, SQLMetric> {
-        let mut metrics = HashMap::new();
-        metrics.insert("outputRows".to_owned(), (*self.output_rows).clone());
-        metrics.insert("sortTime".to_owned(), (*self.sort_time_nanos).clone());
+    fn metrics(&self) -> MetricsSet {
+        let mut metrics = MetricsSet::new();
+        metrics.push(MetricsSet::OUTPUT_ROWS, &self.output_rows);
+        metrics.push(MetricsSet::ELAPSED_COMPUTE, &self.sort_time_nanos);
+        metrics.push(MetricsSet::INPUT_BATCHES, &self.input_batches);
+        metrics.push(MetricsSet::PEAK_MEM_USED, self.memory.peak());
         metrics
     }
 }
@@ -257,6 +273,7 @@ pin_project! {
         finished: bool,
         schema: SchemaRef,
         output_rows: Arc<SQLMetric>,
+        _drop_helper: AbortOnDropSingle<()>,
     }
 }
 
@@ -266,23 +283,42 @@ impl SortStream {
         expr: Vec<PhysicalSortExpr>,
         output_rows: Arc<SQLMetric>,
         sort_time: Arc<SQLMetric>,
+        input_batches: Arc<SQLMetric>,
+        memory: Arc<MemoryUsage>,
     ) -> Self {
         let (tx, rx) = futures::channel::oneshot::channel();
 
         let schema = input.schema();
-        tokio::spawn(async move {
+        let join_handle = tokio::spawn(async move {
             let schema = input.schema();
-            let sorted_batch = common::collect(input)
-                .await
-                .map_err(DataFusionError::into_arrow_external_error)
-                .and_then(move |batches| {
+            let sorted_batch = match common::collect(input).await {
+                // sorting is CPU intensive, so it runs off the async worker threads
+                Ok(batches) => common::spawn_compute(move || {
+                    input_batches.add(batches.len());
+                    let input_size = common::batches_memory_size(&batches);
+                    memory.grow(input_size);
+
                     let now = Instant::now();
                     let result = sort_batches(&batches, &schema, &expr);
                     sort_time.add(now.elapsed().as_nanos() as usize);
-                    result
-                });
 
-            tx.send(sorted_batch)
+                    // the sorted batch is handed over to the consumer
+                    let output_size = match &result {
+                        Ok(Some(batch)) => common::batch_memory_size(batch),
+                        _ => 0,
+                    };
+                    memory.grow(output_size);
+                    memory.shrink(input_size + output_size);
+                    Ok(result)
+                })
+                .await
+                .map_err(DataFusionError::into_arrow_external_error)
+                .and_then(|result| result),
+                Err(e) => Err(DataFusionError::into_arrow_external_error(e)),
+            };
+
+            // failing here is OK, the receiver is gone and does not care about the result
+            tx.send(sorted_batch).ok();
         });
 
         Self {
@@ -290,6 +326,7 @@ impl SortStream {
             finished: false,
             schema,
             output_rows,
+            _drop_helper: AbortOnDropSingle::new(join_handle),
         }
     }
 }
@@ -461,8 +498,11 @@ mod tests {
         assert_eq!(DataType::Float64, *sort_exec.schema().field(1).data_type());
 
         let result: Vec<RecordBatch> = collect(sort_exec.clone()).await?;
-        assert!(sort_exec.metrics().get("sortTime").unwrap().value() > 0);
-        assert_eq!(sort_exec.metrics().get("outputRows").unwrap().value(), 8);
+        let metrics = sort_exec.metrics();
+        assert!(metrics.elapsed_compute().unwrap() > 0);
+        assert_eq!(metrics.output_rows(), Some(8));
+        assert_eq!(metrics.input_batches(), Some(1));
+        assert!(metrics.peak_mem_used().unwrap() > 0);
         assert_eq!(result.len(), 1);
 
         let columns = result[0].columns();
@@ -0,0 +1,236 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan reading the record batches of a stream, such as one
+//! produced by another system the query is embedded in.
+
+use std::any::Any;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+use futures::stream::{BoxStream, Stream, StreamExt};
+
+/// A stream of record batches that can be read once
+pub type SharedStream = Arc<Mutex<Option<BoxStream<'static, ArrowResult<RecordBatch>>>>>;
+
+/// Execution plan producing the record batches of a stream. As the stream can
+/// only be read once, so can the plan.
+#[derive(Clone)]
+pub struct StreamExec {
+    /// The stream, until it is executed
+    stream: SharedStream,
+    /// The indices of the columns to produce, all of them if `None`
+    projection: Option<Vec<usize>>,
+    /// The output schema, after the projection
+    schema: SchemaRef,
+}
+
+impl StreamExec {
+    /// Create a new StreamExec reading the columns `projection` of the
+    /// batches of `stream`, which are of the schema `schema`
+    pub fn new(
+        stream: SharedStream,
+        schema: SchemaRef,
+        projection: Option<Vec<usize>>,
+    ) -> Self {
+        let schema = match &projection {
+            Some(columns) => Arc::new(Schema::new(
+                columns.iter().map(|i| schema.field(*i).clone()).collect(),
+            )),
+            None => schema,
+        };
+        Self {
+            stream,
+            projection,
+            schema,
+        }
+    }
+}
+
+impl fmt::Debug for StreamExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamExec")
+            .field("projection", &self.projection)
+            .field("schema", &self.schema)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for StreamExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(Arc::new(self.clone()))
+        } else {
+            Err(DataFusionError::Internal(format!(
+                "Children cannot be replaced in {:?}",
+                self
+            )))
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "StreamExec invalid partition {}",
+                partition
+            )));
+        }
+        let input = self.stream.lock().unwrap().take().ok_or_else(|| {
+            DataFusionError::Execution("The stream was already read".to_string())
+        })?;
+        Ok(Box::pin(StreamExecStream {
+            input: Mutex::new(input),
+            projection: self.projection.clone(),
+            schema: self.schema.clone(),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "StreamExec: projection={:?}", self.projection)
+            }
+        }
+    }
+}
+
+/// The StreamExec stream, projecting the batches of its input
+struct StreamExecStream {
+    /// The input, in a mutex as record batch streams are `Sync`
+    input: Mutex<BoxStream<'static, ArrowResult<RecordBatch>>>,
+    projection: Option<Vec<usize>>,
+    schema: SchemaRef,
+}
+
+impl Stream for StreamExecStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.input.get_mut().unwrap().poll_next_unpin(cx);
+        poll.map(|batch| {
+            batch.map(|batch| {
+                let batch = batch?;
+                let columns = match &self.projection {
+                    Some(columns) => {
+                        columns.iter().map(|i| batch.column(*i).clone()).collect()
+                    }
+                    None => batch.columns().to_vec(),
+                };
+                // also checks the batch is of the declared schema
+                RecordBatch::try_new(self.schema.clone(), columns)
+            })
+        })
+    }
+}
+
+impl RecordBatchStream for StreamExecStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+
+    #[tokio::test]
+    async fn read_once_with_projection() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["x", "y"])),
+            ],
+        )?;
+        let stream: SharedStream = Arc::new(Mutex::new(Some(
+            futures::stream::iter(vec![Ok(batch.clone()), Ok(batch)]).boxed(),
+        )));
+
+        let exec = StreamExec::new(stream, schema, Some(vec![1]));
+        assert_eq!(exec.schema().fields().len(), 1);
+        let batches = common::collect(exec.execute(0).await?).await?;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].schema(), exec.schema());
+        assert_eq!(batches[0].num_rows(), 2);
+
+        let err = exec.execute(0).await.err().unwrap();
+        assert!(err.to_string().contains("already read"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn batches_of_another_schema() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Utf8, false)])),
+            vec![Arc::new(StringArray::from(vec!["x"]))],
+        )?;
+        let stream: SharedStream = Arc::new(Mutex::new(Some(
+            futures::stream::iter(vec![Ok(batch)]).boxed(),
+        )));
+
+        let exec = StreamExec::new(stream, schema, None);
+        assert!(common::collect(exec.execute(0).await?).await.is_err());
+        Ok(())
+    }
+}
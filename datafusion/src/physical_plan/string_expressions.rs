@@ -22,6 +22,7 @@
 //! String expressions
 
 use std::any::type_name;
+use std::iter;
 use std::sync::Arc;
 
 use crate::{
@@ -36,6 +37,8 @@ use arrow::{
     datatypes::{ArrowNativeType, ArrowPrimitiveType, DataType},
 };
 
+use uuid::Uuid;
+
 use super::ColumnarValue;
 
 macro_rules! downcast_string_arg {
@@ -520,6 +523,176 @@ pub fn rtrim<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
     }
 }
 
+/// Returns the number of single character insertions, deletions and substitutions needed to turn one string into the other.
+/// levenshtein('kitten', 'sitting') = 3
+pub fn levenshtein<T: ArrowPrimitiveType>(args: &[ArrayRef]) -> Result<ArrayRef>
+where
+    T::Native: StringOffsetSizeTrait,
+{
+    let left_array = downcast_string_array::<T::Native>(&args[0], "left")?;
+    let right_array = downcast_string_array::<T::Native>(&args[1], "right")?;
+
+    let result = left_array
+        .iter()
+        .zip(right_array.iter())
+        .map(|(left, right)| match (left, right) {
+            (Some(left), Some(right)) => levenshtein_distance(left, right, None)
+                .map(|distance| T::Native::from_usize(distance).unwrap()),
+            _ => None,
+        })
+        .collect::<PrimitiveArray<T>>();
+
+    Ok(Arc::new(result) as ArrayRef)
+}
+
+/// Returns true if the Levenshtein distance of both strings is at most max_distance.
+/// fuzzy_match('kitten', 'sitting', 3) = 't'
+pub fn fuzzy_match<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let left_array = downcast_string_arg!(args[0], "left", T);
+    let right_array = downcast_string_arg!(args[1], "right", T);
+    let max_distance_array = downcast_arg!(args[2], "max_distance", Int64Array);
+
+    let result = left_array
+        .iter()
+        .zip(right_array.iter())
+        .zip(max_distance_array.iter())
+        .map(
+            |((left, right), max_distance)| match (left, right, max_distance) {
+                (Some(left), Some(right), Some(max_distance)) => {
+                    if max_distance < 0 {
+                        Err(DataFusionError::Execution(
+                            "max_distance must not be negative".to_string(),
+                        ))
+                    } else {
+                        let max_distance = max_distance as usize;
+                        Ok(Some(
+                            levenshtein_distance(left, right, Some(max_distance))
+                                .is_some(),
+                        ))
+                    }
+                }
+                _ => Ok(None),
+            },
+        )
+        .collect::<Result<BooleanArray>>()?;
+
+    Ok(Arc::new(result) as ArrayRef)
+}
+
+/// The Levenshtein distance of the characters of both strings, or None if it
+/// is larger than `max`, in which case the computation stops early
+fn levenshtein_distance(left: &str, right: &str, max: Option<usize>) -> Option<usize> {
+    let left = left.chars().collect::<Vec<_>>();
+    let right = right.chars().collect::<Vec<_>>();
+    let exceeds = |distance: usize| max.map_or(false, |max| distance > max);
+
+    let length_difference = if left.len() > right.len() {
+        left.len() - right.len()
+    } else {
+        right.len() - left.len()
+    };
+    if exceeds(length_difference) {
+        return None;
+    }
+
+    // the distances of the prefixes of left so far to each prefix of right
+    let mut previous = (0..=right.len()).collect::<Vec<_>>();
+    let mut current = vec![0; right.len() + 1];
+    for (i, l) in left.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(l != r);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if exceeds(*current.iter().min().unwrap()) {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[right.len()];
+    if exceeds(distance) {
+        None
+    } else {
+        Some(distance)
+    }
+}
+
+/// Returns the American Soundex code of a string, which is the same for names that sound alike. Characters other than ASCII letters are ignored.
+/// soundex('Robert') = 'R163'
+pub fn soundex<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let string_array = downcast_string_arg!(args[0], "string", T);
+
+    let result = string_array
+        .iter()
+        .map(|string| string.map(soundex_code))
+        .collect::<GenericStringArray<T>>();
+
+    Ok(Arc::new(result) as ArrayRef)
+}
+
+fn soundex_code(string: &str) -> String {
+    // the digit of each letter, where 0 separates equal digits and '-' does not
+    fn digit(letter: char) -> char {
+        match letter {
+            'B' | 'F' | 'P' | 'V' => '1',
+            'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+            'D' | 'T' => '3',
+            'L' => '4',
+            'M' | 'N' => '5',
+            'R' => '6',
+            'H' | 'W' => '-',
+            _ => '0',
+        }
+    }
+
+    let mut letters = string
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase());
+    let first = match letters.next() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+
+    let mut code = first.to_string();
+    let mut last = digit(first);
+    for letter in letters {
+        match digit(letter) {
+            '-' => {}
+            '0' => last = '0',
+            d if d != last => {
+                code.push(d);
+                if code.len() == 4 {
+                    break;
+                }
+                last = d;
+            }
+            _ => {}
+        }
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+fn downcast_string_array<'a, T: StringOffsetSizeTrait>(
+    array: &'a ArrayRef,
+    name: &str,
+) -> Result<&'a GenericStringArray<T>> {
+    array
+        .as_any()
+        .downcast_ref::<GenericStringArray<T>>()
+        .ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "could not cast {} to {}",
+                name,
+                type_name::<GenericStringArray<T>>()
+            ))
+        })
+}
+
 /// Splits string at occurrences of delimiter and returns the n'th field (counting from one).
 /// split_part('abc~@~def~@~ghi', '~@~', 2) = 'def'
 pub fn split_part<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
@@ -571,18 +744,17 @@ pub fn starts_with<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayR
 }
 
 /// Converts the number to its equivalent hexadecimal representation.
+/// Negative numbers are represented in two's complement.
 /// to_hex(2147483647) = '7fffffff'
 pub fn to_hex<T: ArrowPrimitiveType>(args: &[ArrayRef]) -> Result<ArrayRef>
 where
-    T::Native: StringOffsetSizeTrait,
+    T::Native: std::fmt::LowerHex,
 {
     let integer_array = downcast_primitive_array_arg!(args[0], "integer", T);
 
     let result = integer_array
         .iter()
-        .map(|integer| {
-            integer.map(|integer| format!("{:x}", integer.to_usize().unwrap()))
-        })
+        .map(|integer| integer.map(|integer| format!("{:x}", integer)))
         .collect::<GenericStringArray<i32>>();
 
     Ok(Arc::new(result) as ArrayRef)
@@ -593,3 +765,20 @@ where
 pub fn upper(args: &[ColumnarValue]) -> Result<ColumnarValue> {
     handle(args, |string| string.to_ascii_uppercase(), "upper")
 }
+
+/// Returns a new random version 4 UUID for every row.
+/// uuid() = 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'
+pub fn uuid(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let len: usize = match &args[0] {
+        ColumnarValue::Array(array) => array.len(),
+        _ => {
+            return Err(DataFusionError::Internal(
+                "Expect uuid function to take no param".to_string(),
+            ))
+        }
+    };
+    let array = iter::repeat_with(|| Some(Uuid::new_v4().to_string()))
+        .take(len)
+        .collect::<StringArray>();
+    Ok(ColumnarValue::Array(Arc::new(array)))
+}
@@ -0,0 +1,120 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Struct expressions
+
+use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
+use arrow::array::{ArrayRef, StructArray};
+use arrow::datatypes::{DataType, Field};
+use std::sync::Arc;
+
+fn struct_fields(names: &[String], types: &[DataType]) -> Vec<Field> {
+    names
+        .iter()
+        .zip(types)
+        .map(|(name, data_type)| Field::new(name, data_type.clone(), true))
+        .collect()
+}
+
+/// The type of a struct with the field names `names` and the field types `types`
+pub fn struct_type(names: &[String], types: &[DataType]) -> DataType {
+    DataType::Struct(struct_fields(names, types))
+}
+
+/// The field names of the struct built by `struct`, i.e. `c0`, `c1`, ...
+pub fn struct_names(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("c{}", i)).collect()
+}
+
+/// The field names of the struct built by `named_struct(name1, value1, ...)`
+/// from the values of its arguments, `None` for arguments that are not
+/// literals. The field names must be string literals.
+pub fn named_struct_names(args: &[Option<&ScalarValue>]) -> Result<Vec<String>> {
+    if args.is_empty() || args.len() % 2 != 0 {
+        return Err(DataFusionError::Plan(format!(
+            "named_struct requires pairs of field names and values, got {} arguments",
+            args.len()
+        )));
+    }
+    args.iter()
+        .step_by(2)
+        .map(|arg| match arg {
+            Some(ScalarValue::Utf8(Some(name))) => Ok(name.clone()),
+            Some(ScalarValue::LargeUtf8(Some(name))) => Ok(name.clone()),
+            _ => Err(DataFusionError::Plan(
+                "named_struct requires string literals as field names".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Builds the struct with the field names `names` of the field values `values`
+pub fn named_struct(names: &[String], values: &[ArrayRef]) -> Result<ArrayRef> {
+    let types = values
+        .iter()
+        .map(|value| value.data_type().clone())
+        .collect::<Vec<_>>();
+    let fields = struct_fields(names, &types)
+        .into_iter()
+        .zip(values.iter().cloned())
+        .collect::<Vec<_>>();
+    Ok(Arc::new(StructArray::from(fields)))
+}
+
+/// Builds a struct of the arguments, whose fields are named `c0`, `c1`, ...
+/// struct(1, 'a') = {c0: 1, c1: 'a'}
+pub fn struct_expr(args: &[ArrayRef]) -> Result<ArrayRef> {
+    named_struct(&struct_names(args.len()), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int32Array, StringArray};
+
+    #[test]
+    fn test_named_struct_names() -> Result<()> {
+        let name = ScalarValue::Utf8(Some("a".to_string()));
+        let value = ScalarValue::Int32(Some(1));
+        assert_eq!(
+            named_struct_names(&[Some(&name), None, Some(&name), Some(&value)])?,
+            vec!["a", "a"]
+        );
+        assert!(named_struct_names(&[]).is_err());
+        assert!(named_struct_names(&[Some(&name)]).is_err());
+        assert!(named_struct_names(&[None, None]).is_err());
+        assert!(named_struct_names(&[Some(&value), None]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_expr() -> Result<()> {
+        let ids: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None]));
+        let names: ArrayRef = Arc::new(StringArray::from(vec!["x", "y"]));
+        let result = struct_expr(&[ids.clone(), names.clone()])?;
+        assert_eq!(
+            result.data_type(),
+            &struct_type(&struct_names(2), &[DataType::Int32, DataType::Utf8])
+        );
+        let result = result.as_any().downcast_ref::<StructArray>().unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.column(0), &ids);
+        assert_eq!(result.column(1), &names);
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the execution plan that emits a `tracing` span for each partition
+//! of its input, so that the execution of a query can be profiled with the
+//! tooling of the `tracing` ecosystem.
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use tracing::{field, Instrument, Span};
+
+use super::common::batch_memory_size;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    displayable, DisplayFormatType, Distribution, ExecutionPlan, MetricsSet, Partitioning,
+};
+
+/// Wraps every node of `plan` in a [`TracedExec`], numbering the nodes in
+/// pre-order starting at zero for the root.
+pub fn instrument_plan(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let mut next_id = 0;
+    instrument(plan, &mut next_id)
+}
+
+fn instrument(
+    plan: Arc<dyn ExecutionPlan>,
+    next_id: &mut usize,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let node_id = *next_id;
+    *next_id += 1;
+
+    let children = plan
+        .children()
+        .into_iter()
+        .map(|child| instrument(child, next_id))
+        .collect::<Result<Vec<_>>>()?;
+    let plan = if children.is_empty() {
+        plan
+    } else {
+        plan.with_new_children(children)?
+    };
+    Ok(Arc::new(TracedExec::new(plan, node_id)))
+}
+
+/// Executes its input within a `tracing` span per partition, which records
+/// the plan node id, the partition and the number of rows and bytes
+/// produced. The span is entered whenever the output stream is polled, so
+/// that events and spans of the input are attributed to it.
+#[derive(Debug)]
+pub struct TracedExec {
+    /// Input execution plan
+    input: Arc<dyn ExecutionPlan>,
+    /// Id of the input within the plan
+    node_id: usize,
+}
+
+impl TracedExec {
+    /// Create a new TracedExec for `input`, identified by `node_id`
+    pub fn new(input: Arc<dyn ExecutionPlan>, node_id: usize) -> Self {
+        Self { input, node_id }
+    }
+
+    /// Input execution plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// Id of the input within the plan
+    pub fn node_id(&self) -> usize {
+        self.node_id
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for TracedExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        self.input.required_child_distribution()
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(TracedExec::new(children[0].clone(), self.node_id))),
+            _ => Err(DataFusionError::Internal(
+                "TracedExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let span = tracing::info_span!(
+            "execute",
+            operator = %displayable(self.input.as_ref()).one_line(),
+            node_id = self.node_id,
+            partition = partition,
+            rows = field::Empty,
+            bytes = field::Empty,
+        );
+        let input = self
+            .input
+            .execute(partition)
+            .instrument(span.clone())
+            .await?;
+
+        Ok(Box::pin(TracedStream {
+            input,
+            span,
+            rows: 0,
+            bytes: 0,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "TracedExec: node_id={}", self.node_id)
+            }
+        }
+    }
+
+    fn metrics(&self) -> MetricsSet {
+        self.input.metrics()
+    }
+}
+
+/// Stream that enters its span while polling its input
+struct TracedStream {
+    input: SendableRecordBatchStream,
+    span: Span,
+    /// Number of rows produced so far
+    rows: usize,
+    /// Number of bytes produced so far
+    bytes: usize,
+}
+
+impl Stream for TracedStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let _entered = this.span.enter();
+        let poll = this.input.poll_next_unpin(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(batch))) => {
+                this.rows += batch.num_rows();
+                this.bytes += batch_memory_size(batch);
+            }
+            Poll::Ready(None) => {
+                this.span.record("rows", &this.rows);
+                this.span.record("bytes", &this.bytes);
+            }
+            _ => {}
+        }
+        poll
+    }
+}
+
+impl RecordBatchStream for TracedStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::{col, lit, PhysicalSortExpr};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::sort::SortExec;
+    use crate::physical_plan::{collect, filter::FilterExec};
+    use crate::scalar::ScalarValue;
+    use crate::test::build_table_i32;
+
+    #[tokio::test]
+    async fn instrument_every_node() -> Result<()> {
+        let batch = build_table_i32(
+            ("a", &vec![3, 1, 2]),
+            ("b", &vec![1, 2, 3]),
+            ("c", &vec![4, 5, 6]),
+        );
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        let filter = Arc::new(FilterExec::try_new(
+            lit(ScalarValue::Boolean(Some(true))),
+            input,
+        )?);
+        let sort_expr = PhysicalSortExpr {
+            expr: col("a"),
+            options: Default::default(),
+        };
+        let plan = Arc::new(SortExec::try_new(vec![sort_expr], filter)?);
+
+        let plan = instrument_plan(plan)?;
+        let mut node = plan.clone();
+        for expected_id in 0..3 {
+            let traced = node
+                .as_any()
+                .downcast_ref::<TracedExec>()
+                .expect("traced node");
+            assert_eq!(traced.node_id(), expected_id);
+            node = match traced.input().children().first() {
+                Some(child) => child.clone(),
+                None => break,
+            };
+        }
+
+        let batches = collect(plan).await?;
+        assert_eq!(batches[0].num_rows(), 3);
+        Ok(())
+    }
+}
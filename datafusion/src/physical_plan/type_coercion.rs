@@ -37,6 +37,21 @@ use super::{functions::Signature, PhysicalExpr};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::expressions::try_cast;
 
+/// The numeric types, ordered such that each type can be coerced to the
+/// following types of the same kind
+pub static NUMERICS: &[DataType] = &[
+    DataType::Int8,
+    DataType::UInt8,
+    DataType::Int16,
+    DataType::UInt16,
+    DataType::Int32,
+    DataType::UInt32,
+    DataType::Int64,
+    DataType::UInt64,
+    DataType::Float32,
+    DataType::Float64,
+];
+
 /// Returns `expressions` coerced to types compatible with
 /// `signature`, if possible.
 ///
@@ -117,6 +132,14 @@ fn get_valid_types(
                 .map(|_| current_types[0].clone())
                 .collect()]
         }
+        Signature::VariadicAny => vec![current_types.to_vec()],
+        Signature::Numeric(number) => get_valid_types(
+            &Signature::Uniform(*number, NUMERICS.to_vec()),
+            current_types,
+        )?,
+        Signature::VariadicNumeric => {
+            get_valid_types(&Signature::Variadic(NUMERICS.to_vec()), current_types)?
+        }
         Signature::Exact(valid_types) => vec![valid_types.clone()],
         Signature::Any(number) => {
             if current_types.len() != *number {
@@ -131,7 +154,10 @@ fn get_valid_types(
         Signature::OneOf(types) => {
             let mut r = vec![];
             for s in types {
-                r.extend(get_valid_types(s, current_types)?);
+                // signatures with a different number of arguments do not apply
+                if let Ok(valid_types) = get_valid_types(s, current_types) {
+                    r.extend(valid_types);
+                }
             }
             r
         }
@@ -322,6 +348,18 @@ mod tests {
                 Signature::Any(1),
                 vec![DataType::Float32],
             )?,
+            // common numeric type is u16
+            case(
+                vec![DataType::UInt8, DataType::UInt16],
+                Signature::Numeric(2),
+                vec![DataType::UInt16, DataType::UInt16],
+            )?,
+            // common numeric type is f64
+            case(
+                vec![DataType::Int32, DataType::Float64, DataType::UInt8],
+                Signature::VariadicNumeric,
+                vec![DataType::Float64, DataType::Float64, DataType::Float64],
+            )?,
         ];
 
         for case in cases {
@@ -53,6 +53,8 @@ pub struct AggregateUDF {
     pub accumulator: AccumulatorFunctionImplementation,
     /// the accumulator's state's description as a function of the return type
     pub state_type: StateTypeFunction,
+    /// Other names the function is registered under
+    pub aliases: Vec<String>,
 }
 
 impl Debug for AggregateUDF {
@@ -86,9 +88,20 @@ impl AggregateUDF {
             return_type: return_type.clone(),
             accumulator: accumulator.clone(),
             state_type: state_type.clone(),
+            aliases: vec![],
         }
     }
 
+    /// Returns this UDAF registered under the names `aliases` in addition to
+    /// its name
+    pub fn with_aliases(
+        mut self,
+        aliases: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.aliases = aliases.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// creates a logical expression with a call of the UDAF
     /// This utility allows using the UDAF without requiring access to the registry.
     pub fn call(&self, args: Vec<Expr>) -> Expr {
@@ -132,6 +145,13 @@ pub struct AggregateFunctionExpr {
     name: String,
 }
 
+impl AggregateFunctionExpr {
+    /// The user defined aggregate function this expression evaluates
+    pub fn fun(&self) -> &AggregateUDF {
+        &self.fun
+    }
+}
+
 impl AggregateExpr for AggregateFunctionExpr {
     /// Return a reference to Any that can be used for downcasting
     fn as_any(&self) -> &dyn Any {
@@ -20,14 +20,18 @@
 use fmt::{Debug, Formatter};
 use std::fmt;
 
-use arrow::datatypes::Schema;
+use arrow::datatypes::{DataType, Schema};
 
 use crate::error::Result;
+use crate::scalar::ScalarValue;
 use crate::{logical_plan::Expr, physical_plan::PhysicalExpr};
 
 use super::{
+    async_udf::AsyncScalarUDF,
+    expressions::Literal,
     functions::{
-        ReturnTypeFunction, ScalarFunctionExpr, ScalarFunctionImplementation, Signature,
+        ReturnTypeFromArgsFunction, ReturnTypeFunction, ScalarFunctionExpr,
+        ScalarFunctionImplementation, Signature,
     },
     type_coercion::coerce,
 };
@@ -42,6 +46,9 @@ pub struct ScalarUDF {
     pub signature: Signature,
     /// Return type
     pub return_type: ReturnTypeFunction,
+    /// Return type depending on the values of literal arguments, which takes
+    /// precedence over `return_type` if set
+    pub return_type_from_args: Option<ReturnTypeFromArgsFunction>,
     /// actual implementation
     ///
     /// The fn param is the wrapped function but be aware that the function will
@@ -51,6 +58,11 @@ pub struct ScalarUDF {
     /// the batch's row count (so that the generative zero-argument function can know
     /// the result array size).
     pub fun: ScalarFunctionImplementation,
+    /// The asynchronous implementation of an async UDF, in which case `fun`
+    /// must not be called; see [`AsyncScalarUDF::into_scalar_udf`]
+    pub async_udf: Option<Arc<AsyncScalarUDF>>,
+    /// Other names the function is registered under
+    pub aliases: Vec<String>,
 }
 
 impl Debug for ScalarUDF {
@@ -81,7 +93,44 @@ impl ScalarUDF {
             name: name.to_owned(),
             signature: signature.clone(),
             return_type: return_type.clone(),
+            return_type_from_args: None,
             fun: fun.clone(),
+            async_udf: None,
+            aliases: vec![],
+        }
+    }
+
+    /// Returns this UDF registered under the names `aliases` in addition to
+    /// its name
+    pub fn with_aliases(
+        mut self,
+        aliases: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.aliases = aliases.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns this UDF with its return type computed by `return_type` from
+    /// the types of its arguments and the values of its literal arguments,
+    /// e.g. for a function `parse_as(expr, 'Int64')`
+    pub fn with_return_type_from_args(
+        mut self,
+        return_type: ReturnTypeFromArgsFunction,
+    ) -> Self {
+        self.return_type_from_args = Some(return_type);
+        self
+    }
+
+    /// Returns the return type of a call with arguments of `arg_types`, of
+    /// which those that are literals have the values `literals`
+    pub fn return_type_for(
+        &self,
+        arg_types: &[DataType],
+        literals: &[Option<&ScalarValue>],
+    ) -> Result<Arc<DataType>> {
+        match &self.return_type_from_args {
+            Some(return_type) => return_type(arg_types, literals),
+            None => (self.return_type)(arg_types),
         }
     }
 
@@ -102,6 +151,12 @@ pub fn create_physical_expr(
     args: &[Arc<dyn PhysicalExpr>],
     input_schema: &Schema,
 ) -> Result<Arc<dyn PhysicalExpr>> {
+    // the values of literal arguments as written, before coercion
+    let literals = args
+        .iter()
+        .map(|arg| arg.as_any().downcast_ref::<Literal>().map(|l| l.value()))
+        .collect::<Vec<_>>();
+
     // coerce
     let args = coerce(args, input_schema, &fun.signature)?;
 
@@ -114,6 +169,6 @@ pub fn create_physical_expr(
         &fun.name,
         fun.fun.clone(),
         args,
-        (fun.return_type)(&arg_types)?.as_ref(),
+        fun.return_type_for(&arg_types, &literals)?.as_ref(),
     )))
 }
@@ -0,0 +1,127 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! User-defined table function (UDTF) support
+
+use fmt::{Debug, Formatter};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::datasource::generate_series::GenerateSeriesTable;
+use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
+
+/// Implementation of a table function, which returns the table for the
+/// values of its (literal) arguments
+pub type TableFunctionImplementation =
+    Arc<dyn Fn(&[ScalarValue]) -> Result<Arc<dyn TableProvider>> + Send + Sync>;
+
+/// Logical representation of a user-defined table function, which can be
+/// called with literal arguments in the FROM clause of a query, e.g.
+/// `SELECT * FROM my_table_function(1, 'a')`
+#[derive(Clone)]
+pub struct TableUDF {
+    /// name
+    pub name: String,
+    /// actual implementation
+    pub fun: TableFunctionImplementation,
+}
+
+impl Debug for TableUDF {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TableUDF")
+            .field("name", &self.name)
+            .field("fun", &"<FUNC>")
+            .finish()
+    }
+}
+
+impl PartialEq for TableUDF {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl TableUDF {
+    /// Create a new TableUDF
+    pub fn new(name: &str, fun: &TableFunctionImplementation) -> Self {
+        Self {
+            name: name.to_owned(),
+            fun: fun.clone(),
+        }
+    }
+
+    /// Returns the table of a call with the arguments `args`
+    pub fn call(&self, args: &[ScalarValue]) -> Result<Arc<dyn TableProvider>> {
+        (self.fun)(args)
+    }
+}
+
+/// Returns the built-in table function called `name`, if any:
+///
+/// * `generate_series(start, stop[, step])` returns the integers from `start`
+///   up to and including `stop`
+/// * `range(start, stop[, step])` returns the integers from `start` up to but
+///   excluding `stop`
+pub fn builtin_table_function(name: &str) -> Option<TableUDF> {
+    let include_stop = match name {
+        "generate_series" => true,
+        "range" => false,
+        _ => return None,
+    };
+    let column_name = name.to_owned();
+    let fun: TableFunctionImplementation = Arc::new(move |args| {
+        let (start, stop, step) = series_args(&column_name, args)?;
+        Ok(Arc::new(GenerateSeriesTable::try_new(
+            &column_name,
+            start,
+            stop,
+            step,
+            include_stop,
+        )?))
+    });
+    Some(TableUDF::new(name, &fun))
+}
+
+/// Returns the start, stop and step of a call of a series function
+fn series_args(name: &str, args: &[ScalarValue]) -> Result<(i64, i64, i64)> {
+    let values = args
+        .iter()
+        .map(|arg| match arg {
+            ScalarValue::Int8(Some(v)) => Ok(*v as i64),
+            ScalarValue::Int16(Some(v)) => Ok(*v as i64),
+            ScalarValue::Int32(Some(v)) => Ok(*v as i64),
+            ScalarValue::Int64(Some(v)) => Ok(*v),
+            ScalarValue::UInt8(Some(v)) => Ok(*v as i64),
+            ScalarValue::UInt16(Some(v)) => Ok(*v as i64),
+            ScalarValue::UInt32(Some(v)) => Ok(*v as i64),
+            other => Err(DataFusionError::Plan(format!(
+                "The arguments of {} must be non-null integers, got {:?}",
+                name, other
+            ))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    match values.as_slice() {
+        [start, stop] => Ok((*start, *stop, 1)),
+        [start, stop, step] => Ok((*start, *stop, *step)),
+        _ => Err(DataFusionError::Plan(format!(
+            "{} takes 2 or 3 arguments, got {}",
+            name,
+            values.len()
+        ))),
+    }
+}
@@ -28,7 +28,8 @@ use std::sync::Arc;
 use crate::error::{DataFusionError, Result};
 use arrow::{
     array::{
-        ArrayRef, GenericStringArray, Int64Array, PrimitiveArray, StringOffsetSizeTrait,
+        Array, ArrayRef, GenericStringArray, Int64Array, PrimitiveArray,
+        StringOffsetSizeTrait,
     },
     datatypes::{ArrowNativeType, ArrowPrimitiveType},
 };
@@ -210,6 +211,64 @@ pub fn lpad<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
     }
 }
 
+/// Replaces the substring of string that starts at the start'th character and extends for count characters with replacement. If count is omitted, it defaults to the length of replacement. (Same as overlay(string placing replacement from start for count).)
+/// overlay('Txxxxas', 'hom', 2, 4) = 'Thomas'
+pub fn overlay<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
+    if args.len() != 3 && args.len() != 4 {
+        return Err(DataFusionError::Internal(format!(
+            "overlay was called with {} arguments. It requires 3 or 4.",
+            args.len()
+        )));
+    }
+
+    let string_array = downcast_string_arg!(args[0], "string", T);
+    let replacement_array = downcast_string_arg!(args[1], "replacement", T);
+    let start_array = downcast_arg!(args[2], "start", Int64Array);
+    let count_array = match args.get(3) {
+        Some(count) => Some(downcast_arg!(count, "count", Int64Array)),
+        None => None,
+    };
+
+    let result = (0..string_array.len())
+        .map(|i| {
+            if string_array.is_null(i)
+                || replacement_array.is_null(i)
+                || start_array.is_null(i)
+                || count_array.map_or(false, |counts| counts.is_null(i))
+            {
+                return Ok(None);
+            }
+
+            let start = start_array.value(i);
+            if start <= 0 {
+                return Err(DataFusionError::Execution(
+                    "overlay start position must be positive".to_string(),
+                ));
+            }
+
+            let graphemes = string_array.value(i).graphemes(true).collect::<Vec<&str>>();
+            let replacement = replacement_array.value(i);
+            let count = match count_array {
+                Some(counts) => counts.value(i),
+                None => replacement.graphemes(true).count() as i64,
+            };
+
+            let prefix_end = ((start - 1) as usize).min(graphemes.len());
+            let suffix_start = (start - 1)
+                .saturating_add(count)
+                .max(0)
+                .min(graphemes.len() as i64) as usize;
+
+            let mut result = graphemes[..prefix_end].concat();
+            result.push_str(replacement);
+            result.push_str(&graphemes[suffix_start..].concat());
+            Ok(Some(result))
+        })
+        .collect::<Result<GenericStringArray<T>>>()?;
+
+    Ok(Arc::new(result) as ArrayRef)
+}
+
 /// Reverses the order of the characters in the string.
 /// reverse('abcde') = 'edcba'
 pub fn reverse<T: StringOffsetSizeTrait>(args: &[ArrayRef]) -> Result<ArrayRef> {
@@ -19,14 +19,21 @@
 // or implementation to ensure compatibility and are subject to
 // the Postgres license.
 
-//! The Union operator combines multiple inputs with the same schema
+//! The Union operator combines multiple inputs with compatible schemas
 
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::{any::Any, sync::Arc};
 
-use arrow::datatypes::SchemaRef;
+use arrow::compute::cast;
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use futures::{Stream, StreamExt};
 
-use super::{ExecutionPlan, Partitioning, SendableRecordBatchStream};
-use crate::error::Result;
+use super::expressions::order_coercion;
+use super::{ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
 use async_trait::async_trait;
 
 /// UNION ALL execution plan
@@ -34,15 +41,64 @@ use async_trait::async_trait;
 pub struct UnionExec {
     /// Input execution plan
     inputs: Vec<Arc<dyn ExecutionPlan>>,
+    /// Schema of the union, to which the batches of all inputs are coerced
+    schema: SchemaRef,
 }
 
 impl UnionExec {
-    /// Create a new UnionExec
-    pub fn new(inputs: Vec<Arc<dyn ExecutionPlan>>) -> Self {
-        UnionExec { inputs }
+    /// Create a new UnionExec. The inputs must have the same number of
+    /// columns, see [`union_schema`] for how their schemas are combined.
+    pub fn try_new(inputs: Vec<Arc<dyn ExecutionPlan>>) -> Result<Self> {
+        let schemas = inputs
+            .iter()
+            .map(|input| input.schema())
+            .collect::<Vec<_>>();
+        let schema = Arc::new(union_schema(&schemas)?);
+        Ok(UnionExec { inputs, schema })
     }
 }
 
+/// Combines the schemas of the inputs of a union. Columns are matched by
+/// position and named after the first schema. Each column has the common
+/// type of the matching columns, and is nullable if any of them is.
+pub fn union_schema(schemas: &[SchemaRef]) -> Result<Schema> {
+    let (first, rest) = schemas.split_first().ok_or_else(|| {
+        DataFusionError::Plan("Union requires at least one input".to_string())
+    })?;
+
+    let mut fields = first.fields().clone();
+    for schema in rest {
+        if schema.fields().len() != fields.len() {
+            return Err(DataFusionError::Plan(format!(
+                "Union inputs have different numbers of columns: {} and {}",
+                fields.len(),
+                schema.fields().len()
+            )));
+        }
+        fields = fields
+            .iter()
+            .zip(schema.fields())
+            .map(|(field, other)| {
+                let data_type = order_coercion(field.data_type(), other.data_type())
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(format!(
+                            "Union cannot coerce column {} from {:?} and {:?}",
+                            field.name(),
+                            field.data_type(),
+                            other.data_type()
+                        ))
+                    })?;
+                Ok(Field::new(
+                    field.name(),
+                    data_type,
+                    field.is_nullable() || other.is_nullable(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+    }
+    Ok(Schema::new(fields))
+}
+
 #[async_trait]
 impl ExecutionPlan for UnionExec {
     /// Return a reference to Any that can be used for downcasting
@@ -51,14 +107,19 @@ impl ExecutionPlan for UnionExec {
     }
 
     fn schema(&self) -> SchemaRef {
-        self.inputs[0].schema()
+        self.schema.clone()
     }
 
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         self.inputs.clone()
     }
 
-    /// Output of the union is the combination of all output partitions of the inputs
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children.iter().any(|unbounded| *unbounded))
+    }
+
+    /// Output of the union is the combination of all output partitions of the
+    /// inputs, which are not merged
     fn output_partitioning(&self) -> Partitioning {
         // Sums all the output partitions
         let num_partitions = self
@@ -75,7 +136,7 @@ impl ExecutionPlan for UnionExec {
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        Ok(Arc::new(UnionExec::new(children)))
+        Ok(Arc::new(UnionExec::try_new(children)?))
     }
 
     async fn execute(&self, mut partition: usize) -> Result<SendableRecordBatchStream> {
@@ -83,7 +144,14 @@ impl ExecutionPlan for UnionExec {
         for input in self.inputs.iter() {
             // Calculate whether partition belongs to the current partition
             if partition < input.output_partitioning().partition_count() {
-                return input.execute(partition).await;
+                let stream = input.execute(partition).await?;
+                if input.schema() == self.schema {
+                    return Ok(stream);
+                }
+                return Ok(Box::pin(CoercedStream {
+                    schema: self.schema.clone(),
+                    input: stream,
+                }));
             } else {
                 partition -= input.output_partitioning().partition_count();
             }
@@ -96,15 +164,65 @@ impl ExecutionPlan for UnionExec {
     }
 }
 
+/// Casts the batches of an input of the union to the schema of the union
+struct CoercedStream {
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+}
+
+fn coerce_batch(batch: &RecordBatch, schema: &SchemaRef) -> ArrowResult<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| {
+            if column.data_type() == field.data_type() {
+                Ok(column.clone())
+            } else {
+                cast(column, field.data_type())
+            }
+        })
+        .collect::<ArrowResult<Vec<_>>>()?;
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+impl Stream for CoercedStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.input.poll_next_unpin(cx).map(|x| match x {
+            Some(Ok(batch)) => Some(coerce_batch(&batch, &self.schema)),
+            other => other,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // same number of record batches
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for CoercedStream {
+    /// Get the schema
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::physical_plan::memory::MemoryExec;
     use crate::physical_plan::{
         collect,
         csv::{CsvExec, CsvReadOptions},
     };
     use crate::test;
-    use arrow::record_batch::RecordBatch;
+    use arrow::array::{Array, Int32Array, Int64Array};
+    use arrow::datatypes::DataType;
 
     #[tokio::test]
     async fn test_union_partitions() -> Result<()> {
@@ -130,7 +248,8 @@ mod tests {
             None,
         )?;
 
-        let union_exec = Arc::new(UnionExec::new(vec![Arc::new(csv), Arc::new(csv2)]));
+        let union_exec =
+            Arc::new(UnionExec::try_new(vec![Arc::new(csv), Arc::new(csv2)])?);
 
         // Should have 9 partitions and 9 output batches
         assert_eq!(union_exec.output_partitioning().partition_count(), 9);
@@ -140,4 +259,80 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_union_coerces_schemas() -> Result<()> {
+        let schema1 =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch1 = RecordBatch::try_new(
+            schema1.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2]))],
+        )?;
+        let input1 =
+            MemoryExec::try_new(&[vec![batch1.clone()], vec![batch1]], schema1, None)?;
+
+        let schema2 = Arc::new(Schema::new(vec![Field::new("b", DataType::Int64, true)]));
+        let batch2 = RecordBatch::try_new(
+            schema2.clone(),
+            vec![Arc::new(Int64Array::from(vec![Some(3), None]))],
+        )?;
+        let input2 = MemoryExec::try_new(&[vec![batch2]], schema2, None)?;
+
+        let union_exec = Arc::new(UnionExec::try_new(vec![
+            Arc::new(input1),
+            Arc::new(input2),
+        ])?);
+
+        // the column is widened, nullable and named after the first input
+        let expected_schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        assert_eq!(union_exec.schema().as_ref(), &expected_schema);
+
+        // the partitions of the inputs are not merged
+        assert_eq!(union_exec.output_partitioning().partition_count(), 3);
+
+        let result: Vec<RecordBatch> = collect(union_exec).await?;
+        assert_eq!(result.len(), 3);
+        for batch in &result {
+            assert_eq!(batch.schema().as_ref(), &expected_schema);
+        }
+        let values = result
+            .iter()
+            .flat_map(|batch| {
+                let array = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                (0..array.len())
+                    .map(|i| {
+                        if array.is_null(i) {
+                            None
+                        } else {
+                            Some(array.value(i))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![Some(1), Some(2), Some(1), Some(2), Some(3), None]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_union_incompatible_schemas() {
+        let schema1 =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let schema2 =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Boolean, false)]));
+        let schema3 = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        assert!(union_schema(&[schema1.clone(), schema2]).is_err());
+        assert!(union_schema(&[schema1, schema3]).is_err());
+    }
 }
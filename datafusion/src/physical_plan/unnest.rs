@@ -0,0 +1,321 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the unnest operator, which expands a list column into one row per
+//! item of its lists
+
+use std::any::Any;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+use arrow::array::{Array, ArrayRef, GenericListArray, OffsetSizeTrait, UInt64Array};
+use arrow::compute::take;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+
+use async_trait::async_trait;
+
+use futures::stream::{Stream, StreamExt};
+
+/// UnnestExec expands the list column at `column_index` into one row per item,
+/// repeating the values of the other columns. Rows with null or empty lists
+/// produce no rows.
+#[derive(Debug)]
+pub struct UnnestExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The index of the list column to unnest
+    column_index: usize,
+    /// The output schema, in which the list column is replaced by its items
+    schema: SchemaRef,
+}
+
+impl UnnestExec {
+    /// Create an UnnestExec of the list column `column_index` of `input`
+    pub fn try_new(input: Arc<dyn ExecutionPlan>, column_index: usize) -> Result<Self> {
+        let input_schema = input.schema();
+        let fields = input_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if i != column_index {
+                    return Ok(field.clone());
+                }
+                match field.data_type() {
+                    DataType::List(item) | DataType::LargeList(item) => Ok(Field::new(
+                        field.name(),
+                        item.data_type().clone(),
+                        item.is_nullable(),
+                    )),
+                    other => Err(DataFusionError::Plan(format!(
+                        "Only list columns can be unnested, column '{}' is of type {:?}",
+                        field.name(),
+                        other
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            input,
+            column_index,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The index of the list column to unnest
+    pub fn column_index(&self) -> usize {
+        self.column_index
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for UnnestExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn unbounded_output(&self, children: &[bool]) -> Result<bool> {
+        Ok(children[0])
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(UnnestExec::try_new(
+                children[0].clone(),
+                self.column_index,
+            )?)),
+            _ => Err(DataFusionError::Internal(
+                "UnnestExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        Ok(Box::pin(UnnestExecStream {
+            schema: self.schema.clone(),
+            column_index: self.column_index,
+            input: self.input.execute(partition).await?,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "UnnestExec: column={}",
+                    self.schema.field(self.column_index).name()
+                )
+            }
+        }
+    }
+}
+
+/// Returns the items of the list array `array` and the range of the items of
+/// each row, which is empty for null lists
+fn list_ranges<O: OffsetSizeTrait>(array: &ArrayRef) -> (ArrayRef, Vec<Range<usize>>) {
+    let list = array
+        .as_any()
+        .downcast_ref::<GenericListArray<O>>()
+        .unwrap();
+    let offsets = list.value_offsets();
+    let ranges = (0..list.len())
+        .map(|i| {
+            if list.is_null(i) {
+                0..0
+            } else {
+                offsets[i].to_usize().unwrap()..offsets[i + 1].to_usize().unwrap()
+            }
+        })
+        .collect();
+    (list.values(), ranges)
+}
+
+fn unnest_batch(
+    batch: &RecordBatch,
+    column_index: usize,
+    schema: &SchemaRef,
+) -> ArrowResult<RecordBatch> {
+    let list = batch.column(column_index);
+    let (values, ranges) = match list.data_type() {
+        DataType::LargeList(_) => list_ranges::<i64>(list),
+        _ => list_ranges::<i32>(list),
+    };
+
+    // the row of each output row, and the item of the unnested column
+    let row_indices = UInt64Array::from(
+        ranges
+            .iter()
+            .enumerate()
+            .flat_map(|(row, range)| std::iter::repeat(row as u64).take(range.len()))
+            .collect::<Vec<_>>(),
+    );
+    let value_indices = UInt64Array::from(
+        ranges
+            .into_iter()
+            .flat_map(|range| range.map(|i| i as u64))
+            .collect::<Vec<_>>(),
+    );
+
+    let columns = batch
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            if i == column_index {
+                take(values.as_ref(), &value_indices, None)
+            } else {
+                take(column.as_ref(), &row_indices, None)
+            }
+        })
+        .collect::<ArrowResult<Vec<_>>>()?;
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+/// The UnnestExec stream unnests each batch of its input
+struct UnnestExecStream {
+    /// Output schema
+    schema: SchemaRef,
+    /// The index of the list column to unnest
+    column_index: usize,
+    /// The input partition to unnest
+    input: SendableRecordBatchStream,
+}
+
+impl Stream for UnnestExecStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.input.poll_next_unpin(cx).map(|x| match x {
+            Some(Ok(batch)) => {
+                Some(unnest_batch(&batch, self.column_index, &self.schema))
+            }
+            other => other,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // same number of record batches
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for UnnestExecStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::array::{Int32Array, ListArray};
+    use arrow::datatypes::Int32Type;
+
+    #[tokio::test]
+    async fn unnest_list_column() -> Result<()> {
+        let lists = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![]),
+            Some(vec![Some(3), None]),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("items", lists.data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![10, 20, 30, 40])),
+                Arc::new(lists),
+            ],
+        )?;
+        let input = MemoryExec::try_new(&[vec![batch]], schema, None)?;
+
+        let unnest = UnnestExec::try_new(Arc::new(input), 1)?;
+        assert_eq!(unnest.schema().field(1).data_type(), &DataType::Int32);
+
+        let batches = collect(Arc::new(unnest)).await?;
+        let batch = &batches[0];
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        let items = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(
+            ids.iter().collect::<Vec<_>>(),
+            vec![Some(10), Some(10), Some(40), Some(40)]
+        );
+        assert_eq!(
+            items.iter().collect::<Vec<_>>(),
+            vec![Some(1), Some(2), Some(3), None]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unnest_non_list_column() {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let input = MemoryExec::try_new(&[], schema, None).unwrap();
+        assert!(UnnestExec::try_new(Arc::new(input), 0).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan for a relation of literal rows, such as `VALUES (1, 'a')`
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    memory::MemoryStream, ColumnarValue, DisplayFormatType, Distribution, ExecutionPlan,
+    Partitioning, PhysicalExpr,
+};
+use crate::scalar::ScalarValue;
+use arrow::array::{new_null_array, ArrayRef};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use super::SendableRecordBatchStream;
+
+use async_trait::async_trait;
+
+/// Execution plan for a relation of literal rows. The rows are evaluated
+/// once, when the plan is created, and produced as record batches in a
+/// single partition.
+#[derive(Debug)]
+pub struct ValuesExec {
+    /// The schema of the produced rows
+    schema: SchemaRef,
+    /// The materialized rows
+    data: Vec<RecordBatch>,
+}
+
+impl ValuesExec {
+    /// Create a new ValuesExec from rows of expressions, which must not
+    /// reference any column and must evaluate to the types of `schema`
+    pub fn try_new(
+        schema: SchemaRef,
+        data: Vec<Vec<Arc<dyn PhysicalExpr>>>,
+    ) -> Result<Self> {
+        if data.is_empty() {
+            return Err(DataFusionError::Plan(
+                "Values list cannot be empty".to_string(),
+            ));
+        }
+
+        // the expressions are evaluated against a single placeholder row
+        let placeholder_schema = Arc::new(Schema::new(vec![Field::new(
+            "placeholder",
+            DataType::Null,
+            true,
+        )]));
+        let placeholder = RecordBatch::try_new(
+            placeholder_schema,
+            vec![new_null_array(&DataType::Null, 1)],
+        )?;
+
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let values = data
+                    .iter()
+                    .map(|row| {
+                        let expr = row.get(i).ok_or_else(|| {
+                            DataFusionError::Plan(format!(
+                                "Inconsistent number of values in VALUES row: \
+                                 expected {}, got {}",
+                                schema.fields().len(),
+                                row.len()
+                            ))
+                        })?;
+                        let value = match expr.evaluate(&placeholder)? {
+                            ColumnarValue::Scalar(value) => value,
+                            ColumnarValue::Array(array) => {
+                                ScalarValue::try_from_array(&array, 0)?
+                            }
+                        };
+                        if value.get_datatype() != *field.data_type() {
+                            return Err(DataFusionError::Plan(format!(
+                                "Expected {:?} for {} of VALUES, got {:?}",
+                                field.data_type(),
+                                field.name(),
+                                value.get_datatype()
+                            )));
+                        }
+                        Ok(value)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                ScalarValue::iter_to_array(values)
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+        Ok(Self {
+            schema,
+            data: vec![batch],
+        })
+    }
+
+    /// Create a new ValuesExec from already materialized batches, such as
+    /// the result of a constant subplan
+    pub fn try_new_from_batches(
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<Self> {
+        if let Some(batch) = batches.iter().find(|batch| batch.schema() != schema) {
+            return Err(DataFusionError::Plan(format!(
+                "Batch schema {:?} does not match the schema of ValuesExec {:?}",
+                batch.schema(),
+                schema
+            )));
+        }
+        Ok(Self {
+            schema,
+            data: batches,
+        })
+    }
+
+    /// The materialized rows
+    pub fn data(&self) -> Vec<RecordBatch> {
+        self.data.clone()
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for ValuesExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::UnspecifiedDistribution
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            0 => Ok(Arc::new(ValuesExec {
+                schema: self.schema.clone(),
+                data: self.data.clone(),
+            })),
+            _ => Err(DataFusionError::Internal(
+                "ValuesExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        // ValuesExec has a single output partition
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "ValuesExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+
+        Ok(Box::pin(MemoryStream::try_new(
+            self.data.clone(),
+            self.schema.clone(),
+            None,
+        )?))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "ValuesExec")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::expressions::lit;
+    use arrow::array::{Int64Array, StringArray};
+
+    #[tokio::test]
+    async fn values_exec() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("column1", DataType::Int64, false),
+            Field::new("column2", DataType::Utf8, true),
+        ]));
+        let values = ValuesExec::try_new(
+            schema.clone(),
+            vec![
+                vec![
+                    lit(ScalarValue::Int64(Some(1))),
+                    lit(ScalarValue::from("a")),
+                ],
+                vec![
+                    lit(ScalarValue::Int64(Some(2))),
+                    lit(ScalarValue::Utf8(None)),
+                ],
+            ],
+        )?;
+        assert_eq!(values.schema(), schema);
+
+        let batches = common::collect(values.execute(0).await?).await?;
+        assert_eq!(batches.len(), 1);
+        let column1 = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(column1, &Int64Array::from(vec![1, 2]));
+        let column2 = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(column2, &StringArray::from(vec![Some("a"), None]));
+
+        // ask for the wrong partition
+        assert!(values.execute(1).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn values_exec_type_mismatch() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "column1",
+            DataType::Int64,
+            false,
+        )]));
+        let result =
+            ValuesExec::try_new(schema, vec![vec![lit(ScalarValue::Int32(Some(1)))]]);
+        assert!(result.is_err());
+    }
+}
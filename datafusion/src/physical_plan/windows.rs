@@ -20,6 +20,7 @@
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     aggregates,
+    common::AbortOnDropSingle,
     expressions::{FirstValue, LastValue, Literal, NthValue, RowNumber},
     type_coercion::coerce,
     window_functions::signature_for_built_in,
@@ -320,6 +321,7 @@ pin_project! {
         #[pin]
         output: futures::channel::oneshot::Receiver<ArrowResult<RecordBatch>>,
         finished: bool,
+        _drop_helper: AbortOnDropSingle<()>,
     }
 }
 
@@ -466,15 +468,17 @@ impl WindowAggStream {
     ) -> Self {
         let (tx, rx) = futures::channel::oneshot::channel();
         let schema_clone = schema.clone();
-        tokio::spawn(async move {
+        let join_handle = tokio::spawn(async move {
             let result = compute_window_aggregate(schema_clone, window_expr, input).await;
-            tx.send(result)
+            // failing here is OK, the receiver is gone and does not care about the result
+            tx.send(result).ok();
         });
 
         Self {
             output: rx,
             finished: false,
             schema,
+            _drop_helper: AbortOnDropSingle::new(join_handle),
         }
     }
 }
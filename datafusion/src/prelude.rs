@@ -30,8 +30,10 @@ pub use crate::execution::context::{ExecutionConfig, ExecutionContext};
 pub use crate::logical_plan::{
     array, ascii, avg, bit_length, btrim, character_length, chr, col, concat, concat_ws,
     count, create_udf, in_list, initcap, left, length, lit, lower, lpad, ltrim, max, md5,
-    min, now, octet_length, random, regexp_replace, repeat, replace, reverse, right,
-    rpad, rtrim, sha224, sha256, sha384, sha512, split_part, starts_with, strpos, substr,
-    sum, to_hex, translate, trim, upper, JoinType, Partitioning,
+    min, monotonic_id, now, octet_length, random, regexp_replace, repeat, replace,
+    reverse, right, rpad, rtrim, sha224, sha256, sha384, sha512, split_part, starts_with,
+    strpos, substr, sum, to_hex, translate, trim, upper, uuid, DuplicateKeep, JoinType,
+    Partitioning, SampleMethod,
 };
+pub use crate::physical_plan::common::ListingOptions;
 pub use crate::physical_plan::csv::CsvReadOptions;
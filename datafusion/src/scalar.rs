@@ -20,6 +20,7 @@
 use crate::error::{DataFusionError, Result};
 use arrow::{
     array::*,
+    buffer::Buffer,
     datatypes::{
         ArrowDictionaryKeyType, ArrowNativeType, DataType, Field, Float32Type,
         Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, IntervalUnit, TimeUnit,
@@ -65,6 +66,8 @@ pub enum ScalarValue {
     LargeBinary(Option<Vec<u8>>),
     /// list of nested ScalarValue
     List(Option<Vec<ScalarValue>>, DataType),
+    /// struct of nested ScalarValue, one per field
+    Struct(Option<Vec<ScalarValue>>, Vec<Field>),
     /// Date stored as a signed 32bit int
     Date32(Option<i32>),
     /// Date stored as a signed 64bit int
@@ -236,6 +239,7 @@ impl ScalarValue {
             ScalarValue::List(_, data_type) => {
                 DataType::List(Box::new(Field::new("item", data_type.clone(), true)))
             }
+            ScalarValue::Struct(_, fields) => DataType::Struct(fields.clone()),
             ScalarValue::Date32(_) => DataType::Date32,
             ScalarValue::Date64(_) => DataType::Date64,
             ScalarValue::IntervalYearMonth(_) => {
@@ -282,6 +286,7 @@ impl ScalarValue {
                 | ScalarValue::Utf8(None)
                 | ScalarValue::LargeUtf8(None)
                 | ScalarValue::List(None, _)
+                | ScalarValue::Struct(None, _)
                 | ScalarValue::TimestampMillisecond(None)
                 | ScalarValue::TimestampMicrosecond(None)
                 | ScalarValue::TimestampNanosecond(None)
@@ -520,6 +525,8 @@ impl ScalarValue {
             DataType::List(fields) if fields.data_type() == &DataType::LargeUtf8 => {
                 build_array_list_string!(LargeStringBuilder, LargeUtf8)
             }
+            DataType::List(field) => Self::iter_to_list_array(scalars, field)?,
+            DataType::Struct(fields) => Self::iter_to_struct_array(scalars, fields)?,
             _ => {
                 return Err(DataFusionError::Internal(format!(
                     "Unsupported creation of {:?} array from ScalarValue {:?}",
@@ -532,6 +539,108 @@ impl ScalarValue {
         Ok(array)
     }
 
+    /// Creates a list array of lists of `field` from `scalars`, for any type
+    /// of the items, including nested ones
+    fn iter_to_list_array(
+        scalars: impl IntoIterator<Item = ScalarValue>,
+        field: &Field,
+    ) -> Result<ArrayRef> {
+        let mut values = vec![];
+        let mut offsets = vec![0_i32];
+        let mut validity = BooleanBufferBuilder::new(0);
+        for scalar in scalars {
+            match scalar {
+                ScalarValue::List(Some(xs), _) => {
+                    values.extend(xs);
+                    validity.append(true);
+                }
+                ScalarValue::List(None, _) => validity.append(false),
+                sv => {
+                    return Err(DataFusionError::Internal(format!(
+                        "Inconsistent types in ScalarValue::iter_to_array. \
+                         Expected List, got {:?}",
+                        sv
+                    )))
+                }
+            }
+            offsets.push(i32::try_from(values.len()).map_err(|_| {
+                DataFusionError::Internal(
+                    "Too many values for a list array in ScalarValue::iter_to_array"
+                        .to_string(),
+                )
+            })?);
+        }
+
+        let values = if values.is_empty() {
+            new_empty_array(field.data_type())
+        } else {
+            Self::iter_to_array(values)?
+        };
+        check_nested_type(field.data_type(), &values)?;
+
+        let data = ArrayData::builder(DataType::List(Box::new(field.clone())))
+            .len(offsets.len() - 1)
+            .add_buffer(Buffer::from_slice_ref(&offsets))
+            .add_child_data(values.data().clone())
+            .null_bit_buffer(validity.finish())
+            .build();
+        Ok(make_array(data))
+    }
+
+    /// Creates a struct array with `fields` from `scalars`
+    fn iter_to_struct_array(
+        scalars: impl IntoIterator<Item = ScalarValue>,
+        fields: &[Field],
+    ) -> Result<ArrayRef> {
+        let mut columns = vec![vec![]; fields.len()];
+        let mut validity = BooleanBufferBuilder::new(0);
+        let mut len = 0;
+        for scalar in scalars {
+            match scalar {
+                ScalarValue::Struct(Some(values), _) if values.len() == fields.len() => {
+                    for (column, value) in columns.iter_mut().zip(values) {
+                        column.push(value);
+                    }
+                    validity.append(true);
+                }
+                ScalarValue::Struct(None, _) => {
+                    for (column, field) in columns.iter_mut().zip(fields) {
+                        column.push(ScalarValue::try_from(field.data_type())?);
+                    }
+                    validity.append(false);
+                }
+                sv => {
+                    return Err(DataFusionError::Internal(format!(
+                        "Inconsistent types in ScalarValue::iter_to_array. \
+                         Expected Struct with {} fields, got {:?}",
+                        fields.len(),
+                        sv
+                    )))
+                }
+            }
+            len += 1;
+        }
+
+        let mut builder = ArrayData::builder(DataType::Struct(fields.to_vec()))
+            .len(len)
+            .null_bit_buffer(validity.finish());
+        for (column, field) in columns.into_iter().zip(fields) {
+            let column = Self::iter_to_array(column)?;
+            check_nested_type(field.data_type(), &column)?;
+            builder = builder.add_child_data(column.data().clone());
+        }
+        Ok(make_array(builder.build()))
+    }
+
+    /// Converts a nested scalar value into an array of `size` rows
+    fn nested_array_of_size(&self, size: usize) -> ArrayRef {
+        if size == 0 {
+            return new_empty_array(&self.get_datatype());
+        }
+        Self::iter_to_array(repeat(self.clone()).take(size))
+            .unwrap_or_else(|e| panic!("Unexpected nested ScalarValue {:?}: {}", self, e))
+    }
+
     /// Converts a scalar value into an array of `size` rows.
     pub fn to_array_of_size(&self, size: usize) -> ArrayRef {
         match self {
@@ -644,8 +753,9 @@ impl ScalarValue {
                 DataType::LargeUtf8 => {
                     build_list!(LargeStringBuilder, LargeUtf8, values, size)
                 }
-                dt => panic!("Unexpected DataType for list {:?}", dt),
+                _ => return self.nested_array_of_size(size),
             }),
+            ScalarValue::Struct(_, _) => self.nested_array_of_size(size),
             ScalarValue::Date32(e) => {
                 build_array_from_option!(Date32, Date32Array, e, size)
             }
@@ -670,6 +780,137 @@ impl ScalarValue {
         }
     }
 
+    /// Appends this value to `builder`, which builds arrays of the data type
+    /// of this value and was created by
+    /// [`make_builder`](Self::make_builder). Only values of the
+    /// data types that [`can_append_to_builder`](Self::can_append_to_builder)
+    /// can be appended.
+    pub fn append_to_builder(&self, builder: &mut dyn ArrayBuilder) -> Result<()> {
+        macro_rules! append_value {
+            ($BUILDER_TY:ident, $VALUE:expr) => {{
+                let builder = builder
+                    .as_any_mut()
+                    .downcast_mut::<$BUILDER_TY>()
+                    .ok_or_else(|| {
+                        DataFusionError::Internal(format!(
+                            "Can not append {:?} to a builder of another type",
+                            self
+                        ))
+                    })?;
+                match $VALUE {
+                    Some(value) => builder.append_value(value)?,
+                    None => builder.append_null()?,
+                }
+            }};
+        }
+
+        match self {
+            ScalarValue::Boolean(v) => append_value!(BooleanBuilder, *v),
+            ScalarValue::Float32(v) => append_value!(Float32Builder, *v),
+            ScalarValue::Float64(v) => append_value!(Float64Builder, *v),
+            ScalarValue::Int8(v) => append_value!(Int8Builder, *v),
+            ScalarValue::Int16(v) => append_value!(Int16Builder, *v),
+            ScalarValue::Int32(v) => append_value!(Int32Builder, *v),
+            ScalarValue::Int64(v) => append_value!(Int64Builder, *v),
+            ScalarValue::UInt8(v) => append_value!(UInt8Builder, *v),
+            ScalarValue::UInt16(v) => append_value!(UInt16Builder, *v),
+            ScalarValue::UInt32(v) => append_value!(UInt32Builder, *v),
+            ScalarValue::UInt64(v) => append_value!(UInt64Builder, *v),
+            ScalarValue::Utf8(v) => append_value!(StringBuilder, v.as_deref()),
+            ScalarValue::Binary(v) => append_value!(BinaryBuilder, v.as_deref()),
+            ScalarValue::Date32(v) => append_value!(Date32Builder, *v),
+            ScalarValue::Date64(v) => append_value!(Date64Builder, *v),
+            ScalarValue::TimestampSecond(v) => {
+                append_value!(TimestampSecondBuilder, *v)
+            }
+            ScalarValue::TimestampMillisecond(v) => {
+                append_value!(TimestampMillisecondBuilder, *v)
+            }
+            ScalarValue::TimestampMicrosecond(v) => {
+                append_value!(TimestampMicrosecondBuilder, *v)
+            }
+            ScalarValue::TimestampNanosecond(v) => {
+                append_value!(TimestampNanosecondBuilder, *v)
+            }
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "Appending {:?} to a builder is not supported",
+                    other
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether values of `data_type` can be appended to builders with
+    /// [`append_to_builder`](Self::append_to_builder)
+    pub fn can_append_to_builder(data_type: &DataType) -> bool {
+        matches!(
+            data_type,
+            DataType::Boolean
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32
+                | DataType::UInt64
+                | DataType::Utf8
+                | DataType::Binary
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, _)
+        )
+    }
+
+    /// Creates a builder of arrays of `data_type` with room for `capacity`
+    /// values, for the data types that
+    /// [`can_append_to_builder`](Self::can_append_to_builder). The time zone
+    /// of timestamps is not kept.
+    pub fn make_builder(
+        data_type: &DataType,
+        capacity: usize,
+    ) -> Result<Box<dyn ArrayBuilder>> {
+        Ok(match data_type {
+            DataType::Boolean => Box::new(BooleanBuilder::new(capacity)),
+            DataType::Float32 => Box::new(Float32Builder::new(capacity)),
+            DataType::Float64 => Box::new(Float64Builder::new(capacity)),
+            DataType::Int8 => Box::new(Int8Builder::new(capacity)),
+            DataType::Int16 => Box::new(Int16Builder::new(capacity)),
+            DataType::Int32 => Box::new(Int32Builder::new(capacity)),
+            DataType::Int64 => Box::new(Int64Builder::new(capacity)),
+            DataType::UInt8 => Box::new(UInt8Builder::new(capacity)),
+            DataType::UInt16 => Box::new(UInt16Builder::new(capacity)),
+            DataType::UInt32 => Box::new(UInt32Builder::new(capacity)),
+            DataType::UInt64 => Box::new(UInt64Builder::new(capacity)),
+            DataType::Utf8 => Box::new(StringBuilder::new(capacity)),
+            DataType::Binary => Box::new(BinaryBuilder::new(capacity)),
+            DataType::Date32 => Box::new(Date32Builder::new(capacity)),
+            DataType::Date64 => Box::new(Date64Builder::new(capacity)),
+            DataType::Timestamp(TimeUnit::Second, _) => {
+                Box::new(TimestampSecondBuilder::new(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                Box::new(TimestampMillisecondBuilder::new(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                Box::new(TimestampMicrosecondBuilder::new(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                Box::new(TimestampNanosecondBuilder::new(capacity))
+            }
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "Building arrays of {:?} is not supported",
+                    other
+                )))
+            }
+        })
+    }
+
     /// Converts a value in `array` at `index` into a ScalarValue
     pub fn try_from_array(array: &ArrayRef, index: usize) -> Result<Self> {
         Ok(match array.data_type() {
@@ -705,6 +946,37 @@ impl ScalarValue {
                 };
                 ScalarValue::List(value, nested_type.data_type().clone())
             }
+            DataType::Struct(fields) => {
+                let struct_array = array
+                    .as_any()
+                    .downcast_ref::<StructArray>()
+                    .ok_or_else(|| {
+                        DataFusionError::Internal(
+                            "Failed to downcast StructArray".to_string(),
+                        )
+                    })?;
+                let value = match struct_array.is_null(index) {
+                    true => None,
+                    false => Some(
+                        struct_array
+                            .columns()
+                            .iter()
+                            .map(|column| ScalarValue::try_from_array(column, index))
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                };
+                ScalarValue::Struct(value, fields.clone())
+            }
+            DataType::Binary => typed_cast!(array, index, BinaryArray, Binary),
+            DataType::LargeBinary => {
+                typed_cast!(array, index, LargeBinaryArray, LargeBinary)
+            }
+            DataType::Interval(IntervalUnit::DayTime) => {
+                typed_cast!(array, index, IntervalDayTimeArray, IntervalDayTime)
+            }
+            DataType::Interval(IntervalUnit::YearMonth) => {
+                typed_cast!(array, index, IntervalYearMonthArray, IntervalYearMonth)
+            }
             DataType::Date32 => {
                 typed_cast!(array, index, Date32Array, Date32)
             }
@@ -782,6 +1054,20 @@ impl ScalarValue {
     }
 }
 
+/// Checks that the values built for a nested array have the declared type
+fn check_nested_type(data_type: &DataType, values: &ArrayRef) -> Result<()> {
+    if values.data_type() == data_type {
+        Ok(())
+    } else {
+        Err(DataFusionError::Internal(format!(
+            "Inconsistent types in ScalarValue::iter_to_array. \
+             Expected nested values of {:?}, got {:?}",
+            data_type,
+            values.data_type()
+        )))
+    }
+}
+
 impl From<f64> for ScalarValue {
     fn from(value: f64) -> Self {
         ScalarValue::Float64(Some(value))
@@ -951,6 +1237,17 @@ impl TryFrom<&DataType> for ScalarValue {
             DataType::List(ref nested_type) => {
                 ScalarValue::List(None, nested_type.data_type().clone())
             }
+            DataType::Struct(fields) => ScalarValue::Struct(None, fields.clone()),
+            DataType::Binary => ScalarValue::Binary(None),
+            DataType::LargeBinary => ScalarValue::LargeBinary(None),
+            DataType::Date32 => ScalarValue::Date32(None),
+            DataType::Date64 => ScalarValue::Date64(None),
+            DataType::Interval(IntervalUnit::DayTime) => {
+                ScalarValue::IntervalDayTime(None)
+            }
+            DataType::Interval(IntervalUnit::YearMonth) => {
+                ScalarValue::IntervalYearMonth(None)
+            }
             _ => {
                 return Err(DataFusionError::NotImplemented(format!(
                     "Can't create a scalar of type \"{:?}\"",
@@ -1023,6 +1320,18 @@ impl fmt::Display for ScalarValue {
                 )?,
                 None => write!(f, "NULL")?,
             },
+            ScalarValue::Struct(e, fields) => match e {
+                Some(l) => write!(
+                    f,
+                    "{{{}}}",
+                    l.iter()
+                        .zip(fields)
+                        .map(|(v, field)| format!("{}: {}", field.name(), v))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )?,
+                None => write!(f, "NULL")?,
+            },
             ScalarValue::Date32(e) => format_option!(f, e)?,
             ScalarValue::Date64(e) => format_option!(f, e)?,
             ScalarValue::IntervalDayTime(e) => format_option!(f, e)?,
@@ -1065,6 +1374,7 @@ impl fmt::Debug for ScalarValue {
             ScalarValue::LargeBinary(None) => write!(f, "LargeBinary({})", self),
             ScalarValue::LargeBinary(Some(_)) => write!(f, "LargeBinary(\"{}\")", self),
             ScalarValue::List(_, _) => write!(f, "List([{}])", self),
+            ScalarValue::Struct(_, _) => write!(f, "Struct({})", self),
             ScalarValue::Date32(_) => write!(f, "Date32(\"{}\")", self),
             ScalarValue::Date64(_) => write!(f, "Date64(\"{}\")", self),
             ScalarValue::IntervalDayTime(_) => {
@@ -1186,6 +1496,121 @@ mod tests {
         assert_eq!(prim_array.value(2), 101);
     }
 
+    fn key_value_fields() -> Vec<Field> {
+        vec![
+            Field::new("key", DataType::Utf8, false),
+            Field::new("value", DataType::Int64, true),
+        ]
+    }
+
+    fn key_value(key: &str, value: Option<i64>) -> ScalarValue {
+        ScalarValue::Struct(
+            Some(vec![
+                ScalarValue::Utf8(Some(key.to_string())),
+                ScalarValue::Int64(value),
+            ]),
+            key_value_fields(),
+        )
+    }
+
+    #[test]
+    fn scalar_struct_to_array_of_size() {
+        let scalar = key_value("a", Some(1));
+        assert_eq!(scalar.get_datatype(), DataType::Struct(key_value_fields()));
+        assert_eq!(format!("{}", scalar), "{key: a, value: 1}");
+
+        let array = scalar.to_array_of_size(3);
+        let array = array.as_any().downcast_ref::<StructArray>().unwrap();
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.null_count(), 0);
+        let values = array
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(values, &Int64Array::from(vec![1, 1, 1]));
+
+        let null = ScalarValue::try_from(&scalar.get_datatype()).unwrap();
+        assert!(null.is_null());
+        assert_eq!(null.to_array_of_size(2).null_count(), 2);
+        assert_eq!(scalar.to_array_of_size(0).len(), 0);
+    }
+
+    #[test]
+    fn scalar_struct_list_round_trip() {
+        // a map is represented as a list of key/value structs
+        let entries = DataType::Struct(key_value_fields());
+        let scalars = vec![
+            ScalarValue::List(
+                Some(vec![key_value("a", Some(1)), key_value("b", None)]),
+                entries.clone(),
+            ),
+            ScalarValue::List(None, entries.clone()),
+            ScalarValue::List(Some(vec![]), entries.clone()),
+            ScalarValue::List(Some(vec![key_value("c", Some(3))]), entries.clone()),
+        ];
+
+        let array = ScalarValue::iter_to_array(scalars.clone()).unwrap();
+        assert_eq!(
+            array.data_type(),
+            &DataType::List(Box::new(Field::new("item", entries, true)))
+        );
+        assert_eq!(array.len(), 4);
+        assert!(array.is_null(1));
+
+        let list_array = array.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(list_array.value_offsets(), &[0, 2, 2, 2, 3]);
+
+        let round_trip = (0..array.len())
+            .map(|i| ScalarValue::try_from_array(&array, i))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(round_trip, scalars);
+    }
+
+    #[test]
+    fn scalar_nested_list_round_trip() {
+        let inner = |values: Vec<i64>| {
+            ScalarValue::List(
+                Some(
+                    values
+                        .into_iter()
+                        .map(|v| ScalarValue::Int64(Some(v)))
+                        .collect(),
+                ),
+                DataType::Int64,
+            )
+        };
+        let inner_type =
+            DataType::List(Box::new(Field::new("item", DataType::Int64, true)));
+        let scalars = vec![
+            ScalarValue::List(
+                Some(vec![inner(vec![1, 2]), inner(vec![3])]),
+                inner_type.clone(),
+            ),
+            ScalarValue::List(
+                Some(vec![ScalarValue::List(None, DataType::Int64)]),
+                inner_type,
+            ),
+        ];
+
+        let array = ScalarValue::iter_to_array(scalars.clone()).unwrap();
+        assert_eq!(array.len(), 2);
+
+        let round_trip = (0..array.len())
+            .map(|i| ScalarValue::try_from_array(&array, i))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(round_trip, scalars);
+
+        let repeated = scalars[0].to_array_of_size(3);
+        assert_eq!(repeated.len(), 3);
+        assert_eq!(
+            ScalarValue::try_from_array(&repeated, 2).unwrap(),
+            scalars[0]
+        );
+    }
+
     /// Creates array directly and via ScalarValue and ensures they are the same
     macro_rules! check_scalar_iter {
         ($SCALAR_T:ident, $ARRAYTYPE:ident, $INPUT:expr) => {{
@@ -1320,4 +1745,27 @@ mod tests {
         assert!(result.to_string().contains("Inconsistent types in ScalarValue::iter_to_array. Expected Boolean, got Int32(5)"),
                 "{}", result);
     }
+
+    #[test]
+    fn scalar_append_to_builder() -> Result<()> {
+        let mut builder = ScalarValue::make_builder(&DataType::Utf8, 2)?;
+        ScalarValue::Utf8(Some("foo".to_string())).append_to_builder(builder.as_mut())?;
+        ScalarValue::Utf8(None).append_to_builder(builder.as_mut())?;
+        let array = builder.finish();
+        let expected: ArrayRef = Arc::new(StringArray::from(vec![Some("foo"), None]));
+        assert_eq!(&array, &expected);
+
+        // values of other types are not appended
+        let err = ScalarValue::Int32(Some(1))
+            .append_to_builder(builder.as_mut())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Can not append Int32(1)"),
+            "{}",
+            err
+        );
+        assert!(!ScalarValue::can_append_to_builder(&DataType::LargeUtf8));
+
+        Ok(())
+    }
 }
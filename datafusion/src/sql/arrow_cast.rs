@@ -0,0 +1,352 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Implementation of the `arrow_cast` function, which casts to any arrow
+//! [`DataType`] given by the name printed by `arrow_typeof`, such as
+//! `arrow_cast(x, 'Timestamp(Nanosecond, None)')`. This allows casting to
+//! types that can not be expressed in SQL.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use arrow::datatypes::{DataType, Field, IntervalUnit, TimeUnit};
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Expr;
+use crate::scalar::ScalarValue;
+
+/// Creates the cast of `arrow_cast(expr, '<data type>')`
+pub(crate) fn create_arrow_cast(mut args: Vec<Expr>) -> Result<Expr> {
+    if args.len() != 2 {
+        return Err(DataFusionError::Plan(format!(
+            "arrow_cast requires 2 arguments, got {}",
+            args.len()
+        )));
+    }
+    let data_type = match args.pop() {
+        Some(Expr::Literal(ScalarValue::Utf8(Some(name)))) => parse_data_type(&name)?,
+        other => {
+            return Err(DataFusionError::Plan(format!(
+            "arrow_cast requires its second argument to be a string literal, got {:?}",
+            other
+        )))
+        }
+    };
+    Ok(Expr::Cast {
+        expr: Box::new(args.pop().unwrap()),
+        data_type,
+    })
+}
+
+/// Parses the name of a [`DataType`] as printed by its `Debug` implementation,
+/// e.g. `Int32` or `Timestamp(Nanosecond, Some("UTC"))`. The item type of
+/// lists is given without its field, e.g. `List(Int32)`.
+pub(crate) fn parse_data_type(name: &str) -> Result<DataType> {
+    let mut parser = Parser {
+        name,
+        tokens: Tokenizer {
+            name,
+            chars: name.chars().peekable(),
+        }
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .peekable(),
+    };
+    let data_type = parser.parse_data_type()?;
+    match parser.tokens.next() {
+        None => Ok(data_type),
+        Some(token) => {
+            Err(parser.error(&format!("unexpected '{}' after the type", token)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Integer(i64),
+    String(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Integer(value) => write!(f, "{}", value),
+            Token::String(value) => write!(f, "\"{}\"", value),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    name: &'a str,
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut value = String::new();
+        while let Some(c) = self.chars.peek().copied().filter(|c| predicate(*c)) {
+            value.push(c);
+            self.chars.next();
+        }
+        value
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.take_while(char::is_whitespace);
+        let c = *self.chars.peek()?;
+        Some(match c {
+            '(' | ')' | ',' => {
+                self.chars.next();
+                Ok(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                })
+            }
+            '"' => {
+                self.chars.next();
+                let value = self.take_while(|c| c != '"');
+                match self.chars.next() {
+                    Some(_) => Ok(Token::String(value)),
+                    None => Err(DataFusionError::Plan(format!(
+                        "Unsupported type '{}': unterminated string",
+                        self.name
+                    ))),
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let value = self.take_while(|c| c.is_ascii_digit());
+                value.parse().map(Token::Integer).map_err(|e| {
+                    DataFusionError::Plan(format!(
+                        "Unsupported type '{}': {}",
+                        self.name, e
+                    ))
+                })
+            }
+            c if c.is_ascii_alphabetic() => Ok(Token::Word(
+                self.take_while(|c| c.is_ascii_alphanumeric() || c == '_'),
+            )),
+            c => Err(DataFusionError::Plan(format!(
+                "Unsupported type '{}': unexpected character '{}'",
+                self.name, c
+            ))),
+        })
+    }
+}
+
+struct Parser<'a> {
+    name: &'a str,
+    tokens: Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> DataFusionError {
+        DataFusionError::Plan(format!("Unsupported type '{}': {}", self.name, message))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.tokens.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => {
+                Err(self.error(&format!("expected '{}', got '{}'", expected, token)))
+            }
+            None => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn parse_word(&mut self) -> Result<String> {
+        match self.tokens.next() {
+            Some(Token::Word(word)) => Ok(word),
+            Some(token) => Err(self.error(&format!("expected a name, got '{}'", token))),
+            None => Err(self.error("expected a name")),
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<i64> {
+        match self.tokens.next() {
+            Some(Token::Integer(value)) => Ok(value),
+            Some(token) => {
+                Err(self.error(&format!("expected a number, got '{}'", token)))
+            }
+            None => Err(self.error("expected a number")),
+        }
+    }
+
+    /// Parses `(<argument>)` by `f`
+    fn parse_argument<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.expect(Token::LParen)?;
+        let value = f(self)?;
+        self.expect(Token::RParen)?;
+        Ok(value)
+    }
+
+    fn parse_time_unit(&mut self) -> Result<TimeUnit> {
+        match self.parse_word()?.as_str() {
+            "Second" => Ok(TimeUnit::Second),
+            "Millisecond" => Ok(TimeUnit::Millisecond),
+            "Microsecond" => Ok(TimeUnit::Microsecond),
+            "Nanosecond" => Ok(TimeUnit::Nanosecond),
+            other => Err(self.error(&format!("unknown time unit '{}'", other))),
+        }
+    }
+
+    /// Parses `None` or `Some("<timezone>")`
+    fn parse_timezone(&mut self) -> Result<Option<String>> {
+        match self.parse_word()?.as_str() {
+            "None" => Ok(None),
+            "Some" => self.parse_argument(|parser| match parser.tokens.next() {
+                Some(Token::String(timezone)) => Ok(Some(timezone)),
+                _ => Err(parser.error("expected a quoted timezone")),
+            }),
+            other => Err(self.error(&format!("expected None or Some, got '{}'", other))),
+        }
+    }
+
+    fn parse_data_type(&mut self) -> Result<DataType> {
+        let word = self.parse_word()?;
+        Ok(match word.as_str() {
+            "Null" => DataType::Null,
+            "Boolean" => DataType::Boolean,
+            "Int8" => DataType::Int8,
+            "Int16" => DataType::Int16,
+            "Int32" => DataType::Int32,
+            "Int64" => DataType::Int64,
+            "UInt8" => DataType::UInt8,
+            "UInt16" => DataType::UInt16,
+            "UInt32" => DataType::UInt32,
+            "UInt64" => DataType::UInt64,
+            "Float16" => DataType::Float16,
+            "Float32" => DataType::Float32,
+            "Float64" => DataType::Float64,
+            "Date32" => DataType::Date32,
+            "Date64" => DataType::Date64,
+            "Binary" => DataType::Binary,
+            "LargeBinary" => DataType::LargeBinary,
+            "Utf8" => DataType::Utf8,
+            "LargeUtf8" => DataType::LargeUtf8,
+            "Timestamp" => self.parse_argument(|parser| {
+                let unit = parser.parse_time_unit()?;
+                parser.expect(Token::Comma)?;
+                Ok(DataType::Timestamp(unit, parser.parse_timezone()?))
+            })?,
+            "Time32" => DataType::Time32(self.parse_argument(Self::parse_time_unit)?),
+            "Time64" => DataType::Time64(self.parse_argument(Self::parse_time_unit)?),
+            "Duration" => DataType::Duration(self.parse_argument(Self::parse_time_unit)?),
+            "Interval" => {
+                self.parse_argument(|parser| match parser.parse_word()?.as_str() {
+                    "YearMonth" => Ok(DataType::Interval(IntervalUnit::YearMonth)),
+                    "DayTime" => Ok(DataType::Interval(IntervalUnit::DayTime)),
+                    other => {
+                        Err(parser.error(&format!("unknown interval unit '{}'", other)))
+                    }
+                })?
+            }
+            "FixedSizeBinary" => {
+                DataType::FixedSizeBinary(self.parse_argument(Self::parse_integer)? as i32)
+            }
+            "Decimal" => self.parse_argument(|parser| {
+                let precision = parser.parse_integer()?;
+                parser.expect(Token::Comma)?;
+                let scale = parser.parse_integer()?;
+                Ok(DataType::Decimal(precision as usize, scale as usize))
+            })?,
+            "Dictionary" => self.parse_argument(|parser| {
+                let key_type = parser.parse_data_type()?;
+                parser.expect(Token::Comma)?;
+                let value_type = parser.parse_data_type()?;
+                Ok(DataType::Dictionary(
+                    Box::new(key_type),
+                    Box::new(value_type),
+                ))
+            })?,
+            "List" => DataType::List(Box::new(Field::new(
+                "item",
+                self.parse_argument(Self::parse_data_type)?,
+                true,
+            ))),
+            "LargeList" => DataType::LargeList(Box::new(Field::new(
+                "item",
+                self.parse_argument(Self::parse_data_type)?,
+                true,
+            ))),
+            other => return Err(self.error(&format!("unknown type '{}'", other))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_data_type_roundtrip() -> Result<()> {
+        let types = vec![
+            DataType::Null,
+            DataType::Boolean,
+            DataType::Int8,
+            DataType::UInt64,
+            DataType::Float64,
+            DataType::Utf8,
+            DataType::LargeBinary,
+            DataType::Date32,
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            DataType::Timestamp(TimeUnit::Second, Some("+08:00".to_string())),
+            DataType::Time64(TimeUnit::Microsecond),
+            DataType::Duration(TimeUnit::Millisecond),
+            DataType::Interval(IntervalUnit::DayTime),
+            DataType::FixedSizeBinary(16),
+            DataType::Decimal(10, 2),
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+        ];
+        for data_type in types {
+            assert_eq!(parse_data_type(&format!("{:?}", data_type))?, data_type);
+        }
+
+        assert_eq!(
+            parse_data_type("LargeList( Int32 )")?,
+            DataType::LargeList(Box::new(Field::new("item", DataType::Int32, true)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_data_type_errors() {
+        for name in &[
+            "",
+            "Int",
+            "Int32 Int32",
+            "Timestamp(Nanosecond)",
+            "Timestamp(Hour, None)",
+            "Timestamp(Second, Some(\"UTC))",
+            "Decimal(10, -1)",
+        ] {
+            assert!(parse_data_type(name).is_err(), "{}", name);
+        }
+    }
+}
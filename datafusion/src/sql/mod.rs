@@ -18,6 +18,8 @@
 //! This module provides a SQL parser that translates SQL queries into an abstract syntax
 //! tree (AST), and a SQL query planner that creates a logical plan from the AST.
 
+mod arrow_cast;
 pub mod parser;
 pub mod planner;
+pub mod unparser;
 mod utils;
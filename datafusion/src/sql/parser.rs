@@ -20,7 +20,10 @@
 //! Declares a SQL parser based on sqlparser that handles custom formats that we need.
 
 use sqlparser::{
-    ast::{ColumnDef, ColumnOptionDef, Statement as SQLStatement, TableConstraint},
+    ast::{
+        ColumnDef, ColumnOptionDef, DataType as SQLDataType, Statement as SQLStatement,
+        TableConstraint,
+    },
     dialect::{keywords::Keyword, Dialect, GenericDialect},
     parser::{Parser, ParserError},
     tokenizer::{Token, Tokenizer},
@@ -59,6 +62,44 @@ pub struct CreateExternalTable {
     pub location: String,
 }
 
+/// An argument of a function defined with `CREATE FUNCTION`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateFunctionArg {
+    /// Optional argument name
+    pub name: Option<String>,
+    /// Argument type
+    pub data_type: SQLDataType,
+}
+
+/// DataFusion extension DDL for
+/// `CREATE [OR REPLACE] FUNCTION name(args) [RETURNS type] [LANGUAGE lang] [AS 'body']`,
+/// whose function is created by the context's
+/// [`FunctionFactory`](crate::execution::function_factory::FunctionFactory)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateFunction {
+    /// Whether an existing function of the same name is replaced
+    pub or_replace: bool,
+    /// Function name, lowercase unless quoted
+    pub name: String,
+    /// Function arguments
+    pub args: Vec<CreateFunctionArg>,
+    /// Optional return type
+    pub return_type: Option<SQLDataType>,
+    /// Optional language the body is written in
+    pub language: Option<String>,
+    /// Optional function body
+    pub body: Option<String>,
+}
+
+/// DataFusion extension DDL for `DROP FUNCTION [IF EXISTS] name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropFunction {
+    /// Whether dropping a function that does not exist succeeds
+    pub if_exists: bool,
+    /// Function name, lowercase unless quoted
+    pub name: String,
+}
+
 /// DataFusion Statement representations.
 ///
 /// Tokens parsed by `DFParser` are converted into these values.
@@ -68,6 +109,10 @@ pub enum Statement {
     Statement(SQLStatement),
     /// Extension: `CREATE EXTERNAL TABLE`
     CreateExternalTable(CreateExternalTable),
+    /// Extension: `CREATE FUNCTION`
+    CreateFunction(CreateFunction),
+    /// Extension: `DROP FUNCTION`
+    DropFunction(DropFunction),
 }
 
 /// SQL Parser
@@ -145,6 +190,9 @@ impl<'a> DFParser<'a> {
                         // use custom parsing
                         self.parse_create()
                     }
+                    Keyword::DROP if self.parse_words(&["DROP", "FUNCTION"]) => {
+                        self.parse_drop_function()
+                    }
                     _ => {
                         // use the native parser
                         Ok(Statement::Statement(self.parser.parse_statement()?))
@@ -162,6 +210,10 @@ impl<'a> DFParser<'a> {
     pub fn parse_create(&mut self) -> Result<Statement, ParserError> {
         if self.parser.parse_keyword(Keyword::EXTERNAL) {
             self.parse_create_external_table()
+        } else if self.parse_words(&["FUNCTION"]) {
+            self.parse_create_function(false)
+        } else if self.parse_words(&["OR", "REPLACE", "FUNCTION"]) {
+            self.parse_create_function(true)
         } else {
             Ok(Statement::Statement(self.parser.parse_create()?))
         }
@@ -265,6 +317,103 @@ impl<'a> DFParser<'a> {
         Ok(Statement::CreateExternalTable(create))
     }
 
+    fn parse_create_function(
+        &mut self,
+        or_replace: bool,
+    ) -> Result<Statement, ParserError> {
+        let name = self.parse_function_name()?;
+
+        self.parser.expect_token(&Token::LParen)?;
+        let mut args = vec![];
+        if !self.parser.consume_token(&Token::RParen) {
+            loop {
+                args.push(self.parse_function_arg()?);
+                if self.parser.consume_token(&Token::RParen) {
+                    break;
+                }
+                self.parser.expect_token(&Token::Comma)?;
+            }
+        }
+
+        // the remaining clauses may come in any order
+        let mut return_type = None;
+        let mut language = None;
+        let mut body = None;
+        loop {
+            if return_type.is_none() && self.parse_words(&["RETURNS"]) {
+                return_type = Some(self.parser.parse_data_type()?);
+            } else if language.is_none() && self.parse_words(&["LANGUAGE"]) {
+                language = Some(self.parser.parse_identifier()?.value);
+            } else if body.is_none() && self.parse_words(&["AS"]) {
+                body = Some(self.parser.parse_literal_string()?);
+            } else {
+                break;
+            }
+        }
+
+        Ok(Statement::CreateFunction(CreateFunction {
+            or_replace,
+            name,
+            args,
+            return_type,
+            language,
+            body,
+        }))
+    }
+
+    fn parse_function_arg(&mut self) -> Result<CreateFunctionArg, ParserError> {
+        // an argument is named if its first word is followed by another word,
+        // as in `x INT`, rather than by the end of the argument or the
+        // parameters of its type, as in `INT` or `VARCHAR(10)`
+        let name = match (self.parser.next_token(), self.parser.peek_token()) {
+            (Token::Word(name), Token::Word(_)) if name.keyword != Keyword::DOUBLE => {
+                Some(name.value)
+            }
+            _ => {
+                self.parser.prev_token();
+                None
+            }
+        };
+        let data_type = self.parser.parse_data_type()?;
+        Ok(CreateFunctionArg { name, data_type })
+    }
+
+    fn parse_drop_function(&mut self) -> Result<Statement, ParserError> {
+        let if_exists = self.parse_words(&["IF", "EXISTS"]);
+        let name = self.parse_function_name()?;
+        Ok(Statement::DropFunction(DropFunction { if_exists, name }))
+    }
+
+    /// Parses a function name, which is lowercase unless quoted like the
+    /// names functions are called with
+    fn parse_function_name(&mut self) -> Result<String, ParserError> {
+        let ident = self.parser.parse_identifier()?;
+        Ok(match ident.quote_style {
+            Some(_) => ident.value,
+            None => ident.value.to_lowercase(),
+        })
+    }
+
+    /// Consumes the unquoted words `expected`, ignoring their case, if they
+    /// are the next tokens
+    fn parse_words(&mut self, expected: &[&str]) -> bool {
+        for (i, expected) in expected.iter().enumerate() {
+            match self.parser.peek_token() {
+                Token::Word(w)
+                    if w.quote_style.is_none()
+                        && w.value.eq_ignore_ascii_case(expected) =>
+                {
+                    self.parser.next_token();
+                }
+                _ => {
+                    (0..i).for_each(|_| self.parser.prev_token());
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     /// Parses the set of valid formats
     fn parse_file_format(&mut self) -> Result<FileType, ParserError> {
         match self.parser.next_token() {
@@ -377,4 +526,84 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn create_function() -> Result<(), ParserError> {
+        let sql = "CREATE FUNCTION Add_One(x BIGINT) RETURNS BIGINT AS 'x + 1'";
+        let expected = Statement::CreateFunction(CreateFunction {
+            or_replace: false,
+            name: "add_one".into(),
+            args: vec![CreateFunctionArg {
+                name: Some("x".into()),
+                data_type: DataType::BigInt,
+            }],
+            return_type: Some(DataType::BigInt),
+            language: None,
+            body: Some("x + 1".into()),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // unnamed arguments and clauses in any order
+        let sql =
+            "create or replace function \"MyFunc\"(INT, DOUBLE PRECISION, VARCHAR(10)) \
+                   LANGUAGE wasm AS 'my_func.wasm' RETURNS DOUBLE PRECISION";
+        let expected = Statement::CreateFunction(CreateFunction {
+            or_replace: true,
+            name: "MyFunc".into(),
+            args: vec![
+                CreateFunctionArg {
+                    name: None,
+                    data_type: DataType::Int,
+                },
+                CreateFunctionArg {
+                    name: None,
+                    data_type: DataType::Double,
+                },
+                CreateFunctionArg {
+                    name: None,
+                    data_type: DataType::Varchar(Some(10)),
+                },
+            ],
+            return_type: Some(DataType::Double),
+            language: Some("wasm".into()),
+            body: Some("my_func.wasm".into()),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "CREATE FUNCTION f()";
+        let expected = Statement::CreateFunction(CreateFunction {
+            or_replace: false,
+            name: "f".into(),
+            args: vec![],
+            return_type: None,
+            language: None,
+            body: None,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        expect_parse_error("CREATE FUNCTION f(x INT", "Expected ,, found: EOF");
+
+        Ok(())
+    }
+
+    #[test]
+    fn drop_function() -> Result<(), ParserError> {
+        let expected = Statement::DropFunction(DropFunction {
+            if_exists: false,
+            name: "add_one".into(),
+        });
+        expect_parse_ok("DROP FUNCTION ADD_ONE", expected)?;
+
+        let expected = Statement::DropFunction(DropFunction {
+            if_exists: true,
+            name: "add_one".into(),
+        });
+        expect_parse_ok("drop function if exists add_one", expected)?;
+
+        // other DROP statements are parsed by sqlparser
+        let statements = DFParser::parse_sql("DROP TABLE t")?;
+        assert!(matches!(statements[0], Statement::Statement(_)));
+
+        Ok(())
+    }
 }
@@ -25,8 +25,8 @@ use crate::catalog::TableReference;
 use crate::datasource::TableProvider;
 use crate::logical_plan::Expr::Alias;
 use crate::logical_plan::{
-    and, lit, DFSchema, Expr, LogicalPlan, LogicalPlanBuilder, Operator, PlanType,
-    StringifiedPlan, ToDFSchema,
+    and, col, lit, union_schema, DFSchema, Expr, LogicalPlan, LogicalPlanBuilder,
+    Operator, PlanType, StringifiedPlan, ToDFSchema,
 };
 use crate::scalar::ScalarValue;
 use crate::{
@@ -35,6 +35,7 @@ use crate::{
 };
 use crate::{
     physical_plan::udf::ScalarUDF,
+    physical_plan::udtf::{self, TableUDF},
     physical_plan::{aggregates, functions, window_functions},
     sql::parser::{CreateExternalTable, FileType, Statement as DFStatement},
 };
@@ -46,14 +47,15 @@ use crate::prelude::JoinType;
 use sqlparser::ast::{
     BinaryOperator, DataType as SQLDataType, DateTimeField, Expr as SQLExpr, FunctionArg,
     Ident, Join, JoinConstraint, JoinOperator, ObjectName, Query, Select, SelectItem,
-    SetExpr, SetOperator, ShowStatementFilter, TableFactor, TableWithJoins,
-    UnaryOperator, Value,
+    SetExpr, SetOperator, SetVariableValue, ShowStatementFilter, TableFactor,
+    TableWithJoins, UnaryOperator, Value,
 };
 use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
 use sqlparser::ast::{OrderByExpr, Statement};
 use sqlparser::parser::ParserError::ParserError;
 
 use super::{
+    arrow_cast::create_arrow_cast,
     parser::DFParser,
     utils::{
         can_columns_satisfy_exprs, expand_wildcard, expr_as_column_expr, extract_aliases,
@@ -71,6 +73,8 @@ pub trait ContextProvider {
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>>;
     /// Getter for a UDAF description
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>>;
+    /// Getter for a UDTF description
+    fn get_table_function_meta(&self, name: &str) -> Option<Arc<TableUDF>>;
 }
 
 /// SQL query planner
@@ -89,6 +93,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         match statement {
             DFStatement::CreateExternalTable(s) => self.external_table_to_plan(&s),
             DFStatement::Statement(s) => self.sql_statement_to_plan(&s),
+            DFStatement::CreateFunction(_) | DFStatement::DropFunction(_) => {
+                Err(DataFusionError::NotImplemented(
+                    "CREATE FUNCTION and DROP FUNCTION statements can only be executed \
+                     with ExecutionContext::sql"
+                        .to_string(),
+                ))
+            }
         }
     }
 
@@ -98,10 +109,19 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             Statement::Explain {
                 verbose,
                 statement,
-                analyze: _,
-            } => self.explain_statement_to_plan(*verbose, &statement),
+                analyze,
+            } => {
+                if *analyze {
+                    self.explain_analyze_statement_to_plan(*verbose, &statement)
+                } else {
+                    self.explain_statement_to_plan(*verbose, &statement)
+                }
+            }
             Statement::Query(query) => self.query_to_plan(&query),
             Statement::ShowVariable { variable } => self.show_variable_to_plan(&variable),
+            Statement::SetVariable {
+                variable, value, ..
+            } => self.set_variable_to_plan(variable, value),
             Statement::ShowColumns {
                 extended,
                 full,
@@ -177,13 +197,15 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                             set_expr
                         )));
                     }
-                    if !inputs.iter().all(|s| s.schema() == inputs[0].schema()) {
-                        return Err(DataFusionError::Plan(
+                    // the types of the columns may differ, as long as they can
+                    // be coerced to a common type
+                    let schema = union_schema(&inputs).map_err(|_| {
+                        DataFusionError::Plan(
                             "UNION ALL schemas are expected to be the same".to_string(),
-                        ));
-                    }
+                        )
+                    })?;
                     Ok(LogicalPlan::Union {
-                        schema: inputs[0].schema().clone(),
+                        schema,
                         inputs,
                         alias,
                     })
@@ -193,6 +215,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     op
                 ))),
             },
+            SetExpr::Values(values) => self.sql_values_to_plan(&values.0),
             _ => Err(DataFusionError::NotImplemented(format!(
                 "Query {} not implemented yet",
                 set_expr
@@ -200,6 +223,20 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Generate a logical plan from the rows of a VALUES list
+    fn sql_values_to_plan(&self, values: &[Vec<SQLExpr>]) -> Result<LogicalPlan> {
+        let empty_schema = DFSchema::empty();
+        let values = values
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|expr| self.sql_to_rex(expr, &empty_schema))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        LogicalPlanBuilder::values(values)?.build()
+    }
+
     /// Generate a logical plan from a CREATE EXTERNAL TABLE statement
     pub fn external_table_to_plan(
         &self,
@@ -269,6 +306,20 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         })
     }
 
+    /// Generate a plan for EXPLAIN ANALYZE ... that will run the statement
+    /// and print out its plan with the collected metrics
+    pub fn explain_analyze_statement_to_plan(
+        &self,
+        verbose: bool,
+        statement: &Statement,
+    ) -> Result<LogicalPlan> {
+        let plan = self.sql_statement_to_plan(&statement)?;
+
+        LogicalPlanBuilder::from(&plan)
+            .explain_analyze(verbose)?
+            .build()
+    }
+
     fn build_schema(&self, columns: &[SQLColumnDef]) -> Result<Schema> {
         let mut fields = Vec::new();
 
@@ -425,7 +476,10 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<LogicalPlan> {
         match relation {
-            TableFactor::Table { name, .. } => {
+            TableFactor::Table { name, args, .. } if !args.is_empty() => {
+                self.table_function_to_plan(name, args)
+            }
+            TableFactor::Table { name, args, .. } => {
                 let table_name = name.to_string();
                 let cte = ctes.get(&table_name);
                 match (
@@ -436,6 +490,10 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     (_, Some(provider)) => {
                         LogicalPlanBuilder::scan(&table_name, provider, None)?.build()
                     }
+                    // a table function without arguments
+                    (_, None) if self.table_function(name).is_some() => {
+                        self.table_function_to_plan(name, args)
+                    }
                     (_, None) => Err(DataFusionError::Plan(format!(
                         "Table or CTE with name '{}' not found",
                         name
@@ -460,6 +518,38 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Returns the built-in or user-defined table function called `name`
+    fn table_function(&self, name: &ObjectName) -> Option<Arc<TableUDF>> {
+        let name = normalize_function_name(name);
+        udtf::builtin_table_function(&name)
+            .map(Arc::new)
+            .or_else(|| self.schema_provider.get_table_function_meta(&name))
+    }
+
+    /// Generate a logical plan scanning the table returned by a call of a
+    /// table function with literal arguments in the FROM clause
+    fn table_function_to_plan(
+        &self,
+        name: &ObjectName,
+        args: &[FunctionArg],
+    ) -> Result<LogicalPlan> {
+        let fun = self.table_function(name).ok_or_else(|| {
+            DataFusionError::Plan(format!("Invalid table function '{}'", name))
+        })?;
+        let args = args
+            .iter()
+            .map(|arg| match self.sql_fn_arg_to_logical_expr(arg)? {
+                Expr::Literal(value) => Ok(value),
+                other => Err(DataFusionError::Plan(format!(
+                    "The arguments of table function '{}' must be literals, got {:?}",
+                    fun.name, other
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let provider = fun.call(&args)?;
+        LogicalPlanBuilder::scan(&fun.name, provider, None)?.build()
+    }
+
     /// Generate a logic plan from an SQL select
     fn select_to_plan(
         &self,
@@ -468,7 +558,14 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     ) -> Result<LogicalPlan> {
         let plans = self.plan_from_tables(&select.from, ctes)?;
 
-        let plan = match &select.selection {
+        // EXISTS and IN predicates with subqueries are planned as semi or
+        // anti joins once the rest of the WHERE clause is applied
+        let mut subquery_predicates = vec![];
+        let selection = select.selection.as_ref().and_then(|selection| {
+            split_subquery_predicates(selection, &mut subquery_predicates)
+        });
+
+        let plan = match &selection {
             Some(predicate_expr) => {
                 // build join schema
                 let mut fields = vec![];
@@ -537,10 +634,16 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 }
             }
         };
-        let plan = plan?;
+        let mut plan = plan?;
+        for predicate in subquery_predicates {
+            plan = self.subquery_predicate_to_plan(&plan, predicate, ctes)?;
+        }
+
+        // Unnest the list columns passed to `unnest` in the SELECT list.
+        let (plan, projection) = self.plan_unnest(plan, &select.projection)?;
 
         // The SELECT expressions, with wildcards expanded.
-        let select_exprs = self.prepare_select_exprs(&plan, &select.projection)?;
+        let select_exprs = self.prepare_select_exprs(&plan, &projection)?;
 
         // Optionally the HAVING expression.
         let having_expr_opt = select
@@ -645,6 +748,239 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         self.project(&plan, exprs)
     }
 
+    /// Filters `plan` by an `EXISTS`, `NOT EXISTS`, `IN` or `NOT IN`
+    /// predicate with a subquery, planned as a semi join or, if negated, an
+    /// anti join with the subquery.
+    ///
+    /// `x NOT IN (subquery)` is not true if `x` is `NULL`, or if the subquery
+    /// returns a `NULL` value, so such rows are filtered out before and
+    /// after the anti join respectively, see [`Self::not_in_subquery_to_plan`].
+    fn subquery_predicate_to_plan(
+        &self,
+        plan: &LogicalPlan,
+        predicate: &SQLExpr,
+        ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<LogicalPlan> {
+        let (subquery_plan, keys, negated) = match predicate {
+            SQLExpr::Exists(subquery) => {
+                let (subquery_plan, keys) =
+                    self.exists_subquery_to_plan(subquery, plan.schema(), ctes)?;
+                (subquery_plan, keys, false)
+            }
+            SQLExpr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr,
+            } => match expr.as_ref() {
+                SQLExpr::Exists(subquery) => {
+                    let (subquery_plan, keys) =
+                        self.exists_subquery_to_plan(subquery, plan.schema(), ctes)?;
+                    (subquery_plan, keys, true)
+                }
+                _ => {
+                    return Err(DataFusionError::Internal(format!(
+                        "Unexpected subquery predicate {:?}",
+                        predicate
+                    )))
+                }
+            },
+            SQLExpr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => {
+                let column = match self.sql_to_rex(expr, plan.schema())? {
+                    Expr::Column(name) => name,
+                    other => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "IN subqueries are only supported for columns, not {:?}",
+                            other
+                        )))
+                    }
+                };
+                let subquery_plan =
+                    self.query_to_plan_with_alias(subquery, None, &mut ctes.clone())?;
+                let fields = subquery_plan.schema().fields();
+                if fields.len() != 1 {
+                    return Err(DataFusionError::Plan(format!(
+                        "IN subquery must return exactly one column, not {}",
+                        fields.len()
+                    )));
+                }
+                if *negated {
+                    let subquery_column = fields[0].name().clone();
+                    return self.not_in_subquery_to_plan(
+                        plan,
+                        &column,
+                        &subquery_plan,
+                        &subquery_column,
+                    );
+                }
+                let keys = vec![(column, fields[0].name().clone())];
+                (subquery_plan, keys, false)
+            }
+            _ => {
+                return Err(DataFusionError::Internal(format!(
+                    "Unexpected subquery predicate {:?}",
+                    predicate
+                )))
+            }
+        };
+
+        let join_type = if negated {
+            JoinType::Anti
+        } else {
+            JoinType::Semi
+        };
+        let left_keys: Vec<_> = keys.iter().map(|(l, _)| l.as_str()).collect();
+        let right_keys: Vec<_> = keys.iter().map(|(_, r)| r.as_str()).collect();
+        LogicalPlanBuilder::from(plan)
+            .join(&subquery_plan, join_type, &left_keys, &right_keys)?
+            .build()
+    }
+
+    /// Filters `plan` by `column NOT IN (subquery)`, where the subquery
+    /// returns `subquery_column`. Following three-valued logic, rows whose
+    /// `column` is `NULL` are filtered out before the anti join with the
+    /// subquery, and all rows are filtered out if the subquery returns a
+    /// `NULL` value: by another anti join on a constant key with the `NULL`
+    /// values of the subquery.
+    fn not_in_subquery_to_plan(
+        &self,
+        plan: &LogicalPlan,
+        column: &str,
+        subquery_plan: &LogicalPlan,
+        subquery_column: &str,
+    ) -> Result<LogicalPlan> {
+        let columns: Vec<_> = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| col(field.name()))
+            .collect();
+        let nulls = LogicalPlanBuilder::from(subquery_plan)
+            .filter(col(subquery_column).is_null())?
+            .project(vec![lit(true).alias(NOT_IN_NULL_KEY)])?
+            .build()?;
+        LogicalPlanBuilder::from(plan)
+            .filter(col(column).is_not_null())?
+            .join(subquery_plan, JoinType::Anti, &[column], &[subquery_column])?
+            .with_column(NOT_IN_KEY, lit(true))?
+            .join(&nulls, JoinType::Anti, &[NOT_IN_KEY], &[NOT_IN_NULL_KEY])?
+            .project(columns)?
+            .build()
+    }
+
+    /// Plans the subquery of an `EXISTS` predicate, whose WHERE clause
+    /// compares columns of the subquery to columns of `outer_schema` for
+    /// equality. Returns the subquery without these comparisons, and the
+    /// pairs of outer and subquery columns they compare.
+    ///
+    /// Columns may be qualified by the name or alias of a relation. Columns
+    /// qualified by a relation of the subquery are columns of the subquery,
+    /// other qualified columns are columns of the outer query. Unqualified
+    /// columns of the subquery hide columns of the outer query with the same
+    /// name.
+    fn exists_subquery_to_plan(
+        &self,
+        subquery: &Query,
+        outer_schema: &DFSchema,
+        ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<(LogicalPlan, Vec<(String, String)>)> {
+        let select = match &subquery.body {
+            SetExpr::Select(select) => select,
+            _ => {
+                return Err(DataFusionError::NotImplemented(
+                    "EXISTS subqueries are only supported for SELECT queries".to_string(),
+                ))
+            }
+        };
+        if subquery.with.is_some()
+            || subquery.limit.is_some()
+            || !select.group_by.is_empty()
+            || select.having.is_some()
+        {
+            return Err(DataFusionError::NotImplemented(
+                "EXISTS subqueries with WITH, GROUP BY, HAVING or LIMIT clauses \
+                 are not supported"
+                    .to_string(),
+            ));
+        }
+
+        let mut subquery_columns = vec![];
+        for plan in self.plan_from_tables(&select.from, &mut ctes.clone())? {
+            for field in plan.schema().fields() {
+                subquery_columns.push(field.name().clone());
+            }
+        }
+        let subquery_relations = relation_names(&select.from);
+        let is_subquery_relation = |relation: &Option<String>| match relation {
+            Some(relation) => subquery_relations.contains(relation),
+            None => true,
+        };
+        let is_outer_column = |(relation, name): &(Option<String>, String)| {
+            outer_schema.field_with_unqualified_name(name).is_ok()
+                && match relation {
+                    Some(relation) => !subquery_relations.contains(relation),
+                    None => !subquery_columns.contains(name),
+                }
+        };
+        let is_subquery_column = |(relation, name): &(Option<String>, String)| {
+            is_subquery_relation(relation) && subquery_columns.contains(name)
+        };
+
+        let mut predicates = vec![];
+        if let Some(selection) = &select.selection {
+            split_conjunction(selection, &mut predicates);
+        }
+        let mut keys = vec![];
+        let mut selection: Option<SQLExpr> = None;
+        for predicate in predicates {
+            if let SQLExpr::BinaryOp {
+                left,
+                op: BinaryOperator::Eq,
+                right,
+            } = predicate
+            {
+                if let (Some(l), Some(r)) =
+                    (column_reference(left), column_reference(right))
+                {
+                    if is_outer_column(&l) && is_subquery_column(&r) {
+                        keys.push((l.1, r.1));
+                        continue;
+                    }
+                    if is_outer_column(&r) && is_subquery_column(&l) {
+                        keys.push((r.1, l.1));
+                        continue;
+                    }
+                }
+            }
+            selection = Some(match selection {
+                Some(selection) => SQLExpr::BinaryOp {
+                    left: Box::new(selection),
+                    op: BinaryOperator::And,
+                    right: Box::new(predicate.clone()),
+                },
+                None => predicate.clone(),
+            });
+        }
+        if keys.is_empty() {
+            return Err(DataFusionError::NotImplemented(
+                "EXISTS subqueries are only supported if they compare columns of \
+                 the outer query for equality"
+                    .to_string(),
+            ));
+        }
+
+        // all columns are kept for the join keys to be part of the output
+        let select = Select {
+            projection: vec![SelectItem::Wildcard],
+            selection,
+            ..select.as_ref().clone()
+        };
+        let plan = self.select_to_plan(&select, &mut ctes.clone())?;
+        Ok((plan, keys))
+    }
+
     /// Returns the `Expr`'s corresponding to a SQL query's SELECT expressions.
     ///
     /// Wildcards are expanded into the concrete list of columns.
@@ -664,6 +1000,53 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             .collect::<Vec<Expr>>())
     }
 
+    /// Wraps `plan` in an unnest for each `unnest(<column>)` of the SELECT
+    /// expressions, which are replaced by a reference to the unnested column.
+    fn plan_unnest(
+        &self,
+        plan: LogicalPlan,
+        projection: &[SelectItem],
+    ) -> Result<(LogicalPlan, Vec<SelectItem>)> {
+        let mut builder = LogicalPlanBuilder::from(&plan);
+        let projection = projection
+            .iter()
+            .map(|item| {
+                let (expr, alias) = match item {
+                    SelectItem::UnnamedExpr(expr) => (expr, None),
+                    SelectItem::ExprWithAlias { expr, alias } => (expr, Some(alias)),
+                    _ => return Ok(item.clone()),
+                };
+                let function = match expr {
+                    SQLExpr::Function(function)
+                        if function.name.to_string().eq_ignore_ascii_case("unnest") =>
+                    {
+                        function
+                    }
+                    _ => return Ok(item.clone()),
+                };
+                let column = match function.args.as_slice() {
+                    [FunctionArg::Unnamed(SQLExpr::Identifier(ident))] => ident.clone(),
+                    _ => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "Unsupported arguments to unnest: {}",
+                            function
+                        )))
+                    }
+                };
+                builder = builder.unnest(&column.value)?;
+                let expr = SQLExpr::Identifier(column);
+                Ok(match alias {
+                    Some(alias) => SelectItem::ExprWithAlias {
+                        expr,
+                        alias: alias.clone(),
+                    },
+                    None => SelectItem::UnnamedExpr(expr),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((builder.build()?, projection))
+    }
+
     /// Wrap a plan in a projection
     fn project(&self, input: &LogicalPlan, expr: Vec<Expr>) -> Result<LogicalPlan> {
         self.validate_schema_satisfies_exprs(&input.schema(), &expr)?;
@@ -962,6 +1345,27 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 data_type: convert_data_type(data_type)?,
             }),
 
+            SQLExpr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                let mut args = vec![
+                    self.sql_expr_to_logical_expr(expr)?,
+                    match substring_from {
+                        Some(from) => self.sql_expr_to_logical_expr(from)?,
+                        None => lit(1_i64),
+                    },
+                ];
+                if let Some(count) = substring_for {
+                    args.push(self.sql_expr_to_logical_expr(count)?);
+                }
+                Ok(Expr::ScalarFunction {
+                    fun: functions::BuiltinScalarFunction::Substr,
+                    args,
+                })
+            }
+
             SQLExpr::TypedString {
                 ref data_type,
                 ref value,
@@ -1071,19 +1475,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             }
 
             SQLExpr::Function(function) => {
-                let name = if function.name.0.len() > 1 {
-                    // DF doesn't handle compound identifiers
-                    // (e.g. "foo.bar") for function names yet
-                    function.name.to_string()
-                } else {
-                    // if there is a quote style, then don't normalize
-                    // the name, otherwise normalize to lowercase
-                    let ident = &function.name.0[0];
-                    match ident.quote_style {
-                        Some(_) => ident.value.clone(),
-                        None => ident.value.to_ascii_lowercase(),
-                    }
-                };
+                let name = normalize_function_name(&function.name);
+
+                // casts to arrow types that can not be expressed in SQL
+                if name == "arrow_cast" {
+                    let args = self.function_args_to_expr(function)?;
+                    return create_arrow_cast(args);
+                }
 
                 // first, scalar built-in
                 if let Ok(fun) = functions::BuiltinScalarFunction::from_str(&name) {
@@ -1391,6 +1789,39 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    fn set_variable_to_plan(
+        &self,
+        variable: &Ident,
+        value: &[SetVariableValue],
+    ) -> Result<LogicalPlan> {
+        let value = match value {
+            [value] => value,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "SET {} expects exactly one value",
+                    variable
+                )))
+            }
+        };
+        let value = match value {
+            SetVariableValue::Ident(ident) => ident.value.clone(),
+            SetVariableValue::Literal(Value::Number(n, _)) => n.clone(),
+            SetVariableValue::Literal(Value::SingleQuotedString(s)) => s.clone(),
+            SetVariableValue::Literal(Value::Boolean(b)) => b.to_string(),
+            SetVariableValue::Literal(value) => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unsupported value for SET {}: {}",
+                    variable, value
+                )))
+            }
+        };
+        Ok(LogicalPlan::SetVariable {
+            variable: variable.value.clone(),
+            value,
+            schema: Arc::new(DFSchema::empty()),
+        })
+    }
+
     fn show_columns_to_plan(
         &self,
         extended: bool,
@@ -1460,6 +1891,135 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     }
 }
 
+/// Returns the name of a function as it is looked up: if there is a quote
+/// style, the name is not normalized, otherwise it is normalized to lowercase
+fn normalize_function_name(name: &ObjectName) -> String {
+    if name.0.len() > 1 {
+        // DF doesn't handle compound identifiers
+        // (e.g. "foo.bar") for function names yet
+        name.to_string()
+    } else {
+        let ident = &name.0[0];
+        match ident.quote_style {
+            Some(_) => ident.value.clone(),
+            None => ident.value.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Splits the conjunction `expr` into its `EXISTS`, `NOT EXISTS`, `IN` and
+/// `NOT IN` predicates with subqueries, which are added to `subqueries`,
+/// and the conjunction of the remaining predicates, if any
+fn split_subquery_predicates<'a>(
+    expr: &'a SQLExpr,
+    subqueries: &mut Vec<&'a SQLExpr>,
+) -> Option<SQLExpr> {
+    match expr {
+        SQLExpr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let left = split_subquery_predicates(left, subqueries);
+            let right = split_subquery_predicates(right, subqueries);
+            match (left, right) {
+                (Some(left), Some(right)) => Some(SQLExpr::BinaryOp {
+                    left: Box::new(left),
+                    op: BinaryOperator::And,
+                    right: Box::new(right),
+                }),
+                (left, right) => left.or(right),
+            }
+        }
+        SQLExpr::Exists(_) | SQLExpr::InSubquery { .. } => {
+            subqueries.push(expr);
+            None
+        }
+        SQLExpr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr: negated,
+        } if matches!(negated.as_ref(), SQLExpr::Exists(_)) => {
+            subqueries.push(expr);
+            None
+        }
+        other => Some(other.clone()),
+    }
+}
+
+/// Name of the constant key added to the rows of the outer query of a
+/// `NOT IN` subquery, see `SqlToRel::not_in_subquery_to_plan`
+const NOT_IN_KEY: &str = "__not_in_key";
+
+/// Name of the constant key of the `NULL` values of a `NOT IN` subquery
+const NOT_IN_NULL_KEY: &str = "__not_in_null";
+
+/// Returns the relation, if qualified, and the name of the column `expr`
+/// refers to, if it is a column
+fn column_reference(expr: &SQLExpr) -> Option<(Option<String>, String)> {
+    match expr {
+        SQLExpr::Identifier(id) => Some((None, id.value.clone())),
+        SQLExpr::CompoundIdentifier(ids) => match ids.as_slice() {
+            [.., relation, column] => {
+                Some((Some(relation.value.clone()), column.value.clone()))
+            }
+            _ => None,
+        },
+        SQLExpr::Nested(nested) => column_reference(nested),
+        _ => None,
+    }
+}
+
+/// Returns the names and aliases of the relations of a FROM clause, by
+/// which its columns can be qualified
+fn relation_names(from: &[TableWithJoins]) -> Vec<String> {
+    fn add_relation(relation: &TableFactor, names: &mut Vec<String>) {
+        match relation {
+            TableFactor::Table { name, alias, .. } => {
+                if let Some(table) = name.0.last() {
+                    names.push(table.value.clone());
+                }
+                if let Some(alias) = alias {
+                    names.push(alias.name.value.clone());
+                }
+            }
+            TableFactor::Derived {
+                alias: Some(alias), ..
+            } => names.push(alias.name.value.clone()),
+            TableFactor::NestedJoin(nested) => {
+                add_relation(&nested.relation, names);
+                for join in &nested.joins {
+                    add_relation(&join.relation, names);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut names = vec![];
+    for table in from {
+        add_relation(&table.relation, &mut names);
+        for join in &table.joins {
+            add_relation(&join.relation, &mut names);
+        }
+    }
+    names
+}
+
+/// Adds the predicates of the conjunction `expr` to `predicates`
+fn split_conjunction<'a>(expr: &'a SQLExpr, predicates: &mut Vec<&'a SQLExpr>) {
+    match expr {
+        SQLExpr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            split_conjunction(left, predicates);
+            split_conjunction(right, predicates);
+        }
+        SQLExpr::Nested(nested) => split_conjunction(nested, predicates),
+        other => predicates.push(other),
+    }
+}
+
 /// Remove join expressions from a filter expression
 fn remove_join_expressions(
     expr: &Expr,
@@ -2594,6 +3154,26 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn set_variable() {
+        quick_test("SET batch_size = 1024", "SetVariable: batch_size = 1024");
+        quick_test(
+            "SET parquet_pruning TO false",
+            "SetVariable: parquet_pruning = false",
+        );
+        quick_test(
+            "SET local_dirs = '/tmp/a,/tmp/b'",
+            "SetVariable: local_dirs = /tmp/a,/tmp/b",
+        );
+
+        let err = logical_plan("SET batch_size = 1024, 2048")
+            .expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"SET batch_size expects exactly one value\")",
+            format!("{:?}", err)
+        );
+    }
+
     #[test]
     fn create_external_table_csv_no_schema() {
         let sql = "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 'foo.csv'";
@@ -2650,6 +3230,102 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    /// The type of the join at the top of the input of `plan`
+    fn join_type(plan: &LogicalPlan) -> JoinType {
+        match plan.inputs()[0] {
+            LogicalPlan::Join { join_type, .. } => *join_type,
+            other => panic!("expected a join, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn in_subquery() {
+        let sql = "SELECT id FROM person \
+            WHERE id IN (SELECT customer_id FROM orders)";
+        let expected = "Projection: #id\
+            \n  Join: id = customer_id\
+            \n    TableScan: person projection=None\
+            \n    Projection: #customer_id\
+            \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+        assert!(matches!(
+            join_type(&logical_plan(sql).unwrap()),
+            JoinType::Semi
+        ));
+
+        // rows with NULL values are filtered out, and all rows if the
+        // subquery returns a NULL value
+        let sql = "SELECT id FROM person \
+            WHERE id NOT IN (SELECT customer_id FROM orders)";
+        let expected = "Projection: #id\
+            \n  Projection: #id, #first_name, #last_name, #age, #state, #salary, #birth_date, #😀\
+            \n    Join: __not_in_key = __not_in_null\
+            \n      Projection: #id, #first_name, #last_name, #age, #state, #salary, #birth_date, #😀, Boolean(true) AS __not_in_key\
+            \n        Join: id = customer_id\
+            \n          Filter: #id IS NOT NULL\
+            \n            TableScan: person projection=None\
+            \n          Projection: #customer_id\
+            \n            TableScan: orders projection=None\
+            \n      Projection: Boolean(true) AS __not_in_null\
+            \n        Filter: #customer_id IS NULL\
+            \n          Projection: #customer_id\
+            \n            TableScan: orders projection=None";
+        quick_test(sql, expected);
+        let plan = logical_plan(sql).unwrap();
+        assert!(matches!(join_type(plan.inputs()[0]), JoinType::Anti));
+
+        let sql = "SELECT id FROM person \
+            WHERE id IN (SELECT customer_id, order_id FROM orders)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"IN subquery must return exactly one column, not 2\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn exists_correlated_subquery() {
+        let sql = "SELECT id FROM person \
+            WHERE state = 'CO' \
+            AND NOT EXISTS (SELECT 1 FROM orders WHERE customer_id = id AND qty > 1)";
+        let expected = "Projection: #id\
+            \n  Join: id = customer_id\
+            \n    Filter: #state Eq Utf8(\"CO\")\
+            \n      TableScan: person projection=None\
+            \n    Projection: #order_id, #customer_id, #o_item_id, #qty, #price, #delivered\
+            \n      Filter: #qty Gt Int64(1)\
+            \n        TableScan: orders projection=None";
+        quick_test(sql, expected);
+        assert!(matches!(
+            join_type(&logical_plan(sql).unwrap()),
+            JoinType::Anti
+        ));
+
+        let sql = "SELECT id FROM person \
+            WHERE EXISTS (SELECT 1 FROM orders WHERE id = customer_id)";
+        assert!(matches!(
+            join_type(&logical_plan(sql).unwrap()),
+            JoinType::Semi
+        ));
+
+        // columns qualified by a relation of the subquery belong to it
+        let sql = "SELECT id FROM person \
+            WHERE EXISTS (SELECT 1 FROM orders o WHERE o.customer_id = person.id)";
+        let expected = "Projection: #id\
+            \n  Join: id = customer_id\
+            \n    TableScan: person projection=None\
+            \n    Projection: #order_id, #customer_id, #o_item_id, #qty, #price, #delivered\
+            \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn exists_uncorrelated_subquery() {
+        let sql = "SELECT id FROM person WHERE EXISTS (SELECT 1 FROM orders)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(matches!(err, DataFusionError::NotImplemented(_)));
+    }
+
     #[test]
     fn boolean_literal_in_condition_expression() {
         let sql = "SELECT order_id \
@@ -2762,6 +3438,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_arrow_cast() {
+        let sql = "SELECT arrow_cast(age, 'Timestamp(Second, None)') FROM person";
+        let expected = "Projection: CAST(#age AS Timestamp(Second, None))\
+            \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT arrow_cast(age, 'Int') FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Unsupported type 'Int': unknown type 'Int'\")",
+            format!("{:?}", err)
+        );
+    }
+
     #[test]
     fn select_typedstring() {
         let sql = "SELECT date '2020-12-10' AS date FROM person";
@@ -2770,6 +3461,26 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn select_unnest() {
+        let sql = "SELECT id, UNNEST(tags) AS tag FROM posts WHERE id > 1";
+        let expected = "Projection: #id, #tags AS tag\
+            \n  Unnest: #tags\
+            \n    Filter: #id Gt Int64(1)\
+            \n      TableScan: posts projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_unnest_expression() {
+        let sql = "SELECT unnest(tags || tags) FROM posts";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "NotImplemented(\"Unsupported arguments to unnest: unnest(tags || tags)\")",
+            format!("{:?}", err)
+        );
+    }
+
     #[test]
     fn select_multibyte_column() {
         let sql = r#"SELECT "😀" FROM person"#;
@@ -2778,6 +3489,31 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn select_from_table_function() {
+        quick_test(
+            "SELECT * FROM generate_series(1, 10, 2) WHERE generate_series > 3",
+            "Projection: #generate_series\
+             \n  Filter: #generate_series Gt Int64(3)\
+             \n    TableScan: generate_series projection=None",
+        );
+        quick_test(
+            "SELECT range FROM RANGE(-5, 5)",
+            "Projection: #range\
+             \n  TableScan: range projection=None",
+        );
+
+        let err = logical_plan("SELECT * FROM range(1, age)").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("The arguments of table function 'range' must be literals"));
+        let err = logical_plan("SELECT * FROM unknown_function(1)").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Invalid table function 'unknown_function'"
+        );
+    }
+
     fn logical_plan(sql: &str) -> Result<LogicalPlan> {
         let planner = SqlToRel::new(&MockContextProvider {});
         let result = DFParser::parse_sql(&sql);
@@ -2821,6 +3557,18 @@ mod tests {
                     Field::new("price", DataType::Float64, false),
                     Field::new("delivered", DataType::Boolean, false),
                 ])),
+                "posts" => Some(Schema::new(vec![
+                    Field::new("id", DataType::UInt32, false),
+                    Field::new(
+                        "tags",
+                        DataType::List(Box::new(Field::new(
+                            "item",
+                            DataType::Utf8,
+                            true,
+                        ))),
+                        true,
+                    ),
+                ])),
                 "lineitem" => Some(Schema::new(vec![
                     Field::new("l_item_id", DataType::UInt32, false),
                     Field::new("l_description", DataType::Utf8, false),
@@ -2861,6 +3609,10 @@ mod tests {
             }
         }
 
+        fn get_table_function_meta(&self, _name: &str) -> Option<Arc<TableUDF>> {
+            None
+        }
+
         fn get_aggregate_meta(&self, _name: &str) -> Option<Arc<AggregateUDF>> {
             unimplemented!()
         }
@@ -0,0 +1,658 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Converts [`Expr`]s and simple [`LogicalPlan`]s back into SQL strings,
+//! e.g. to push predicates down to an external database.
+//!
+//! Identifiers are always double-quoted, with each part of a qualified table
+//! name quoted separately, and compound expressions are parenthesized, so
+//! that the generated SQL does not depend on the case sensitivity or the
+//! operator precedence of the database executing it.
+
+use std::collections::HashMap;
+
+use arrow::datatypes::{DataType, TimeUnit};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::{Expr, LogicalPlan, Operator};
+use crate::physical_plan::functions::BuiltinScalarFunction;
+use crate::scalar::ScalarValue;
+
+/// Converts an [`Expr`] into a SQL expression string.
+///
+/// ```
+/// # use datafusion::logical_plan::{col, lit};
+/// # use datafusion::sql::unparser::expr_to_sql;
+/// let expr = col("a").gt(lit(5)).and(col("b").is_null());
+/// assert_eq!(
+///     expr_to_sql(&expr).unwrap(),
+///     r#"("a" > 5) AND ("b" IS NULL)"#
+/// );
+/// ```
+pub fn expr_to_sql(expr: &Expr) -> Result<String> {
+    to_sql(expr, &HashMap::new())
+}
+
+/// Converts a [`LogicalPlan`] into a single SQL `SELECT` statement.
+///
+/// Only plans made of a table scan (or an empty relation) followed by at
+/// most one of each filter, aggregate, filter on the aggregate, projection,
+/// sort and limit, in that order, are supported.
+pub fn plan_to_sql(plan: &LogicalPlan) -> Result<String> {
+    Ok(select(plan)?.to_string())
+}
+
+/// The clauses of a `SELECT` statement, built from the bottom of a plan up
+#[derive(Default)]
+struct Select {
+    projection: Option<Vec<String>>,
+    projected: bool,
+    from: Option<String>,
+    selection: Vec<String>,
+    aggregated: bool,
+    group_by: Vec<String>,
+    having: Vec<String>,
+    order_by: Vec<String>,
+    limit: Option<usize>,
+    /// SQL for the output columns of an aggregate or projection, by name
+    columns: HashMap<String, String>,
+}
+
+impl std::fmt::Display for Select {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.projection {
+            Some(projection) => write!(f, "SELECT {}", projection.join(", "))?,
+            None => write!(f, "SELECT *")?,
+        }
+        if let Some(from) = &self.from {
+            write!(f, " FROM {}", from)?;
+        }
+        if !self.selection.is_empty() {
+            write!(f, " WHERE {}", self.selection.join(" AND "))?;
+        }
+        if !self.group_by.is_empty() {
+            write!(f, " GROUP BY {}", self.group_by.join(", "))?;
+        }
+        if !self.having.is_empty() {
+            write!(f, " HAVING {}", self.having.join(" AND "))?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, " ORDER BY {}", self.order_by.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+fn select(plan: &LogicalPlan) -> Result<Select> {
+    let unsupported = || {
+        Err(DataFusionError::NotImplemented(format!(
+            "Unsupported logical plan for conversion to SQL: {}",
+            plan.display()
+        )))
+    };
+
+    match plan {
+        LogicalPlan::TableScan {
+            table_name,
+            projection,
+            projected_schema,
+            filters,
+            limit,
+            ..
+        } => Ok(Select {
+            projection: projection.as_ref().map(|_| {
+                projected_schema
+                    .fields()
+                    .iter()
+                    .map(|field| quote_identifier(field.name()))
+                    .collect()
+            }),
+            from: Some(
+                table_name
+                    .split('.')
+                    .map(quote_identifier)
+                    .collect::<Vec<_>>()
+                    .join("."),
+            ),
+            selection: filters
+                .iter()
+                .map(|filter| nested_to_sql(filter, &HashMap::new()))
+                .collect::<Result<_>>()?,
+            limit: *limit,
+            ..Default::default()
+        }),
+        LogicalPlan::EmptyRelation {
+            produce_one_row, ..
+        } => Ok(Select {
+            selection: if *produce_one_row {
+                vec![]
+            } else {
+                vec!["FALSE".to_string()]
+            },
+            ..Default::default()
+        }),
+        LogicalPlan::Filter { predicate, input } => {
+            let mut select = select(input)?;
+            if select.projected || select.limit.is_some() || !select.order_by.is_empty() {
+                return unsupported();
+            }
+            let predicate = nested_to_sql(predicate, &select.columns)?;
+            if select.aggregated {
+                select.having.push(predicate);
+            } else {
+                select.selection.push(predicate);
+            }
+            Ok(select)
+        }
+        LogicalPlan::Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        } => {
+            let mut select = select(input)?;
+            if select.projected
+                || select.aggregated
+                || select.limit.is_some()
+                || !select.order_by.is_empty()
+            {
+                return unsupported();
+            }
+            select.group_by = group_expr
+                .iter()
+                .map(|e| to_sql(e, &select.columns))
+                .collect::<Result<_>>()?;
+            let mut projection = vec![];
+            for e in group_expr.iter().chain(aggr_expr) {
+                let sql = to_sql(e, &select.columns)?;
+                select.columns.insert(e.name(input.schema())?, sql.clone());
+                projection.push(sql);
+            }
+            select.projection = Some(projection);
+            select.aggregated = true;
+            Ok(select)
+        }
+        LogicalPlan::Projection { expr, input, .. } => {
+            let mut select = select(input)?;
+            if select.projected || select.limit.is_some() {
+                return unsupported();
+            }
+            // later clauses can refer to aliased expressions by their alias
+            let mut projection = vec![];
+            let mut columns = HashMap::new();
+            for e in expr {
+                let sql = to_sql(e, &select.columns)?;
+                let column = match e {
+                    Expr::Alias(_, name) => quote_identifier(name),
+                    _ => sql.clone(),
+                };
+                columns.insert(e.name(input.schema())?, column);
+                projection.push(sql);
+            }
+            select.projection = Some(projection);
+            select.columns = columns;
+            select.projected = true;
+            Ok(select)
+        }
+        LogicalPlan::Sort { expr, input } => {
+            let mut select = select(input)?;
+            if select.limit.is_some() {
+                return unsupported();
+            }
+            select.order_by = expr
+                .iter()
+                .map(|e| to_sql(e, &select.columns))
+                .collect::<Result<_>>()?;
+            Ok(select)
+        }
+        LogicalPlan::Limit { n, input } => {
+            let mut select = select(input)?;
+            select.limit = Some(select.limit.map_or(*n, |limit| limit.min(*n)));
+            Ok(select)
+        }
+        _ => unsupported(),
+    }
+}
+
+/// Converts `expr` to SQL, replacing the columns found in `columns`
+fn to_sql(expr: &Expr, columns: &HashMap<String, String>) -> Result<String> {
+    let list = |exprs: &[Expr]| {
+        exprs
+            .iter()
+            .map(|e| to_sql(e, columns))
+            .collect::<Result<Vec<_>>>()
+            .map(|args| args.join(", "))
+    };
+
+    Ok(match expr {
+        Expr::Alias(e, name) => {
+            format!("{} AS {}", to_sql(e, columns)?, quote_identifier(name))
+        }
+        Expr::Column(name) => match columns.get(name) {
+            Some(sql) => sql.clone(),
+            None => quote_identifier(name),
+        },
+        Expr::ScalarVariable(names) => names.join("."),
+        Expr::Literal(value) => literal_to_sql(value)?,
+        Expr::BinaryExpr { left, op, right } => {
+            let op = match op {
+                Operator::NotEq => "<>".to_string(),
+                op => op.to_string(),
+            };
+            format!(
+                "{} {} {}",
+                nested_to_sql(left, columns)?,
+                op,
+                nested_to_sql(right, columns)?
+            )
+        }
+        Expr::Not(e) => format!("NOT {}", nested_to_sql(e, columns)?),
+        Expr::IsNotNull(e) => format!("{} IS NOT NULL", nested_to_sql(e, columns)?),
+        Expr::IsNull(e) => format!("{} IS NULL", nested_to_sql(e, columns)?),
+        Expr::Negative(e) => format!("-({})", to_sql(e, columns)?),
+        Expr::Between {
+            expr,
+            negated,
+            low,
+            high,
+        } => format!(
+            "{} {}BETWEEN {} AND {}",
+            nested_to_sql(expr, columns)?,
+            if *negated { "NOT " } else { "" },
+            nested_to_sql(low, columns)?,
+            nested_to_sql(high, columns)?
+        ),
+        Expr::Case {
+            expr,
+            when_then_expr,
+            else_expr,
+        } => {
+            let mut sql = "CASE".to_string();
+            if let Some(e) = expr {
+                sql += &format!(" {}", nested_to_sql(e, columns)?);
+            }
+            for (when, then) in when_then_expr {
+                sql += &format!(
+                    " WHEN {} THEN {}",
+                    nested_to_sql(when, columns)?,
+                    nested_to_sql(then, columns)?
+                );
+            }
+            if let Some(e) = else_expr {
+                sql += &format!(" ELSE {}", nested_to_sql(e, columns)?);
+            }
+            sql + " END"
+        }
+        Expr::Cast { expr, data_type } => format!(
+            "CAST({} AS {})",
+            to_sql(expr, columns)?,
+            data_type_to_sql(data_type)?
+        ),
+        Expr::TryCast { expr, data_type } => format!(
+            "TRY_CAST({} AS {})",
+            to_sql(expr, columns)?,
+            data_type_to_sql(data_type)?
+        ),
+        Expr::Sort {
+            expr,
+            asc,
+            nulls_first,
+        } => format!(
+            "{} {} {}",
+            nested_to_sql(expr, columns)?,
+            if *asc { "ASC" } else { "DESC" },
+            if *nulls_first {
+                "NULLS FIRST"
+            } else {
+                "NULLS LAST"
+            }
+        ),
+        Expr::ScalarFunction { fun, args } => {
+            format!("{}({})", function_name(fun)?, list(args)?)
+        }
+        Expr::ScalarUDF { fun, args } => format!("{}({})", fun.name, list(args)?),
+        Expr::AggregateFunction {
+            fun,
+            args,
+            distinct,
+        } => format!(
+            "{}({}{})",
+            fun,
+            if *distinct { "DISTINCT " } else { "" },
+            list(args)?
+        ),
+        Expr::WindowFunction { fun, args } => {
+            format!("{}({}) OVER ()", fun, list(args)?)
+        }
+        Expr::AggregateUDF { fun, args } => format!("{}({})", fun.name, list(args)?),
+        Expr::InList {
+            expr,
+            list: values,
+            negated,
+        } => format!(
+            "{} {}IN ({})",
+            nested_to_sql(expr, columns)?,
+            if *negated { "NOT " } else { "" },
+            list(values)?
+        ),
+        Expr::Wildcard => "*".to_string(),
+    })
+}
+
+/// Converts `expr` to SQL, in parentheses unless it is a single term
+fn nested_to_sql(expr: &Expr, columns: &HashMap<String, String>) -> Result<String> {
+    let sql = to_sql(expr, columns)?;
+    Ok(match expr {
+        Expr::Column(_)
+        | Expr::ScalarVariable(_)
+        | Expr::Literal(_)
+        | Expr::Case { .. }
+        | Expr::Cast { .. }
+        | Expr::TryCast { .. }
+        | Expr::ScalarFunction { .. }
+        | Expr::ScalarUDF { .. }
+        | Expr::AggregateFunction { .. }
+        | Expr::AggregateUDF { .. }
+        | Expr::Wildcard => sql,
+        _ => format!("({})", sql),
+    })
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Returns the SQL name of a built-in scalar function, which is the snake
+/// case of its variant for all functions but a few
+fn function_name(fun: &BuiltinScalarFunction) -> Result<String> {
+    if let BuiltinScalarFunction::ConcatWithSeparator = fun {
+        return Ok("concat_ws".to_string());
+    }
+    let debug = format!("{:?}", fun);
+    let mut name = String::new();
+    let mut previous_lowercase = false;
+    for c in debug.chars() {
+        if c.is_ascii_uppercase() && previous_lowercase {
+            name.push('_');
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        name.push(c.to_ascii_lowercase());
+    }
+    match name.parse::<BuiltinScalarFunction>() {
+        Ok(parsed) if &parsed == fun => Ok(name),
+        _ => match debug.to_lowercase().parse::<BuiltinScalarFunction>() {
+            Ok(parsed) if &parsed == fun => Ok(debug.to_lowercase()),
+            _ => Err(DataFusionError::NotImplemented(format!(
+                "Unsupported function for conversion to SQL: {}",
+                debug
+            ))),
+        },
+    }
+}
+
+fn data_type_to_sql(data_type: &DataType) -> Result<&'static str> {
+    Ok(match data_type {
+        DataType::Boolean => "BOOLEAN",
+        DataType::Int16 => "SMALLINT",
+        DataType::Int32 => "INT",
+        DataType::Int64 => "BIGINT",
+        DataType::Float32 => "REAL",
+        DataType::Float64 => "DOUBLE",
+        DataType::Utf8 => "VARCHAR",
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => "TIMESTAMP",
+        DataType::Date32 => "DATE",
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported data type for conversion to SQL: {:?}",
+                data_type
+            )))
+        }
+    })
+}
+
+fn literal_to_sql(value: &ScalarValue) -> Result<String> {
+    let unsupported = || {
+        Err(DataFusionError::NotImplemented(format!(
+            "Unsupported literal for conversion to SQL: {:?}",
+            value
+        )))
+    };
+
+    if value.is_null() {
+        return Ok(match data_type_to_sql(&value.get_datatype()) {
+            Ok(data_type) => format!("CAST(NULL AS {})", data_type),
+            Err(_) => "NULL".to_string(),
+        });
+    }
+
+    Ok(match value {
+        ScalarValue::Boolean(Some(v)) => if *v { "TRUE" } else { "FALSE" }.to_string(),
+        ScalarValue::Int8(Some(v)) => v.to_string(),
+        ScalarValue::Int16(Some(v)) => v.to_string(),
+        ScalarValue::Int32(Some(v)) => v.to_string(),
+        ScalarValue::Int64(Some(v)) => v.to_string(),
+        ScalarValue::UInt8(Some(v)) => v.to_string(),
+        ScalarValue::UInt16(Some(v)) => v.to_string(),
+        ScalarValue::UInt32(Some(v)) => v.to_string(),
+        ScalarValue::UInt64(Some(v)) => v.to_string(),
+        ScalarValue::Float32(Some(v)) if v.is_finite() => float_to_sql(v.to_string()),
+        ScalarValue::Float64(Some(v)) if v.is_finite() => float_to_sql(v.to_string()),
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => quote_string(v),
+        ScalarValue::Date32(Some(v)) => {
+            let date = NaiveDate::from_ymd(1970, 1, 1) + Duration::days(i64::from(*v));
+            format!("CAST('{}' AS DATE)", date)
+        }
+        ScalarValue::Date64(Some(v)) => {
+            let date = NaiveDate::from_ymd(1970, 1, 1)
+                + Duration::days(v.div_euclid(86_400_000));
+            format!("CAST('{}' AS DATE)", date)
+        }
+        ScalarValue::TimestampSecond(Some(v)) => timestamp_to_sql(*v, 1_000_000_000),
+        ScalarValue::TimestampMillisecond(Some(v)) => timestamp_to_sql(*v, 1_000_000),
+        ScalarValue::TimestampMicrosecond(Some(v)) => timestamp_to_sql(*v, 1_000),
+        ScalarValue::TimestampNanosecond(Some(v)) => timestamp_to_sql(*v, 1),
+        _ => return unsupported(),
+    })
+}
+
+/// Makes sure a float literal is not parsed as an integer
+fn float_to_sql(value: String) -> String {
+    if value.contains('.') {
+        value
+    } else {
+        value + ".0"
+    }
+}
+
+fn timestamp_to_sql(value: i64, nanos_per_unit: i64) -> String {
+    let nanos_per_second = 1_000_000_000 / nanos_per_unit;
+    let timestamp = NaiveDateTime::from_timestamp(
+        value.div_euclid(nanos_per_second),
+        (value.rem_euclid(nanos_per_second) * nanos_per_unit) as u32,
+    );
+    format!(
+        "CAST('{}' AS TIMESTAMP)",
+        timestamp.format("%Y-%m-%dT%H:%M:%S%.f")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::empty::EmptyTable;
+    use crate::execution::context::ExecutionContext;
+    use crate::logical_plan::{col, in_list, lit, when, LogicalPlanBuilder};
+    use crate::test::test_table_scan;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn expr_to_sql_operators() -> Result<()> {
+        let expr = col("a")
+            .not_eq(lit("it's"))
+            .or(col("b").lt(lit(1.0)).and(col("c").is_not_null().not()));
+        assert_eq!(
+            expr_to_sql(&expr)?,
+            r#"("a" <> 'it''s') OR (("b" < 1.0) AND (NOT ("c" IS NOT NULL)))"#
+        );
+
+        let expr = Expr::Between {
+            expr: Box::new(Expr::Negative(Box::new(col("a")))),
+            negated: true,
+            low: Box::new(lit(-1)),
+            high: Box::new(col("b") + lit(2)),
+        };
+        assert_eq!(
+            expr_to_sql(&expr)?,
+            r#"(-("a")) NOT BETWEEN -1 AND ("b" + 2)"#
+        );
+
+        let expr = in_list(col("a"), vec![lit(1), lit(2)], false).alias("a in list");
+        assert_eq!(expr_to_sql(&expr)?, r#""a" IN (1, 2) AS "a in list""#);
+
+        let expr = col("a").sort(false, true);
+        assert_eq!(expr_to_sql(&expr)?, r#""a" DESC NULLS FIRST"#);
+        Ok(())
+    }
+
+    #[test]
+    fn expr_to_sql_functions() -> Result<()> {
+        let expr = when(col("a").eq(lit(1)), lit("one")).otherwise(Expr::Cast {
+            expr: Box::new(col("a")),
+            data_type: DataType::Utf8,
+        })?;
+        assert_eq!(
+            expr_to_sql(&expr)?,
+            r#"CASE WHEN ("a" = 1) THEN 'one' ELSE CAST("a" AS VARCHAR) END"#
+        );
+
+        let functions = vec![
+            (BuiltinScalarFunction::CharacterLength, "character_length"),
+            (BuiltinScalarFunction::ConcatWithSeparator, "concat_ws"),
+            (BuiltinScalarFunction::MD5, "md5"),
+            (BuiltinScalarFunction::Log10, "log10"),
+            (BuiltinScalarFunction::ToTimestamp, "to_timestamp"),
+        ];
+        for (fun, name) in functions {
+            let expr = Expr::ScalarFunction {
+                fun,
+                args: vec![col("a"), lit("b")],
+            };
+            assert_eq!(expr_to_sql(&expr)?, format!(r#"{}("a", 'b')"#, name));
+        }
+
+        let expr = Expr::AggregateFunction {
+            fun: crate::physical_plan::aggregates::AggregateFunction::Count,
+            args: vec![col("a")],
+            distinct: true,
+        };
+        assert_eq!(expr_to_sql(&expr)?, r#"COUNT(DISTINCT "a")"#);
+        Ok(())
+    }
+
+    #[test]
+    fn expr_to_sql_literals() -> Result<()> {
+        let literals = vec![
+            (ScalarValue::Boolean(Some(true)), "TRUE"),
+            (ScalarValue::Float64(Some(1.0)), "1.0"),
+            (ScalarValue::Float32(Some(-0.5)), "-0.5"),
+            (ScalarValue::UInt64(Some(u64::MAX)), "18446744073709551615"),
+            (ScalarValue::Int64(None), "CAST(NULL AS BIGINT)"),
+            (ScalarValue::UInt8(None), "NULL"),
+            (
+                ScalarValue::Date32(Some(18628)),
+                "CAST('2021-01-01' AS DATE)",
+            ),
+            (
+                ScalarValue::TimestampMillisecond(Some(1_609_459_200_123)),
+                "CAST('2021-01-01T00:00:00.123' AS TIMESTAMP)",
+            ),
+            (
+                ScalarValue::TimestampSecond(Some(-1)),
+                "CAST('1969-12-31T23:59:59' AS TIMESTAMP)",
+            ),
+        ];
+        for (value, expected) in literals {
+            assert_eq!(expr_to_sql(&Expr::Literal(value))?, expected);
+        }
+
+        let err = expr_to_sql(&lit(ScalarValue::Float64(Some(f64::NAN)))).unwrap_err();
+        assert!(matches!(err, DataFusionError::NotImplemented(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn plan_to_sql_round_trip() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, false),
+            Field::new("c", DataType::Int64, false),
+        ]));
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", Arc::new(EmptyTable::new(schema)))?;
+
+        let plan = ctx.create_logical_plan(
+            "SELECT b, SUM(c) AS total FROM t WHERE a > 1 \
+             GROUP BY b HAVING SUM(c) > 10 ORDER BY total DESC LIMIT 3",
+        )?;
+        let sql = plan_to_sql(&plan)?;
+        assert_eq!(
+            sql,
+            r#"SELECT "b", SUM("c") AS "total" FROM "t" WHERE ("a" > 1) GROUP BY "b" HAVING (SUM("c") > 10) ORDER BY "total" DESC NULLS FIRST LIMIT 3"#
+        );
+
+        let field_names = |plan: &LogicalPlan| {
+            plan.schema()
+                .fields()
+                .iter()
+                .map(|f| (f.name().clone(), f.data_type().clone()))
+                .collect::<Vec<_>>()
+        };
+        let round_trip = ctx.create_logical_plan(&sql)?;
+        assert_eq!(field_names(&round_trip), field_names(&plan));
+        Ok(())
+    }
+
+    #[test]
+    fn plan_to_sql_builder() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(&test_table_scan()?)
+            .filter(col("a").eq(lit(1_u32)))?
+            .project(vec![col("b"), col("c")])?
+            .limit(10)?
+            .build()?;
+        assert_eq!(
+            plan_to_sql(&plan)?,
+            r#"SELECT "b", "c" FROM "test" WHERE ("a" = 1) LIMIT 10"#
+        );
+
+        // a filter on top of a limit needs a subquery
+        let plan = LogicalPlanBuilder::from(&plan)
+            .filter(col("b").eq(lit(1_u32)))?
+            .build()?;
+        let err = plan_to_sql(&plan).unwrap_err();
+        assert!(matches!(err, DataFusionError::NotImplemented(_)));
+        Ok(())
+    }
+}
@@ -15,16 +15,26 @@
 // specific language governing permissions and limitations
 // under the License.
 
-//! Simple iterator over batches for use in testing
+//! Simple iterator over batches and execution plans for use in testing
 
-use std::task::{Context, Poll};
+use std::{
+    any::Any,
+    pin::Pin,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+};
 
 use arrow::{
     datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch,
 };
+use async_trait::async_trait;
 use futures::Stream;
 
-use crate::physical_plan::RecordBatchStream;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
+    SendableRecordBatchStream,
+};
 
 /// Index into the data that has been returned so far
 #[derive(Debug, Default, Clone)]
@@ -100,3 +110,121 @@ impl RecordBatchStream for TestStream {
         self.data[0].schema()
     }
 }
+
+/// Execution plan whose partitions never produce a batch. Used to check that
+/// operators release their inputs, see [`assert_strong_count_converges_to_zero`]
+#[derive(Debug)]
+pub struct BlockingExec {
+    /// Schema that is mocked by this plan
+    schema: SchemaRef,
+    /// Number of output partitions
+    n_partitions: usize,
+    /// Ref-counting helper to check if the plan and the produced streams are
+    /// still in memory
+    refs: Arc<()>,
+}
+
+impl BlockingExec {
+    /// Create a new exec with the given schema and number of partitions
+    pub fn new(schema: SchemaRef, n_partitions: usize) -> Self {
+        Self {
+            schema,
+            n_partitions,
+            refs: Default::default(),
+        }
+    }
+
+    /// Weak pointer that can be used for ref-counting this exec and its streams
+    pub fn refs(&self) -> Weak<()> {
+        Arc::downgrade(&self.refs)
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for BlockingExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(self.n_partitions)
+    }
+
+    fn with_new_children(
+        &self,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Internal(format!(
+            "Children cannot be replaced in {:?}",
+            self
+        )))
+    }
+
+    async fn execute(&self, _partition: usize) -> Result<SendableRecordBatchStream> {
+        Ok(Box::pin(BlockingStream {
+            schema: self.schema.clone(),
+            _refs: self.refs.clone(),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "BlockingExec",)
+            }
+        }
+    }
+}
+
+/// Stream that never produces a batch, see [`BlockingExec`]
+#[derive(Debug)]
+pub struct BlockingStream {
+    /// Schema mocked by this stream
+    schema: SchemaRef,
+    /// Ref-counting helper to check if the stream is still in memory
+    _refs: Arc<()>,
+}
+
+impl Stream for BlockingStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        Poll::Pending
+    }
+}
+
+impl RecordBatchStream for BlockingStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Asserts that the strong count of `refs` drops to zero, allowing the
+/// runtime some time to abort and drop spawned tasks
+pub async fn assert_strong_count_converges_to_zero<T>(refs: Weak<T>) {
+    tokio::time::timeout(std::time::Duration::from_secs(10), async {
+        loop {
+            if Weak::strong_count(&refs) == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
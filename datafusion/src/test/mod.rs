@@ -91,6 +91,23 @@ pub fn create_partitioned_csv(filename: &str, partitions: usize) -> Result<Strin
     Ok(tmp_dir.into_path().to_str().unwrap().to_string())
 }
 
+/// Writes CSV files with the columns `a` and `b` into the Hive-style
+/// partitions `year=2020/month=01` (2 rows), `year=2020/month=02` and
+/// `year=2021/month=01` (1 row each) in `dir`
+pub fn create_hive_partitioned_csv(dir: &std::path::Path) -> Result<()> {
+    let partitions = [
+        ("year=2020/month=01", "a,b\n1,x\n2,y\n"),
+        ("year=2020/month=02", "a,b\n3,z\n"),
+        ("year=2021/month=01", "a,b\n4,w\n"),
+    ];
+    for (partition, data) in partitions.iter() {
+        let partition = dir.join(partition);
+        std::fs::create_dir_all(&partition)?;
+        std::fs::write(partition.join("0.csv"), data)?;
+    }
+    Ok(())
+}
+
 /// Get the schema for the aggregate_test_* csv files
 pub fn aggr_test_schema() -> SchemaRef {
     Arc::new(Schema::new(vec![
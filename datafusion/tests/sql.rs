@@ -41,7 +41,10 @@ use datafusion::{
     error::{DataFusionError, Result},
     physical_plan::ColumnarValue,
 };
-use datafusion::{execution::context::ExecutionContext, physical_plan::displayable};
+use datafusion::{
+    execution::context::{ExecutionContext, TimeSource},
+    physical_plan::displayable,
+};
 
 #[tokio::test]
 async fn nyc() -> Result<()> {
@@ -916,6 +919,20 @@ async fn union_all() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn union_all_coerced_types() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT CAST(1 AS INT) as x UNION ALL SELECT 2.5 as x ORDER BY x";
+    let plan = ctx.create_logical_plan(sql)?;
+    let field = plan.schema().field_with_unqualified_name("x")?;
+    assert_eq!(field.data_type(), &DataType::Float64);
+
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["1"], vec!["2.5"]];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn csv_union_all() -> Result<()> {
     let mut ctx = ExecutionContext::new();
@@ -1327,6 +1344,33 @@ async fn equijoin() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn exists_and_in_subqueries() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id FROM t1 WHERE t1_id IN (SELECT t2_id FROM t2) ORDER BY t1_id";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["11"], vec!["22"], vec!["44"]];
+    assert_eq!(expected, actual);
+
+    let sql = "SELECT t1_id, t1_name FROM t1 \
+               WHERE NOT EXISTS (SELECT * FROM t2 WHERE t2_id = t1_id AND t2_name <> 'x') \
+               ORDER BY t1_id";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["33", "c"], vec!["44", "d"]];
+    assert_eq!(expected, actual);
+
+    let sql = "SELECT t1_id FROM t1 WHERE t1_id NOT IN (SELECT t2_id FROM t2)";
+    let actual = execute(&mut ctx, sql).await;
+    assert_eq!(vec![vec!["33"]], actual);
+
+    // no value is NOT IN a subquery returning NULL
+    let sql = "SELECT t1_id FROM t1 WHERE t1_id NOT IN \
+               (SELECT t2_id FROM t1 LEFT JOIN t2 ON t1_id = t2_id)";
+    let actual = execute(&mut ctx, sql).await;
+    assert!(actual.is_empty(), "unexpected rows {:?}", actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn left_join() -> Result<()> {
     let mut ctx = create_join_context("t1_id", "t2_id")?;
@@ -1620,6 +1664,70 @@ async fn csv_explain_verbose() {
     assert!(actual.contains("#c2 Gt Int64(10)"), "Actual: '{}'", actual);
 }
 
+#[tokio::test]
+async fn csv_explain_analyze() {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_csv_by_sql(&mut ctx).await;
+    let sql = "EXPLAIN ANALYZE SELECT count(*), c1 FROM aggregate_test_100 \
+               GROUP BY c1 ORDER BY c1";
+    let actual = execute(&mut ctx, sql).await;
+
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0][0], "Plan with Metrics");
+    let plan = &actual[0][1];
+    assert!(plan.starts_with("SortExec: "), "Actual: '{}'", plan);
+    // the sort produced one row per group
+    assert!(
+        plan.lines().next().unwrap().contains("output_rows=5"),
+        "Actual: '{}'",
+        plan
+    );
+    assert!(plan.contains("HashAggregateExec"), "Actual: '{}'", plan);
+}
+
+#[tokio::test]
+async fn query_values() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT column1, column2 FROM (VALUES (1, 'a'), (2, NULL), (3, 'c')) AS t \
+               WHERE column1 > 1 ORDER BY column1";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["2", "NULL"], vec!["3", "c"]];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_monotonic_id() -> Result<()> {
+    // one partition per input partition, so that the partition indices are known
+    let mut ctx =
+        ExecutionContext::with_config(ExecutionConfig::new().with_concurrency(2));
+    let schema = Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, false)]));
+    let batch = |values: Vec<i32>| {
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+    };
+    let table = MemTable::try_new(
+        schema.clone(),
+        vec![
+            vec![batch(vec![1, 2])?, batch(vec![3])?],
+            vec![batch(vec![4, 5])?],
+        ],
+    )?;
+    ctx.register_table("t", Arc::new(table))?;
+
+    // the partition index is stored above the low 33 bits of the ids
+    let sql = "SELECT c1, monotonic_id() FROM t ORDER BY c1";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![
+        vec!["1", "0"],
+        vec!["2", "1"],
+        vec!["3", "2"],
+        vec!["4", "8589934592"],
+        vec!["5", "8589934593"],
+    ];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
 fn aggr_test_schema() -> SchemaRef {
     Arc::new(Schema::new(vec![
         Field::new("c1", DataType::Utf8, false),
@@ -1895,6 +2003,123 @@ async fn query_array() -> Result<()> {
         vec!["[aaa,3]"],
     ];
     assert_eq!(expected, actual);
+
+    let sql = "SELECT make_array(c2, c2 + 1) FROM test WHERE c2 IS NOT NULL";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["[0, 1]"], vec!["[1, 2]"], vec!["[3, 4]"]];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_map() -> Result<()> {
+    let key = Field::new("key", DataType::Utf8, false);
+    let value = Field::new("value", DataType::Int64, true);
+    let entries = StructArray::from(vec![
+        (
+            key.clone(),
+            Arc::new(StringArray::from(vec!["a", "b", "a"])) as ArrayRef,
+        ),
+        (
+            value.clone(),
+            Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+        ),
+    ]);
+    let map_type = DataType::List(Box::new(Field::new(
+        "entries",
+        DataType::Struct(vec![key, value]),
+        false,
+    )));
+    let data = ArrayData::builder(map_type.clone())
+        .len(3)
+        .add_buffer(arrow::buffer::Buffer::from_slice_ref(&[0i32, 2, 3, 3]))
+        .add_child_data(entries.data().clone())
+        .build();
+    let schema = Arc::new(Schema::new(vec![Field::new("c1", map_type, true)]));
+    let data = RecordBatch::try_new(schema.clone(), vec![make_array(data)])?;
+
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("test", Arc::new(table))?;
+    let sql = "SELECT map_keys(c1), map_values(c1), element_at(c1, 'b') FROM test";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![
+        vec!["[a, b]", "[1, 2]", "2"],
+        vec!["[a]", "[3]", "NULL"],
+        vec!["[]", "[]", "NULL"],
+    ];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_short_circuit() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)]));
+
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int32Array::from(vec![
+            Some(0),
+            Some(1),
+            None,
+            Some(3),
+        ]))],
+    )?;
+
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("test", Arc::new(table))?;
+
+    // the division is not evaluated for the rows where c1 is zero
+    let sql = "SELECT CASE WHEN c1 = 0 THEN -1 ELSE 3 / c1 END FROM test";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["-1"], vec!["3"], vec!["NULL"], vec!["1"]];
+    assert_eq!(expected, actual);
+
+    let sql = "SELECT c1 <> 0 AND 3 / c1 > 1 FROM test";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["false"], vec!["true"], vec!["NULL"], vec!["false"]];
+    assert_eq!(expected, actual);
+
+    let sql = "SELECT COALESCE(c1, 10) FROM test";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["0"], vec!["1"], vec!["10"], vec!["3"]];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_null_handling_functions() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)]));
+
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int32Array::from(vec![
+            Some(0),
+            Some(1),
+            None,
+            Some(3),
+        ]))],
+    )?;
+
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("test", Arc::new(table))?;
+
+    // ifnull is an alias of nvl, so it needs a different column name
+    let sql = "SELECT 3 / nullif(c1, 0), nvl(c1, 10), ifnull(c1, 10) AS ifnull, \
+               nvl2(c1, 'set', 'unset') FROM test";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![
+        vec!["NULL", "0", "0", "set"],
+        vec!["3", "1", "1", "set"],
+        vec!["NULL", "10", "10", "unset"],
+        vec!["1", "3", "3", "set"],
+    ];
+    assert_eq!(expected, actual);
     Ok(())
 }
 
@@ -2375,6 +2600,26 @@ macro_rules! test_expression {
     };
 }
 
+#[tokio::test]
+async fn test_math_expressions() -> Result<()> {
+    test_expression!("power(2, 10)", "1024");
+    test_expression!("power(CAST(NULL AS double), 2)", "NULL");
+    test_expression!("atan2(1, 1)", "0.7853981633974483");
+    test_expression!("log(2, 8)", "3");
+    test_expression!("log(100)", "2");
+    test_expression!("cbrt(27)", "3");
+    test_expression!("pi()", "3.141592653589793");
+    test_expression!("degrees(pi())", "180");
+    test_expression!("radians(180)", "3.141592653589793");
+    test_expression!("trunc(3.789, 1)", "3.7");
+    test_expression!("trunc(3.789)", "3");
+    test_expression!("factorial(5)", "120");
+    test_expression!("gcd(12, 18)", "6");
+    test_expression!("lcm(4, 6)", "12");
+    test_expression!("lcm(4, CAST(NULL AS bigint))", "NULL");
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_boolean_expressions() -> Result<()> {
     test_expression!("true", "true");
@@ -2382,6 +2627,35 @@ async fn test_boolean_expressions() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[cfg_attr(not(feature = "encoding_expressions"), ignore)]
+async fn test_encoding_expressions() -> Result<()> {
+    test_expression!("encode('hello', 'hex')", "68656c6c6f");
+    test_expression!("encode('hello', 'BASE64')", "aGVsbG8=");
+    test_expression!("encode(NULL, 'hex')", "NULL");
+    test_expression!("decode('68656c6c6f', 'hex')", "68656c6c6f");
+    test_expression!("encode(decode('aGVsbG8=', 'base64'), 'hex')", "68656c6c6f");
+    test_expression!("decode(NULL, 'base64')", "NULL");
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "json_expressions"), ignore)]
+async fn test_json_expressions() -> Result<()> {
+    test_expression!(r#"json_get('{"a": [1, "b"]}', 'a')"#, r#"[1,"b"]"#);
+    test_expression!(r#"json_get('{"a": [1, "b"]}', 'a', 1)"#, r#""b""#);
+    test_expression!(r#"json_get_str('{"a": [1, "b"]}', 'a', 1)"#, "b");
+    test_expression!(r#"json_get_str('{"a": [1, "b"]}', 'a', -1)"#, "b");
+    test_expression!(r#"json_get_int('{"a": [1, "b"]}', 'a', 0)"#, "1");
+    test_expression!(r#"json_get_int('{"a": [1, "b"]}', 'a', 1)"#, "NULL");
+    test_expression!(r#"json_get_float('{"a": 1.5}', 'a')"#, "1.5");
+    test_expression!(r#"json_get_bool('{"a": true}', 'a')"#, "true");
+    test_expression!(r#"json_get('{"a": 1}', 'b')"#, "NULL");
+    test_expression!("json_get('not json', 'a')", "NULL");
+    test_expression!("json_get(NULL, 'a')", "NULL");
+    Ok(())
+}
+
 #[tokio::test]
 #[cfg_attr(not(feature = "crypto_expressions"), ignore)]
 async fn test_crypto_expressions() -> Result<()> {
@@ -2595,9 +2869,20 @@ async fn test_string_expressions() -> Result<()> {
     test_expression!("starts_with('alphabet', 'blph')", "false");
     test_expression!("starts_with(NULL, 'blph')", "NULL");
     test_expression!("starts_with('alphabet', NULL)", "NULL");
+    test_expression!("levenshtein('kitten', 'sitting')", "3");
+    test_expression!("levenshtein('', 'abc')", "3");
+    test_expression!("levenshtein('abc', NULL)", "NULL");
+    test_expression!("soundex('Robert')", "R163");
+    test_expression!("soundex('Rupert')", "R163");
+    test_expression!("soundex('Ashcraft')", "A261");
+    test_expression!("soundex(NULL)", "NULL");
+    test_expression!("fuzzy_match('kitten', 'sitting', 3)", "true");
+    test_expression!("fuzzy_match('kitten', 'sitting', 2)", "false");
+    test_expression!("fuzzy_match('kitten', NULL, 2)", "NULL");
     test_expression!("to_hex(2147483647)", "7fffffff");
     test_expression!("to_hex(9223372036854775807)", "7fffffffffffffff");
     test_expression!("to_hex(CAST(NULL AS int))", "NULL");
+    test_expression!("to_hex(-1)", "ffffffffffffffff");
     test_expression!("trim(' tom ')", "tom");
     test_expression!("trim(' tom')", "tom");
     test_expression!("trim('')", "");
@@ -2642,6 +2927,13 @@ async fn test_unicode_expressions() -> Result<()> {
     test_expression!("lpad('xyxhi', 3)", "xyx");
     test_expression!("lpad(NULL, 0)", "NULL");
     test_expression!("lpad(NULL, 5, 'xy')", "NULL");
+    test_expression!("overlay('Txxxxas', 'hom', 2, 4)", "Thomas");
+    test_expression!("overlay('Txxxxas', 'hom', 2)", "Thomxas");
+    test_expression!("overlay('abc', 'xyz', 10)", "abcxyz");
+    test_expression!("overlay('abc', 'x', 2, 0)", "axbc");
+    test_expression!("overlay('josé', 'e', 4, 1)", "jose");
+    test_expression!("overlay(NULL, 'hom', 2, 4)", "NULL");
+    test_expression!("overlay('Txxxxas', 'hom', 2, CAST(NULL AS INT))", "NULL");
     test_expression!("reverse('abcde')", "edcba");
     test_expression!("reverse('loẅks')", "skẅol");
     test_expression!("reverse(NULL)", "NULL");
@@ -2679,6 +2971,9 @@ async fn test_unicode_expressions() -> Result<()> {
     test_expression!("substr('alphabet', 3, 20)", "phabet");
     test_expression!("substr('alphabet', CAST(NULL AS int), 20)", "NULL");
     test_expression!("substr('alphabet', 3, CAST(NULL AS int))", "NULL");
+    test_expression!("substring('alphabet' FROM 3)", "phabet");
+    test_expression!("substring('alphabet' FROM 3 FOR 2)", "ph");
+    test_expression!("substring('alphabet' FOR 5)", "alpha");
     test_expression!("translate('12345', '143', 'ax')", "a2x5");
     test_expression!("translate(NULL, '143', 'ax')", "NULL");
     test_expression!("translate('12345', NULL, 'ax')", "NULL");
@@ -2716,6 +3011,7 @@ async fn test_regex_expressions() -> Result<()> {
     test_expression!("regexp_match('aa', '.*-(\\d)')", "NULL");
     test_expression!("regexp_match(NULL, '.*-(\\d)')", "NULL");
     test_expression!("regexp_match('aaa-0', NULL)", "NULL");
+    test_expression!("regexp_match('aBc', '(b|d)', 'i')", "[B]");
     Ok(())
 }
 
@@ -2732,6 +3028,59 @@ async fn test_extract_date_part() -> Result<()> {
         "EXTRACT(year FROM to_timestamp('2020-09-08T12:00:00+00:00'))",
         "2020"
     );
+    test_expression!("EXTRACT(month FROM CAST('2020-09-08' AS DATE))", "9");
+    test_expression!("date_part('quarter', CAST('2020-09-08' AS DATE))", "3");
+    test_expression!("date_part('dow', CAST('2020-09-08' AS DATE))", "2");
+    test_expression!("date_part('doy', CAST('2020-09-08' AS DATE))", "252");
+    test_expression!("date_part('week', CAST('2020-09-08' AS DATE))", "37");
+    test_expression!(
+        "date_part('epoch', to_timestamp('2020-09-08T12:00:00+00:00'))",
+        "1599566400"
+    );
+    test_expression!(
+        "date_part('millisecond', to_timestamp('2020-09-08T12:00:12.345+00:00'))",
+        "12345"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn overflowing_casts() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT CAST(c AS SMALLINT) FROM (SELECT 40000 AS c) t";
+    let err = ctx.sql(sql)?.collect().await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("without overflow or loss of precision"));
+    ctx.sql("SELECT CAST(40000 AS SMALLINT)")?
+        .collect()
+        .await
+        .unwrap_err();
+
+    let actual = execute(&mut ctx, "SELECT TRY_CAST(40000 AS SMALLINT)").await;
+    assert_eq!(actual, vec![vec!["NULL"]]);
+
+    ctx.sql("SET safe_casts = true")?;
+    let actual = execute(&mut ctx, sql).await;
+    assert_eq!(actual, vec![vec!["NULL"]]);
+    let actual = execute(&mut ctx, "SELECT CAST(40000 AS SMALLINT)").await;
+    assert_eq!(actual, vec![vec!["NULL"]]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_to_char() -> Result<()> {
+    test_expression!(
+        "to_char(to_timestamp('2020-09-08T12:05:00+00:00'), 'YYYY-MM-DD HH24:MI')",
+        "2020-09-08 12:05"
+    );
+    test_expression!(
+        "to_char(CAST('2020-09-08' AS DATE), 'Dy DD Mon YYYY')",
+        "Tue 08 Sep 2020"
+    );
+    test_expression!("to_char(1234567, 'FM999,999,999')", "1,234,567");
+    test_expression!("to_char(-12.346, 'FM990.00')", "-12.35");
+    test_expression!("to_char(CAST(NULL AS INT), '999')", "NULL");
     Ok(())
 }
 
@@ -2889,6 +3238,24 @@ async fn test_cast_expressions() -> Result<()> {
     test_expression!("CAST(NULL AS INT)", "NULL");
     test_expression!("TRY_CAST('0' AS INT)", "0");
     test_expression!("TRY_CAST('x' AS INT)", "NULL");
+    test_expression!("arrow_cast(256, 'UInt16')", "256");
+    test_expression!("arrow_cast(NULL, 'Int8')", "NULL");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_arrow_typeof() -> Result<()> {
+    test_expression!("arrow_typeof(1)", "Int64");
+    test_expression!("arrow_typeof('a')", "Utf8");
+    test_expression!("arrow_typeof(arrow_cast(1, 'Int8'))", "Int8");
+    test_expression!(
+        "arrow_typeof(arrow_cast('2021-01-01T00:00:00', 'Timestamp(Nanosecond, None)'))",
+        "Timestamp(Nanosecond, None)"
+    );
+    test_expression!(
+        "arrow_typeof(arrow_cast('a', 'Dictionary(Int32, Utf8)'))",
+        "Dictionary(Int32, Utf8)"
+    );
     Ok(())
 }
 
@@ -2910,6 +3277,30 @@ async fn test_current_timestamp_expressions() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_current_timestamp_expressions_time_source() -> Result<()> {
+    #[derive(Debug)]
+    struct FixedTimeSource {}
+    impl TimeSource for FixedTimeSource {
+        fn now(&self) -> DateTime<Utc> {
+            Utc.ymd(2021, 5, 1).and_hms(12, 30, 0)
+        }
+    }
+
+    let config = ExecutionConfig::new().with_time_source(Arc::new(FixedTimeSource {}));
+    let mut ctx = ExecutionContext::with_config(config);
+    let actual = execute(&mut ctx, "SELECT NOW()").await;
+    assert_eq!(actual, vec![vec!["2021-05-01 12:30:00"]]);
+
+    // also when planning without optimizing
+    let plan = ctx.create_logical_plan("SELECT NOW()")?;
+    let plan = ctx.create_physical_plan(&plan)?;
+    let actual = result_vec(&collect(plan).await?);
+    assert_eq!(actual, vec![vec!["2021-05-01 12:30:00"]]);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_current_timestamp_expressions_non_optimized() -> Result<()> {
     let t1 = chrono::Utc::now().timestamp();
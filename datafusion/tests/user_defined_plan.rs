@@ -163,9 +163,9 @@ async fn topk_plan() -> Result<()> {
     let mut ctx = setup_table(make_topk_context()).await?;
 
     let expected = vec![
-        "| logical_plan after topk                 | TopK: k=3                                                                            |",
-        "|                                         |   Projection: #customer_id, #revenue                                                 |",
-        "|                                         |     TableScan: sales projection=Some([0, 1])                                         |",
+        "| logical_plan after topk                   | TopK: k=3                                                                            |",
+        "|                                           |   Projection: #customer_id, #revenue                                                 |",
+        "|                                           |     TableScan: sales projection=Some([0, 1])                                         |",
     ].join("\n");
 
     let explain_query = format!("EXPLAIN VERBOSE {}", QUERY);
@@ -178,6 +178,17 @@ async fn topk_plan() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+// Run the query planning TopK nodes with the default query planner
+async fn topk_query_extension_planner() -> Result<()> {
+    let config = ExecutionConfig::new()
+        .add_extension_planner(Arc::new(TopKPlanner {}))
+        .with_concurrency(48)
+        .add_optimizer_rule(Arc::new(TopKOptimizerRule {}));
+    let ctx = setup_table(ExecutionContext::with_config(config)).await?;
+    run_and_compare_query(ctx, "Topk extension planner context").await
+}
+
 fn make_topk_context() -> ExecutionContext {
     let config = ExecutionConfig::new()
         .with_query_planner(Arc::new(TopKQueryPlanner {}))